@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{
+    commands::workspaces::switch_workspace,
+    core::{
+        errors::{AppError, AppResult},
+        paths, policy,
+        types::{
+            BackupSettings, CompressNodeTextResponse, ListBackupsResponse, PortableExportResponse,
+            RunMigrationsResponse, SchemaInfo, StorageStatus, WorkspaceSummary,
+        },
+    },
+    db::{
+        backups,
+        repositories::{documents, settings},
+        schema, storage, Database, MigrationPolicy,
+    },
+    AppState,
+};
+
+#[tauri::command]
+pub async fn get_schema_info(state: State<'_, AppState>) -> AppResult<SchemaInfo> {
+    let db = state.db();
+    schema::schema_info(&db).await
+}
+
+/// Where the database lives and whether a sync client could corrupt it.
+#[tauri::command]
+pub async fn get_storage_status(state: State<'_, AppState>) -> AppResult<StorageStatus> {
+    let db = state.db();
+    storage::storage_status(&db).await
+}
+
+/// Backs the database up and applies pending migrations once no query is
+/// running and no ingestion is writing.
+#[tauri::command]
+pub async fn run_pending_migrations(
+    state: State<'_, AppState>,
+) -> AppResult<RunMigrationsResponse> {
+    let db = state.db();
+    let _permit = state.scheduler.background().await;
+    schema::run_pending_migrations(&db).await
+}
+
+/// Nodes compressed per batch by `compress_node_text`.
+const COMPRESS_BATCH: i64 = 500;
+
+/// Compresses the long node text of documents ingested before compression
+/// was added; new nodes are compressed as they are stored. Runs in batches,
+/// each waiting until no query is running, so it can be left going.
+#[tauri::command]
+pub async fn compress_node_text(state: State<'_, AppState>) -> AppResult<CompressNodeTextResponse> {
+    let db = state.db();
+    let mut response = CompressNodeTextResponse::default();
+    loop {
+        let _permit = state.scheduler.background().await;
+        let (nodes, saved) = documents::compress_stored_text(db.writer(), COMPRESS_BATCH).await?;
+        if nodes == 0 {
+            return Ok(response);
+        }
+        response.compressed_nodes += nodes;
+        response.bytes_saved += saved;
+    }
+}
+
+/// Writes all data to a JSON file that does not depend on the schema. Works
+/// on a read-only database opened because its schema is newer than this app.
+#[tauri::command]
+pub async fn export_portable_database(
+    state: State<'_, AppState>,
+    dest_path: String,
+) -> AppResult<PortableExportResponse> {
+    let db = state.db();
+    let dest = PathBuf::from(dest_path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err(AppError::InvalidInput(
+            "export path cannot be empty".to_string(),
+        ));
+    }
+    if db.path().is_some_and(|path| path == dest) {
+        return Err(AppError::InvalidInput(
+            "export path cannot be the database file".to_string(),
+        ));
+    }
+    let exported = schema::export_portable(&db, &paths::for_io(&dest)).await?;
+    state.exports.record(&dest);
+    Ok(exported)
+}
+
+#[tauri::command]
+pub async fn get_backup_settings(state: State<'_, AppState>) -> AppResult<BackupSettings> {
+    let db = state.db();
+    settings::get_setting(db.pool(), settings::BACKUP_SETTING).await
+}
+
+/// Saves the backup schedule; the first backup is taken at the next check
+/// when the folder has none yet.
+#[tauri::command]
+pub async fn set_backup_settings(
+    state: State<'_, AppState>,
+    settings: BackupSettings,
+) -> AppResult<BackupSettings> {
+    policy::current().ensure_unlocked(settings::BACKUP_SETTING)?;
+    let db = state.db();
+    let backup_settings = backups::validate(settings)?;
+    settings::set_setting(db.writer(), settings::BACKUP_SETTING, &backup_settings).await?;
+    Ok(backup_settings)
+}
+
+/// Backups in the configured folder, newest first.
+#[tauri::command]
+pub async fn list_backups(state: State<'_, AppState>) -> AppResult<ListBackupsResponse> {
+    let db = state.db();
+    let backup_settings: BackupSettings =
+        settings::get_setting(db.pool(), settings::BACKUP_SETTING).await?;
+    let backups = match backup_settings.folder.as_deref() {
+        Some(folder) => backups::list_backups(&paths::from_input(folder)?)?,
+        None => vec![],
+    };
+    Ok(ListBackupsResponse {
+        folder: backup_settings.folder,
+        backups,
+    })
+}
+
+/// Restores a backup as a new workspace beside the open database and
+/// switches to it. The open database is left as it is, so it can be
+/// reopened from the workspace list.
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> AppResult<WorkspaceSummary> {
+    let db = state.db();
+    let db_path = db.path().map(PathBuf::from).ok_or_else(|| {
+        AppError::InvalidInput("an in-memory database cannot be restored into".to_string())
+    })?;
+    let backup = paths::from_input(&path)?;
+    let taken_at = backups::backup_time(&backup).ok_or_else(|| {
+        AppError::InvalidInput(format!("{} is not a backup", paths::display(&backup)))
+    })?;
+    let dest = backups::restore_destination(&db_path);
+    backups::restore_to(&backup, &dest)?;
+    let restored = Database::open_file(&dest, MigrationPolicy::Apply).await?;
+    let name = format!("Restored {}", taken_at.format("%Y-%m-%d %H:%M"));
+    switch_workspace(&app, &state, restored, &dest, Some(&name)).await
+}
+
+/// Takes a backup whenever one is due under the open database's
+/// [`BackupSettings`]. Runs for the life of the app; a failed backup is
+/// logged and tried again at the next check.
+pub(crate) async fn run_backup_schedule(app: AppHandle) {
+    loop {
+        tokio::time::sleep(backups::BACKUP_CHECK_INTERVAL).await;
+        let state = app.state::<AppState>();
+        let db = state.db();
+        if let Err(err) = backup_if_due(&state, &db).await {
+            eprintln!(
+                "Scheduled backup failed: {}",
+                settings::privacy_redactor(db.pool()).await.error(&err)
+            );
+        }
+    }
+}
+
+async fn backup_if_due(state: &AppState, db: &Database) -> AppResult<()> {
+    if db.is_read_only() || db.path().is_none() {
+        return Ok(());
+    }
+    let backup_settings: BackupSettings =
+        settings::get_setting(db.pool(), settings::BACKUP_SETTING).await?;
+    let Some(folder) = backup_settings.folder.as_deref() else {
+        return Ok(());
+    };
+    let folder = paths::from_input(folder)?;
+    let latest = backups::list_backups(&folder)?
+        .first()
+        .map(|backup| backup.created_at);
+    if !backups::is_due(&backup_settings, latest, Utc::now()) {
+        return Ok(());
+    }
+    let _permit = state.scheduler.background().await;
+    backups::create_backup(db, &folder, backup_settings.include_assets).await?;
+    backups::rotate(&folder, backup_settings.keep)?;
+    Ok(())
+}
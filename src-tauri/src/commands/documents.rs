@@ -1,4 +1,8 @@
-use std::path::PathBuf;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    time::Instant,
+};
 
 use sha2::{Digest, Sha256};
 use tauri::{AppHandle, Emitter, State};
@@ -7,24 +11,109 @@ use uuid::Uuid;
 use crate::{
     core::{
         errors::{AppError, AppResult},
+        exports,
+        metrics::Metric,
+        paths, policy,
+        privacy::Redactor,
+        progress::IngestProgress,
         types::{
-            DeleteDocumentResponse, DocumentPreviewBlock, ExportMarkdownResponse, GetDocumentPreviewResponse,
-            GetGraphLayoutResponse, GetNodeResponse, GetTreeResponse, GraphNodePosition,
-            IngestDocumentResponse, IngestProgressEvent, ListDocumentsResponse, OpenDocumentResponse,
-            SaveGraphLayoutResponse,
+            CorpusChange, DecomposeClaimsResponse, DeleteDocumentResponse, DocumentLockStatus,
+            DocumentPreviewBlock, DocumentSummary, DocumentVisibilityResponse,
+            EnrichTablesResponse, ExportedFile, ExternalParserConfig, ExtractChartDataResponse,
+            GetAssetResponse, GetDocumentPreviewResponse, GetGraphLayoutResponse, GetNodeResponse,
+            GetProjectTreeDeltaResponse, GetTreeResponse, GraphNodePosition, ImportCorpusResponse,
+            ImportPlan, ImportSource, ImportedDocument, IndexAcronymsResponse, IngestCompleteEvent,
+            IngestDocumentResponse, IngestFailedEvent, ListDocumentsResponse, ModelSelection,
+            NodeType, OpenDocumentResponse, ParserKind, ParserOverride, PolicyFeature,
+            PreviewIngestResponse, ProviderTimeoutSettings, RecentDocumentsResponse,
+            ReingestDocumentResponse, RetrievalSettings, RevealExportResponse,
+            SaveGraphLayoutResponse, ScanDirectoryResponse, SearchAllProjectsResponse, SkippedPath,
+            UndoKind, UndoLastOperationResponse,
         },
     },
-    db::repositories::documents,
-    sidecar::native_parser,
+    db::{
+        repositories::{activity, assets, documents, metrics, projects, settings},
+        undo::{self, UndoSnapshot},
+        Database,
+    },
+    enrichment::{acronyms, charts, claims, tables},
+    providers::provider_for_selection,
+    security::{document_lock, key_pool},
+    sidecar::{
+        anchors::{self, NodeFingerprint},
+        dates, docling_client, figures, ignore, importers, mime,
+        native_parser::{self, ParseLimits},
+        preview, quantities, references, thumbnails,
+        types::{NormalizedPayload, SidecarNode},
+    },
     AppState,
 };
 
-fn checksum_bytes(bytes: &[u8]) -> String {
+/// Hashes the file as it streams past so large files are never held in memory.
+fn checksum_file(path: &Path) -> AppResult<String> {
+    let mut file = std::fs::File::open(path).map_err(|err| AppError::Io(err.to_string()))?;
     let mut hasher = Sha256::new();
-    hasher.update(bytes);
-    format!("{:x}", hasher.finalize())
+    std::io::copy(&mut file, &mut hasher).map_err(|err| AppError::Io(err.to_string()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The MIME type the parser settled on, which overrides the caller's hint when
+/// the file's content disagrees with it.
+fn parsed_mime(parsed: &NormalizedPayload, hint: &str) -> String {
+    parsed.document.metadata["mime"]
+        .as_str()
+        .unwrap_or(hint)
+        .to_string()
+}
+
+/// Runs the external parser when the override names it or its config routes
+/// this file's type to it, and the native parser otherwise, then records each
+/// node's quantities and dates and the document's reference metadata.
+fn parse_document(
+    external: &ExternalParserConfig,
+    path: &Path,
+    mime_type: &str,
+    limits: ParseLimits,
+    parser_override: &ParserOverride,
+    on_page: &mut dyn FnMut(u32, u32),
+) -> AppResult<NormalizedPayload> {
+    let external_chosen = match parser_override.parser {
+        Some(ParserKind::External) => true,
+        Some(_) => false,
+        None => docling_client::handles(external, &mime::resolve(path, mime_type)?.mime),
+    };
+    let mut parsed = if external_chosen {
+        let resolved = mime::resolve(path, mime_type)?;
+        let mut parsed = docling_client::parse(external, path, &resolved.mime)?;
+        native_parser::record_source(&mut parsed, resolved, parser_override)?;
+        parsed
+    } else {
+        native_parser::parse_with_progress(path, mime_type, limits, parser_override, on_page)?
+    };
+    quantities::record(&mut parsed);
+    dates::record(&mut parsed);
+    references::record(&mut parsed);
+    Ok(parsed)
 }
 
+async fn parse_limits(pool: &sqlx::SqlitePool, project_id: &str) -> AppResult<ParseLimits> {
+    let rules = projects::get_ingest_rules(pool, project_id).await?;
+    Ok(ParseLimits::default().with_max_file_bytes(rules.max_file_bytes))
+}
+
+/// Arguments of one `ingest_document` call.
+struct IngestRequest {
+    project_id: String,
+    file_path: String,
+    mime_type: String,
+    display_name: Option<String>,
+    share_existing: bool,
+    parser_override: Option<ParserOverride>,
+}
+
+/// Ingests a file and reports the result as `ingest/complete` or `ingest/failed`
+/// as well as over invoke, so batch and watch flows can refresh reactively.
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn ingest_document(
     app: AppHandle,
@@ -33,22 +122,158 @@ pub async fn ingest_document(
     file_path: String,
     mime_type: String,
     display_name: Option<String>,
+    share_existing: Option<bool>,
+    parser_override: Option<ParserOverride>,
+) -> AppResult<IngestDocumentResponse> {
+    let request = IngestRequest {
+        project_id,
+        file_path,
+        mime_type,
+        display_name,
+        share_existing: share_existing.unwrap_or(false),
+        parser_override,
+    };
+    ingest_and_report(&app, &state, request).await
+}
+
+/// Runs one ingest, counts it in the local metrics and reports it as
+/// `ingest/complete` or `ingest/failed`.
+async fn ingest_and_report(
+    app: &AppHandle,
+    state: &AppState,
+    request: IngestRequest,
 ) -> AppResult<IngestDocumentResponse> {
-    let path = PathBuf::from(&file_path);
-    if !path.exists() {
-        return Err(AppError::NotFound(format!("file {file_path}")));
+    let job_id = Uuid::new_v4().to_string();
+    let (project_id, file_path) = (request.project_id.clone(), request.file_path.clone());
+    let started = Instant::now();
+    let result = ingest(app, state, &job_id, request).await;
+    record_ingest_metric(&state.db(), "ingest", started, &result).await;
+    match result {
+        Ok((response, complete)) => {
+            let _ = app.emit("ingest/complete", complete);
+            Ok(response)
+        }
+        Err(err) => {
+            let redactor = settings::privacy_redactor(state.db().pool()).await;
+            emit_failed(
+                app,
+                redactor,
+                &job_id,
+                Some(&project_id),
+                None,
+                &file_path,
+                &err,
+            );
+            Err(err)
+        }
     }
+}
+
+/// Counts an ingest as done or failed in the local metrics, under `label`.
+async fn record_ingest_metric<T>(
+    db: &Database,
+    label: &str,
+    started: Instant,
+    result: &AppResult<T>,
+) {
+    let metric = match result {
+        Ok(_) => Metric::Ingest,
+        Err(_) => Metric::IngestFailure,
+    };
+    let _ = metrics::record(db.writer(), metric, label, Some(started.elapsed())).await;
+}
 
-    let bytes = std::fs::read(&path).map_err(|err| AppError::Io(err.to_string()))?;
-    let checksum = checksum_bytes(&bytes);
+fn emit_failed(
+    app: &AppHandle,
+    redactor: Redactor,
+    job_id: &str,
+    project_id: Option<&str>,
+    document_id: Option<&str>,
+    file_path: &str,
+    err: &AppError,
+) {
+    let _ = app.emit(
+        "ingest/failed",
+        IngestFailedEvent {
+            job_id: job_id.to_string(),
+            project_id: project_id.map(str::to_string),
+            document_id: document_id.map(str::to_string),
+            file_path: file_path.to_string(),
+            code: err.code().to_string(),
+            message: redactor.error(err),
+            retryable: err.retryable(),
+        },
+    );
+}
+
+/// Emits `corpus/changed` to each project in `project_ids` after `change` to a
+/// document. Like the other events this is best effort: the change has
+/// already been stored, so a failed count only skips the event.
+pub(crate) async fn emit_corpus_changed(
+    app: &AppHandle,
+    db: &Database,
+    project_ids: &[String],
+    change: CorpusChange,
+    document_id: &str,
+) {
+    for project_id in project_ids {
+        if let Ok(event) =
+            documents::corpus_changed_event(db.pool(), project_id, change, document_id).await
+        {
+            let _ = app.emit("corpus/changed", event);
+        }
+    }
+}
+
+async fn ingest(
+    app: &AppHandle,
+    state: &AppState,
+    job_id: &str,
+    request: IngestRequest,
+) -> AppResult<(IngestDocumentResponse, IngestCompleteEvent)> {
+    let db = state.db();
+    let IngestRequest {
+        project_id,
+        file_path,
+        mime_type,
+        display_name,
+        share_existing,
+        parser_override,
+    } = request;
+    projects::ensure_writable(db.pool(), &project_id).await?;
+    let complete = |response: &IngestDocumentResponse, warnings: Vec<String>| IngestCompleteEvent {
+        job_id: job_id.to_string(),
+        project_id: project_id.clone(),
+        file_path: file_path.clone(),
+        document_id: response.document_id.clone(),
+        root_node_id: response.root_node_id.clone(),
+        node_count: response.node_count,
+        section_count: response.section_count,
+        shared_from_project_id: response.shared_from_project_id.clone(),
+        reingested: false,
+        warnings,
+    };
+    let path = paths::resolve_input(&file_path)?;
+
+    let limits = parse_limits(db.pool(), &project_id).await?;
+    let external: ExternalParserConfig =
+        settings::get_setting(db.pool(), settings::EXTERNAL_PARSER_SETTING).await?;
+    native_parser::check_file_size(&path, limits.max_file_bytes)?;
+    let checksum = checksum_file(&path)?;
     
     // Check for existing document with same checksum
-    if let Some(existing) = documents::find_by_checksum(state.db.pool(), &project_id, &checksum).await? {
+    if let Some(existing) = documents::find_by_checksum(db.pool(), &project_id, &checksum).await? {
         // Try to get the tree for the existing document
-        match documents::get_tree(state.db.pool(), &existing.id, None, 8).await {
+        match documents::get_tree(db.pool(), &existing.id, None, 8).await {
             Ok(existing_nodes) => {
                 // Verify the document has a valid root node
                 if let Some(root) = existing_nodes.iter().find(|node| node.parent_id.is_none()) {
+                    if parser_override.is_some() {
+                        return Err(AppError::InvalidInput(format!(
+                            "file is already ingested as document {}; reingest it to apply a parser override",
+                            existing.id
+                        )));
+                    }
                     let section_count = existing_nodes
                         .iter()
                         .filter(|node| {
@@ -60,51 +285,83 @@ pub async fn ingest_document(
                         .count();
                     
                     eprintln!("Document already exists with checksum {}, returning cached result", checksum);
-                    return Ok(IngestDocumentResponse {
+                    let response = IngestDocumentResponse {
                         document_id: existing.id,
                         root_node_id: root.id.clone(),
                         node_count: existing_nodes.len(),
                         section_count,
-                    });
+                        shared_from_project_id: None,
+                    };
+                    let complete = complete(&response, Vec::new());
+                    return Ok((response, complete));
                 } else {
                     // Document exists but has no root node - it's corrupted, delete it
                     eprintln!("Found corrupted document {} (no root node), deleting and re-parsing", existing.id);
-                    let _ = documents::delete_document(state.db.pool(), &existing.id).await;
+                    let _ = documents::delete_document(db.writer(), &existing.id).await;
                 }
             }
             Err(e) => {
                 // Failed to get tree - document is corrupted, delete it
                 eprintln!("Found corrupted document {} (failed to get tree: {}), deleting and re-parsing", existing.id, e);
-                let _ = documents::delete_document(state.db.pool(), &existing.id).await;
+                let _ = documents::delete_document(db.writer(), &existing.id).await;
             }
         }
     }
 
-    let job_id = Uuid::new_v4().to_string();
-    let _ = app.emit(
-        "ingest/progress",
-        IngestProgressEvent {
-            job_id: job_id.clone(),
-            stage: "queued".to_string(),
-            percent: 0,
-            message: "Starting ingestion".to_string(),
-        },
-    );
+    if share_existing {
+        if let Some(shared) = documents::find_document_anywhere(db.pool(), &checksum).await? {
+            if let Some(response) = shared_ingest_response(db.pool(), &shared).await? {
+                documents::link_document(db.writer(), &project_id, &shared.id).await?;
+                emit_corpus_changed(
+                    app,
+                    &db,
+                    std::slice::from_ref(&project_id),
+                    CorpusChange::DocumentAdded,
+                    &shared.id,
+                )
+                .await;
+                let complete = complete(&response, Vec::new());
+                return Ok((response, complete));
+            }
+        }
+    }
+
+    let total_bytes = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    let progress = IngestProgress::new(job_id, total_bytes);
+    let _ = app.emit("ingest/progress", progress.start("queued", 0, "Starting ingestion"));
 
     let _ = app.emit(
         "ingest/progress",
-        IngestProgressEvent {
-            job_id: job_id.clone(),
-            stage: "parse".to_string(),
-            percent: 30,
-            message: "Parsing document\u{2026}".to_string(),
-        },
+        progress.start("parse", 30, "Parsing document\u{2026}"),
     );
     
-    let parsed = match native_parser::parse(&path, &mime_type) {
+    // Large PDFs take a while; move the bar from 30% towards 80% as pages finish,
+    // emitting only when the percentage changes.
+    let mut last_percent = 30;
+    let mut on_page = |done: u32, total: u32| {
+        let percent = 30 + 50 * i64::from(done) / i64::from(total.max(1));
+        if percent == last_percent {
+            return;
+        }
+        last_percent = percent;
+        let _ = app.emit("ingest/progress", progress.pages(percent, done, total));
+    };
+    let parser_override = parser_override.unwrap_or_default();
+    let mut parsed = match parse_document(
+        &external,
+        &path,
+        &mime_type,
+        limits,
+        &parser_override,
+        &mut on_page,
+    ) {
         Ok(p) => p,
         Err(e) => {
-            eprintln!("Document parsing failed for {:?}: {:?}", path, e);
+            eprintln!(
+                "Document parsing failed for {:?}: {}",
+                path,
+                settings::privacy_redactor(db.pool()).await.error(&e)
+            );
             return Err(e);
         }
     };
@@ -115,72 +372,677 @@ pub async fn ingest_document(
             .map(|name| name.to_string_lossy().to_string())
             .unwrap_or_else(|| parsed.document.title.clone())
     });
+    anchors::assign_stable_ids(&document_id, &mut parsed);
 
+    // Parsing runs alongside queries; the writes wait until none is running.
+    let _permit = state.scheduler.background().await;
     documents::insert_document(
-        state.db.pool(),
+        db.writer(),
         &document_id,
         &project_id,
         &name,
-        &mime_type,
+        &parsed_mime(&parsed, &mime_type),
         &checksum,
         parsed.document.pages,
     )
     .await?;
 
-    if let Err(err) = documents::insert_nodes(state.db.pool(), &document_id, &parsed.nodes).await {
-        let _ = documents::delete_document(state.db.pool(), &document_id).await;
+    if let Err(err) =
+        documents::insert_nodes(db.writer(), &document_id, &parsed.nodes).await
+    {
+        let _ = documents::delete_document(db.writer(), &document_id).await;
         return Err(err);
     }
+    if let Err(err) = store_figure_images(&db, &parsed.nodes, path.parent()).await {
+        let _ = documents::delete_document(db.writer(), &document_id).await;
+        return Err(err);
+    }
+    store_thumbnail(
+        &db,
+        &document_id,
+        &path,
+        &parsed_mime(&parsed, &mime_type),
+        &parsed,
+    )
+    .await;
+    if let Err(err) = tables::enrich_table_schemas(&db, None, &document_id).await {
+        let _ = documents::delete_document(db.writer(), &document_id).await;
+        return Err(err);
+    }
+    if let Err(err) = acronyms::index_acronyms(&db, &document_id).await {
+        let _ = documents::delete_document(db.writer(), &document_id).await;
+        return Err(err);
+    }
+    // Best effort: a path that is not valid Unicode only loses the stale check.
+    if let Ok(source_path) = paths::to_utf8(&path) {
+        documents::set_ingest_source(db.writer(), &document_id, &source_path).await?;
+    }
 
-    let _ = app.emit(
-        "ingest/progress",
-        IngestProgressEvent {
-            job_id,
-            stage: "finalize".to_string(),
-            percent: 100,
-            message: "Indexing complete".to_string(),
-        },
-    );
+    let _ = app.emit("ingest/progress", progress.finalize(&document_id));
+    emit_corpus_changed(
+        app,
+        &db,
+        std::slice::from_ref(&project_id),
+        CorpusChange::DocumentAdded,
+        &document_id,
+    )
+    .await;
 
     let root = parsed
         .nodes
         .first()
         .ok_or_else(|| AppError::Internal("normalized payload contains no root node".to_string()))?;
-    let section_count = parsed
-        .nodes
+    let response = IngestDocumentResponse {
+        document_id,
+        root_node_id: root.id.clone(),
+        node_count: parsed.nodes.len(),
+        section_count: section_count(&parsed.nodes),
+        shared_from_project_id: None,
+    };
+    let complete = complete(&response, preview::summarize(&parsed).warnings);
+    Ok((response, complete))
+}
+
+fn section_count(nodes: &[SidecarNode]) -> usize {
+    nodes
         .iter()
         .filter(|node| {
             let kind = node.node_type.to_ascii_lowercase();
             kind == "section" || kind == "subsection"
         })
+        .count()
+}
+
+/// Ingest result for a document parsed in another project, or `None` when its
+/// tree has no root and it should not be shared.
+async fn shared_ingest_response(
+    pool: &sqlx::SqlitePool,
+    shared: &DocumentSummary,
+) -> AppResult<Option<IngestDocumentResponse>> {
+    let nodes = documents::get_tree(pool, &shared.id, None, 8).await?;
+    let Some(root) = nodes.iter().find(|node| node.parent_id.is_none()) else {
+        return Ok(None);
+    };
+    let section_count = nodes
+        .iter()
+        .filter(|node| matches!(node.node_type, NodeType::Section | NodeType::Subsection))
         .count();
+    Ok(Some(IngestDocumentResponse {
+        document_id: shared.id.clone(),
+        root_node_id: root.id.clone(),
+        node_count: nodes.len(),
+        section_count,
+        shared_from_project_id: Some(shared.project_id.clone()),
+    }))
+}
+
+/// Re-parses a document in place after its file changed. Unchanged nodes keep
+/// their ids; moved or edited ones are remapped so citations and graph layouts
+/// still resolve. Nodes added by enrichment passes are dropped with the old tree
+/// and only table typing is redone here. Without `file_path` the file the
+/// document was last ingested from is read again. The result is also emitted
+/// as `ingest/complete` or `ingest/failed`.
+#[tauri::command]
+pub async fn reingest_document(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    document_id: String,
+    file_path: Option<String>,
+    mime_type: Option<String>,
+    parser_override: Option<ParserOverride>,
+) -> AppResult<ReingestDocumentResponse> {
+    let db = state.db();
+    let file_path = match file_path {
+        Some(file_path) => file_path,
+        None => documents::get_document(db.pool(), &document_id)
+            .await?
+            .source_path
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "document {document_id} has no recorded source file; choose the file to reingest"
+                ))
+            })?,
+    };
+    let job_id = Uuid::new_v4().to_string();
+    let started = Instant::now();
+    let result = reingest(
+        &state,
+        &job_id,
+        &document_id,
+        &file_path,
+        mime_type,
+        parser_override,
+    )
+    .await;
+    record_ingest_metric(&db, "reingest", started, &result).await;
+    match result {
+        Ok((response, complete)) => {
+            let _ = app.emit("ingest/complete", complete);
+            if let Ok(project_ids) = documents::document_project_ids(db.pool(), &document_id).await
+            {
+                emit_corpus_changed(
+                    &app,
+                    &db,
+                    &project_ids,
+                    CorpusChange::DocumentReingested,
+                    &document_id,
+                )
+                .await;
+            }
+            Ok(response)
+        }
+        Err(err) => {
+            let project_id = documents::get_document(db.pool(), &document_id)
+                .await
+                .ok()
+                .map(|document| document.project_id);
+            emit_failed(
+                &app,
+                settings::privacy_redactor(db.pool()).await,
+                &job_id,
+                project_id.as_deref(),
+                Some(&document_id),
+                &file_path,
+                &err,
+            );
+            Err(err)
+        }
+    }
+}
+
+async fn reingest(
+    state: &AppState,
+    job_id: &str,
+    document_id: &str,
+    file_path: &str,
+    mime_type: Option<String>,
+    parser_override: Option<ParserOverride>,
+) -> AppResult<(ReingestDocumentResponse, IngestCompleteEvent)> {
+    let db = state.db();
+    let pool = db.pool();
+    projects::ensure_document_writable(pool, document_id).await?;
+    let document = documents::get_document(pool, document_id).await?;
+    let path = paths::resolve_input(file_path)?;
+    let limits = parse_limits(pool, &document.project_id).await?;
+    native_parser::check_file_size(&path, limits.max_file_bytes)?;
+    let checksum = checksum_file(&path)?;
+    if let Some(existing) =
+        documents::find_by_checksum(pool, &document.project_id, &checksum).await?
+    {
+        if existing.id != document_id {
+            return Err(AppError::InvalidInput(format!(
+                "file is already ingested as document {}",
+                existing.id
+            )));
+        }
+    }
+
+    let mime_type = mime_type.unwrap_or(document.mime);
+    let external: ExternalParserConfig =
+        settings::get_setting(pool, settings::EXTERNAL_PARSER_SETTING).await?;
+    let mut parsed = parse_document(
+        &external,
+        &path,
+        &mime_type,
+        limits,
+        &parser_override.unwrap_or_default(),
+        &mut |_, _| {},
+    )?;
+    let mime_type = parsed_mime(&parsed, &mime_type);
+    anchors::assign_stable_ids(document_id, &mut parsed);
+    let _permit = state.scheduler.background().await;
+    let old = documents::get_node_fingerprints(pool, document_id).await?;
+    let new = parsed
+        .nodes
+        .iter()
+        .map(NodeFingerprint::of)
+        .collect::<Vec<_>>();
+    let remaps = anchors::remap_node_ids(&old, &new);
+    let layout = documents::get_graph_layout(pool, document_id).await?;
 
-    Ok(IngestDocumentResponse {
+    let writer = db.writer();
+    documents::replace_document_nodes(
+        writer,
         document_id,
+        &checksum,
+        &mime_type,
+        parsed.document.pages,
+        &parsed.nodes,
+        &remaps,
+    )
+    .await?;
+    let layout = layout
+        .into_iter()
+        .map(|mut position| {
+            if let Some(remap) = remaps.iter().find(|remap| remap.old_id == position.node_id) {
+                position.node_id = remap.new_id.clone();
+            }
+            position
+        })
+        .collect::<Vec<_>>();
+    documents::save_graph_layout(writer, document_id, &layout).await?;
+    store_figure_images(&db, &parsed.nodes, path.parent()).await?;
+    store_thumbnail(&db, document_id, &path, &mime_type, &parsed).await;
+    // Figures the new version no longer has, and the old thumbnail.
+    collect_assets(&db).await;
+    tables::enrich_table_schemas(&db, None, document_id).await?;
+    acronyms::index_acronyms(&db, document_id).await?;
+    if let Ok(source_path) = paths::to_utf8(&path) {
+        documents::set_ingest_source(writer, document_id, &source_path).await?;
+    }
+
+    let root = parsed.nodes.first().ok_or_else(|| {
+        AppError::Internal("normalized payload contains no root node".to_string())
+    })?;
+    let new_ids = new
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect::<HashSet<_>>();
+    let unchanged_nodes = old
+        .iter()
+        .filter(|node| new_ids.contains(node.id.as_str()))
+        .count();
+    let complete = IngestCompleteEvent {
+        job_id: job_id.to_string(),
+        project_id: document.project_id,
+        file_path: file_path.to_string(),
+        document_id: document_id.to_string(),
         root_node_id: root.id.clone(),
         node_count: parsed.nodes.len(),
-        section_count,
-    })
+        section_count: section_count(&parsed.nodes),
+        shared_from_project_id: None,
+        reingested: true,
+        warnings: preview::summarize(&parsed).warnings,
+    };
+    let response = ReingestDocumentResponse {
+        document_id: document_id.to_string(),
+        root_node_id: root.id.clone(),
+        node_count: parsed.nodes.len(),
+        unchanged_nodes,
+        remapped_nodes: remaps.len(),
+        dropped_nodes: old.len() - unchanged_nodes - remaps.len(),
+    };
+    Ok((response, complete))
 }
 
+/// Reads the data series out of a document's chart figures into child Table nodes,
+/// using the selected synthesis model.
+#[tauri::command]
+pub async fn extract_chart_data(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    document_id: String,
+) -> AppResult<ExtractChartDataResponse> {
+    let db = state.db();
+    projects::ensure_document_writable(db.pool(), &document_id).await?;
+    let selection: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
+    let timeouts: ProviderTimeoutSettings =
+        settings::get_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING).await?;
+    let provider = provider_for_selection(&selection, &timeouts)?;
+    let keys = key_pool::load_key_pool(db.pool(), selection.provider.clone()).await?;
+    let report =
+        charts::extract_chart_tables(&db, provider.as_ref(), &keys, &document_id).await?;
+    tables::enrich_table_schemas(&db, None, &document_id).await?;
+    if !report.table_node_ids.is_empty() {
+        emit_nodes_added(&app, &db, &document_id).await;
+    }
+    Ok(report)
+}
+
+async fn emit_nodes_added(app: &AppHandle, db: &Database, document_id: &str) {
+    if let Ok(project_ids) = documents::document_project_ids(db.pool(), document_id).await {
+        emit_corpus_changed(app, db, &project_ids, CorpusChange::NodesAdded, document_id).await;
+    }
+}
+
+/// Re-runs table typing for a document. Ingestion already types tables locally;
+/// `use_model` also asks the selected model to name headerless tables' columns.
+#[tauri::command]
+pub async fn enrich_tables(
+    state: State<'_, AppState>,
+    document_id: String,
+    use_model: Option<bool>,
+) -> AppResult<EnrichTablesResponse> {
+    let db = state.db();
+    projects::ensure_document_writable(db.pool(), &document_id).await?;
+    let document = documents::get_document(db.pool(), &document_id).await?;
+    let snapshot = undo::snapshot_node_metadata(&db, &document_id).await?;
+    let response = if !use_model.unwrap_or(false) {
+        tables::enrich_table_schemas(&db, None, &document_id).await?
+    } else {
+        let selection: ModelSelection =
+            settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
+        let timeouts: ProviderTimeoutSettings =
+            settings::get_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING).await?;
+        let provider = provider_for_selection(&selection, &timeouts)?;
+        let keys = key_pool::load_key_pool(db.pool(), selection.provider.clone()).await?;
+        tables::enrich_table_schemas(&db, Some((provider.as_ref(), &keys)), &document_id).await?
+    };
+    if response.inferred_locally + response.inferred_with_model > 0 {
+        let label = format!("Enrich tables in {}", document.name);
+        record_undo(&db, &label, &snapshot).await;
+    }
+    Ok(response)
+}
+
+/// Re-reads the acronyms a document defines, for documents ingested before
+/// acronyms were indexed.
+#[tauri::command]
+pub async fn index_acronyms(
+    state: State<'_, AppState>,
+    document_id: String,
+) -> AppResult<IndexAcronymsResponse> {
+    let db = state.db();
+    projects::ensure_document_writable(db.pool(), &document_id).await?;
+    acronyms::index_acronyms(&db, &document_id).await
+}
+
+/// Splits paragraphs into Claim nodes for sentence-level citations; all of the
+/// document's paragraphs unless `paragraph_ids` picks some.
+#[tauri::command]
+pub async fn decompose_claims(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    document_id: String,
+    paragraph_ids: Option<Vec<String>>,
+    use_model: Option<bool>,
+) -> AppResult<DecomposeClaimsResponse> {
+    let db = state.db();
+    projects::ensure_document_writable(db.pool(), &document_id).await?;
+    let paragraph_ids = paragraph_ids.as_deref();
+    let response = if !use_model.unwrap_or(false) {
+        claims::decompose_claims(&db, None, &document_id, paragraph_ids).await?
+    } else {
+        let selection: ModelSelection =
+            settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
+        let timeouts: ProviderTimeoutSettings =
+            settings::get_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING).await?;
+        let provider = provider_for_selection(&selection, &timeouts)?;
+        let keys = key_pool::load_key_pool(db.pool(), selection.provider.clone()).await?;
+        claims::decompose_claims(
+            &db,
+            Some((provider.as_ref(), &keys)),
+            &document_id,
+            paragraph_ids,
+        )
+        .await?
+    };
+    if response.claims_created > 0 {
+        emit_nodes_added(&app, &db, &document_id).await;
+    }
+    Ok(response)
+}
+
+/// Lists the files in a folder that the project's ingest rules let through, for
+/// the caller to ingest one by one, along with what was skipped and why.
+/// Refused for a project that cannot take new documents, so watched folders
+/// of an archived project stay idle.
+#[tauri::command]
+pub async fn scan_directory(
+    state: State<'_, AppState>,
+    project_id: String,
+    dir_path: String,
+) -> AppResult<ScanDirectoryResponse> {
+    let db = state.db();
+    projects::ensure_writable(db.pool(), &project_id).await?;
+    let rules = projects::get_ingest_rules(db.pool(), &project_id).await?;
+    ignore::scan_directory(&paths::for_io(&paths::from_input(&dir_path)?), &rules)
+}
+
+/// Lists what `import_corpus` would ingest from the export at `dir_path`,
+/// without storing anything.
+#[tauri::command]
+pub async fn preview_import(
+    state: State<'_, AppState>,
+    project_id: String,
+    source: ImportSource,
+    dir_path: String,
+    folders_as_projects: Option<bool>,
+) -> AppResult<ImportPlan> {
+    let db = state.db();
+    import_plan(&db, &project_id, source, &dir_path, folders_as_projects).await
+}
+
+/// Imports an Obsidian vault, a Notion export or a Zotero RDF export into
+/// `project_id`. Unless `folders_as_projects` is false, each top-level folder
+/// or collection goes to the project of that name, which is created when
+/// there is none. Files are ingested one by one like `ingest_document`, so
+/// each reports `ingest/complete` or `ingest/failed`, and one that fails does
+/// not stop the rest. Tags and attachment links are stored once all are in.
+#[tauri::command]
+pub async fn import_corpus(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    source: ImportSource,
+    dir_path: String,
+    folders_as_projects: Option<bool>,
+) -> AppResult<ImportCorpusResponse> {
+    let db = state.db();
+    let plan = import_plan(&db, &project_id, source, &dir_path, folders_as_projects).await?;
+    let redactor = settings::privacy_redactor(db.pool()).await;
+    let mut response = ImportCorpusResponse {
+        skipped: plan.skipped,
+        ..ImportCorpusResponse::default()
+    };
+    let mut project_ids = HashMap::new();
+    let mut document_ids = HashMap::new();
+    for item in &plan.items {
+        let target = match &item.project {
+            None => project_id.clone(),
+            Some(name) => match project_ids.get(name) {
+                Some(id) => id.clone(),
+                None => {
+                    let (id, created) = import_project(&db, name).await?;
+                    if created {
+                        response.created_project_ids.push(id.clone());
+                    }
+                    project_ids.insert(name.clone(), id.clone());
+                    id
+                }
+            },
+        };
+        let request = IngestRequest {
+            project_id: target.clone(),
+            file_path: item.path.clone(),
+            mime_type: item.mime_type.clone(),
+            display_name: item.display_name.clone(),
+            share_existing: false,
+            parser_override: None,
+        };
+        match ingest_and_report(&app, &state, request).await {
+            Ok(ingested) => {
+                document_ids.insert(item.path.as_str(), ingested.document_id.clone());
+                response.imported.push(ImportedDocument {
+                    path: item.path.clone(),
+                    document_id: ingested.document_id,
+                    project_id: target,
+                });
+            }
+            Err(err) => response.failed.push(SkippedPath {
+                path: item.path.clone(),
+                reason: redactor.error(&err),
+            }),
+        }
+    }
+
+    let _permit = state.scheduler.background().await;
+    for item in &plan.items {
+        let Some(document_id) = document_ids.get(item.path.as_str()) else {
+            continue;
+        };
+        if !item.tags.is_empty() {
+            documents::set_tags(db.writer(), document_id, &item.tags).await?;
+        }
+        for attachment in &item.attachments {
+            if let Some(attachment_id) = document_ids.get(attachment.as_str()) {
+                documents::link_attachment(db.writer(), document_id, attachment_id).await?;
+            }
+        }
+    }
+    Ok(response)
+}
+
+async fn import_plan(
+    db: &Database,
+    project_id: &str,
+    source: ImportSource,
+    dir_path: &str,
+    folders_as_projects: Option<bool>,
+) -> AppResult<ImportPlan> {
+    policy::current().ensure_enabled(PolicyFeature::Import)?;
+    projects::ensure_writable(db.pool(), project_id).await?;
+    let rules = projects::get_ingest_rules(db.pool(), project_id).await?;
+    importers::plan_import(
+        &paths::for_io(&paths::from_input(dir_path)?),
+        source,
+        &rules,
+        folders_as_projects.unwrap_or(true),
+    )
+}
+
+/// The id of the project named `name`, and whether it had to be created.
+async fn import_project(db: &Database, name: &str) -> AppResult<(String, bool)> {
+    let existing = projects::list_projects(db.pool())
+        .await?
+        .into_iter()
+        .find(|project| project.name == name);
+    if let Some(project) = existing {
+        return Ok((project.id, false));
+    }
+    let id = Uuid::new_v4().to_string();
+    projects::create_project(db.writer(), &id, name).await?;
+    Ok((id, true))
+}
+
+/// Parses a file the way `ingest_document` would and reports the tree it would
+/// produce, with warnings about parse quality, without storing anything. The
+/// project's size limit applies when `project_id` is given.
+#[tauri::command]
+pub async fn preview_ingest(
+    state: State<'_, AppState>,
+    file_path: String,
+    mime_type: String,
+    project_id: Option<String>,
+    parser_override: Option<ParserOverride>,
+) -> AppResult<PreviewIngestResponse> {
+    let db = state.db();
+    let path = paths::resolve_input(&file_path)?;
+    let pool = db.pool();
+    let limits = match project_id {
+        Some(project_id) => parse_limits(pool, &project_id).await?,
+        None => ParseLimits::default(),
+    };
+    let external: ExternalParserConfig =
+        settings::get_setting(pool, settings::EXTERNAL_PARSER_SETTING).await?;
+    native_parser::check_file_size(&path, limits.max_file_bytes)?;
+    let parsed = parse_document(
+        &external,
+        &path,
+        &mime_type,
+        limits,
+        &parser_override.unwrap_or_default(),
+        &mut |_, _| {},
+    )?;
+    Ok(preview::summarize(&parsed))
+}
+
+/// Keeps the image behind each Figure node so synthesis can look at it, not just its caption.
+async fn store_figure_images(
+    db: &Database,
+    nodes: &[SidecarNode],
+    source_dir: Option<&Path>,
+) -> AppResult<()> {
+    for node in nodes.iter().filter(|node| node.node_type == "Figure") {
+        if let Some(image) = figures::resolve_figure_image(&node.text, source_dir) {
+            assets::put_node_image(db, &node.id, &image).await?;
+        }
+    }
+    Ok(())
+}
+
+/// A document without a thumbnail still ingests; `get_document_thumbnail`
+/// makes a card for it when one is asked for.
+async fn store_thumbnail(
+    db: &Database,
+    document_id: &str,
+    path: &Path,
+    mime_type: &str,
+    parsed: &NormalizedPayload,
+) {
+    let image = thumbnails::generate(path, mime_type, parsed);
+    if let Err(err) = assets::put_document_thumbnail(db, document_id, &image).await {
+        eprintln!(
+            "Could not store the thumbnail of {document_id}: {}",
+            settings::privacy_redactor(db.pool()).await.error(&err)
+        );
+    }
+}
+
+/// Removes stored images no node refers to any more, after documents or
+/// nodes were deleted. The deletion already succeeded, so a failure here is
+/// only logged; the next collection picks up what this one missed.
+pub(crate) async fn collect_assets(db: &Database) {
+    if let Err(err) = assets::collect_garbage(db, assets::GC_GRACE).await {
+        eprintln!(
+            "Could not collect unused assets: {}",
+            settings::privacy_redactor(db.pool()).await.error(&err)
+        );
+    }
+}
+
+/// Hidden documents are listed only with `include_hidden`. Documents whose
+/// source file changed on disk since they were last ingested are marked
+/// `stale`, and each comes with the tags and attachments an import gave it.
 #[tauri::command]
 pub async fn list_documents(
     state: State<'_, AppState>,
     project_id: String,
+    include_hidden: Option<bool>,
 ) -> AppResult<ListDocumentsResponse> {
-    let docs = documents::list_documents(state.db.pool(), &project_id).await?;
+    let db = state.db();
+    let mut docs =
+        documents::list_documents(db.pool(), &project_id, include_hidden.unwrap_or(false)).await?;
+    for doc in &mut docs {
+        doc.stale = match (&doc.source_path, doc.last_ingested_at) {
+            (Some(path), Some(ingested_at)) => paths::modified_since(path, ingested_at),
+            _ => false,
+        };
+        doc.tags = documents::get_tags(db.pool(), &doc.id).await?;
+        doc.attachment_ids = documents::get_attachment_ids(db.pool(), &doc.id).await?;
+    }
     Ok(ListDocumentsResponse { documents: docs })
 }
 
+/// Loads a document and records it as recently opened, in `project_id` when
+/// it was opened from a project it is linked into.
 #[tauri::command]
 pub async fn open_document(
     state: State<'_, AppState>,
     document_id: String,
+    project_id: Option<String>,
 ) -> AppResult<OpenDocumentResponse> {
-    let document = documents::get_document(state.db.pool(), &document_id).await?;
+    let db = state.db();
+    documents::ensure_unlocked(db.pool(), &document_id).await?;
+    let document = documents::get_document(db.pool(), &document_id).await?;
+    let project_id = project_id.unwrap_or_else(|| document.project_id.clone());
+    activity::record_document_opened(db.writer(), &project_id, &document_id).await?;
     Ok(OpenDocumentResponse { document })
 }
 
+#[tauri::command]
+pub async fn list_recent_documents(
+    state: State<'_, AppState>,
+    project_id: Option<String>,
+    limit: Option<usize>,
+) -> AppResult<RecentDocumentsResponse> {
+    let db = state.db();
+    let documents =
+        activity::recent_documents(db.pool(), project_id.as_deref(), limit.unwrap_or(20)).await?;
+    Ok(RecentDocumentsResponse { documents })
+}
+
 #[tauri::command]
 pub async fn get_tree(
     state: State<'_, AppState>,
@@ -188,8 +1050,10 @@ pub async fn get_tree(
     parent_node_id: Option<String>,
     depth: Option<i64>,
 ) -> AppResult<GetTreeResponse> {
+    let db = state.db();
+    documents::ensure_unlocked(db.pool(), &document_id).await?;
     let nodes = documents::get_tree(
-        state.db.pool(),
+        db.pool(),
         &document_id,
         parent_node_id.as_deref(),
         depth.unwrap_or(3),
@@ -204,13 +1068,61 @@ pub async fn get_project_tree(
     project_id: String,
     depth: Option<i64>,
 ) -> AppResult<GetTreeResponse> {
-    let nodes = documents::get_project_tree(state.db.pool(), &project_id, depth.unwrap_or(3)).await?;
+    let db = state.db();
+    let nodes = documents::get_project_tree(db.pool(), &project_id, depth.unwrap_or(3)).await?;
     Ok(GetTreeResponse { nodes })
 }
 
+/// The changes to a project's tree after `since_revision`, so a refresh only
+/// transfers nodes that changed. Pass the `revision` of the previous response,
+/// or 0 for the whole tree.
+#[tauri::command]
+pub async fn get_project_tree_delta(
+    state: State<'_, AppState>,
+    project_id: String,
+    since_revision: Option<i64>,
+) -> AppResult<GetProjectTreeDeltaResponse> {
+    let db = state.db();
+    documents::get_project_tree_delta(db.pool(), &project_id, since_revision.unwrap_or(0)).await
+}
+
+/// Searches the documents of every project, grouped by project, for when it
+/// is not clear which project a document was ingested into. Archived
+/// projects are searched only with `include_archived`; `fuzziness` defaults
+/// to the retrieval setting.
+#[tauri::command]
+pub async fn search_all_projects(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+    include_archived: Option<bool>,
+    fuzziness: Option<u8>,
+) -> AppResult<SearchAllProjectsResponse> {
+    let db = state.db();
+    let fuzziness = match fuzziness {
+        Some(fuzziness) => fuzziness,
+        None => {
+            settings::get_setting::<RetrievalSettings>(db.pool(), settings::RETRIEVAL_SETTING)
+                .await?
+                .fuzziness
+        }
+    };
+    let search_query = documents::expand_fuzzy_terms(db.pool(), &query, fuzziness).await?;
+    let groups = documents::search_all_nodes(
+        db.pool(),
+        &search_query,
+        limit.unwrap_or(50),
+        include_archived.unwrap_or(false),
+    )
+    .await?;
+    Ok(SearchAllProjectsResponse { query, groups })
+}
+
 #[tauri::command]
 pub async fn get_node(state: State<'_, AppState>, node_id: String) -> AppResult<GetNodeResponse> {
-    let node = documents::get_node(state.db.pool(), &node_id).await?;
+    let db = state.db();
+    let node = documents::get_node(db.pool(), &node_id).await?;
+    documents::ensure_unlocked(db.pool(), &node.document_id).await?;
     Ok(GetNodeResponse { node })
 }
 
@@ -219,7 +1131,9 @@ pub async fn get_document_preview(
     state: State<'_, AppState>,
     document_id: String,
 ) -> AppResult<GetDocumentPreviewResponse> {
-    let blocks = documents::get_document_preview(state.db.pool(), &document_id)
+    let db = state.db();
+    documents::ensure_unlocked(db.pool(), &document_id).await?;
+    let blocks = documents::get_document_preview(db.pool(), &document_id)
         .await?
         .into_iter()
         .map(|node| DocumentPreviewBlock {
@@ -244,7 +1158,8 @@ pub async fn get_graph_layout(
     state: State<'_, AppState>,
     document_id: String,
 ) -> AppResult<GetGraphLayoutResponse> {
-    let positions = documents::get_graph_layout(state.db.pool(), &document_id).await?;
+    let db = state.db();
+    let positions = documents::get_graph_layout(db.pool(), &document_id).await?;
     Ok(GetGraphLayoutResponse {
         document_id,
         positions,
@@ -257,29 +1172,254 @@ pub async fn save_graph_layout(
     document_id: String,
     positions: Vec<GraphNodePosition>,
 ) -> AppResult<SaveGraphLayoutResponse> {
-    let saved = documents::save_graph_layout(state.db.pool(), &document_id, &positions).await?;
+    let db = state.db();
+    // Saving no positions clears the layout, which can be undone.
+    let cleared = if positions.is_empty() {
+        documents::get_graph_layout(db.pool(), &document_id).await?
+    } else {
+        vec![]
+    };
+    let saved = documents::save_graph_layout(db.writer(), &document_id, &positions).await?;
+    if !cleared.is_empty() {
+        let snapshot = UndoSnapshot::LayoutWipe {
+            document_id,
+            positions: cleared,
+        };
+        record_undo(&db, "Clear graph layout", &snapshot).await;
+    }
     Ok(SaveGraphLayoutResponse { saved })
 }
 
+/// Writes a document as Markdown, named after the document unless
+/// `dest_path` names the file; see [`exports::destination`].
 #[tauri::command]
 pub async fn export_markdown(
     state: State<'_, AppState>,
     document_id: String,
-) -> AppResult<ExportMarkdownResponse> {
-    let export_dir = state.data_dir.join("exports");
-    std::fs::create_dir_all(&export_dir).map_err(|err| AppError::Io(err.to_string()))?;
-    let file_path = export_dir.join(format!("{document_id}.md"));
-    documents::export_markdown(state.db.pool(), &document_id, &file_path).await?;
-    Ok(ExportMarkdownResponse {
-        file_path: file_path.to_string_lossy().to_string(),
+    dest_path: Option<String>,
+    overwrite: Option<bool>,
+) -> AppResult<ExportedFile> {
+    let db = state.db();
+    documents::ensure_unlocked(db.pool(), &document_id).await?;
+    let document = documents::get_document(db.pool(), &document_id).await?;
+    let file_path = exports::destination(
+        dest_path.as_deref(),
+        &state.data_dir.join("exports"),
+        &document.name,
+        "md",
+        overwrite.unwrap_or(false),
+    )?;
+    let replaced = paths::for_io(&file_path).exists();
+    documents::export_markdown(db.pool(), &document_id, &paths::for_io(&file_path)).await?;
+    state.exports.record(&file_path);
+    Ok(ExportedFile {
+        file_path: paths::to_utf8(&file_path)?,
+        replaced,
+    })
+}
+
+/// Shows an exported file in the system file manager. Only files the app
+/// exported and paths inside its exports folder are opened.
+#[tauri::command]
+pub async fn reveal_export(
+    state: State<'_, AppState>,
+    path: String,
+) -> AppResult<RevealExportResponse> {
+    let path = state.exports.check(&path)?;
+    exports::reveal(&path)?;
+    Ok(RevealExportResponse {
+        path: paths::to_utf8(&path)?,
     })
 }
 
 #[tauri::command]
 pub async fn delete_document(
+    app: AppHandle,
     state: State<'_, AppState>,
     document_id: String,
+    project_id: Option<String>,
 ) -> AppResult<DeleteDocumentResponse> {
-    let deleted = documents::delete_document(state.db.pool(), &document_id).await?;
+    let db = state.db();
+    let undo_entry = match documents::get_document(db.pool(), &document_id).await {
+        Ok(document) => Some((
+            format!("Delete {}", document.name),
+            undo::snapshot_document(&db, &document_id).await?,
+        )),
+        Err(AppError::NotFound(_)) => None,
+        Err(err) => return Err(err),
+    };
+    // With a project, only that project lets go of the document; other projects
+    // it is shared with keep it.
+    let (deleted, affected) = match project_id {
+        Some(project_id) => {
+            projects::ensure_writable(db.pool(), &project_id).await?;
+            let deleted =
+                documents::remove_document_from_project(db.writer(), &project_id, &document_id)
+                    .await?;
+            (deleted, vec![project_id])
+        }
+        None => {
+            projects::ensure_document_writable(db.pool(), &document_id).await?;
+            let affected = documents::document_project_ids(db.pool(), &document_id).await?;
+            let deleted = documents::delete_document(db.writer(), &document_id).await?;
+            (deleted, affected)
+        }
+    };
+    if deleted {
+        if let Some((label, snapshot)) = &undo_entry {
+            record_undo(&db, label, snapshot).await;
+        }
+        collect_assets(&db).await;
+        emit_corpus_changed(
+            &app,
+            &db,
+            &affected,
+            CorpusChange::DocumentRemoved,
+            &document_id,
+        )
+        .await;
+    }
     Ok(DeleteDocumentResponse { deleted })
 }
+
+/// Adds an operation to the undo journal. Best effort: the operation has
+/// already happened, so a failure only means it cannot be undone.
+async fn record_undo(db: &Database, label: &str, snapshot: &UndoSnapshot) {
+    if let Err(err) = undo::record(db, label, snapshot).await {
+        eprintln!(
+            "Could not record undo entry: {}",
+            settings::privacy_redactor(db.pool()).await.error(&err)
+        );
+    }
+}
+
+/// Reverses the newest document delete, node edit or layout wipe made within
+/// [`undo::UNDO_WINDOW`].
+#[tauri::command]
+pub async fn undo_last_operation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<UndoLastOperationResponse> {
+    let db = state.db();
+    let operation = undo::undo_last(&db, undo::UNDO_WINDOW).await?;
+    if let Some(operation) = &operation {
+        if operation.kind == UndoKind::DocumentDelete {
+            let project_ids = documents::document_project_ids(db.pool(), &operation.document_id)
+                .await
+                .unwrap_or_default();
+            emit_corpus_changed(
+                &app,
+                &db,
+                &project_ids,
+                CorpusChange::DocumentAdded,
+                &operation.document_id,
+            )
+            .await;
+        }
+    }
+    Ok(UndoLastOperationResponse { operation })
+}
+
+/// A stored image, e.g. a figure, by the id in its node's `assets` metadata.
+#[tauri::command]
+pub async fn get_asset(
+    state: State<'_, AppState>,
+    asset_id: String,
+) -> AppResult<GetAssetResponse> {
+    let db = state.db();
+    assets::ensure_asset_unlocked(db.pool(), &asset_id).await?;
+    assets::get_asset(&db, &asset_id).await
+}
+
+/// The document's thumbnail. One ingested before thumbnails were made gets a
+/// card from its stored text, kept for next time.
+#[tauri::command]
+pub async fn get_document_thumbnail(
+    state: State<'_, AppState>,
+    document_id: String,
+) -> AppResult<GetAssetResponse> {
+    let db = state.db();
+    documents::ensure_unlocked(db.pool(), &document_id).await?;
+    if let Some(thumbnail) = assets::get_document_thumbnail(&db, &document_id).await? {
+        return Ok(thumbnail);
+    }
+    let document = documents::get_document(db.pool(), &document_id).await?;
+    let nodes = documents::get_document_preview(db.pool(), &document_id).await?;
+    let card = thumbnails::text_card(
+        &document.name,
+        nodes
+            .iter()
+            .filter(|node| node.node_type != NodeType::Figure)
+            .map(|node| node.text.as_str()),
+    );
+    assets::put_document_thumbnail(&db, &document_id, &card).await
+}
+
+/// Sets whether the document is hidden from the default list and locked out
+/// of previews and retrieval. Taking a lock off asks for the passphrase, when
+/// one is set.
+#[tauri::command]
+pub async fn set_document_visibility(
+    state: State<'_, AppState>,
+    document_id: String,
+    hidden: bool,
+    locked: bool,
+    passphrase: Option<String>,
+) -> AppResult<DocumentVisibilityResponse> {
+    let db = state.db();
+    projects::ensure_document_writable(db.pool(), &document_id).await?;
+    let current = documents::get_document(db.pool(), &document_id).await?;
+    if current.locked && !locked {
+        document_lock::check_passphrase(db.pool(), passphrase.as_deref()).await?;
+    }
+    let document = documents::set_visibility(db.writer(), &document_id, hidden, locked).await?;
+    Ok(DocumentVisibilityResponse { document })
+}
+
+/// Unlocks a locked document for [`document_lock::UNLOCK_SECONDS`].
+#[tauri::command]
+pub async fn unlock_document(
+    state: State<'_, AppState>,
+    document_id: String,
+    passphrase: Option<String>,
+) -> AppResult<DocumentVisibilityResponse> {
+    let db = state.db();
+    document_lock::check_passphrase(db.pool(), passphrase.as_deref()).await?;
+    let document =
+        documents::unlock_document(db.writer(), &document_id, document_lock::UNLOCK_SECONDS)
+            .await?;
+    Ok(DocumentVisibilityResponse { document })
+}
+
+#[tauri::command]
+pub async fn relock_document(
+    state: State<'_, AppState>,
+    document_id: String,
+) -> AppResult<DocumentVisibilityResponse> {
+    let db = state.db();
+    let document = documents::relock_document(db.writer(), &document_id).await?;
+    Ok(DocumentVisibilityResponse { document })
+}
+
+#[tauri::command]
+pub async fn get_document_lock_status(state: State<'_, AppState>) -> AppResult<DocumentLockStatus> {
+    let db = state.db();
+    Ok(DocumentLockStatus {
+        passphrase_set: document_lock::passphrase_set(db.pool()).await?,
+    })
+}
+
+/// Sets, changes or (with no `passphrase`) removes the passphrase locked
+/// documents ask for; `current` must match the one already set.
+#[tauri::command]
+pub async fn set_document_lock_passphrase(
+    state: State<'_, AppState>,
+    current: Option<String>,
+    passphrase: Option<String>,
+) -> AppResult<DocumentLockStatus> {
+    let db = state.db();
+    document_lock::set_passphrase(db.writer(), current.as_deref(), passphrase.as_deref()).await?;
+    Ok(DocumentLockStatus {
+        passphrase_set: document_lock::passphrase_set(db.pool()).await?,
+    })
+}
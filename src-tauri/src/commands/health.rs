@@ -0,0 +1,25 @@
+use tauri::State;
+
+use crate::{
+    core::{errors::AppResult, scheduler::Scheduler, types::HealthCheckResponse},
+    db::Database,
+    AppState,
+};
+
+#[tauri::command]
+pub async fn health_check(state: State<'_, AppState>) -> AppResult<HealthCheckResponse> {
+    let db = state.db();
+    Ok(health(&db, &state.scheduler).await)
+}
+
+/// Whether the database answers, how busy its pool is and how much work is
+/// queued behind it.
+pub async fn health(db: &Database, scheduler: &Scheduler) -> HealthCheckResponse {
+    let database_ok = sqlx::query("SELECT 1").execute(db.pool()).await.is_ok();
+    HealthCheckResponse {
+        database_ok,
+        pool_size: db.pool().size(),
+        pool_idle: db.pool().num_idle(),
+        queue: scheduler.depth(),
+    }
+}
@@ -0,0 +1,69 @@
+use tauri::State;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        exports, metrics, paths, policy,
+        types::{ExportedFile, GetMetricsResponse, MetricsSettings},
+    },
+    db::repositories::{metrics as metrics_repo, settings},
+    AppState,
+};
+
+#[tauri::command]
+pub async fn get_metrics(state: State<'_, AppState>) -> AppResult<GetMetricsResponse> {
+    let db = state.db();
+    Ok(GetMetricsResponse {
+        settings: metrics_repo::get_settings(db.pool()).await?,
+        metrics: metrics_repo::list_metrics(db.pool()).await?,
+    })
+}
+
+/// Turns local metrics on or off. Totals recorded so far are kept either way.
+#[tauri::command]
+pub async fn set_metrics_settings(
+    state: State<'_, AppState>,
+    settings: MetricsSettings,
+) -> AppResult<MetricsSettings> {
+    policy::current().ensure_unlocked(settings::METRICS_SETTING)?;
+    let db = state.db();
+    metrics_repo::set_settings(db.writer(), &settings).await?;
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn reset_metrics(state: State<'_, AppState>) -> AppResult<GetMetricsResponse> {
+    let db = state.db();
+    metrics_repo::reset_metrics(db.writer()).await?;
+    Ok(GetMetricsResponse {
+        settings: metrics_repo::get_settings(db.pool()).await?,
+        metrics: vec![],
+    })
+}
+
+/// Writes the metrics in the Prometheus text format, e.g. into the folder a
+/// node exporter's textfile collector reads; pass `overwrite` to refresh the
+/// same file.
+#[tauri::command]
+pub async fn export_metrics_prometheus(
+    state: State<'_, AppState>,
+    dest_path: Option<String>,
+    overwrite: Option<bool>,
+) -> AppResult<ExportedFile> {
+    let db = state.db();
+    let text = metrics::prometheus_text(&metrics_repo::list_metrics(db.pool()).await?);
+    let file_path = exports::destination(
+        dest_path.as_deref(),
+        &state.data_dir.join("exports"),
+        "vectorless-metrics",
+        "prom",
+        overwrite.unwrap_or(false),
+    )?;
+    let replaced = paths::for_io(&file_path).exists();
+    std::fs::write(paths::for_io(&file_path), text).map_err(|err| AppError::Io(err.to_string()))?;
+    state.exports.record(&file_path);
+    Ok(ExportedFile {
+        file_path: paths::to_utf8(&file_path)?,
+        replaced,
+    })
+}
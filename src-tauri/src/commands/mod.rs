@@ -1,4 +1,10 @@
+pub mod database;
 pub mod documents;
+pub mod health;
+pub mod metrics;
+pub mod node_types;
 pub mod projects;
+pub mod prompts;
 pub mod reasoning;
 pub mod settings;
+pub mod workspaces;
@@ -0,0 +1,47 @@
+use tauri::State;
+
+use crate::{
+    core::{
+        errors::AppResult,
+        types::{DeleteNodeTypeResponse, ListNodeTypesResponse, SaveNodeTypeResponse},
+    },
+    db::repositories::node_types,
+    AppState,
+};
+
+#[tauri::command]
+pub async fn list_node_types(state: State<'_, AppState>) -> AppResult<ListNodeTypesResponse> {
+    let db = state.db();
+    let node_types = node_types::list_node_types(db.pool()).await?;
+    Ok(ListNodeTypesResponse { node_types })
+}
+
+#[tauri::command]
+pub async fn save_node_type(
+    state: State<'_, AppState>,
+    name: String,
+    label: Option<String>,
+    description: Option<String>,
+    color: Option<String>,
+) -> AppResult<SaveNodeTypeResponse> {
+    let db = state.db();
+    let node_type = node_types::save_node_type(
+        db.writer(),
+        &name,
+        label.as_deref(),
+        description.as_deref(),
+        color.as_deref(),
+    )
+    .await?;
+    Ok(SaveNodeTypeResponse { node_type })
+}
+
+#[tauri::command]
+pub async fn delete_node_type(
+    state: State<'_, AppState>,
+    name: String,
+) -> AppResult<DeleteNodeTypeResponse> {
+    let db = state.db();
+    let removed = node_types::delete_node_type(db.writer(), &name).await?;
+    Ok(DeleteNodeTypeResponse { removed })
+}
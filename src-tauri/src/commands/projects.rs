@@ -2,11 +2,23 @@ use tauri::State;
 use uuid::Uuid;
 
 use crate::{
+    commands::documents::collect_assets,
     core::{
         errors::{AppError, AppResult},
-        types::{CreateProjectResponse, DeleteProjectResponse, ListProjectsResponse, RenameProjectResponse},
+        exports, paths, policy,
+        types::{
+            ArchiveProjectResponse, BibliographyFormat, BundleManifest, CreateProjectResponse,
+            DeleteProjectResponse, ExportBibliographyResponse, ExportSharedBundleResponse,
+            ExportedFile, GetDeleteImpactResponse, IngestRules, IngestRulesResponse,
+            ListAcronymsResponse, ListProjectsResponse, OpenSharedBundleResponse, PolicyFeature,
+            RenameProjectResponse, ResearchLogSettings, ResearchLogSettingsResponse,
+        },
     },
-    db::repositories::projects,
+    db::{
+        bundle,
+        repositories::{acronyms, documents, projects},
+    },
+    sidecar::{ignore::normalize_rules, references},
     AppState,
 };
 
@@ -20,7 +32,8 @@ fn normalized_name(name: &str) -> AppResult<String> {
 
 #[tauri::command]
 pub async fn list_projects(state: State<'_, AppState>) -> AppResult<ListProjectsResponse> {
-    let projects = projects::list_projects(state.db.pool()).await?;
+    let db = state.db();
+    let projects = projects::list_projects(db.pool()).await?;
     Ok(ListProjectsResponse { projects })
 }
 
@@ -29,9 +42,10 @@ pub async fn create_project(
     state: State<'_, AppState>,
     name: String,
 ) -> AppResult<CreateProjectResponse> {
+    let db = state.db();
     let id = Uuid::new_v4().to_string();
     let normalized = normalized_name(&name)?;
-    let project = projects::create_project(state.db.pool(), &id, &normalized).await?;
+    let project = projects::create_project(db.writer(), &id, &normalized).await?;
     Ok(CreateProjectResponse { project })
 }
 
@@ -41,16 +55,218 @@ pub async fn rename_project(
     project_id: String,
     name: String,
 ) -> AppResult<RenameProjectResponse> {
+    let db = state.db();
     let normalized = normalized_name(&name)?;
-    let project = projects::rename_project(state.db.pool(), &project_id, &normalized).await?;
+    let project = projects::rename_project(db.writer(), &project_id, &normalized).await?;
     Ok(RenameProjectResponse { project })
 }
 
+/// Reports what deleting the project would remove, with the token
+/// `delete_project` asks for.
+#[tauri::command]
+pub async fn get_delete_impact(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<GetDeleteImpactResponse> {
+    let db = state.db();
+    let impact = projects::delete_impact(db.pool(), &project_id).await?;
+    Ok(GetDeleteImpactResponse { impact })
+}
+
+/// Deletes the project and everything it owns. `confirmation_token` must be
+/// the one `get_delete_impact` returned for the project as it is now.
 #[tauri::command]
 pub async fn delete_project(
     state: State<'_, AppState>,
     project_id: String,
+    confirmation_token: String,
 ) -> AppResult<DeleteProjectResponse> {
-    let deleted = projects::delete_project(state.db.pool(), &project_id).await?;
+    let db = state.db();
+    let impact = projects::delete_impact(db.pool(), &project_id).await?;
+    if impact.confirmation_token != confirmation_token {
+        return Err(AppError::InvalidInput(format!(
+            "project {project_id} changed since its delete impact was read; review it again before deleting"
+        )));
+    }
+    let deleted = projects::delete_project(db.writer(), &project_id).await?;
+    if deleted {
+        collect_assets(&db).await;
+    }
     Ok(DeleteProjectResponse { deleted })
 }
+
+/// Puts a project away: it can still be opened and queried, but its
+/// documents cannot be ingested, changed or enriched until it is unarchived,
+/// and global search leaves it out unless asked.
+#[tauri::command]
+pub async fn archive_project(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<ArchiveProjectResponse> {
+    let db = state.db();
+    let project = projects::set_archived(db.writer(), &project_id, true).await?;
+    Ok(ArchiveProjectResponse { project })
+}
+
+#[tauri::command]
+pub async fn unarchive_project(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<ArchiveProjectResponse> {
+    let db = state.db();
+    let project = projects::set_archived(db.writer(), &project_id, false).await?;
+    Ok(ArchiveProjectResponse { project })
+}
+
+#[tauri::command]
+pub async fn get_ingest_rules(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<IngestRulesResponse> {
+    let db = state.db();
+    let rules = projects::get_ingest_rules(db.pool(), &project_id).await?;
+    Ok(IngestRulesResponse { project_id, rules })
+}
+
+#[tauri::command]
+pub async fn set_ingest_rules(
+    state: State<'_, AppState>,
+    project_id: String,
+    rules: IngestRules,
+) -> AppResult<IngestRulesResponse> {
+    let db = state.db();
+    let rules = normalize_rules(rules)?;
+    let rules = projects::set_ingest_rules(db.writer(), &project_id, &rules).await?;
+    Ok(IngestRulesResponse { project_id, rules })
+}
+
+/// Acronyms the project's documents define, for a glossary view. Questions
+/// already match either form of each.
+#[tauri::command]
+pub async fn list_acronyms(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<ListAcronymsResponse> {
+    let db = state.db();
+    let acronyms = acronyms::list_project_acronyms(db.pool(), &project_id).await?;
+    Ok(ListAcronymsResponse {
+        project_id,
+        acronyms,
+    })
+}
+
+#[tauri::command]
+pub async fn get_research_log_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<ResearchLogSettingsResponse> {
+    let db = state.db();
+    let settings = projects::get_research_log_settings(db.pool(), &project_id).await?;
+    Ok(ResearchLogSettingsResponse {
+        project_id,
+        settings,
+    })
+}
+
+/// Switches the project's Research Log on or off. Turning it off keeps the
+/// log document and what it already holds.
+#[tauri::command]
+pub async fn set_research_log_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+    settings: ResearchLogSettings,
+) -> AppResult<ResearchLogSettingsResponse> {
+    let db = state.db();
+    projects::ensure_writable(db.pool(), &project_id).await?;
+    let settings = projects::set_research_log_settings(db.writer(), &project_id, &settings).await?;
+    Ok(ResearchLogSettingsResponse {
+        project_id,
+        settings,
+    })
+}
+
+/// Writes a read-only, verifiable copy of the project for someone else. With
+/// no `dest_path` the bundle goes to the app's exports folder.
+#[tauri::command]
+pub async fn export_shared_bundle(
+    state: State<'_, AppState>,
+    project_id: String,
+    dest_path: Option<String>,
+) -> AppResult<ExportSharedBundleResponse> {
+    policy::current().ensure_enabled(PolicyFeature::SharedBundles)?;
+    let db = state.db();
+    let dest = match dest_path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => paths::from_input(&path)?,
+        _ => state
+            .data_dir
+            .join("exports")
+            .join(format!("{project_id}.{}", bundle::BUNDLE_EXTENSION)),
+    };
+    let exported = bundle::export_shared_bundle(&db, &project_id, &paths::for_io(&dest)).await?;
+    state.exports.record(&dest);
+    Ok(exported)
+}
+
+/// Writes the reference metadata of the project's documents as BibTeX or
+/// CSL-JSON, named after the project unless `dest_path` names the file; see
+/// [`exports::destination`]. Only documents whose title and authors or DOI
+/// were detected at ingest are included.
+#[tauri::command]
+pub async fn export_bibliography(
+    state: State<'_, AppState>,
+    project_id: String,
+    format: BibliographyFormat,
+    dest_path: Option<String>,
+    overwrite: Option<bool>,
+) -> AppResult<ExportBibliographyResponse> {
+    let db = state.db();
+    let project = projects::get_project(db.pool(), &project_id).await?;
+    let entries = documents::project_references(db.pool(), &project_id).await?;
+    if entries.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "no document in {} has reference metadata; reingest documents added before it was detected",
+            project.name
+        )));
+    }
+    let (contents, extension) = match format {
+        BibliographyFormat::Bibtex => (references::bibtex(&entries), "bib"),
+        BibliographyFormat::CslJson => (
+            serde_json::to_string_pretty(&references::csl_json(&entries))?,
+            "json",
+        ),
+    };
+    let file_path = exports::destination(
+        dest_path.as_deref(),
+        &state.data_dir.join("exports"),
+        &project.name,
+        extension,
+        overwrite.unwrap_or(false),
+    )?;
+    let replaced = paths::for_io(&file_path).exists();
+    std::fs::write(paths::for_io(&file_path), contents)?;
+    state.exports.record(&file_path);
+    Ok(ExportBibliographyResponse {
+        file: ExportedFile {
+            file_path: paths::to_utf8(&file_path)?,
+            replaced,
+        },
+        entry_count: entries.len(),
+    })
+}
+
+#[tauri::command]
+pub async fn verify_shared_bundle(path: String) -> AppResult<BundleManifest> {
+    bundle::verify_shared_bundle(&paths::resolve_input(&path)?)
+}
+
+/// Verifies a bundle and adds it as a read-only project.
+#[tauri::command]
+pub async fn open_shared_bundle(
+    state: State<'_, AppState>,
+    path: String,
+) -> AppResult<OpenSharedBundleResponse> {
+    policy::current().ensure_enabled(PolicyFeature::SharedBundles)?;
+    let db = state.db();
+    let _permit = state.scheduler.background().await;
+    bundle::open_shared_bundle(&db, &paths::resolve_input(&path)?).await
+}
@@ -0,0 +1,53 @@
+use tauri::State;
+
+use crate::{
+    core::{
+        errors::AppResult,
+        types::{
+            GetPromptTemplateHistoryResponse, ListPromptTemplatesResponse,
+            SavePromptTemplateResponse,
+        },
+    },
+    db::repositories::prompts,
+    AppState,
+};
+
+#[tauri::command]
+pub async fn list_prompt_templates(
+    state: State<'_, AppState>,
+) -> AppResult<ListPromptTemplatesResponse> {
+    let db = state.db();
+    let templates = prompts::list_active_templates(db.pool()).await?;
+    Ok(ListPromptTemplatesResponse { templates })
+}
+
+#[tauri::command]
+pub async fn get_prompt_template_history(
+    state: State<'_, AppState>,
+    name: String,
+) -> AppResult<GetPromptTemplateHistoryResponse> {
+    let db = state.db();
+    let versions = prompts::list_template_history(db.pool(), &name).await?;
+    Ok(GetPromptTemplateHistoryResponse { versions })
+}
+
+#[tauri::command]
+pub async fn save_prompt_template(
+    state: State<'_, AppState>,
+    name: String,
+    body: String,
+) -> AppResult<SavePromptTemplateResponse> {
+    let db = state.db();
+    let template = prompts::save_template(db.writer(), &name, &body).await?;
+    Ok(SavePromptTemplateResponse { template })
+}
+
+#[tauri::command]
+pub async fn reset_prompt_template(
+    state: State<'_, AppState>,
+    name: String,
+) -> AppResult<SavePromptTemplateResponse> {
+    let db = state.db();
+    let template = prompts::reset_template(db.pool(), &name).await?;
+    Ok(SavePromptTemplateResponse { template })
+}
@@ -1,14 +1,45 @@
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
 use tauri::{AppHandle, Emitter, State};
 use uuid::Uuid;
 
 use crate::{
+    commands::documents::emit_corpus_changed,
     core::{
         errors::{AppError, AppResult},
-        types::{Provider, ReasoningCompleteEvent, ReasoningErrorEvent, RunReasoningQueryResponse},
+        exports,
+        metrics::Metric,
+        paths,
+        types::{
+            AnswerRecord, AnswerRevision, BenchmarkProgressEvent, BenchmarkReport, CitationHeatmap,
+            CorpusChange, DateRange, ExportedFile, GenerationSettings, ModelPricing,
+            ModelSelection, PlannerModel, Provider, ProviderTimeoutSettings, ProviderTimeouts,
+            QualityTrendsResponse, QueryCostEstimate, QueryScope, ReasoningCompleteEvent,
+            ReasoningErrorEvent, ReasoningPhaseEvent, RecentQueriesResponse, RetrievalSettings,
+            RunComparison, RunManifest, RunOverrides, RunPhase, RunReasoningQueryResponse,
+            RunTimeline, ScopeDecision, UnusedContentReport,
+        },
+    },
+    db::{
+        repositories::{
+            activity, documents, metrics, projects, provider_cache::ProviderCacheConfig, reasoning,
+            settings,
+        },
+        Database,
+    },
+    providers::{
+        generation::{merge_settings, validate_settings},
+        planner_provider_for_selection,
+        pricing::PricingTable,
+        provider_for_selection,
     },
-    db::repositories::reasoning,
-    reasoner::query_scope::requires_project_scope,
-    security::keyring,
+    reasoner::{
+        benchmark, compare,
+        executor::{self, ExecutionResult, ReasoningExecutor, RunEvent, RunOptions},
+        query_scope::decide_scope,
+        research_log, share, timeline,
+    },
+    security::key_pool,
     AppState,
 };
 
@@ -20,78 +51,767 @@ pub async fn run_reasoning_query(
     query: String,
     max_steps: Option<i64>,
     focus_document_id: Option<String>,
+    generation: Option<GenerationSettings>,
+    attach_figure_images: Option<bool>,
+    expand_context: Option<bool>,
+    infer_focus_document: Option<bool>,
+    force_scope: Option<QueryScope>,
+    date_range: Option<DateRange>,
 ) -> AppResult<RunReasoningQueryResponse> {
+    let db = state.db();
     if query.trim().is_empty() {
         return Err(AppError::InvalidInput("query cannot be empty".to_string()));
     }
+    if force_scope == Some(QueryScope::Document) && focus_document_id.is_none() {
+        return Err(AppError::InvalidInput(
+            "choose a document to keep the question to".to_string(),
+        ));
+    }
+    if let Some(DateRange {
+        from: Some(from),
+        to: Some(to),
+    }) = date_range
+    {
+        if from > to {
+            return Err(AppError::InvalidInput(format!(
+                "date range starts ({from}) after it ends ({to})"
+            )));
+        }
+    }
+    let project_generation = projects::get_generation_settings(db.pool(), &project_id).await?;
+    let generation = match generation {
+        Some(overrides) => {
+            validate_settings(&overrides)?;
+            merge_settings(&project_generation, &overrides)
+        }
+        None => project_generation,
+    };
+    let selection: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
 
-    let run_id = Uuid::new_v4().to_string();
-    let api_key = keyring::get_provider_key(Provider::Gemini)?;
-    let effective_focus_document_id = if requires_project_scope(&query) {
-        None
-    } else {
-        focus_document_id.clone()
+    start_run(
+        &app,
+        &state,
+        db,
+        RunRequest {
+            project_id,
+            query,
+            focus_document_id,
+            max_steps,
+            generation,
+            attach_figure_images: attach_figure_images.unwrap_or(false),
+            expand_context: expand_context.unwrap_or(false),
+            infer_focus_document: infer_focus_document.unwrap_or(true),
+            force_scope,
+            date_range,
+            selection,
+            rerun_of: None,
+        },
+    )
+    .await
+}
+
+/// Asks a stored run's question again with some of its settings changed, to
+/// produce the B side of an A/B comparison. Unchanged settings come from the
+/// source run's manifest rather than today's defaults.
+#[tauri::command]
+pub async fn rerun_with(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    run_id: String,
+    overrides: Option<RunOverrides>,
+) -> AppResult<RunReasoningQueryResponse> {
+    let db = state.db();
+    let source = reasoning::get_run(db.pool(), &run_id).await?.run;
+    let overrides = overrides.unwrap_or_default();
+    let manifest = source.manifest.as_ref();
+
+    let base_generation = match manifest {
+        Some(manifest) => manifest.generation.clone(),
+        None => projects::get_generation_settings(db.pool(), &source.project_id).await?,
+    };
+    let generation = match &overrides.generation {
+        Some(changes) => {
+            validate_settings(changes)?;
+            merge_settings(&base_generation, changes)
+        }
+        None => base_generation,
+    };
+    let current: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
+    let selection = rerun_selection(current, manifest, &overrides)?;
+    // The stored focus already went through the scope heuristic; keep it.
+    let force_scope = source.document_id.is_some().then_some(QueryScope::Document);
+
+    start_run(
+        &app,
+        &state,
+        db,
+        RunRequest {
+            project_id: source.project_id,
+            query: source.query,
+            focus_document_id: source.document_id,
+            max_steps: overrides.max_steps.or(source.max_steps),
+            generation,
+            attach_figure_images: overrides
+                .attach_figure_images
+                .unwrap_or(source.attach_figure_images),
+            expand_context: overrides
+                .expand_context
+                .unwrap_or_else(|| manifest.is_some_and(|manifest| manifest.expand_context)),
+            infer_focus_document: overrides
+                .infer_focus_document
+                .unwrap_or_else(|| manifest.is_some_and(|manifest| manifest.infer_focus_document)),
+            force_scope,
+            date_range: manifest.and_then(|manifest| manifest.date_range),
+            selection,
+            rerun_of: Some(run_id),
+        },
+    )
+    .await
+}
+
+/// Adds a document to a finished run's evidence and answers its question
+/// again from both, rather than starting a new run; see
+/// [`ReasoningExecutor::add_document`]. The run keeps the models and settings
+/// it was asked with. Steps arrive as `reasoning/step` events and the result
+/// also as `reasoning/complete`.
+#[tauri::command]
+pub async fn add_document_to_run(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    run_id: String,
+    document_id: String,
+) -> AppResult<ReasoningCompleteEvent> {
+    let db = state.db();
+    let source = reasoning::get_run(db.pool(), &run_id).await?.run;
+    let manifest = source.manifest.as_ref();
+    let generation = match manifest {
+        Some(manifest) => manifest.generation.clone(),
+        None => projects::get_generation_settings(db.pool(), &source.project_id).await?,
+    };
+    let current: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
+    let selection = rerun_selection(current, manifest, &RunOverrides::default())?;
+    let (executor, options) = prepare_run(&state, &db, &selection).await?;
+    let retrieval = match manifest {
+        Some(manifest) => RetrievalSettings {
+            fuzziness: manifest.fuzziness,
+            ranking: manifest.ranking.clone().unwrap_or_default(),
+        },
+        None => options.retrieval.clone(),
+    };
+
+    let _permit = state.scheduler.interactive();
+    let result = executor
+        .add_document(
+            &db,
+            &run_id,
+            &document_id,
+            RunOptions {
+                generation,
+                attach_figure_images: source.attach_figure_images,
+                expand_context: manifest.is_some_and(|manifest| manifest.expand_context),
+                date_range: manifest.and_then(|manifest| manifest.date_range),
+                retrieval,
+                ..options
+            },
+            |event| emit_run_event(&app, event),
+        )
+        .await?;
+    let complete = ReasoningCompleteEvent {
+        run_id: result.run_id,
+        answer_id: result.answer_id,
+        final_confidence: result.final_confidence,
+        total_latency_ms: result.total_latency_ms,
+        token_usage: result.token_usage,
+        cost_usd: result.cost_usd,
+        insufficient_evidence: result.insufficient_evidence,
+    };
+    let _ = app.emit("reasoning/complete", complete.clone());
+    Ok(complete)
+}
+
+/// Projects what asking `query` would cost before spending anything:
+/// retrieval runs as it would for the question, but no provider is called.
+/// `settings` changes the models or run settings the way
+/// [`rerun_with`] overrides do.
+#[tauri::command]
+pub async fn estimate_query_cost(
+    state: State<'_, AppState>,
+    project_id: String,
+    query: String,
+    focus_document_id: Option<String>,
+    settings: Option<RunOverrides>,
+) -> AppResult<QueryCostEstimate> {
+    let db = state.db();
+    if query.trim().is_empty() {
+        return Err(AppError::InvalidInput("query cannot be empty".to_string()));
+    }
+    let overrides = settings.unwrap_or_default();
+    let project_generation = projects::get_generation_settings(db.pool(), &project_id).await?;
+    let generation = match &overrides.generation {
+        Some(changes) => {
+            validate_settings(changes)?;
+            merge_settings(&project_generation, changes)
+        }
+        None => project_generation,
+    };
+    let current: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
+    let selection = rerun_selection(current, None, &overrides)?;
+    let (executor, options) = prepare_run(&state, &db, &selection).await?;
+    let scope = decide_scope(&query, focus_document_id, None, &options.language);
+    executor
+        .estimate_cost(
+            &db,
+            &project_id,
+            scope,
+            &query,
+            &RunOptions {
+                max_steps: overrides.max_steps.map(|value| value.max(1) as usize),
+                generation,
+                expand_context: overrides.expand_context.unwrap_or(false),
+                ..options
+            },
+        )
+        .await
+}
+
+/// The source run's provider and models with `overrides` applied. Context
+/// length and pricing carry over from the current selection only for the
+/// models it names; others are looked up as usual.
+fn rerun_selection(
+    current: ModelSelection,
+    manifest: Option<&RunManifest>,
+    overrides: &RunOverrides,
+) -> AppResult<ModelSelection> {
+    let source_provider = manifest
+        .and_then(|manifest| manifest.provider.clone())
+        .unwrap_or_else(|| current.provider.clone());
+    let provider = overrides
+        .provider
+        .clone()
+        .unwrap_or_else(|| source_provider.clone());
+    let model = match (&overrides.model, manifest) {
+        (Some(model), _) => model.trim().to_string(),
+        (None, _) if provider != source_provider => {
+            return Err(AppError::InvalidInput(format!(
+                "choose a {} model to rerun with",
+                provider.as_str()
+            )));
+        }
+        (None, Some(manifest)) => manifest.model.clone(),
+        (None, None) => current.model.clone(),
     };
-    let db = state.db.clone();
-    let executor = state.executor.clone();
+    if model.is_empty() {
+        return Err(AppError::InvalidInput("model cannot be empty".to_string()));
+    }
+    let planner_model = match (&overrides.planner_model, manifest) {
+        (Some(planner), _) => Some(planner.trim().to_string()),
+        (None, _) if provider != source_provider => None,
+        (None, Some(manifest)) => {
+            Some(manifest.planner_model.clone()).filter(|planner| *planner != manifest.model)
+        }
+        (None, None) => current
+            .planner
+            .as_ref()
+            .map(|planner| planner.model.clone()),
+    }
+    .filter(|planner| !planner.is_empty() && *planner != model);
+
+    let same_provider = provider == current.provider;
+    let known = same_provider && model == current.model;
+    let planner = planner_model.map(|planner_model| match &current.planner {
+        Some(planner) if same_provider && planner.model == planner_model => planner.clone(),
+        _ => PlannerModel {
+            model: planner_model,
+            context_length: None,
+            pricing: None,
+        },
+    });
+    Ok(ModelSelection {
+        context_length: known.then_some(current.context_length).flatten(),
+        pricing: known.then_some(current.pricing).flatten(),
+        provider,
+        model,
+        planner,
+    })
+}
+
+/// A run ready to start, with settings already resolved.
+struct RunRequest {
+    project_id: String,
+    query: String,
+    focus_document_id: Option<String>,
+    max_steps: Option<i64>,
+    generation: GenerationSettings,
+    attach_figure_images: bool,
+    expand_context: bool,
+    infer_focus_document: bool,
+    force_scope: Option<QueryScope>,
+    date_range: Option<DateRange>,
+    selection: ModelSelection,
+    rerun_of: Option<String>,
+}
+
+/// Starts `request` in the background and returns its run id; progress and
+/// the outcome arrive as `reasoning/*` events.
+async fn start_run(
+    app: &AppHandle,
+    state: &AppState,
+    db: Database,
+    request: RunRequest,
+) -> AppResult<RunReasoningQueryResponse> {
+    let RunRequest {
+        project_id,
+        query,
+        focus_document_id,
+        max_steps,
+        generation,
+        attach_figure_images,
+        expand_context,
+        infer_focus_document,
+        force_scope,
+        date_range,
+        selection,
+        rerun_of,
+    } = request;
+    let run_id = Uuid::new_v4().to_string();
+    let (executor, options) = prepare_run(state, &db, &selection).await?;
+    activity::record_query_asked(db.writer(), &project_id, &run_id, &query).await?;
+    let scope = decide_scope(&query, focus_document_id, force_scope, &options.language);
+    let effective_focus_document_id = scope.focus_document_id.clone();
     let run_id_for_task = run_id.clone();
-    let project_id_for_task = project_id.clone();
-    let focus_document_id_for_task = effective_focus_document_id.clone();
-    let query_for_task = query.clone();
     let app_for_task = app.clone();
+    let permit = state.scheduler.interactive();
 
     tauri::async_runtime::spawn(async move {
+        let _permit = permit;
         let outcome = executor
-            .run(
+            .run_with_options(
                 &db,
-                &project_id_for_task,
-                focus_document_id_for_task.as_deref(),
+                &project_id,
+                effective_focus_document_id.as_deref(),
                 run_id_for_task.clone(),
-                &query_for_task,
-                max_steps.map(|value| value.max(1) as usize),
-                &api_key,
-                |step_event| {
-                    let _ = app_for_task.emit("reasoning/step", step_event);
+                &query,
+                RunOptions {
+                    max_steps: max_steps.map(|value| value.max(1) as usize),
+                    generation,
+                    attach_figure_images,
+                    expand_context,
+                    infer_focus_document: infer_focus_document
+                        && force_scope != Some(QueryScope::Project),
+                    date_range,
+                    rerun_of,
+                    ..options
                 },
+                |event| emit_run_event(&app_for_task, event),
             )
             .await;
 
-        match outcome {
-            Ok(result) => {
-                let _ = app_for_task.emit(
-                    "reasoning/complete",
-                    ReasoningCompleteEvent {
-                        run_id: result.run_id,
-                        answer_id: result.answer_id,
-                        final_confidence: result.final_confidence,
-                        total_latency_ms: result.total_latency_ms,
-                        token_usage: result.token_usage,
-                        cost_usd: result.cost_usd,
-                    },
-                );
-            }
-            Err(err) => {
-                let _ = reasoning::fail_run(db.pool(), &run_id_for_task).await;
-                let _ = app_for_task.emit(
-                    "reasoning/error",
-                    ReasoningErrorEvent {
-                        run_id: run_id_for_task,
-                        code: err.code().to_string(),
-                        message: err.to_string(),
-                        retryable: err.retryable(),
-                    },
-                );
-            }
+        let completed = outcome.is_ok();
+        emit_outcome(&app_for_task, &db, &run_id_for_task, outcome).await;
+        if completed {
+            append_to_research_log(&app_for_task, &db, &run_id_for_task).await;
         }
     });
 
     Ok(RunReasoningQueryResponse {
         run_id,
         status: "started".to_string(),
+        scope,
     })
 }
 
+#[tauri::command]
+pub async fn replay_run(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    run_id: String,
+) -> AppResult<RunReasoningQueryResponse> {
+    let db = state.db();
+    // Validate up front so a missing run surfaces as a command error, not an event.
+    let source = reasoning::get_run(db.pool(), &run_id).await?;
+    // A replay reads what the source run read; its scope was decided then.
+    let scope = ScopeDecision {
+        scope: if source.run.document_id.is_some() {
+            QueryScope::Document
+        } else {
+            QueryScope::Project
+        },
+        focus_document_id: source.run.document_id,
+        focus_dropped: false,
+        forced: false,
+    };
+
+    let replay_run_id = Uuid::new_v4().to_string();
+    let replay_run_id_for_task = replay_run_id.clone();
+    let app_for_task = app.clone();
+    let permit = state.scheduler.interactive();
+
+    tauri::async_runtime::spawn(async move {
+        let _permit = permit;
+        let outcome = executor::replay_run(&db, &run_id, replay_run_id_for_task.clone(), |event| {
+            emit_run_event(&app_for_task, event)
+        })
+        .await;
+
+        emit_outcome(&app_for_task, &db, &replay_run_id_for_task, outcome).await;
+    });
+
+    Ok(RunReasoningQueryResponse {
+        run_id: replay_run_id,
+        status: "started".to_string(),
+        scope,
+    })
+}
+
+/// The executor for `selection` and the run options every run made with it
+/// shares: keys, pricing and the provider.
+async fn prepare_run(
+    state: &AppState,
+    db: &Database,
+    selection: &ModelSelection,
+) -> AppResult<(ReasoningExecutor, RunOptions)> {
+    let api_keys = key_pool::load_key_pool(db.pool(), selection.provider.clone()).await?;
+    let pricing_overrides: BTreeMap<String, ModelPricing> =
+        settings::get_setting(db.pool(), settings::PRICING_OVERRIDES_SETTING).await?;
+    let override_table = PricingTable::default().with_overrides(&pricing_overrides);
+    let pricing = override_table.lookup(&selection.model);
+    let planner_pricing = match &selection.planner {
+        Some(planner) => override_table.lookup(&planner.model),
+        None => pricing,
+    };
+    let timeouts: ProviderTimeoutSettings =
+        settings::get_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING).await?;
+    // The shared executor's client was built with the default timeouts.
+    let executor = if selection.provider == Provider::Gemini
+        && selection.model == state.executor.model()
+        && selection.planner.is_none()
+        && timeouts.gemini == ProviderTimeouts::default()
+    {
+        state.executor.clone()
+    } else {
+        let mut executor =
+            ReasoningExecutor::with_provider(provider_for_selection(selection, &timeouts)?)
+                .with_cache(ProviderCacheConfig::default());
+        if let Some(planner) = planner_provider_for_selection(selection, &timeouts)? {
+            executor = executor.with_planner_provider(planner);
+        }
+        executor
+    };
+    let options = RunOptions {
+        api_keys,
+        pricing,
+        planner_pricing,
+        provider: Some(selection.provider.clone()),
+        language: settings::get_setting(db.pool(), settings::LANGUAGE_RESOURCES_SETTING).await?,
+        quality: settings::get_setting(db.pool(), settings::QUALITY_THRESHOLDS_SETTING).await?,
+        privacy: settings::get_setting(db.pool(), settings::PRIVACY_SETTING).await?,
+        retrieval: settings::get_setting(db.pool(), settings::RETRIEVAL_SETTING).await?,
+        ..RunOptions::default()
+    };
+    Ok((executor, options))
+}
+
+/// Asks every question in a Q&A file against the project, scores the answers
+/// and writes a JSON report to the app's benchmarks folder. Each scored case
+/// is also sent as a `benchmark/progress` event.
+#[tauri::command]
+pub async fn run_benchmark(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    qa_file: String,
+) -> AppResult<BenchmarkReport> {
+    let db = state.db();
+    projects::get_project(db.pool(), &project_id).await?;
+    let qa_path = PathBuf::from(qa_file.trim());
+    let cases = benchmark::parse_qa_set(&std::fs::read_to_string(&qa_path)?)?;
+    let generation = projects::get_generation_settings(db.pool(), &project_id).await?;
+    let selection: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await?;
+    let (executor, options) = prepare_run(&state, &db, &selection).await?;
+
+    let benchmark_id = Uuid::new_v4().to_string();
+    let total = cases.len();
+    let mut completed = 0;
+    let mut report = benchmark::run_benchmark(
+        &executor,
+        &db,
+        &project_id,
+        &qa_path.to_string_lossy(),
+        &cases,
+        RunOptions {
+            generation,
+            infer_focus_document: true,
+            ..options
+        },
+        || state.scheduler.interactive(),
+        |result| {
+            completed += 1;
+            let _ = app.emit(
+                "benchmark/progress",
+                BenchmarkProgressEvent {
+                    benchmark_id: benchmark_id.clone(),
+                    completed,
+                    total,
+                    result: result.clone(),
+                },
+            );
+        },
+    )
+    .await?;
+    report.id = benchmark_id;
+    let report_path = state
+        .data_dir
+        .join("benchmarks")
+        .join(format!("{}.json", report.id));
+    report.report_path = Some(report_path.to_string_lossy().to_string());
+    benchmark::write_report(&report, &report_path)?;
+    Ok(report)
+}
+
+fn emit_run_event(app: &AppHandle, event: RunEvent) {
+    let _ = match event {
+        RunEvent::Step(step) => app.emit("reasoning/step", step),
+        RunEvent::Phase(phase) => app.emit("reasoning/phase", phase),
+    };
+}
+
+async fn emit_outcome(
+    app: &AppHandle,
+    db: &Database,
+    run_id: &str,
+    outcome: AppResult<ExecutionResult>,
+) {
+    let (metric, duration) = match &outcome {
+        Ok(result) => (
+            Metric::Run,
+            Some(Duration::from_millis(result.total_latency_ms.max(0) as u64)),
+        ),
+        Err(_) => (Metric::RunFailure, None),
+    };
+    let _ = metrics::record(db.writer(), metric, "", duration).await;
+    match outcome {
+        Ok(result) => {
+            let _ = app.emit(
+                "reasoning/complete",
+                ReasoningCompleteEvent {
+                    run_id: result.run_id,
+                    answer_id: result.answer_id,
+                    final_confidence: result.final_confidence,
+                    total_latency_ms: result.total_latency_ms,
+                    token_usage: result.token_usage,
+                    cost_usd: result.cost_usd,
+                    insufficient_evidence: result.insufficient_evidence,
+                },
+            );
+        }
+        Err(err) => {
+            let redactor = settings::privacy_redactor(db.pool()).await;
+            let previous_phase = reasoning::get_run(db.pool(), run_id)
+                .await
+                .map(|stored| stored.run.phase)
+                .unwrap_or(RunPhase::Planning);
+            let _ = reasoning::fail_run(db.writer(), run_id).await;
+            let _ = app.emit(
+                "reasoning/phase",
+                ReasoningPhaseEvent {
+                    run_id: run_id.to_string(),
+                    phase: RunPhase::Failed,
+                    previous_phase,
+                    step_index: 0,
+                },
+            );
+            let _ = app.emit(
+                "reasoning/error",
+                ReasoningErrorEvent {
+                    run_id: run_id.to_string(),
+                    code: err.code().to_string(),
+                    message: redactor.error(&err),
+                    retryable: err.retryable(),
+                    retry_after_secs: err.retry_after_secs(),
+                },
+            );
+        }
+    }
+}
+
+/// Adds a finished run's answer to the project's Research Log when the
+/// project keeps one; see [`research_log::append_run`]. The run has already
+/// completed, so a failure here is only logged.
+async fn append_to_research_log(app: &AppHandle, db: &Database, run_id: &str) {
+    match research_log::append_run(db, run_id).await {
+        Ok(Some(event)) => {
+            let change = if event.created {
+                CorpusChange::DocumentAdded
+            } else {
+                CorpusChange::NodesAdded
+            };
+            let project_id = event.project_id.clone();
+            let document_id = event.document_id.clone();
+            let _ = app.emit("research_log/appended", event);
+            emit_corpus_changed(app, db, &[project_id], change, &document_id).await;
+        }
+        Ok(None) => {}
+        Err(err) => eprintln!(
+            "Could not add run {run_id} to the research log: {}",
+            settings::privacy_redactor(db.pool()).await.error(&err)
+        ),
+    }
+}
+
+#[tauri::command]
+pub async fn list_recent_queries(
+    state: State<'_, AppState>,
+    project_id: Option<String>,
+    limit: Option<usize>,
+) -> AppResult<RecentQueriesResponse> {
+    let db = state.db();
+    let queries =
+        activity::recent_queries(db.pool(), project_id.as_deref(), limit.unwrap_or(20)).await?;
+    Ok(RecentQueriesResponse { queries })
+}
+
 #[tauri::command]
 pub async fn get_run(state: State<'_, AppState>, run_id: String) -> AppResult<crate::core::types::GetRunResponse> {
-    reasoning::get_run(state.db.pool(), &run_id).await
+    let db = state.db();
+    reasoning::get_run(db.pool(), &run_id).await
+}
+
+/// Every answer generated for a run, newest first.
+#[tauri::command]
+pub async fn list_answer_revisions(
+    state: State<'_, AppState>,
+    run_id: String,
+) -> AppResult<Vec<AnswerRevision>> {
+    let db = state.db();
+    reasoning::list_answer_revisions(db.pool(), &run_id).await
+}
+
+/// Makes an earlier answer revision the run's answer again.
+#[tauri::command]
+pub async fn restore_answer_revision(
+    state: State<'_, AppState>,
+    run_id: String,
+    revision: i64,
+) -> AppResult<AnswerRecord> {
+    let db = state.db();
+    reasoning::restore_answer_revision(db.writer(), &run_id, revision).await
+}
+
+/// Day-by-day answer quality for a project, to show whether changes to
+/// prompts, chunking or settings are paying off.
+#[tauri::command]
+pub async fn get_quality_trends(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<QualityTrendsResponse> {
+    let db = state.db();
+    projects::get_project(db.pool(), &project_id).await?;
+    reasoning::quality_trends(db.pool(), &project_id).await
+}
+
+/// How often answers cite each node and section of a project or of one
+/// document, so the tree and graph can be colored by use.
+#[tauri::command]
+pub async fn get_citation_heatmap(
+    state: State<'_, AppState>,
+    project_id: Option<String>,
+    document_id: Option<String>,
+) -> AppResult<CitationHeatmap> {
+    let db = state.db();
+    let scope = match (&project_id, &document_id) {
+        (Some(project_id), None) => {
+            projects::get_project(db.pool(), project_id).await?;
+            reasoning::HeatmapScope::Project(project_id)
+        }
+        (None, Some(document_id)) => {
+            documents::get_document(db.pool(), document_id).await?;
+            reasoning::HeatmapScope::Document(document_id)
+        }
+        _ => {
+            return Err(AppError::InvalidInput(
+                "a citation heatmap needs either a project or a document".to_string(),
+            ))
+        }
+    };
+    reasoning::citation_heatmap(db.pool(), scope).await
+}
+
+/// Sections and documents of a project that no run has retrieved or cited,
+/// to prune a corpus that slows retrieval and inflates cost.
+#[tauri::command]
+pub async fn get_unused_content(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<UnusedContentReport> {
+    let db = state.db();
+    projects::get_project(db.pool(), &project_id).await?;
+    reasoning::unused_content(db.pool(), &project_id).await
+}
+
+/// Side-by-side view of two runs: answers, citation overlap, step counts,
+/// costs and how run B's metrics moved relative to run A.
+#[tauri::command]
+pub async fn compare_runs(
+    state: State<'_, AppState>,
+    run_a: String,
+    run_b: String,
+) -> AppResult<RunComparison> {
+    let db = state.db();
+    let a = reasoning::get_run(db.pool(), &run_a).await?;
+    let b = reasoning::get_run(db.pool(), &run_b).await?;
+    Ok(compare::compare_runs(&a, &b))
+}
+
+/// Writes a run's answer as a single HTML file to send to someone without the
+/// app, named after the question unless `dest_path` names the file; see
+/// [`exports::destination`].
+#[tauri::command]
+pub async fn export_answer_html(
+    state: State<'_, AppState>,
+    run_id: String,
+    dest_path: Option<String>,
+    overwrite: Option<bool>,
+) -> AppResult<ExportedFile> {
+    let db = state.db();
+    let stored = reasoning::get_run(db.pool(), &run_id).await?;
+    let answer = stored
+        .answer
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound(format!("answer for run {run_id}")))?;
+
+    let snippets = share::cited_snippets(db.pool(), answer).await?;
+    let html = share::answer_html(&stored.run, answer, &snippets);
+
+    let file_path = exports::destination(
+        dest_path.as_deref(),
+        &state.data_dir.join("exports"),
+        &stored.run.query,
+        "html",
+        overwrite.unwrap_or(false),
+    )?;
+    let replaced = paths::for_io(&file_path).exists();
+    std::fs::write(paths::for_io(&file_path), html).map_err(|err| AppError::Io(err.to_string()))?;
+    state.exports.record(&file_path);
+    Ok(ExportedFile {
+        file_path: paths::to_utf8(&file_path)?,
+        replaced,
+    })
+}
+
+/// Per-phase, per-step and per-provider-call durations of a run, to show
+/// where a slow run spent its time.
+#[tauri::command]
+pub async fn get_run_timeline(
+    state: State<'_, AppState>,
+    run_id: String,
+) -> AppResult<RunTimeline> {
+    let db = state.db();
+    let run = reasoning::get_run(db.pool(), &run_id).await?;
+    let calls = reasoning::list_provider_responses(db.pool(), &run_id).await?;
+    Ok(timeline::build_timeline(&run, &calls))
 }
@@ -1,23 +1,446 @@
+use std::collections::BTreeMap;
+
 use tauri::State;
+use uuid::Uuid;
 
 use crate::{
     core::{
         errors::{AppError, AppResult},
-        types::{Provider, SetProviderKeyResponse},
+        policy,
+        types::{
+            AddProviderKeyResponse, AdminPolicy, ExternalParserConfig, GenerationSettings,
+            GenerationSettingsResponse, KeySelection, LanguageResources, LanguageResourcesResponse,
+            ListHostedModelsResponse, ListProviderKeysResponse, ModelPricing, ModelSelection,
+            PlannerModel, PricingOverridesResponse, PrivacySettings, Provider,
+            ProviderTimeoutSettings, QualityThresholds, RemoveProviderKeyResponse,
+            RetrievalSettings, SetProviderKeyResponse,
+        },
+    },
+    db::repositories::{documents, projects, provider_keys, settings},
+    providers::{
+        generation::validate_settings, openrouter::OpenRouterClient, pricing::validate_overrides,
+        timeouts::validate_settings as validate_timeouts,
     },
+    reasoner::{language, query_class::validate_thresholds, ranking},
     security::keyring,
+    sidecar::docling_client,
     AppState,
 };
 
 #[tauri::command]
 pub async fn set_provider_key(
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
     provider: Provider,
     api_key: String,
 ) -> AppResult<SetProviderKeyResponse> {
+    policy::current().ensure_unlocked(settings::PROVIDER_KEYS_LOCK)?;
+    let db = state.db();
     if api_key.trim().is_empty() {
         return Err(AppError::InvalidInput("api key cannot be empty".to_string()));
     }
-    keyring::set_provider_key(provider, &api_key)?;
+    keyring::set_provider_key(provider.clone(), &api_key)?;
+    provider_keys::upsert_key(
+        db.writer(),
+        keyring::DEFAULT_KEY_ID,
+        &provider,
+        "Default",
+        &provider_keys::key_hint(&api_key),
+        None,
+    )
+    .await?;
     Ok(SetProviderKeyResponse { stored: true })
 }
+
+#[tauri::command]
+pub async fn list_provider_keys(
+    state: State<'_, AppState>,
+    provider: Provider,
+) -> AppResult<ListProviderKeysResponse> {
+    let db = state.db();
+    let keys = provider_keys::list_keys(db.pool(), &provider).await?;
+    let selection = provider_keys::get_key_selection(db.pool()).await?;
+    Ok(ListProviderKeysResponse { keys, selection })
+}
+
+#[tauri::command]
+pub async fn add_provider_key(
+    state: State<'_, AppState>,
+    provider: Provider,
+    label: String,
+    api_key: String,
+    daily_request_quota: Option<i64>,
+) -> AppResult<AddProviderKeyResponse> {
+    policy::current().ensure_unlocked(settings::PROVIDER_KEYS_LOCK)?;
+    let db = state.db();
+    if api_key.trim().is_empty() {
+        return Err(AppError::InvalidInput("api key cannot be empty".to_string()));
+    }
+    if label.trim().is_empty() {
+        return Err(AppError::InvalidInput("key label cannot be empty".to_string()));
+    }
+    if daily_request_quota.is_some_and(|quota| quota <= 0) {
+        return Err(AppError::InvalidInput(
+            "daily request quota must be greater than 0".to_string(),
+        ));
+    }
+    let id = Uuid::new_v4().to_string();
+    keyring::set_key_secret(&provider, &id, api_key.trim())?;
+    let key = provider_keys::upsert_key(
+        db.writer(),
+        &id,
+        &provider,
+        label.trim(),
+        &provider_keys::key_hint(&api_key),
+        daily_request_quota,
+    )
+    .await?;
+    Ok(AddProviderKeyResponse { key })
+}
+
+#[tauri::command]
+pub async fn remove_provider_key(
+    state: State<'_, AppState>,
+    provider: Provider,
+    key_id: String,
+) -> AppResult<RemoveProviderKeyResponse> {
+    policy::current().ensure_unlocked(settings::PROVIDER_KEYS_LOCK)?;
+    let db = state.db();
+    let key = match provider_keys::get_key(db.pool(), &key_id).await {
+        Ok(key) => key,
+        Err(AppError::NotFound(_)) => return Ok(RemoveProviderKeyResponse { removed: false }),
+        Err(err) => return Err(err),
+    };
+    if key.provider != provider {
+        return Err(AppError::InvalidInput(format!(
+            "key {key_id} belongs to {}, not {}",
+            key.provider.as_str(),
+            provider.as_str()
+        )));
+    }
+    keyring::delete_key_secret(&key.provider, &key_id)?;
+    let removed = provider_keys::delete_key(db.writer(), &key_id).await?;
+    Ok(RemoveProviderKeyResponse { removed })
+}
+
+#[tauri::command]
+pub async fn set_key_selection(
+    state: State<'_, AppState>,
+    selection: KeySelection,
+) -> AppResult<KeySelection> {
+    policy::current().ensure_unlocked(settings::KEY_SELECTION_SETTING)?;
+    let db = state.db();
+    provider_keys::set_key_selection(db.writer(), selection).await?;
+    Ok(selection)
+}
+
+#[tauri::command]
+pub async fn get_generation_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> AppResult<GenerationSettingsResponse> {
+    let db = state.db();
+    let settings = projects::get_generation_settings(db.pool(), &project_id).await?;
+    Ok(GenerationSettingsResponse {
+        project_id,
+        settings,
+    })
+}
+
+#[tauri::command]
+pub async fn set_generation_settings(
+    state: State<'_, AppState>,
+    project_id: String,
+    settings: GenerationSettings,
+) -> AppResult<GenerationSettingsResponse> {
+    policy::current().ensure_unlocked(settings::GENERATION_SETTINGS_LOCK)?;
+    let db = state.db();
+    validate_settings(&settings)?;
+    let settings =
+        projects::set_generation_settings(db.writer(), &project_id, &settings).await?;
+    Ok(GenerationSettingsResponse {
+        project_id,
+        settings,
+    })
+}
+
+#[tauri::command]
+pub async fn list_openrouter_models() -> AppResult<ListHostedModelsResponse> {
+    let models = OpenRouterClient::new("")?.list_models().await?;
+    Ok(ListHostedModelsResponse {
+        provider: Provider::OpenRouter,
+        models,
+    })
+}
+
+#[tauri::command]
+pub async fn get_model_selection(state: State<'_, AppState>) -> AppResult<ModelSelection> {
+    let db = state.db();
+    settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING).await
+}
+
+#[tauri::command]
+pub async fn set_model_selection(
+    state: State<'_, AppState>,
+    provider: Provider,
+    model: String,
+    planner_model: Option<String>,
+) -> AppResult<ModelSelection> {
+    policy::current().ensure_unlocked(settings::MODEL_SELECTION_SETTING)?;
+    let db = state.db();
+    let model = model.trim().to_string();
+    if model.is_empty() {
+        return Err(AppError::InvalidInput("model cannot be empty".to_string()));
+    }
+    let planner_model = planner_model
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty() && *value != model);
+    let selection = match provider {
+        Provider::Gemini => ModelSelection {
+            provider,
+            model,
+            context_length: None,
+            pricing: None,
+            planner: planner_model.map(|model| PlannerModel {
+                model,
+                context_length: None,
+                pricing: None,
+            }),
+        },
+        Provider::OpenRouter => {
+            let listed = OpenRouterClient::new(model.clone())?.list_models().await?;
+            let find = |id: &str| {
+                listed
+                    .iter()
+                    .find(|candidate| candidate.id == id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        AppError::InvalidInput(format!("unknown OpenRouter model '{id}'"))
+                    })
+            };
+            let synthesis = find(&model)?;
+            let planner = planner_model
+                .as_deref()
+                .map(find)
+                .transpose()?
+                .map(|planner| PlannerModel {
+                    model: planner.id,
+                    context_length: planner.context_length,
+                    pricing: planner.pricing,
+                });
+            ModelSelection {
+                provider,
+                model,
+                context_length: synthesis.context_length,
+                pricing: synthesis.pricing,
+                planner,
+            }
+        }
+    };
+    settings::set_setting(
+        db.writer(),
+        settings::MODEL_SELECTION_SETTING,
+        &selection,
+    )
+    .await?;
+    Ok(selection)
+}
+
+#[tauri::command]
+pub async fn get_pricing_overrides(
+    state: State<'_, AppState>,
+) -> AppResult<PricingOverridesResponse> {
+    let db = state.db();
+    let overrides =
+        settings::get_setting(db.pool(), settings::PRICING_OVERRIDES_SETTING).await?;
+    Ok(PricingOverridesResponse { overrides })
+}
+
+/// Replaces the user's per-model rates; an empty map reverts to the bundled table.
+#[tauri::command]
+pub async fn set_pricing_overrides(
+    state: State<'_, AppState>,
+    overrides: BTreeMap<String, ModelPricing>,
+) -> AppResult<PricingOverridesResponse> {
+    policy::current().ensure_unlocked(settings::PRICING_OVERRIDES_SETTING)?;
+    let db = state.db();
+    validate_overrides(&overrides)?;
+    settings::set_setting(
+        db.writer(),
+        settings::PRICING_OVERRIDES_SETTING,
+        &overrides,
+    )
+    .await?;
+    Ok(PricingOverridesResponse { overrides })
+}
+
+#[tauri::command]
+pub async fn get_external_parser(state: State<'_, AppState>) -> AppResult<ExternalParserConfig> {
+    let db = state.db();
+    settings::get_setting(db.pool(), settings::EXTERNAL_PARSER_SETTING).await
+}
+
+/// Saves the external parser executable and the MIME types routed to it.
+#[tauri::command]
+pub async fn set_external_parser(
+    state: State<'_, AppState>,
+    config: ExternalParserConfig,
+) -> AppResult<ExternalParserConfig> {
+    policy::current().ensure_unlocked(settings::EXTERNAL_PARSER_SETTING)?;
+    let db = state.db();
+    let config = docling_client::validate_config(config)?;
+    settings::set_setting(db.writer(), settings::EXTERNAL_PARSER_SETTING, &config).await?;
+    Ok(config)
+}
+
+#[tauri::command]
+pub async fn get_language_resources(
+    state: State<'_, AppState>,
+) -> AppResult<LanguageResourcesResponse> {
+    let db = state.db();
+    let resources: LanguageResources =
+        settings::get_setting(db.pool(), settings::LANGUAGE_RESOURCES_SETTING).await?;
+    Ok(language::response(resources))
+}
+
+/// Saves the word lists scope detection and answer scoring read queries with.
+#[tauri::command]
+pub async fn set_language_resources(
+    state: State<'_, AppState>,
+    resources: LanguageResources,
+) -> AppResult<LanguageResourcesResponse> {
+    policy::current().ensure_unlocked(settings::LANGUAGE_RESOURCES_SETTING)?;
+    let db = state.db();
+    let resources = language::validate_resources(resources)?;
+    settings::set_setting(
+        db.writer(),
+        settings::LANGUAGE_RESOURCES_SETTING,
+        &resources,
+    )
+    .await?;
+    Ok(language::response(resources))
+}
+
+/// Replaces the word lists with the bundled defaults for `language`.
+#[tauri::command]
+pub async fn reset_language_resources(
+    state: State<'_, AppState>,
+    language: String,
+) -> AppResult<LanguageResourcesResponse> {
+    policy::current().ensure_unlocked(settings::LANGUAGE_RESOURCES_SETTING)?;
+    let db = state.db();
+    let resources = language::defaults(&language).ok_or_else(|| {
+        AppError::InvalidInput(format!("no bundled word lists for language '{language}'"))
+    })?;
+    settings::set_setting(
+        db.writer(),
+        settings::LANGUAGE_RESOURCES_SETTING,
+        &resources,
+    )
+    .await?;
+    Ok(language::response(resources))
+}
+
+#[tauri::command]
+pub async fn get_quality_thresholds(state: State<'_, AppState>) -> AppResult<QualityThresholds> {
+    let db = state.db();
+    settings::get_setting(db.pool(), settings::QUALITY_THRESHOLDS_SETTING).await
+}
+
+/// Saves the quality gate of each query class; weights are scaled to sum to 1.
+#[tauri::command]
+pub async fn set_quality_thresholds(
+    state: State<'_, AppState>,
+    thresholds: QualityThresholds,
+) -> AppResult<QualityThresholds> {
+    policy::current().ensure_unlocked(settings::QUALITY_THRESHOLDS_SETTING)?;
+    let db = state.db();
+    let thresholds = validate_thresholds(thresholds)?;
+    settings::set_setting(
+        db.writer(),
+        settings::QUALITY_THRESHOLDS_SETTING,
+        &thresholds,
+    )
+    .await?;
+    Ok(thresholds)
+}
+
+#[tauri::command]
+pub async fn get_privacy_settings(state: State<'_, AppState>) -> AppResult<PrivacySettings> {
+    let db = state.db();
+    settings::get_setting(db.pool(), settings::PRIVACY_SETTING).await
+}
+
+/// Turns redaction of document text in logs, diagnostics and error events on or off.
+#[tauri::command]
+pub async fn set_privacy_settings(
+    state: State<'_, AppState>,
+    privacy: PrivacySettings,
+) -> AppResult<PrivacySettings> {
+    policy::current().ensure_unlocked(settings::PRIVACY_SETTING)?;
+    let db = state.db();
+    settings::set_setting(db.writer(), settings::PRIVACY_SETTING, &privacy).await?;
+    Ok(privacy)
+}
+
+#[tauri::command]
+pub async fn get_retrieval_settings(state: State<'_, AppState>) -> AppResult<RetrievalSettings> {
+    let db = state.db();
+    settings::get_setting(db.pool(), settings::RETRIEVAL_SETTING).await
+}
+
+/// Saves how forgiving the keyword search is of typos, for searches and runs
+/// that do not set it themselves, and how runs rank what it finds.
+#[tauri::command]
+pub async fn set_retrieval_settings(
+    state: State<'_, AppState>,
+    retrieval: RetrievalSettings,
+) -> AppResult<RetrievalSettings> {
+    policy::current().ensure_unlocked(settings::RETRIEVAL_SETTING)?;
+    let db = state.db();
+    if retrieval.fuzziness > documents::MAX_FUZZINESS {
+        return Err(AppError::InvalidInput(format!(
+            "fuzziness must be at most {}",
+            documents::MAX_FUZZINESS
+        )));
+    }
+    let retrieval = RetrievalSettings {
+        ranking: ranking::validate(retrieval.ranking)?,
+        ..retrieval
+    };
+    settings::set_setting(db.writer(), settings::RETRIEVAL_SETTING, &retrieval).await?;
+    Ok(retrieval)
+}
+
+#[tauri::command]
+pub async fn get_provider_timeouts(
+    state: State<'_, AppState>,
+) -> AppResult<ProviderTimeoutSettings> {
+    let db = state.db();
+    settings::get_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING).await
+}
+
+/// Saves the connect, per-call and keep-alive timeouts of each provider. They
+/// apply from the next run or model call.
+#[tauri::command]
+pub async fn set_provider_timeouts(
+    state: State<'_, AppState>,
+    timeouts: ProviderTimeoutSettings,
+) -> AppResult<ProviderTimeoutSettings> {
+    policy::current().ensure_unlocked(settings::PROVIDER_TIMEOUTS_SETTING)?;
+    let db = state.db();
+    validate_timeouts(&timeouts)?;
+    settings::set_setting(
+        db.writer(),
+        settings::PROVIDER_TIMEOUTS_SETTING,
+        &timeouts,
+    )
+    .await?;
+    Ok(timeouts)
+}
+
+/// The administrator's policy from the config file: what is locked, preset
+/// or turned off, for settings screens to show.
+#[tauri::command]
+pub async fn get_admin_policy() -> AppResult<AdminPolicy> {
+    Ok(policy::current().clone())
+}
@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        policy,
+        types::{ListWorkspacesResponse, WorkspaceSummary},
+    },
+    db::{repositories::settings, storage, workspaces, Database, MigrationPolicy},
+    AppState,
+};
+
+#[tauri::command]
+pub async fn list_workspaces(state: State<'_, AppState>) -> AppResult<ListWorkspacesResponse> {
+    let db = state.db();
+    let workspaces = state.workspaces.list(db.path())?;
+    Ok(ListWorkspacesResponse { workspaces })
+}
+
+#[tauri::command]
+pub async fn open_workspace(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    name: Option<String>,
+) -> AppResult<WorkspaceSummary> {
+    let path = workspaces::resolve_workspace_path(&path)?;
+    let db = workspaces::open_existing(&path).await?;
+    switch_workspace(&app, &state, db, &path, name.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn create_workspace(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    name: Option<String>,
+) -> AppResult<WorkspaceSummary> {
+    let path = workspaces::resolve_workspace_path(&path)?;
+    let db = workspaces::create_new(&path).await?;
+    switch_workspace(&app, &state, db, &path, name.as_deref()).await
+}
+
+/// Moves the open workspace to `dest_path`, e.g. out of a synced folder. The
+/// copy is taken once running work has drained, so no write is lost; the old
+/// file is left where it was.
+#[tauri::command]
+pub async fn relocate_workspace(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    dest_path: String,
+) -> AppResult<WorkspaceSummary> {
+    let dest = workspaces::resolve_workspace_path(&dest_path)?;
+    if dest.exists() {
+        return Err(AppError::InvalidInput(format!(
+            "{} already exists",
+            dest.to_string_lossy()
+        )));
+    }
+    if let Some(provider) = storage::sync_provider(&dest) {
+        return Err(AppError::InvalidInput(format!(
+            "{} is inside a {provider} folder; choose a local folder",
+            dest.to_string_lossy()
+        )));
+    }
+    let name = {
+        let _drain = state.scheduler.drain().await;
+        let current = state.db();
+        if current.is_read_only() {
+            return Err(AppError::InvalidInput(
+                "a read-only database cannot be relocated".to_string(),
+            ));
+        }
+        storage::copy_to(&current, &dest).await?;
+        let db = Database::open_file(&dest, MigrationPolicy::Apply).await?;
+        state.database.replace(db);
+        state
+            .workspaces
+            .list(current.path())?
+            .into_iter()
+            .find(|workspace| workspace.active)
+            .map(|workspace| workspace.name)
+    };
+    announce_workspace(&app, &state, &dest, name.as_deref())
+}
+
+/// Makes `db` the app's database once running queries and ingestion writes
+/// have finished, then tells the frontend to reload.
+pub(crate) async fn switch_workspace(
+    app: &AppHandle,
+    state: &AppState,
+    db: Database,
+    path: &Path,
+    name: Option<&str>,
+) -> AppResult<WorkspaceSummary> {
+    if let Err(err) = settings::apply_policy(db.writer(), policy::current()).await {
+        eprintln!("could not apply the administrator policy: {err}");
+    }
+    {
+        let _drain = state.scheduler.drain().await;
+        state.database.replace(db);
+    }
+    announce_workspace(app, state, path, name)
+}
+
+fn announce_workspace(
+    app: &AppHandle,
+    state: &AppState,
+    path: &Path,
+    name: Option<&str>,
+) -> AppResult<WorkspaceSummary> {
+    state.workspaces.remember(path, name)?;
+    let summary = state
+        .workspaces
+        .list(Some(path))?
+        .into_iter()
+        .find(|workspace| workspace.active)
+        .ok_or_else(|| AppError::Internal("workspace missing from registry".to_string()))?;
+    let _ = app.emit("workspace/changed", &summary);
+    Ok(summary)
+}
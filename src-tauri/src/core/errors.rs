@@ -9,22 +9,48 @@ pub enum AppError {
     NotFound(String),
     #[error("database error: {0}")]
     Database(String),
+    #[error("database busy: {0}")]
+    DatabaseBusy(String),
+    #[error(
+        "database schema v{database_version} is newer than this app supports (v{app_version}); \
+         install the latest Vectorless, or export the data with export_portable_database and \
+         start from a fresh database"
+    )]
+    IncompatibleSchema {
+        database_version: i64,
+        app_version: i64,
+    },
     #[error("io error: {0}")]
     Io(String),
+    #[error("file too large: {size_bytes} bytes exceeds the {limit_bytes} byte limit")]
+    FileTooLarge { size_bytes: u64, limit_bytes: u64 },
     #[error("sidecar error: {0}")]
     Sidecar(String),
     #[error("provider auth failed")]
     ProviderAuth,
     #[error("provider rate limited")]
     ProviderRateLimited,
+    /// The account or key has used up a quota, e.g. Gemini's requests per
+    /// day, as opposed to a momentary rate limit.
+    #[error("provider quota exceeded: {message}")]
+    ProviderQuotaExceeded {
+        message: String,
+        /// When the provider said the quota resets, if it did.
+        retry_after_secs: Option<u64>,
+    },
     #[error("provider timeout")]
     ProviderTimeout,
+    #[error("provider blocked content: {0}")]
+    ProviderContentBlocked(String),
     #[error("provider invalid response: {0}")]
     ProviderInvalidResponse(String),
     #[error("network error: {0}")]
     Network(String),
     #[error("quality gate failed: {0}")]
     QualityGateFailed(String),
+    /// Refused by the administrator's policy, e.g. a locked setting.
+    #[error("disabled by your administrator: {0}")]
+    PolicyRestricted(String),
     #[error("internal error: {0}")]
     Internal(String),
 }
@@ -34,9 +60,10 @@ impl serde::Serialize for AppError {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("AppError", 2)?;
+        let mut state = serializer.serialize_struct("AppError", 3)?;
         state.serialize_field("code", self.code())?;
         state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("retryAfterSecs", &self.retry_after_secs())?;
         state.end()
     }
 }
@@ -47,24 +74,49 @@ impl AppError {
             Self::InvalidInput(_) => "INVALID_INPUT",
             Self::NotFound(_) => "NOT_FOUND",
             Self::Database(_) => "DATABASE_ERROR",
+            Self::DatabaseBusy(_) => "DATABASE_BUSY",
+            Self::IncompatibleSchema { .. } => "INCOMPATIBLE_SCHEMA",
             Self::Io(_) => "IO_ERROR",
+            Self::FileTooLarge { .. } => "FILE_TOO_LARGE",
             Self::Sidecar(_) => "SIDECAR_ERROR",
             Self::ProviderAuth => "PROVIDER_AUTH",
             Self::ProviderRateLimited => "PROVIDER_RATE_LIMITED",
+            Self::ProviderQuotaExceeded { .. } => "PROVIDER_QUOTA_EXCEEDED",
             Self::ProviderTimeout => "PROVIDER_TIMEOUT",
+            Self::ProviderContentBlocked(_) => "PROVIDER_CONTENT_BLOCKED",
             Self::ProviderInvalidResponse(_) => "PROVIDER_INVALID_RESPONSE",
             Self::Network(_) => "NETWORK_ERROR",
             Self::QualityGateFailed(_) => "QUALITY_GATE_FAILED",
+            Self::PolicyRestricted(_) => "POLICY_RESTRICTED",
             Self::Internal(_) => "INTERNAL_ERROR",
         }
     }
 
+    /// Whether trying again may succeed. A spent quota only counts when the
+    /// provider said when it resets.
     pub fn retryable(&self) -> bool {
         matches!(
             self,
-            Self::ProviderRateLimited | Self::ProviderTimeout | Self::Network(_)
+            Self::ProviderRateLimited
+                | Self::ProviderTimeout
+                | Self::Network(_)
+                | Self::DatabaseBusy(_)
+                | Self::ProviderQuotaExceeded {
+                    retry_after_secs: Some(_),
+                    ..
+                }
         )
     }
+
+    /// How long to wait before trying again, when the provider said.
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            Self::ProviderQuotaExceeded {
+                retry_after_secs, ..
+            } => *retry_after_secs,
+            _ => None,
+        }
+    }
 }
 
 impl From<std::io::Error> for AppError {
@@ -75,10 +127,26 @@ impl From<std::io::Error> for AppError {
 
 impl From<sqlx::Error> for AppError {
     fn from(value: sqlx::Error) -> Self {
+        if is_busy(&value) {
+            return Self::DatabaseBusy(value.to_string());
+        }
         Self::Database(value.to_string())
     }
 }
 
+/// True for SQLite lock contention (`SQLITE_BUSY`, `SQLITE_LOCKED` and their
+/// extended codes) and for a pool that had no free connection in time.
+fn is_busy(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db) => db
+            .code()
+            .and_then(|code| code.parse::<i32>().ok())
+            .is_some_and(|code| matches!(code & 0xff, 5 | 6)),
+        _ => false,
+    }
+}
+
 impl From<sqlx::migrate::MigrateError> for AppError {
     fn from(value: sqlx::migrate::MigrateError) -> Self {
         Self::Database(value.to_string())
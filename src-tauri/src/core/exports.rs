@@ -0,0 +1,141 @@
+//! Where exports go: the file an export is written to, and the exports the
+//! app has written, so a finished export can be shown in the system file
+//! manager. Only those files and the app's own exports folder are revealed;
+//! the frontend cannot use that to open arbitrary locations.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+};
+
+use crate::core::{
+    errors::{AppError, AppResult},
+    paths,
+};
+
+#[derive(Debug, Default)]
+struct Revealable {
+    /// The app's exports folder; anything inside it may be shown.
+    dir: Option<PathBuf>,
+    /// Files written by an export, wherever the user put them.
+    files: HashSet<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExportRegistry(Arc<Mutex<Revealable>>);
+
+impl ExportRegistry {
+    /// A registry that allows `default_dir`, the app's own exports folder.
+    pub fn new(default_dir: &Path) -> Self {
+        Self(Arc::new(Mutex::new(Revealable {
+            dir: Some(default_dir.to_path_buf()),
+            files: HashSet::new(),
+        })))
+    }
+
+    /// Allows `file` itself, once an export has been written to it. The
+    /// folder holding it stays off limits.
+    pub fn record(&self, file: &Path) {
+        let file = paths::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .files
+            .insert(file);
+    }
+
+    /// `raw` resolved, when it names a recorded export or something inside
+    /// the exports folder.
+    pub fn check(&self, raw: &str) -> AppResult<PathBuf> {
+        let path = paths::canonicalize(&paths::from_input(raw)?)?;
+        let revealable = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        // The exports folder may not have existed when the registry was made.
+        let in_dir = revealable.dir.as_ref().is_some_and(|dir| {
+            let dir = paths::canonicalize(dir).unwrap_or_else(|_| dir.clone());
+            path.starts_with(&dir)
+        });
+        if !in_dir && !revealable.files.contains(&path) {
+            return Err(AppError::InvalidInput(format!(
+                "{} is not an export",
+                paths::display(&path)
+            )));
+        }
+        Ok(path)
+    }
+}
+
+/// The file an export goes to. `dest` is the file to write, as picked in a
+/// save dialog, or a folder to write a file named after `title` into;
+/// without one, `default_dir` is used. When the file already exists the
+/// export gets a numbered name beside it instead, unless `overwrite` is set.
+pub fn destination(
+    dest: Option<&str>,
+    default_dir: &Path,
+    title: &str,
+    extension: &str,
+    overwrite: bool,
+) -> AppResult<PathBuf> {
+    let dest = match dest.filter(|path| !path.trim().is_empty()) {
+        Some(path) => Some(paths::from_input(path)?),
+        None => None,
+    };
+    match dest {
+        Some(file) if !paths::for_io(&file).is_dir() => {
+            if overwrite || !paths::for_io(&file).exists() {
+                return Ok(file);
+            }
+            let dir = file.parent().unwrap_or(Path::new(""));
+            let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+            let extension = file
+                .extension()
+                .map_or(extension.into(), |ext| ext.to_string_lossy());
+            Ok(paths::available_path(dir, &stem, &extension))
+        }
+        dir => {
+            let dir = dir.unwrap_or_else(|| default_dir.to_path_buf());
+            std::fs::create_dir_all(paths::for_io(&dir))
+                .map_err(|err| AppError::Io(err.to_string()))?;
+            let stem = paths::sanitize_file_name(title);
+            Ok(if overwrite {
+                dir.join(format!("{stem}.{extension}"))
+            } else {
+                paths::available_path(&dir, &stem, extension)
+            })
+        }
+    }
+}
+
+/// Opens the system file manager at `path`, selecting it where the platform
+/// supports that.
+pub fn reveal(path: &Path) -> AppResult<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("explorer");
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        command.arg(select);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else {
+        // xdg-open has no way to select a file; open the folder holding it.
+        let folder = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let mut command = Command::new("xdg-open");
+        command.arg(folder);
+        command
+    };
+    let mut child = command
+        .spawn()
+        .map_err(|err| AppError::Io(format!("could not open the file manager: {err}")))?;
+    // Reap the launcher once it exits; its status says nothing useful
+    // (explorer reports failure even when it opened).
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! The local app metrics and their Prometheus text rendering. Values are
+//! totalled in the `metrics` table by
+//! [`repositories::metrics`](crate::db::repositories::metrics) while the
+//! metrics setting is on; nothing is sent anywhere.
+
+use std::fmt::Write;
+
+use crate::core::types::MetricSample;
+
+/// Prefix of every metric name in the Prometheus rendering.
+pub const PROMETHEUS_PREFIX: &str = "vectorless";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// A document ingested, with how long it took.
+    Ingest,
+    IngestFailure,
+    /// A reasoning run completed, with how long it took.
+    Run,
+    RunFailure,
+    /// A provider call answered, by model, with its latency. Cached
+    /// responses are not counted.
+    ProviderCall,
+    ProviderFailure,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 6] = [
+        Metric::Ingest,
+        Metric::IngestFailure,
+        Metric::Run,
+        Metric::RunFailure,
+        Metric::ProviderCall,
+        Metric::ProviderFailure,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::Ingest => "ingests",
+            Metric::IngestFailure => "ingest_failures",
+            Metric::Run => "runs",
+            Metric::RunFailure => "run_failures",
+            Metric::ProviderCall => "provider_calls",
+            Metric::ProviderFailure => "provider_failures",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|metric| metric.as_str() == name)
+    }
+
+    /// Whether the metric records durations as well as a count.
+    pub fn timed(self) -> bool {
+        matches!(self, Metric::Ingest | Metric::Run | Metric::ProviderCall)
+    }
+
+    /// The Prometheus label a sample's `label` is rendered under.
+    fn label_key(self) -> &'static str {
+        match self {
+            Metric::ProviderCall | Metric::ProviderFailure => "model",
+            _ => "label",
+        }
+    }
+
+    fn help(self) -> &'static str {
+        match self {
+            Metric::Ingest => "Documents ingested",
+            Metric::IngestFailure => "Ingests that failed",
+            Metric::Run => "Reasoning runs completed",
+            Metric::RunFailure => "Reasoning runs that failed",
+            Metric::ProviderCall => "Provider calls answered",
+            Metric::ProviderFailure => "Provider calls that failed",
+        }
+    }
+}
+
+/// `samples` in the Prometheus text exposition format: a `_total` counter
+/// per metric and, for timed ones, the seconds spent in all and the longest
+/// single duration. Samples of unknown metrics are left out.
+pub fn prometheus_text(samples: &[MetricSample]) -> String {
+    let mut out = String::new();
+    for metric in Metric::ALL {
+        let family = samples
+            .iter()
+            .filter(|sample| sample.name == metric.as_str())
+            .collect::<Vec<_>>();
+        if family.is_empty() {
+            continue;
+        }
+        let base = format!("{PROMETHEUS_PREFIX}_{}", metric.as_str());
+        let _ = writeln!(out, "# HELP {base}_total {}.", metric.help());
+        let _ = writeln!(out, "# TYPE {base}_total counter");
+        for sample in &family {
+            let labels = labels(metric, &sample.label);
+            let _ = writeln!(out, "{base}_total{labels} {}", sample.count);
+        }
+        if !metric.timed() {
+            continue;
+        }
+        let _ = writeln!(out, "# HELP {base}_seconds_total Time spent, in seconds.");
+        let _ = writeln!(out, "# TYPE {base}_seconds_total counter");
+        for sample in &family {
+            let labels = labels(metric, &sample.label);
+            let _ = writeln!(
+                out,
+                "{base}_seconds_total{labels} {}",
+                seconds(sample.total_ms)
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP {base}_seconds_max Longest single duration, in seconds."
+        );
+        let _ = writeln!(out, "# TYPE {base}_seconds_max gauge");
+        for sample in &family {
+            let labels = labels(metric, &sample.label);
+            let _ = writeln!(out, "{base}_seconds_max{labels} {}", seconds(sample.max_ms));
+        }
+    }
+    out
+}
+
+fn labels(metric: Metric, label: &str) -> String {
+    if label.is_empty() {
+        return String::new();
+    }
+    let escaped = label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("{{{}=\"{escaped}\"}}", metric.label_key())
+}
+
+fn seconds(ms: i64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
@@ -1,2 +1,9 @@
 pub mod errors;
+pub mod exports;
+pub mod metrics;
+pub mod paths;
+pub mod policy;
+pub mod privacy;
+pub mod progress;
+pub mod scheduler;
 pub mod types;
@@ -0,0 +1,228 @@
+//! Paths shared by ingestion and export. File paths cross the frontend
+//! boundary as strings, so they are converted here once rather than with ad
+//! hoc `to_string_lossy` calls, which quietly turn a name that is not valid
+//! Unicode into one that no longer opens. On Windows, canonical paths drop
+//! the `\\?\` verbatim prefix where that is safe, as the `dunce` crate does,
+//! and get it back for file system calls on paths past the legacy
+//! `MAX_PATH` limit.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use crate::core::errors::{AppError, AppResult};
+
+/// Longest path, in UTF-16 units, Windows APIs accept without `\\?\`.
+pub const MAX_PATH: usize = 260;
+
+const VERBATIM: &str = r"\\?\";
+const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+/// Longest file name, before the extension, [`sanitize_file_name`] returns.
+pub const MAX_FILE_NAME_CHARS: usize = 120;
+
+/// Characters Windows does not allow in a file name.
+const FORBIDDEN_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves in every directory, with or without an
+/// extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A path received from the frontend: surrounding whitespace and the quotes
+/// a copied Windows path often carries are dropped.
+pub fn from_input(raw: &str) -> AppResult<PathBuf> {
+    let trimmed = raw.trim();
+    let unquoted = trimmed
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or(trimmed);
+    if unquoted.is_empty() {
+        return Err(AppError::InvalidInput(
+            "file path cannot be empty".to_string(),
+        ));
+    }
+    Ok(PathBuf::from(unquoted))
+}
+
+/// The absolute path of an existing file or folder, symlinks resolved and
+/// without a verbatim prefix it does not need.
+pub fn canonicalize(path: &Path) -> AppResult<PathBuf> {
+    match std::fs::canonicalize(for_io(path)) {
+        Ok(canonical) => Ok(simplify(&canonical)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Err(AppError::NotFound(format!("file {}", display(path))))
+        }
+        Err(err) => Err(AppError::Io(format!("{}: {err}", display(path)))),
+    }
+}
+
+/// An existing file or folder named by the frontend, in the form file
+/// system calls should get.
+pub fn resolve_input(raw: &str) -> AppResult<PathBuf> {
+    Ok(for_io(&canonicalize(&from_input(raw)?)?))
+}
+
+/// `path` in the form file system calls should get: on Windows, long
+/// absolute paths gain the verbatim prefix; elsewhere it is unchanged.
+pub fn for_io(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(extended) = path.to_str().and_then(extend_str) {
+            return PathBuf::from(extended);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// `path` without its verbatim prefix on Windows when the plain form means
+/// the same thing; elsewhere it is unchanged.
+pub fn simplify(path: &Path) -> PathBuf {
+    if cfg!(windows) {
+        if let Some(plain) = path.to_str().and_then(simplify_str) {
+            return PathBuf::from(plain);
+        }
+    }
+    path.to_path_buf()
+}
+
+/// `path` as a string to store or return. A path that is not valid Unicode
+/// is refused rather than replaced by one that points elsewhere.
+pub fn to_utf8(path: &Path) -> AppResult<String> {
+    path.to_str().map(str::to_string).ok_or_else(|| {
+        AppError::InvalidInput(format!(
+            "path {} is not valid Unicode; rename it to ingest it",
+            display(path)
+        ))
+    })
+}
+
+/// Whether the file at `path` was modified after `since`. A file that is
+/// gone, or whose modification time cannot be read, counts as unchanged.
+pub fn modified_since(path: &str, since: DateTime<Utc>) -> bool {
+    std::fs::metadata(for_io(Path::new(path)))
+        .and_then(|meta| meta.modified())
+        .is_ok_and(|modified| DateTime::<Utc>::from(modified) > since)
+}
+
+/// `path` for messages only; invalid sequences show as U+FFFD.
+pub fn display(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// A file name for `title` that every platform accepts: characters Windows
+/// forbids become `_`, whitespace runs become one space, trailing dots and
+/// spaces go, reserved device names get a `_` suffix, and the name is cut
+/// to [`MAX_FILE_NAME_CHARS`]. An extension left over from the source file
+/// (`Report.pdf`) is dropped.
+pub fn sanitize_file_name(title: &str) -> String {
+    let title = title.trim();
+    let title = match title.rsplit_once('.') {
+        Some((stem, extension))
+            if !stem.trim().is_empty()
+                && (1..=5).contains(&extension.len())
+                && extension.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            stem
+        }
+        _ => title,
+    };
+    let name = title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .map(|c| {
+            if c.is_control() || FORBIDDEN_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(MAX_FILE_NAME_CHARS)
+        .collect::<String>();
+    let name = name.trim_end_matches(['.', ' ']).to_string();
+    if name.is_empty() {
+        return "document".to_string();
+    }
+    if plain_component(&name) {
+        name
+    } else {
+        format!("{name}_")
+    }
+}
+
+/// `dir/stem.extension`, or with ` (2)`, ` (3)`, … after the stem when that
+/// file already exists.
+pub fn available_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let first = dir.join(format!("{stem}.{extension}"));
+    if !for_io(&first).exists() {
+        return first;
+    }
+    (2..)
+        .map(|n| dir.join(format!("{stem} ({n}).{extension}")))
+        .find(|path| !for_io(path).exists())
+        .expect("some numbered name is free")
+}
+
+/// The plain form of a verbatim Windows path (`\\?\C:\…` or
+/// `\\?\UNC\server\share\…`), or `None` when it is not one or needs the
+/// prefix: too long, or naming something the plain form cannot reach, such
+/// as a reserved device name or a component ending in a dot or space.
+pub fn simplify_str(path: &str) -> Option<String> {
+    let plain = if let Some(rest) = path.strip_prefix(VERBATIM_UNC) {
+        format!(r"\\{rest}")
+    } else {
+        let rest = path.strip_prefix(VERBATIM)?;
+        if !starts_with_drive(rest) {
+            return None;
+        }
+        rest.to_string()
+    };
+    let reachable = plain.encode_utf16().count() < MAX_PATH
+        && !plain.contains('/')
+        && plain
+            .trim_start_matches('\\')
+            .split('\\')
+            .skip(1)
+            .all(plain_component);
+    reachable.then_some(plain)
+}
+
+/// `path` with the verbatim prefix when it is an absolute Windows path of
+/// at least [`MAX_PATH`] units; `None` when it stays as it is.
+pub fn extend_str(path: &str) -> Option<String> {
+    if path.starts_with(VERBATIM)
+        || path.starts_with(r"\\.\")
+        || path.encode_utf16().count() < MAX_PATH
+    {
+        return None;
+    }
+    // Verbatim paths are passed through untouched, so separators must
+    // already be backslashes.
+    let path = path.replace('/', "\\");
+    if let Some(share) = path.strip_prefix(r"\\") {
+        return Some(format!("{VERBATIM_UNC}{share}"));
+    }
+    starts_with_drive(&path).then(|| format!("{VERBATIM}{path}"))
+}
+
+/// Whether `path` starts with a drive root such as `C:\`.
+fn starts_with_drive(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\'
+}
+
+fn plain_component(component: &str) -> bool {
+    if component.is_empty() {
+        return true;
+    }
+    if component.ends_with('.') || component.ends_with(' ') {
+        return false;
+    }
+    let stem = component.split('.').next().unwrap_or(component);
+    !RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+}
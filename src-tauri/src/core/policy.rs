@@ -0,0 +1,185 @@
+//! Administrator policy for managed deployments, read from
+//! `vectorless.config.toml`: provider endpoints, offline mode, preset and
+//! locked settings, and features turned off. A system-wide file wins over one
+//! in the data folder; without either nothing is restricted. Besides the
+//! `app_settings` keys, `locked` accepts `provider_keys` and `generation`,
+//! which freeze the stored API keys and every project's generation settings
+//! but cannot be preset. The policy is read once at startup and installed for
+//! the process, since provider clients check it where no app state reaches.
+//!
+//! ```toml
+//! offline = false
+//! disabled_features = ["import", "shared_bundles"]
+//! locked = ["privacy", "provider_timeouts"]
+//!
+//! [endpoints]
+//! gemini = "https://llm-gateway.example.com/gemini/v1beta"
+//!
+//! [settings.privacy]
+//! redactText = true
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        paths,
+        types::{AdminPolicy, PolicyFeature, Provider, ProviderEndpoints},
+    },
+    db::repositories::settings::EXTERNAL_PARSER_SETTING,
+};
+
+pub const CONFIG_FILE_NAME: &str = "vectorless.config.toml";
+
+static POLICY: OnceLock<AdminPolicy> = OnceLock::new();
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    offline: bool,
+    endpoints: ProviderEndpoints,
+    disabled_features: Vec<PolicyFeature>,
+    locked: Vec<String>,
+    settings: BTreeMap<String, Value>,
+}
+
+/// Where IT installs the config file for every user of the machine.
+pub fn system_config_path() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        PathBuf::from(std::env::var_os("ProgramData")?).join("Vectorless")
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/Vectorless")
+    } else {
+        PathBuf::from("/etc/vectorless")
+    };
+    Some(dir.join(CONFIG_FILE_NAME))
+}
+
+/// Reads the system config file or, without one, the one in `data_dir`.
+pub fn load(data_dir: &Path) -> AppResult<AdminPolicy> {
+    let candidates = system_config_path()
+        .into_iter()
+        .chain([data_dir.join(CONFIG_FILE_NAME)]);
+    for path in candidates {
+        match std::fs::read_to_string(paths::for_io(&path)) {
+            Ok(text) => return parse(&text, &path),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(AdminPolicy::default())
+}
+
+/// Parses the config file at `path`. Unknown keys are refused rather than
+/// ignored, so a misspelt restriction does not go unenforced.
+pub fn parse(text: &str, path: &Path) -> AppResult<AdminPolicy> {
+    let config: ConfigFile = toml::from_str(text)
+        .map_err(|err| AppError::InvalidInput(format!("{}: {err}", paths::display(path))))?;
+    let endpoints = ProviderEndpoints {
+        gemini: endpoint(config.endpoints.gemini, "gemini")?,
+        open_router: endpoint(config.endpoints.open_router, "openrouter")?,
+    };
+    let mut disabled_features = config.disabled_features;
+    disabled_features.sort();
+    disabled_features.dedup();
+    let mut presets = config.settings;
+    let mut locked_settings = config
+        .locked
+        .iter()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .collect::<Vec<_>>();
+    // With the external parser off, its setting stays at the default, which
+    // routes nothing to a parser.
+    if disabled_features.contains(&PolicyFeature::ExternalParser) {
+        presets.remove(EXTERNAL_PARSER_SETTING);
+        locked_settings.push(EXTERNAL_PARSER_SETTING.to_string());
+    }
+    locked_settings.sort();
+    locked_settings.dedup();
+    Ok(AdminPolicy {
+        source: Some(paths::display(path)),
+        offline: config.offline,
+        endpoints,
+        disabled_features,
+        locked_settings,
+        presets,
+    })
+}
+
+fn endpoint(url: Option<String>, provider: &str) -> AppResult<Option<String>> {
+    let Some(url) = url else {
+        return Ok(None);
+    };
+    let url = url.trim().trim_end_matches('/');
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(AppError::InvalidInput(format!(
+            "the {provider} endpoint must be an http or https URL"
+        )));
+    }
+    Ok(Some(url.to_string()))
+}
+
+/// Makes `policy` the one [`current`] returns. Only the first call counts, so
+/// it must come before anything reads the policy.
+pub fn install(policy: AdminPolicy) {
+    let _ = POLICY.set(policy);
+}
+
+/// The installed policy; an unrestricted one when none was installed.
+pub fn current() -> &'static AdminPolicy {
+    POLICY.get_or_init(AdminPolicy::default)
+}
+
+impl AdminPolicy {
+    pub fn locks(&self, key: &str) -> bool {
+        self.locked_settings.iter().any(|locked| locked == key)
+    }
+
+    pub fn ensure_unlocked(&self, key: &str) -> AppResult<()> {
+        if self.locks(key) {
+            return Err(AppError::PolicyRestricted(format!(
+                "the {key} setting is locked"
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn ensure_enabled(&self, feature: PolicyFeature) -> AppResult<()> {
+        if self.disabled_features.contains(&feature) {
+            return Err(AppError::PolicyRestricted(format!(
+                "{} is turned off",
+                feature.as_str()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuses model calls in offline mode.
+    pub fn ensure_online(&self) -> AppResult<()> {
+        if self.offline {
+            return Err(AppError::PolicyRestricted(
+                "offline mode is on, so models cannot be called".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The API base URL to call `provider` at, `default` unless the policy
+    /// names another.
+    pub fn endpoint<'a>(&'a self, provider: &Provider, default: &'a str) -> &'a str {
+        let configured = match provider {
+            Provider::Gemini => &self.endpoints.gemini,
+            Provider::OpenRouter => &self.endpoints.open_router,
+        };
+        configured.as_deref().unwrap_or(default)
+    }
+}
@@ -0,0 +1,85 @@
+//! Privacy mode for text that leaves the database through log lines, run
+//! diagnostics and error events. With it on, which is the default, document
+//! text and the outside text an error quotes (a provider response body that
+//! can echo the prompt, a parser's stderr) are replaced by their length and
+//! a short hash: a shared log carries no passages, yet equal texts can still
+//! be matched up.
+
+use sha2::{Digest, Sha256};
+
+use crate::core::{errors::AppError, types::PrivacySettings};
+
+/// Hex digits of the SHA-256 kept in a fingerprint.
+const HASH_CHARS: usize = 8;
+
+/// Where an error detail stops describing the failure and starts quoting,
+/// in order of preference.
+const QUOTE_MARKERS: &[&str] = &[" body ", ": "];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Redactor {
+    enabled: bool,
+}
+
+impl Redactor {
+    pub fn new(settings: PrivacySettings) -> Self {
+        Self {
+            enabled: settings.redact_text,
+        }
+    }
+
+    /// `text` itself, or its fingerprint in privacy mode.
+    pub fn text(&self, text: &str) -> String {
+        if self.enabled {
+            fingerprint(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// An error detail with what follows its first quote marker
+    /// fingerprinted in privacy mode. A detail without one is the app's own
+    /// wording and is kept.
+    pub fn quoted(&self, detail: &str) -> String {
+        if !self.enabled {
+            return detail.to_string();
+        }
+        let start = QUOTE_MARKERS
+            .iter()
+            .find_map(|marker| detail.find(marker).map(|at| at + marker.len()));
+        match start {
+            Some(start) if start < detail.len() => {
+                format!("{}{}", &detail[..start], fingerprint(&detail[start..]))
+            }
+            _ => detail.to_string(),
+        }
+    }
+
+    /// The message for `err`, with quoted text redacted from the errors that
+    /// carry some.
+    pub fn error(&self, err: &AppError) -> String {
+        match err {
+            AppError::Sidecar(detail) => AppError::Sidecar(self.quoted(detail)).to_string(),
+            AppError::ProviderInvalidResponse(detail) => {
+                AppError::ProviderInvalidResponse(self.quoted(detail)).to_string()
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(PrivacySettings::default())
+    }
+}
+
+/// `[N chars #hash]` for `text`.
+pub fn fingerprint(text: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(text.as_bytes()));
+    format!(
+        "[{} chars #{}]",
+        text.chars().count(),
+        &digest[..HASH_CHARS]
+    )
+}
@@ -0,0 +1,93 @@
+//! Progress reporting for one ingestion job. Parsers report pages, not bytes,
+//! so bytes processed are estimated from the share of pages done and the ETA
+//! extrapolates the rate seen so far.
+
+use std::time::{Duration, Instant};
+
+use crate::core::types::IngestProgressEvent;
+
+/// Builds the `ingest/progress` events of one job.
+#[derive(Debug, Clone)]
+pub struct IngestProgress {
+    job_id: String,
+    total_bytes: u64,
+    started: Instant,
+}
+
+impl IngestProgress {
+    pub fn new(job_id: &str, total_bytes: u64) -> Self {
+        Self::started_at(job_id, total_bytes, Instant::now())
+    }
+
+    /// A tracker whose clock started at `started`.
+    pub fn started_at(job_id: &str, total_bytes: u64, started: Instant) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            total_bytes,
+            started,
+        }
+    }
+
+    /// An event for `stage` with no bytes processed yet.
+    pub fn start(&self, stage: &str, percent: i64, message: &str) -> IngestProgressEvent {
+        self.event(stage, percent, 0, message.to_string())
+    }
+
+    /// An event after `done` of `total` pages have been parsed.
+    pub fn pages(&self, percent: i64, done: u32, total: u32) -> IngestProgressEvent {
+        let bytes =
+            u128::from(self.total_bytes) * u128::from(done.min(total)) / u128::from(total.max(1));
+        self.event(
+            "parse",
+            percent,
+            bytes as u64,
+            format!("Parsed page {done} of {total}"),
+        )
+    }
+
+    /// The final event, carrying the id of the stored document.
+    pub fn finalize(&self, document_id: &str) -> IngestProgressEvent {
+        IngestProgressEvent {
+            document_id: Some(document_id.to_string()),
+            ..self.event(
+                "finalize",
+                100,
+                self.total_bytes,
+                "Indexing complete".to_string(),
+            )
+        }
+    }
+
+    fn event(
+        &self,
+        stage: &str,
+        percent: i64,
+        bytes_processed: u64,
+        message: String,
+    ) -> IngestProgressEvent {
+        IngestProgressEvent {
+            job_id: self.job_id.clone(),
+            stage: stage.to_string(),
+            percent,
+            message,
+            bytes_processed,
+            total_bytes: self.total_bytes,
+            eta_ms: estimate_remaining(self.started.elapsed(), bytes_processed, self.total_bytes)
+                .map(|left| left.as_millis() as i64),
+            document_id: None,
+        }
+    }
+}
+
+/// Time left to process `total` bytes at the rate `done` bytes took in
+/// `elapsed`, or `None` before any progress has been made.
+pub fn estimate_remaining(elapsed: Duration, done: u64, total: u64) -> Option<Duration> {
+    if done == 0 || total == 0 {
+        return None;
+    }
+    if done >= total {
+        return Some(Duration::ZERO);
+    }
+    let left = elapsed.as_secs_f64() * (total - done) as f64 / done as f64;
+    Some(Duration::from_secs_f64(left))
+}
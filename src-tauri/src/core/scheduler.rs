@@ -0,0 +1,167 @@
+//! Prioritizes interactive work over background writes on the shared SQLite
+//! database. A reasoning run holds an interactive permit for its whole run;
+//! ingestion takes a background permit only around its writes, and waits for
+//! it while any reasoning run is active, so a large batch import cannot starve
+//! the pool a query is reading from.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+use crate::core::types::QueueDepth;
+
+/// Background writers allowed at once. SQLite serializes writes anyway; a
+/// second slot lets one job prepare while another commits.
+pub const BACKGROUND_SLOTS: usize = 2;
+
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    background: Arc<Semaphore>,
+    background_slots: u32,
+    interactive_active: AtomicUsize,
+    background_queued: AtomicUsize,
+    background_running: AtomicUsize,
+    idle: Notify,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(BACKGROUND_SLOTS)
+    }
+}
+
+impl Scheduler {
+    pub fn new(background_slots: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                background: Arc::new(Semaphore::new(background_slots.max(1))),
+                background_slots: background_slots.max(1) as u32,
+                interactive_active: AtomicUsize::new(0),
+                background_queued: AtomicUsize::new(0),
+                background_running: AtomicUsize::new(0),
+                idle: Notify::new(),
+            }),
+        }
+    }
+
+    /// Marks interactive work as running until the permit is dropped. Never
+    /// waits.
+    pub fn interactive(&self) -> InteractivePermit {
+        self.inner.interactive_active.fetch_add(1, Ordering::SeqCst);
+        InteractivePermit {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Waits until no interactive work is running and a background slot is
+    /// free. Background jobs already holding a permit are not interrupted.
+    pub async fn background(&self) -> BackgroundPermit {
+        let inner = &self.inner;
+        let queued = Queued::new(&inner.background_queued);
+        let permit = loop {
+            let idle = inner.idle.notified();
+            if inner.interactive_active.load(Ordering::SeqCst) > 0 {
+                idle.await;
+                continue;
+            }
+            let permit = inner
+                .background
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("scheduler semaphore is never closed");
+            // A query may have started while this job waited for a slot.
+            if inner.interactive_active.load(Ordering::SeqCst) == 0 {
+                break permit;
+            }
+        };
+        drop(queued);
+        inner.background_running.fetch_add(1, Ordering::SeqCst);
+        BackgroundPermit {
+            inner: self.inner.clone(),
+            _permit: permit,
+        }
+    }
+
+    /// Waits until no reasoning run or background write is in progress and
+    /// holds off new background writes until the permit is dropped. Used before
+    /// swapping the database out from under the app.
+    pub async fn drain(&self) -> DrainPermit {
+        let inner = &self.inner;
+        let permit = inner
+            .background
+            .clone()
+            .acquire_many_owned(inner.background_slots)
+            .await
+            .expect("scheduler semaphore is never closed");
+        loop {
+            let idle = inner.idle.notified();
+            if inner.interactive_active.load(Ordering::SeqCst) == 0 {
+                break;
+            }
+            idle.await;
+        }
+        DrainPermit { _permit: permit }
+    }
+
+    pub fn depth(&self) -> QueueDepth {
+        QueueDepth {
+            interactive_active: self.inner.interactive_active.load(Ordering::SeqCst),
+            background_running: self.inner.background_running.load(Ordering::SeqCst),
+            background_queued: self.inner.background_queued.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Counts a waiting background job, including one whose wait is cancelled.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl<'a> Queued<'a> {
+    fn new(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self(count)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Held for the duration of a reasoning run.
+pub struct InteractivePermit {
+    inner: Arc<Inner>,
+}
+
+impl Drop for InteractivePermit {
+    fn drop(&mut self) {
+        if self.inner.interactive_active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// Held while the database is swapped; background writes wait for it.
+pub struct DrainPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Held while a background job writes.
+pub struct BackgroundPermit {
+    inner: Arc<Inner>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for BackgroundPermit {
+    fn drop(&mut self) {
+        self.inner.background_running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
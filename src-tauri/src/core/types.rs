@@ -1,4 +1,6 @@
-use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -6,10 +8,23 @@ use serde_json::Value;
 #[serde(rename_all = "lowercase")]
 pub enum Provider {
     Gemini,
+    OpenRouter,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "snake_case")]
+impl Provider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gemini => "gemini",
+            Self::OpenRouter => "openrouter",
+        }
+    }
+}
+
+/// The kind of a node. Besides the built-in kinds, a parser or a user may
+/// name its own (a contract's "Clause", a spec's "Requirement"); those are
+/// carried as `Custom` under their snake_case key and described in the
+/// node type registry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum NodeType {
     Document,
     Section,
@@ -22,11 +37,27 @@ pub enum NodeType {
     Caption,
     Reference,
     Unknown,
+    Custom(String),
 }
 
 impl NodeType {
+    pub const BUILT_IN: [NodeType; 11] = [
+        Self::Document,
+        Self::Section,
+        Self::Subsection,
+        Self::Paragraph,
+        Self::Claim,
+        Self::Table,
+        Self::Figure,
+        Self::Equation,
+        Self::Caption,
+        Self::Reference,
+        Self::Unknown,
+    ];
+
     pub fn from_str(raw: &str) -> Self {
-        match raw.to_ascii_lowercase().as_str() {
+        let key = Self::key(raw);
+        match key.as_str() {
             "document" => Self::Document,
             "section" => Self::Section,
             "subsection" => Self::Subsection,
@@ -37,11 +68,98 @@ impl NodeType {
             "equation" => Self::Equation,
             "caption" => Self::Caption,
             "reference" => Self::Reference,
-            _ => Self::Unknown,
+            "" | "unknown" => Self::Unknown,
+            _ => Self::Custom(key),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Document => "document",
+            Self::Section => "section",
+            Self::Subsection => "subsection",
+            Self::Paragraph => "paragraph",
+            Self::Claim => "claim",
+            Self::Table => "table",
+            Self::Figure => "figure",
+            Self::Equation => "equation",
+            Self::Caption => "caption",
+            Self::Reference => "reference",
+            Self::Unknown => "unknown",
+            Self::Custom(name) => name,
+        }
+    }
+
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(_))
+    }
+
+    /// The snake_case key a type name is stored and compared under, so
+    /// "TestCase", "test case" and "test-case" are one type.
+    pub fn key(raw: &str) -> String {
+        let mut key = String::with_capacity(raw.len() + 4);
+        let mut previous: Option<char> = None;
+        for ch in raw.trim().chars() {
+            if ch.is_alphanumeric() {
+                if ch.is_uppercase()
+                    && previous.is_some_and(|prev| prev.is_lowercase() || prev.is_ascii_digit())
+                {
+                    key.push('_');
+                }
+                key.extend(ch.to_lowercase());
+            } else if !key.is_empty() && !key.ends_with('_') {
+                key.push('_');
+            }
+            previous = Some(ch);
         }
+        key.trim_end_matches('_').to_string()
     }
 }
 
+impl Serialize for NodeType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|raw| Self::from_str(&raw))
+    }
+}
+
+/// A node type as the registry describes it: every built-in type plus the
+/// ones defined for this workspace.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeTypeInfo {
+    pub node_type: NodeType,
+    pub label: String,
+    pub description: String,
+    pub color: Option<String>,
+    pub built_in: bool,
+    /// Nodes across all documents currently of this type.
+    pub node_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListNodeTypesResponse {
+    pub node_types: Vec<NodeTypeInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveNodeTypeResponse {
+    pub node_type: NodeTypeInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteNodeTypeResponse {
+    pub removed: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetProviderKeyResponse {
@@ -55,6 +173,37 @@ pub struct IngestDocumentResponse {
     pub root_node_id: String,
     pub node_count: usize,
     pub section_count: usize,
+    /// Set when an existing parse from this project was linked in instead of
+    /// parsing the file again.
+    #[serde(default)]
+    pub shared_from_project_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReingestDocumentResponse {
+    pub document_id: String,
+    pub root_node_id: String,
+    pub node_count: usize,
+    /// Nodes whose id survived unchanged.
+    pub unchanged_nodes: usize,
+    /// Moved or edited nodes whose old id now resolves to a new one.
+    pub remapped_nodes: usize,
+    /// Old nodes with no counterpart in the new version.
+    pub dropped_nodes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentVisibilityResponse {
+    pub document: DocumentSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentLockStatus {
+    /// Unlocking and removing locks asks for the passphrase.
+    pub passphrase_set: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +211,12 @@ pub struct IngestDocumentResponse {
 pub struct ProjectSummary {
     pub id: String,
     pub name: String,
+    /// Opened from a shared bundle; its documents cannot be changed.
+    pub read_only: bool,
+    /// Put away: still openable, but unchanged until unarchived and left out
+    /// of global search by default.
+    #[serde(default)]
+    pub archived: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -84,12 +239,42 @@ pub struct RenameProjectResponse {
     pub project: ProjectSummary,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveProjectResponse {
+    pub project: ProjectSummary,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteProjectResponse {
     pub deleted: bool,
 }
 
+/// What deleting a project removes with it. Documents linked in from other
+/// projects stay with their owners and are not counted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDeleteImpact {
+    pub project_id: String,
+    pub documents: u64,
+    pub nodes: u64,
+    pub runs: u64,
+    /// Graph node positions arranged by hand on the project's documents.
+    pub layout_positions: u64,
+    /// The project's documents that other projects link to and lose too.
+    pub shared_documents: u64,
+    /// Passed back to `delete_project`; it changes whenever the counts do,
+    /// so a deletion only goes ahead for the impact that was shown.
+    pub confirmation_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDeleteImpactResponse {
+    pub impact: ProjectDeleteImpact,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DocumentSummary {
@@ -99,6 +284,31 @@ pub struct DocumentSummary {
     pub mime: String,
     pub checksum: String,
     pub pages: i64,
+    /// Left out of the document list unless hidden documents are asked for.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Kept out of previews and retrieval unless `unlocked`.
+    #[serde(default)]
+    pub locked: bool,
+    /// A locked document whose unlock has not run out yet.
+    #[serde(default)]
+    pub unlocked: bool,
+    /// The file the document was last ingested from, if known.
+    #[serde(default)]
+    pub source_path: Option<String>,
+    #[serde(default)]
+    pub last_ingested_at: Option<DateTime<Utc>>,
+    /// The file at `source_path` changed after the last ingest; only the
+    /// document list checks.
+    #[serde(default)]
+    pub stale: bool,
+    /// Tags carried over from an import; only the document list fills them.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Documents this one embeds or links to, from an import; only the
+    /// document list fills them.
+    #[serde(default)]
+    pub attachment_ids: Vec<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -114,6 +324,51 @@ pub struct OpenDocumentResponse {
     pub document: DocumentSummary,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentDocument {
+    pub document: DocumentSummary,
+    /// Where it was opened; differs from `document.project_id` for a
+    /// document linked into another project.
+    pub opened_in_project_id: String,
+    pub opened_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentQuery {
+    pub project_id: String,
+    /// The latest run of the query.
+    pub run_id: String,
+    pub query: String,
+    pub asked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentDocumentsResponse {
+    pub documents: Vec<RecentDocument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentQueriesResponse {
+    pub queries: Vec<RecentQuery>,
+}
+
+/// A node named by a reasoning step, with what the trace needs to show it
+/// without looking the node up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeRef {
+    pub id: String,
+    pub title: String,
+    pub document_id: String,
+    pub document_name: String,
+    pub node_type: NodeType,
+    pub ordinal_path: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DocNodeSummary {
@@ -150,6 +405,51 @@ pub struct GetTreeResponse {
     pub nodes: Vec<DocNodeSummary>,
 }
 
+/// What changed in a project's tree after `since_revision`. Apply it to a
+/// tree fetched earlier: replace or add `nodes`, then drop the removed nodes
+/// and every node of the removed and locked documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetProjectTreeDeltaResponse {
+    /// Pass back as `since_revision` on the next call.
+    pub revision: i64,
+    /// `nodes` is the whole tree rather than a delta, because the client had
+    /// no revision, one this database never issued, or one older than the
+    /// removals it still keeps. Replace the tree rather than applying it.
+    pub full: bool,
+    pub nodes: Vec<DocNodeSummary>,
+    pub removed_node_ids: Vec<String>,
+    pub removed_document_ids: Vec<String>,
+    /// Documents in the project that are locked right now.
+    pub locked_document_ids: Vec<String>,
+}
+
+/// A node found by `search_all_projects` and the document it is in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalSearchHit {
+    pub node: DocNodeSummary,
+    pub document_name: String,
+}
+
+/// The hits in one project, best first. A document linked into several
+/// projects is listed under each of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSearchGroup {
+    pub project_id: String,
+    pub project_name: String,
+    pub hits: Vec<GlobalSearchHit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchAllProjectsResponse {
+    pub query: String,
+    /// Ordered by each project's best hit.
+    pub groups: Vec<ProjectSearchGroup>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DocumentPreviewBlock {
@@ -194,12 +494,32 @@ pub enum RunPhase {
     Failed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl RunPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Planning => "planning",
+            Self::Retrieval => "retrieval",
+            Self::Synthesis => "synthesis",
+            Self::Validation => "validation",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QualityMetrics {
     pub overall: f64,
     pub query_alignment: f64,
+    /// Share of the answer's claims backed by a citation.
     pub citation_coverage: f64,
+    /// Valid citations over all retrieved evidence, which is what
+    /// `citation_coverage` measured before it tracked the answer's claims.
+    /// Reported alongside it while comparisons span both; zero on runs
+    /// scored before it was split out.
+    #[serde(default)]
+    pub evidence_recall: f64,
     pub cross_document_coverage: f64,
     pub grounded: bool,
 }
@@ -210,12 +530,334 @@ impl Default for QualityMetrics {
             overall: 0.0,
             query_alignment: 0.0,
             citation_coverage: 0.0,
+            evidence_recall: 0.0,
             cross_document_coverage: 0.0,
             grounded: false,
         }
     }
 }
 
+/// What kind of answer a query asks for; picks the quality gate a run's
+/// answer has to pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryClass {
+    /// A short lookup: a number, a name, a date.
+    Factual,
+    /// An overview of a document or topic.
+    Summary,
+    /// How documents or parts of one relate or differ.
+    Comparison,
+    /// Every item of some kind pulled out of the sources.
+    Extraction,
+    /// A figure combined from several values: a total, a count, an average.
+    Aggregation,
+}
+
+impl QueryClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Factual => "factual",
+            Self::Summary => "summary",
+            Self::Comparison => "comparison",
+            Self::Extraction => "extraction",
+            Self::Aggregation => "aggregation",
+        }
+    }
+}
+
+/// How much each metric counts towards `QualityMetrics::overall`; weights
+/// sum to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityWeights {
+    pub query_alignment: f64,
+    pub citation_coverage: f64,
+    pub cross_document_coverage: f64,
+    pub grounding: f64,
+}
+
+impl Default for QualityWeights {
+    fn default() -> Self {
+        Self {
+            query_alignment: 0.4,
+            citation_coverage: 0.25,
+            cross_document_coverage: 0.2,
+            grounding: 0.15,
+        }
+    }
+}
+
+/// The quality gate for one query class.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityPolicy {
+    /// Lowest `overall` score an answer is returned with.
+    pub min_overall: f64,
+    pub weights: QualityWeights,
+}
+
+/// Quality gates per query class, kept in settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QualityThresholds {
+    pub factual: QualityPolicy,
+    pub summary: QualityPolicy,
+    pub comparison: QualityPolicy,
+    pub extraction: QualityPolicy,
+    pub aggregation: QualityPolicy,
+}
+
+impl QualityThresholds {
+    pub fn policy(&self, class: QueryClass) -> QualityPolicy {
+        match class {
+            QueryClass::Factual => self.factual,
+            QueryClass::Summary => self.summary,
+            QueryClass::Comparison => self.comparison,
+            QueryClass::Extraction => self.extraction,
+            QueryClass::Aggregation => self.aggregation,
+        }
+    }
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        Self {
+            factual: QualityPolicy {
+                min_overall: 0.60,
+                weights: QualityWeights::default(),
+            },
+            // An overview touches many parts of the query loosely; what
+            // matters more is that its claims are backed.
+            summary: QualityPolicy {
+                min_overall: 0.55,
+                weights: QualityWeights {
+                    query_alignment: 0.3,
+                    citation_coverage: 0.35,
+                    cross_document_coverage: 0.2,
+                    grounding: 0.15,
+                },
+            },
+            comparison: QualityPolicy {
+                min_overall: 0.70,
+                weights: QualityWeights::default(),
+            },
+            extraction: QualityPolicy {
+                min_overall: 0.60,
+                weights: QualityWeights {
+                    query_alignment: 0.35,
+                    citation_coverage: 0.35,
+                    cross_document_coverage: 0.15,
+                    grounding: 0.15,
+                },
+            },
+            // A total is only as good as the values it is added up from.
+            aggregation: QualityPolicy {
+                min_overall: 0.60,
+                weights: QualityWeights {
+                    query_alignment: 0.3,
+                    citation_coverage: 0.35,
+                    cross_document_coverage: 0.15,
+                    grounding: 0.2,
+                },
+            },
+        }
+    }
+}
+
+/// How much document text may reach logs, run diagnostics and error events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrivacySettings {
+    /// Replaces text quoted from documents or provider responses with its
+    /// length and a short hash.
+    pub redact_text: bool,
+}
+
+impl Default for PrivacySettings {
+    fn default() -> Self {
+        Self { redact_text: true }
+    }
+}
+
+/// How the keyword search matches query terms and how runs rank its hits,
+/// kept in settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetrievalSettings {
+    /// Edits (a letter added, dropped, changed, or two swapped) a query term
+    /// may be from an indexed word and still find it, up to 2; 0 matches
+    /// only what is typed.
+    pub fuzziness: u8,
+    /// How runs order the nodes they retrieve.
+    pub ranking: RankingWeights,
+}
+
+/// Weights that order a run's candidate nodes. Keyword relevance is scaled
+/// so the best hit scores 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RankingWeights {
+    /// How much a query term counts in a node's title.
+    pub title: f64,
+    /// How much a query term counts in a node's text.
+    pub text: f64,
+    /// Added for each quantity or date range the query asks for that a
+    /// node's recorded metadata matches.
+    pub metadata: f64,
+    /// Multipliers for node types, applied to every query or only to those
+    /// using one of their hints.
+    pub node_types: Vec<NodeTypeWeight>,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            title: 2.0,
+            text: 1.0,
+            metadata: 1.0,
+            node_types: vec![NodeTypeWeight {
+                node_type: NodeType::Table,
+                weight: 1.5,
+                hints: [
+                    "how much",
+                    "how many",
+                    "percentage",
+                    "percent",
+                    "%",
+                    "proportion",
+                ]
+                .map(String::from)
+                .to_vec(),
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeTypeWeight {
+    pub node_type: NodeType,
+    pub weight: f64,
+    /// Lowercase phrases; empty applies the weight to every query.
+    #[serde(default)]
+    pub hints: Vec<String>,
+}
+
+/// Calendar days a question's evidence is kept to, both ends included; an
+/// open end is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateRange {
+    #[serde(default)]
+    pub from: Option<NaiveDate>,
+    #[serde(default)]
+    pub to: Option<NaiveDate>,
+}
+
+/// Quality of a set of evaluated runs: those that reached the quality gate,
+/// whether or not they passed it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityAggregate {
+    pub run_count: i64,
+    pub avg_overall: f64,
+    pub grounding_rate: f64,
+    pub gate_failure_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityTrendPoint {
+    /// UTC day the runs started, as `YYYY-MM-DD`.
+    pub day: String,
+    #[serde(flatten)]
+    pub quality: QualityAggregate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualityTrendsResponse {
+    pub project_id: String,
+    /// One point per day with evaluated runs, oldest first.
+    pub points: Vec<QualityTrendPoint>,
+    pub total: QualityAggregate,
+}
+
+/// How often answers cite each part of a project's or a document's tree,
+/// for coloring the tree and graph by use. Nodes no answer has cited, nor
+/// anything beneath them, are left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationHeatmap {
+    pub project_id: Option<String>,
+    pub document_id: Option<String>,
+    /// Answers citing at least one node in scope.
+    pub answer_count: i64,
+    /// The highest `total` of any node, which `intensity` is relative to.
+    pub max_total: i64,
+    /// Most cited first.
+    pub nodes: Vec<CitationHeat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationHeat {
+    pub node_id: String,
+    pub document_id: String,
+    pub parent_id: Option<String>,
+    pub node_type: NodeType,
+    pub title: String,
+    pub ordinal_path: String,
+    /// Answers citing this node itself.
+    pub citations: i64,
+    /// Answers citing this node or any node beneath it, each counted once.
+    pub total: i64,
+    /// `total` over the map's `max_total`, from 0 to 1.
+    pub intensity: f64,
+}
+
+/// Content of a project that its runs have never used, as candidates for
+/// pruning: every document and section is searched on each question, so
+/// unused ones still cost retrieval time and prompt tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedContentReport {
+    pub project_id: String,
+    /// Runs whose steps and answers were looked at.
+    pub run_count: i64,
+    /// Sections that no run retrieved or cited, nor anything in them; a
+    /// section's unused subsections are not listed again. Largest first.
+    pub sections: Vec<UnusedSection>,
+    /// Documents no answer has cited, largest first.
+    pub documents: Vec<UnusedDocument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedSection {
+    pub node_id: String,
+    pub document_id: String,
+    pub document_name: String,
+    pub node_type: NodeType,
+    pub title: String,
+    pub ordinal_path: String,
+    /// The section and everything in it.
+    pub node_count: usize,
+    pub estimated_tokens: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedDocument {
+    pub document_id: String,
+    pub name: String,
+    pub node_count: usize,
+    pub estimated_tokens: usize,
+    /// Nodes runs looked at without citing; 0 when it was never retrieved.
+    pub retrieved_nodes: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReasoningRun {
@@ -232,6 +874,81 @@ pub struct ReasoningRun {
     pub cost_usd: f64,
     pub quality_json: Value,
     pub planner_trace_json: Value,
+    pub prompt_versions_json: Value,
+    pub max_steps: Option<i64>,
+    pub replay_of: Option<String>,
+    pub diagnostics_json: Value,
+    pub attach_figure_images: bool,
+    /// `None` for runs recorded before manifests were kept.
+    pub manifest: Option<RunManifest>,
+}
+
+/// Everything needed to explain or reproduce a run later: the models and
+/// prompt versions it used, the settings in force and the exact versions of
+/// the documents it read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunManifest {
+    pub app_version: String,
+    /// `None` when responses were replayed rather than requested.
+    pub provider: Option<Provider>,
+    pub model: String,
+    pub planner_model: String,
+    pub prompt_versions: Value,
+    pub generation: GenerationSettings,
+    pub pricing: Option<ModelPricing>,
+    pub planner_pricing: Option<ModelPricing>,
+    pub max_steps: i64,
+    pub attach_figure_images: bool,
+    /// Whether evidence was read with its neighbours; see `RunOptions`.
+    #[serde(default)]
+    pub expand_context: bool,
+    /// The focus the run was asked with; an inferred one is in the planner
+    /// trace.
+    pub focus_document_id: Option<String>,
+    #[serde(default)]
+    pub infer_focus_document: bool,
+    /// Typo tolerance of the keyword search; see [`RetrievalSettings`].
+    #[serde(default)]
+    pub fuzziness: u8,
+    /// How candidates were ranked; absent for runs from before ranking
+    /// weights could be configured.
+    #[serde(default)]
+    pub ranking: Option<RankingWeights>,
+    /// The dates the run was asked to keep its evidence to.
+    #[serde(default)]
+    pub date_range: Option<DateRange>,
+    /// How the query was classified, and the quality gate that applied.
+    #[serde(default)]
+    pub query_class: Option<QueryClass>,
+    #[serde(default)]
+    pub quality_policy: Option<QualityPolicy>,
+    /// Word lists, quality thresholds and redaction in force, so a replay
+    /// reads the query as the run did; absent for runs from before they
+    /// were recorded, which replay with the current settings.
+    #[serde(default)]
+    pub language: Option<LanguageResources>,
+    #[serde(default)]
+    pub quality: Option<QualityThresholds>,
+    #[serde(default)]
+    pub privacy: Option<PrivacySettings>,
+    pub replay_of: Option<String>,
+    /// The run this one re-asked with different settings, if any.
+    #[serde(default)]
+    pub rerun_of: Option<String>,
+    /// Documents the run's evidence came from; filled in once evidence is
+    /// gathered.
+    pub documents: Vec<RunManifestDocument>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunManifestDocument {
+    pub id: String,
+    pub name: String,
+    /// SHA-256 of the file as ingested; a re-ingested document changes it.
+    pub checksum: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -243,7 +960,11 @@ pub struct ReasoningStep {
     pub thought: String,
     pub action: String,
     pub observation: String,
+    /// Empty for steps recorded before summaries were kept.
+    pub summary: String,
     pub node_refs: Vec<String>,
+    /// Empty for steps recorded before refs were resolved.
+    pub resolved_refs: Vec<NodeRef>,
     pub confidence: f64,
     pub latency_ms: i64,
 }
@@ -258,40 +979,593 @@ pub struct AnswerRecord {
     pub grounded: bool,
 }
 
+/// One answer generated for a run. The run's [`AnswerRecord`] is a copy of
+/// the `current` revision.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct RunReasoningQueryResponse {
+pub struct AnswerRevision {
     pub run_id: String,
-    pub status: String,
+    pub revision: i64,
+    pub answer_markdown: String,
+    pub citations: Vec<String>,
+    pub confidence: f64,
+    pub grounded: bool,
+    /// Provider, models, generation settings, step budget and prompt
+    /// versions it was generated with; empty for imported answers.
+    pub generation: Value,
+    pub current: bool,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GetRunResponse {
-    pub run: ReasoningRun,
-    pub steps: Vec<ReasoningStep>,
-    pub answer: Option<AnswerRecord>,
+pub struct RunReasoningQueryResponse {
+    pub run_id: String,
+    pub status: String,
+    pub scope: ScopeDecision,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a query would cost if run now, projected from retrieval alone; no
+/// provider is called. `max_*` figures allow for every backtrack and JSON
+/// retry the run could make.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ExportMarkdownResponse {
-    pub file_path: String,
+pub struct QueryCostEstimate {
+    pub scope: ScopeDecision,
+    /// Evidence snippets the synthesis prompt would carry, after trimming to
+    /// the model's context window.
+    pub evidence_count: usize,
+    pub evidence_tokens: usize,
+    /// Snippets dropped to fit the context window.
+    pub trimmed_evidence: usize,
+    /// One entry per model the run would call: the planner, then synthesis.
+    pub models: Vec<ModelCostEstimate>,
+    pub cost_usd: f64,
+    pub max_cost_usd: f64,
+    /// Provider time only; `None` until each model has answered a call.
+    pub latency_ms: Option<i64>,
+    pub max_latency_ms: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct DeleteDocumentResponse {
-    pub deleted: bool,
+pub struct ModelCostEstimate {
+    /// `planner` or `synthesis`.
+    pub call_kind: String,
+    pub model: String,
+    pub pricing: ModelPricing,
+    pub calls: usize,
+    pub max_calls: usize,
+    /// Tokens over all `calls`.
+    pub prompt_tokens: usize,
+    pub output_tokens: usize,
+    pub cost_usd: f64,
+    pub max_cost_usd: f64,
+    /// Mean of the model's recent answered calls of this kind.
+    pub avg_call_latency_ms: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct IngestProgressEvent {
+/// Whether a run reads one document or the whole project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryScope {
+    Document,
+    Project,
+}
+
+/// How a run's retrieval was scoped, and whether the user's focus document
+/// was kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeDecision {
+    pub scope: QueryScope,
+    pub focus_document_id: Option<String>,
+    /// The focus document was set aside because the query reads as a
+    /// question across documents.
+    pub focus_dropped: bool,
+    /// The scope came from `force_scope` rather than the heuristic.
+    pub forced: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRunResponse {
+    pub run: ReasoningRun,
+    pub steps: Vec<ReasoningStep>,
+    pub answer: Option<AnswerRecord>,
+}
+
+/// Changes for `rerun_with`; anything left unset is taken from the source run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunOverrides {
+    #[serde(default)]
+    pub provider: Option<Provider>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// A separate plan-step model; an empty string plans with `model`.
+    #[serde(default)]
+    pub planner_model: Option<String>,
+    /// Merged over the source run's generation settings.
+    #[serde(default)]
+    pub generation: Option<GenerationSettings>,
+    #[serde(default)]
+    pub max_steps: Option<i64>,
+    #[serde(default)]
+    pub attach_figure_images: Option<bool>,
+    #[serde(default)]
+    pub expand_context: Option<bool>,
+    #[serde(default)]
+    pub infer_focus_document: Option<bool>,
+}
+
+/// One run's half of a comparison.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunComparisonSide {
+    pub run_id: String,
+    pub query: String,
+    pub status: RunStatus,
+    pub model: Option<String>,
+    pub answer_markdown: Option<String>,
+    pub confidence: Option<f64>,
+    pub citations: Vec<String>,
+    pub step_count: i64,
+    pub cost_usd: f64,
+    pub total_latency_ms: Option<i64>,
+    pub quality: Option<QualityMetrics>,
+}
+
+/// Run B minus run A; `None` where either side has no value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDeltas {
+    pub confidence: Option<f64>,
+    pub overall: Option<f64>,
+    pub query_alignment: Option<f64>,
+    pub citation_coverage: Option<f64>,
+    pub evidence_recall: Option<f64>,
+    pub cross_document_coverage: Option<f64>,
+    pub step_count: i64,
+    pub cost_usd: f64,
+    pub total_latency_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunComparison {
+    pub run_a: RunComparisonSide,
+    pub run_b: RunComparisonSide,
+    pub same_query: bool,
+    pub shared_citations: Vec<String>,
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    /// Shared citations over all distinct citations, 1.0 when neither cites.
+    pub citation_overlap: f64,
+    pub deltas: RunDeltas,
+}
+
+/// One question of a benchmark Q&A set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkCase {
+    /// Optional label carried into the report.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub question: String,
+    /// Words or phrases the answer must contain, ignoring case.
+    #[serde(default)]
+    pub expected_keywords: Vec<String>,
+    /// Node ids, document ids or document names the answer must cite.
+    #[serde(default)]
+    pub expected_citations: Vec<String>,
+    /// The whole answer, compared ignoring case, whitespace and trailing
+    /// punctuation.
+    #[serde(default)]
+    pub expected_answer: Option<String>,
+    #[serde(default)]
+    pub focus_document_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkCaseStatus {
+    /// Answered and met every expectation.
+    Passed,
+    /// Answered, or turned away by the quality gate, but missed an expectation.
+    Failed,
+    /// The run broke before it could be scored.
+    Errored,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkCaseResult {
+    pub index: usize,
+    pub id: Option<String>,
+    pub question: String,
+    pub run_id: String,
+    pub status: BenchmarkCaseStatus,
+    pub error: Option<String>,
+    pub answer_markdown: Option<String>,
+    pub confidence: Option<f64>,
+    pub quality: Option<QualityMetrics>,
+    pub keywords_matched: Vec<String>,
+    pub keywords_missing: Vec<String>,
+    pub citations_matched: Vec<String>,
+    pub citations_missing: Vec<String>,
+    /// `None` when the case gives no expected answer.
+    pub exact_match: Option<bool>,
+    pub cost_usd: f64,
+    pub latency_ms: i64,
+}
+
+/// Totals over a benchmark; rates are `None` when no case set that
+/// expectation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkSummary {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub errored: usize,
+    pub pass_rate: f64,
+    pub avg_overall: Option<f64>,
+    pub keyword_recall: Option<f64>,
+    pub citation_recall: Option<f64>,
+    pub exact_match_rate: Option<f64>,
+    pub total_cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub id: String,
+    pub project_id: String,
+    pub qa_file: String,
+    pub model: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub summary: BenchmarkSummary,
+    pub cases: Vec<BenchmarkCaseResult>,
+    /// Where the JSON report was written.
+    pub report_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkProgressEvent {
+    pub benchmark_id: String,
+    pub completed: usize,
+    pub total: usize,
+    pub result: BenchmarkCaseResult,
+}
+
+/// Where a run's time went, for diagnosing slow runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunTimeline {
+    pub run_id: String,
+    /// `None` while the run is still going.
+    pub total_ms: Option<i64>,
+    pub phases: Vec<PhaseTiming>,
+    pub steps: Vec<StepTiming>,
+    pub provider_calls: Vec<ProviderCallTiming>,
+    /// Time spent waiting on the provider, cache hits included.
+    pub provider_ms: i64,
+    /// Step time not spent waiting on the provider: retrieval queries and
+    /// the writes that record each step.
+    pub db_ms: i64,
+    /// `total_ms` not covered by any phase, e.g. loading prompts and storing
+    /// the answer.
+    pub unaccounted_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    pub phase: RunPhase,
+    pub duration_ms: i64,
+    /// Part of `duration_ms` spent waiting on the provider.
+    pub provider_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepTiming {
+    pub idx: i64,
+    pub step_type: String,
+    pub phase: RunPhase,
+    pub latency_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCallTiming {
+    pub seq: i64,
+    pub call_kind: String,
+    pub model: String,
+    pub latency_ms: i64,
+    /// Served from the provider cache rather than the provider.
+    pub cached: bool,
+}
+
+/// A file written by an export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedFile {
+    pub file_path: String,
+    /// An existing file at `file_path` was overwritten, which only happens
+    /// when the export was asked to.
+    pub replaced: bool,
+}
+
+/// How `export_bibliography` writes a project's references.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BibliographyFormat {
+    Bibtex,
+    CslJson,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBibliographyResponse {
+    #[serde(flatten)]
+    pub file: ExportedFile,
+    pub entry_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevealExportResponse {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteDocumentResponse {
+    pub deleted: bool,
+}
+
+/// A destructive operation the undo journal can reverse.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UndoKind {
+    /// A document deleted, or removed from one project.
+    DocumentDelete,
+    /// Node metadata rewritten, e.g. by table enrichment.
+    NodeEdit,
+    /// A document's saved graph layout cleared.
+    LayoutWipe,
+}
+
+impl UndoKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DocumentDelete => "document_delete",
+            Self::NodeEdit => "node_edit",
+            Self::LayoutWipe => "layout_wipe",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoOperation {
+    pub id: i64,
+    pub kind: UndoKind,
+    pub document_id: String,
+    /// What was done, for the undo button, e.g. "Delete Report.pdf".
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `operation` is the one undone; `None` when nothing is left to undo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoLastOperationResponse {
+    pub operation: Option<UndoOperation>,
+}
+
+/// What a node's asset is for. A node has at most one asset per role.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AssetRole {
+    /// The image behind a Figure node.
+    Figure,
+    /// A scanned page as OCR read it.
+    PageImage,
+    /// A rendered page for previews.
+    PagePreview,
+    /// A document's picture in lists, kept on its root node.
+    Thumbnail,
+}
+
+impl AssetRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Figure => "figure",
+            Self::PageImage => "pageImage",
+            Self::PagePreview => "pagePreview",
+            Self::Thumbnail => "thumbnail",
+        }
+    }
+}
+
+/// A stored asset, inlined as a data URL for an `<img>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAssetResponse {
+    pub asset_id: String,
+    pub mime: String,
+    pub byte_len: i64,
+    pub data_url: String,
+}
+
+/// Assets no node refers to any more, removed from the store.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetGcReport {
+    pub removed: usize,
+    pub bytes_freed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestProgressEvent {
     pub job_id: String,
     pub stage: String,
     pub percent: i64,
     pub message: String,
+    /// Bytes of the file parsed so far; estimated from pages for paged formats.
+    pub bytes_processed: u64,
+    pub total_bytes: u64,
+    /// Estimated time left, once enough of the file has been parsed to tell.
+    pub eta_ms: Option<i64>,
+    /// The new document's id, set on the `finalize` event.
+    pub document_id: Option<String>,
+}
+
+/// Work waiting on or holding the database, by priority.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueDepth {
+    /// Reasoning runs in progress; background writes wait while any are.
+    pub interactive_active: usize,
+    pub background_running: usize,
+    pub background_queued: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckResponse {
+    pub database_ok: bool,
+    pub pool_size: u32,
+    pub pool_idle: usize,
+    pub queue: QueueDepth,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    /// When the migration ran; `None` while it is pending.
+    pub installed_on: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaInfo {
+    /// Newest applied migration, `None` for an empty database.
+    pub current_version: Option<i64>,
+    /// Newest migration this build knows.
+    pub latest_version: i64,
+    pub applied: Vec<MigrationInfo>,
+    pub pending: Vec<MigrationInfo>,
+    /// Applied migrations this build does not know, left by a newer version.
+    pub unknown_versions: Vec<i64>,
+    /// True when the database was opened read-only because its schema is newer
+    /// than this build.
+    pub read_only: bool,
+    pub db_path: Option<String>,
+    /// Size of the database file plus its write-ahead log.
+    pub db_file_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunMigrationsResponse {
+    pub applied: Vec<MigrationInfo>,
+    /// Copy of the database taken before migrating, when there was one to take.
+    pub backup_path: Option<String>,
+    pub current_version: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompressNodeTextResponse {
+    pub compressed_nodes: usize,
+    pub bytes_saved: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortableExportResponse {
+    pub path: String,
+    pub schema_version: Option<i64>,
+    pub tables: usize,
+    pub rows: usize,
+}
+
+/// Emitted as `ingest/complete` once a document is ingested or re-ingested.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestCompleteEvent {
+    pub job_id: String,
+    pub project_id: String,
+    pub file_path: String,
+    pub document_id: String,
+    pub root_node_id: String,
+    pub node_count: usize,
+    pub section_count: usize,
+    pub shared_from_project_id: Option<String>,
+    /// True when an existing document was re-parsed in place.
+    pub reingested: bool,
+    /// Parse-quality warnings, as in an ingest preview.
+    pub warnings: Vec<String>,
+}
+
+/// What changed in a project's documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorpusChange {
+    /// Ingested, or linked from another project.
+    DocumentAdded,
+    DocumentRemoved,
+    /// Re-parsed in place; its nodes were replaced.
+    DocumentReingested,
+    /// Nodes added under an existing document, e.g. extracted chart tables.
+    NodesAdded,
+}
+
+/// Emitted as `corpus/changed`, once per affected project, whenever a
+/// document is added, removed or gains nodes, so tree and graph views can
+/// patch in the one document rather than re-fetch the project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorpusChangedEvent {
+    pub project_id: String,
+    pub change: CorpusChange,
+    pub document_id: String,
+    /// The document's nodes after the change; 0 once removed.
+    pub node_count: i64,
+    pub section_count: i64,
+    pub stats: CorpusStats,
+}
+
+/// Size of a project's corpus: its own and linked documents, leaving out
+/// locked ones the way the project tree does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorpusStats {
+    pub document_count: i64,
+    pub node_count: i64,
+}
+
+/// Emitted as `ingest/failed` with the error's code when ingestion fails.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestFailedEvent {
+    pub job_id: String,
+    pub project_id: Option<String>,
+    /// Set when re-ingesting an existing document.
+    pub document_id: Option<String>,
+    pub file_path: String,
+    pub code: String,
+    pub message: String,
+    pub retryable: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -303,11 +1577,24 @@ pub struct ReasoningStepEvent {
     pub thought: String,
     pub action: String,
     pub observation: String,
+    /// What the step found, in plain language.
+    pub summary: String,
     pub node_refs: Vec<String>,
+    /// `node_refs` resolved; nodes that no longer exist are left out.
+    pub resolved_refs: Vec<NodeRef>,
     pub latency_ms: i64,
     pub confidence: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReasoningPhaseEvent {
+    pub run_id: String,
+    pub phase: RunPhase,
+    pub previous_phase: RunPhase,
+    pub step_index: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GraphNodePosition {
@@ -338,6 +1625,9 @@ pub struct ReasoningCompleteEvent {
     pub total_latency_ms: i64,
     pub token_usage: Value,
     pub cost_usd: f64,
+    /// The answer reports missing evidence rather than answering.
+    #[serde(default)]
+    pub insufficient_evidence: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -347,4 +1637,807 @@ pub struct ReasoningErrorEvent {
     pub code: String,
     pub message: String,
     pub retryable: bool,
+    /// How long to wait before retrying, when the provider said, e.g. until
+    /// a spent quota resets.
+    #[serde(default)]
+    pub retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplateRecord {
+    pub name: String,
+    pub version: i64,
+    pub body: String,
+    pub builtin: bool,
+    pub variables: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPromptTemplatesResponse {
+    pub templates: Vec<PromptTemplateRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPromptTemplateHistoryResponse {
+    pub versions: Vec<PromptTemplateRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavePromptTemplateResponse {
+    pub template: PromptTemplateRecord,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafetySetting {
+    pub category: String,
+    pub threshold: String,
+}
+
+/// Sampling and safety options sent with provider calls. Unset fields fall back
+/// to the project's settings, then to the per-call defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationSettings {
+    #[serde(default)]
+    pub temperature: Option<f64>,
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+    #[serde(default)]
+    pub safety_settings: Vec<SafetySetting>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationSettingsResponse {
+    pub project_id: String,
+    pub settings: GenerationSettings,
+}
+
+/// How a key is chosen when a provider has several stored keys.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySelection {
+    /// Least recently used key first.
+    #[default]
+    RoundRobin,
+    /// Key with the most remaining daily quota first.
+    QuotaAware,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderKeyRecord {
+    pub id: String,
+    pub provider: Provider,
+    pub label: String,
+    pub key_hint: String,
+    pub daily_request_quota: Option<i64>,
+    pub requests_today: i64,
+    pub total_requests: i64,
+    pub total_tokens: i64,
+    pub rate_limited_count: i64,
+    pub cooldown_until: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListProviderKeysResponse {
+    pub keys: Vec<ProviderKeyRecord>,
+    pub selection: KeySelection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddProviderKeyResponse {
+    pub key: ProviderKeyRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveProviderKeyResponse {
+    pub removed: bool,
+}
+
+/// USD cost per token for a model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPricing {
+    pub input_per_token: f64,
+    pub output_per_token: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostedModel {
+    pub id: String,
+    pub name: String,
+    pub context_length: Option<usize>,
+    pub pricing: Option<ModelPricing>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListHostedModelsResponse {
+    pub provider: Provider,
+    pub models: Vec<HostedModel>,
+}
+
+/// The provider and model reasoning runs are sent to. `model` writes the final
+/// answer; plan steps use `planner` when set, on the same provider and keys.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSelection {
+    pub provider: Provider,
+    pub model: String,
+    #[serde(default)]
+    pub context_length: Option<usize>,
+    #[serde(default)]
+    pub pricing: Option<ModelPricing>,
+    #[serde(default)]
+    pub planner: Option<PlannerModel>,
+}
+
+/// A separate, usually cheaper, model for plan steps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannerModel {
+    pub model: String,
+    #[serde(default)]
+    pub context_length: Option<usize>,
+    #[serde(default)]
+    pub pricing: Option<ModelPricing>,
+}
+
+impl Default for ModelSelection {
+    fn default() -> Self {
+        Self {
+            provider: Provider::Gemini,
+            model: "gemini-2.0-flash".to_string(),
+            context_length: None,
+            pricing: None,
+            planner: None,
+        }
+    }
+}
+
+/// How long provider requests may take, per provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderTimeoutSettings {
+    pub gemini: ProviderTimeouts,
+    pub openrouter: ProviderTimeouts,
+}
+
+impl ProviderTimeoutSettings {
+    pub fn for_provider(&self, provider: &Provider) -> ProviderTimeouts {
+        match provider {
+            Provider::Gemini => self.gemini,
+            Provider::OpenRouter => self.openrouter,
+        }
+    }
+}
+
+/// Timeouts, in seconds, for one provider's requests. Each call kind has its
+/// own budget for the whole request: a plan step is short, while a synthesis
+/// on a slow model can take minutes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderTimeouts {
+    pub connect_secs: u64,
+    pub planner_secs: u64,
+    pub synthesis_secs: u64,
+    pub enrichment_secs: u64,
+    /// How long an idle connection is kept for reuse; 0 opens a new
+    /// connection for every request.
+    pub keep_alive_secs: u64,
+}
+
+impl Default for ProviderTimeouts {
+    fn default() -> Self {
+        Self {
+            connect_secs: 10,
+            planner_secs: 30,
+            synthesis_secs: 180,
+            enrichment_secs: 120,
+            keep_alive_secs: 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingOverridesResponse {
+    pub overrides: BTreeMap<String, ModelPricing>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractChartDataResponse {
+    pub document_id: String,
+    pub figures_scanned: usize,
+    pub already_extracted: usize,
+    pub skipped_without_image: usize,
+    pub not_charts: usize,
+    pub failed: usize,
+    pub table_node_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Integer,
+    Number,
+    Percent,
+    Currency,
+    Date,
+    Boolean,
+    Text,
+    Empty,
+}
+
+impl ColumnType {
+    pub fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            ColumnType::Integer | ColumnType::Number | ColumnType::Percent | ColumnType::Currency
+        )
+    }
+}
+
+/// Where a table's column names came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderSource {
+    /// A markdown header row above a `---` separator.
+    Markdown,
+    /// A text-only first row above typed data.
+    Inferred,
+    /// Named by the enrichment model.
+    Model,
+    /// No header could be found; columns are named by position.
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableColumn {
+    pub name: String,
+    pub data_type: ColumnType,
+    pub unit: Option<String>,
+}
+
+/// Column layout of a Table node, stored under `metadata.tableSchema`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSchema {
+    pub header_source: HeaderSource,
+    pub has_header_row: bool,
+    pub row_count: usize,
+    pub columns: Vec<TableColumn>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichTablesResponse {
+    pub document_id: String,
+    pub tables_scanned: usize,
+    pub already_typed: usize,
+    pub inferred_locally: usize,
+    pub inferred_with_model: usize,
+    pub unresolved: usize,
+    pub unparseable: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecomposeClaimsResponse {
+    pub document_id: String,
+    pub paragraphs_scanned: usize,
+    pub already_decomposed: usize,
+    pub single_claim: usize,
+    pub failed: usize,
+    pub claims_created: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexAcronymsResponse {
+    pub document_id: String,
+    pub acronym_count: usize,
+}
+
+/// An acronym a document defines, and the node that defines it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Acronym {
+    pub acronym: String,
+    pub expansion: String,
+    pub document_id: String,
+    pub node_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAcronymsResponse {
+    pub project_id: String,
+    pub acronyms: Vec<Acronym>,
+}
+
+/// Which files directory ingestion and watched folders leave out of a project.
+/// Patterns without a `/` match any single path component (`node_modules`,
+/// `*.tmp`); patterns with one match the path relative to the scanned folder,
+/// where `**` spans any number of directories.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IngestRules {
+    pub ignore_globs: Vec<String>,
+    /// Lowercase, without the leading dot.
+    pub ignored_extensions: Vec<String>,
+    pub max_file_bytes: Option<u64>,
+}
+
+impl Default for IngestRules {
+    fn default() -> Self {
+        Self {
+            ignore_globs: ["node_modules", "__pycache__", ".*", "~$*", "*~"]
+                .map(String::from)
+                .to_vec(),
+            ignored_extensions: [
+                "tmp", "swp", "exe", "dll", "so", "dylib", "bin", "iso", "dmg", "zip", "7z",
+                "tar", "gz", "mp3", "mp4", "mov", "wav",
+            ]
+            .map(String::from)
+            .to_vec(),
+            max_file_bytes: Some(100 * 1024 * 1024),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestRulesResponse {
+    pub project_id: String,
+    pub rules: IngestRules,
+}
+
+/// Whether a project keeps a Research Log: a document the app appends each
+/// accepted answer to, so earlier findings can be retrieved like any file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResearchLogSettings {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResearchLogSettingsResponse {
+    pub project_id: String,
+    pub settings: ResearchLogSettings,
+}
+
+/// Emitted as `research_log/appended` once an answer has been added to the
+/// project's Research Log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResearchLogAppendedEvent {
+    pub project_id: String,
+    pub run_id: String,
+    pub document_id: String,
+    /// The section holding the entry.
+    pub node_id: String,
+    /// The log document was created for this entry.
+    pub created: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannedFile {
+    pub path: String,
+    pub size_bytes: u64,
+}
+
+/// A file or whole directory a scan left out, with the rule that excluded it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedPath {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanDirectoryResponse {
+    pub files: Vec<ScannedFile>,
+    pub skipped: Vec<SkippedPath>,
+}
+
+/// The note tool a corpus is imported from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportSource {
+    /// A vault folder of Markdown notes.
+    Obsidian,
+    /// A Markdown or HTML export, unzipped.
+    Notion,
+    /// A folder exported as Zotero RDF with its files.
+    Zotero,
+}
+
+/// One file an import ingests.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportItem {
+    pub path: String,
+    pub mime_type: String,
+    /// The document's name when the file's own name is not it, e.g. a Notion
+    /// page without its id or a Zotero item's title.
+    pub display_name: Option<String>,
+    /// The project to ingest into, by name; `None` for the project the import
+    /// was started from.
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    /// Paths of the other items this one embeds or links to.
+    pub attachments: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPlan {
+    pub items: Vec<ImportItem>,
+    pub skipped: Vec<SkippedPath>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedDocument {
+    pub path: String,
+    pub document_id: String,
+    pub project_id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCorpusResponse {
+    pub imported: Vec<ImportedDocument>,
+    /// Files that could not be ingested, with why.
+    pub failed: Vec<SkippedPath>,
+    pub skipped: Vec<SkippedPath>,
+    /// Projects made for folders or collections no project was named after.
+    pub created_project_ids: Vec<String>,
+}
+
+/// One section of a previewed parse, with the start of its first block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestPreviewSection {
+    pub title: String,
+    pub block_count: usize,
+    pub char_count: usize,
+    pub excerpt: String,
+}
+
+/// What ingesting a file would produce, without anything being stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewIngestResponse {
+    pub title: String,
+    pub mime: String,
+    pub pages: i64,
+    pub node_count: usize,
+    pub section_count: usize,
+    pub paragraph_count: usize,
+    pub table_count: usize,
+    pub figure_count: usize,
+    pub char_count: usize,
+    pub sections: Vec<IngestPreviewSection>,
+    /// Signs of a poor parse worth checking before ingesting.
+    pub warnings: Vec<String>,
+    /// The document metadata the parser recorded (encoding, MIME, override...).
+    pub metadata: Value,
+}
+
+/// Parser a user can force for one document when the automatic choice gives a
+/// bad tree.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ParserKind {
+    Pdf,
+    Docx,
+    Xlsx,
+    Pptx,
+    /// The spine's chapters, each read like an HTML page.
+    Epub,
+    Image,
+    /// Headings, paragraphs, tables and images read from the markup.
+    Html,
+    /// Any file read as text, with the usual heading heuristics.
+    PlainText,
+    /// Text where only `#` lines outside code fences start sections.
+    MarkdownStrict,
+    /// The configured [`ExternalParserConfig`] executable.
+    External,
+}
+
+/// Parser choice and options passed to ingest or reingest, recorded in the
+/// document's metadata.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ParserOverride {
+    /// `None` keeps the parser picked from the file's type.
+    pub parser: Option<ParserKind>,
+    /// Read two-column PDF pages column by column; on unless `false`.
+    pub pdf_columns: Option<bool>,
+    /// Strip repeated PDF headers, footers and page numbers; on unless `false`.
+    pub pdf_boilerplate: Option<bool>,
+}
+
+/// An external parser executable (Docling or anything speaking its JSON
+/// contract over stdio) and the MIME types routed to it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExternalParserConfig {
+    pub enabled: bool,
+    /// Path to the executable.
+    pub command: String,
+    pub args: Vec<String>,
+    pub timeout_secs: u64,
+    /// Exact types such as `application/pdf`, or `type/*` for a whole family.
+    pub mime_types: Vec<String>,
+}
+
+impl Default for ExternalParserConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            command: String::new(),
+            args: Vec::new(),
+            timeout_secs: 300,
+            mime_types: vec!["application/pdf".to_string()],
+        }
+    }
+}
+
+/// The words query-scope detection and answer scoring read a query with, for
+/// one language. Defaults come from `reasoner::language`; users can edit them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageResources {
+    /// Language code the defaults were taken from, e.g. `en`.
+    pub language: String,
+    /// Words that ask how things relate, e.g. "compare", "between".
+    pub relation_hints: Vec<String>,
+    /// Words that refer to several documents, e.g. "files", "these documents".
+    pub multi_doc_hints: Vec<String>,
+    /// Words that point at one document or a place in it, e.g. "this file".
+    pub single_doc_hints: Vec<String>,
+    /// Pronouns that refer back to several things, e.g. "they".
+    pub plural_pronouns: Vec<String>,
+    /// Words left out when checking how much of the query an answer covers.
+    pub stopwords: Vec<String>,
+    /// Groups of words that earn an answer partial credit for each other
+    /// when scoring query alignment.
+    #[serde(default)]
+    pub synonyms: Vec<Vec<String>>,
+    /// Words that ask for an overview, e.g. "summarize".
+    #[serde(default)]
+    pub summary_hints: Vec<String>,
+    /// Words that ask for every item of a kind, e.g. "list all".
+    #[serde(default)]
+    pub extraction_hints: Vec<String>,
+    /// Words that ask for a figure combined from several values, e.g.
+    /// "in total".
+    #[serde(default)]
+    pub aggregation_hints: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageResourcesResponse {
+    pub resources: LanguageResources,
+    /// Languages with bundled defaults.
+    pub languages: Vec<String>,
+    /// The lists differ from the bundled defaults for `resources.language`.
+    pub customized: bool,
+}
+
+/// A database file the app can switch to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSummary {
+    pub path: String,
+    pub name: String,
+    /// The workspace the app is using now.
+    pub active: bool,
+    /// False once the file has been moved or deleted.
+    pub exists: bool,
+    pub last_opened_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWorkspacesResponse {
+    pub workspaces: Vec<WorkspaceSummary>,
+}
+
+/// Where the open database lives and whether it is safe from sync clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStatus {
+    pub path: Option<String>,
+    /// The sync service whose folder holds the database, e.g. `Dropbox`.
+    pub sync_provider: Option<String>,
+    /// Journaled with a rollback journal rather than WAL.
+    pub sync_safe: bool,
+    /// SQLite's `journal_mode`, e.g. `wal` or `delete`.
+    pub journal_mode: String,
+    pub warning: Option<String>,
+}
+
+/// Automatic backups of the open database to a folder of the user's choice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub folder: Option<String>,
+    pub interval_hours: u32,
+    /// Backups kept in the folder; older ones are removed.
+    pub keep: u32,
+    /// Also copy the asset store, i.e. figure and page images.
+    pub include_assets: bool,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folder: None,
+            interval_hours: 24,
+            keep: 7,
+            include_assets: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+    pub byte_len: u64,
+    pub includes_assets: bool,
+}
+
+/// Newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListBackupsResponse {
+    pub folder: Option<String>,
+    pub backups: Vec<BackupInfo>,
+}
+
+/// Describes a shared bundle. `manifest_sha256` covers every other field but
+/// `signature` and `payload_sha256` the bundled data, so a bundle damaged
+/// after export is refused. `signature` is the ed25519 signature of
+/// `manifest_sha256` by `signer_key`, both in base64; the key says who
+/// exported the bundle once the recipient has it from the sender.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub format: String,
+    pub format_version: u32,
+    pub bundle_id: String,
+    pub project_name: String,
+    pub exported_at: DateTime<Utc>,
+    pub documents: usize,
+    pub nodes: usize,
+    pub summaries: usize,
+    pub payload_sha256: String,
+    pub manifest_sha256: String,
+    pub signer_key: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSharedBundleResponse {
+    pub path: String,
+    pub manifest: BundleManifest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenSharedBundleResponse {
+    pub project: ProjectSummary,
+    pub manifest: BundleManifest,
+    /// The bundle had been opened before; its existing project is returned.
+    pub already_open: bool,
+}
+
+/// Whether the app keeps local metrics: counts and durations of ingests,
+/// runs and provider calls, for watching long corpus builds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+}
+
+/// One metric, totalled since the metrics were last reset.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricSample {
+    pub name: String,
+    /// What the metric is broken down by, e.g. the model of a provider call;
+    /// empty when it is not.
+    pub label: String,
+    pub count: i64,
+    /// Zero for metrics that only count.
+    pub total_ms: i64,
+    pub max_ms: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMetricsResponse {
+    pub settings: MetricsSettings,
+    pub metrics: Vec<MetricSample>,
+}
+
+/// Features an administrator can turn off in the config file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyFeature {
+    /// Importing Obsidian vaults, Notion exports and Zotero libraries.
+    Import,
+    /// Routing documents to an external parser executable.
+    ExternalParser,
+    /// Exporting and opening shared project bundles.
+    SharedBundles,
+}
+
+impl PolicyFeature {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Import => "import",
+            Self::ExternalParser => "external_parser",
+            Self::SharedBundles => "shared_bundles",
+        }
+    }
+}
+
+/// Provider API base URLs used instead of the public ones, e.g. a company
+/// gateway.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct ProviderEndpoints {
+    pub gemini: Option<String>,
+    #[serde(alias = "openrouter")]
+    pub open_router: Option<String>,
+}
+
+/// The administrator's policy from `vectorless.config.toml`; without a config
+/// file it restricts nothing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AdminPolicy {
+    /// The config file the policy was read from.
+    pub source: Option<String>,
+    /// Model calls are refused, so no document text leaves the machine.
+    pub offline: bool,
+    pub endpoints: ProviderEndpoints,
+    pub disabled_features: Vec<PolicyFeature>,
+    /// `app_settings` keys users cannot change.
+    pub locked_settings: Vec<String>,
+    /// Values of `app_settings` keys, in the shape their get command returns.
+    pub presets: BTreeMap<String, Value>,
 }
@@ -0,0 +1,105 @@
+//! Content-addressed files for images too large to keep in the database:
+//! figures, OCR page images and page previews. An asset is named by the
+//! SHA-256 of its bytes, so storing the same image twice keeps one file.
+//! Which nodes use an asset is recorded in the database; see
+//! [`crate::db::repositories::assets`].
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::core::errors::{AppError, AppResult};
+
+/// Folder of the default database's store, beside the database file.
+pub const ASSETS_DIR_NAME: &str = "assets";
+
+#[derive(Debug, Clone)]
+pub struct AssetStore {
+    root: PathBuf,
+}
+
+impl AssetStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store beside a database file: `assets` for the default database,
+    /// `<file name>.assets` for any other, so workspaces sharing a folder
+    /// keep separate stores.
+    pub fn beside(db_path: &Path) -> Self {
+        let dir = db_path.parent().unwrap_or_else(|| Path::new("."));
+        let is_default = db_path
+            .file_name()
+            .is_some_and(|name| name == crate::db::DB_FILE_NAME);
+        Self::new(if is_default {
+            dir.join(ASSETS_DIR_NAME)
+        } else {
+            let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+            name.push(".assets");
+            dir.join(name)
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Writes `bytes` unless they are already stored and returns their id.
+    pub fn put(&self, bytes: &[u8]) -> AppResult<String> {
+        let asset_id = asset_id(bytes);
+        let path = self.path(&asset_id)?;
+        if !path.is_file() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            // Written aside and renamed, so a reader never sees half a file.
+            let partial = path.with_extension(format!("{}.partial", Uuid::new_v4()));
+            std::fs::write(&partial, bytes)?;
+            if let Err(err) = std::fs::rename(&partial, &path) {
+                let _ = std::fs::remove_file(&partial);
+                return Err(err.into());
+            }
+        }
+        Ok(asset_id)
+    }
+
+    pub fn read(&self, asset_id: &str) -> AppResult<Vec<u8>> {
+        match std::fs::read(self.path(asset_id)?) {
+            Ok(bytes) => Ok(bytes),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Err(AppError::NotFound(format!("asset {asset_id}")))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Deletes an asset's file; one already gone is not an error.
+    pub fn remove(&self, asset_id: &str) -> AppResult<()> {
+        match std::fs::remove_file(self.path(asset_id)?) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// `<root>/<first two hex digits>/<id>`, keeping folders small. Only
+    /// well-formed ids are accepted, so an id cannot name a path outside the
+    /// store.
+    fn path(&self, asset_id: &str) -> AppResult<PathBuf> {
+        let well_formed = asset_id.len() == 64
+            && asset_id
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte));
+        if !well_formed {
+            return Err(AppError::InvalidInput(format!(
+                "invalid asset id {asset_id}"
+            )));
+        }
+        Ok(self.root.join(&asset_id[..2]).join(asset_id))
+    }
+}
+
+/// An asset's id: the SHA-256 of its bytes, in lowercase hex.
+pub fn asset_id(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
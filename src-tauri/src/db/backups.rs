@@ -0,0 +1,215 @@
+//! Scheduled backups of the open database to a folder the user picks. A
+//! backup is a consistent copy taken with `VACUUM INTO` and named after when
+//! it was taken, optionally with the asset store beside it; only the newest
+//! [`BackupSettings::keep`] stay in the folder. Restoring never overwrites the
+//! open database: the backup is copied to a new workspace file next to it,
+//! which the app then switches to.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        paths,
+        types::{BackupInfo, BackupSettings},
+    },
+    db::{asset_store::AssetStore, storage, Database},
+};
+
+/// File names of backups start with this, followed by when they were taken.
+pub const BACKUP_PREFIX: &str = "vectorless-backup-";
+const BACKUP_EXTENSION: &str = "sqlite";
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+/// How often the schedule checks whether a backup is due.
+pub const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Checks the settings and trims the folder; a folder is required once
+/// backups are enabled.
+pub fn validate(settings: BackupSettings) -> AppResult<BackupSettings> {
+    let folder = settings
+        .folder
+        .as_deref()
+        .map(str::trim)
+        .filter(|folder| !folder.is_empty())
+        .map(|folder| paths::from_input(folder).and_then(|path| paths::to_utf8(&path)))
+        .transpose()?;
+    if settings.enabled && folder.is_none() {
+        return Err(AppError::InvalidInput(
+            "choose a folder for backups before enabling them".to_string(),
+        ));
+    }
+    if settings.interval_hours == 0 {
+        return Err(AppError::InvalidInput(
+            "backup interval must be at least 1 hour".to_string(),
+        ));
+    }
+    if settings.keep == 0 {
+        return Err(AppError::InvalidInput(
+            "at least 1 backup must be kept".to_string(),
+        ));
+    }
+    Ok(BackupSettings { folder, ..settings })
+}
+
+/// Whether a backup should be taken at `now`, given when the newest one was.
+pub fn is_due(
+    settings: &BackupSettings,
+    latest: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    if !settings.enabled || settings.folder.is_none() {
+        return false;
+    }
+    latest.is_none_or(|latest| {
+        now - latest >= chrono::Duration::hours(i64::from(settings.interval_hours))
+    })
+}
+
+/// Backups in `folder`, newest first. A folder that does not exist yet has
+/// none.
+pub fn list_backups(folder: &Path) -> AppResult<Vec<BackupInfo>> {
+    let entries = match std::fs::read_dir(paths::for_io(folder)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+    let mut backups = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        let Some(created_at) = backup_time(&path) else {
+            continue;
+        };
+        let meta = std::fs::metadata(&path)?;
+        if !meta.is_file() {
+            continue;
+        }
+        backups.push(BackupInfo {
+            path: paths::display(&paths::simplify(&path)),
+            created_at,
+            byte_len: meta.len(),
+            includes_assets: AssetStore::beside(&path).root().is_dir(),
+        });
+    }
+    backups.sort_by(|left, right| right.created_at.cmp(&left.created_at));
+    Ok(backups)
+}
+
+/// When the backup at `path` was taken, read from its name; `None` for files
+/// that are not backups.
+pub fn backup_time(path: &Path) -> Option<DateTime<Utc>> {
+    if path.extension()?.to_str()? != BACKUP_EXTENSION {
+        return None;
+    }
+    let stamp = path.file_stem()?.to_str()?.strip_prefix(BACKUP_PREFIX)?;
+    NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT)
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// Copies the database, and with `include_assets` its asset store, into
+/// `folder`.
+pub async fn create_backup(
+    db: &Database,
+    folder: &Path,
+    include_assets: bool,
+) -> AppResult<BackupInfo> {
+    if db.path().is_none() {
+        return Err(AppError::InvalidInput(
+            "an in-memory database cannot be backed up".to_string(),
+        ));
+    }
+    let created_at = Utc::now();
+    let target = folder.join(format!(
+        "{BACKUP_PREFIX}{}.{BACKUP_EXTENSION}",
+        created_at.format(STAMP_FORMAT)
+    ));
+    storage::copy_to(db, &paths::for_io(&target)).await?;
+    if include_assets {
+        copy_dir(db.assets().root(), AssetStore::beside(&target).root())?;
+    }
+    Ok(BackupInfo {
+        path: paths::display(&target),
+        created_at: backup_time(&target).unwrap_or(created_at),
+        byte_len: std::fs::metadata(paths::for_io(&target))?.len(),
+        includes_assets: include_assets,
+    })
+}
+
+/// Removes all but the newest `keep` backups in `folder` and returns how many
+/// went.
+pub fn rotate(folder: &Path, keep: u32) -> AppResult<usize> {
+    let stale = list_backups(folder)?
+        .into_iter()
+        .skip(keep as usize)
+        .collect::<Vec<_>>();
+    for backup in &stale {
+        let path = PathBuf::from(&backup.path);
+        std::fs::remove_file(paths::for_io(&path))?;
+        let assets = AssetStore::beside(&path).root().to_path_buf();
+        if assets.is_dir() {
+            std::fs::remove_dir_all(paths::for_io(&assets))?;
+        }
+    }
+    Ok(stale.len())
+}
+
+/// The new workspace file a backup is restored to, beside the open
+/// database.
+pub fn restore_destination(db_path: &Path) -> PathBuf {
+    let dir = db_path.parent().unwrap_or_else(|| Path::new("."));
+    let stem = db_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "vectorless".to_string());
+    paths::available_path(
+        dir,
+        &format!("{stem}-restored-{}", Utc::now().format("%Y%m%dT%H%M%S")),
+        BACKUP_EXTENSION,
+    )
+}
+
+/// Copies the backup at `backup`, and its assets when it has them, to the
+/// workspace file `dest`, which must not exist yet.
+pub fn restore_to(backup: &Path, dest: &Path) -> AppResult<()> {
+    if backup_time(backup).is_none() || !paths::for_io(backup).is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "{} is not a backup",
+            paths::display(backup)
+        )));
+    }
+    if paths::for_io(dest).exists() {
+        return Err(AppError::InvalidInput(format!(
+            "{} already exists",
+            paths::display(dest)
+        )));
+    }
+    std::fs::copy(paths::for_io(backup), paths::for_io(dest))?;
+    let assets = AssetStore::beside(backup);
+    if assets.root().is_dir() {
+        copy_dir(assets.root(), AssetStore::beside(dest).root())?;
+    }
+    Ok(())
+}
+
+/// Copies the files under `from` into `to`, keeping their layout.
+fn copy_dir(from: &Path, to: &Path) -> AppResult<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(paths::for_io(to))?;
+    for entry in std::fs::read_dir(paths::for_io(from))? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), paths::for_io(&target))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,582 @@
+//! Shared bundles hand a read-only copy of a project to someone else: its
+//! documents, their node trees and figure images, and the answers of completed
+//! reasoning runs. API keys, settings and run traces stay behind.
+//!
+//! A bundle is a ZIP with `manifest.json` and `payload.json`. The manifest
+//! records the SHA-256 of the payload and of the manifest itself, and the
+//! exporter's ed25519 signature of the latter with the public key to check it
+//! against (see [`bundle_signing`]). A bundle edited, damaged or truncated
+//! after export is refused; who signed it is for the recipient to confirm by
+//! comparing the key with the one the sender gives them.
+//!
+//! Opening a bundle mounts it as a new read-only project. Document and node
+//! ids are reissued so a bundle can be opened in the database it came from.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use base64::Engine;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{
+            BundleManifest, ExportSharedBundleResponse, NodeType, OpenSharedBundleResponse,
+            ProjectSummary,
+        },
+    },
+    db::{
+        node_text,
+        repositories::{assets, documents, node_types, projects},
+        retry_busy, Database,
+    },
+    enrichment::acronyms,
+    providers::ImagePart,
+    security::bundle_signing::{self, BundleSigner},
+    sidecar::types::SidecarNode,
+};
+
+pub const BUNDLE_FORMAT: &str = "vectorless-shared-bundle";
+pub const BUNDLE_FORMAT_VERSION: u32 = 2;
+/// Extension the export dialog suggests.
+pub const BUNDLE_EXTENSION: &str = "vlbundle";
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PAYLOAD_ENTRY: &str = "payload.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundlePayload {
+    documents: Vec<BundleDocument>,
+    summaries: Vec<BundleSummary>,
+    /// Definitions of the custom node types the documents' nodes carry.
+    #[serde(default)]
+    node_types: Vec<BundleNodeType>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleDocument {
+    id: String,
+    name: String,
+    mime: String,
+    checksum: String,
+    pages: i64,
+    nodes: Vec<SidecarNode>,
+    assets: Vec<BundleAsset>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleAsset {
+    node_id: String,
+    mime: String,
+    base64: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleNodeType {
+    name: String,
+    label: String,
+    description: String,
+    color: Option<String>,
+}
+
+/// The question and grounded answer of a completed run.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleSummary {
+    query: String,
+    document_id: Option<String>,
+    answer_markdown: String,
+    citations: Value,
+    confidence: f64,
+    grounded: bool,
+    started_at: String,
+}
+
+/// Writes `project_id` to a bundle at `dest`.
+pub async fn export_shared_bundle(
+    db: &Database,
+    project_id: &str,
+    dest: &Path,
+) -> AppResult<ExportSharedBundleResponse> {
+    let pool = db.pool();
+    let project = projects::get_project(pool, project_id).await?;
+
+    let mut bundled = vec![];
+    let mut node_count = 0;
+    for document in documents::list_documents(pool, project_id, true).await? {
+        documents::ensure_unlocked(pool, &document.id).await?;
+        let nodes = document_nodes(db, &document.id).await?;
+        node_count += nodes.len();
+        let figure_ids = nodes
+            .iter()
+            .filter(|node| node.node_type == "Figure")
+            .map(|node| node.id.clone())
+            .collect::<Vec<_>>();
+        let assets = assets::get_node_images(db, &figure_ids)
+            .await?
+            .into_iter()
+            .map(|(node_id, image)| BundleAsset {
+                node_id,
+                mime: image.mime,
+                base64: base64::engine::general_purpose::STANDARD.encode(&image.data),
+            })
+            .collect::<Vec<_>>();
+        bundled.push(BundleDocument {
+            id: document.id,
+            name: document.name,
+            mime: document.mime,
+            checksum: document.checksum,
+            pages: document.pages,
+            nodes,
+            assets,
+        });
+    }
+    let summaries = project_summaries(db, project_id).await?;
+    let used = bundled
+        .iter()
+        .flat_map(|document| &document.nodes)
+        .map(|node| NodeType::from_str(&node.node_type))
+        .filter(NodeType::is_custom)
+        .collect::<HashSet<_>>();
+    let node_types = node_types::list_node_types(pool)
+        .await?
+        .into_iter()
+        .filter(|info| !info.built_in && used.contains(&info.node_type))
+        .map(|info| BundleNodeType {
+            name: info.node_type.as_str().to_string(),
+            label: info.label,
+            description: info.description,
+            color: info.color,
+        })
+        .collect();
+
+    let payload = BundlePayload {
+        documents: bundled,
+        summaries,
+        node_types,
+    };
+    let payload_bytes = serde_json::to_vec(&payload)
+        .map_err(|err| AppError::Internal(format!("cannot encode bundle: {err}")))?;
+    let signer = BundleSigner::load_or_create()?;
+    let mut manifest = BundleManifest {
+        format: BUNDLE_FORMAT.to_string(),
+        format_version: BUNDLE_FORMAT_VERSION,
+        bundle_id: Uuid::new_v4().to_string(),
+        project_name: project.name,
+        exported_at: Utc::now(),
+        documents: payload.documents.len(),
+        nodes: node_count,
+        summaries: payload.summaries.len(),
+        payload_sha256: format!("{:x}", Sha256::digest(&payload_bytes)),
+        manifest_sha256: String::new(),
+        signer_key: signer.public_key(),
+        signature: String::new(),
+    };
+    manifest.manifest_sha256 = manifest_checksum(&manifest)?;
+    manifest.signature = signer.sign(manifest.manifest_sha256.as_bytes());
+
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|err| AppError::Internal(format!("cannot encode bundle manifest: {err}")))?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(File::create(dest)?));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, bytes) in [
+        (MANIFEST_ENTRY, &manifest_bytes),
+        (PAYLOAD_ENTRY, &payload_bytes),
+    ] {
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(bytes)?;
+    }
+    zip.finish().map_err(zip_error)?;
+
+    Ok(ExportSharedBundleResponse {
+        path: dest.to_string_lossy().to_string(),
+        manifest,
+    })
+}
+
+/// Checks the bundle at `path` and returns its manifest without opening it.
+pub fn verify_shared_bundle(path: &Path) -> AppResult<BundleManifest> {
+    read_bundle(path).map(|(manifest, _)| manifest)
+}
+
+/// Verifies the bundle at `path` and mounts it as a read-only project. A
+/// bundle that was opened before returns its existing project.
+pub async fn open_shared_bundle(db: &Database, path: &Path) -> AppResult<OpenSharedBundleResponse> {
+    let (manifest, payload) = read_bundle(path)?;
+    if let Some(project) = projects::find_by_bundle(db.pool(), &manifest.bundle_id).await? {
+        return Ok(OpenSharedBundleResponse {
+            project,
+            manifest,
+            already_open: true,
+        });
+    }
+
+    let project_id = Uuid::new_v4().to_string();
+    let name = free_project_name(db, &manifest.project_name).await?;
+    projects::create_shared_project(db.writer(), &project_id, &name, &manifest.bundle_id).await?;
+    // The project owns everything below, so deleting it undoes a partial mount.
+    if let Err(err) = mount(db, &project_id, payload).await {
+        let _ = projects::delete_project(db.writer(), &project_id).await;
+        return Err(err);
+    }
+    let project = projects::get_project(db.pool(), &project_id).await?;
+    Ok(OpenSharedBundleResponse {
+        project,
+        manifest,
+        already_open: false,
+    })
+}
+
+async fn mount(db: &Database, project_id: &str, payload: BundlePayload) -> AppResult<()> {
+    let writer = db.writer();
+    // Fresh ids for everything, so the bundle never collides with its source.
+    let mut ids = HashMap::new();
+    for document in &payload.documents {
+        ids.insert(document.id.clone(), Uuid::new_v4().to_string());
+        for node in &document.nodes {
+            ids.insert(node.id.clone(), Uuid::new_v4().to_string());
+        }
+    }
+    let remap = |id: &str| {
+        ids.get(id)
+            .cloned()
+            .ok_or_else(|| AppError::InvalidInput(format!("bundle refers to unknown id {id}")))
+    };
+
+    for document in payload.documents {
+        let document_id = remap(&document.id)?;
+        documents::insert_document(
+            writer,
+            &document_id,
+            project_id,
+            &document.name,
+            &document.mime,
+            &document.checksum,
+            document.pages,
+        )
+        .await?;
+        let nodes = parents_first(document.nodes)
+            .into_iter()
+            .map(|node| {
+                Ok(SidecarNode {
+                    id: remap(&node.id)?,
+                    parent_id: node.parent_id.as_deref().map(remap).transpose()?,
+                    metadata: remap_ids(node.metadata, &ids),
+                    ..node
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        documents::insert_nodes(writer, &document_id, &nodes).await?;
+        for asset in document.assets {
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(asset.base64.as_bytes())
+                .map_err(|err| AppError::InvalidInput(format!("bundle image is corrupt: {err}")))?;
+            let image = ImagePart {
+                mime: asset.mime,
+                data,
+            };
+            assets::put_node_image(db, &remap(&asset.node_id)?, &image).await?;
+        }
+        acronyms::index_acronyms(db, &document_id).await?;
+    }
+
+    // Types already defined here keep their local display.
+    for node_type in &payload.node_types {
+        let name = NodeType::from_str(&node_type.name);
+        if !name.is_custom() {
+            continue;
+        }
+        sqlx::query(
+            "INSERT OR IGNORE INTO node_types (name, label, description, color) VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(name.as_str())
+        .bind(&node_type.label)
+        .bind(&node_type.description)
+        .bind(&node_type.color)
+        .execute(writer)
+        .await?;
+    }
+
+    for summary in payload.summaries {
+        let run_id = Uuid::new_v4().to_string();
+        let document_id = summary.document_id.as_deref().map(remap).transpose()?;
+        let citations = remap_ids(summary.citations, &ids).to_string();
+        let (run_id, document_id, citations) = (&run_id, &document_id, &citations);
+        let summary = &summary;
+        retry_busy(|| async move {
+            let mut tx = writer.begin().await?;
+            sqlx::query(
+                r#"
+                INSERT INTO reasoning_runs (
+                  id, project_id, document_id, query, status, phase, started_at, ended_at
+                )
+                VALUES (?1, ?2, ?3, ?4, 'completed', 'completed', ?5, ?5)
+                "#,
+            )
+            .bind(run_id)
+            .bind(project_id)
+            .bind(document_id)
+            .bind(&summary.query)
+            .bind(&summary.started_at)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO answers (run_id, answer_markdown, citations_json, confidence, grounded)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(run_id)
+            .bind(&summary.answer_markdown)
+            .bind(citations)
+            .bind(summary.confidence)
+            .bind(summary.grounded)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO answer_revisions (
+                  run_id, revision, answer_markdown, citations_json, confidence, grounded
+                )
+                VALUES (?1, 1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(run_id)
+            .bind(&summary.answer_markdown)
+            .bind(citations)
+            .bind(summary.confidence)
+            .bind(summary.grounded)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn document_nodes(
+    db: &Database,
+    document_id: &str,
+) -> AppResult<Vec<SidecarNode>> {
+    sqlx::query(
+        r#"
+        SELECT id, parent_id, node_type, title, text, text_zstd, page_start, page_end,
+               ordinal_path, bbox_json, metadata_json
+        FROM doc_nodes
+        WHERE document_id = ?1
+        ORDER BY ordinal_path, id
+        "#,
+    )
+    .bind(document_id)
+    .fetch_all(db.pool())
+    .await?
+    .into_iter()
+    .map(|row| {
+        let bbox: String = row.try_get("bbox_json")?;
+        let metadata: String = row.try_get("metadata_json")?;
+        Ok(SidecarNode {
+            id: row.try_get("id")?,
+            parent_id: row.try_get("parent_id")?,
+            node_type: row.try_get("node_type")?,
+            title: row.try_get("title")?,
+            text: node_text::from_row(&row)?,
+            page_start: row.try_get("page_start")?,
+            page_end: row.try_get("page_end")?,
+            ordinal_path: row.try_get("ordinal_path")?,
+            bbox: serde_json::from_str(&bbox).unwrap_or(Value::Null),
+            metadata: serde_json::from_str(&metadata).unwrap_or(Value::Null),
+        })
+    })
+    .collect()
+}
+
+async fn project_summaries(db: &Database, project_id: &str) -> AppResult<Vec<BundleSummary>> {
+    sqlx::query(
+        r#"
+        SELECT r.query, r.document_id, r.started_at, a.answer_markdown, a.citations_json,
+               a.confidence, a.grounded
+        FROM reasoning_runs r
+        JOIN answers a ON a.run_id = r.id
+        WHERE r.project_id = ?1 AND r.status = 'completed'
+        ORDER BY r.started_at
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(db.pool())
+    .await?
+    .into_iter()
+    .map(|row| {
+        let citations: String = row.try_get("citations_json")?;
+        Ok(BundleSummary {
+            query: row.try_get("query")?,
+            document_id: row.try_get("document_id")?,
+            answer_markdown: row.try_get("answer_markdown")?,
+            citations: serde_json::from_str(&citations).unwrap_or(Value::Array(vec![])),
+            confidence: row.try_get("confidence")?,
+            grounded: row.try_get("grounded")?,
+            started_at: row.try_get("started_at")?,
+        })
+    })
+    .collect()
+}
+
+fn read_bundle(path: &Path) -> AppResult<(BundleManifest, BundlePayload)> {
+    let file = File::open(path)
+        .map_err(|err| AppError::Io(format!("cannot open bundle {}: {err}", path.display())))?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))
+        .map_err(|err| AppError::InvalidInput(format!("not a shared bundle: {err}")))?;
+    let manifest_bytes = read_entry(&mut archive, MANIFEST_ENTRY)?;
+    let payload_bytes = read_entry(&mut archive, PAYLOAD_ENTRY)?;
+
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|err| AppError::InvalidInput(format!("invalid bundle manifest: {err}")))?;
+    if manifest.format != BUNDLE_FORMAT {
+        return Err(AppError::InvalidInput(format!(
+            "not a shared bundle: format is {}",
+            manifest.format
+        )));
+    }
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "bundle format v{} needs a newer version of the app",
+            manifest.format_version
+        )));
+    }
+    // Version 1 bundles carried no signature.
+    if manifest.format_version < BUNDLE_FORMAT_VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "bundle format v{} is unsigned; export it again",
+            manifest.format_version
+        )));
+    }
+    if manifest_checksum(&manifest)? != manifest.manifest_sha256 {
+        return Err(AppError::InvalidInput(
+            "bundle failed its checksum: the manifest changed after export".to_string(),
+        ));
+    }
+    if !bundle_signing::verify(
+        &manifest.signer_key,
+        manifest.manifest_sha256.as_bytes(),
+        &manifest.signature,
+    ) {
+        return Err(AppError::InvalidInput(
+            "bundle failed its signature check: it was not signed by its signer key".to_string(),
+        ));
+    }
+    if format!("{:x}", Sha256::digest(&payload_bytes)) != manifest.payload_sha256 {
+        return Err(AppError::InvalidInput(
+            "bundle failed its checksum: the contents changed after export".to_string(),
+        ));
+    }
+    let payload = serde_json::from_slice(&payload_bytes)
+        .map_err(|err| AppError::InvalidInput(format!("invalid bundle contents: {err}")))?;
+    Ok((manifest, payload))
+}
+
+fn read_entry<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> AppResult<Vec<u8>> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|_| AppError::InvalidInput(format!("bundle is missing {name}")))?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// SHA-256 of the manifest with its own checksum and the signature of that
+/// checksum left blank.
+fn manifest_checksum(manifest: &BundleManifest) -> AppResult<String> {
+    let blank = BundleManifest {
+        manifest_sha256: String::new(),
+        signature: String::new(),
+        ..manifest.clone()
+    };
+    let bytes = serde_json::to_vec(&blank)
+        .map_err(|err| AppError::Internal(format!("cannot encode bundle manifest: {err}")))?;
+    let mut hasher = Sha256::new();
+    hasher.update(BUNDLE_FORMAT.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(&bytes);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Nodes ordered so every parent comes before its children, as the parent
+/// foreign key requires.
+pub(crate) fn parents_first(nodes: Vec<SidecarNode>) -> Vec<SidecarNode> {
+    let ids = nodes
+        .iter()
+        .map(|node| node.id.clone())
+        .collect::<HashSet<_>>();
+    let mut children: HashMap<String, Vec<SidecarNode>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for node in nodes {
+        match node.parent_id.clone().filter(|parent| ids.contains(parent)) {
+            Some(parent) => children.entry(parent).or_default().push(node),
+            None => queue.push_back(node),
+        }
+    }
+    let mut ordered = Vec::with_capacity(ids.len());
+    while let Some(node) = queue.pop_front() {
+        if let Some(kids) = children.remove(&node.id) {
+            queue.extend(kids);
+        }
+        ordered.push(node);
+    }
+    ordered
+}
+
+/// Replaces every string in `value` that is a bundled id with its new id.
+fn remap_ids(value: Value, ids: &HashMap<String, String>) -> Value {
+    match value {
+        Value::String(text) => Value::String(ids.get(&text).cloned().unwrap_or(text)),
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| remap_ids(v, ids)).collect()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, v)| (key, remap_ids(v, ids)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// `name (shared)`, numbered when that is taken.
+async fn free_project_name(db: &Database, name: &str) -> AppResult<String> {
+    let taken = projects::list_projects(db.pool())
+        .await?
+        .into_iter()
+        .map(|project: ProjectSummary| project.name.to_lowercase())
+        .collect::<HashSet<_>>();
+    let base = format!("{} (shared)", name.trim());
+    let mut candidate = base.clone();
+    let mut n = 2;
+    while taken.contains(&candidate.to_lowercase()) {
+        candidate = format!("{base} {n}");
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+fn zip_error(err: zip::result::ZipError) -> AppError {
+    AppError::Io(format!("cannot write bundle: {err}"))
+}
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS prompt_templates (
+  name TEXT NOT NULL,
+  version INTEGER NOT NULL,
+  body TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  PRIMARY KEY (name, version)
+);
+
+ALTER TABLE reasoning_runs
+ADD COLUMN prompt_versions_json TEXT NOT NULL DEFAULT '{}';
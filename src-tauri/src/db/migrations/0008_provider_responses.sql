@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS provider_responses (
+  run_id TEXT NOT NULL,
+  seq INTEGER NOT NULL,
+  call_kind TEXT NOT NULL,
+  request_hash TEXT NOT NULL,
+  model TEXT NOT NULL,
+  response_json TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  PRIMARY KEY (run_id, seq),
+  FOREIGN KEY(run_id) REFERENCES reasoning_runs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_provider_responses_hash ON provider_responses(run_id, request_hash);
+
+ALTER TABLE reasoning_runs
+ADD COLUMN max_steps INTEGER;
+
+ALTER TABLE reasoning_runs
+ADD COLUMN replay_of TEXT;
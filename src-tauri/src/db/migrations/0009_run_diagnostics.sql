@@ -0,0 +1,2 @@
+ALTER TABLE reasoning_runs
+ADD COLUMN diagnostics_json TEXT NOT NULL DEFAULT '[]';
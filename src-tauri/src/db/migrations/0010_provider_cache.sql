@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS provider_cache (
+  cache_key TEXT PRIMARY KEY,
+  call_kind TEXT NOT NULL,
+  model TEXT NOT NULL,
+  response_json TEXT NOT NULL,
+  hit_count INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  last_used_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  expires_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_provider_cache_expires ON provider_cache(expires_at);
+CREATE INDEX IF NOT EXISTS idx_provider_cache_last_used ON provider_cache(last_used_at);
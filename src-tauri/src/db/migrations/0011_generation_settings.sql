@@ -0,0 +1,2 @@
+ALTER TABLE projects
+ADD COLUMN generation_settings_json TEXT NOT NULL DEFAULT '{}';
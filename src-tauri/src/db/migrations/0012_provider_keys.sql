@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS provider_keys (
+  id TEXT PRIMARY KEY NOT NULL,
+  provider TEXT NOT NULL,
+  label TEXT NOT NULL,
+  key_hint TEXT NOT NULL,
+  daily_request_quota INTEGER,
+  usage_day TEXT,
+  requests_today INTEGER NOT NULL DEFAULT 0,
+  total_requests INTEGER NOT NULL DEFAULT 0,
+  total_tokens INTEGER NOT NULL DEFAULT 0,
+  rate_limited_count INTEGER NOT NULL DEFAULT 0,
+  cooldown_until TEXT,
+  last_used_at TEXT,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_provider_keys_provider ON provider_keys(provider);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS node_assets (
+  node_id TEXT PRIMARY KEY NOT NULL,
+  document_id TEXT NOT NULL,
+  mime TEXT NOT NULL,
+  bytes BLOB NOT NULL,
+  byte_len INTEGER NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  FOREIGN KEY(node_id) REFERENCES doc_nodes(id) ON DELETE CASCADE,
+  FOREIGN KEY(document_id) REFERENCES documents(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_assets_document ON node_assets(document_id);
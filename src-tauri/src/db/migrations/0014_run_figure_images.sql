@@ -0,0 +1,2 @@
+ALTER TABLE reasoning_runs
+ADD COLUMN attach_figure_images INTEGER NOT NULL DEFAULT 0;
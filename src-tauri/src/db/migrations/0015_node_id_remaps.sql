@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS node_id_remaps (
+  old_node_id TEXT PRIMARY KEY NOT NULL,
+  new_node_id TEXT NOT NULL,
+  document_id TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  FOREIGN KEY(document_id) REFERENCES documents(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_id_remaps_new ON node_id_remaps(new_node_id);
@@ -0,0 +1,3 @@
+-- '{}' deserializes to the built-in ignore rules.
+ALTER TABLE projects
+ADD COLUMN ingest_rules_json TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,10 @@
+-- Projects opened from a shared bundle are read-only; the bundle id lets the
+-- same bundle be opened twice without duplicating it.
+ALTER TABLE projects
+ADD COLUMN read_only INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE projects
+ADD COLUMN source_bundle_id TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_source_bundle
+ON projects(source_bundle_id) WHERE source_bundle_id IS NOT NULL;
@@ -0,0 +1,4 @@
+-- What a run was configured with and which document versions it read, so an
+-- answer can be explained after settings or documents change.
+ALTER TABLE reasoning_runs
+ADD COLUMN manifest_json TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,4 @@
+-- The plain-language summary streamed with each step, kept so a finished
+-- run's trace reads the same as it did live.
+ALTER TABLE reasoning_steps
+ADD COLUMN summary TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,4 @@
+-- Titles and document names of the nodes each step names, so the trace can
+-- show them without a lookup per node.
+ALTER TABLE reasoning_steps
+ADD COLUMN resolved_refs_json TEXT NOT NULL DEFAULT '[]';
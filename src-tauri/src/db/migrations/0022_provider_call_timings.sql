@@ -0,0 +1,6 @@
+-- How long each provider call took and whether the cache answered it, for
+-- the run timeline.
+ALTER TABLE provider_responses
+ADD COLUMN latency_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE provider_responses
+ADD COLUMN cached INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Whether accepted answers are appended to the project's Research Log
+-- document; '{}' leaves it off.
+ALTER TABLE projects
+ADD COLUMN research_log_json TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,15 @@
+-- Documents opened and queries asked, newest last, for "pick up where you
+-- left off". Only the most recent entries of each kind are kept.
+CREATE TABLE IF NOT EXISTS activity (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  kind TEXT NOT NULL CHECK (kind IN ('document_opened', 'query_asked')),
+  project_id TEXT NOT NULL,
+  document_id TEXT,
+  run_id TEXT,
+  query TEXT,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE,
+  FOREIGN KEY(document_id) REFERENCES documents(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_kind ON activity(kind, id);
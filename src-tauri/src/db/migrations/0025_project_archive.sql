@@ -0,0 +1,4 @@
+-- Archived projects stay openable but cannot change, and global search
+-- leaves them out unless asked.
+ALTER TABLE projects
+ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- Shared desktops: hidden documents stay out of the default list, and locked
+-- ones out of previews and retrieval until unlocked. An unlock lasts until
+-- `unlocked_until` (Unix seconds).
+ALTER TABLE documents
+ADD COLUMN hidden INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE documents
+ADD COLUMN locked INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE documents
+ADD COLUMN unlocked_until INTEGER;
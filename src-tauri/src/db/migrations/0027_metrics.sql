@@ -0,0 +1,12 @@
+-- Local app metrics, kept only when the metrics setting is on: a running
+-- count and duration total per metric and label (e.g. the model of a
+-- provider call). Nothing leaves the machine.
+CREATE TABLE IF NOT EXISTS metrics (
+  name TEXT NOT NULL,
+  label TEXT NOT NULL DEFAULT '',
+  count INTEGER NOT NULL DEFAULT 0,
+  total_ms INTEGER NOT NULL DEFAULT 0,
+  max_ms INTEGER NOT NULL DEFAULT 0,
+  updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  PRIMARY KEY (name, label)
+);
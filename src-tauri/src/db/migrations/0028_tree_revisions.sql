@@ -0,0 +1,120 @@
+-- Incremental project trees: each node mutation takes the next value of one
+-- revision counter, and removals leave a record at their revision, so a
+-- client can ask for what changed after the revision it last saw.
+CREATE TABLE IF NOT EXISTS tree_revision (
+  id INTEGER PRIMARY KEY NOT NULL CHECK (id = 1),
+  value INTEGER NOT NULL
+);
+
+INSERT OR IGNORE INTO tree_revision (id, value) VALUES (1, 0);
+
+ALTER TABLE doc_nodes
+ADD COLUMN revision INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_doc_nodes_revision ON doc_nodes(revision);
+
+-- Nodes removed from a document that still exists, e.g. by a reingest.
+CREATE TABLE IF NOT EXISTS doc_node_tombstones (
+  node_id TEXT NOT NULL,
+  document_id TEXT NOT NULL,
+  revision INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_doc_node_tombstones_revision ON doc_node_tombstones(revision);
+
+-- A document entering (present = 1) or leaving (present = 0) a project's
+-- tree without its nodes changing: linked, unlinked, deleted, or unlocked.
+CREATE TABLE IF NOT EXISTS tree_document_events (
+  project_id TEXT NOT NULL,
+  document_id TEXT NOT NULL,
+  present INTEGER NOT NULL,
+  revision INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tree_document_events_project
+ON tree_document_events(project_id, revision);
+
+-- Only text changes need the search index; a revision bump does not.
+DROP TRIGGER IF EXISTS doc_nodes_au;
+
+CREATE TRIGGER doc_nodes_au AFTER UPDATE OF document_id, title, text ON doc_nodes BEGIN
+  DELETE FROM doc_nodes_fts WHERE node_id = old.id;
+  INSERT INTO doc_nodes_fts (node_id, document_id, title, text)
+  VALUES (new.id, new.document_id, new.title, new.text);
+END;
+
+CREATE TRIGGER doc_nodes_revision_ai AFTER INSERT ON doc_nodes BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  UPDATE doc_nodes SET revision = (SELECT value FROM tree_revision) WHERE id = new.id;
+END;
+
+CREATE TRIGGER doc_nodes_revision_au
+AFTER UPDATE OF document_id, parent_id, node_type, title, text, page_start, page_end,
+  bbox_json, metadata_json, ordinal_path
+ON doc_nodes BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  UPDATE doc_nodes SET revision = (SELECT value FROM tree_revision) WHERE id = new.id;
+END;
+
+-- Nodes cascading from a deleted document are covered by its document event.
+CREATE TRIGGER doc_nodes_revision_ad AFTER DELETE ON doc_nodes
+WHEN EXISTS (SELECT 1 FROM documents WHERE id = old.document_id)
+BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  INSERT INTO doc_node_tombstones (node_id, document_id, revision)
+  SELECT old.id, old.document_id, value FROM tree_revision;
+END;
+
+CREATE TRIGGER documents_revision_bd BEFORE DELETE ON documents BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT old.project_id, old.id, 0, value FROM tree_revision;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT dl.project_id, old.id, 0, tr.value
+  FROM document_links dl, tree_revision tr
+  WHERE dl.document_id = old.id;
+END;
+
+CREATE TRIGGER documents_revision_owner AFTER UPDATE OF project_id ON documents
+WHEN old.project_id != new.project_id
+BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT old.project_id, new.id, 0, value FROM tree_revision;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT new.project_id, new.id, 1, value FROM tree_revision
+  WHERE NOT EXISTS (
+    SELECT 1 FROM document_links WHERE project_id = new.project_id AND document_id = new.id
+  );
+END;
+
+-- Locked documents are reported on every delta, so only becoming visible
+-- again needs an event.
+CREATE TRIGGER documents_revision_unlock AFTER UPDATE OF locked, unlocked_until ON documents
+WHEN (old.locked != new.locked OR old.unlocked_until IS NOT new.unlocked_until)
+  AND (new.locked = 0 OR new.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT new.project_id, new.id, 1, value FROM tree_revision;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT dl.project_id, new.id, 1, tr.value
+  FROM document_links dl, tree_revision tr
+  WHERE dl.document_id = new.id;
+END;
+
+CREATE TRIGGER document_links_revision_ai AFTER INSERT ON document_links BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT new.project_id, new.document_id, 1, value FROM tree_revision;
+END;
+
+-- Skipped when the document itself is being deleted, or when the project
+-- just became its owner.
+CREATE TRIGGER document_links_revision_ad AFTER DELETE ON document_links
+WHEN EXISTS (SELECT 1 FROM documents WHERE id = old.document_id AND project_id != old.project_id)
+BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  INSERT INTO tree_document_events (project_id, document_id, present, revision)
+  SELECT old.project_id, old.document_id, 0, value FROM tree_revision;
+END;
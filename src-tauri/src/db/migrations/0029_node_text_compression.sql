@@ -0,0 +1,23 @@
+-- Long node text is stored zstd-compressed in `text_zstd`, leaving `text`
+-- empty. The repository writes the plain text to the search index for those
+-- nodes itself, so the index triggers skip them.
+ALTER TABLE doc_nodes
+ADD COLUMN text_zstd BLOB;
+
+DROP TRIGGER IF EXISTS doc_nodes_ai;
+DROP TRIGGER IF EXISTS doc_nodes_au;
+
+CREATE TRIGGER doc_nodes_ai AFTER INSERT ON doc_nodes
+WHEN new.text_zstd IS NULL
+BEGIN
+  INSERT INTO doc_nodes_fts (node_id, document_id, title, text)
+  VALUES (new.id, new.document_id, new.title, new.text);
+END;
+
+CREATE TRIGGER doc_nodes_au AFTER UPDATE OF document_id, title, text ON doc_nodes
+WHEN new.text_zstd IS NULL
+BEGIN
+  DELETE FROM doc_nodes_fts WHERE node_id = old.id;
+  INSERT INTO doc_nodes_fts (node_id, document_id, title, text)
+  VALUES (new.id, new.document_id, new.title, new.text);
+END;
@@ -0,0 +1,42 @@
+-- Images kept as files in the asset store, named by the SHA-256 of their
+-- bytes. A node refers to its assets in its metadata, as
+-- `"assets": [{"assetId": ..., "role": ...}]`; asset_refs mirrors those
+-- entries so an asset's references are a join away, and one with none left
+-- is removed by garbage collection. `touched_at` is renewed whenever the
+-- asset is stored again, so collection spares assets about to be referred to.
+CREATE TABLE IF NOT EXISTS assets (
+  id TEXT PRIMARY KEY NOT NULL,
+  mime TEXT NOT NULL,
+  byte_len INTEGER NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  touched_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- No foreign key to assets: a node copied from a bundle may name an asset
+-- before it is stored.
+CREATE TABLE IF NOT EXISTS asset_refs (
+  node_id TEXT NOT NULL,
+  role TEXT NOT NULL,
+  asset_id TEXT NOT NULL,
+  PRIMARY KEY (node_id, role),
+  FOREIGN KEY(node_id) REFERENCES doc_nodes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_asset_refs_asset ON asset_refs(asset_id);
+
+CREATE TRIGGER doc_nodes_asset_refs_ai AFTER INSERT ON doc_nodes BEGIN
+  INSERT OR REPLACE INTO asset_refs (node_id, role, asset_id)
+  SELECT new.id, json_extract(value, '$.role'), json_extract(value, '$.assetId')
+  FROM json_each(new.metadata_json, '$.assets')
+  WHERE json_extract(value, '$.role') IS NOT NULL
+    AND json_extract(value, '$.assetId') IS NOT NULL;
+END;
+
+CREATE TRIGGER doc_nodes_asset_refs_au AFTER UPDATE OF metadata_json ON doc_nodes BEGIN
+  DELETE FROM asset_refs WHERE node_id = new.id;
+  INSERT OR REPLACE INTO asset_refs (node_id, role, asset_id)
+  SELECT new.id, json_extract(value, '$.role'), json_extract(value, '$.assetId')
+  FROM json_each(new.metadata_json, '$.assets')
+  WHERE json_extract(value, '$.role') IS NOT NULL
+    AND json_extract(value, '$.assetId') IS NOT NULL;
+END;
@@ -0,0 +1,4 @@
+-- The words in the search index, one row per distinct term with the number
+-- of nodes it occurs in. Typo-tolerant search looks up the indexed words
+-- close to a query term that matches nothing.
+CREATE VIRTUAL TABLE IF NOT EXISTS doc_nodes_fts_vocab USING fts5vocab(doc_nodes_fts, 'row');
@@ -0,0 +1,12 @@
+-- Acronyms each document defines, with the expansion it gives and the node
+-- that gives it. A project's acronyms are those of the documents it holds.
+CREATE TABLE IF NOT EXISTS acronyms (
+  document_id TEXT NOT NULL,
+  acronym TEXT NOT NULL,
+  expansion TEXT NOT NULL,
+  node_id TEXT NOT NULL,
+  PRIMARY KEY (document_id, acronym),
+  FOREIGN KEY(document_id) REFERENCES documents(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_acronyms_acronym ON acronyms(acronym);
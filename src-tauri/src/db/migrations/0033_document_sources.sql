@@ -0,0 +1,9 @@
+-- The file each document was last ingested from, and when, so the document
+-- list can flag documents whose file changed on disk afterwards. Documents
+-- from before this migration, and those linked or imported from a bundle,
+-- have no source.
+ALTER TABLE documents
+ADD COLUMN source_path TEXT;
+
+ALTER TABLE documents
+ADD COLUMN last_ingested_at TEXT;
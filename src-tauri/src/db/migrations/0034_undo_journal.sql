@@ -0,0 +1,11 @@
+-- Snapshots taken before destructive operations, so the latest can be undone
+-- for a short while. Only the newest entries are kept, and undo ignores ones
+-- past the undo window.
+CREATE TABLE IF NOT EXISTS undo_journal (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  kind TEXT NOT NULL CHECK (kind IN ('document_delete', 'node_edit', 'layout_wipe')),
+  document_id TEXT NOT NULL,
+  label TEXT NOT NULL,
+  snapshot_json TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
@@ -0,0 +1,22 @@
+-- Tags and attachment links carried over when a corpus is imported from a
+-- note tool: tags come from folders and the notes themselves, and a note is
+-- linked to the files it embeds or links to.
+CREATE TABLE IF NOT EXISTS document_tags (
+  document_id TEXT NOT NULL,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (document_id, tag),
+  FOREIGN KEY(document_id) REFERENCES documents(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_tags_tag ON document_tags(tag);
+
+CREATE TABLE IF NOT EXISTS document_attachments (
+  document_id TEXT NOT NULL,
+  attachment_id TEXT NOT NULL,
+  PRIMARY KEY (document_id, attachment_id),
+  FOREIGN KEY(document_id) REFERENCES documents(id) ON DELETE CASCADE,
+  FOREIGN KEY(attachment_id) REFERENCES documents(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_attachments_attachment
+  ON document_attachments(attachment_id);
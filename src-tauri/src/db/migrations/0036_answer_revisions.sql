@@ -0,0 +1,35 @@
+-- Every answer generated for a run, with what it was generated with, so one
+-- answer replacing another does not lose it. `answers` holds the current
+-- revision. Existing answers become their run's first revision.
+CREATE TABLE IF NOT EXISTS answer_revisions (
+  run_id TEXT NOT NULL,
+  revision INTEGER NOT NULL,
+  answer_markdown TEXT NOT NULL,
+  citations_json TEXT NOT NULL DEFAULT '[]',
+  confidence REAL NOT NULL DEFAULT 0.0,
+  grounded INTEGER NOT NULL DEFAULT 0,
+  generation_json TEXT NOT NULL DEFAULT '{}',
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+  PRIMARY KEY (run_id, revision),
+  FOREIGN KEY(run_id) REFERENCES reasoning_runs(id) ON DELETE CASCADE
+);
+
+ALTER TABLE answers
+ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+
+INSERT OR IGNORE INTO answer_revisions (
+  run_id, revision, answer_markdown, citations_json, confidence, grounded,
+  generation_json, created_at
+)
+SELECT a.run_id, 1, a.answer_markdown, a.citations_json, a.confidence, a.grounded,
+       json_object(
+         'provider', json_extract(r.manifest_json, '$.provider'),
+         'model', json_extract(r.manifest_json, '$.model'),
+         'plannerModel', json_extract(r.manifest_json, '$.plannerModel'),
+         'generation', json(COALESCE(json_extract(r.manifest_json, '$.generation'), '{}')),
+         'maxSteps', r.max_steps,
+         'promptVersions', json(r.prompt_versions_json)
+       ),
+       COALESCE(r.ended_at, r.started_at)
+FROM answers a
+JOIN reasoning_runs r ON r.id = a.run_id;
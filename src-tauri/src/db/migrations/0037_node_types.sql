@@ -0,0 +1,10 @@
+-- Node types defined for this workspace beyond the built-in ones, keyed by
+-- the snake_case name nodes carry in doc_nodes.node_type, with how to show
+-- them.
+CREATE TABLE IF NOT EXISTS node_types (
+  name TEXT PRIMARY KEY,
+  label TEXT NOT NULL,
+  description TEXT NOT NULL DEFAULT '',
+  color TEXT,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
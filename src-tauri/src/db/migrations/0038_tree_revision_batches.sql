@@ -0,0 +1,49 @@
+-- Ingest and reingest take one tree revision per batch and stamp their nodes
+-- and tombstones with it themselves. The per-row triggers stay for writers
+-- that do not, and skip the rows a batch already stamped.
+DROP TRIGGER IF EXISTS doc_nodes_revision_ai;
+
+CREATE TRIGGER doc_nodes_revision_ai AFTER INSERT ON doc_nodes
+WHEN new.revision = 0
+BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  UPDATE doc_nodes SET revision = (SELECT value FROM tree_revision) WHERE id = new.id;
+END;
+
+-- Compressing stored text changes how it is kept, not what it says.
+DROP TRIGGER IF EXISTS doc_nodes_revision_au;
+
+CREATE TRIGGER doc_nodes_revision_au
+AFTER UPDATE OF document_id, parent_id, node_type, title, text, page_start, page_end,
+  bbox_json, metadata_json, ordinal_path
+ON doc_nodes
+WHEN new.text_zstd IS old.text_zstd
+BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  UPDATE doc_nodes SET revision = (SELECT value FROM tree_revision) WHERE id = new.id;
+END;
+
+CREATE INDEX IF NOT EXISTS idx_doc_node_tombstones_node
+ON doc_node_tombstones(node_id, revision);
+
+DROP TRIGGER IF EXISTS doc_nodes_revision_ad;
+
+CREATE TRIGGER doc_nodes_revision_ad AFTER DELETE ON doc_nodes
+WHEN EXISTS (SELECT 1 FROM documents WHERE id = old.document_id)
+  AND NOT EXISTS (
+    SELECT 1 FROM doc_node_tombstones
+    WHERE node_id = old.id AND revision = (SELECT value FROM tree_revision)
+  )
+BEGIN
+  UPDATE tree_revision SET value = value + 1;
+  INSERT INTO doc_node_tombstones (node_id, document_id, revision)
+  SELECT old.id, old.document_id, value FROM tree_revision;
+END;
+
+-- Tombstones and document events at or below `pruned_through` have been
+-- deleted; a client that last saw an older revision reloads the whole tree.
+ALTER TABLE tree_revision
+ADD COLUMN pruned_through INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_tree_document_events_revision
+ON tree_document_events(revision);
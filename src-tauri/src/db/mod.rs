@@ -1,41 +1,148 @@
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 use sqlx::{
-    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
     SqlitePool,
 };
 
 use crate::core::errors::{AppError, AppResult};
+use asset_store::AssetStore;
+use storage::StorageMode;
 
+pub mod asset_store;
+pub mod backups;
+pub mod bundle;
+pub mod node_text;
 pub mod repositories;
+pub mod schema;
+pub mod storage;
+pub mod undo;
+pub mod workspaces;
 
+/// Name of the database file inside the app data folder.
+pub const DB_FILE_NAME: &str = "vectorless.sqlite";
+/// How long a connection waits on a locked database before SQLite reports
+/// `SQLITE_BUSY`.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Attempts made by [`retry_busy`] before giving up.
+const BUSY_ATTEMPTS: u32 = 4;
+/// First pause between attempts; doubled after each one.
+const BUSY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Readers share a pool; every write goes through a single connection so the
+/// app never contends with itself for SQLite's write lock. WAL mode lets reads
+/// continue while that connection writes.
 #[derive(Clone)]
 pub struct Database {
     pool: SqlitePool,
+    writer: SqlitePool,
+    /// The database file; `None` in memory.
+    path: Option<PathBuf>,
+    /// Image files referred to from the database's nodes.
+    assets: AssetStore,
+    read_only: bool,
+    /// Journaled for a cloud-synced folder; see [`storage`].
+    sync_safe: bool,
+}
+
+/// Whether opening a database applies pending migrations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationPolicy {
+    /// Back the database up and migrate it before use.
+    Apply,
+    /// Leave the schema alone until [`schema::run_pending_migrations`] is called.
+    Defer,
 }
 
 impl Database {
     pub async fn new(app_data_dir: &Path) -> AppResult<Self> {
+        Self::open(app_data_dir, MigrationPolicy::Apply).await
+    }
+
+    /// Opens the database in `app_data_dir`. A database written by a newer
+    /// version of the app fails with [`AppError::IncompatibleSchema`] before
+    /// anything is changed.
+    pub async fn open(app_data_dir: &Path, policy: MigrationPolicy) -> AppResult<Self> {
         std::fs::create_dir_all(app_data_dir)?;
-        let db_path = app_data_dir.join("vectorless.sqlite");
-        let connect_options = SqliteConnectOptions::from_str(&format!(
-            "sqlite:{}",
-            db_path.to_string_lossy().replace('\\', "/")
-        ))
-        .map_err(|err| AppError::Database(err.to_string()))?
-        .create_if_missing(true)
-        .journal_mode(SqliteJournalMode::Wal)
-        .synchronous(SqliteSynchronous::Normal);
+        Self::open_file(&app_data_dir.join(DB_FILE_NAME), policy).await
+    }
+
+    /// Like [`Database::open`], for a database file anywhere on disk; the file
+    /// is created when missing. Files in a synced folder open sync-safe.
+    pub async fn open_file(db_path: &Path, policy: MigrationPolicy) -> AppResult<Self> {
+        Self::open_file_with(db_path, policy, StorageMode::Auto).await
+    }
+
+    pub async fn open_file_with(
+        db_path: &Path,
+        policy: MigrationPolicy,
+        storage: StorageMode,
+    ) -> AppResult<Self> {
+        let db_path = db_path.to_path_buf();
+        let sync_safe = storage.sync_safe_for(&db_path);
+        let (journal_mode, synchronous) = storage::journal_settings(sync_safe);
+        let options = connect_options(&db_path)?
+            .create_if_missing(true)
+            .journal_mode(journal_mode)
+            .synchronous(synchronous)
+            .foreign_keys(true)
+            .busy_timeout(BUSY_TIMEOUT);
+        let writer = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options.clone())
+            .await?;
         let pool = SqlitePoolOptions::new()
-            .max_connections(10)
-            .connect_with(connect_options)
+            .max_connections(8)
+            .connect_with(options)
             .await?;
-        sqlx::query("PRAGMA foreign_keys = ON;")
-            .execute(&pool)
+        let db = Self {
+            pool,
+            writer,
+            assets: AssetStore::beside(&db_path),
+            path: Some(db_path),
+            read_only: false,
+            sync_safe,
+        };
+        schema::check_compatible(db.pool()).await?;
+        if policy == MigrationPolicy::Apply {
+            schema::run_pending_migrations(&db).await?;
+        }
+        Ok(db)
+    }
+
+    /// Opens an existing database without migrating or writing to it, e.g. to
+    /// export data from a schema this build cannot use.
+    pub async fn open_read_only(app_data_dir: &Path) -> AppResult<Self> {
+        Self::open_file_read_only(&app_data_dir.join(DB_FILE_NAME)).await
+    }
+
+    pub async fn open_file_read_only(db_path: &Path) -> AppResult<Self> {
+        let db_path = db_path.to_path_buf();
+        if !db_path.exists() {
+            return Err(AppError::NotFound(format!(
+                "database {}",
+                db_path.to_string_lossy()
+            )));
+        }
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(
+                connect_options(&db_path)?
+                    .read_only(true)
+                    .busy_timeout(BUSY_TIMEOUT),
+            )
             .await?;
-        sqlx::migrate!("./src/db/migrations").run(&pool).await?;
-        Ok(Self { pool })
+        Ok(Self {
+            writer: pool.clone(),
+            pool,
+            assets: AssetStore::beside(&db_path),
+            path: Some(db_path),
+            read_only: true,
+            sync_safe: false,
+        })
     }
 
     pub async fn in_memory() -> AppResult<Self> {
@@ -46,13 +153,78 @@ impl Database {
         sqlx::query("PRAGMA foreign_keys = ON;")
             .execute(&pool)
             .await?;
-        sqlx::migrate!("./src/db/migrations").run(&pool).await?;
-        Ok(Self { pool })
+        schema::MIGRATOR.run(&pool).await?;
+        // An in-memory database lives on its one connection, so reads and
+        // writes share it.
+        Ok(Self {
+            writer: pool.clone(),
+            pool,
+            path: None,
+            // Nothing outlives an in-memory database, but its assets still
+            // need a folder of their own.
+            assets: AssetStore::new(
+                std::env::temp_dir().join(format!("vectorless-assets-{}", uuid::Uuid::new_v4())),
+            ),
+            read_only: false,
+            sync_safe: false,
+        })
     }
 
+    /// Pool for reads.
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
+
+    /// The single connection all writes go through.
+    pub fn writer(&self) -> &SqlitePool {
+        &self.writer
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn assets(&self) -> &AssetStore {
+        &self.assets
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn is_sync_safe(&self) -> bool {
+        self.sync_safe
+    }
+}
+
+fn connect_options(db_path: &Path) -> AppResult<SqliteConnectOptions> {
+    SqliteConnectOptions::from_str(&format!(
+        "sqlite:{}",
+        db_path.to_string_lossy().replace('\\', "/")
+    ))
+    .map_err(|err| AppError::Database(err.to_string()))
+}
+
+/// Runs `op`, running it again with backoff while it fails with
+/// [`AppError::DatabaseBusy`]. `op` must be safe to repeat: a single
+/// statement or a whole transaction.
+pub async fn retry_busy<T, F, Fut>(mut op: F) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let mut backoff = BUSY_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(AppError::DatabaseBusy(_)) if attempt < BUSY_ATTEMPTS => {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
 }
 
 pub fn default_data_dir(base: Option<PathBuf>) -> Result<PathBuf, AppError> {
@@ -0,0 +1,40 @@
+//! Transparent zstd compression of long node text. A compressed node keeps
+//! an empty `text` column and its bytes in `text_zstd`; the search index
+//! still holds the plain text, so matching is unaffected.
+
+use sqlx::{sqlite::SqliteRow, Row};
+
+use crate::core::errors::{AppError, AppResult};
+
+/// Text shorter than this stays plain; the zstd frame would eat most of the
+/// saving.
+pub const COMPRESS_MIN_BYTES: usize = 2048;
+/// zstd's default level: most of the ratio at a fraction of the cost of the
+/// higher levels, which matters while a large PDF is being ingested.
+const LEVEL: i32 = 3;
+
+/// The compressed form of `text`, or `None` when it is below the threshold
+/// or does not shrink.
+pub fn compress(text: &str) -> AppResult<Option<Vec<u8>>> {
+    if text.len() < COMPRESS_MIN_BYTES {
+        return Ok(None);
+    }
+    let bytes = zstd::bulk::compress(text.as_bytes(), LEVEL)
+        .map_err(|err| AppError::Internal(format!("cannot compress node text: {err}")))?;
+    Ok((bytes.len() < text.len()).then_some(bytes))
+}
+
+pub fn decompress(bytes: &[u8]) -> AppResult<String> {
+    let plain = zstd::stream::decode_all(bytes)
+        .map_err(|err| AppError::Database(format!("corrupt compressed node text: {err}")))?;
+    String::from_utf8(plain)
+        .map_err(|err| AppError::Database(format!("compressed node text is not UTF-8: {err}")))
+}
+
+/// A node's text from a row that selected both `text` and `text_zstd`.
+pub fn from_row(row: &SqliteRow) -> AppResult<String> {
+    match row.try_get::<Option<Vec<u8>>, _>("text_zstd")? {
+        Some(bytes) => decompress(&bytes),
+        None => Ok(row.try_get("text")?),
+    }
+}
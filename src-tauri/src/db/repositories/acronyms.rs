@@ -0,0 +1,112 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::{errors::AppResult, types::Acronym},
+    db::retry_busy,
+    enrichment::acronyms::AcronymDefinition,
+};
+
+/// Replaces the acronyms recorded for a document with `definitions`, each
+/// with the node that defines it.
+pub async fn replace_document_acronyms(
+    pool: &SqlitePool,
+    document_id: &str,
+    definitions: &[(String, AcronymDefinition)],
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM acronyms WHERE document_id = ?1")
+            .bind(document_id)
+            .execute(&mut *tx)
+            .await?;
+        for (node_id, definition) in definitions {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO acronyms (document_id, acronym, expansion, node_id)
+                VALUES (?1, ?2, ?3, ?4)
+                "#,
+            )
+            .bind(document_id)
+            .bind(&definition.acronym)
+            .bind(&definition.expansion)
+            .bind(node_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Acronyms defined by the documents in a project, owned or linked, leaving
+/// out locked documents that are not unlocked.
+pub async fn list_project_acronyms(pool: &SqlitePool, project_id: &str) -> AppResult<Vec<Acronym>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT a.acronym, a.expansion, a.document_id, a.node_id
+        FROM acronyms a
+        JOIN documents d ON d.id = a.document_id
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+        ORDER BY a.acronym, a.expansion, d.created_at
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(Acronym {
+                acronym: row.try_get("acronym")?,
+                expansion: row.try_get("expansion")?,
+                document_id: row.try_get("document_id")?,
+                node_id: row.try_get("node_id")?,
+            })
+        })
+        .collect()
+}
+
+/// `query` with the project's acronyms spelled out after each one it uses,
+/// and the acronym after each expansion it spells out, so the keyword search
+/// matches either form. An acronym of three or more letters is recognised
+/// in lowercase too; shorter ones ("IT", "US") only as written.
+pub async fn expand_query(pool: &SqlitePool, project_id: &str, query: &str) -> AppResult<String> {
+    let acronyms = list_project_acronyms(pool, project_id).await?;
+    let words = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    let spelled = format!(" {} ", words.join(" ").to_lowercase());
+    let mut expanded = query.to_string();
+    let mut added: Vec<&str> = vec![];
+    for acronym in &acronyms {
+        let names = |word: &str| {
+            word == acronym.acronym
+                || (acronym.acronym.len() >= 3 && word.eq_ignore_ascii_case(&acronym.acronym))
+        };
+        let used = words
+            .iter()
+            .any(|word| names(word) || word.strip_suffix('s').is_some_and(names));
+        let expansion = acronym
+            .expansion
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let addition = if used {
+            acronym.expansion.as_str()
+        } else if spelled.contains(&format!(" {expansion} ")) {
+            acronym.acronym.as_str()
+        } else {
+            continue;
+        };
+        if !added.contains(&addition) {
+            added.push(addition);
+            expanded.push(' ');
+            expanded.push_str(addition);
+        }
+    }
+    Ok(expanded)
+}
@@ -0,0 +1,181 @@
+//! What the user did recently: documents opened and queries asked, to build
+//! a home screen from. Entries are never edited; each kind keeps only its
+//! newest [`KEEP_PER_KIND`] rows.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{RecentDocument, RecentQuery},
+    },
+    db::{repositories::documents, retry_busy},
+};
+
+/// Rows kept per kind; older ones are dropped as new ones arrive.
+pub const KEEP_PER_KIND: i64 = 200;
+
+const DOCUMENT_OPENED: &str = "document_opened";
+const QUERY_ASKED: &str = "query_asked";
+
+fn parse_timestamp(value: String) -> AppResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|v| v.with_timezone(&Utc))
+        .map_err(|err| AppError::Database(format!("invalid timestamp {value}: {err}")))
+}
+
+pub async fn record_document_opened(
+    pool: &SqlitePool,
+    project_id: &str,
+    document_id: &str,
+) -> AppResult<()> {
+    record(
+        pool,
+        DOCUMENT_OPENED,
+        project_id,
+        Some(document_id),
+        None,
+        None,
+    )
+    .await
+}
+
+pub async fn record_query_asked(
+    pool: &SqlitePool,
+    project_id: &str,
+    run_id: &str,
+    query: &str,
+) -> AppResult<()> {
+    record(
+        pool,
+        QUERY_ASKED,
+        project_id,
+        None,
+        Some(run_id),
+        Some(query),
+    )
+    .await
+}
+
+async fn record(
+    pool: &SqlitePool,
+    kind: &str,
+    project_id: &str,
+    document_id: Option<&str>,
+    run_id: Option<&str>,
+    query: Option<&str>,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO activity (kind, project_id, document_id, run_id, query)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(kind)
+        .bind(project_id)
+        .bind(document_id)
+        .bind(run_id)
+        .bind(query)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM activity
+            WHERE kind = ?1
+              AND id <= (SELECT id FROM activity WHERE kind = ?1 ORDER BY id DESC LIMIT 1 OFFSET ?2)
+            "#,
+        )
+        .bind(kind)
+        .bind(KEEP_PER_KIND)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Documents most recently opened, each once, newest first; in `project_id`
+/// only when given. Deleted and hidden documents drop out.
+pub async fn recent_documents(
+    pool: &SqlitePool,
+    project_id: Option<&str>,
+    limit: usize,
+) -> AppResult<Vec<RecentDocument>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT d.id, d.project_id, d.name, d.mime, d.checksum, d.pages, d.hidden, d.locked,
+               d.unlocked_until, d.created_at, d.source_path, d.last_ingested_at,
+               a.project_id AS opened_in_project_id, a.created_at AS opened_at
+        FROM activity a
+        JOIN documents d ON d.id = a.document_id
+        WHERE a.id IN (
+          SELECT MAX(id) FROM activity
+          WHERE kind = ?1 AND (?2 IS NULL OR project_id = ?2)
+          GROUP BY document_id
+        )
+          AND d.hidden = 0
+        ORDER BY a.id DESC
+        LIMIT ?3
+        "#,
+    )
+    .bind(DOCUMENT_OPENED)
+    .bind(project_id)
+    .bind(limit.max(1) as i64)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let opened_in_project_id: String = row.try_get("opened_in_project_id")?;
+            let opened_at: String = row.try_get("opened_at")?;
+            Ok(RecentDocument {
+                opened_in_project_id,
+                opened_at: parse_timestamp(opened_at)?,
+                document: documents::map_document_summary(row)?,
+            })
+        })
+        .collect()
+}
+
+/// Queries most recently asked, newest first, a repeated question listed once
+/// per project with its latest run; in `project_id` only when given.
+pub async fn recent_queries(
+    pool: &SqlitePool,
+    project_id: Option<&str>,
+    limit: usize,
+) -> AppResult<Vec<RecentQuery>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT project_id, run_id, query, created_at
+        FROM activity
+        WHERE id IN (
+          SELECT MAX(id) FROM activity
+          WHERE kind = ?1 AND (?2 IS NULL OR project_id = ?2)
+          GROUP BY project_id, query
+        )
+        ORDER BY id DESC
+        LIMIT ?3
+        "#,
+    )
+    .bind(QUERY_ASKED)
+    .bind(project_id)
+    .bind(limit.max(1) as i64)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let created_at: String = row.try_get("created_at")?;
+            Ok(RecentQuery {
+                project_id: row.try_get("project_id")?,
+                run_id: row.try_get("run_id")?,
+                query: row.try_get("query")?,
+                asked_at: parse_timestamp(created_at)?,
+            })
+        })
+        .collect()
+}
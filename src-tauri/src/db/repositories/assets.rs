@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use base64::Engine;
+use serde_json::{json, Value};
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{AssetGcReport, AssetRole, GetAssetResponse},
+    },
+    db::{asset_store, retry_busy, Database},
+    providers::ImagePart,
+};
+
+/// How long an asset nothing refers to is kept before [`collect_garbage`]
+/// removes it; long enough for an ingest to store an image and then write
+/// the node that refers to it.
+pub const GC_GRACE: Duration = Duration::from_secs(600);
+
+/// Stores `bytes` in the database's asset store and returns their id.
+/// Storing an asset again renews it, so a collection in between spares it.
+pub async fn put_asset(db: &Database, mime: &str, bytes: &[u8]) -> AppResult<String> {
+    let asset_id = asset_store::asset_id(bytes);
+    let id = asset_id.as_str();
+    // The row before the file: a collection removes files while holding the
+    // writer, so once the row is renewed the file cannot go from under it.
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO assets (id, mime, byte_len)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(id) DO UPDATE SET
+              touched_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            "#,
+        )
+        .bind(id)
+        .bind(mime)
+        .bind(bytes.len() as i64)
+        .execute(db.writer())
+        .await?;
+        Ok(())
+    })
+    .await?;
+    db.assets().put(bytes)?;
+    Ok(asset_id)
+}
+
+/// Makes `asset_id` the node's asset for `role` by recording it in the node's
+/// metadata, replacing the one it had.
+pub async fn attach_asset(
+    pool: &SqlitePool,
+    node_id: &str,
+    role: AssetRole,
+    asset_id: &str,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        let metadata_json: String =
+            sqlx::query_scalar("SELECT metadata_json FROM doc_nodes WHERE id = ?1")
+                .bind(node_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("node {node_id}")))?;
+        let mut metadata = match serde_json::from_str(&metadata_json) {
+            Ok(Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        let mut entries = match metadata.remove("assets") {
+            Some(Value::Array(entries)) => entries,
+            _ => vec![],
+        };
+        entries.retain(|entry| entry["role"] != role.as_str());
+        entries.push(json!({ "assetId": asset_id, "role": role.as_str() }));
+        metadata.insert("assets".to_string(), Value::Array(entries));
+        sqlx::query("UPDATE doc_nodes SET metadata_json = ?2 WHERE id = ?1")
+            .bind(node_id)
+            .bind(Value::Object(metadata).to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Keeps `image` as the Figure node's picture.
+pub async fn put_node_image(db: &Database, node_id: &str, image: &ImagePart) -> AppResult<()> {
+    let asset_id = put_asset(db, &image.mime, &image.data).await?;
+    attach_asset(db.writer(), node_id, AssetRole::Figure, &asset_id).await
+}
+
+pub async fn get_node_image(db: &Database, node_id: &str) -> AppResult<Option<ImagePart>> {
+    if let Some((_, image)) = node_asset(db, node_id, AssetRole::Figure).await? {
+        return Ok(Some(image));
+    }
+
+    // Images stored before the asset store stay in the database.
+    let row = sqlx::query("SELECT mime, bytes FROM node_assets WHERE node_id = ?1")
+        .bind(node_id)
+        .fetch_optional(db.pool())
+        .await?;
+    match row {
+        Some(row) => Ok(Some(ImagePart {
+            mime: row.try_get("mime")?,
+            data: row.try_get("bytes")?,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// The asset a node keeps for `role`, with its id; `None` when it has none
+/// or its file is gone.
+async fn node_asset(
+    db: &Database,
+    node_id: &str,
+    role: AssetRole,
+) -> AppResult<Option<(String, ImagePart)>> {
+    let row = sqlx::query(
+        r#"
+        SELECT a.id, a.mime
+        FROM asset_refs r
+        JOIN assets a ON a.id = r.asset_id
+        WHERE r.node_id = ?1 AND r.role = ?2
+        "#,
+    )
+    .bind(node_id)
+    .bind(role.as_str())
+    .fetch_optional(db.pool())
+    .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let asset_id: String = row.try_get("id")?;
+    match db.assets().read(&asset_id) {
+        Ok(data) => Ok(Some((
+            asset_id,
+            ImagePart {
+                mime: row.try_get("mime")?,
+                data,
+            },
+        ))),
+        Err(AppError::NotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+async fn root_node_id(pool: &SqlitePool, document_id: &str) -> AppResult<String> {
+    sqlx::query_scalar(
+        r#"
+        SELECT id FROM doc_nodes
+        WHERE document_id = ?1 AND parent_id IS NULL
+        ORDER BY ordinal_path
+        LIMIT 1
+        "#,
+    )
+    .bind(document_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("root node of document {document_id}")))
+}
+
+/// Keeps `image` as the document's thumbnail, on its root node.
+pub async fn put_document_thumbnail(
+    db: &Database,
+    document_id: &str,
+    image: &ImagePart,
+) -> AppResult<GetAssetResponse> {
+    let root_id = root_node_id(db.writer(), document_id).await?;
+    let asset_id = put_asset(db, &image.mime, &image.data).await?;
+    attach_asset(db.writer(), &root_id, AssetRole::Thumbnail, &asset_id).await?;
+    Ok(asset_response(asset_id, image.clone()))
+}
+
+pub async fn get_document_thumbnail(
+    db: &Database,
+    document_id: &str,
+) -> AppResult<Option<GetAssetResponse>> {
+    let root_id = root_node_id(db.pool(), document_id).await?;
+    Ok(node_asset(db, &root_id, AssetRole::Thumbnail)
+        .await?
+        .map(|(asset_id, image)| asset_response(asset_id, image)))
+}
+
+/// Images stored for `node_ids`, in the given order, skipping nodes without one.
+pub async fn get_node_images(
+    db: &Database,
+    node_ids: &[String],
+) -> AppResult<Vec<(String, ImagePart)>> {
+    let mut images = vec![];
+    for node_id in node_ids {
+        if let Some(image) = get_node_image(db, node_id).await? {
+            images.push((node_id.clone(), image));
+        }
+    }
+    Ok(images)
+}
+
+pub async fn get_asset(db: &Database, asset_id: &str) -> AppResult<GetAssetResponse> {
+    let mime: String = sqlx::query_scalar("SELECT mime FROM assets WHERE id = ?1")
+        .bind(asset_id)
+        .fetch_optional(db.pool())
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("asset {asset_id}")))?;
+    let data = db.assets().read(asset_id)?;
+    Ok(asset_response(
+        asset_id.to_string(),
+        ImagePart { mime, data },
+    ))
+}
+
+/// Fails for an asset only locked documents refer to, until one of them is
+/// unlocked. Identical images share an asset, so one an open document also
+/// uses stays readable.
+pub async fn ensure_asset_unlocked(pool: &SqlitePool, asset_id: &str) -> AppResult<()> {
+    let (referring, readable): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+          COUNT(*),
+          COALESCE(SUM(d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER)), 0)
+        FROM asset_refs r
+        JOIN doc_nodes n ON n.id = r.node_id
+        JOIN documents d ON d.id = n.document_id
+        WHERE r.asset_id = ?1
+        "#,
+    )
+    .bind(asset_id)
+    .fetch_one(pool)
+    .await?;
+    if referring > 0 && readable == 0 {
+        return Err(AppError::InvalidInput(format!(
+            "asset {asset_id} belongs to a locked document; unlock it first"
+        )));
+    }
+    Ok(())
+}
+
+fn asset_response(asset_id: String, image: ImagePart) -> GetAssetResponse {
+    GetAssetResponse {
+        asset_id,
+        byte_len: image.data.len() as i64,
+        data_url: format!(
+            "data:{};base64,{}",
+            image.mime,
+            base64::engine::general_purpose::STANDARD.encode(&image.data)
+        ),
+        mime: image.mime,
+    }
+}
+
+/// Removes assets no node has referred to for at least `min_age`, rows and
+/// files both. Run after documents or nodes are deleted.
+pub async fn collect_garbage(db: &Database, min_age: Duration) -> AppResult<AssetGcReport> {
+    let modifier = format!("-{} seconds", min_age.as_secs());
+    let modifier = modifier.as_str();
+    retry_busy(|| async move {
+        let mut tx = db.writer().begin().await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, byte_len
+            FROM assets
+            WHERE touched_at <= strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?1)
+              AND id NOT IN (SELECT asset_id FROM asset_refs)
+            "#,
+        )
+        .bind(modifier)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut report = AssetGcReport::default();
+        for row in rows {
+            let asset_id: String = row.try_get("id")?;
+            sqlx::query("DELETE FROM assets WHERE id = ?1")
+                .bind(&asset_id)
+                .execute(&mut *tx)
+                .await?;
+            db.assets().remove(&asset_id)?;
+            report.removed += 1;
+            report.bytes_freed += row.try_get::<i64, _>("byte_len")?;
+        }
+        tx.commit().await?;
+        Ok(report)
+    })
+    .await
+}
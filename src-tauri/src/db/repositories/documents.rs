@@ -1,14 +1,24 @@
 use std::{collections::HashSet, path::Path};
 
 use chrono::{DateTime, Utc};
-use sqlx::{QueryBuilder, Row, SqlitePool};
+use sqlx::{QueryBuilder, Row, SqliteConnection, SqlitePool};
 
 use crate::{
     core::{
         errors::{AppError, AppResult},
-        types::{DocNodeDetail, DocNodeSummary, DocumentSummary, GraphNodePosition, NodeType},
+        types::{
+            CorpusChange, CorpusChangedEvent, CorpusStats, DateRange, DocNodeDetail,
+            DocNodeSummary, DocumentSummary, GetProjectTreeDeltaResponse, GlobalSearchHit,
+            GraphNodePosition, NodeRef, NodeType, ProjectSearchGroup,
+        },
+    },
+    db::{node_text, repositories::node_types, retry_busy},
+    sidecar::{
+        anchors::{self, NodeFingerprint, NodeRemap},
+        quantities::{Quantity, QuantityFilter},
+        references::Reference,
+        types::SidecarNode,
     },
-    sidecar::types::SidecarNode,
 };
 
 fn parse_timestamp(value: String) -> AppResult<DateTime<Utc>> {
@@ -23,7 +33,13 @@ pub async fn find_by_checksum(
     checksum: &str,
 ) -> AppResult<Option<DocumentSummary>> {
     let maybe_row = sqlx::query(
-        "SELECT id, project_id, name, mime, checksum, pages, created_at FROM documents WHERE project_id = ?1 AND checksum = ?2",
+        r#"
+        SELECT id, project_id, name, mime, checksum, pages, hidden, locked, unlocked_until, created_at,
+               source_path, last_ingested_at
+        FROM documents
+        WHERE checksum = ?2
+          AND (project_id = ?1 OR id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+        "#,
     )
     .bind(project_id)
     .bind(checksum)
@@ -35,6 +51,117 @@ pub async fn find_by_checksum(
         .transpose()
 }
 
+/// Oldest document with this checksum in any project, for sharing a parse
+/// across projects. Documents of read-only shared bundles are not offered.
+pub async fn find_document_anywhere(
+    pool: &SqlitePool,
+    checksum: &str,
+) -> AppResult<Option<DocumentSummary>> {
+    let maybe_row = sqlx::query(
+        r#"
+        SELECT id, project_id, name, mime, checksum, pages, hidden, locked, unlocked_until, created_at,
+               source_path, last_ingested_at
+        FROM documents
+        WHERE checksum = ?1
+          AND project_id NOT IN (SELECT id FROM projects WHERE read_only = 1)
+        ORDER BY created_at ASC
+        LIMIT 1
+        "#,
+    )
+    .bind(checksum)
+    .fetch_optional(pool)
+    .await?;
+
+    maybe_row.map(map_document_summary).transpose()
+}
+
+/// Makes a document owned by another project visible in `project_id`.
+pub async fn link_document(
+    pool: &SqlitePool,
+    project_id: &str,
+    document_id: &str,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO document_links (project_id, document_id)
+            SELECT ?1, id FROM documents WHERE id = ?2 AND project_id != ?1
+            ON CONFLICT(project_id, document_id) DO NOTHING
+            "#,
+        )
+        .bind(project_id)
+        .bind(document_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Removes a document from one project. A linked copy only loses its link; the
+/// owner's copy passes ownership to the earliest linked project, and the
+/// document is deleted once no project holds it.
+pub async fn remove_document_from_project(
+    pool: &SqlitePool,
+    project_id: &str,
+    document_id: &str,
+) -> AppResult<bool> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        let unlinked =
+            sqlx::query("DELETE FROM document_links WHERE project_id = ?1 AND document_id = ?2")
+                .bind(project_id)
+                .bind(document_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        if unlinked > 0 {
+            tx.commit().await?;
+            return Ok(true);
+        }
+
+        let owned = sqlx::query("SELECT 1 FROM documents WHERE id = ?1 AND project_id = ?2")
+            .bind(document_id)
+            .bind(project_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .is_some();
+        if !owned {
+            return Ok(false);
+        }
+        let heir = sqlx::query(
+            "SELECT project_id FROM document_links WHERE document_id = ?1 ORDER BY created_at ASC, project_id LIMIT 1",
+        )
+        .bind(document_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        match heir {
+            Some(row) => {
+                let heir: String = row.try_get("project_id")?;
+                sqlx::query("UPDATE documents SET project_id = ?2 WHERE id = ?1")
+                    .bind(document_id)
+                    .bind(&heir)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("DELETE FROM document_links WHERE project_id = ?1 AND document_id = ?2")
+                    .bind(&heir)
+                    .bind(document_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM documents WHERE id = ?1")
+                    .bind(document_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(true)
+    })
+    .await
+}
+
 pub async fn insert_document(
     pool: &SqlitePool,
     id: &str,
@@ -44,28 +171,165 @@ pub async fn insert_document(
     checksum: &str,
     pages: i64,
 ) -> AppResult<()> {
-    sqlx::query(
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO documents (id, project_id, name, mime, checksum, pages)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(id)
+        .bind(project_id)
+        .bind(name)
+        .bind(mime)
+        .bind(checksum)
+        .bind(pages)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Records that the document was just ingested from the file at
+/// `source_path`.
+pub async fn set_ingest_source(
+    pool: &SqlitePool,
+    document_id: &str,
+    source_path: &str,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE documents
+            SET source_path = ?2,
+                last_ingested_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+            WHERE id = ?1
+            "#,
+        )
+        .bind(document_id)
+        .bind(source_path)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Replaces the document's tags.
+pub async fn set_tags(pool: &SqlitePool, document_id: &str, tags: &[String]) -> AppResult<()> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM document_tags WHERE document_id = ?1")
+            .bind(document_id)
+            .execute(&mut *tx)
+            .await?;
+        for tag in tags {
+            sqlx::query(
+                r#"
+                INSERT INTO document_tags (document_id, tag)
+                VALUES (?1, ?2)
+                ON CONFLICT(document_id, tag) DO NOTHING
+                "#,
+            )
+            .bind(document_id)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn get_tags(pool: &SqlitePool, document_id: &str) -> AppResult<Vec<String>> {
+    let tags =
+        sqlx::query_scalar("SELECT tag FROM document_tags WHERE document_id = ?1 ORDER BY tag")
+            .bind(document_id)
+            .fetch_all(pool)
+            .await?;
+    Ok(tags)
+}
+
+/// Links `attachment_id` to the document that embeds or links to it. Nothing
+/// is linked unless both documents exist.
+pub async fn link_attachment(
+    pool: &SqlitePool,
+    document_id: &str,
+    attachment_id: &str,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO document_attachments (document_id, attachment_id)
+            SELECT ?1, ?2
+            WHERE ?1 != ?2
+              AND EXISTS (SELECT 1 FROM documents WHERE id = ?1)
+              AND EXISTS (SELECT 1 FROM documents WHERE id = ?2)
+            ON CONFLICT(document_id, attachment_id) DO NOTHING
+            "#,
+        )
+        .bind(document_id)
+        .bind(attachment_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn get_attachment_ids(pool: &SqlitePool, document_id: &str) -> AppResult<Vec<String>> {
+    let ids = sqlx::query_scalar(
+        "SELECT attachment_id FROM document_attachments WHERE document_id = ?1 ORDER BY attachment_id",
+    )
+    .bind(document_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+/// Every attachment link the document is on either side of, as
+/// `(document_id, attachment_id)`.
+pub async fn attachment_links(
+    pool: &SqlitePool,
+    document_id: &str,
+) -> AppResult<Vec<(String, String)>> {
+    let rows = sqlx::query(
         r#"
-        INSERT INTO documents (id, project_id, name, mime, checksum, pages)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        SELECT document_id, attachment_id
+        FROM document_attachments
+        WHERE document_id = ?1 OR attachment_id = ?1
+        ORDER BY document_id, attachment_id
         "#,
     )
-    .bind(id)
-    .bind(project_id)
-    .bind(name)
-    .bind(mime)
-    .bind(checksum)
-    .bind(pages)
-    .execute(pool)
+    .bind(document_id)
+    .fetch_all(pool)
     .await?;
-    Ok(())
+    rows.into_iter()
+        .map(|row| Ok((row.try_get("document_id")?, row.try_get("attachment_id")?)))
+        .collect()
 }
 
-pub async fn list_documents(pool: &SqlitePool, project_id: &str) -> AppResult<Vec<DocumentSummary>> {
+/// Documents of the project, newest first; hidden ones only with
+/// `include_hidden`.
+pub async fn list_documents(
+    pool: &SqlitePool,
+    project_id: &str,
+    include_hidden: bool,
+) -> AppResult<Vec<DocumentSummary>> {
     let rows = sqlx::query(
-        "SELECT id, project_id, name, mime, checksum, pages, created_at FROM documents WHERE project_id = ?1 ORDER BY created_at DESC",
+        r#"
+        SELECT id, project_id, name, mime, checksum, pages, hidden, locked, unlocked_until, created_at,
+               source_path, last_ingested_at
+        FROM documents
+        WHERE (project_id = ?1 OR id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (?2 OR hidden = 0)
+        ORDER BY created_at DESC
+        "#,
     )
     .bind(project_id)
+    .bind(include_hidden)
     .fetch_all(pool)
     .await?;
 
@@ -74,7 +338,12 @@ pub async fn list_documents(pool: &SqlitePool, project_id: &str) -> AppResult<Ve
 
 pub async fn get_document(pool: &SqlitePool, document_id: &str) -> AppResult<DocumentSummary> {
     let row = sqlx::query(
-        "SELECT id, project_id, name, mime, checksum, pages, created_at FROM documents WHERE id = ?1",
+        r#"
+        SELECT id, project_id, name, mime, checksum, pages, hidden, locked, unlocked_until, created_at,
+               source_path, last_ingested_at
+        FROM documents
+        WHERE id = ?1
+        "#,
     )
     .bind(document_id)
     .fetch_optional(pool)
@@ -84,40 +353,368 @@ pub async fn get_document(pool: &SqlitePool, document_id: &str) -> AppResult<Doc
     map_document_summary(row)
 }
 
+/// Sets the document's `hidden` and `locked` flags. Changing `locked` ends
+/// any unlock still running.
+pub async fn set_visibility(
+    pool: &SqlitePool,
+    document_id: &str,
+    hidden: bool,
+    locked: bool,
+) -> AppResult<DocumentSummary> {
+    let changed = retry_busy(|| async move {
+        let result = sqlx::query(
+            r#"
+            UPDATE documents
+            SET hidden = ?2,
+                locked = ?3,
+                unlocked_until = CASE WHEN locked = ?3 THEN unlocked_until ELSE NULL END
+            WHERE id = ?1
+            "#,
+        )
+        .bind(document_id)
+        .bind(hidden)
+        .bind(locked)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    })
+    .await?;
+    if changed == 0 {
+        return Err(AppError::NotFound(format!("document {document_id}")));
+    }
+    get_document(pool, document_id).await
+}
+
+/// Unlocks a locked document for `seconds`; previews and retrieval include
+/// it until then.
+pub async fn unlock_document(
+    pool: &SqlitePool,
+    document_id: &str,
+    seconds: i64,
+) -> AppResult<DocumentSummary> {
+    let until = Utc::now().timestamp() + seconds.max(1);
+    retry_busy(|| async move {
+        sqlx::query("UPDATE documents SET unlocked_until = ?2 WHERE id = ?1 AND locked = 1")
+            .bind(document_id)
+            .bind(until)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await?;
+    get_document(pool, document_id).await
+}
+
+/// Ends an unlock before it runs out.
+pub async fn relock_document(pool: &SqlitePool, document_id: &str) -> AppResult<DocumentSummary> {
+    retry_busy(|| async move {
+        sqlx::query("UPDATE documents SET unlocked_until = NULL WHERE id = ?1")
+            .bind(document_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    })
+    .await?;
+    get_document(pool, document_id).await
+}
+
+/// Fails for a locked document that is not unlocked, before its content is
+/// shown or reasoned over.
+pub async fn ensure_unlocked(pool: &SqlitePool, document_id: &str) -> AppResult<()> {
+    let document = get_document(pool, document_id).await?;
+    if document.locked && !document.unlocked {
+        return Err(AppError::InvalidInput(format!(
+            "document {document_id} is locked; unlock it first"
+        )));
+    }
+    Ok(())
+}
+
 pub async fn insert_nodes(
     pool: &SqlitePool,
     document_id: &str,
     nodes: &[SidecarNode],
 ) -> AppResult<()> {
-    let mut tx = pool.begin().await?;
-    for node in nodes {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        let revision = next_tree_revision(&mut tx).await?;
+        for node in nodes {
+            insert_node(&mut tx, document_id, node, revision).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Revisions of the tree whose removals are kept for clients catching up;
+/// one that last saw an older revision gets the whole tree again.
+pub const TREE_HISTORY_REVISIONS: i64 = 10_000;
+
+/// Takes the tree revision for a batch of node writes, so a whole ingest
+/// moves the tree on by one, and forgets removals past the history kept.
+async fn next_tree_revision(conn: &mut SqliteConnection) -> AppResult<i64> {
+    let revision: i64 = sqlx::query_scalar(
+        "UPDATE tree_revision SET value = value + 1 WHERE id = 1 RETURNING value",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    forget_tree_history(conn, TREE_HISTORY_REVISIONS).await?;
+    Ok(revision)
+}
+
+/// Deletes the tombstones and document events of all but the last
+/// `keep_revisions` revisions; see [`get_project_tree_delta`].
+pub async fn prune_tree_history(pool: &SqlitePool, keep_revisions: i64) -> AppResult<()> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        forget_tree_history(&mut tx, keep_revisions).await?;
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+async fn forget_tree_history(conn: &mut SqliteConnection, keep_revisions: i64) -> AppResult<()> {
+    let through: Option<i64> = sqlx::query_scalar(
+        r#"
+        UPDATE tree_revision SET pruned_through = value - ?1
+        WHERE id = 1 AND pruned_through < value - ?1
+        RETURNING pruned_through
+        "#,
+    )
+    .bind(keep_revisions)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(through) = through else {
+        return Ok(());
+    };
+    sqlx::query("DELETE FROM doc_node_tombstones WHERE revision <= ?1")
+        .bind(through)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM tree_document_events WHERE revision <= ?1")
+        .bind(through)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+async fn insert_node(
+    conn: &mut SqliteConnection,
+    document_id: &str,
+    node: &SidecarNode,
+    revision: i64,
+) -> AppResult<()> {
+    let compressed = node_text::compress(&node.text)?;
+    let stored_text = if compressed.is_some() {
+        ""
+    } else {
+        node.text.as_str()
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO doc_nodes (
+          id, document_id, parent_id, node_type, title, text, text_zstd, page_start, page_end,
+          bbox_json, metadata_json, ordinal_path, revision
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+        "#,
+    )
+    .bind(&node.id)
+    .bind(document_id)
+    .bind(&node.parent_id)
+    .bind(node.node_type.as_str())
+    .bind(&node.title)
+    .bind(stored_text)
+    .bind(&compressed)
+    .bind(node.page_start)
+    .bind(node.page_end)
+    .bind(node.bbox.to_string())
+    .bind(node.metadata.to_string())
+    .bind(&node.ordinal_path)
+    .bind(revision)
+    .execute(&mut *conn)
+    .await?;
+    if compressed.is_some() {
+        // The index trigger skips compressed nodes; index the plain text here.
         sqlx::query(
-            r#"
-            INSERT INTO doc_nodes (
-              id, document_id, parent_id, node_type, title, text, page_start, page_end,
-              bbox_json, metadata_json, ordinal_path
-            )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-            "#,
+            "INSERT INTO doc_nodes_fts (node_id, document_id, title, text) VALUES (?1, ?2, ?3, ?4)",
         )
         .bind(&node.id)
         .bind(document_id)
-        .bind(&node.parent_id)
-        .bind(node.node_type.as_str())
         .bind(&node.title)
         .bind(&node.text)
-        .bind(node.page_start)
-        .bind(node.page_end)
-        .bind(node.bbox.to_string())
-        .bind(node.metadata.to_string())
-        .bind(&node.ordinal_path)
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await?;
     }
-    tx.commit().await?;
     Ok(())
 }
 
+/// Compresses the long text of up to `limit` nodes stored before compression
+/// was added. Returns how many nodes were compressed and the bytes saved;
+/// call until it compresses none. The search index keeps the plain text.
+pub async fn compress_stored_text(pool: &SqlitePool, limit: i64) -> AppResult<(usize, i64)> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, text
+            FROM doc_nodes
+            WHERE text_zstd IS NULL AND length(CAST(text AS BLOB)) >= ?1
+            LIMIT ?2
+            "#,
+        )
+        .bind(node_text::COMPRESS_MIN_BYTES as i64)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut compressed_nodes = 0;
+        let mut saved = 0_i64;
+        for row in rows {
+            let id: String = row.try_get("id")?;
+            let text: String = row.try_get("text")?;
+            // Text that does not shrink stays plain and is offered again next
+            // time; that is rare enough not to track.
+            let Some(bytes) = node_text::compress(&text)? else {
+                continue;
+            };
+            sqlx::query("UPDATE doc_nodes SET text = '', text_zstd = ?2 WHERE id = ?1")
+                .bind(&id)
+                .bind(&bytes)
+                .execute(&mut *tx)
+                .await?;
+            compressed_nodes += 1;
+            saved += (text.len() - bytes.len()) as i64;
+        }
+        tx.commit().await?;
+        Ok((compressed_nodes, saved))
+    })
+    .await
+}
+
+/// Fingerprints of the parser-produced nodes of a document; nodes added by
+/// enrichment passes are left out since re-ingestion never reproduces them.
+pub async fn get_node_fingerprints(
+    pool: &SqlitePool,
+    document_id: &str,
+) -> AppResult<Vec<NodeFingerprint>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, node_type, ordinal_path, title, text, text_zstd
+        FROM doc_nodes
+        WHERE document_id = ?1
+          AND COALESCE(json_extract(metadata_json, '$.parser'), '') != 'enrichment'
+        ORDER BY ordinal_path
+        "#,
+    )
+    .bind(document_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let node_type: String = row.try_get("node_type")?;
+            let title: String = row.try_get("title")?;
+            let text = node_text::from_row(&row)?;
+            Ok(NodeFingerprint {
+                id: row.try_get("id")?,
+                ordinal_path: row.try_get("ordinal_path")?,
+                content_hash: anchors::content_hash(&node_type, &title, &text),
+                node_type,
+            })
+        })
+        .collect()
+}
+
+/// Swaps a document's nodes for a re-ingested version in one transaction and
+/// records which old ids the new nodes replace. Existing remaps pointing at a
+/// replaced id are forwarded so every old id resolves in one hop.
+pub async fn replace_document_nodes(
+    pool: &SqlitePool,
+    document_id: &str,
+    checksum: &str,
+    mime: &str,
+    pages: i64,
+    nodes: &[SidecarNode],
+    remaps: &[NodeRemap],
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE documents SET checksum = ?2, mime = ?3, pages = ?4 WHERE id = ?1")
+            .bind(document_id)
+            .bind(checksum)
+            .bind(mime)
+            .bind(pages)
+            .execute(&mut *tx)
+            .await?;
+        // Tombstoned here in one statement rather than by the delete trigger
+        // row by row.
+        let revision = next_tree_revision(&mut tx).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO doc_node_tombstones (node_id, document_id, revision)
+            SELECT id, document_id, ?2 FROM doc_nodes WHERE document_id = ?1
+            "#,
+        )
+        .bind(document_id)
+        .bind(revision)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM doc_nodes WHERE document_id = ?1")
+            .bind(document_id)
+            .execute(&mut *tx)
+            .await?;
+        for node in nodes {
+            insert_node(&mut tx, document_id, node, revision).await?;
+        }
+        for remap in remaps {
+            sqlx::query("UPDATE node_id_remaps SET new_node_id = ?2 WHERE new_node_id = ?1")
+                .bind(&remap.old_id)
+                .bind(&remap.new_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO node_id_remaps (old_node_id, new_node_id, document_id)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(old_node_id) DO UPDATE SET
+                  new_node_id = excluded.new_node_id,
+                  created_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+                "#,
+            )
+            .bind(&remap.old_id)
+            .bind(&remap.new_id)
+            .bind(document_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            r#"
+            DELETE FROM node_id_remaps
+            WHERE document_id = ?1
+              AND old_node_id IN (SELECT id FROM doc_nodes WHERE document_id = ?1)
+            "#,
+        )
+        .bind(document_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Current id of a node that was replaced by re-ingestion, if any.
+pub async fn remapped_node_id(pool: &SqlitePool, node_id: &str) -> AppResult<Option<String>> {
+    let row = sqlx::query("SELECT new_node_id FROM node_id_remaps WHERE old_node_id = ?1")
+        .bind(node_id)
+        .fetch_optional(pool)
+        .await?;
+    row.map(|row| row.try_get("new_node_id").map_err(AppError::from))
+        .transpose()
+}
+
 pub async fn get_tree(
     pool: &SqlitePool,
     document_id: &str,
@@ -128,7 +725,7 @@ pub async fn get_tree(
         let rows = if let Some(parent) = parent_id {
             sqlx::query(
                 r#"
-                SELECT id, document_id, parent_id, node_type, title, text, ordinal_path, page_start, page_end
+                SELECT id, document_id, parent_id, node_type, title, text, text_zstd, ordinal_path, page_start, page_end
                 FROM doc_nodes
                 WHERE document_id = ?1 AND parent_id = ?2
                 ORDER BY ordinal_path
@@ -141,7 +738,7 @@ pub async fn get_tree(
         } else {
             sqlx::query(
                 r#"
-                SELECT id, document_id, parent_id, node_type, title, text, ordinal_path, page_start, page_end
+                SELECT id, document_id, parent_id, node_type, title, text, text_zstd, ordinal_path, page_start, page_end
                 FROM doc_nodes
                 WHERE document_id = ?1 AND parent_id IS NULL
                 ORDER BY ordinal_path
@@ -171,7 +768,7 @@ pub async fn get_tree(
           JOIN tree ON child.parent_id = tree.id
           WHERE child.document_id = ?1 AND tree.depth < ?3
         )
-        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.ordinal_path, dn.page_start, dn.page_end
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end
         FROM doc_nodes dn
         JOIN tree ON dn.id = tree.id
         ORDER BY CASE WHEN dn.parent_id IS NULL THEN 0 ELSE 1 END, dn.ordinal_path
@@ -195,10 +792,12 @@ pub async fn get_project_tree(
     if depth <= 1 {
         let rows = sqlx::query(
             r#"
-            SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.ordinal_path, dn.page_start, dn.page_end
+            SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end
             FROM doc_nodes dn
             JOIN documents d ON d.id = dn.document_id
-            WHERE d.project_id = ?1 AND dn.parent_id IS NULL
+            WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+              AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+              AND dn.parent_id IS NULL
             ORDER BY d.created_at ASC, dn.ordinal_path
             "#,
         )
@@ -214,14 +813,16 @@ pub async fn get_project_tree(
           SELECT dn.id, 0
           FROM doc_nodes dn
           JOIN documents d ON d.id = dn.document_id
-          WHERE d.project_id = ?1 AND dn.parent_id IS NULL
+          WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+            AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+            AND dn.parent_id IS NULL
           UNION ALL
           SELECT child.id, tree.depth + 1
           FROM doc_nodes child
           JOIN tree ON child.parent_id = tree.id
           WHERE tree.depth < ?2
         )
-        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.ordinal_path, dn.page_start, dn.page_end
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end
         FROM doc_nodes dn
         JOIN documents d ON d.id = dn.document_id
         JOIN tree ON dn.id = tree.id
@@ -229,10 +830,118 @@ pub async fn get_project_tree(
         "#,
     )
     .bind(project_id)
-    .bind(depth)
-    .fetch_all(pool)
+    .bind(depth)
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter().map(map_node_summary).collect()
+}
+
+/// Nodes of `project_id`'s visible documents that changed after
+/// `since_revision`, with what was removed since; see
+/// [`GetProjectTreeDeltaResponse`]. A revision of 0, one newer than the
+/// database's, or one older than the removals still kept gets the whole tree.
+/// Removals are kept for the last [`TREE_HISTORY_REVISIONS`] revisions.
+pub async fn get_project_tree_delta(
+    pool: &SqlitePool,
+    project_id: &str,
+    since_revision: i64,
+) -> AppResult<GetProjectTreeDeltaResponse> {
+    // One read transaction, so the revision matches the rows read with it.
+    let mut tx = pool.begin().await?;
+    let (revision, pruned_through): (i64, i64) =
+        sqlx::query_as("SELECT value, pruned_through FROM tree_revision WHERE id = 1")
+            .fetch_one(&mut *tx)
+            .await?;
+    let full = since_revision <= 0 || since_revision > revision || since_revision < pruned_through;
+    // Nodes stored before revisions were tracked are at revision 0.
+    let since = if full { -1 } else { since_revision };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end
+        FROM doc_nodes dn
+        JOIN documents d ON d.id = dn.document_id
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+          AND (dn.revision > ?2
+               OR d.id IN (
+                 SELECT document_id FROM tree_document_events
+                 WHERE project_id = ?1 AND present = 1 AND revision > ?2
+               ))
+        ORDER BY d.created_at ASC, CASE WHEN dn.parent_id IS NULL THEN 0 ELSE 1 END, dn.ordinal_path
+        "#,
+    )
+    .bind(project_id)
+    .bind(since)
+    .fetch_all(&mut *tx)
+    .await?;
+    let nodes = rows
+        .into_iter()
+        .map(map_node_summary)
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let (removed_node_ids, removed_document_ids) = if full {
+        (Vec::new(), Vec::new())
+    } else {
+        // A reingest can put back a node under the id it removed.
+        let removed_node_ids = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT t.node_id
+            FROM doc_node_tombstones t
+            JOIN documents d ON d.id = t.document_id
+            WHERE t.revision > ?2
+              AND (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+              AND NOT EXISTS (SELECT 1 FROM doc_nodes WHERE id = t.node_id)
+            ORDER BY t.node_id
+            "#,
+        )
+        .bind(project_id)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?;
+        let removed_document_ids = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT e.document_id
+            FROM tree_document_events e
+            WHERE e.project_id = ?1 AND e.present = 0 AND e.revision > ?2
+              AND NOT EXISTS (
+                SELECT 1 FROM documents d
+                WHERE d.id = e.document_id
+                  AND (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+              )
+            ORDER BY e.document_id
+            "#,
+        )
+        .bind(project_id)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?;
+        (removed_node_ids, removed_document_ids)
+    };
+
+    let locked_document_ids = sqlx::query_scalar(
+        r#"
+        SELECT d.id
+        FROM documents d
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND d.locked = 1
+          AND (d.unlocked_until IS NULL OR d.unlocked_until <= CAST(strftime('%s', 'now') AS INTEGER))
+        ORDER BY d.id
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(&mut *tx)
     .await?;
-    rows.into_iter().map(map_node_summary).collect()
+    tx.commit().await?;
+
+    Ok(GetProjectTreeDeltaResponse {
+        revision,
+        full,
+        nodes,
+        removed_node_ids,
+        removed_document_ids,
+        locked_document_ids,
+    })
 }
 
 pub async fn search_project_nodes(
@@ -242,21 +951,41 @@ pub async fn search_project_nodes(
     query: &str,
     limit: usize,
 ) -> AppResult<Vec<DocNodeSummary>> {
+    let scored =
+        score_project_nodes(pool, project_id, focus_document_id, query, 1.0, 1.0, limit).await?;
+    Ok(scored.into_iter().map(|(node, _)| node).collect())
+}
+
+/// [`search_project_nodes`] with a query term counting `title_weight` in a
+/// node's title and `text_weight` in its text, and each hit's relevance:
+/// higher is better, and 0 for hits found by substring after no keyword hit.
+pub async fn score_project_nodes(
+    pool: &SqlitePool,
+    project_id: &str,
+    focus_document_id: Option<&str>,
+    query: &str,
+    title_weight: f64,
+    text_weight: f64,
+    limit: usize,
+) -> AppResult<Vec<(DocNodeSummary, f64)>> {
     let cap = (limit.max(1).min(200)) as i64;
     let Some(match_query) = fts_match_query(query) else {
         return Ok(vec![]);
     };
 
+    // The id columns are unindexed; only title and text carry weight.
     let rows = sqlx::query(
         r#"
-        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.ordinal_path, dn.page_start, dn.page_end
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end,
+               bm25(doc_nodes_fts, 0.0, 0.0, ?5, ?6) AS score
         FROM doc_nodes_fts
         JOIN doc_nodes dn ON dn.id = doc_nodes_fts.node_id
         JOIN documents d ON d.id = dn.document_id
-        WHERE d.project_id = ?1
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
           AND (?2 IS NULL OR dn.document_id = ?2)
           AND doc_nodes_fts MATCH ?3
-        ORDER BY bm25(doc_nodes_fts, 1.2, 1.0) ASC,
+        ORDER BY score ASC,
                  CASE dn.node_type WHEN 'Section' THEN 0 WHEN 'Subsection' THEN 1 ELSE 2 END,
                  dn.ordinal_path
         LIMIT ?4
@@ -266,11 +995,20 @@ pub async fn search_project_nodes(
     .bind(focus_document_id)
     .bind(&match_query)
     .bind(cap)
+    .bind(title_weight)
+    .bind(text_weight)
     .fetch_all(pool)
     .await?;
 
     if !rows.is_empty() {
-        return rows.into_iter().map(map_node_summary).collect();
+        return rows
+            .into_iter()
+            .map(|row| {
+                // bm25 is negative, more so for better matches.
+                let score: f64 = row.try_get("score")?;
+                Ok((map_node_summary(row)?, -score))
+            })
+            .collect();
     }
 
     let Some(like_term) = normalized_terms(query).into_iter().next() else {
@@ -280,12 +1018,15 @@ pub async fn search_project_nodes(
 
     let fallback_rows = sqlx::query(
         r#"
-        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.ordinal_path, dn.page_start, dn.page_end
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end
         FROM doc_nodes dn
         JOIN documents d ON d.id = dn.document_id
-        WHERE d.project_id = ?1
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
           AND (?2 IS NULL OR dn.document_id = ?2)
-          AND (LOWER(dn.title) LIKE ?3 OR LOWER(dn.text) LIKE ?3)
+          -- Compressed text can only be matched in the index.
+          AND (LOWER(dn.title) LIKE ?3
+               OR dn.id IN (SELECT node_id FROM doc_nodes_fts WHERE LOWER(doc_nodes_fts.text) LIKE ?3))
         ORDER BY CASE dn.node_type WHEN 'Section' THEN 0 WHEN 'Subsection' THEN 1 ELSE 2 END,
                  dn.ordinal_path
         LIMIT ?4
@@ -298,14 +1039,258 @@ pub async fn search_project_nodes(
     .fetch_all(pool)
     .await?;
 
-    fallback_rows.into_iter().map(map_node_summary).collect()
+    fallback_rows
+        .into_iter()
+        .map(|row| Ok((map_node_summary(row)?, 0.0)))
+        .collect()
+}
+
+/// Nodes in a project with a quantity in `filter`'s range, read from the
+/// `quantities` metadata recorded at parse time.
+pub async fn search_quantity_nodes(
+    pool: &SqlitePool,
+    project_id: &str,
+    focus_document_id: Option<&str>,
+    filter: &QuantityFilter,
+    limit: usize,
+) -> AppResult<Vec<DocNodeSummary>> {
+    let cap = (limit.max(1).min(200)) as i64;
+    let rows = sqlx::query(
+        r#"
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end
+        FROM doc_nodes dn
+        JOIN documents d ON d.id = dn.document_id
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+          AND (?2 IS NULL OR dn.document_id = ?2)
+          AND EXISTS (
+            SELECT 1 FROM json_each(dn.metadata_json, '$.quantities') q
+            WHERE json_extract(q.value, '$.unit') = ?3
+              AND (?4 IS NULL OR json_extract(q.value, '$.value') > ?4
+                   OR (?5 AND json_extract(q.value, '$.value') = ?4))
+              AND (?6 IS NULL OR json_extract(q.value, '$.value') < ?6
+                   OR (?7 AND json_extract(q.value, '$.value') = ?6))
+          )
+        ORDER BY CASE dn.node_type WHEN 'Section' THEN 0 WHEN 'Subsection' THEN 1 ELSE 2 END,
+                 dn.ordinal_path
+        LIMIT ?8
+        "#,
+    )
+    .bind(project_id)
+    .bind(focus_document_id)
+    .bind(&filter.unit)
+    .bind(filter.min)
+    .bind(filter.min_inclusive)
+    .bind(filter.max)
+    .bind(filter.max_inclusive)
+    .bind(cap)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(map_node_summary).collect()
+}
+
+/// The quantities recorded at parse time for each of `node_ids`, in order,
+/// leaving out nodes without any.
+pub async fn get_node_quantities(
+    pool: &SqlitePool,
+    node_ids: &[String],
+) -> AppResult<Vec<(String, Vec<Quantity>)>> {
+    let mut found = Vec::with_capacity(node_ids.len());
+    for node_id in node_ids {
+        let recorded: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT json_extract(metadata_json, '$.quantities') FROM doc_nodes WHERE id = ?1",
+        )
+        .bind(node_id)
+        .fetch_optional(pool)
+        .await?;
+        let Some(json) = recorded.flatten() else {
+            continue;
+        };
+        let quantities: Vec<Quantity> = serde_json::from_str(&json).unwrap_or_default();
+        if !quantities.is_empty() {
+            found.push((node_id.clone(), quantities));
+        }
+    }
+    Ok(found)
+}
+
+/// The reference metadata recorded at parse time for the project's
+/// documents, oldest first, by document id. Locked documents are left out.
+pub async fn project_references(
+    pool: &SqlitePool,
+    project_id: &str,
+) -> AppResult<Vec<(String, Reference)>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT d.id, json_extract(dn.metadata_json, '$.reference') AS reference
+        FROM documents d
+        JOIN doc_nodes dn ON dn.document_id = d.id AND dn.parent_id IS NULL
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND d.locked = 0
+          AND json_extract(dn.metadata_json, '$.reference') IS NOT NULL
+        ORDER BY d.created_at ASC, d.id
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+    let mut found = Vec::with_capacity(rows.len());
+    for row in rows {
+        let json: String = row.try_get("reference")?;
+        if let Ok(reference) = serde_json::from_str(&json) {
+            found.push((row.try_get("id")?, reference));
+        }
+    }
+    Ok(found)
+}
+
+/// Nodes in a project that mention a date in `range`, read from the `dates`
+/// metadata recorded at parse time; a month or year counts when it overlaps.
+pub async fn search_date_nodes(
+    pool: &SqlitePool,
+    project_id: &str,
+    focus_document_id: Option<&str>,
+    range: &DateRange,
+    limit: usize,
+) -> AppResult<Vec<DocNodeSummary>> {
+    let cap = (limit.max(1).min(200)) as i64;
+    let rows = sqlx::query(
+        r#"
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end
+        FROM doc_nodes dn
+        JOIN documents d ON d.id = dn.document_id
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+          AND (?2 IS NULL OR dn.document_id = ?2)
+          AND EXISTS (
+            SELECT 1 FROM json_each(dn.metadata_json, '$.dates') dates
+            WHERE (?3 IS NULL OR json_extract(dates.value, '$.end') >= ?3)
+              AND (?4 IS NULL OR json_extract(dates.value, '$.start') <= ?4)
+          )
+        ORDER BY CASE dn.node_type WHEN 'Section' THEN 0 WHEN 'Subsection' THEN 1 ELSE 2 END,
+                 dn.ordinal_path
+        LIMIT ?5
+        "#,
+    )
+    .bind(project_id)
+    .bind(focus_document_id)
+    .bind(range.from.map(|date| date.to_string()))
+    .bind(range.to.map(|date| date.to_string()))
+    .bind(cap)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(map_node_summary).collect()
+}
+
+/// [`search_project_nodes`] over every project, for finding which project a
+/// document landed in. At most `limit` hits in all, grouped by project;
+/// archived projects only with `include_archived`. Like the project search
+/// and tree, it leaves out locked documents that are not unlocked.
+pub async fn search_all_nodes(
+    pool: &SqlitePool,
+    query: &str,
+    limit: usize,
+    include_archived: bool,
+) -> AppResult<Vec<ProjectSearchGroup>> {
+    let cap = (limit.max(1).min(200)) as i64;
+    let Some(match_query) = fts_match_query(query) else {
+        return Ok(vec![]);
+    };
+
+    let rows = sqlx::query(
+        r#"
+        WITH visible(project_id, document_id) AS (
+          SELECT project_id, id FROM documents
+          UNION
+          SELECT project_id, document_id FROM document_links
+        )
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end,
+               d.name AS document_name, p.id AS project_id, p.name AS project_name
+        FROM doc_nodes_fts
+        JOIN doc_nodes dn ON dn.id = doc_nodes_fts.node_id
+        JOIN documents d ON d.id = dn.document_id
+        JOIN visible v ON v.document_id = dn.document_id
+        JOIN projects p ON p.id = v.project_id
+        WHERE doc_nodes_fts MATCH ?1
+          AND (?3 OR p.archived = 0)
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+        ORDER BY bm25(doc_nodes_fts, 1.2, 1.0) ASC,
+                 CASE dn.node_type WHEN 'Section' THEN 0 WHEN 'Subsection' THEN 1 ELSE 2 END,
+                 p.name, dn.ordinal_path
+        LIMIT ?2
+        "#,
+    )
+    .bind(&match_query)
+    .bind(cap)
+    .bind(include_archived)
+    .fetch_all(pool)
+    .await?;
+
+    let rows = if rows.is_empty() {
+        let Some(like_term) = normalized_terms(query).into_iter().next() else {
+            return Ok(vec![]);
+        };
+        sqlx::query(
+            r#"
+            WITH visible(project_id, document_id) AS (
+              SELECT project_id, id FROM documents
+              UNION
+              SELECT project_id, document_id FROM document_links
+            )
+            SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd, dn.ordinal_path, dn.page_start, dn.page_end,
+                   d.name AS document_name, p.id AS project_id, p.name AS project_name
+            FROM doc_nodes dn
+            JOIN documents d ON d.id = dn.document_id
+            JOIN visible v ON v.document_id = dn.document_id
+            JOIN projects p ON p.id = v.project_id
+            WHERE (LOWER(dn.title) LIKE ?1
+                   OR dn.id IN (SELECT node_id FROM doc_nodes_fts WHERE LOWER(doc_nodes_fts.text) LIKE ?1))
+              AND (?3 OR p.archived = 0)
+              AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+            ORDER BY CASE dn.node_type WHEN 'Section' THEN 0 WHEN 'Subsection' THEN 1 ELSE 2 END,
+                     p.name, dn.ordinal_path
+            LIMIT ?2
+            "#,
+        )
+        .bind(format!("%{like_term}%"))
+        .bind(cap)
+        .bind(include_archived)
+        .fetch_all(pool)
+        .await?
+    } else {
+        rows
+    };
+
+    let mut groups: Vec<ProjectSearchGroup> = vec![];
+    for row in rows {
+        let project_id: String = row.try_get("project_id")?;
+        let project_name: String = row.try_get("project_name")?;
+        let hit = GlobalSearchHit {
+            document_name: row.try_get("document_name")?,
+            node: map_node_summary(row)?,
+        };
+        match groups
+            .iter_mut()
+            .find(|group| group.project_id == project_id)
+        {
+            Some(group) => group.hits.push(hit),
+            None => groups.push(ProjectSearchGroup {
+                project_id,
+                project_name,
+                hits: vec![hit],
+            }),
+        }
+    }
+    Ok(groups)
 }
 
 pub async fn get_document_preview(pool: &SqlitePool, document_id: &str) -> AppResult<Vec<DocNodeSummary>> {
     let _ = get_document(pool, document_id).await?;
     let rows = sqlx::query(
         r#"
-        SELECT id, document_id, parent_id, node_type, title, text, ordinal_path, page_start, page_end
+        SELECT id, document_id, parent_id, node_type, title, text, text_zstd, ordinal_path, page_start, page_end
         FROM doc_nodes
         WHERE document_id = ?1
         ORDER BY CASE WHEN parent_id IS NULL THEN 0 ELSE 1 END, ordinal_path
@@ -319,28 +1304,160 @@ pub async fn get_document_preview(pool: &SqlitePool, document_id: &str) -> AppRe
 }
 
 pub async fn get_node(pool: &SqlitePool, node_id: &str) -> AppResult<DocNodeDetail> {
+    if let Some(row) = fetch_node_row(pool, node_id).await? {
+        return map_node_detail(row);
+    }
+    // Citations and pins made before a re-ingest still name the old id.
+    if let Some(current_id) = remapped_node_id(pool, node_id).await? {
+        if let Some(row) = fetch_node_row(pool, &current_id).await? {
+            return map_node_detail(row);
+        }
+    }
+    Err(AppError::NotFound(format!("node {node_id}")))
+}
+
+/// Resolves `node_ids` to their titles and document names, in order. Ids
+/// that name no node are skipped.
+pub async fn resolve_node_refs(pool: &SqlitePool, node_ids: &[String]) -> AppResult<Vec<NodeRef>> {
+    let mut refs = Vec::with_capacity(node_ids.len());
+    for node_id in node_ids {
+        let row = sqlx::query(
+            r#"
+            SELECT dn.id, dn.title, dn.document_id, d.name AS document_name, dn.node_type, dn.ordinal_path
+            FROM doc_nodes dn
+            JOIN documents d ON d.id = dn.document_id
+            WHERE dn.id = ?1
+            "#,
+        )
+        .bind(node_id)
+        .fetch_optional(pool)
+        .await?;
+        if let Some(row) = row {
+            let node_type: String = row.try_get("node_type")?;
+            refs.push(NodeRef {
+                id: row.try_get("id")?,
+                title: row.try_get("title")?,
+                document_id: row.try_get("document_id")?,
+                document_name: row.try_get("document_name")?,
+                node_type: NodeType::from_str(&node_type),
+                ordinal_path: row.try_get("ordinal_path")?,
+            });
+        }
+    }
+    Ok(refs)
+}
+
+async fn fetch_node_row(
+    pool: &SqlitePool,
+    node_id: &str,
+) -> AppResult<Option<sqlx::sqlite::SqliteRow>> {
     let row = sqlx::query(
         r#"
-        SELECT id, document_id, parent_id, node_type, title, text, ordinal_path, page_start, page_end, bbox_json, metadata_json
+        SELECT id, document_id, parent_id, node_type, title, text, text_zstd, ordinal_path, page_start, page_end, bbox_json, metadata_json
         FROM doc_nodes
         WHERE id = ?1
         "#,
     )
     .bind(node_id)
     .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| AppError::NotFound(format!("node {node_id}")))?;
+    .await?;
+    Ok(row)
+}
 
-    map_node_detail(row)
+/// Replaces a node's metadata, e.g. after an enrichment pass annotated it.
+pub async fn update_node_metadata(
+    pool: &SqlitePool,
+    node_id: &str,
+    metadata: &serde_json::Value,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        let changed = sqlx::query("UPDATE doc_nodes SET metadata_json = ?1 WHERE id = ?2")
+            .bind(metadata.to_string())
+            .bind(node_id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        if changed == 0 {
+            return Err(AppError::NotFound(format!("node {node_id}")));
+        }
+        Ok(())
+    })
+    .await
 }
 
 pub async fn delete_document(pool: &SqlitePool, document_id: &str) -> AppResult<bool> {
-    let changed = sqlx::query("DELETE FROM documents WHERE id = ?1")
-        .bind(document_id)
-        .execute(pool)
-        .await?
-        .rows_affected();
-    Ok(changed > 0)
+    retry_busy(|| async move {
+        let changed = sqlx::query("DELETE FROM documents WHERE id = ?1")
+            .bind(document_id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        Ok(changed > 0)
+    })
+    .await
+}
+
+/// Projects that hold a document: its owner first, then any linked ones.
+pub async fn document_project_ids(pool: &SqlitePool, document_id: &str) -> AppResult<Vec<String>> {
+    let ids = sqlx::query_scalar(
+        r#"
+        SELECT project_id FROM documents WHERE id = ?1
+        UNION ALL
+        SELECT project_id FROM (
+          SELECT project_id FROM document_links WHERE document_id = ?1 ORDER BY created_at ASC, project_id
+        )
+        "#,
+    )
+    .bind(document_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+/// Builds the `corpus/changed` payload for one project after `change` to a
+/// document. Counts are read after the change, so a removed document reports
+/// no nodes.
+pub async fn corpus_changed_event(
+    pool: &SqlitePool,
+    project_id: &str,
+    change: CorpusChange,
+    document_id: &str,
+) -> AppResult<CorpusChangedEvent> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS node_count,
+               COALESCE(SUM(CASE WHEN node_type IN ('Section', 'Subsection') THEN 1 ELSE 0 END), 0) AS section_count
+        FROM doc_nodes
+        WHERE document_id = ?1
+        "#,
+    )
+    .bind(document_id)
+    .fetch_one(pool)
+    .await?;
+    let stats = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS document_count,
+               COALESCE(SUM((SELECT COUNT(*) FROM doc_nodes dn WHERE dn.document_id = d.id)), 0) AS node_count
+        FROM documents d
+        WHERE (d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1))
+          AND (d.locked = 0 OR d.unlocked_until > CAST(strftime('%s', 'now') AS INTEGER))
+        "#,
+    )
+    .bind(project_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CorpusChangedEvent {
+        project_id: project_id.to_string(),
+        change,
+        document_id: document_id.to_string(),
+        node_count: row.try_get("node_count")?,
+        section_count: row.try_get("section_count")?,
+        stats: CorpusStats {
+            document_count: stats.try_get("document_count")?,
+            node_count: stats.try_get("node_count")?,
+        },
+    })
 }
 
 pub async fn get_graph_layout(
@@ -375,59 +1492,62 @@ pub async fn save_graph_layout(
     document_id: &str,
     positions: &[GraphNodePosition],
 ) -> AppResult<usize> {
-    let mut tx = pool.begin().await?;
-    let mut saved = 0usize;
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        let mut saved = 0usize;
 
-    if positions.is_empty() {
-        sqlx::query("DELETE FROM graph_layouts WHERE document_id = ?1")
-            .bind(document_id)
-            .execute(&mut *tx)
-            .await?;
-        tx.commit().await?;
-        return Ok(0);
-    }
+        if positions.is_empty() {
+            sqlx::query("DELETE FROM graph_layouts WHERE document_id = ?1")
+                .bind(document_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            return Ok(0);
+        }
 
-    let mut cleanup = QueryBuilder::new(
-        "DELETE FROM graph_layouts WHERE document_id = ",
-    );
-    cleanup
-        .push_bind(document_id)
-        .push(" AND node_id NOT IN (");
-    let mut separated = cleanup.separated(", ");
-    for position in positions {
-        separated.push_bind(&position.node_id);
-    }
-    cleanup.push(")");
-    cleanup.build().execute(&mut *tx).await?;
+        let mut cleanup = QueryBuilder::new(
+            "DELETE FROM graph_layouts WHERE document_id = ",
+        );
+        cleanup
+            .push_bind(document_id)
+            .push(" AND node_id NOT IN (");
+        let mut separated = cleanup.separated(", ");
+        for position in positions {
+            separated.push_bind(&position.node_id);
+        }
+        cleanup.push(")");
+        cleanup.build().execute(&mut *tx).await?;
 
-    for position in positions {
-        let affected = sqlx::query(
-            r#"
-            INSERT INTO graph_layouts (document_id, node_id, x, y, updated_at)
-            SELECT ?1, ?2, ?3, ?4, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
-            WHERE EXISTS (
-              SELECT 1
-              FROM doc_nodes
-              WHERE document_id = ?1 AND id = ?2
+        for position in positions {
+            let affected = sqlx::query(
+                r#"
+                INSERT INTO graph_layouts (document_id, node_id, x, y, updated_at)
+                SELECT ?1, ?2, ?3, ?4, strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+                WHERE EXISTS (
+                  SELECT 1
+                  FROM doc_nodes
+                  WHERE document_id = ?1 AND id = ?2
+                )
+                ON CONFLICT(document_id, node_id) DO UPDATE SET
+                  x = excluded.x,
+                  y = excluded.y,
+                  updated_at = excluded.updated_at
+                "#,
             )
-            ON CONFLICT(document_id, node_id) DO UPDATE SET
-              x = excluded.x,
-              y = excluded.y,
-              updated_at = excluded.updated_at
-            "#,
-        )
-        .bind(document_id)
-        .bind(&position.node_id)
-        .bind(position.x)
-        .bind(position.y)
-        .execute(&mut *tx)
-        .await?
-        .rows_affected();
-        saved += affected as usize;
-    }
+            .bind(document_id)
+            .bind(&position.node_id)
+            .bind(position.x)
+            .bind(position.y)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            saved += affected as usize;
+        }
 
-    tx.commit().await?;
-    Ok(saved)
+        tx.commit().await?;
+        Ok(saved)
+    })
+    .await
 }
 
 pub async fn export_markdown(
@@ -438,7 +1558,7 @@ pub async fn export_markdown(
     let document = get_document(pool, document_id).await?;
     let nodes = sqlx::query(
         r#"
-        SELECT id, document_id, parent_id, node_type, title, text, ordinal_path, page_start, page_end, bbox_json, metadata_json
+        SELECT id, document_id, parent_id, node_type, title, text, text_zstd, ordinal_path, page_start, page_end, bbox_json, metadata_json
         FROM doc_nodes
         WHERE document_id = ?1
         ORDER BY ordinal_path
@@ -448,6 +1568,8 @@ pub async fn export_markdown(
     .fetch_all(pool)
     .await?;
 
+    let labels = node_types::custom_labels(pool).await?;
+
     let mut out = String::new();
     out.push_str("# ");
     out.push_str(&document.name);
@@ -480,6 +1602,25 @@ pub async fn export_markdown(
                     out.push_str("\n\n");
                 }
             }
+            NodeType::Custom(ref name) => {
+                // Custom types keep their label so the export still reads as
+                // a clause or a requirement.
+                let label = labels
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| node_types::default_label(&node.node_type));
+                out.push_str("**");
+                out.push_str(&label);
+                if !node.title.is_empty() {
+                    out.push_str(": ");
+                    out.push_str(&node.title);
+                }
+                out.push_str("**\n");
+                if !node.text.is_empty() {
+                    out.push_str(&node.text);
+                    out.push_str("\n\n");
+                }
+            }
             _ => {
                 if !node.title.is_empty() {
                     out.push_str("**");
@@ -498,8 +1639,11 @@ pub async fn export_markdown(
     Ok(())
 }
 
-fn map_document_summary(row: sqlx::sqlite::SqliteRow) -> AppResult<DocumentSummary> {
+pub(crate) fn map_document_summary(row: sqlx::sqlite::SqliteRow) -> AppResult<DocumentSummary> {
     let created_at: String = row.try_get("created_at")?;
+    let locked: bool = row.try_get("locked")?;
+    let unlocked_until: Option<i64> = row.try_get("unlocked_until")?;
+    let last_ingested_at: Option<String> = row.try_get("last_ingested_at")?;
     Ok(DocumentSummary {
         id: row.try_get("id")?,
         project_id: row.try_get("project_id")?,
@@ -507,6 +1651,14 @@ fn map_document_summary(row: sqlx::sqlite::SqliteRow) -> AppResult<DocumentSumma
         mime: row.try_get("mime")?,
         checksum: row.try_get("checksum")?,
         pages: row.try_get("pages")?,
+        hidden: row.try_get("hidden")?,
+        locked,
+        unlocked: locked && unlocked_until.is_some_and(|until| until > Utc::now().timestamp()),
+        source_path: row.try_get("source_path")?,
+        last_ingested_at: last_ingested_at.map(parse_timestamp).transpose()?,
+        stale: false,
+        tags: vec![],
+        attachment_ids: vec![],
         created_at: parse_timestamp(created_at)?,
     })
 }
@@ -519,7 +1671,7 @@ fn map_node_summary(row: sqlx::sqlite::SqliteRow) -> AppResult<DocNodeSummary> {
         parent_id: row.try_get("parent_id")?,
         node_type: NodeType::from_str(&node_type),
         title: row.try_get("title")?,
-        text: row.try_get("text")?,
+        text: node_text::from_row(&row)?,
         ordinal_path: row.try_get("ordinal_path")?,
         page_start: row.try_get("page_start")?,
         page_end: row.try_get("page_end")?,
@@ -536,7 +1688,7 @@ fn map_node_detail(row: sqlx::sqlite::SqliteRow) -> AppResult<DocNodeDetail> {
         parent_id: row.try_get("parent_id")?,
         node_type: NodeType::from_str(&node_type),
         title: row.try_get("title")?,
-        text: row.try_get("text")?,
+        text: node_text::from_row(&row)?,
         ordinal_path: row.try_get("ordinal_path")?,
         page_start: row.try_get("page_start")?,
         page_end: row.try_get("page_end")?,
@@ -559,7 +1711,9 @@ fn fts_match_query(query: &str) -> Option<String> {
     )
 }
 
-fn normalized_terms(query: &str) -> Vec<String> {
+/// Distinct lowercase search terms of `query`, stopwords and single letters
+/// left out.
+pub fn normalized_terms(query: &str) -> Vec<String> {
     let mut seen = HashSet::new();
     let mut terms = Vec::new();
 
@@ -578,6 +1732,103 @@ fn normalized_terms(query: &str) -> Vec<String> {
     terms
 }
 
+/// Highest [`RetrievalSettings::fuzziness`](crate::core::types::RetrievalSettings).
+pub const MAX_FUZZINESS: u8 = 2;
+/// Indexed words a misspelled term is widened to, closest first.
+const FUZZY_ALTERNATIVES: usize = 3;
+
+/// `query` with every term the index has no word for followed by the indexed
+/// words within `fuzziness` edits of it, so the keyword search still finds
+/// "liability cap" when asked for "liabiltiy cap". Terms of up to three
+/// letters have too many neighbours to guess from and are taken as typed;
+/// those of four or five get at most one edit.
+pub async fn expand_fuzzy_terms(
+    pool: &SqlitePool,
+    query: &str,
+    fuzziness: u8,
+) -> AppResult<String> {
+    let fuzziness = usize::from(fuzziness.min(MAX_FUZZINESS));
+    let mut expanded = query.to_string();
+    for term in normalized_terms(query) {
+        let edits = match term.len() {
+            0..=3 => 0,
+            4..=5 => 1,
+            _ => fuzziness,
+        }
+        .min(fuzziness);
+        if edits == 0 {
+            continue;
+        }
+        // Terms are ASCII, so bumping the last byte bounds every word the
+        // term is a prefix of; those already match as typed.
+        let mut upper = term.clone().into_bytes();
+        if let Some(last) = upper.last_mut() {
+            *last += 1;
+        }
+        let upper = String::from_utf8(upper).unwrap_or_default();
+        let known: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM doc_nodes_fts_vocab WHERE term >= ?1 AND term < ?2)",
+        )
+        .bind(&term)
+        .bind(&upper)
+        .fetch_one(pool)
+        .await?;
+        if known {
+            continue;
+        }
+
+        let rows = sqlx::query(
+            "SELECT term, doc FROM doc_nodes_fts_vocab WHERE length(term) BETWEEN ?1 AND ?2",
+        )
+        .bind(term.len().saturating_sub(edits) as i64)
+        .bind((term.len() + edits) as i64)
+        .fetch_all(pool)
+        .await?;
+        let mut near = vec![];
+        for row in rows {
+            let word: String = row.try_get("term")?;
+            if let Some(distance) = edit_distance(&term, &word, edits) {
+                near.push((distance, -row.try_get::<i64, _>("doc")?, word));
+            }
+        }
+        near.sort();
+        for (_, _, word) in near.into_iter().take(FUZZY_ALTERNATIVES) {
+            expanded.push(' ');
+            expanded.push_str(&word);
+        }
+    }
+    Ok(expanded)
+}
+
+/// Edits between `a` and `b`, counting two swapped neighbours as one, or
+/// `None` when there are more than `max`.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut best = substitution.min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    let distance = rows[a.len()][b.len()];
+    (distance <= max).then_some(distance)
+}
+
 fn is_stopword(term: &str) -> bool {
     matches!(
         term,
@@ -0,0 +1,101 @@
+//! Local app metrics: a running count and duration total per metric and
+//! label. Nothing is recorded while the metrics setting is off; see
+//! [`crate::core::metrics`] for what is measured.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        metrics::Metric,
+        types::{MetricSample, MetricsSettings},
+    },
+    db::{repositories::settings, retry_busy},
+};
+
+pub async fn get_settings(pool: &SqlitePool) -> AppResult<MetricsSettings> {
+    settings::get_setting(pool, settings::METRICS_SETTING).await
+}
+
+pub async fn set_settings(pool: &SqlitePool, value: &MetricsSettings) -> AppResult<()> {
+    settings::set_setting(pool, settings::METRICS_SETTING, value).await
+}
+
+/// Counts one `metric` under `label`, adding `duration` to its totals for
+/// timed metrics. A no-op while metrics are off.
+pub async fn record(
+    pool: &SqlitePool,
+    metric: Metric,
+    label: &str,
+    duration: Option<Duration>,
+) -> AppResult<()> {
+    if !get_settings(pool).await?.enabled {
+        return Ok(());
+    }
+    let ms = duration
+        .filter(|_| metric.timed())
+        .map(|duration| duration.as_millis().min(i64::MAX as u128) as i64)
+        .unwrap_or(0);
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO metrics (name, label, count, total_ms, max_ms)
+            VALUES (?1, ?2, 1, ?3, ?3)
+            ON CONFLICT(name, label) DO UPDATE SET
+              count = count + 1,
+              total_ms = total_ms + excluded.total_ms,
+              max_ms = MAX(max_ms, excluded.max_ms),
+              updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            "#,
+        )
+        .bind(metric.as_str())
+        .bind(label)
+        .bind(ms)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Every metric recorded since the last reset, by name and label.
+pub async fn list_metrics(pool: &SqlitePool) -> AppResult<Vec<MetricSample>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT name, label, count, total_ms, max_ms, updated_at
+        FROM metrics
+        ORDER BY name, label
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let updated_at: String = row.try_get("updated_at")?;
+            Ok(MetricSample {
+                name: row.try_get("name")?,
+                label: row.try_get("label")?,
+                count: row.try_get("count")?,
+                total_ms: row.try_get("total_ms")?,
+                max_ms: row.try_get("max_ms")?,
+                updated_at: DateTime::parse_from_rfc3339(&updated_at)
+                    .map(|value| value.with_timezone(&Utc))
+                    .map_err(|err| {
+                        AppError::Database(format!("invalid timestamp {updated_at}: {err}"))
+                    })?,
+            })
+        })
+        .collect()
+}
+
+pub async fn reset_metrics(pool: &SqlitePool) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query("DELETE FROM metrics").execute(pool).await?;
+        Ok(())
+    })
+    .await
+}
@@ -1,3 +1,12 @@
+pub mod acronyms;
+pub mod activity;
+pub mod assets;
 pub mod documents;
+pub mod metrics;
+pub mod node_types;
 pub mod projects;
+pub mod prompts;
+pub mod provider_cache;
+pub mod provider_keys;
 pub mod reasoning;
+pub mod settings;
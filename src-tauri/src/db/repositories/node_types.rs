@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use sqlx::{Row, SqlitePool};
+
+use crate::core::{
+    errors::{AppError, AppResult},
+    types::{NodeType, NodeTypeInfo},
+};
+
+fn built_in_description(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::Document => "The root of a document's tree.",
+        NodeType::Section => "A top-level part of a document.",
+        NodeType::Subsection => "A part nested in a section.",
+        NodeType::Paragraph => "A block of prose.",
+        NodeType::Claim => "A statement extracted from a paragraph.",
+        NodeType::Table => "Tabular data, rendered as a Markdown table.",
+        NodeType::Figure => "An image or chart with its caption.",
+        NodeType::Equation => "A formula.",
+        NodeType::Caption => "A caption not attached to a figure or table.",
+        NodeType::Reference => "A bibliography or citation entry.",
+        NodeType::Unknown => "Content the parser could not classify.",
+        NodeType::Custom(_) => "",
+    }
+}
+
+/// "test_case" -> "Test Case", the label a type gets until one is given.
+pub fn default_label(node_type: &NodeType) -> String {
+    node_type
+        .as_str()
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn valid_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+async fn node_counts(pool: &SqlitePool) -> AppResult<BTreeMap<String, i64>> {
+    let rows = sqlx::query("SELECT node_type, COUNT(*) AS nodes FROM doc_nodes GROUP BY node_type")
+        .fetch_all(pool)
+        .await?;
+    let mut counts = BTreeMap::new();
+    for row in rows {
+        let node_type = NodeType::from_str(&row.get::<String, _>("node_type"));
+        *counts.entry(node_type.as_str().to_string()).or_insert(0) += row.get::<i64, _>("nodes");
+    }
+    Ok(counts)
+}
+
+/// Labels of the defined custom types, for rendering nodes outside the app.
+pub async fn custom_labels(pool: &SqlitePool) -> AppResult<BTreeMap<String, String>> {
+    let rows = sqlx::query("SELECT name, label FROM node_types")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("name"), row.get("label")))
+        .collect())
+}
+
+/// Every built-in type, then the defined custom types, then custom types
+/// nodes carry (from a parser) that nobody has defined yet.
+pub async fn list_node_types(pool: &SqlitePool) -> AppResult<Vec<NodeTypeInfo>> {
+    let mut counts = node_counts(pool).await?;
+    let mut types = NodeType::BUILT_IN
+        .iter()
+        .map(|node_type| NodeTypeInfo {
+            node_type: node_type.clone(),
+            label: default_label(node_type),
+            description: built_in_description(node_type).to_string(),
+            color: None,
+            built_in: true,
+            node_count: counts.remove(node_type.as_str()).unwrap_or(0),
+        })
+        .collect::<Vec<_>>();
+
+    let rows = sqlx::query("SELECT name, label, description, color FROM node_types ORDER BY name")
+        .fetch_all(pool)
+        .await?;
+    for row in rows {
+        let name: String = row.get("name");
+        types.push(NodeTypeInfo {
+            node_count: counts.remove(&name).unwrap_or(0),
+            node_type: NodeType::Custom(name),
+            label: row.get("label"),
+            description: row.get("description"),
+            color: row.get("color"),
+            built_in: false,
+        });
+    }
+
+    types.extend(counts.into_iter().map(|(name, node_count)| {
+        let node_type = NodeType::Custom(name);
+        NodeTypeInfo {
+            label: default_label(&node_type),
+            node_type,
+            description: String::new(),
+            color: None,
+            built_in: false,
+            node_count,
+        }
+    }));
+    Ok(types)
+}
+
+/// Defines a custom type, or updates the display of one already defined.
+/// `name` is keyed the way node types are, so "TestCase" defines
+/// `test_case`.
+pub async fn save_node_type(
+    pool: &SqlitePool,
+    name: &str,
+    label: Option<&str>,
+    description: Option<&str>,
+    color: Option<&str>,
+) -> AppResult<NodeTypeInfo> {
+    let node_type = match NodeType::from_str(name) {
+        NodeType::Custom(key) => NodeType::Custom(key),
+        NodeType::Unknown if NodeType::key(name).is_empty() => {
+            return Err(AppError::InvalidInput(
+                "node type name must contain a letter or digit".to_string(),
+            ))
+        }
+        built_in => {
+            return Err(AppError::InvalidInput(format!(
+                "{} is a built-in node type",
+                built_in.as_str()
+            )))
+        }
+    };
+    let label = label
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| default_label(&node_type));
+    let description = description.map(str::trim).unwrap_or_default();
+    let color = color.map(str::trim).filter(|color| !color.is_empty());
+    if let Some(color) = color {
+        if !valid_color(color) {
+            return Err(AppError::InvalidInput(format!(
+                "color must be #rgb or #rrggbb, got {color}"
+            )));
+        }
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO node_types (name, label, description, color)
+        VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT(name) DO UPDATE SET
+          label = excluded.label,
+          description = excluded.description,
+          color = excluded.color
+        "#,
+    )
+    .bind(node_type.as_str())
+    .bind(&label)
+    .bind(description)
+    .bind(color)
+    .execute(pool)
+    .await?;
+
+    let node_count = node_counts(pool)
+        .await?
+        .remove(node_type.as_str())
+        .unwrap_or(0);
+    Ok(NodeTypeInfo {
+        node_type,
+        label,
+        description: description.to_string(),
+        color: color.map(str::to_string),
+        built_in: false,
+        node_count,
+    })
+}
+
+/// Removes a custom type's definition. Nodes of that type keep it and are
+/// shown with its default label.
+pub async fn delete_node_type(pool: &SqlitePool, name: &str) -> AppResult<bool> {
+    let node_type = NodeType::from_str(name);
+    if !node_type.is_custom() {
+        return Err(AppError::InvalidInput(format!(
+            "{} is a built-in node type",
+            node_type.as_str()
+        )));
+    }
+    let result = sqlx::query("DELETE FROM node_types WHERE name = ?1")
+        .bind(node_type.as_str())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
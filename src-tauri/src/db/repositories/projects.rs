@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
 use sqlx::{Row, SqlitePool};
 
 use crate::core::{
     errors::{AppError, AppResult},
-    types::ProjectSummary,
+    types::{
+        GenerationSettings, IngestRules, ProjectDeleteImpact, ProjectSummary, ResearchLogSettings,
+    },
 };
 
 fn parse_timestamp(value: String) -> AppResult<DateTime<Utc>> {
@@ -15,7 +18,7 @@ fn parse_timestamp(value: String) -> AppResult<DateTime<Utc>> {
 pub async fn list_projects(pool: &SqlitePool) -> AppResult<Vec<ProjectSummary>> {
     let rows = sqlx::query(
         r#"
-        SELECT id, name, created_at, updated_at
+        SELECT id, name, read_only, archived, created_at, updated_at
         FROM projects
         ORDER BY created_at ASC
         "#,
@@ -70,10 +73,59 @@ pub async fn delete_project(pool: &SqlitePool, id: &str) -> AppResult<bool> {
     Ok(affected > 0)
 }
 
+/// Counts what [`delete_project`] would cascade away for the project.
+pub async fn delete_impact(pool: &SqlitePool, id: &str) -> AppResult<ProjectDeleteImpact> {
+    get_project(pool, id).await?;
+    let row = sqlx::query(
+        r#"
+        SELECT
+          (SELECT COUNT(*) FROM documents WHERE project_id = ?1) AS documents,
+          (SELECT COUNT(*) FROM doc_nodes n
+             JOIN documents d ON d.id = n.document_id
+            WHERE d.project_id = ?1) AS nodes,
+          (SELECT COUNT(*) FROM reasoning_runs WHERE project_id = ?1) AS runs,
+          (SELECT COUNT(*) FROM graph_layouts g
+             JOIN documents d ON d.id = g.document_id
+            WHERE d.project_id = ?1) AS layout_positions,
+          (SELECT COUNT(DISTINCT l.document_id) FROM document_links l
+             JOIN documents d ON d.id = l.document_id
+            WHERE d.project_id = ?1 AND l.project_id != ?1) AS shared_documents,
+          (SELECT MAX(created_at) FROM reasoning_runs WHERE project_id = ?1) AS last_run_at
+        "#,
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+
+    let count =
+        |column: &str| -> AppResult<u64> { Ok(row.try_get::<i64, _>(column)?.max(0) as u64) };
+    let documents = count("documents")?;
+    let nodes = count("nodes")?;
+    let runs = count("runs")?;
+    let layout_positions = count("layout_positions")?;
+    let shared_documents = count("shared_documents")?;
+    let last_run_at: Option<String> = row.try_get("last_run_at")?;
+    // The latest run is part of the token so that a run replacing a deleted
+    // one, which leaves the count as it was, still asks for a fresh look.
+    let digest = Sha256::digest(format!(
+        "{id}:{documents}:{nodes}:{runs}:{layout_positions}:{shared_documents}:{}",
+        last_run_at.unwrap_or_default()
+    ));
+    Ok(ProjectDeleteImpact {
+        project_id: id.to_string(),
+        documents,
+        nodes,
+        runs,
+        layout_positions,
+        shared_documents,
+        confirmation_token: format!("{digest:x}")[..16].to_string(),
+    })
+}
+
 pub async fn get_project(pool: &SqlitePool, id: &str) -> AppResult<ProjectSummary> {
     let row = sqlx::query(
         r#"
-        SELECT id, name, created_at, updated_at
+        SELECT id, name, read_only, archived, created_at, updated_at
         FROM projects
         WHERE id = ?1
         "#,
@@ -86,12 +138,234 @@ pub async fn get_project(pool: &SqlitePool, id: &str) -> AppResult<ProjectSummar
     map_project_summary(row)
 }
 
+/// Fails for a project opened from a shared bundle, whose content cannot
+/// change, and for an archived project until it is unarchived.
+pub async fn ensure_writable(pool: &SqlitePool, id: &str) -> AppResult<()> {
+    let project = get_project(pool, id).await?;
+    if project.read_only {
+        return Err(AppError::InvalidInput(format!(
+            "project {id} is a read-only shared bundle"
+        )));
+    }
+    if project.archived {
+        return Err(AppError::InvalidInput(format!(
+            "project {id} is archived; unarchive it to change it"
+        )));
+    }
+    Ok(())
+}
+
+/// Archives or unarchives a project.
+pub async fn set_archived(
+    pool: &SqlitePool,
+    id: &str,
+    archived: bool,
+) -> AppResult<ProjectSummary> {
+    let affected = sqlx::query(
+        r#"
+        UPDATE projects
+        SET archived = ?2,
+            updated_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .bind(archived)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("project {id}")));
+    }
+    get_project(pool, id).await
+}
+
+/// [`ensure_writable`] for the project that owns `document_id`.
+pub async fn ensure_document_writable(pool: &SqlitePool, document_id: &str) -> AppResult<()> {
+    let row = sqlx::query(
+        r#"
+        SELECT p.read_only, p.archived
+        FROM documents d
+        JOIN projects p ON p.id = d.project_id
+        WHERE d.id = ?1
+        "#,
+    )
+    .bind(document_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("document {document_id}")))?;
+    if row.try_get::<bool, _>("read_only")? {
+        return Err(AppError::InvalidInput(format!(
+            "document {document_id} belongs to a read-only shared bundle"
+        )));
+    }
+    if row.try_get::<bool, _>("archived")? {
+        return Err(AppError::InvalidInput(format!(
+            "document {document_id} belongs to an archived project"
+        )));
+    }
+    Ok(())
+}
+
+/// Creates the read-only project a shared bundle is mounted as.
+pub async fn create_shared_project(
+    pool: &SqlitePool,
+    id: &str,
+    name: &str,
+    bundle_id: &str,
+) -> AppResult<ProjectSummary> {
+    sqlx::query(
+        r#"
+        INSERT INTO projects (id, name, read_only, source_bundle_id)
+        VALUES (?1, ?2, 1, ?3)
+        "#,
+    )
+    .bind(id)
+    .bind(name)
+    .bind(bundle_id)
+    .execute(pool)
+    .await?;
+    get_project(pool, id).await
+}
+
+/// The project a bundle was already opened as, if any.
+pub async fn find_by_bundle(
+    pool: &SqlitePool,
+    bundle_id: &str,
+) -> AppResult<Option<ProjectSummary>> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, name, read_only, archived, created_at, updated_at
+        FROM projects
+        WHERE source_bundle_id = ?1
+        "#,
+    )
+    .bind(bundle_id)
+    .fetch_optional(pool)
+    .await?;
+    row.map(map_project_summary).transpose()
+}
+
+pub async fn get_generation_settings(pool: &SqlitePool, id: &str) -> AppResult<GenerationSettings> {
+    let raw: String = sqlx::query("SELECT generation_settings_json FROM projects WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("project {id}")))?
+        .try_get("generation_settings_json")?;
+    serde_json::from_str(&raw)
+        .map_err(|err| AppError::Database(format!("invalid generation settings for {id}: {err}")))
+}
+
+pub async fn set_generation_settings(
+    pool: &SqlitePool,
+    id: &str,
+    settings: &GenerationSettings,
+) -> AppResult<GenerationSettings> {
+    let affected = sqlx::query(
+        r#"
+        UPDATE projects
+        SET generation_settings_json = ?2,
+            updated_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .bind(serde_json::to_string(settings).map_err(|err| AppError::Internal(err.to_string()))?)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("project {id}")));
+    }
+    get_generation_settings(pool, id).await
+}
+
+pub async fn get_ingest_rules(pool: &SqlitePool, id: &str) -> AppResult<IngestRules> {
+    let raw: String = sqlx::query("SELECT ingest_rules_json FROM projects WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("project {id}")))?
+        .try_get("ingest_rules_json")?;
+    serde_json::from_str(&raw)
+        .map_err(|err| AppError::Database(format!("invalid ingest rules for {id}: {err}")))
+}
+
+pub async fn set_ingest_rules(
+    pool: &SqlitePool,
+    id: &str,
+    rules: &IngestRules,
+) -> AppResult<IngestRules> {
+    let affected = sqlx::query(
+        r#"
+        UPDATE projects
+        SET ingest_rules_json = ?2,
+            updated_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .bind(serde_json::to_string(rules).map_err(|err| AppError::Internal(err.to_string()))?)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("project {id}")));
+    }
+    get_ingest_rules(pool, id).await
+}
+
+pub async fn get_research_log_settings(
+    pool: &SqlitePool,
+    id: &str,
+) -> AppResult<ResearchLogSettings> {
+    let raw: String = sqlx::query("SELECT research_log_json FROM projects WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("project {id}")))?
+        .try_get("research_log_json")?;
+    serde_json::from_str(&raw)
+        .map_err(|err| AppError::Database(format!("invalid research log settings for {id}: {err}")))
+}
+
+pub async fn set_research_log_settings(
+    pool: &SqlitePool,
+    id: &str,
+    settings: &ResearchLogSettings,
+) -> AppResult<ResearchLogSettings> {
+    let affected = sqlx::query(
+        r#"
+        UPDATE projects
+        SET research_log_json = ?2,
+            updated_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .bind(serde_json::to_string(settings).map_err(|err| AppError::Internal(err.to_string()))?)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("project {id}")));
+    }
+    get_research_log_settings(pool, id).await
+}
+
 fn map_project_summary(row: sqlx::sqlite::SqliteRow) -> AppResult<ProjectSummary> {
     let created_at: String = row.try_get("created_at")?;
     let updated_at: String = row.try_get("updated_at")?;
     Ok(ProjectSummary {
         id: row.try_get("id")?,
         name: row.try_get("name")?,
+        read_only: row.try_get("read_only")?,
+        archived: row.try_get("archived")?,
         created_at: parse_timestamp(created_at)?,
         updated_at: parse_timestamp(updated_at)?,
     })
@@ -0,0 +1,207 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::PromptTemplateRecord,
+    },
+    reasoner::prompts::{self, PromptSet, PromptTemplate, BUILTIN_TEMPLATE_VERSION},
+};
+
+fn parse_timestamp(value: String) -> AppResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|v| v.with_timezone(&Utc))
+        .map_err(|err| AppError::Database(format!("invalid timestamp {value}: {err}")))
+}
+
+fn builtin_record(name: &str) -> AppResult<PromptTemplateRecord> {
+    let body = prompts::default_template(name)
+        .ok_or_else(|| AppError::NotFound(format!("prompt template {name}")))?;
+    Ok(PromptTemplateRecord {
+        name: name.to_string(),
+        version: BUILTIN_TEMPLATE_VERSION,
+        body: body.to_string(),
+        builtin: true,
+        variables: variables_for(name),
+        created_at: None,
+    })
+}
+
+fn variables_for(name: &str) -> Vec<String> {
+    prompts::template_variables(name)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Returns the latest stored version of a template, falling back to the built-in body.
+pub async fn get_active_template(pool: &SqlitePool, name: &str) -> AppResult<PromptTemplateRecord> {
+    let row = sqlx::query(
+        r#"
+        SELECT name, version, body, created_at
+        FROM prompt_templates
+        WHERE name = ?1
+        ORDER BY version DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+
+    match row {
+        Some(row) => map_template(row),
+        None => builtin_record(name),
+    }
+}
+
+pub async fn get_template_version(
+    pool: &SqlitePool,
+    name: &str,
+    version: i64,
+) -> AppResult<PromptTemplateRecord> {
+    if version == BUILTIN_TEMPLATE_VERSION {
+        return builtin_record(name);
+    }
+    let row = sqlx::query(
+        "SELECT name, version, body, created_at FROM prompt_templates WHERE name = ?1 AND version = ?2",
+    )
+    .bind(name)
+    .bind(version)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("prompt template {name} v{version}")))?;
+    map_template(row)
+}
+
+pub async fn list_active_templates(pool: &SqlitePool) -> AppResult<Vec<PromptTemplateRecord>> {
+    let mut templates = Vec::with_capacity(prompts::TEMPLATE_NAMES.len());
+    for name in prompts::TEMPLATE_NAMES {
+        templates.push(get_active_template(pool, name).await?);
+    }
+    Ok(templates)
+}
+
+pub async fn list_template_history(
+    pool: &SqlitePool,
+    name: &str,
+) -> AppResult<Vec<PromptTemplateRecord>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT name, version, body, created_at
+        FROM prompt_templates
+        WHERE name = ?1
+        ORDER BY version DESC
+        "#,
+    )
+    .bind(name)
+    .fetch_all(pool)
+    .await?;
+
+    let mut versions = rows
+        .into_iter()
+        .map(map_template)
+        .collect::<AppResult<Vec<_>>>()?;
+    versions.push(builtin_record(name)?);
+    Ok(versions)
+}
+
+/// Stores `body` as the next version of the template. Earlier versions are kept so
+/// runs that recorded them stay reproducible.
+pub async fn save_template(
+    pool: &SqlitePool,
+    name: &str,
+    body: &str,
+) -> AppResult<PromptTemplateRecord> {
+    prompts::validate_template(name, body)?;
+    let mut tx = pool.begin().await?;
+    let next_version: i64 = sqlx::query(
+        "SELECT COALESCE(MAX(version), 0) + 1 AS next_version FROM prompt_templates WHERE name = ?1",
+    )
+    .bind(name)
+    .fetch_one(&mut *tx)
+    .await?
+    .try_get("next_version")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO prompt_templates (name, version, body)
+        VALUES (?1, ?2, ?3)
+        "#,
+    )
+    .bind(name)
+    .bind(next_version)
+    .bind(body)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    get_template_version(pool, name, next_version).await
+}
+
+/// Resets a template by storing the built-in body as a new version.
+pub async fn reset_template(pool: &SqlitePool, name: &str) -> AppResult<PromptTemplateRecord> {
+    let body = prompts::default_template(name)
+        .ok_or_else(|| AppError::NotFound(format!("prompt template {name}")))?;
+    save_template(pool, name, body).await
+}
+
+pub async fn load_prompt_set(pool: &SqlitePool) -> AppResult<PromptSet> {
+    let planner = get_active_template(pool, prompts::PLANNER_TEMPLATE).await?;
+    let synthesis = get_active_template(pool, prompts::SYNTHESIS_TEMPLATE).await?;
+    Ok(PromptSet {
+        planner: into_template(planner),
+        synthesis: into_template(synthesis),
+    })
+}
+
+/// Resolves the exact template versions recorded on a run.
+pub async fn load_prompt_set_for_versions(
+    pool: &SqlitePool,
+    versions: &serde_json::Value,
+) -> AppResult<PromptSet> {
+    let version_of = |name: &str| {
+        versions
+            .get(name)
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or(BUILTIN_TEMPLATE_VERSION)
+    };
+    let planner = get_template_version(
+        pool,
+        prompts::PLANNER_TEMPLATE,
+        version_of(prompts::PLANNER_TEMPLATE),
+    )
+    .await?;
+    let synthesis = get_template_version(
+        pool,
+        prompts::SYNTHESIS_TEMPLATE,
+        version_of(prompts::SYNTHESIS_TEMPLATE),
+    )
+    .await?;
+    Ok(PromptSet {
+        planner: into_template(planner),
+        synthesis: into_template(synthesis),
+    })
+}
+
+fn into_template(record: PromptTemplateRecord) -> PromptTemplate {
+    PromptTemplate {
+        name: record.name,
+        version: record.version,
+        body: record.body,
+    }
+}
+
+fn map_template(row: sqlx::sqlite::SqliteRow) -> AppResult<PromptTemplateRecord> {
+    let name: String = row.try_get("name")?;
+    let created_at: String = row.try_get("created_at")?;
+    Ok(PromptTemplateRecord {
+        variables: variables_for(&name),
+        version: row.try_get("version")?,
+        body: row.try_get("body")?,
+        builtin: false,
+        created_at: Some(parse_timestamp(created_at)?),
+        name,
+    })
+}
@@ -0,0 +1,118 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::errors::{AppError, AppResult},
+    db::retry_busy,
+    providers::{CallKind, ProviderResponse},
+};
+
+/// Limits applied to the provider response cache.
+#[derive(Debug, Clone, Copy)]
+pub struct ProviderCacheConfig {
+    pub ttl_secs: i64,
+    pub max_entries: i64,
+}
+
+impl Default for ProviderCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 24 * 60 * 60,
+            max_entries: 500,
+        }
+    }
+}
+
+/// Returns an unexpired cached response and bumps its hit statistics.
+pub async fn get_cached_response(
+    pool: &SqlitePool,
+    cache_key: &str,
+) -> AppResult<Option<ProviderResponse>> {
+    retry_busy(|| async move {
+        let row = sqlx::query(
+            r#"
+            UPDATE provider_cache
+            SET hit_count = hit_count + 1,
+                last_used_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            WHERE cache_key = ?1
+              AND expires_at > strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            RETURNING response_json
+            "#,
+        )
+        .bind(cache_key)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|row| {
+            let raw: String = row.try_get("response_json")?;
+            serde_json::from_str(&raw).map_err(|err| AppError::Database(err.to_string()))
+        })
+        .transpose()
+    })
+    .await
+}
+
+/// Stores a response, then evicts expired entries and the least recently used
+/// ones beyond the configured cap.
+pub async fn put_cached_response(
+    pool: &SqlitePool,
+    cache_key: &str,
+    kind: CallKind,
+    model: &str,
+    response: &ProviderResponse,
+    config: ProviderCacheConfig,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        let response_json = serde_json::to_string(response)
+            .map_err(|err: serde_json::Error| AppError::Internal(err.to_string()))?;
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO provider_cache (cache_key, call_kind, model, response_json, expires_at)
+            VALUES (?1, ?2, ?3, ?4, strftime('%Y-%m-%dT%H:%M:%fZ', 'now', '+' || ?5 || ' seconds'))
+            ON CONFLICT(cache_key) DO UPDATE SET
+              response_json = excluded.response_json,
+              hit_count = 0,
+              created_at = excluded.created_at,
+              last_used_at = excluded.last_used_at,
+              expires_at = excluded.expires_at
+            "#,
+        )
+        .bind(cache_key)
+        .bind(kind.as_str())
+        .bind(model)
+        .bind(response_json)
+        .bind(config.ttl_secs)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "DELETE FROM provider_cache WHERE expires_at <= strftime('%Y-%m-%dT%H:%M:%fZ', 'now')",
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM provider_cache
+            WHERE cache_key IN (
+              SELECT cache_key
+              FROM provider_cache
+              ORDER BY last_used_at DESC
+              LIMIT -1 OFFSET ?1
+            )
+            "#,
+        )
+        .bind(config.max_entries)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn clear_cache(pool: &SqlitePool) -> AppResult<u64> {
+    let result = sqlx::query("DELETE FROM provider_cache")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
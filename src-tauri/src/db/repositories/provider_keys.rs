@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{KeySelection, Provider, ProviderKeyRecord},
+    },
+    db::{repositories::settings, retry_busy},
+};
+
+fn parse_timestamp(value: String) -> AppResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|v| v.with_timezone(&Utc))
+        .map_err(|err| AppError::Database(format!("invalid timestamp {value}: {err}")))
+}
+
+/// Last four characters of a key, enough to tell stored keys apart in the UI.
+pub fn key_hint(api_key: &str) -> String {
+    let chars = api_key.trim().chars().collect::<Vec<_>>();
+    let tail = chars[chars.len().saturating_sub(4)..]
+        .iter()
+        .collect::<String>();
+    format!("…{tail}")
+}
+
+pub async fn list_keys(
+    pool: &SqlitePool,
+    provider: &Provider,
+) -> AppResult<Vec<ProviderKeyRecord>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, provider, label, key_hint, daily_request_quota,
+               CASE WHEN usage_day = date('now') THEN requests_today ELSE 0 END AS requests_today,
+               total_requests, total_tokens, rate_limited_count, cooldown_until, last_used_at, created_at
+        FROM provider_keys
+        WHERE provider = ?1
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(provider.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(map_key).collect()
+}
+
+pub async fn get_key(pool: &SqlitePool, id: &str) -> AppResult<ProviderKeyRecord> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, provider, label, key_hint, daily_request_quota,
+               CASE WHEN usage_day = date('now') THEN requests_today ELSE 0 END AS requests_today,
+               total_requests, total_tokens, rate_limited_count, cooldown_until, last_used_at, created_at
+        FROM provider_keys
+        WHERE id = ?1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("provider key {id}")))?;
+    map_key(row)
+}
+
+/// Registers key metadata; the secret itself lives in the OS keyring.
+pub async fn upsert_key(
+    pool: &SqlitePool,
+    id: &str,
+    provider: &Provider,
+    label: &str,
+    key_hint: &str,
+    daily_request_quota: Option<i64>,
+) -> AppResult<ProviderKeyRecord> {
+    sqlx::query(
+        r#"
+        INSERT INTO provider_keys (id, provider, label, key_hint, daily_request_quota)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT(id) DO UPDATE SET
+          label = excluded.label,
+          key_hint = excluded.key_hint,
+          daily_request_quota = excluded.daily_request_quota,
+          cooldown_until = NULL
+        "#,
+    )
+    .bind(id)
+    .bind(provider.as_str())
+    .bind(label)
+    .bind(key_hint)
+    .bind(daily_request_quota)
+    .execute(pool)
+    .await?;
+    get_key(pool, id).await
+}
+
+pub async fn delete_key(pool: &SqlitePool, id: &str) -> AppResult<bool> {
+    retry_busy(|| async move {
+        let affected = sqlx::query("DELETE FROM provider_keys WHERE id = ?1")
+            .bind(id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        Ok(affected > 0)
+    })
+    .await
+}
+
+pub async fn record_key_usage(pool: &SqlitePool, id: &str, tokens: i64) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE provider_keys
+            SET requests_today = CASE WHEN usage_day = date('now') THEN requests_today + 1 ELSE 1 END,
+                usage_day = date('now'),
+                total_requests = total_requests + 1,
+                total_tokens = total_tokens + ?2,
+                last_used_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(tokens)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Parks a key after a 429 so other keys are preferred until the cooldown ends.
+pub async fn mark_rate_limited(pool: &SqlitePool, id: &str, cooldown_secs: i64) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE provider_keys
+            SET rate_limited_count = rate_limited_count + 1,
+                cooldown_until = strftime('%Y-%m-%dT%H:%M:%fZ', 'now', '+' || ?2 || ' seconds'),
+                last_used_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .bind(cooldown_secs)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn get_key_selection(pool: &SqlitePool) -> AppResult<KeySelection> {
+    settings::get_setting(pool, settings::KEY_SELECTION_SETTING).await
+}
+
+pub async fn set_key_selection(pool: &SqlitePool, selection: KeySelection) -> AppResult<()> {
+    settings::set_setting(pool, settings::KEY_SELECTION_SETTING, &selection).await
+}
+
+fn map_key(row: sqlx::sqlite::SqliteRow) -> AppResult<ProviderKeyRecord> {
+    let provider: String = row.try_get("provider")?;
+    let cooldown_until: Option<String> = row.try_get("cooldown_until")?;
+    let last_used_at: Option<String> = row.try_get("last_used_at")?;
+    let created_at: String = row.try_get("created_at")?;
+    Ok(ProviderKeyRecord {
+        id: row.try_get("id")?,
+        provider: serde_json::from_value(serde_json::Value::String(provider))
+            .map_err(|err| AppError::Database(err.to_string()))?,
+        label: row.try_get("label")?,
+        key_hint: row.try_get("key_hint")?,
+        daily_request_quota: row.try_get("daily_request_quota")?,
+        requests_today: row.try_get("requests_today")?,
+        total_requests: row.try_get("total_requests")?,
+        total_tokens: row.try_get("total_tokens")?,
+        rate_limited_count: row.try_get("rate_limited_count")?,
+        cooldown_until: cooldown_until.map(parse_timestamp).transpose()?,
+        last_used_at: last_used_at.map(parse_timestamp).transpose()?,
+        created_at: parse_timestamp(created_at)?,
+    })
+}
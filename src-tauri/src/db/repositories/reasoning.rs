@@ -1,11 +1,37 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
 use chrono::{DateTime, Utc};
 use sqlx::{Row, SqlitePool};
 
-use crate::core::{
-    errors::{AppError, AppResult},
-    types::{AnswerRecord, GetRunResponse, ReasoningRun, ReasoningStep, RunPhase, RunStatus},
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{
+            AnswerRecord, AnswerRevision, CitationHeat, CitationHeatmap, GetRunResponse, NodeRef,
+            NodeType, QualityAggregate, QualityMetrics, QualityTrendPoint, QualityTrendsResponse,
+            ReasoningRun, ReasoningStep, RunManifest, RunPhase, RunStatus, UnusedContentReport,
+            UnusedDocument, UnusedSection,
+        },
+    },
+    db::{node_text, retry_busy},
+    providers::{tokens::estimate_tokens, CallKind, ProviderResponse},
 };
 
+/// What an answer revision was generated with, read from its run: provider,
+/// models, generation settings, step budget and prompt versions. The
+/// `0036_answer_revisions` migration backfills with the same expression.
+const REVISION_GENERATION_JSON: &str = r#"json_object(
+  'provider', json_extract(r.manifest_json, '$.provider'),
+  'model', json_extract(r.manifest_json, '$.model'),
+  'plannerModel', json_extract(r.manifest_json, '$.plannerModel'),
+  'generation', json(COALESCE(json_extract(r.manifest_json, '$.generation'), '{}')),
+  'maxSteps', r.max_steps,
+  'promptVersions', json(r.prompt_versions_json)
+)"#;
+
 #[derive(Debug, Clone)]
 pub struct NewStep<'a> {
     pub run_id: &'a str,
@@ -14,11 +40,31 @@ pub struct NewStep<'a> {
     pub thought: &'a str,
     pub action: &'a str,
     pub observation: &'a str,
+    pub summary: &'a str,
     pub node_refs: Vec<String>,
+    pub resolved_refs: &'a [NodeRef],
     pub confidence: f64,
     pub latency_ms: i64,
 }
 
+#[derive(Debug, Clone)]
+pub struct RecordedProviderResponse {
+    pub seq: i64,
+    pub call_kind: String,
+    pub request_hash: String,
+    pub model: String,
+    pub response: ProviderResponse,
+    pub latency_ms: i64,
+    pub cached: bool,
+}
+
+/// How long a provider call took and whether the cache answered it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallTiming {
+    pub latency_ms: i64,
+    pub cached: bool,
+}
+
 fn parse_timestamp(value: String) -> AppResult<DateTime<Utc>> {
     DateTime::parse_from_rfc3339(&value)
         .map(|v| v.with_timezone(&Utc))
@@ -32,60 +78,273 @@ pub async fn create_run(
     document_id: Option<&str>,
     query: &str,
 ) -> AppResult<()> {
-    sqlx::query(
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO reasoning_runs (id, project_id, document_id, query, status)
+            VALUES (?1, ?2, ?3, ?4, 'running')
+            "#,
+        )
+        .bind(run_id)
+        .bind(project_id)
+        .bind(document_id)
+        .bind(query)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn update_run_phase(pool: &SqlitePool, run_id: &str, phase: &str) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET phase = ?2
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(phase)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn set_run_prompt_versions(
+    pool: &SqlitePool,
+    run_id: &str,
+    prompt_versions_json: serde_json::Value,
+) -> AppResult<()> {
+    let prompt_versions_json = &prompt_versions_json;
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET prompt_versions_json = ?2
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(prompt_versions_json.to_string())
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn set_run_manifest(
+    pool: &SqlitePool,
+    run_id: &str,
+    manifest: &RunManifest,
+) -> AppResult<()> {
+    let manifest_json =
+        &serde_json::to_string(manifest).map_err(|err| AppError::Internal(err.to_string()))?;
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET manifest_json = ?2
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(manifest_json)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn set_run_diagnostics(
+    pool: &SqlitePool,
+    run_id: &str,
+    diagnostics_json: serde_json::Value,
+) -> AppResult<()> {
+    let diagnostics_json = &diagnostics_json;
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET diagnostics_json = ?2
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(diagnostics_json.to_string())
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn set_run_execution(
+    pool: &SqlitePool,
+    run_id: &str,
+    max_steps: i64,
+    replay_of: Option<&str>,
+    attach_figure_images: bool,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET max_steps = ?2,
+                replay_of = ?3,
+                attach_figure_images = ?4
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(max_steps)
+        .bind(replay_of)
+        .bind(attach_figure_images)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+pub async fn record_provider_response(
+    pool: &SqlitePool,
+    run_id: &str,
+    kind: CallKind,
+    request_hash: &str,
+    model: &str,
+    response: &ProviderResponse,
+    timing: CallTiming,
+) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO provider_responses (
+              run_id, seq, call_kind, request_hash, model, response_json, latency_ms, cached
+            )
+            SELECT ?1, COALESCE(MAX(seq), 0) + 1, ?2, ?3, ?4, ?5, ?6, ?7
+            FROM provider_responses
+            WHERE run_id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(kind.as_str())
+        .bind(request_hash)
+        .bind(model)
+        .bind(
+            serde_json::to_string(response)
+                .map_err(|err: serde_json::Error| AppError::Internal(err.to_string()))?,
+        )
+        .bind(timing.latency_ms)
+        .bind(timing.cached)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Mean latency of the last `sample` answered (not cached) calls of `kind`
+/// to `model`; `None` before the first.
+pub async fn average_call_latency(
+    pool: &SqlitePool,
+    model: &str,
+    kind: CallKind,
+    sample: i64,
+) -> AppResult<Option<i64>> {
+    let row = sqlx::query(
         r#"
-        INSERT INTO reasoning_runs (id, project_id, document_id, query, status)
-        VALUES (?1, ?2, ?3, ?4, 'running')
+        SELECT CAST(AVG(latency_ms) AS INTEGER) AS latency_ms
+        FROM (
+          SELECT latency_ms
+          FROM provider_responses
+          WHERE model = ?1 AND call_kind = ?2 AND cached = 0 AND latency_ms > 0
+          ORDER BY created_at DESC
+          LIMIT ?3
+        )
         "#,
     )
-    .bind(run_id)
-    .bind(project_id)
-    .bind(document_id)
-    .bind(query)
-    .execute(pool)
+    .bind(model)
+    .bind(kind.as_str())
+    .bind(sample)
+    .fetch_one(pool)
     .await?;
-    Ok(())
+    Ok(row.try_get("latency_ms")?)
 }
 
-pub async fn update_run_phase(pool: &SqlitePool, run_id: &str, phase: &str) -> AppResult<()> {
-    sqlx::query(
+pub async fn list_provider_responses(
+    pool: &SqlitePool,
+    run_id: &str,
+) -> AppResult<Vec<RecordedProviderResponse>> {
+    let rows = sqlx::query(
         r#"
-        UPDATE reasoning_runs
-        SET phase = ?2
-        WHERE id = ?1
+        SELECT seq, call_kind, request_hash, model, response_json, latency_ms, cached
+        FROM provider_responses
+        WHERE run_id = ?1
+        ORDER BY seq ASC
         "#,
     )
     .bind(run_id)
-    .bind(phase)
-    .execute(pool)
+    .fetch_all(pool)
     .await?;
-    Ok(())
+
+    rows.into_iter()
+        .map(|row| {
+            let response_raw: String = row.try_get("response_json")?;
+            Ok(RecordedProviderResponse {
+                seq: row.try_get("seq")?,
+                call_kind: row.try_get("call_kind")?,
+                request_hash: row.try_get("request_hash")?,
+                model: row.try_get("model")?,
+                response: serde_json::from_str(&response_raw).map_err(|err| {
+                    AppError::Database(format!("invalid recorded response: {err}"))
+                })?,
+                latency_ms: row.try_get("latency_ms")?,
+                cached: row.try_get("cached")?,
+            })
+        })
+        .collect()
 }
 
 pub async fn add_step(pool: &SqlitePool, step: NewStep<'_>) -> AppResult<()> {
-    sqlx::query(
-        r#"
-        INSERT INTO reasoning_steps (
-          run_id, idx, step_type, thought, action, observation, node_refs_json, confidence, latency_ms
+    let step = &step;
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO reasoning_steps (
+              run_id, idx, step_type, thought, action, observation, summary, node_refs_json,
+              resolved_refs_json, confidence, latency_ms
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
         )
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-        "#,
-    )
-    .bind(step.run_id)
-    .bind(step.idx)
-    .bind(step.step_type)
-    .bind(step.thought)
-    .bind(step.action)
-    .bind(step.observation)
-    .bind(
-        serde_json::to_string(&step.node_refs)
-            .map_err(|err: serde_json::Error| AppError::Internal(err.to_string()))?,
-    )
-    .bind(step.confidence)
-    .bind(step.latency_ms)
-    .execute(pool)
-    .await?;
-    Ok(())
+        .bind(step.run_id)
+        .bind(step.idx)
+        .bind(step.step_type)
+        .bind(step.thought)
+        .bind(step.action)
+        .bind(step.observation)
+        .bind(step.summary)
+        .bind(
+            serde_json::to_string(&step.node_refs)
+                .map_err(|err: serde_json::Error| AppError::Internal(err.to_string()))?,
+        )
+        .bind(
+            serde_json::to_string(step.resolved_refs)
+                .map_err(|err: serde_json::Error| AppError::Internal(err.to_string()))?,
+        )
+        .bind(step.confidence)
+        .bind(step.latency_ms)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
 }
 
 pub async fn complete_run(
@@ -101,69 +360,129 @@ pub async fn complete_run(
     quality_json: serde_json::Value,
     planner_trace_json: serde_json::Value,
 ) -> AppResult<()> {
-    let mut tx = pool.begin().await?;
-    sqlx::query(
-        r#"
-        UPDATE reasoning_runs
-        SET status = 'completed',
-            phase = 'completed',
-            ended_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
-            total_latency_ms = ?2,
-            token_usage_json = ?3,
-            cost_usd = ?4,
-            quality_json = ?5,
-            planner_trace_json = ?6
-        WHERE id = ?1
-        "#,
-    )
-    .bind(run_id)
-    .bind(total_latency_ms)
-    .bind(token_usage_json.to_string())
-    .bind(cost_usd)
-    .bind(quality_json.to_string())
-    .bind(planner_trace_json.to_string())
-    .execute(&mut *tx)
-    .await?;
-    sqlx::query(
-        r#"
-        INSERT OR REPLACE INTO answers (run_id, answer_markdown, citations_json, confidence, grounded)
-        VALUES (?1, ?2, ?3, ?4, ?5)
-        "#,
-    )
-    .bind(run_id)
-    .bind(answer_markdown)
-    .bind(
-        serde_json::to_string(&citations)
-            .map_err(|err: serde_json::Error| AppError::Internal(err.to_string()))?,
-    )
-    .bind(confidence)
-    .bind(if grounded { 1 } else { 0 })
-    .execute(&mut *tx)
-    .await?;
-    tx.commit().await?;
-    Ok(())
+    let token_usage_json = &token_usage_json;
+    let citations = &citations;
+    let quality_json = &quality_json;
+    let planner_trace_json = &planner_trace_json;
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET status = 'completed',
+                phase = 'completed',
+                ended_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+                total_latency_ms = ?2,
+                token_usage_json = ?3,
+                cost_usd = ?4,
+                quality_json = ?5,
+                planner_trace_json = ?6
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(total_latency_ms)
+        .bind(token_usage_json.to_string())
+        .bind(cost_usd)
+        .bind(quality_json.to_string())
+        .bind(planner_trace_json.to_string())
+        .execute(&mut *tx)
+        .await?;
+        let citations_json = serde_json::to_string(&citations)
+            .map_err(|err: serde_json::Error| AppError::Internal(err.to_string()))?;
+        let revision: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(revision), 0) + 1 FROM answer_revisions WHERE run_id = ?1",
+        )
+        .bind(run_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO answer_revisions (
+              run_id, revision, answer_markdown, citations_json, confidence, grounded,
+              generation_json
+            )
+            SELECT r.id, ?2, ?3, ?4, ?5, ?6, {REVISION_GENERATION_JSON}
+            FROM reasoning_runs r
+            WHERE r.id = ?1
+            "#
+        ))
+        .bind(run_id)
+        .bind(revision)
+        .bind(answer_markdown)
+        .bind(&citations_json)
+        .bind(confidence)
+        .bind(if grounded { 1 } else { 0 })
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO answers (run_id, answer_markdown, citations_json, confidence, grounded, revision)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(run_id)
+        .bind(answer_markdown)
+        .bind(&citations_json)
+        .bind(confidence)
+        .bind(if grounded { 1 } else { 0 })
+        .bind(revision)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Stores the metrics of a run the quality gate turned away, so trends count
+/// it; `fail_run` marks it failed.
+pub async fn set_run_quality(
+    pool: &SqlitePool,
+    run_id: &str,
+    quality_json: serde_json::Value,
+) -> AppResult<()> {
+    let quality_json = &quality_json;
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET quality_json = ?2
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .bind(quality_json.to_string())
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
 }
 
 pub async fn fail_run(pool: &SqlitePool, run_id: &str) -> AppResult<()> {
-    sqlx::query(
-        r#"
-        UPDATE reasoning_runs
-        SET status = 'failed',
-            phase = 'failed',
-            ended_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
-        WHERE id = ?1
-        "#,
-    )
-    .bind(run_id)
-    .execute(pool)
-    .await?;
-    Ok(())
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            UPDATE reasoning_runs
+            SET status = 'failed',
+                phase = 'failed',
+                ended_at = (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+            WHERE id = ?1
+            "#,
+        )
+        .bind(run_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
 }
 
 pub async fn get_run(pool: &SqlitePool, run_id: &str) -> AppResult<GetRunResponse> {
     let run_row = sqlx::query(
         r#"
-        SELECT id, project_id, document_id, query, status, phase, started_at, ended_at, total_latency_ms, token_usage_json, cost_usd, quality_json, planner_trace_json
+        SELECT id, project_id, document_id, query, status, phase, started_at, ended_at, total_latency_ms, token_usage_json, cost_usd, quality_json, planner_trace_json, prompt_versions_json, max_steps, replay_of, diagnostics_json, attach_figure_images, manifest_json
         FROM reasoning_runs
         WHERE id = ?1
         "#,
@@ -180,6 +499,9 @@ pub async fn get_run(pool: &SqlitePool, run_id: &str) -> AppResult<GetRunRespons
     let token_usage_raw: String = run_row.try_get("token_usage_json")?;
     let quality_raw: String = run_row.try_get("quality_json")?;
     let planner_trace_raw: String = run_row.try_get("planner_trace_json")?;
+    let prompt_versions_raw: String = run_row.try_get("prompt_versions_json")?;
+    let diagnostics_raw: String = run_row.try_get("diagnostics_json")?;
+    let manifest_raw: String = run_row.try_get("manifest_json")?;
     let run = ReasoningRun {
         id: run_row.try_get("id")?,
         project_id: run_row.try_get("project_id")?,
@@ -200,11 +522,19 @@ pub async fn get_run(pool: &SqlitePool, run_id: &str) -> AppResult<GetRunRespons
         quality_json: serde_json::from_str(&quality_raw).unwrap_or_else(|_| serde_json::json!({})),
         planner_trace_json: serde_json::from_str(&planner_trace_raw)
             .unwrap_or_else(|_| serde_json::json!([])),
+        prompt_versions_json: serde_json::from_str(&prompt_versions_raw)
+            .unwrap_or_else(|_| serde_json::json!({})),
+        max_steps: run_row.try_get("max_steps")?,
+        replay_of: run_row.try_get("replay_of")?,
+        diagnostics_json: serde_json::from_str(&diagnostics_raw)
+            .unwrap_or_else(|_| serde_json::json!([])),
+        attach_figure_images: run_row.try_get("attach_figure_images")?,
+        manifest: serde_json::from_str(&manifest_raw).ok(),
     };
 
     let step_rows = sqlx::query(
         r#"
-        SELECT run_id, idx, step_type, thought, action, observation, node_refs_json, confidence, latency_ms
+        SELECT run_id, idx, step_type, thought, action, observation, summary, node_refs_json, resolved_refs_json, confidence, latency_ms
         FROM reasoning_steps
         WHERE run_id = ?1
         ORDER BY idx ASC
@@ -217,6 +547,7 @@ pub async fn get_run(pool: &SqlitePool, run_id: &str) -> AppResult<GetRunRespons
     let mut steps = Vec::with_capacity(step_rows.len());
     for row in step_rows {
         let node_refs_raw: String = row.try_get("node_refs_json")?;
+        let resolved_refs_raw: String = row.try_get("resolved_refs_json")?;
         steps.push(ReasoningStep {
             run_id: row.try_get("run_id")?,
             idx: row.try_get("idx")?,
@@ -224,7 +555,9 @@ pub async fn get_run(pool: &SqlitePool, run_id: &str) -> AppResult<GetRunRespons
             thought: row.try_get("thought")?,
             action: row.try_get("action")?,
             observation: row.try_get("observation")?,
+            summary: row.try_get("summary")?,
             node_refs: serde_json::from_str(&node_refs_raw).unwrap_or_else(|_| vec![]),
+            resolved_refs: serde_json::from_str(&resolved_refs_raw).unwrap_or_else(|_| vec![]),
             confidence: row.try_get("confidence")?,
             latency_ms: row.try_get("latency_ms")?,
         });
@@ -251,6 +584,103 @@ pub async fn get_run(pool: &SqlitePool, run_id: &str) -> AppResult<GetRunRespons
     Ok(GetRunResponse { run, steps, answer })
 }
 
+/// Every answer generated for `run_id`, newest first, marking the one its
+/// answer currently is.
+pub async fn list_answer_revisions(
+    pool: &SqlitePool,
+    run_id: &str,
+) -> AppResult<Vec<AnswerRevision>> {
+    let exists = sqlx::query("SELECT 1 FROM reasoning_runs WHERE id = ?1")
+        .bind(run_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound(format!("run {run_id}")));
+    }
+    let rows = sqlx::query(
+        r#"
+        SELECT v.run_id, v.revision, v.answer_markdown, v.citations_json, v.confidence,
+               v.grounded, v.generation_json, v.created_at,
+               COALESCE(a.revision = v.revision, 0) AS current
+        FROM answer_revisions v
+        LEFT JOIN answers a ON a.run_id = v.run_id
+        WHERE v.run_id = ?1
+        ORDER BY v.revision DESC
+        "#,
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let citations_raw: String = row.try_get("citations_json")?;
+            let generation_raw: String = row.try_get("generation_json")?;
+            Ok(AnswerRevision {
+                run_id: row.try_get("run_id")?,
+                revision: row.try_get("revision")?,
+                answer_markdown: row.try_get("answer_markdown")?,
+                citations: serde_json::from_str(&citations_raw).unwrap_or_else(|_| vec![]),
+                confidence: row.try_get("confidence")?,
+                grounded: row.try_get::<i64, _>("grounded")? == 1,
+                generation: serde_json::from_str(&generation_raw)
+                    .unwrap_or_else(|_| serde_json::json!({})),
+                current: row.try_get::<i64, _>("current")? == 1,
+                created_at: parse_timestamp(row.try_get("created_at")?)?,
+            })
+        })
+        .collect()
+}
+
+/// Makes revision `revision` the run's answer again. Later revisions are
+/// kept, so restoring can itself be undone.
+pub async fn restore_answer_revision(
+    pool: &SqlitePool,
+    run_id: &str,
+    revision: i64,
+) -> AppResult<AnswerRecord> {
+    retry_busy(|| async move {
+        let mut tx = pool.begin().await?;
+        let row = sqlx::query(
+            r#"
+            SELECT answer_markdown, citations_json, confidence, grounded
+            FROM answer_revisions
+            WHERE run_id = ?1 AND revision = ?2
+            "#,
+        )
+        .bind(run_id)
+        .bind(revision)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("revision {revision} of run {run_id}")))?;
+        let citations_raw: String = row.try_get("citations_json")?;
+        let answer = AnswerRecord {
+            run_id: run_id.to_string(),
+            answer_markdown: row.try_get("answer_markdown")?,
+            citations: serde_json::from_str(&citations_raw).unwrap_or_else(|_| vec![]),
+            confidence: row.try_get("confidence")?,
+            grounded: row.try_get::<i64, _>("grounded")? == 1,
+        };
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO answers (run_id, answer_markdown, citations_json, confidence, grounded, revision)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(run_id)
+        .bind(&answer.answer_markdown)
+        .bind(&citations_raw)
+        .bind(answer.confidence)
+        .bind(if answer.grounded { 1 } else { 0 })
+        .bind(revision)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(answer)
+    })
+    .await
+}
+
 fn parse_phase(raw: &str) -> RunPhase {
     match raw {
         "planning" => RunPhase::Planning,
@@ -262,3 +692,407 @@ fn parse_phase(raw: &str) -> RunPhase {
         _ => RunPhase::Planning,
     }
 }
+
+#[derive(Default)]
+struct QualityTally {
+    runs: i64,
+    overall: f64,
+    grounded: i64,
+    gate_failures: i64,
+}
+
+impl QualityTally {
+    fn add(&mut self, quality: &QualityMetrics, gate_failed: bool) {
+        self.runs += 1;
+        self.overall += quality.overall;
+        self.grounded += i64::from(quality.grounded);
+        self.gate_failures += i64::from(gate_failed);
+    }
+
+    fn aggregate(&self) -> QualityAggregate {
+        if self.runs == 0 {
+            return QualityAggregate::default();
+        }
+        let runs = self.runs as f64;
+        QualityAggregate {
+            run_count: self.runs,
+            avg_overall: self.overall / runs,
+            grounding_rate: self.grounded as f64 / runs,
+            gate_failure_rate: self.gate_failures as f64 / runs,
+        }
+    }
+}
+
+/// Daily quality of a project's evaluated runs. A run counts once it reached
+/// the quality gate: completed runs with a grounded answer passed it, while
+/// those answering with missing evidence, and older failed runs with
+/// metrics, were turned away by it. Runs that failed earlier, or predate
+/// stored metrics, are left out.
+pub async fn quality_trends(
+    pool: &SqlitePool,
+    project_id: &str,
+) -> AppResult<QualityTrendsResponse> {
+    let rows = sqlx::query(
+        r#"
+        SELECT substr(r.started_at, 1, 10) AS day, r.status, r.quality_json,
+               COALESCE(a.grounded, 0) AS grounded
+        FROM reasoning_runs r
+        LEFT JOIN answers a ON a.run_id = r.id
+        WHERE r.project_id = ?1 AND r.status IN ('completed', 'failed')
+        ORDER BY r.started_at ASC
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut days: std::collections::BTreeMap<String, QualityTally> = Default::default();
+    let mut total = QualityTally::default();
+    for row in rows {
+        let quality_raw: String = row.try_get("quality_json")?;
+        let Ok(quality) = serde_json::from_str::<QualityMetrics>(&quality_raw) else {
+            continue;
+        };
+        let gate_failed = row.try_get::<String, _>("status")? == "failed"
+            || row.try_get::<i64, _>("grounded")? == 0;
+        days.entry(row.try_get("day")?)
+            .or_default()
+            .add(&quality, gate_failed);
+        total.add(&quality, gate_failed);
+    }
+
+    Ok(QualityTrendsResponse {
+        project_id: project_id.to_string(),
+        points: days
+            .into_iter()
+            .map(|(day, tally)| QualityTrendPoint {
+                day,
+                quality: tally.aggregate(),
+            })
+            .collect(),
+        total: total.aggregate(),
+    })
+}
+
+/// What a citation heatmap covers.
+#[derive(Debug, Clone, Copy)]
+pub enum HeatmapScope<'a> {
+    /// A project's documents, owned or linked, as its own runs cite them.
+    Project(&'a str),
+    /// One document, as runs in any project cite it.
+    Document(&'a str),
+}
+
+/// Citation counts per node across the current answers of all runs in
+/// scope. Citations of nodes replaced by a re-ingest count under their new
+/// ids. A node's `total` also counts answers citing nodes beneath it, so a
+/// section is as hot as its content is cited.
+pub async fn citation_heatmap(
+    pool: &SqlitePool,
+    scope: HeatmapScope<'_>,
+) -> AppResult<CitationHeatmap> {
+    let (answers, nodes) = match scope {
+        HeatmapScope::Project(project_id) => (
+            sqlx::query(
+                r#"
+                SELECT a.citations_json
+                FROM answers a
+                JOIN reasoning_runs r ON r.id = a.run_id
+                WHERE r.project_id = ?1
+                "#,
+            )
+            .bind(project_id)
+            .fetch_all(pool)
+            .await?,
+            sqlx::query(
+                r#"
+                SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.ordinal_path
+                FROM doc_nodes dn
+                JOIN documents d ON d.id = dn.document_id
+                WHERE d.project_id = ?1
+                   OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1)
+                "#,
+            )
+            .bind(project_id)
+            .fetch_all(pool)
+            .await?,
+        ),
+        HeatmapScope::Document(document_id) => (
+            sqlx::query("SELECT citations_json FROM answers")
+                .fetch_all(pool)
+                .await?,
+            sqlx::query(
+                r#"
+                SELECT id, document_id, parent_id, node_type, title, ordinal_path
+                FROM doc_nodes
+                WHERE document_id = ?1
+                "#,
+            )
+            .bind(document_id)
+            .fetch_all(pool)
+            .await?,
+        ),
+    };
+    let remaps = sqlx::query("SELECT old_node_id, new_node_id FROM node_id_remaps")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| Ok((row.try_get("old_node_id")?, row.try_get("new_node_id")?)))
+        .collect::<AppResult<HashMap<String, String>>>()?;
+
+    let mut heat = nodes
+        .into_iter()
+        .map(|row| {
+            Ok(CitationHeat {
+                node_id: row.try_get("id")?,
+                document_id: row.try_get("document_id")?,
+                parent_id: row.try_get("parent_id")?,
+                node_type: NodeType::from_str(&row.try_get::<String, _>("node_type")?),
+                title: row.try_get("title")?,
+                ordinal_path: row.try_get("ordinal_path")?,
+                citations: 0,
+                total: 0,
+                intensity: 0.0,
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+    let index = heat
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.node_id.clone(), i))
+        .collect::<HashMap<_, _>>();
+
+    let mut answer_count = 0;
+    for row in answers {
+        let raw: String = row.try_get("citations_json")?;
+        let cited = serde_json::from_str::<Vec<String>>(&raw)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|id| remaps.get(&id).cloned().unwrap_or(id))
+            .filter_map(|id| index.get(&id).copied())
+            .collect::<HashSet<_>>();
+        if cited.is_empty() {
+            continue;
+        }
+        answer_count += 1;
+        let mut reached = HashSet::new();
+        for &i in &cited {
+            heat[i].citations += 1;
+            let mut at = Some(i);
+            while let Some(i) = at.filter(|&i| reached.insert(i)) {
+                heat[i].total += 1;
+                at = heat[i]
+                    .parent_id
+                    .as_deref()
+                    .and_then(|id| index.get(id).copied());
+            }
+        }
+    }
+
+    heat.retain(|node| node.total > 0);
+    let max_total = heat.iter().map(|node| node.total).max().unwrap_or(0);
+    for node in &mut heat {
+        node.intensity = node.total as f64 / max_total as f64;
+    }
+    heat.sort_by(|a, b| {
+        b.total
+            .cmp(&a.total)
+            .then(b.citations.cmp(&a.citations))
+            .then_with(|| a.document_id.cmp(&b.document_id))
+            .then_with(|| a.ordinal_path.cmp(&b.ordinal_path))
+    });
+
+    let (project_id, document_id) = match scope {
+        HeatmapScope::Project(project_id) => (Some(project_id.to_string()), None),
+        HeatmapScope::Document(document_id) => (None, Some(document_id.to_string())),
+    };
+    Ok(CitationHeatmap {
+        project_id,
+        document_id,
+        answer_count,
+        max_total,
+        nodes: heat,
+    })
+}
+
+/// Sections and documents of `project_id` that none of its runs has used; see
+/// [`UnusedContentReport`]. A node counts as retrieved once a step names it
+/// and as cited once any answer revision does, under its current id if it
+/// was re-ingested since. A section is used when it or anything in it was.
+pub async fn unused_content(pool: &SqlitePool, project_id: &str) -> AppResult<UnusedContentReport> {
+    let run_count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM reasoning_runs WHERE project_id = ?1")
+            .bind(project_id)
+            .fetch_one(pool)
+            .await?;
+    let remaps = sqlx::query("SELECT old_node_id, new_node_id FROM node_id_remaps")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| Ok((row.try_get("old_node_id")?, row.try_get("new_node_id")?)))
+        .collect::<AppResult<HashMap<String, String>>>()?;
+    let node_ids = |rows: Vec<sqlx::sqlite::SqliteRow>| -> AppResult<HashSet<String>> {
+        let mut ids = HashSet::new();
+        for row in rows {
+            let raw: String = row.try_get("ids_json")?;
+            for id in serde_json::from_str::<Vec<String>>(&raw).unwrap_or_default() {
+                ids.insert(remaps.get(&id).cloned().unwrap_or(id));
+            }
+        }
+        Ok(ids)
+    };
+    let retrieved = node_ids(
+        sqlx::query(
+            r#"
+            SELECT s.node_refs_json AS ids_json
+            FROM reasoning_steps s
+            JOIN reasoning_runs r ON r.id = s.run_id
+            WHERE r.project_id = ?1
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await?,
+    )?;
+    let cited = node_ids(
+        sqlx::query(
+            r#"
+            SELECT a.citations_json AS ids_json
+            FROM answers a
+            JOIN reasoning_runs r ON r.id = a.run_id
+            WHERE r.project_id = ?1
+            UNION ALL
+            SELECT v.citations_json
+            FROM answer_revisions v
+            JOIN reasoning_runs r ON r.id = v.run_id
+            WHERE r.project_id = ?1
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await?,
+    )?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT dn.id, dn.document_id, dn.parent_id, dn.node_type, dn.title, dn.text, dn.text_zstd,
+               dn.ordinal_path, d.name AS document_name
+        FROM doc_nodes dn
+        JOIN documents d ON d.id = dn.document_id
+        WHERE d.project_id = ?1 OR d.id IN (SELECT document_id FROM document_links WHERE project_id = ?1)
+        ORDER BY d.created_at ASC, d.id, dn.ordinal_path
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+    struct Node {
+        id: String,
+        document_id: String,
+        document_name: String,
+        parent_id: Option<String>,
+        node_type: NodeType,
+        title: String,
+        ordinal_path: String,
+        tokens: usize,
+    }
+    let nodes = rows
+        .into_iter()
+        .map(|row| {
+            Ok(Node {
+                id: row.try_get("id")?,
+                document_id: row.try_get("document_id")?,
+                document_name: row.try_get("document_name")?,
+                parent_id: row.try_get("parent_id")?,
+                node_type: NodeType::from_str(&row.try_get::<String, _>("node_type")?),
+                title: row.try_get("title")?,
+                ordinal_path: row.try_get("ordinal_path")?,
+                tokens: estimate_tokens(&node_text::from_row(&row)?),
+            })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    // Each node's size and use, added to every node above it.
+    let index = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    let mut subtree = nodes
+        .iter()
+        .map(|node| (1, node.tokens))
+        .collect::<Vec<_>>();
+    let mut used = nodes
+        .iter()
+        .map(|node| retrieved.contains(&node.id) || cited.contains(&node.id))
+        .collect::<Vec<_>>();
+    for (i, node) in nodes.iter().enumerate() {
+        let node_used = used[i];
+        let mut parent = node.parent_id.as_deref();
+        while let Some(&p) = parent.and_then(|id| index.get(id)) {
+            subtree[p].0 += 1;
+            subtree[p].1 += node.tokens;
+            used[p] |= node_used;
+            parent = nodes[p].parent_id.as_deref();
+        }
+    }
+
+    let is_section =
+        |node: &Node| matches!(node.node_type, NodeType::Section | NodeType::Subsection);
+    let mut sections = nodes
+        .iter()
+        .enumerate()
+        .filter(|&(i, node)| is_section(node) && !used[i])
+        .filter(|(_, node)| {
+            !node
+                .parent_id
+                .as_deref()
+                .and_then(|id| index.get(id))
+                .is_some_and(|&p| is_section(&nodes[p]) && !used[p])
+        })
+        .map(|(i, node)| UnusedSection {
+            node_id: node.id.clone(),
+            document_id: node.document_id.clone(),
+            document_name: node.document_name.clone(),
+            node_type: node.node_type.clone(),
+            title: node.title.clone(),
+            ordinal_path: node.ordinal_path.clone(),
+            node_count: subtree[i].0,
+            estimated_tokens: subtree[i].1,
+        })
+        .collect::<Vec<_>>();
+    sections.sort_by_key(|section| Reverse(section.estimated_tokens));
+
+    let mut documents: Vec<UnusedDocument> = vec![];
+    let mut document_cited = HashSet::new();
+    for node in &nodes {
+        if cited.contains(&node.id) {
+            document_cited.insert(node.document_id.as_str());
+        }
+        let document = match documents.last_mut() {
+            Some(document) if document.document_id == node.document_id => document,
+            _ => {
+                documents.push(UnusedDocument {
+                    document_id: node.document_id.clone(),
+                    name: node.document_name.clone(),
+                    node_count: 0,
+                    estimated_tokens: 0,
+                    retrieved_nodes: 0,
+                });
+                documents.last_mut().expect("pushed above")
+            }
+        };
+        document.node_count += 1;
+        document.estimated_tokens += node.tokens;
+        document.retrieved_nodes += usize::from(retrieved.contains(&node.id));
+    }
+    documents.retain(|document| !document_cited.contains(document.document_id.as_str()));
+    documents.sort_by_key(|document| Reverse(document.estimated_tokens));
+
+    Ok(UnusedContentReport {
+        project_id: project_id.to_string(),
+        run_count,
+        sections,
+        documents,
+    })
+}
@@ -0,0 +1,180 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sqlx::{Row, SqlitePool};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        privacy::Redactor,
+        types::{
+            AdminPolicy, BackupSettings, ExternalParserConfig, KeySelection, LanguageResources,
+            MetricsSettings, ModelPricing, ModelSelection, PrivacySettings,
+            ProviderTimeoutSettings, QualityThresholds, RetrievalSettings,
+        },
+    },
+    db::retry_busy,
+};
+
+/// `app_settings` key holding the [`ModelSelection`](crate::core::types::ModelSelection).
+pub const MODEL_SELECTION_SETTING: &str = "model_selection";
+/// `app_settings` key holding user per-model rates that override the bundled pricing table.
+pub const PRICING_OVERRIDES_SETTING: &str = "pricing_overrides";
+/// `app_settings` key holding the [`ExternalParserConfig`](crate::core::types::ExternalParserConfig).
+pub const EXTERNAL_PARSER_SETTING: &str = "external_parser";
+/// `app_settings` key holding the [`LanguageResources`](crate::core::types::LanguageResources).
+pub const LANGUAGE_RESOURCES_SETTING: &str = "language_resources";
+/// `app_settings` key holding the [`QualityThresholds`](crate::core::types::QualityThresholds).
+pub const QUALITY_THRESHOLDS_SETTING: &str = "quality_thresholds";
+/// `app_settings` key holding the [`PrivacySettings`](crate::core::types::PrivacySettings).
+pub const PRIVACY_SETTING: &str = "privacy";
+/// `app_settings` key holding the [`MetricsSettings`](crate::core::types::MetricsSettings).
+pub const METRICS_SETTING: &str = "metrics";
+/// `app_settings` key holding the [`ProviderTimeoutSettings`](crate::core::types::ProviderTimeoutSettings).
+pub const PROVIDER_TIMEOUTS_SETTING: &str = "provider_timeouts";
+/// `app_settings` key holding the [`RetrievalSettings`](crate::core::types::RetrievalSettings).
+pub const RETRIEVAL_SETTING: &str = "retrieval";
+/// `app_settings` key holding the [`BackupSettings`](crate::core::types::BackupSettings).
+pub const BACKUP_SETTING: &str = "backups";
+/// `app_settings` key holding the [`KeySelection`](crate::core::types::KeySelection).
+pub const KEY_SELECTION_SETTING: &str = "provider_key_selection";
+/// Policy name that locks the stored provider keys. The keys live in the
+/// keyring, so the config file can lock them but not preset them.
+pub const PROVIDER_KEYS_LOCK: &str = "provider_keys";
+/// Policy name that locks every project's generation settings. They are
+/// stored per project, so the config file can lock them but not preset them.
+pub const GENERATION_SETTINGS_LOCK: &str = "generation";
+
+/// Reads a JSON value from `app_settings`; unreadable values fall back to the default.
+pub async fn get_setting<T>(pool: &SqlitePool, key: &str) -> AppResult<T>
+where
+    T: DeserializeOwned + Default,
+{
+    let row = sqlx::query("SELECT value_json FROM app_settings WHERE key = ?1")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    match row {
+        Some(row) => {
+            let raw: String = row.try_get("value_json")?;
+            Ok(serde_json::from_str(&raw).unwrap_or_default())
+        }
+        None => Ok(T::default()),
+    }
+}
+
+pub async fn set_setting<T: Serialize>(pool: &SqlitePool, key: &str, value: &T) -> AppResult<()> {
+    retry_busy(|| async move {
+        sqlx::query(
+            r#"
+            INSERT INTO app_settings (key, value_json)
+            VALUES (?1, ?2)
+            ON CONFLICT(key) DO UPDATE SET value_json = excluded.value_json
+            "#,
+        )
+        .bind(key)
+        .bind(serde_json::to_string(value).map_err(|err| AppError::Internal(err.to_string()))?)
+        .execute(pool)
+        .await?;
+        Ok(())
+    })
+    .await
+}
+
+/// The redactor for log lines, diagnostics and events under the privacy
+/// setting; privacy mode when the setting cannot be read.
+pub async fn privacy_redactor(pool: &SqlitePool) -> Redactor {
+    get_setting(pool, PRIVACY_SETTING)
+        .await
+        .map(Redactor::new)
+        .unwrap_or_default()
+}
+
+/// `preset` for `key` in the shape [`get_setting`] reads it, or the setting's
+/// default without one.
+fn preset_value(key: &str, preset: Option<&Value>) -> AppResult<Value> {
+    fn typed<T: DeserializeOwned + Serialize + Default>(
+        key: &str,
+        preset: Option<&Value>,
+    ) -> AppResult<Value> {
+        let value = match preset {
+            Some(preset) => serde_json::from_value::<T>(preset.clone()).map_err(|err| {
+                AppError::InvalidInput(format!("preset for the {key} setting: {err}"))
+            })?,
+            None => T::default(),
+        };
+        Ok(serde_json::to_value(value)?)
+    }
+    match key {
+        MODEL_SELECTION_SETTING => typed::<ModelSelection>(key, preset),
+        PRICING_OVERRIDES_SETTING => typed::<BTreeMap<String, ModelPricing>>(key, preset),
+        EXTERNAL_PARSER_SETTING => typed::<ExternalParserConfig>(key, preset),
+        LANGUAGE_RESOURCES_SETTING => typed::<LanguageResources>(key, preset),
+        QUALITY_THRESHOLDS_SETTING => typed::<QualityThresholds>(key, preset),
+        PRIVACY_SETTING => typed::<PrivacySettings>(key, preset),
+        METRICS_SETTING => typed::<MetricsSettings>(key, preset),
+        PROVIDER_TIMEOUTS_SETTING => typed::<ProviderTimeoutSettings>(key, preset),
+        RETRIEVAL_SETTING => typed::<RetrievalSettings>(key, preset),
+        BACKUP_SETTING => typed::<BackupSettings>(key, preset),
+        KEY_SELECTION_SETTING => typed::<KeySelection>(key, preset),
+        _ => Err(AppError::InvalidInput(format!(
+            "{key} is not a setting the config file can set or lock"
+        ))),
+    }
+}
+
+/// Settings the policy presets or locks.
+fn policy_keys(policy: &AdminPolicy) -> BTreeSet<&str> {
+    policy
+        .presets
+        .keys()
+        .chain(&policy.locked_settings)
+        .map(String::as_str)
+        .collect()
+}
+
+/// Checks that the policy only presets and locks known settings, with values
+/// they accept.
+pub fn validate_policy(policy: &AdminPolicy) -> AppResult<()> {
+    for key in policy_keys(policy) {
+        if lock_only(key) {
+            if policy.presets.contains_key(key) {
+                return Err(AppError::InvalidInput(format!(
+                    "the {key} setting can be locked but not preset"
+                )));
+            }
+            continue;
+        }
+        preset_value(key, policy.presets.get(key))?;
+    }
+    Ok(())
+}
+
+/// Whether `key` names something the policy can lock that is not stored in
+/// `app_settings`.
+fn lock_only(key: &str) -> bool {
+    matches!(key, PROVIDER_KEYS_LOCK | GENERATION_SETTINGS_LOCK)
+}
+
+/// Writes the policy's presets to a workspace: locked settings always, since
+/// the workspace may have been changed without the policy, at their preset or
+/// default; the rest only where the user has not set them.
+pub async fn apply_policy(pool: &SqlitePool, policy: &AdminPolicy) -> AppResult<()> {
+    for key in policy_keys(policy) {
+        if lock_only(key) {
+            continue;
+        }
+        if !policy.locks(key) {
+            let stored = sqlx::query("SELECT 1 FROM app_settings WHERE key = ?1")
+                .bind(key)
+                .fetch_optional(pool)
+                .await?;
+            if stored.is_some() {
+                continue;
+            }
+        }
+        set_setting(pool, key, &preset_value(key, policy.presets.get(key))?).await?;
+    }
+    Ok(())
+}
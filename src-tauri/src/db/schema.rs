@@ -0,0 +1,282 @@
+//! Schema versioning. Migrations are embedded at build time; this module
+//! reports which have run, applies the rest, and copies the database aside
+//! first so an upgrade can be undone by restoring the copy. A database already
+//! migrated by a newer build is refused rather than half-understood; its data
+//! can still be exported to a schema-independent JSON file.
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use base64::Engine;
+use chrono::Utc;
+use serde_json::{json, Value};
+use sqlx::{migrate::Migrator, sqlite::SqliteRow, Column, Row, SqlitePool, TypeInfo, ValueRef};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{MigrationInfo, PortableExportResponse, RunMigrationsResponse, SchemaInfo},
+    },
+    db::Database,
+};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./src/db/migrations");
+
+/// Folder, next to the database file, that holds pre-migration backups.
+pub const BACKUP_DIR: &str = "backups";
+/// `format` field of a portable export.
+pub const PORTABLE_FORMAT: &str = "vectorless-portable";
+/// Bumped when the layout of a portable export changes.
+pub const PORTABLE_FORMAT_VERSION: u32 = 1;
+
+/// Applied and pending migrations and the size of the database on disk.
+pub async fn schema_info(db: &Database) -> AppResult<SchemaInfo> {
+    let applied = applied_migrations(db.pool()).await?;
+    let pending = pending_migrations(&applied);
+    Ok(SchemaInfo {
+        current_version: applied.last().map(|migration| migration.version),
+        latest_version: latest_version(),
+        unknown_versions: unknown_versions(&applied),
+        pending,
+        applied,
+        read_only: db.is_read_only(),
+        db_path: db.path().map(|path| path.to_string_lossy().to_string()),
+        db_file_bytes: db.path().map(file_bytes).unwrap_or(0),
+    })
+}
+
+/// Applies pending migrations. A database that already has a schema is first
+/// copied into [`BACKUP_DIR`]; a new one has nothing to lose and is not.
+pub async fn run_pending_migrations(db: &Database) -> AppResult<RunMigrationsResponse> {
+    if db.is_read_only() {
+        return Err(AppError::InvalidInput(
+            "the database is open read-only and cannot be migrated".to_string(),
+        ));
+    }
+    check_compatible(db.writer()).await?;
+    let before = applied_migrations(db.writer()).await?;
+    let pending = pending_migrations(&before);
+    if pending.is_empty() {
+        return Ok(RunMigrationsResponse {
+            applied: Vec::new(),
+            backup_path: None,
+            current_version: before.last().map(|migration| migration.version),
+        });
+    }
+
+    let backup_path = match (db.path(), before.last()) {
+        (Some(path), Some(current)) => Some(backup(db, path, current.version).await?),
+        _ => None,
+    };
+    MIGRATOR.run(db.writer()).await?;
+
+    let after = applied_migrations(db.writer()).await?;
+    let applied = after
+        .iter()
+        .filter(|migration| pending.iter().any(|p| p.version == migration.version))
+        .cloned()
+        .collect();
+    Ok(RunMigrationsResponse {
+        applied,
+        backup_path: backup_path.map(|path| path.to_string_lossy().to_string()),
+        current_version: after.last().map(|migration| migration.version),
+    })
+}
+
+/// Newest migration embedded in this build.
+pub fn latest_version() -> i64 {
+    MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Migrations recorded as applied, oldest first; empty before the first run.
+pub async fn applied_migrations(pool: &SqlitePool) -> AppResult<Vec<MigrationInfo>> {
+    let tracked = sqlx::query(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_optional(pool)
+    .await?
+    .is_some();
+    if !tracked {
+        return Ok(Vec::new());
+    }
+    let rows = sqlx::query(
+        r#"
+        SELECT version, description, CAST(installed_on AS TEXT) AS installed_on
+        FROM _sqlx_migrations
+        WHERE success = 1
+        ORDER BY version
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(MigrationInfo {
+                version: row.try_get("version")?,
+                description: row.try_get("description")?,
+                installed_on: row.try_get("installed_on")?,
+            })
+        })
+        .collect()
+}
+
+/// Fails with [`AppError::IncompatibleSchema`] when the database records
+/// migrations this build does not have, i.e. a newer version wrote it.
+pub async fn check_compatible(pool: &SqlitePool) -> AppResult<()> {
+    let applied = applied_migrations(pool).await?;
+    match unknown_versions(&applied).last() {
+        Some(&database_version) => Err(AppError::IncompatibleSchema {
+            database_version,
+            app_version: latest_version(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn unknown_versions(applied: &[MigrationInfo]) -> Vec<i64> {
+    applied
+        .iter()
+        .map(|migration| migration.version)
+        .filter(|version| !MIGRATOR.iter().any(|known| known.version == *version))
+        .collect()
+}
+
+fn pending_migrations(applied: &[MigrationInfo]) -> Vec<MigrationInfo> {
+    MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.iter().any(|a| a.version == migration.version))
+        .map(|migration| MigrationInfo {
+            version: migration.version,
+            description: migration.description.to_string(),
+            installed_on: None,
+        })
+        .collect()
+}
+
+/// Writes a consistent copy of the database with `VACUUM INTO`, named after
+/// the schema version it holds.
+async fn backup(db: &Database, db_path: &Path, version: i64) -> AppResult<PathBuf> {
+    let dir = db_path
+        .parent()
+        .map(|parent| parent.join(BACKUP_DIR))
+        .ok_or_else(|| AppError::Internal("database file has no parent folder".to_string()))?;
+    std::fs::create_dir_all(&dir)?;
+    let stem = db_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "vectorless".to_string());
+    let target = dir.join(format!(
+        "{stem}-v{version}-{}.sqlite",
+        Utc::now().format("%Y%m%dT%H%M%S")
+    ));
+    sqlx::query("VACUUM INTO ?1")
+        .bind(target.to_string_lossy().to_string())
+        .execute(db.writer())
+        .await?;
+    Ok(target)
+}
+
+fn file_bytes(path: &Path) -> u64 {
+    let wal = PathBuf::from(format!("{}-wal", path.to_string_lossy()));
+    [path, wal.as_path()]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Writes every table to `dest` as JSON, with no assumptions about the schema,
+/// so data can leave a database this build cannot otherwise use. Blobs are
+/// base64 encoded.
+pub async fn export_portable(db: &Database, dest: &Path) -> AppResult<PortableExportResponse> {
+    let pool = db.pool();
+    let applied = applied_migrations(pool).await?;
+    let tables = sqlx::query(
+        r#"
+        SELECT name, sql
+        FROM sqlite_master
+        WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '_sqlx_migrations'
+        ORDER BY name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut exported = Vec::with_capacity(tables.len());
+    let mut row_count = 0;
+    for table in &tables {
+        let name: String = table.try_get("name")?;
+        let sql: Option<String> = table.try_get("sql")?;
+        let rows = sqlx::query(&format!("SELECT * FROM \"{}\"", name.replace('"', "\"\"")))
+            .fetch_all(pool)
+            .await?;
+        let columns = rows
+            .first()
+            .map(|row| {
+                row.columns()
+                    .iter()
+                    .map(|column| column.name().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let values = rows
+            .iter()
+            .map(|row| (0..row.len()).map(|index| cell(row, index)).collect())
+            .collect::<AppResult<Vec<Vec<Value>>>>()?;
+        row_count += values.len();
+        exported.push(json!({
+            "name": name,
+            "sql": sql,
+            "columns": columns,
+            "rows": values,
+        }));
+    }
+
+    let schema_version = applied.last().map(|migration| migration.version);
+    let document = json!({
+        "format": PORTABLE_FORMAT,
+        "formatVersion": PORTABLE_FORMAT_VERSION,
+        "schemaVersion": schema_version,
+        "exportedAt": Utc::now().to_rfc3339(),
+        "migrations": applied,
+        "tables": exported,
+    });
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = BufWriter::new(File::create(dest)?);
+    serde_json::to_writer(file, &document)
+        .map_err(|err| AppError::Io(format!("cannot write export: {err}")))?;
+    Ok(PortableExportResponse {
+        path: dest.to_string_lossy().to_string(),
+        schema_version,
+        tables: tables.len(),
+        rows: row_count,
+    })
+}
+
+/// One column of a row as JSON, by its SQLite storage class.
+fn cell(row: &SqliteRow, index: usize) -> AppResult<Value> {
+    let raw = row.try_get_raw(index)?;
+    if raw.is_null() {
+        return Ok(Value::Null);
+    }
+    let kind = raw.type_info().name().to_ascii_uppercase();
+    Ok(match kind.as_str() {
+        "INTEGER" | "BOOLEAN" => json!(row.try_get::<i64, _>(index)?),
+        "REAL" => json!(row.try_get::<f64, _>(index)?),
+        "BLOB" => json!({
+            "base64": base64::engine::general_purpose::STANDARD
+                .encode(row.try_get::<Vec<u8>, _>(index)?),
+        }),
+        _ => json!(row.try_get::<String, _>(index)?),
+    })
+}
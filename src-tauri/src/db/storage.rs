@@ -0,0 +1,139 @@
+//! Storage modes for databases kept in cloud-synced folders. iCloud Drive,
+//! Dropbox, OneDrive and similar clients upload files one at a time, so a
+//! WAL-mode database's `-wal` and `-shm` files can reach another machine out
+//! of step with the main file and the copy silently loses or corrupts pages.
+//! In sync-safe mode the database uses a rollback journal (`journal_mode =
+//! DELETE`) with full syncs, so every commit lands in the one file and no
+//! sidecar outlives a transaction. The status command also warns and offers
+//! to move the database somewhere local.
+
+use std::path::{Component, Path};
+
+use sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
+
+use crate::{
+    core::{errors::AppResult, types::StorageStatus},
+    db::Database,
+};
+
+/// How a database file is journaled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StorageMode {
+    /// Sync-safe inside a synced folder, WAL everywhere else.
+    #[default]
+    Auto,
+    /// WAL with normal syncs, wherever the file lives.
+    Standard,
+    /// Rollback journal with full syncs, wherever the file lives.
+    SyncSafe,
+}
+
+impl StorageMode {
+    /// Whether a database at `path` is opened sync-safe.
+    pub fn sync_safe_for(self, path: &Path) -> bool {
+        match self {
+            Self::Auto => sync_provider(path).is_some(),
+            Self::Standard => false,
+            Self::SyncSafe => true,
+        }
+    }
+}
+
+/// Journal mode and sync level for a database opened sync-safe or not.
+pub fn journal_settings(sync_safe: bool) -> (SqliteJournalMode, SqliteSynchronous) {
+    if sync_safe {
+        (SqliteJournalMode::Delete, SqliteSynchronous::Full)
+    } else {
+        (SqliteJournalMode::Wal, SqliteSynchronous::Normal)
+    }
+}
+
+/// Folder names sync clients use for their roots, matched case-insensitively
+/// against each component of a path.
+const SYNC_FOLDERS: &[(&str, &str)] = &[
+    ("mobile documents", "iCloud Drive"),
+    ("icloud drive", "iCloud Drive"),
+    ("icloud", "iCloud Drive"),
+    ("dropbox", "Dropbox"),
+    ("onedrive", "OneDrive"),
+    ("google drive", "Google Drive"),
+    ("googledrive", "Google Drive"),
+    ("my drive", "Google Drive"),
+    ("box", "Box"),
+    ("box sync", "Box"),
+    ("pcloud drive", "pCloud"),
+    ("nextcloud", "Nextcloud"),
+    ("owncloud", "ownCloud"),
+    ("seafile", "Seafile"),
+];
+
+/// The sync service whose folder contains `path`, if any. Recognises the
+/// default folder names, OneDrive's `OneDrive - Company` folders and macOS's
+/// `~/Library/CloudStorage/<Provider>-<account>` mount points.
+pub fn sync_provider(path: &Path) -> Option<&'static str> {
+    let mut after_cloud_storage = false;
+    for component in path.components() {
+        let Component::Normal(name) = component else {
+            continue;
+        };
+        let name = name.to_string_lossy().to_ascii_lowercase();
+        if after_cloud_storage {
+            let provider = name.split('-').next().unwrap_or_default().trim();
+            return SYNC_FOLDERS
+                .iter()
+                .find(|(folder, _)| *folder == provider)
+                .map(|(_, label)| *label)
+                .or(Some("a cloud storage provider"));
+        }
+        if name == "cloudstorage" {
+            after_cloud_storage = true;
+            continue;
+        }
+        if name.starts_with("onedrive - ") {
+            return Some("OneDrive");
+        }
+        if let Some((_, label)) = SYNC_FOLDERS.iter().find(|(folder, _)| *folder == name) {
+            return Some(label);
+        }
+    }
+    None
+}
+
+/// Where the database lives, how it is journaled and whether it should move.
+pub async fn storage_status(db: &Database) -> AppResult<StorageStatus> {
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(db.pool())
+        .await?;
+    let provider = db.path().and_then(sync_provider);
+    let warning = match (provider, db.is_sync_safe()) {
+        (Some(provider), true) => Some(format!(
+            "The database is in a {provider} folder. It runs in sync-safe mode, which is \
+             slower; move it to a local folder for best performance."
+        )),
+        (Some(provider), false) => Some(format!(
+            "The database is in a {provider} folder but uses write-ahead logging, which \
+             sync clients can corrupt. Move it to a local folder or reopen it in \
+             sync-safe mode."
+        )),
+        (None, _) => None,
+    };
+    Ok(StorageStatus {
+        path: db.path().map(|path| path.to_string_lossy().to_string()),
+        sync_provider: provider.map(str::to_string),
+        sync_safe: db.is_sync_safe(),
+        journal_mode: journal_mode.to_ascii_lowercase(),
+        warning,
+    })
+}
+
+/// Writes a consistent copy of `db` to `dest`, which must not exist yet.
+pub async fn copy_to(db: &Database, dest: &Path) -> AppResult<()> {
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    sqlx::query("VACUUM INTO ?1")
+        .bind(dest.to_string_lossy().to_string())
+        .execute(db.writer())
+        .await?;
+    Ok(())
+}
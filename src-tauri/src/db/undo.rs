@@ -0,0 +1,394 @@
+//! A short-lived undo journal for destructive operations. Before a document
+//! is deleted, its node metadata is rewritten, or its graph layout is
+//! cleared, a snapshot of what is about to go is stored; `undo_last` puts the
+//! newest one back. Only the newest [`UNDO_LIMIT`] snapshots are kept, and
+//! one older than the undo window is no longer offered.
+//!
+//! A restored document keeps its ids, so citations in earlier answers still
+//! resolve. Its thumbnail and its place in recent activity are not restored.
+
+use std::time::Duration;
+
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::Row;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{GraphNodePosition, UndoKind, UndoOperation},
+    },
+    db::{
+        bundle,
+        repositories::{assets, documents},
+        retry_busy, Database,
+    },
+    enrichment::acronyms,
+    providers::ImagePart,
+    sidecar::types::SidecarNode,
+};
+
+/// Snapshots kept; older ones are dropped as new ones arrive.
+pub const UNDO_LIMIT: i64 = 10;
+/// How long an operation can be undone.
+pub const UNDO_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// What an operation removed or overwrote, as stored in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum UndoSnapshot {
+    #[serde(rename_all = "camelCase")]
+    DocumentDelete { document: DocumentSnapshot },
+    #[serde(rename_all = "camelCase")]
+    NodeEdit {
+        document_id: String,
+        /// Each node's metadata before the edit.
+        nodes: Vec<(String, Value)>,
+    },
+    #[serde(rename_all = "camelCase")]
+    LayoutWipe {
+        document_id: String,
+        positions: Vec<GraphNodePosition>,
+    },
+}
+
+impl UndoSnapshot {
+    pub fn kind(&self) -> UndoKind {
+        match self {
+            Self::DocumentDelete { .. } => UndoKind::DocumentDelete,
+            Self::NodeEdit { .. } => UndoKind::NodeEdit,
+            Self::LayoutWipe { .. } => UndoKind::LayoutWipe,
+        }
+    }
+
+    pub fn document_id(&self) -> &str {
+        match self {
+            Self::DocumentDelete { document } => &document.id,
+            Self::NodeEdit { document_id, .. } | Self::LayoutWipe { document_id, .. } => {
+                document_id
+            }
+        }
+    }
+}
+
+/// A document with everything deleting it cascades away.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSnapshot {
+    pub id: String,
+    pub project_id: String,
+    /// Projects the document was linked into.
+    pub linked_project_ids: Vec<String>,
+    pub name: String,
+    pub mime: String,
+    pub checksum: String,
+    pub pages: i64,
+    pub hidden: bool,
+    pub locked: bool,
+    pub source_path: Option<String>,
+    pub last_ingested_at: Option<DateTime<Utc>>,
+    pub nodes: Vec<SidecarNode>,
+    pub layout: Vec<GraphNodePosition>,
+    /// Figure images by node id, base64-encoded.
+    pub images: Vec<SnapshotImage>,
+    /// Runs scoped to the document, which lose their scope with it.
+    pub run_ids: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Attachment links on either side of the document, as
+    /// `(document_id, attachment_id)`.
+    #[serde(default)]
+    pub attachment_links: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotImage {
+    pub node_id: String,
+    pub mime: String,
+    pub base64: String,
+}
+
+/// The document as it is, before it is deleted or removed from a project.
+pub async fn snapshot_document(db: &Database, document_id: &str) -> AppResult<UndoSnapshot> {
+    let pool = db.pool();
+    let document = documents::get_document(pool, document_id).await?;
+    let linked_project_ids = documents::document_project_ids(pool, document_id)
+        .await?
+        .into_iter()
+        .skip(1)
+        .collect();
+    let nodes = bundle::document_nodes(db, document_id).await?;
+    let figure_ids = nodes
+        .iter()
+        .filter(|node| node.node_type == "Figure")
+        .map(|node| node.id.clone())
+        .collect::<Vec<_>>();
+    let images = assets::get_node_images(db, &figure_ids)
+        .await?
+        .into_iter()
+        .map(|(node_id, image)| SnapshotImage {
+            node_id,
+            mime: image.mime,
+            base64: base64::engine::general_purpose::STANDARD.encode(&image.data),
+        })
+        .collect();
+    let run_ids = sqlx::query_scalar("SELECT id FROM reasoning_runs WHERE document_id = ?1")
+        .bind(document_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(UndoSnapshot::DocumentDelete {
+        document: DocumentSnapshot {
+            id: document.id,
+            project_id: document.project_id,
+            linked_project_ids,
+            name: document.name,
+            mime: document.mime,
+            checksum: document.checksum,
+            pages: document.pages,
+            hidden: document.hidden,
+            locked: document.locked,
+            source_path: document.source_path,
+            last_ingested_at: document.last_ingested_at,
+            nodes,
+            layout: documents::get_graph_layout(pool, document_id).await?,
+            images,
+            run_ids,
+            tags: documents::get_tags(pool, document_id).await?,
+            attachment_links: documents::attachment_links(pool, document_id).await?,
+        },
+    })
+}
+
+/// The metadata of every node of the document, before an edit.
+pub async fn snapshot_node_metadata(db: &Database, document_id: &str) -> AppResult<UndoSnapshot> {
+    let nodes = bundle::document_nodes(db, document_id)
+        .await?
+        .into_iter()
+        .map(|node| (node.id, node.metadata))
+        .collect();
+    Ok(UndoSnapshot::NodeEdit {
+        document_id: document_id.to_string(),
+        nodes,
+    })
+}
+
+/// Adds `snapshot` to the journal, dropping the oldest entries past
+/// [`UNDO_LIMIT`].
+pub async fn record(db: &Database, label: &str, snapshot: &UndoSnapshot) -> AppResult<i64> {
+    let snapshot_json = serde_json::to_string(snapshot)
+        .map_err(|err| AppError::Internal(format!("cannot encode undo snapshot: {err}")))?;
+    let (kind, document_id) = (snapshot.kind().as_str(), snapshot.document_id());
+    let snapshot_json = &snapshot_json;
+    retry_busy(|| async move {
+        let mut tx = db.writer().begin().await?;
+        let id = sqlx::query(
+            r#"
+            INSERT INTO undo_journal (kind, document_id, label, snapshot_json)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(kind)
+        .bind(document_id)
+        .bind(label)
+        .bind(snapshot_json)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+        sqlx::query(
+            r#"
+            DELETE FROM undo_journal
+            WHERE id <= (SELECT id FROM undo_journal ORDER BY id DESC LIMIT 1 OFFSET ?1)
+            "#,
+        )
+        .bind(UNDO_LIMIT)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(id)
+    })
+    .await
+}
+
+/// Restores the newest operation recorded within `window` and removes it
+/// from the journal. Entries older than `window` are dropped on the way.
+pub async fn undo_last(db: &Database, window: Duration) -> AppResult<Option<UndoOperation>> {
+    let window = chrono::Duration::from_std(window)
+        .map_err(|err| AppError::InvalidInput(format!("invalid undo window: {err}")))?;
+    let cutoff = (Utc::now() - window).to_rfc3339_opts(SecondsFormat::Millis, true);
+    let cutoff = &cutoff;
+    retry_busy(|| async move {
+        sqlx::query("DELETE FROM undo_journal WHERE created_at < ?1")
+            .bind(cutoff)
+            .execute(db.writer())
+            .await?;
+        Ok(())
+    })
+    .await?;
+
+    let Some(row) = sqlx::query(
+        r#"
+        SELECT id, kind, document_id, label, snapshot_json, created_at
+        FROM undo_journal
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(db.pool())
+    .await?
+    else {
+        return Ok(None);
+    };
+    let snapshot_json: String = row.try_get("snapshot_json")?;
+    let snapshot: UndoSnapshot = serde_json::from_str(&snapshot_json)
+        .map_err(|err| AppError::Database(format!("undo snapshot is corrupt: {err}")))?;
+    let created_at: String = row.try_get("created_at")?;
+    let operation = UndoOperation {
+        id: row.try_get("id")?,
+        kind: snapshot.kind(),
+        document_id: row.try_get("document_id")?,
+        label: row.try_get("label")?,
+        created_at: DateTime::parse_from_rfc3339(&created_at)
+            .map(|value| value.with_timezone(&Utc))
+            .map_err(|err| AppError::Database(format!("invalid timestamp {created_at}: {err}")))?,
+    };
+
+    restore(db, snapshot).await?;
+    let id = operation.id;
+    retry_busy(|| async move {
+        sqlx::query("DELETE FROM undo_journal WHERE id = ?1")
+            .bind(id)
+            .execute(db.writer())
+            .await?;
+        Ok(())
+    })
+    .await?;
+    Ok(Some(operation))
+}
+
+async fn restore(db: &Database, snapshot: UndoSnapshot) -> AppResult<()> {
+    let writer = db.writer();
+    match snapshot {
+        UndoSnapshot::DocumentDelete { document } => restore_document(db, document).await,
+        UndoSnapshot::NodeEdit { document_id, nodes } => {
+            let present = bundle::document_nodes(db, &document_id)
+                .await?
+                .into_iter()
+                .map(|node| node.id)
+                .collect::<Vec<_>>();
+            // Nodes replaced since, e.g. by a reingest, are left alone.
+            for (node_id, metadata) in nodes.iter().filter(|(id, _)| present.contains(id)) {
+                documents::update_node_metadata(writer, node_id, metadata).await?;
+            }
+            Ok(())
+        }
+        UndoSnapshot::LayoutWipe {
+            document_id,
+            positions,
+        } => {
+            documents::save_graph_layout(writer, &document_id, &positions).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Puts a deleted document back, or for one that was only removed from a
+/// project, gives it back its owner and links.
+async fn restore_document(db: &Database, document: DocumentSnapshot) -> AppResult<()> {
+    let writer = db.writer();
+    let exists = match documents::get_document(db.pool(), &document.id).await {
+        Ok(_) => true,
+        Err(AppError::NotFound(_)) => false,
+        Err(err) => return Err(err),
+    };
+    if exists {
+        let (id, owner) = (&document.id, &document.project_id);
+        retry_busy(|| async move {
+            let mut tx = writer.begin().await?;
+            sqlx::query("UPDATE documents SET project_id = ?2 WHERE id = ?1")
+                .bind(id)
+                .bind(owner)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM document_links WHERE project_id = ?1 AND document_id = ?2")
+                .bind(owner)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            Ok(())
+        })
+        .await?;
+        for project_id in &document.linked_project_ids {
+            documents::link_document(writer, project_id, &document.id).await?;
+        }
+        return Ok(());
+    }
+
+    documents::insert_document(
+        writer,
+        &document.id,
+        &document.project_id,
+        &document.name,
+        &document.mime,
+        &document.checksum,
+        document.pages,
+    )
+    .await?;
+    let nodes = bundle::parents_first(document.nodes);
+    if let Err(err) = documents::insert_nodes(writer, &document.id, &nodes).await {
+        let _ = documents::delete_document(writer, &document.id).await;
+        return Err(err);
+    }
+    for image in document.images {
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(image.base64.as_bytes())
+            .map_err(|err| AppError::Database(format!("undo image is corrupt: {err}")))?;
+        let image_part = ImagePart {
+            mime: image.mime,
+            data,
+        };
+        assets::put_node_image(db, &image.node_id, &image_part).await?;
+    }
+    documents::save_graph_layout(writer, &document.id, &document.layout).await?;
+    for project_id in &document.linked_project_ids {
+        documents::link_document(writer, project_id, &document.id).await?;
+    }
+    if document.hidden || document.locked {
+        documents::set_visibility(writer, &document.id, document.hidden, document.locked).await?;
+    }
+    let (id, source_path, run_ids) = (&document.id, &document.source_path, &document.run_ids);
+    let last_ingested_at = document
+        .last_ingested_at
+        .map(|at| at.to_rfc3339_opts(SecondsFormat::Millis, true));
+    let last_ingested_at = &last_ingested_at;
+    retry_busy(|| async move {
+        let mut tx = writer.begin().await?;
+        sqlx::query("UPDATE documents SET source_path = ?2, last_ingested_at = ?3 WHERE id = ?1")
+            .bind(id)
+            .bind(source_path)
+            .bind(last_ingested_at)
+            .execute(&mut *tx)
+            .await?;
+        for run_id in run_ids {
+            sqlx::query("UPDATE reasoning_runs SET document_id = ?2 WHERE id = ?1")
+                .bind(run_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    })
+    .await?;
+    if !document.tags.is_empty() {
+        documents::set_tags(writer, &document.id, &document.tags).await?;
+    }
+    for (document_id, attachment_id) in &document.attachment_links {
+        documents::link_attachment(writer, document_id, attachment_id).await?;
+    }
+    acronyms::index_acronyms(db, &document.id).await?;
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Workspaces are separate database files the user can switch between. The
+//! list of known workspaces and the active one live in `workspaces.json` in
+//! the app data folder; the default workspace is the `vectorless.sqlite` the
+//! app always used and cannot be forgotten.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::WorkspaceSummary,
+    },
+    db::{Database, MigrationPolicy, DB_FILE_NAME},
+};
+
+/// Registry file inside the app data folder.
+pub const REGISTRY_FILE_NAME: &str = "workspaces.json";
+const DEFAULT_WORKSPACE_NAME: &str = "Default";
+
+/// The database the app is using, replaceable while the app runs. Callers
+/// take a clone per command, so work already in flight finishes against the
+/// database it started on; its pools close once the last clone is dropped.
+#[derive(Clone)]
+pub struct SharedDatabase(Arc<RwLock<Database>>);
+
+impl SharedDatabase {
+    pub fn new(db: Database) -> Self {
+        Self(Arc::new(RwLock::new(db)))
+    }
+
+    pub fn get(&self) -> Database {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Swaps in `db` and returns the database it replaced.
+    pub fn replace(&self, db: Database) -> Database {
+        let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *current, db)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RegistryFile {
+    active: Option<PathBuf>,
+    workspaces: Vec<RegistryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegistryEntry {
+    path: PathBuf,
+    name: String,
+    last_opened_at: Option<DateTime<Utc>>,
+}
+
+/// Known workspaces, stored next to the default database.
+#[derive(Clone)]
+pub struct WorkspaceRegistry {
+    data_dir: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl WorkspaceRegistry {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn default_path(&self) -> PathBuf {
+        self.data_dir.join(DB_FILE_NAME)
+    }
+
+    /// The workspace to open at startup: the last one opened, or the default
+    /// when that file has since been moved or deleted.
+    pub fn active_path(&self) -> AppResult<PathBuf> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(self
+            .load()?
+            .active
+            .filter(|path| path.is_file())
+            .unwrap_or_else(|| self.default_path()))
+    }
+
+    /// Every known workspace, the default first, then the most recently
+    /// opened. `active` marks the one in use.
+    pub fn list(&self, active: Option<&Path>) -> AppResult<Vec<WorkspaceSummary>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.load()?.workspaces;
+        entries.sort_by(|a, b| b.last_opened_at.cmp(&a.last_opened_at));
+        let default_path = self.default_path();
+        let default = entries
+            .iter()
+            .position(|entry| entry.path == default_path)
+            .map(|index| entries.remove(index))
+            .unwrap_or_else(|| RegistryEntry {
+                path: default_path,
+                name: DEFAULT_WORKSPACE_NAME.to_string(),
+                last_opened_at: None,
+            });
+        Ok(std::iter::once(default)
+            .chain(entries)
+            .map(|entry| WorkspaceSummary {
+                active: active.is_some_and(|active| active == entry.path),
+                exists: entry.path.is_file(),
+                path: entry.path.to_string_lossy().to_string(),
+                name: entry.name,
+                last_opened_at: entry.last_opened_at,
+            })
+            .collect())
+    }
+
+    /// Records `path` as opened now and makes it the startup workspace. A
+    /// blank `name` keeps the stored one, or falls back to the file name.
+    pub fn remember(&self, path: &Path, name: Option<&str>) -> AppResult<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = self.load()?;
+        let name = name.map(str::trim).filter(|name| !name.is_empty());
+        let index = match file.workspaces.iter().position(|entry| entry.path == path) {
+            Some(index) => index,
+            None => {
+                file.workspaces.push(RegistryEntry {
+                    path: path.to_path_buf(),
+                    name: String::new(),
+                    last_opened_at: None,
+                });
+                file.workspaces.len() - 1
+            }
+        };
+        let entry = &mut file.workspaces[index];
+        if let Some(name) = name {
+            entry.name = name.to_string();
+        } else if entry.name.is_empty() {
+            entry.name = self.fallback_name(path);
+        }
+        entry.last_opened_at = Some(Utc::now());
+        file.active = Some(path.to_path_buf());
+        self.save(&file)
+    }
+
+    fn fallback_name(&self, path: &Path) -> String {
+        if path == self.default_path() {
+            return DEFAULT_WORKSPACE_NAME.to_string();
+        }
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    }
+
+    fn registry_path(&self) -> PathBuf {
+        self.data_dir.join(REGISTRY_FILE_NAME)
+    }
+
+    fn load(&self) -> AppResult<RegistryFile> {
+        match std::fs::read(self.registry_path()) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| AppError::Io(format!("cannot read {REGISTRY_FILE_NAME}: {e}"))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(RegistryFile::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes through a temporary file so a crash never leaves half a registry.
+    fn save(&self, file: &RegistryFile) -> AppResult<()> {
+        std::fs::create_dir_all(&self.data_dir)?;
+        let bytes = serde_json::to_vec_pretty(file)
+            .map_err(|e| AppError::Io(format!("cannot write {REGISTRY_FILE_NAME}: {e}")))?;
+        let tmp = self.registry_path().with_extension("json.tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, self.registry_path())?;
+        Ok(())
+    }
+}
+
+/// The database file a user-supplied workspace path refers to: a folder, or a
+/// path without an extension, means `vectorless.sqlite` inside it.
+pub fn resolve_workspace_path(path: &str) -> AppResult<PathBuf> {
+    let path = PathBuf::from(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err(AppError::InvalidInput(
+            "workspace path cannot be empty".to_string(),
+        ));
+    }
+    if path.is_dir() || path.extension().is_none() {
+        return Ok(path.join(DB_FILE_NAME));
+    }
+    Ok(path)
+}
+
+/// Opens the database at `path` for use as a workspace. It must exist and be
+/// a database this build can use.
+pub async fn open_existing(path: &Path) -> AppResult<Database> {
+    if !path.is_file() {
+        return Err(AppError::NotFound(format!(
+            "workspace {}",
+            path.to_string_lossy()
+        )));
+    }
+    Database::open_file(path, MigrationPolicy::Apply).await
+}
+
+/// Creates an empty workspace database at `path`, refusing to reuse a file
+/// that is already there.
+pub async fn create_new(path: &Path) -> AppResult<Database> {
+    if path.exists() {
+        return Err(AppError::InvalidInput(format!(
+            "{} already exists; open it instead",
+            path.to_string_lossy()
+        )));
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    Database::open_file(path, MigrationPolicy::Apply).await
+}
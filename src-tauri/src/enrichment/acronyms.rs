@@ -0,0 +1,162 @@
+//! Finds acronyms a document defines, as in "Service Level Agreement (SLA)"
+//! or "SLA (Service Level Agreement)", so a question using either form finds
+//! text written in the other.
+
+use crate::{
+    core::{
+        errors::AppResult,
+        types::{IndexAcronymsResponse, NodeType},
+    },
+    db::{
+        repositories::{acronyms, documents},
+        Database,
+    },
+};
+
+/// Longest acronym looked for, in letters.
+const MAX_ACRONYM_LETTERS: usize = 10;
+/// Lowercase words an expansion may contain whether or not they give the
+/// acronym a letter, as in "Department of Defense (DoD)".
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "de", "for", "in", "of", "on", "the", "to", "&",
+];
+
+/// An acronym and what it stands for, as one passage spells it out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcronymDefinition {
+    pub acronym: String,
+    pub expansion: String,
+}
+
+/// The acronym definitions in `text`, in order of appearance.
+pub fn detect(text: &str) -> Vec<AcronymDefinition> {
+    let mut found = vec![];
+    let mut rest = text;
+    let mut offset = 0;
+    while let Some(open) = rest.find('(') {
+        let before = &text[..offset + open];
+        let after = &rest[open + 1..];
+        let Some(close) = after.find([')', '(']) else {
+            break;
+        };
+        let inner = after[..close].trim();
+        if let Some(definition) =
+            acronym_first(before, inner).or_else(|| words_first(before, inner))
+        {
+            found.push(definition);
+        }
+        offset += open + 1;
+        rest = &text[offset..];
+    }
+    found
+}
+
+/// "Service Level Agreement (SLA)": the words before the parenthesis spell
+/// the acronym in it.
+fn acronym_first(before: &str, inner: &str) -> Option<AcronymDefinition> {
+    let (acronym, letters) = acronym_letters(inner)?;
+    let words = words(
+        before
+            .rsplit(['.', ';', ':', '\n'])
+            .next()
+            .unwrap_or(before),
+    );
+    let start = spelled_from(&letters, &words)?;
+    Some(AcronymDefinition {
+        acronym,
+        expansion: words[start..].join(" "),
+    })
+}
+
+/// "SLA (Service Level Agreement)": the parenthesis spells the word before it.
+fn words_first(before: &str, inner: &str) -> Option<AcronymDefinition> {
+    let (acronym, letters) = acronym_letters(before.split_whitespace().last()?)?;
+    let words = words(inner);
+    (spelled_from(&letters, &words) == Some(0)).then(|| AcronymDefinition {
+        acronym,
+        expansion: words.join(" "),
+    })
+}
+
+/// `candidate` without surrounding punctuation or a plural `s`, and its
+/// capitals, when it looks like an acronym: two or more capitals and nothing
+/// but letters and digits.
+fn acronym_letters(candidate: &str) -> Option<(String, Vec<char>)> {
+    let trimmed = candidate.trim_matches(|c: char| !c.is_alphanumeric());
+    let acronym = match trimmed.strip_suffix('s') {
+        Some(singular) if singular.ends_with(|c: char| c.is_ascii_uppercase()) => singular,
+        _ => trimmed,
+    };
+    let letters = acronym
+        .chars()
+        .filter(char::is_ascii_uppercase)
+        .collect::<Vec<_>>();
+    let well_formed = acronym.starts_with(|c: char| c.is_ascii_uppercase())
+        && acronym.chars().all(|c| c.is_ascii_alphanumeric())
+        && (2..=MAX_ACRONYM_LETTERS).contains(&letters.len());
+    well_formed.then(|| (acronym.to_string(), letters))
+}
+
+/// Words of `text`, hyphenated ones split, without surrounding punctuation.
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| c.is_whitespace() || c == '-' || c == '/')
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '&'))
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Where among `words` the run ending at the last word starts whose initials
+/// are `letters`.
+fn spelled_from(letters: &[char], words: &[&str]) -> Option<usize> {
+    spell(letters, words, false)
+}
+
+/// Minor words inside the run may give a letter or be skipped: "of" gives
+/// the O of TOS ("Terms of Service"), "and" is skipped in NASA.
+fn spell(letters: &[char], words: &[&str], inside: bool) -> Option<usize> {
+    let (&letter, earlier_letters) = letters.split_last()?;
+    let (&word, earlier_words) = words.split_last()?;
+    let matches = word
+        .chars()
+        .next()
+        .is_some_and(|initial| initial.eq_ignore_ascii_case(&letter));
+    if matches {
+        let start = if earlier_letters.is_empty() {
+            Some(earlier_words.len())
+        } else {
+            spell(earlier_letters, earlier_words, true)
+        };
+        if start.is_some() {
+            return start;
+        }
+    }
+    if inside && MINOR_WORDS.contains(&word.to_lowercase().as_str()) {
+        return spell(letters, earlier_words, true);
+    }
+    None
+}
+
+/// Replaces the document's acronyms with those its text defines now; the
+/// first definition of each acronym is the one kept.
+pub async fn index_acronyms(db: &Database, document_id: &str) -> AppResult<IndexAcronymsResponse> {
+    let nodes = documents::get_document_preview(db.pool(), document_id).await?;
+    let mut definitions: Vec<(String, AcronymDefinition)> = vec![];
+    for node in nodes
+        .iter()
+        .filter(|node| !matches!(node.node_type, NodeType::Figure | NodeType::Table))
+    {
+        for definition in detect(&node.text) {
+            if !definitions
+                .iter()
+                .any(|(_, known)| known.acronym == definition.acronym)
+            {
+                definitions.push((node.id.clone(), definition));
+            }
+        }
+    }
+    acronyms::replace_document_acronyms(db.writer(), document_id, &definitions).await?;
+    Ok(IndexAcronymsResponse {
+        document_id: document_id.to_string(),
+        acronym_count: definitions.len(),
+    })
+}
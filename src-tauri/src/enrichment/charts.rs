@@ -0,0 +1,246 @@
+//! Turns chart-like figures into child Table nodes holding the plotted data, so
+//! numeric questions about a chart can be answered and cited like any table.
+
+use serde_json::Value;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{ExtractChartDataResponse, GenerationSettings, NodeType},
+    },
+    db::{
+        repositories::{assets, documents},
+        Database,
+    },
+    providers::{json_extract::extract_json_object, CallKind, LlmProvider, ProviderResponse},
+    security::key_pool::ApiKeyPool,
+    sidecar::types::SidecarNode,
+};
+
+/// `metadata.kind` of Table nodes created by this pass.
+pub const CHART_DATA_KIND: &str = "chart_data";
+
+const CHART_WORDS: &[&str] = &[
+    "chart",
+    "graph",
+    "plot",
+    "histogram",
+    "bar",
+    "line",
+    "pie",
+    "scatter",
+    "axis",
+    "trend",
+    "series",
+    "distribution",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChartTable {
+    pub title: String,
+    pub table_markdown: String,
+}
+
+/// Id of the Table node extracted from `figure_id`; fixed so reruns skip it.
+pub fn chart_table_node_id(figure_id: &str) -> String {
+    format!("{figure_id}:chart-data")
+}
+
+/// Whether a figure's title or caption suggests it plots data. Image targets
+/// are ignored so base64 payloads and file names can't trigger a match.
+pub fn looks_like_chart(title: &str, text: &str) -> bool {
+    let caption = format!("{title} {}", strip_image_targets(text)).to_ascii_lowercase();
+    caption
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| {
+            CHART_WORDS
+                .iter()
+                .any(|chart_word| word == *chart_word || word.strip_suffix('s') == Some(chart_word))
+        })
+}
+
+fn strip_image_targets(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find("](") {
+        out.push_str(&rest[..open + 1]);
+        rest = &rest[open + 2..];
+        match rest.find(')') {
+            Some(close) => rest = &rest[close + 1..],
+            None => rest = "",
+        }
+    }
+    out.push_str(rest);
+    let lower = out.to_ascii_lowercase();
+    match lower.find("src=") {
+        Some(src) => {
+            let tail = &out[src + 4..];
+            let end = match tail.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    tail[1..].find(quote).map_or(tail.len(), |end| end + 2)
+                }
+                _ => tail
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(tail.len()),
+            };
+            format!("{}{}", &out[..src], &tail[end..])
+        }
+        None => out,
+    }
+}
+
+pub fn chart_extraction_prompt(title: &str, caption: &str) -> String {
+    format!(
+        "You are reading a figure from a document.\n\
+         Figure title: {title}\n\
+         Caption: {caption}\n\n\
+         If the image is a chart or graph, transcribe the data it plots as a GitHub-flavored \
+         markdown table: the first column holds the x-axis categories or values, then one column \
+         per series. Put units in the headers when the axes show them. Only report values you can \
+         read from the image, estimating from gridlines when points are unlabeled; never invent series.\n\n\
+         Respond with JSON only:\n\
+         {{\"is_chart\": true|false, \"title\": \"short table title\", \"table_markdown\": \"| ... |\"}}"
+    )
+}
+
+/// Parses the model's answer; `None` when it says the figure is not a chart.
+pub fn parse_chart_table(response: &ProviderResponse) -> AppResult<Option<ChartTable>> {
+    let parsed = extract_json_object(&response.text).map_err(|err| {
+        AppError::ProviderInvalidResponse(format!("chart output not JSON: {err}"))
+    })?;
+    if !parsed
+        .get("is_chart")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        return Ok(None);
+    }
+    let table_markdown = parsed
+        .get("table_markdown")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if !is_markdown_table(&table_markdown) {
+        return Err(AppError::ProviderInvalidResponse(
+            "chart output has no markdown table".to_string(),
+        ));
+    }
+    let title = parsed
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    Ok(Some(ChartTable {
+        title,
+        table_markdown,
+    }))
+}
+
+fn is_markdown_table(text: &str) -> bool {
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    lines.len() >= 3
+        && lines.iter().all(|line| line.contains('|'))
+        && lines[1].chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+/// Runs chart extraction over every chart-like Figure of a document that has
+/// a stored image and no extracted table yet.
+pub async fn extract_chart_tables(
+    db: &Database,
+    provider: &dyn LlmProvider,
+    keys: &ApiKeyPool,
+    document_id: &str,
+) -> AppResult<ExtractChartDataResponse> {
+    if !provider.supports_images() {
+        return Err(AppError::InvalidInput(format!(
+            "model {} cannot read images",
+            provider.model()
+        )));
+    }
+    let nodes = documents::get_document_preview(db.pool(), document_id).await?;
+    let mut report = ExtractChartDataResponse {
+        document_id: document_id.to_string(),
+        ..ExtractChartDataResponse::default()
+    };
+
+    for figure in nodes
+        .iter()
+        .filter(|node| matches!(node.node_type, NodeType::Figure))
+    {
+        report.figures_scanned += 1;
+        let table_id = chart_table_node_id(&figure.id);
+        if nodes.iter().any(|node| node.id == table_id) {
+            report.already_extracted += 1;
+            continue;
+        }
+        if !looks_like_chart(&figure.title, &figure.text) {
+            continue;
+        }
+        let Some(image) = assets::get_node_image(db, &figure.id).await? else {
+            report.skipped_without_image += 1;
+            continue;
+        };
+
+        let prompt =
+            chart_extraction_prompt(&figure.title, strip_image_targets(&figure.text).trim());
+        let response = keys
+            .complete(
+                db.writer(),
+                provider,
+                CallKind::Enrichment,
+                &prompt,
+                &GenerationSettings::default(),
+                std::slice::from_ref(&image),
+            )
+            .await?;
+        let table = match parse_chart_table(&response) {
+            Ok(Some(table)) => table,
+            Ok(None) => {
+                report.not_charts += 1;
+                continue;
+            }
+            Err(AppError::ProviderInvalidResponse(_)) => {
+                report.failed += 1;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+
+        let title = if table.title.is_empty() {
+            format!("Chart data: {}", figure.title)
+        } else {
+            table.title
+        };
+        documents::insert_nodes(
+            db.writer(),
+            document_id,
+            &[SidecarNode {
+                id: table_id.clone(),
+                parent_id: Some(figure.id.clone()),
+                node_type: "Table".to_string(),
+                title,
+                text: table.table_markdown,
+                page_start: figure.page_start,
+                page_end: figure.page_end,
+                ordinal_path: format!("{}.1", figure.ordinal_path),
+                bbox: Value::Null,
+                metadata: serde_json::json!({
+                    "parser": "enrichment",
+                    "kind": CHART_DATA_KIND,
+                    "sourceFigureId": figure.id,
+                    "model": provider.model(),
+                }),
+            }],
+        )
+        .await?;
+        report.table_node_ids.push(table_id);
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,219 @@
+//! Splits Paragraph nodes into Claim children, one per atomic statement, so
+//! answers can cite the sentence that supports them instead of a whole paragraph.
+
+use serde_json::Value;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{DecomposeClaimsResponse, DocNodeSummary, GenerationSettings, NodeType},
+    },
+    db::{repositories::documents, Database},
+    providers::{json_extract::extract_json_object, CallKind, LlmProvider, ProviderResponse},
+    security::key_pool::ApiKeyPool,
+    sidecar::types::SidecarNode,
+};
+
+/// `metadata.kind` of Claim nodes created by this pass.
+pub const CLAIM_KIND: &str = "claim";
+
+/// Sentences shorter than this are fragments (headings, labels), not claims.
+const MIN_CLAIM_WORDS: usize = 4;
+
+/// Lowercase words that end in a period without ending a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "al", "approx", "cf", "dr", "e.g", "eq", "etc", "fig", "figs", "i.e", "inc", "jr", "ltd", "mr",
+    "mrs", "ms", "no", "prof", "sec", "sr", "st", "vol", "vs",
+];
+
+/// Id of the `index`-th (1-based) claim split from `paragraph_id`.
+pub fn claim_node_id(paragraph_id: &str, index: usize) -> String {
+    format!("{paragraph_id}:claim-{index}")
+}
+
+/// Rule-based sentence segmentation. A sentence ends at `.`, `!` or `?` when
+/// the next word starts a new sentence, skipping abbreviations and initials.
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    let mut sentences = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        current.push(word);
+        let starts_sentence = words.get(index + 1).map_or(true, |next| {
+            next.trim_start_matches(|c| matches!(c, '"' | '\'' | '(' | '“' | '‘'))
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_uppercase() || c.is_ascii_digit())
+        });
+        if ends_sentence(word) && starts_sentence {
+            sentences.push(current.join(" "));
+            current.clear();
+        }
+    }
+    if !current.is_empty() {
+        sentences.push(current.join(" "));
+    }
+    sentences
+}
+
+fn ends_sentence(word: &str) -> bool {
+    let trimmed = word.trim_end_matches(|c| matches!(c, '"' | '\'' | ')' | '”' | '’'));
+    if trimmed.ends_with('!') || trimmed.ends_with('?') {
+        return true;
+    }
+    let Some(stem) = trimmed.strip_suffix('.') else {
+        return false;
+    };
+    let stem = stem.trim_start_matches(|c| matches!(c, '"' | '\'' | '(' | '“' | '‘'));
+    let is_initial = stem.chars().count() == 1 && stem.chars().all(char::is_uppercase);
+    !is_initial && !ABBREVIATIONS.contains(&stem.to_lowercase().as_str())
+}
+
+/// Sentences of a paragraph that read as checkable statements.
+pub fn rule_based_claims(text: &str) -> Vec<String> {
+    split_sentences(text)
+        .into_iter()
+        .filter(|sentence| {
+            !sentence.ends_with('?') && sentence.split_whitespace().count() >= MIN_CLAIM_WORDS
+        })
+        .collect()
+}
+
+pub fn claim_decomposition_prompt(paragraph: &str) -> String {
+    format!(
+        "Split the paragraph below into atomic claims: self-contained statements that can each \
+         be checked on their own. Replace pronouns with what they refer to, keep numbers and units \
+         exactly as written, and never add facts the paragraph does not state. Skip questions and \
+         headings.\n\n\
+         Paragraph:\n{paragraph}\n\n\
+         Respond with JSON only:\n\
+         {{\"claims\": [\"...\"]}}"
+    )
+}
+
+pub fn parse_claims(response: &ProviderResponse) -> AppResult<Vec<String>> {
+    let parsed = extract_json_object(&response.text).map_err(|err| {
+        AppError::ProviderInvalidResponse(format!("claim output not JSON: {err}"))
+    })?;
+    let claims = parsed
+        .get("claims")
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            AppError::ProviderInvalidResponse("claim output has no claims array".to_string())
+        })?;
+    Ok(claims
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::trim)
+        .filter(|claim| !claim.is_empty())
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Splits the selected paragraphs of a document (all of them when
+/// `paragraph_ids` is `None`) into Claim children. Uses the model when given
+/// one, sentence rules otherwise. Paragraphs holding a single claim are left
+/// alone since the paragraph itself is then the finest citation.
+pub async fn decompose_claims(
+    db: &Database,
+    model: Option<(&dyn LlmProvider, &ApiKeyPool)>,
+    document_id: &str,
+    paragraph_ids: Option<&[String]>,
+) -> AppResult<DecomposeClaimsResponse> {
+    let nodes = documents::get_document_preview(db.pool(), document_id).await?;
+    let is_paragraph = |node: &&DocNodeSummary| matches!(node.node_type, NodeType::Paragraph);
+    let paragraphs = match paragraph_ids {
+        Some(ids) => ids
+            .iter()
+            .map(|id| {
+                nodes
+                    .iter()
+                    .find(|node| node.id == *id)
+                    .filter(is_paragraph)
+                    .ok_or_else(|| {
+                        AppError::InvalidInput(format!(
+                            "node {id} is not a paragraph of document {document_id}"
+                        ))
+                    })
+            })
+            .collect::<AppResult<Vec<_>>>()?,
+        None => nodes.iter().filter(is_paragraph).collect(),
+    };
+    let mut report = DecomposeClaimsResponse {
+        document_id: document_id.to_string(),
+        ..DecomposeClaimsResponse::default()
+    };
+
+    for paragraph in paragraphs {
+        report.paragraphs_scanned += 1;
+        if nodes.iter().any(|node| {
+            node.parent_id.as_deref() == Some(paragraph.id.as_str())
+                && matches!(node.node_type, NodeType::Claim)
+        }) {
+            report.already_decomposed += 1;
+            continue;
+        }
+
+        let claims = match model {
+            Some((provider, keys)) => {
+                let response = keys
+                    .complete(
+                        db.writer(),
+                        provider,
+                        CallKind::Enrichment,
+                        &claim_decomposition_prompt(&paragraph.text),
+                        &GenerationSettings::default(),
+                        &[],
+                    )
+                    .await?;
+                match parse_claims(&response) {
+                    Ok(claims) => claims,
+                    Err(AppError::ProviderInvalidResponse(_)) => {
+                        report.failed += 1;
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            None => rule_based_claims(&paragraph.text),
+        };
+        if claims.len() < 2 {
+            report.single_claim += 1;
+            continue;
+        }
+
+        let method = if model.is_some() { "model" } else { "rules" };
+        let claim_nodes = claims
+            .into_iter()
+            .enumerate()
+            .map(|(offset, claim)| {
+                let index = offset + 1;
+                let mut metadata = serde_json::json!({
+                    "parser": "enrichment",
+                    "kind": CLAIM_KIND,
+                    "sourceParagraphId": paragraph.id,
+                    "method": method,
+                });
+                if let Some((provider, _)) = model {
+                    metadata["model"] = provider.model().into();
+                }
+                SidecarNode {
+                    id: claim_node_id(&paragraph.id, index),
+                    parent_id: Some(paragraph.id.clone()),
+                    node_type: "Claim".to_string(),
+                    title: format!("Claim {index}"),
+                    text: claim,
+                    page_start: paragraph.page_start,
+                    page_end: paragraph.page_end,
+                    ordinal_path: format!("{}.{index}", paragraph.ordinal_path),
+                    bbox: Value::Null,
+                    metadata,
+                }
+            })
+            .collect::<Vec<_>>();
+        documents::insert_nodes(db.writer(), document_id, &claim_nodes).await?;
+        report.claims_created += claim_nodes.len();
+    }
+
+    Ok(report)
+}
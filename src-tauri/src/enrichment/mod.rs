@@ -0,0 +1,6 @@
+//! Post-ingestion passes that annotate a document tree or add derived nodes to it.
+
+pub mod acronyms;
+pub mod charts;
+pub mod claims;
+pub mod tables;
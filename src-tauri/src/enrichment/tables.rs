@@ -0,0 +1,481 @@
+//! Infers column names, units and value types for Table nodes and stores them
+//! under `metadata.tableSchema`, so numeric tools can read cells without
+//! guessing. Types always come from the cell values; the model is only asked
+//! to name the columns of tables whose header row can't be found locally.
+
+use chrono::NaiveDate;
+use serde_json::Value;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{
+            ColumnType, EnrichTablesResponse, GenerationSettings, HeaderSource, NodeType,
+            TableColumn, TableSchema,
+        },
+    },
+    db::{repositories::documents, Database},
+    providers::{json_extract::extract_json_object, CallKind, LlmProvider, ProviderResponse},
+    security::key_pool::ApiKeyPool,
+};
+
+/// Metadata key holding a Table node's [`TableSchema`].
+pub const TABLE_SCHEMA_KEY: &str = "tableSchema";
+
+/// Rows shown to the model when it has to name columns.
+const MODEL_SAMPLE_ROWS: usize = 8;
+
+const CURRENCY_SYMBOLS: &[&str] = &["$", "€", "£", "¥", "₹"];
+
+const MISSING_VALUES: &[&str] = &["-", "–", "—", "n/a", "na", "none", "null"];
+
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+    "%d/%m/%Y",
+    "%m/%d/%Y",
+    "%d.%m.%Y",
+    "%b %d, %Y",
+    "%B %d, %Y",
+    "%d %b %Y",
+    "%d %B %Y",
+];
+
+/// Cells of a markdown or tab-separated table, padded to a common width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTable {
+    pub rows: Vec<Vec<String>>,
+    /// The first row sits above a markdown `---` separator.
+    pub markdown_header: bool,
+}
+
+impl ParsedTable {
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+}
+
+pub fn parse_table(text: &str) -> Option<ParsedTable> {
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let piped = lines.iter().filter(|line| line.contains('|')).count();
+    let tabbed = lines.iter().filter(|line| line.contains('\t')).count();
+
+    let mut markdown_header = false;
+    let mut rows = if piped >= 2 {
+        let mut rows = Vec::new();
+        for line in lines.iter().filter(|line| line.contains('|')) {
+            let cells = line
+                .trim_matches('|')
+                .split('|')
+                .map(|cell| cell.trim().to_string())
+                .collect::<Vec<_>>();
+            if is_separator_row(&cells) {
+                markdown_header |= rows.len() == 1;
+                continue;
+            }
+            rows.push(cells);
+        }
+        rows
+    } else if tabbed >= 2 {
+        lines
+            .iter()
+            .filter(|line| line.contains('\t'))
+            .map(|line| {
+                line.split('\t')
+                    .map(|cell| cell.trim().to_string())
+                    .collect()
+            })
+            .collect::<Vec<Vec<String>>>()
+    } else {
+        return None;
+    };
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if rows.len() < 2 || width == 0 {
+        return None;
+    }
+    for row in &mut rows {
+        row.resize(width, String::new());
+    }
+    Some(ParsedTable {
+        rows,
+        markdown_header,
+    })
+}
+
+fn is_separator_row(cells: &[String]) -> bool {
+    cells.iter().any(|cell| cell.contains('-'))
+        && cells
+            .iter()
+            .all(|cell| !cell.is_empty() && cell.chars().all(|c| matches!(c, '-' | ':' | ' ')))
+}
+
+/// Parses a plain number, accepting thousands separators and accounting-style
+/// negatives such as `(1,200)`.
+pub fn parse_number(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    let (negative, raw) = match raw
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        Some(inner) => (true, inner.trim()),
+        None => (false, raw),
+    };
+    let cleaned = raw.replace(',', "").replace('\u{2212}', "-");
+    if !cleaned.chars().any(|c| c.is_ascii_digit())
+        || !cleaned
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+'))
+    {
+        return None;
+    }
+    let value = cleaned.parse::<f64>().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Reads the numeric value of a typed cell: `12%` is 12, `$1,200` is 1200 and
+/// `15 ms` is 15. Returns `None` for blanks and non-numeric text.
+pub fn cell_number(raw: &str) -> Option<f64> {
+    match classify_cell(raw) {
+        Some((kind, _)) if kind.is_numeric() => {
+            let value = clean_cell(raw);
+            let unsigned = value.trim_start_matches(|c| matches!(c, '-' | '+'));
+            let negative = unsigned.len() != value.len() && value.starts_with('-');
+            let body = CURRENCY_SYMBOLS
+                .iter()
+                .find_map(|symbol| unsigned.strip_prefix(symbol))
+                .unwrap_or(unsigned)
+                .trim_end_matches('%');
+            let end = body.find(|c: char| c.is_alphabetic()).unwrap_or(body.len());
+            let number = parse_number(&body[..end])?;
+            Some(if negative { -number } else { number })
+        }
+        _ => None,
+    }
+}
+
+fn clean_cell(raw: &str) -> &str {
+    raw.trim().trim_matches('*').trim()
+}
+
+/// Type of a single cell and the unit written next to its value, or `None`
+/// for a blank or placeholder cell.
+pub fn classify_cell(raw: &str) -> Option<(ColumnType, Option<String>)> {
+    let value = clean_cell(raw);
+    let lower = value.to_lowercase();
+    if value.is_empty() || MISSING_VALUES.contains(&lower.as_str()) {
+        return None;
+    }
+    if matches!(lower.as_str(), "true" | "false" | "yes" | "no") {
+        return Some((ColumnType::Boolean, None));
+    }
+    if let Some(number) = value.strip_suffix('%') {
+        if parse_number(number).is_some() {
+            return Some((ColumnType::Percent, Some("%".to_string())));
+        }
+    }
+    let unsigned = value.trim_start_matches(|c| matches!(c, '-' | '+'));
+    for symbol in CURRENCY_SYMBOLS {
+        if let Some(amount) = unsigned.strip_prefix(symbol) {
+            if parse_number(amount).is_some() {
+                return Some((ColumnType::Currency, Some((*symbol).to_string())));
+            }
+        }
+    }
+    if let Some(kind) = number_kind(value) {
+        return Some((kind, None));
+    }
+    if let Some(split) = value
+        .find(|c: char| c.is_alphabetic())
+        .filter(|split| *split > 0)
+    {
+        let (number, unit) = (value[..split].trim(), value[split..].trim());
+        if unit.len() <= 6 && unit.chars().all(|c| c.is_alphabetic() || c == '/') {
+            if let Some(kind) = number_kind(number) {
+                return Some((kind, Some(unit.to_string())));
+            }
+        }
+    }
+    if is_date(value) {
+        return Some((ColumnType::Date, None));
+    }
+    Some((ColumnType::Text, None))
+}
+
+fn number_kind(value: &str) -> Option<ColumnType> {
+    parse_number(value)?;
+    Some(if value.contains('.') {
+        ColumnType::Number
+    } else {
+        ColumnType::Integer
+    })
+}
+
+fn is_date(value: &str) -> bool {
+    DATE_FORMATS
+        .iter()
+        .any(|format| NaiveDate::parse_from_str(value, format).is_ok())
+        || (value.len() == 7
+            && NaiveDate::parse_from_str(&format!("{value}-01"), "%Y-%m-%d").is_ok())
+}
+
+/// Column type shared by every filled cell; integers mixed with decimals read
+/// as numbers and any other mix falls back to text.
+fn column_type(cells: &[(ColumnType, Option<String>)]) -> (ColumnType, Option<String>) {
+    let Some((first, first_unit)) = cells.first() else {
+        return (ColumnType::Empty, None);
+    };
+    let data_type = if cells.iter().all(|(kind, _)| kind == first) {
+        *first
+    } else if cells
+        .iter()
+        .all(|(kind, _)| matches!(kind, ColumnType::Integer | ColumnType::Number))
+    {
+        ColumnType::Number
+    } else {
+        ColumnType::Text
+    };
+    let unit = first_unit
+        .clone()
+        .filter(|_| data_type != ColumnType::Text)
+        .filter(|unit| cells.iter().all(|(_, other)| other.as_ref() == Some(unit)));
+    (data_type, unit)
+}
+
+/// Splits `Revenue (USD m)` or `Latency [ms]` into a name and a unit.
+pub fn split_header_unit(header: &str) -> (String, Option<String>) {
+    let header = clean_cell(header);
+    for (open, close) in [('(', ')'), ('[', ']')] {
+        if let Some(inner) = header.strip_suffix(close) {
+            if let Some(start) = inner.rfind(open) {
+                let name = inner[..start].trim();
+                let unit = inner[start + 1..].trim();
+                if !name.is_empty() && !unit.is_empty() {
+                    return (name.to_string(), Some(unit.to_string()));
+                }
+            }
+        }
+    }
+    if let Some(name) = header
+        .strip_suffix('%')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        return (name.to_string(), Some("%".to_string()));
+    }
+    (header.to_string(), None)
+}
+
+fn column_cells(rows: &[Vec<String>], index: usize) -> Vec<(ColumnType, Option<String>)> {
+    rows.iter()
+        .filter_map(|row| classify_cell(&row[index]))
+        .collect()
+}
+
+fn first_row_is_header(table: &ParsedTable) -> bool {
+    let Some((first, body)) = table.rows.split_first() else {
+        return false;
+    };
+    let first_is_text = first.iter().any(|cell| !clean_cell(cell).is_empty())
+        && first
+            .iter()
+            .filter_map(|cell| classify_cell(cell))
+            .all(|(kind, _)| kind == ColumnType::Text);
+    first_is_text
+        && (0..table.width()).any(|index| {
+            !matches!(
+                column_type(&column_cells(body, index)).0,
+                ColumnType::Text | ColumnType::Empty
+            )
+        })
+}
+
+fn build_schema(table: &ParsedTable, has_header_row: bool, source: HeaderSource) -> TableSchema {
+    let body = if has_header_row {
+        &table.rows[1..]
+    } else {
+        &table.rows[..]
+    };
+    let columns = (0..table.width())
+        .map(|index| {
+            let (name, header_unit) = if has_header_row {
+                split_header_unit(&table.rows[0][index])
+            } else {
+                (String::new(), None)
+            };
+            let (data_type, value_unit) = column_type(&column_cells(body, index));
+            TableColumn {
+                name: if name.is_empty() {
+                    format!("column_{}", index + 1)
+                } else {
+                    name
+                },
+                data_type,
+                unit: header_unit.or(value_unit),
+            }
+        })
+        .collect();
+    TableSchema {
+        header_source: source,
+        has_header_row,
+        row_count: body.len(),
+        columns,
+    }
+}
+
+/// Schema from local heuristics alone. `HeaderSource::None` means no header
+/// row was found and the columns are only named by position.
+pub fn infer_table_schema(table: &ParsedTable) -> TableSchema {
+    if table.markdown_header {
+        build_schema(table, true, HeaderSource::Markdown)
+    } else if first_row_is_header(table) {
+        build_schema(table, true, HeaderSource::Inferred)
+    } else {
+        build_schema(table, false, HeaderSource::None)
+    }
+}
+
+pub fn header_inference_prompt(title: &str, table: &ParsedTable) -> String {
+    let sample = table
+        .rows
+        .iter()
+        .take(MODEL_SAMPLE_ROWS)
+        .map(|row| row.join("\t"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "You are reading a table from a document.\n\
+         Table title: {title}\n\
+         First rows (tab separated):\n{sample}\n\n\
+         Decide whether the first row is a header row, then give a short name for each of the \
+         {width} columns in order, with the unit of its values when one applies (\"USD m\", \"%\", \
+         \"ms\"). Use the header text when there is one.\n\n\
+         Respond with JSON only:\n\
+         {{\"has_header_row\": true|false, \"columns\": [{{\"name\": \"...\", \"unit\": \"...\" or null}}]}}",
+        width = table.width()
+    )
+}
+
+/// Applies the model's column names to a locally typed table. The model has
+/// to name exactly as many columns as the table has.
+pub fn apply_model_headers(
+    table: &ParsedTable,
+    response: &ProviderResponse,
+) -> AppResult<TableSchema> {
+    let parsed = extract_json_object(&response.text).map_err(|err| {
+        AppError::ProviderInvalidResponse(format!("table header output not JSON: {err}"))
+    })?;
+    let columns = parsed
+        .get("columns")
+        .and_then(Value::as_array)
+        .filter(|columns| columns.len() == table.width())
+        .ok_or_else(|| {
+            AppError::ProviderInvalidResponse(format!(
+                "table header output must name {} columns",
+                table.width()
+            ))
+        })?;
+    let has_header_row = parsed
+        .get("has_header_row")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut schema = build_schema(table, has_header_row, HeaderSource::Model);
+    for (column, named) in schema.columns.iter_mut().zip(columns) {
+        let name = named
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| {
+                AppError::ProviderInvalidResponse(
+                    "table header output has an unnamed column".to_string(),
+                )
+            })?;
+        column.name = name.to_string();
+        if let Some(unit) = named
+            .get("unit")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|unit| !unit.is_empty())
+        {
+            column.unit = Some(unit.to_string());
+        }
+    }
+    Ok(schema)
+}
+
+/// Types every Table node of a document that has no schema yet. With a model,
+/// tables previously left with positional column names are retried too.
+pub async fn enrich_table_schemas(
+    db: &Database,
+    model: Option<(&dyn LlmProvider, &ApiKeyPool)>,
+    document_id: &str,
+) -> AppResult<EnrichTablesResponse> {
+    let nodes = documents::get_document_preview(db.pool(), document_id).await?;
+    let mut report = EnrichTablesResponse {
+        document_id: document_id.to_string(),
+        ..EnrichTablesResponse::default()
+    };
+
+    for node in nodes
+        .iter()
+        .filter(|node| matches!(node.node_type, NodeType::Table))
+    {
+        report.tables_scanned += 1;
+        let mut metadata = documents::get_node(db.pool(), &node.id)
+            .await?
+            .metadata_json;
+        let existing = metadata
+            .get(TABLE_SCHEMA_KEY)
+            .and_then(|schema| serde_json::from_value::<TableSchema>(schema.clone()).ok());
+        if existing
+            .is_some_and(|schema| schema.header_source != HeaderSource::None || model.is_none())
+        {
+            report.already_typed += 1;
+            continue;
+        }
+        let Some(table) = parse_table(&node.text) else {
+            report.unparseable += 1;
+            continue;
+        };
+
+        let mut schema = infer_table_schema(&table);
+        match (schema.header_source, model) {
+            (HeaderSource::None, Some((provider, keys))) => {
+                let response = keys
+                    .complete(
+                        db.writer(),
+                        provider,
+                        CallKind::Enrichment,
+                        &header_inference_prompt(&node.title, &table),
+                        &GenerationSettings::default(),
+                        &[],
+                    )
+                    .await?;
+                match apply_model_headers(&table, &response) {
+                    Ok(named) => {
+                        schema = named;
+                        report.inferred_with_model += 1;
+                    }
+                    Err(AppError::ProviderInvalidResponse(_)) => report.unresolved += 1,
+                    Err(err) => return Err(err),
+                }
+            }
+            (HeaderSource::None, None) => report.unresolved += 1,
+            _ => report.inferred_locally += 1,
+        }
+
+        if !metadata.is_object() {
+            metadata = serde_json::json!({});
+        }
+        metadata[TABLE_SCHEMA_KEY] = serde_json::to_value(&schema)?;
+        documents::update_node_metadata(db.writer(), &node.id, &metadata).await?;
+    }
+
+    Ok(report)
+}
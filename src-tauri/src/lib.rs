@@ -1,6 +1,7 @@
 pub mod commands;
 pub mod core;
 pub mod db;
+pub mod enrichment;
 pub mod providers;
 pub mod reasoner;
 pub mod security;
@@ -10,7 +11,14 @@ use std::path::PathBuf;
 
 use tauri::Manager;
 
-use db::{default_data_dir, Database};
+use crate::core::{errors::AppError, exports::ExportRegistry, policy, scheduler::Scheduler};
+use db::{
+    default_data_dir,
+    repositories::{provider_cache::ProviderCacheConfig, settings},
+    storage::StorageMode,
+    workspaces::{SharedDatabase, WorkspaceRegistry},
+    Database, MigrationPolicy,
+};
 use providers::gemini::GeminiClient;
 use reasoner::executor::ReasoningExecutor;
 
@@ -38,11 +46,51 @@ fn sqlx_debug_enabled() -> bool {
     )
 }
 
+/// `VECTORLESS_DEFER_MIGRATIONS=1` opens the database without upgrading its
+/// schema, leaving it to `run_pending_migrations`.
+fn migration_policy_from_env() -> MigrationPolicy {
+    match std::env::var("VECTORLESS_DEFER_MIGRATIONS")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "1" | "true" | "yes" | "on" => MigrationPolicy::Defer,
+        _ => MigrationPolicy::Apply,
+    }
+}
+
+/// `VECTORLESS_STORAGE_MODE=standard|sync-safe` overrides the journal mode
+/// otherwise picked from whether the database sits in a synced folder.
+fn storage_mode_from_env() -> StorageMode {
+    match std::env::var("VECTORLESS_STORAGE_MODE")
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "standard" | "wal" => StorageMode::Standard,
+        "sync-safe" | "sync_safe" | "delete" => StorageMode::SyncSafe,
+        _ => StorageMode::Auto,
+    }
+}
+
 #[derive(Clone)]
 pub struct AppState {
-    pub db: Database,
+    /// The open workspace; swapped by `open_workspace` and `create_workspace`.
+    pub database: SharedDatabase,
     pub executor: ReasoningExecutor,
+    pub scheduler: Scheduler,
+    pub workspaces: WorkspaceRegistry,
     pub data_dir: PathBuf,
+    /// Folders exports were written to, which `reveal_export` may open.
+    pub exports: ExportRegistry,
+}
+
+impl AppState {
+    /// The database commands should use. Take it once per command so a
+    /// workspace switch cannot change it halfway through.
+    pub fn db(&self) -> Database {
+        self.database.get()
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -65,38 +113,181 @@ pub fn run() {
                 .ok()
                 .unwrap_or_else(|| default_data_dir(None).expect("data dir"));
             let data_dir = base_data_dir.join("vectorless");
-            let db = tauri::async_runtime::block_on(Database::new(&data_dir))
+            std::fs::create_dir_all(&data_dir)?;
+            let admin_policy = policy::load(&data_dir)
+                .and_then(|admin_policy| {
+                    settings::validate_policy(&admin_policy)?;
+                    Ok(admin_policy)
+                })
+                .map_err(|err| std::io::Error::other(err.to_string()))?;
+            policy::install(admin_policy);
+            let workspaces = WorkspaceRegistry::new(&data_dir);
+            let db_path = workspaces
+                .active_path()
                 .map_err(|err| std::io::Error::other(err.to_string()))?;
+            let policy = migration_policy_from_env();
+            let storage = storage_mode_from_env();
+            let db = match tauri::async_runtime::block_on(Database::open_file_with(
+                &db_path, policy, storage,
+            )) {
+                // Keep the newer data intact but reachable, so it can be exported.
+                Err(err @ AppError::IncompatibleSchema { .. }) => {
+                    eprintln!("{err}; opening the database read-only");
+                    tauri::async_runtime::block_on(Database::open_file_read_only(&db_path))
+                }
+                result => result,
+            }
+            .map_err(|err| std::io::Error::other(err.to_string()))?;
+            if let Err(err) = tauri::async_runtime::block_on(settings::apply_policy(
+                db.writer(),
+                policy::current(),
+            )) {
+                eprintln!("could not apply the administrator policy: {err}");
+            }
+            if let Some(provider) = db.path().and_then(db::storage::sync_provider) {
+                eprintln!(
+                    "the database is in a {provider} folder; {}",
+                    if db.is_sync_safe() {
+                        "using sync-safe journaling"
+                    } else {
+                        "write-ahead logging may be corrupted by syncing"
+                    }
+                );
+            }
 
             let gemini = GeminiClient::new("gemini-2.0-flash")
                 .map_err(|err| std::io::Error::other(err.to_string()))?;
-            let executor = ReasoningExecutor::new(gemini);
+            let executor =
+                ReasoningExecutor::new(gemini).with_cache(ProviderCacheConfig::default());
             app.manage(AppState {
-                db,
+                database: SharedDatabase::new(db),
                 executor,
+                scheduler: Scheduler::default(),
+                workspaces,
+                exports: ExportRegistry::new(&data_dir.join("exports")),
                 data_dir,
             });
+            tauri::async_runtime::spawn(commands::database::run_backup_schedule(
+                app.handle().clone(),
+            ));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::settings::set_provider_key,
+            commands::settings::list_provider_keys,
+            commands::settings::add_provider_key,
+            commands::settings::remove_provider_key,
+            commands::settings::set_key_selection,
+            commands::settings::list_openrouter_models,
+            commands::settings::get_model_selection,
+            commands::settings::set_model_selection,
+            commands::settings::get_pricing_overrides,
+            commands::settings::set_pricing_overrides,
+            commands::settings::get_external_parser,
+            commands::settings::set_external_parser,
+            commands::settings::get_language_resources,
+            commands::settings::set_language_resources,
+            commands::settings::reset_language_resources,
+            commands::settings::get_quality_thresholds,
+            commands::settings::set_quality_thresholds,
+            commands::settings::get_privacy_settings,
+            commands::settings::set_privacy_settings,
+            commands::settings::get_retrieval_settings,
+            commands::settings::set_retrieval_settings,
+            commands::settings::get_provider_timeouts,
+            commands::settings::set_provider_timeouts,
+            commands::settings::get_generation_settings,
+            commands::settings::set_generation_settings,
+            commands::settings::get_admin_policy,
             commands::projects::list_projects,
             commands::projects::create_project,
             commands::projects::rename_project,
+            commands::projects::get_delete_impact,
             commands::projects::delete_project,
+            commands::projects::archive_project,
+            commands::projects::unarchive_project,
+            commands::projects::get_ingest_rules,
+            commands::projects::set_ingest_rules,
+            commands::projects::list_acronyms,
+            commands::projects::get_research_log_settings,
+            commands::projects::set_research_log_settings,
+            commands::projects::export_shared_bundle,
+            commands::projects::export_bibliography,
+            commands::projects::verify_shared_bundle,
+            commands::projects::open_shared_bundle,
             commands::documents::ingest_document,
+            commands::documents::reingest_document,
+            commands::documents::scan_directory,
+            commands::documents::preview_import,
+            commands::documents::import_corpus,
+            commands::documents::preview_ingest,
             commands::documents::list_documents,
             commands::documents::open_document,
+            commands::documents::list_recent_documents,
             commands::documents::get_tree,
             commands::documents::get_project_tree,
+            commands::documents::get_project_tree_delta,
+            commands::documents::get_asset,
+            commands::documents::get_document_thumbnail,
+            commands::documents::search_all_projects,
             commands::documents::get_node,
             commands::documents::get_document_preview,
             commands::documents::get_graph_layout,
             commands::documents::save_graph_layout,
             commands::documents::export_markdown,
+            commands::documents::reveal_export,
+            commands::documents::extract_chart_data,
+            commands::documents::enrich_tables,
+            commands::documents::index_acronyms,
+            commands::documents::decompose_claims,
             commands::documents::delete_document,
+            commands::documents::undo_last_operation,
+            commands::documents::set_document_visibility,
+            commands::documents::unlock_document,
+            commands::documents::relock_document,
+            commands::documents::get_document_lock_status,
+            commands::documents::set_document_lock_passphrase,
             commands::reasoning::run_reasoning_query,
             commands::reasoning::get_run,
+            commands::reasoning::list_answer_revisions,
+            commands::reasoning::restore_answer_revision,
+            commands::reasoning::replay_run,
+            commands::reasoning::rerun_with,
+            commands::reasoning::add_document_to_run,
+            commands::reasoning::estimate_query_cost,
+            commands::reasoning::compare_runs,
+            commands::reasoning::export_answer_html,
+            commands::reasoning::get_run_timeline,
+            commands::reasoning::get_quality_trends,
+            commands::reasoning::get_citation_heatmap,
+            commands::reasoning::get_unused_content,
+            commands::reasoning::list_recent_queries,
+            commands::reasoning::run_benchmark,
+            commands::prompts::list_prompt_templates,
+            commands::prompts::get_prompt_template_history,
+            commands::prompts::save_prompt_template,
+            commands::prompts::reset_prompt_template,
+            commands::node_types::list_node_types,
+            commands::node_types::save_node_type,
+            commands::node_types::delete_node_type,
+            commands::health::health_check,
+            commands::metrics::get_metrics,
+            commands::metrics::set_metrics_settings,
+            commands::metrics::reset_metrics,
+            commands::metrics::export_metrics_prometheus,
+            commands::database::get_schema_info,
+            commands::database::run_pending_migrations,
+            commands::database::compress_node_text,
+            commands::database::export_portable_database,
+            commands::database::get_storage_status,
+            commands::database::get_backup_settings,
+            commands::database::set_backup_settings,
+            commands::database::list_backups,
+            commands::database::restore_backup,
+            commands::workspaces::list_workspaces,
+            commands::workspaces::open_workspace,
+            commands::workspaces::create_workspace,
+            commands::workspaces::relocate_workspace,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,117 @@
+//! Turns a provider's error response into an [`AppError`]. Spent quotas get
+//! their own error with a reset hint rather than passing as a rate limit or
+//! an invalid response:
+//! - Google answers `RESOURCE_EXHAUSTED`, with a `QuotaFailure` naming the
+//!   quota and a `RetryInfo` delay in its details;
+//! - OpenRouter answers 402 for spent credits, and 429 with the reset time
+//!   in `metadata.headers` for its daily limits.
+
+use chrono::Utc;
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use crate::core::errors::AppError;
+
+const QUOTA_FAILURE: &str = "type.googleapis.com/google.rpc.QuotaFailure";
+const RETRY_INFO: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+/// The error for a response with a non-success `status`. `retry_after` is the
+/// response's `Retry-After` header and `body` its text.
+pub fn error_for_status(status: StatusCode, retry_after: Option<&str>, body: &str) -> AppError {
+    let parsed = serde_json::from_str::<Value>(body).unwrap_or(Value::Null);
+    // Google sometimes wraps the error in a one-element array.
+    let error = parsed
+        .as_array()
+        .and_then(|items| items.first())
+        .unwrap_or(&parsed)
+        .get("error");
+    if let Some(error) = error {
+        if let Some(quota) = quota_error(status, error, retry_after) {
+            return quota;
+        }
+    }
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => AppError::ProviderAuth,
+        StatusCode::TOO_MANY_REQUESTS => AppError::ProviderRateLimited,
+        status => AppError::ProviderInvalidResponse(format!("status {status} body {body}")),
+    }
+}
+
+fn quota_error(status: StatusCode, error: &Value, retry_after: Option<&str>) -> Option<AppError> {
+    let details = error
+        .get("details")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let detail = |kind: &str| {
+        details
+            .iter()
+            .find(|detail| detail.get("@type").and_then(Value::as_str) == Some(kind))
+    };
+    let quota_failure = detail(QUOTA_FAILURE);
+    let reset_at_ms = error
+        .pointer("/metadata/headers/X-RateLimit-Reset")
+        .and_then(|value| match value {
+            Value::String(text) => text.trim().parse::<i64>().ok(),
+            other => other.as_i64(),
+        });
+    let exhausted = error.get("status").and_then(Value::as_str) == Some("RESOURCE_EXHAUSTED")
+        || quota_failure.is_some()
+        || status == StatusCode::PAYMENT_REQUIRED
+        || (status == StatusCode::TOO_MANY_REQUESTS && reset_at_ms.is_some());
+    if !exhausted {
+        return None;
+    }
+
+    let mut message = error
+        .get("message")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .unwrap_or("quota exhausted")
+        .to_string();
+    let quotas = quota_failure
+        .and_then(|failure| failure.get("violations"))
+        .and_then(Value::as_array)
+        .map(|violations| {
+            violations
+                .iter()
+                .filter_map(|violation| {
+                    violation
+                        .get("quotaId")
+                        .or_else(|| violation.get("quotaMetric"))
+                        .and_then(Value::as_str)
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if !quotas.is_empty() {
+        message = format!("{message} ({})", quotas.join(", "));
+    }
+
+    let retry_after_secs = detail(RETRY_INFO)
+        .and_then(|info| info.get("retryDelay"))
+        .and_then(Value::as_str)
+        .and_then(parse_delay)
+        .or_else(|| retry_after.and_then(|header| header.trim().parse::<u64>().ok()))
+        .or_else(|| {
+            reset_at_ms.map(|reset| {
+                let wait_ms = reset - Utc::now().timestamp_millis();
+                (wait_ms.max(0) as u64).div_ceil(1000)
+            })
+        });
+    Some(AppError::ProviderQuotaExceeded {
+        message,
+        retry_after_secs,
+    })
+}
+
+/// Seconds in a protobuf duration such as `"37s"` or `"0.5s"`, rounded up.
+fn parse_delay(delay: &str) -> Option<u64> {
+    let seconds = delay.trim().strip_suffix('s')?.parse::<f64>().ok()?;
+    if seconds.is_finite() && seconds >= 0.0 {
+        Some(seconds.ceil() as u64)
+    } else {
+        None
+    }
+}
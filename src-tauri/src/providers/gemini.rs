@@ -1,15 +1,31 @@
-use std::time::Duration;
-
-use reqwest::StatusCode;
+use base64::Engine;
+use futures::future::BoxFuture;
+use reqwest::header::RETRY_AFTER;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::core::errors::{AppError, AppResult};
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        policy,
+        types::{GenerationSettings, ModelPricing, Provider, ProviderTimeouts},
+    },
+    providers::{
+        errors, generation::gemini_generation_config, json_extract::extract_json_object, timeouts,
+        CallKind, ImagePart, LlmProvider, ProviderResponse,
+    },
+};
+
+const API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Finish reasons Gemini reports when a candidate was withheld by its safety filters.
+const BLOCKED_FINISH_REASONS: &[&str] = &["SAFETY", "PROHIBITED_CONTENT", "BLOCKLIST", "SPII"];
 
 #[derive(Debug, Clone)]
 pub struct GeminiClient {
     http: reqwest::Client,
     model: String,
+    timeouts: ProviderTimeouts,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,37 +60,89 @@ fn default_planner_decision() -> String {
 
 impl GeminiClient {
     pub fn new(model: impl Into<String>) -> AppResult<Self> {
-        let http = reqwest::Client::builder()
-            .timeout(Duration::from_secs(60))
-            .build()
-            .map_err(|err| AppError::Network(err.to_string()))?;
+        Self::with_timeouts(model, ProviderTimeouts::default())
+    }
+
+    pub fn with_timeouts(model: impl Into<String>, timeouts: ProviderTimeouts) -> AppResult<Self> {
         Ok(Self {
-            http,
+            http: timeouts::http_client(&timeouts)?,
             model: model.into(),
+            timeouts,
         })
     }
 
     pub async fn generate_answer(&self, api_key: &str, prompt: &str) -> AppResult<GeminiOutput> {
+        let response = self
+            .generate_content(
+                api_key,
+                CallKind::Synthesis,
+                prompt,
+                &GenerationSettings::default(),
+                &[],
+            )
+            .await?;
+        parse_answer_output(&response, &self.pricing())
+    }
+
+    pub async fn generate_plan_step(
+        &self,
+        api_key: &str,
+        prompt: &str,
+    ) -> AppResult<GeminiPlannerStep> {
+        let response = self
+            .generate_content(
+                api_key,
+                CallKind::Planner,
+                prompt,
+                &GenerationSettings::default(),
+                &[],
+            )
+            .await?;
+        parse_planner_step(&response)
+    }
+
+    async fn generate_content(
+        &self,
+        api_key: &str,
+        kind: CallKind,
+        prompt: &str,
+        settings: &GenerationSettings,
+        images: &[ImagePart],
+    ) -> AppResult<ProviderResponse> {
+        let policy = policy::current();
+        policy.ensure_online()?;
         let endpoint = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            self.model, api_key
+            "{}/models/{}:generateContent?key={}",
+            policy.endpoint(&Provider::Gemini, API_BASE),
+            self.model,
+            api_key
         );
-        let payload = serde_json::json!({
+        let mut parts = vec![serde_json::json!({"text": prompt})];
+        parts.extend(images.iter().map(|image| {
+            serde_json::json!({
+                "inlineData": {
+                    "mimeType": image.mime,
+                    "data": base64::engine::general_purpose::STANDARD.encode(&image.data)
+                }
+            })
+        }));
+        let mut payload = serde_json::json!({
             "contents": [
                 {
                     "role": "user",
-                    "parts": [{"text": prompt}]
+                    "parts": parts
                 }
             ],
-            "generationConfig": {
-                "temperature": 0.2,
-                "responseMimeType": "application/json"
-            }
+            "generationConfig": gemini_generation_config(settings, kind)
         });
+        if !settings.safety_settings.is_empty() {
+            payload["safetySettings"] = serde_json::to_value(&settings.safety_settings)?;
+        }
 
         let response = self
             .http
             .post(endpoint)
+            .timeout(timeouts::request_timeout(&self.timeouts, kind))
             .json(&payload)
             .send()
             .await
@@ -86,158 +154,211 @@ impl GeminiClient {
                 }
             })?;
 
-        match response.status() {
-            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(AppError::ProviderAuth),
-            StatusCode::TOO_MANY_REQUESTS => return Err(AppError::ProviderRateLimited),
-            status if !status.is_success() => {
-                let body = response.text().await.unwrap_or_default();
-                return Err(AppError::ProviderInvalidResponse(format!(
-                    "status {status} body {body}"
-                )));
-            }
-            _ => {}
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let body = response.text().await.unwrap_or_default();
+            return Err(errors::error_for_status(
+                status,
+                retry_after.as_deref(),
+                &body,
+            ));
         }
 
         let body: Value = response
             .json()
             .await
             .map_err(|err| AppError::ProviderInvalidResponse(err.to_string()))?;
-        let text = body
+        if let Some(reason) = body
+            .get("promptFeedback")
+            .and_then(|feedback: &Value| feedback.get("blockReason"))
+            .and_then(Value::as_str)
+        {
+            return Err(AppError::ProviderContentBlocked(format!("prompt blocked: {reason}")));
+        }
+        let candidate = body
             .get("candidates")
             .and_then(Value::as_array)
-            .and_then(|items: &Vec<Value>| items.first())
+            .and_then(|items: &Vec<Value>| items.first());
+        let text = candidate
             .and_then(|item: &Value| item.get("content"))
             .and_then(|content: &Value| content.get("parts"))
             .and_then(Value::as_array)
             .and_then(|parts: &Vec<Value>| parts.first())
             .and_then(|part: &Value| part.get("text"))
+            .and_then(Value::as_str);
+        let finish_reason = candidate
+            .and_then(|item: &Value| item.get("finishReason"))
             .and_then(Value::as_str)
-            .ok_or_else(|| AppError::ProviderInvalidResponse("missing text candidate".to_string()))?;
-
-        let parsed_json: Value = serde_json::from_str(text)
-            .map_err(|err| AppError::ProviderInvalidResponse(format!("model output not JSON: {err}")))?;
-        let answer_markdown = parsed_json
-            .get("answer_markdown")
-            .and_then(Value::as_str)
-            .unwrap_or("No grounded answer could be generated.")
-            .to_string();
-        let confidence = parsed_json
-            .get("confidence")
-            .and_then(Value::as_f64)
-            .unwrap_or(0.5);
-        let citations = parsed_json
-            .get("citations")
-            .and_then(Value::as_array)
-            .map(|items: &Vec<Value>| {
-                items
-                    .iter()
-                    .filter_map(Value::as_str)
-                    .map(ToString::to_string)
-                    .collect()
-            })
             .unwrap_or_default();
+        let text = match text {
+            Some(text) => text,
+            None if BLOCKED_FINISH_REASONS.contains(&finish_reason) => {
+                return Err(AppError::ProviderContentBlocked(format!(
+                    "response blocked: {finish_reason}"
+                )));
+            }
+            None => {
+                return Err(AppError::ProviderInvalidResponse(
+                    "missing text candidate".to_string(),
+                ));
+            }
+        };
 
         let token_usage = body
             .get("usageMetadata")
             .cloned()
             .unwrap_or_else(|| serde_json::json!({}));
 
-        let input_tokens = token_usage
-            .get("promptTokenCount")
-            .and_then(Value::as_f64)
-            .unwrap_or(0.0);
-        let output_tokens = token_usage
-            .get("candidatesTokenCount")
-            .and_then(Value::as_f64)
-            .unwrap_or(0.0);
-
-        // Light-weight estimate for surfaced telemetry in v1.
-        let estimated_cost_usd = ((input_tokens * 0.0000003) + (output_tokens * 0.0000012)) as f64;
-
-        Ok(GeminiOutput {
-            answer: GeminiAnswer {
-                answer_markdown,
-                confidence,
-                citations,
-            },
+        Ok(ProviderResponse {
+            text: text.to_string(),
             token_usage,
-            estimated_cost_usd,
         })
     }
+}
 
-    pub async fn generate_plan_step(
-        &self,
-        api_key: &str,
-        prompt: &str,
-    ) -> AppResult<GeminiPlannerStep> {
-        let endpoint = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            self.model, api_key
-        );
-        let payload = serde_json::json!({
-            "contents": [
-                {
-                    "role": "user",
-                    "parts": [{"text": prompt}]
-                }
-            ],
-            "generationConfig": {
-                "temperature": 0.1,
-                "responseMimeType": "application/json"
-            }
-        });
+impl LlmProvider for GeminiClient {
+    fn model(&self) -> &str {
+        &self.model
+    }
 
-        let response = self
-            .http
-            .post(endpoint)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|err| {
-                if err.is_timeout() {
-                    AppError::ProviderTimeout
-                } else {
-                    AppError::Network(err.to_string())
-                }
-            })?;
+    fn complete<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        Box::pin(async move {
+            self.generate_content(api_key, kind, prompt, &GenerationSettings::default(), &[])
+                .await
+        })
+    }
 
-        match response.status() {
-            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => return Err(AppError::ProviderAuth),
-            StatusCode::TOO_MANY_REQUESTS => return Err(AppError::ProviderRateLimited),
-            status if !status.is_success() => {
-                let body = response.text().await.unwrap_or_default();
-                return Err(AppError::ProviderInvalidResponse(format!(
-                    "status {status} body {body}"
-                )));
-            }
-            _ => {}
-        }
+    fn complete_with_settings<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+        settings: &'a GenerationSettings,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        Box::pin(self.generate_content(api_key, kind, prompt, settings, &[]))
+    }
 
-        let body: Value = response
-            .json()
-            .await
-            .map_err(|err| AppError::ProviderInvalidResponse(err.to_string()))?;
-        let text = body
-            .get("candidates")
-            .and_then(Value::as_array)
-            .and_then(|items: &Vec<Value>| items.first())
-            .and_then(|item: &Value| item.get("content"))
-            .and_then(|content: &Value| content.get("parts"))
-            .and_then(Value::as_array)
-            .and_then(|parts: &Vec<Value>| parts.first())
-            .and_then(|part: &Value| part.get("text"))
-            .and_then(Value::as_str)
-            .ok_or_else(|| AppError::ProviderInvalidResponse("missing text candidate".to_string()))?;
+    fn supports_images(&self) -> bool {
+        true
+    }
 
-        let parsed: GeminiPlannerStep = serde_json::from_str(text)
-            .map_err(|err| AppError::ProviderInvalidResponse(format!("planner output not JSON: {err}")))?;
+    fn complete_with_images<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+        settings: &'a GenerationSettings,
+        images: &'a [ImagePart],
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        Box::pin(self.generate_content(api_key, kind, prompt, settings, images))
+    }
+}
 
-        if parsed.step_type.trim().is_empty() || parsed.objective.trim().is_empty() {
-            return Err(AppError::ProviderInvalidResponse(
-                "planner output missing required fields".to_string(),
-            ));
-        }
+pub fn parse_answer_output(
+    response: &ProviderResponse,
+    pricing: &ModelPricing,
+) -> AppResult<GeminiOutput> {
+    let parsed_json = extract_json_object(&response.text)
+        .map_err(|err| AppError::ProviderInvalidResponse(format!("model output not JSON: {err}")))?;
+    let answer_markdown = parsed_json
+        .get("answer_markdown")
+        .and_then(Value::as_str)
+        .unwrap_or("No grounded answer could be generated.")
+        .to_string();
+    let confidence = parsed_json
+        .get("confidence")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.5);
+    let citations = parsed_json
+        .get("citations")
+        .and_then(Value::as_array)
+        .map(|items: &Vec<Value>| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(GeminiOutput {
+        answer: GeminiAnswer {
+            answer_markdown,
+            confidence,
+            citations,
+        },
+        token_usage: response.token_usage.clone(),
+        estimated_cost_usd: estimate_cost_usd(&response.token_usage, pricing),
+    })
+}
+
+/// Last-resort recovery when the model ignored the JSON contract: the raw text
+/// becomes the answer, citations are whatever `[citation:...]` markers it kept,
+/// and confidence is capped low so the quality gate stays in charge.
+pub fn parse_answer_from_raw_text(
+    response: &ProviderResponse,
+    pricing: &ModelPricing,
+) -> AppResult<GeminiOutput> {
+    let answer_markdown = response.text.trim().to_string();
+    if answer_markdown.is_empty() {
+        return Err(AppError::ProviderInvalidResponse(
+            "model output is empty".to_string(),
+        ));
+    }
+    let citations = answer_markdown
+        .split("[citation:")
+        .skip(1)
+        .filter_map(|rest| rest.split(']').next())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    Ok(GeminiOutput {
+        answer: GeminiAnswer {
+            answer_markdown,
+            confidence: 0.35,
+            citations,
+        },
+        token_usage: response.token_usage.clone(),
+        estimated_cost_usd: estimate_cost_usd(&response.token_usage, pricing),
+    })
+}
+
+/// Cost of a call from its normalized usage counts (`promptTokenCount`,
+/// `candidatesTokenCount`) and the model's per-token rates.
+pub fn estimate_cost_usd(token_usage: &Value, pricing: &ModelPricing) -> f64 {
+    let input_tokens = token_usage
+        .get("promptTokenCount")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0);
+    let output_tokens = token_usage
+        .get("candidatesTokenCount")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0);
+
+    (input_tokens * pricing.input_per_token) + (output_tokens * pricing.output_per_token)
+}
+
+pub fn parse_planner_step(response: &ProviderResponse) -> AppResult<GeminiPlannerStep> {
+    let parsed: GeminiPlannerStep = extract_json_object(&response.text)
+        .and_then(|value| serde_json::from_value(value).map_err(|err| err.to_string()))
+        .map_err(|err| AppError::ProviderInvalidResponse(format!("planner output not JSON: {err}")))?;
 
-        Ok(parsed)
+    if parsed.step_type.trim().is_empty() || parsed.objective.trim().is_empty() {
+        return Err(AppError::ProviderInvalidResponse(
+            "planner output missing required fields".to_string(),
+        ));
     }
+
+    Ok(parsed)
 }
@@ -0,0 +1,115 @@
+//! Validation and layering of [`GenerationSettings`].
+
+use serde_json::Value;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{GenerationSettings, SafetySetting},
+    },
+    providers::CallKind,
+};
+
+pub const SAFETY_CATEGORIES: &[&str] = &[
+    "HARM_CATEGORY_HARASSMENT",
+    "HARM_CATEGORY_HATE_SPEECH",
+    "HARM_CATEGORY_SEXUALLY_EXPLICIT",
+    "HARM_CATEGORY_DANGEROUS_CONTENT",
+    "HARM_CATEGORY_CIVIC_INTEGRITY",
+];
+
+pub const SAFETY_THRESHOLDS: &[&str] = &[
+    "BLOCK_NONE",
+    "BLOCK_ONLY_HIGH",
+    "BLOCK_MEDIUM_AND_ABOVE",
+    "BLOCK_LOW_AND_ABOVE",
+    "OFF",
+];
+
+pub fn validate_settings(settings: &GenerationSettings) -> AppResult<()> {
+    if let Some(temperature) = settings.temperature {
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err(AppError::InvalidInput(
+                "temperature must be between 0 and 2".to_string(),
+            ));
+        }
+    }
+    if let Some(top_p) = settings.top_p {
+        if !(0.0..=1.0).contains(&top_p) {
+            return Err(AppError::InvalidInput(
+                "topP must be between 0 and 1".to_string(),
+            ));
+        }
+    }
+    if settings.max_output_tokens == Some(0) {
+        return Err(AppError::InvalidInput(
+            "maxOutputTokens must be greater than 0".to_string(),
+        ));
+    }
+    for setting in &settings.safety_settings {
+        if !SAFETY_CATEGORIES.contains(&setting.category.as_str()) {
+            return Err(AppError::InvalidInput(format!(
+                "unknown safety category '{}'",
+                setting.category
+            )));
+        }
+        if !SAFETY_THRESHOLDS.contains(&setting.threshold.as_str()) {
+            return Err(AppError::InvalidInput(format!(
+                "unknown safety threshold '{}'",
+                setting.threshold
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Layers a per-run override on top of project settings. Safety settings are
+/// merged per category so an override only has to name what it changes.
+pub fn merge_settings(
+    base: &GenerationSettings,
+    overrides: &GenerationSettings,
+) -> GenerationSettings {
+    let mut safety_settings: Vec<SafetySetting> = base
+        .safety_settings
+        .iter()
+        .filter(|setting| {
+            !overrides
+                .safety_settings
+                .iter()
+                .any(|other| other.category == setting.category)
+        })
+        .cloned()
+        .collect();
+    safety_settings.extend(overrides.safety_settings.iter().cloned());
+
+    GenerationSettings {
+        temperature: overrides.temperature.or(base.temperature),
+        top_p: overrides.top_p.or(base.top_p),
+        max_output_tokens: overrides.max_output_tokens.or(base.max_output_tokens),
+        safety_settings,
+    }
+}
+
+/// Temperature for a call: the configured value, else a low per-kind default.
+pub fn effective_temperature(settings: &GenerationSettings, kind: CallKind) -> f64 {
+    settings.temperature.unwrap_or(match kind {
+        CallKind::Planner => 0.1,
+        CallKind::Synthesis => 0.2,
+        CallKind::Enrichment => 0.0,
+    })
+}
+
+/// Gemini `generationConfig` for a call, with JSON output always requested.
+pub fn gemini_generation_config(settings: &GenerationSettings, kind: CallKind) -> Value {
+    let mut config = serde_json::json!({
+        "temperature": effective_temperature(settings, kind),
+        "responseMimeType": "application/json"
+    });
+    if let Some(top_p) = settings.top_p {
+        config["topP"] = top_p.into();
+    }
+    if let Some(max_output_tokens) = settings.max_output_tokens {
+        config["maxOutputTokens"] = max_output_tokens.into();
+    }
+    config
+}
@@ -0,0 +1,103 @@
+//! Tolerant parsing for JSON embedded in model output.
+//!
+//! Models asked for "only JSON" still wrap it in markdown fences, add a sentence
+//! before or after it, or leave trailing commas. Each repair is tried in turn,
+//! cheapest first, and the first candidate that parses wins.
+
+use serde_json::Value;
+
+/// Extracts the first JSON object from `text`, repairing common formatting slips.
+/// The error describes why the unrepaired text failed to parse.
+pub fn extract_json_object(text: &str) -> Result<Value, String> {
+    let trimmed = text.trim();
+    let strict_error = match serde_json::from_str::<Value>(trimmed) {
+        Ok(value @ Value::Object(_)) => return Ok(value),
+        Ok(_) => "expected a JSON object".to_string(),
+        Err(err) => err.to_string(),
+    };
+
+    let unfenced = strip_code_fences(trimmed);
+    let candidate = first_balanced_object(unfenced).unwrap_or(unfenced);
+    if let Ok(value @ Value::Object(_)) = serde_json::from_str::<Value>(candidate) {
+        return Ok(value);
+    }
+
+    match serde_json::from_str::<Value>(&remove_trailing_commas(candidate)) {
+        Ok(value @ Value::Object(_)) => Ok(value),
+        _ => Err(strict_error),
+    }
+}
+
+fn strip_code_fences(text: &str) -> &str {
+    let Some(start) = text.find("```") else {
+        return text;
+    };
+    let after = &text[start + 3..];
+    // Skip an info string such as `json` on the opening fence line.
+    let body_start = after.find('\n').map(|idx| idx + 1).unwrap_or(0);
+    let body = &after[body_start..];
+    match body.find("```") {
+        Some(end) => body[..end].trim(),
+        None => body.trim(),
+    }
+}
+
+fn first_balanced_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, ch) in text[start..].char_indices() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + offset + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn remove_trailing_commas(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let chars = text.chars().collect::<Vec<_>>();
+    for (idx, &ch) in chars.iter().enumerate() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            out.push(ch);
+            continue;
+        }
+        if ch == '"' {
+            in_string = true;
+        }
+        if ch == ',' {
+            let next = chars[idx + 1..].iter().find(|value| !value.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        out.push(ch);
+    }
+    out
+}
@@ -0,0 +1,174 @@
+//! Fixture-backed provider for regression tests.
+//!
+//! A fixture file lists canned responses. A response with a `requestHash` only
+//! answers that exact request; the rest are served in order per call kind, with
+//! the last one repeating once the sequence is exhausted.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::GenerationSettings,
+    },
+    providers::{request_hash, tokens, CallKind, ImagePart, LlmProvider, ProviderResponse},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockFixture {
+    #[serde(default = "default_mock_model")]
+    pub model: String,
+    /// Overrides the context window derived from the model name.
+    #[serde(default)]
+    pub context_window: Option<usize>,
+    /// Accepts inline images, recording them for inspection.
+    #[serde(default)]
+    pub multimodal: bool,
+    pub responses: Vec<MockResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockResponse {
+    pub kind: CallKind,
+    #[serde(default)]
+    pub request_hash: Option<String>,
+    pub text: String,
+    #[serde(default)]
+    pub token_usage: Value,
+}
+
+fn default_mock_model() -> String {
+    "mock".to_string()
+}
+
+#[derive(Debug, Clone)]
+pub struct MockProvider {
+    model: String,
+    context_window: Option<usize>,
+    multimodal: bool,
+    by_hash: HashMap<String, ProviderResponse>,
+    sequences: HashMap<CallKind, Vec<ProviderResponse>>,
+    cursors: Arc<Mutex<HashMap<CallKind, usize>>>,
+    received_images: Arc<Mutex<Vec<ImagePart>>>,
+}
+
+impl MockProvider {
+    pub fn new(fixture: MockFixture) -> Self {
+        let mut by_hash = HashMap::new();
+        let mut sequences: HashMap<CallKind, Vec<ProviderResponse>> = HashMap::new();
+        for entry in fixture.responses {
+            let response = ProviderResponse {
+                text: entry.text,
+                token_usage: if entry.token_usage.is_null() {
+                    serde_json::json!({})
+                } else {
+                    entry.token_usage
+                },
+            };
+            match entry.request_hash {
+                Some(hash) => {
+                    by_hash.insert(hash, response);
+                }
+                None => sequences.entry(entry.kind).or_default().push(response),
+            }
+        }
+        Self {
+            model: fixture.model,
+            context_window: fixture.context_window,
+            multimodal: fixture.multimodal,
+            by_hash,
+            sequences,
+            cursors: Arc::new(Mutex::new(HashMap::new())),
+            received_images: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> AppResult<Self> {
+        let raw = std::fs::read_to_string(path).map_err(|err| {
+            AppError::Io(format!(
+                "cannot read mock fixture {}: {err}",
+                path.display()
+            ))
+        })?;
+        let fixture: MockFixture = serde_json::from_str(&raw)?;
+        Ok(Self::new(fixture))
+    }
+
+    /// Images passed to multimodal calls so far, across clones of this provider.
+    pub fn received_images(&self) -> Vec<ImagePart> {
+        self.received_images
+            .lock()
+            .map(|images| images.clone())
+            .unwrap_or_default()
+    }
+
+    fn next_in_sequence(&self, kind: CallKind) -> Option<ProviderResponse> {
+        let sequence = self.sequences.get(&kind)?;
+        let mut cursors = self.cursors.lock().ok()?;
+        let cursor = cursors.entry(kind).or_insert(0);
+        let response = sequence.get(*cursor).or_else(|| sequence.last()).cloned();
+        *cursor += 1;
+        response
+    }
+}
+
+impl LlmProvider for MockProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn context_window(&self) -> usize {
+        self.context_window
+            .unwrap_or_else(|| tokens::context_window_for(&self.model))
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        let outcome = self
+            .by_hash
+            .get(&request_hash(kind, prompt))
+            .cloned()
+            .or_else(|| self.next_in_sequence(kind))
+            .ok_or_else(|| {
+                AppError::ProviderInvalidResponse(format!(
+                    "mock fixture has no {} response",
+                    kind.as_str()
+                ))
+            });
+        Box::pin(async move { outcome })
+    }
+
+    fn supports_images(&self) -> bool {
+        self.multimodal
+    }
+
+    fn complete_with_images<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+        settings: &'a GenerationSettings,
+        images: &'a [ImagePart],
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        if self.multimodal {
+            if let Ok(mut received) = self.received_images.lock() {
+                received.extend_from_slice(images);
+            }
+        }
+        self.complete_with_settings(api_key, kind, prompt, settings)
+    }
+}
@@ -1 +1,207 @@
+pub mod errors;
 pub mod gemini;
+pub mod generation;
+pub mod json_extract;
+pub mod mock;
+pub mod openrouter;
+pub mod pricing;
+pub mod replay;
+pub mod timeouts;
+pub mod tokens;
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::core::{
+    errors::AppResult,
+    types::{
+        GenerationSettings, ModelPricing, ModelSelection, Provider, ProviderTimeoutSettings,
+        ProviderTimeouts,
+    },
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    Planner,
+    Synthesis,
+    /// Post-ingestion passes that derive new nodes from existing ones.
+    Enrichment,
+}
+
+impl CallKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Planner => "planner",
+            Self::Synthesis => "synthesis",
+            Self::Enrichment => "enrichment",
+        }
+    }
+}
+
+/// Raw model output for a single call, before any JSON interpretation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderResponse {
+    pub text: String,
+    pub token_usage: Value,
+}
+
+/// An image sent inline with a prompt to a multimodal model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImagePart {
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+/// A text-completion backend used by the reasoning executor.
+pub trait LlmProvider: Send + Sync {
+    fn model(&self) -> &str;
+
+    /// Per-token rates used to estimate the cost of a call.
+    fn pricing(&self) -> ModelPricing {
+        pricing::bundled().pricing_for(self.model())
+    }
+
+    /// Maximum prompt size, in tokens, the model accepts.
+    fn context_window(&self) -> usize {
+        tokens::context_window_for(self.model())
+    }
+
+    fn complete<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>>;
+
+    /// Like [`complete`](Self::complete), honouring sampling and safety settings.
+    /// Providers without such knobs ignore them.
+    fn complete_with_settings<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+        settings: &'a GenerationSettings,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        let _ = settings;
+        self.complete(api_key, kind, prompt)
+    }
+
+    /// Whether [`complete_with_images`](Self::complete_with_images) actually
+    /// sends the images to the model.
+    fn supports_images(&self) -> bool {
+        false
+    }
+
+    /// Like [`complete_with_settings`](Self::complete_with_settings), attaching
+    /// `images` after the prompt. Text-only providers ignore them.
+    fn complete_with_images<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+        settings: &'a GenerationSettings,
+        images: &'a [ImagePart],
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        let _ = images;
+        self.complete_with_settings(api_key, kind, prompt, settings)
+    }
+}
+
+/// Stable key for a provider request, used to match recorded responses on replay.
+pub fn request_hash(kind: CallKind, prompt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(kind.as_str().as_bytes());
+    hasher.update([0u8]);
+    hasher.update(prompt.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Cache key for a request; unlike [`request_hash`] it also depends on the model
+/// and generation settings, since either changes what the provider returns.
+pub fn cache_key(
+    model: &str,
+    kind: CallKind,
+    prompt: &str,
+    settings: &GenerationSettings,
+) -> String {
+    cache_key_with_images(model, kind, prompt, settings, &[])
+}
+
+/// [`cache_key`] for a request that also carries inline images.
+pub fn cache_key_with_images(
+    model: &str,
+    kind: CallKind,
+    prompt: &str,
+    settings: &GenerationSettings,
+    images: &[ImagePart],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(serde_json::to_string(settings).unwrap_or_default().as_bytes());
+    hasher.update([0u8]);
+    hasher.update(request_hash(kind, prompt).as_bytes());
+    for image in images {
+        hasher.update([0u8]);
+        hasher.update(image.mime.as_bytes());
+        hasher.update(Sha256::digest(&image.data));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Builds the backend for the user's provider and model choice, with that
+/// provider's timeouts.
+pub fn provider_for_selection(
+    selection: &ModelSelection,
+    timeouts: &ProviderTimeoutSettings,
+) -> AppResult<Arc<dyn LlmProvider>> {
+    build_provider(
+        &selection.provider,
+        &selection.model,
+        selection.pricing,
+        selection.context_length,
+        timeouts.for_provider(&selection.provider),
+    )
+}
+
+/// Builds the plan-step backend when the selection names a separate planner model.
+pub fn planner_provider_for_selection(
+    selection: &ModelSelection,
+    timeouts: &ProviderTimeoutSettings,
+) -> AppResult<Option<Arc<dyn LlmProvider>>> {
+    selection
+        .planner
+        .as_ref()
+        .map(|planner| {
+            build_provider(
+                &selection.provider,
+                &planner.model,
+                planner.pricing,
+                planner.context_length,
+                timeouts.for_provider(&selection.provider),
+            )
+        })
+        .transpose()
+}
+
+fn build_provider(
+    provider: &Provider,
+    model: &str,
+    pricing: Option<ModelPricing>,
+    context_length: Option<usize>,
+    timeouts: ProviderTimeouts,
+) -> AppResult<Arc<dyn LlmProvider>> {
+    Ok(match provider {
+        Provider::Gemini => Arc::new(gemini::GeminiClient::with_timeouts(model, timeouts)?),
+        Provider::OpenRouter => Arc::new(
+            openrouter::OpenRouterClient::with_timeouts(model, timeouts)?
+                .with_model_info(pricing, context_length),
+        ),
+    })
+}
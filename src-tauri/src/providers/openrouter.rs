@@ -0,0 +1,257 @@
+//! OpenRouter chat-completions backend, giving access to many hosted models
+//! through a single key.
+
+use futures::future::BoxFuture;
+use reqwest::header::RETRY_AFTER;
+use serde_json::Value;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        policy,
+        types::{GenerationSettings, HostedModel, ModelPricing, Provider, ProviderTimeouts},
+    },
+    providers::{
+        errors, generation::effective_temperature, pricing, timeouts, tokens, CallKind,
+        LlmProvider, ProviderResponse,
+    },
+};
+
+const API_BASE: &str = "https://openrouter.ai/api/v1";
+
+#[derive(Debug, Clone)]
+pub struct OpenRouterClient {
+    http: reqwest::Client,
+    model: String,
+    pricing: Option<ModelPricing>,
+    context_length: Option<usize>,
+    timeouts: ProviderTimeouts,
+}
+
+impl OpenRouterClient {
+    pub fn new(model: impl Into<String>) -> AppResult<Self> {
+        Self::with_timeouts(model, ProviderTimeouts::default())
+    }
+
+    pub fn with_timeouts(model: impl Into<String>, timeouts: ProviderTimeouts) -> AppResult<Self> {
+        Ok(Self {
+            http: timeouts::http_client(&timeouts)?,
+            model: model.into(),
+            pricing: None,
+            context_length: None,
+            timeouts,
+        })
+    }
+
+    /// Uses the rates and context size reported by the model listing.
+    pub fn with_model_info(
+        mut self,
+        pricing: Option<ModelPricing>,
+        context_length: Option<usize>,
+    ) -> Self {
+        self.pricing = pricing;
+        self.context_length = context_length;
+        self
+    }
+
+    pub async fn list_models(&self) -> AppResult<Vec<HostedModel>> {
+        let policy = policy::current();
+        policy.ensure_online()?;
+        let response = self
+            .http
+            .get(format!(
+                "{}/models",
+                policy.endpoint(&Provider::OpenRouter, API_BASE)
+            ))
+            .send()
+            .await
+            .map_err(map_request_error)?;
+        let body = check_status(response).await?;
+        let models = body
+            .get("data")
+            .and_then(Value::as_array)
+            .ok_or_else(|| AppError::ProviderInvalidResponse("missing model list".to_string()))?;
+        Ok(models.iter().filter_map(parse_model).collect())
+    }
+
+    async fn chat_completion(
+        &self,
+        api_key: &str,
+        kind: CallKind,
+        prompt: &str,
+        settings: &GenerationSettings,
+    ) -> AppResult<ProviderResponse> {
+        let policy = policy::current();
+        policy.ensure_online()?;
+        let mut payload = serde_json::json!({
+            "model": self.model,
+            "messages": [{"role": "user", "content": prompt}],
+            "temperature": effective_temperature(settings, kind),
+            "response_format": {"type": "json_object"}
+        });
+        if let Some(top_p) = settings.top_p {
+            payload["top_p"] = top_p.into();
+        }
+        if let Some(max_output_tokens) = settings.max_output_tokens {
+            payload["max_tokens"] = max_output_tokens.into();
+        }
+
+        let response = self
+            .http
+            .post(format!(
+                "{}/chat/completions",
+                policy.endpoint(&Provider::OpenRouter, API_BASE)
+            ))
+            .timeout(timeouts::request_timeout(&self.timeouts, kind))
+            .bearer_auth(api_key)
+            .header("X-Title", "Vectorless")
+            .json(&payload)
+            .send()
+            .await
+            .map_err(map_request_error)?;
+        let body = check_status(response).await?;
+
+        let choice = body
+            .get("choices")
+            .and_then(Value::as_array)
+            .and_then(|items: &Vec<Value>| items.first());
+        let finish_reason = choice
+            .and_then(|item: &Value| item.get("finish_reason"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if finish_reason == "content_filter" {
+            return Err(AppError::ProviderContentBlocked(
+                "response blocked: content_filter".to_string(),
+            ));
+        }
+        let text = choice
+            .and_then(|item: &Value| item.get("message"))
+            .and_then(|message: &Value| message.get("content"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                AppError::ProviderInvalidResponse("missing message content".to_string())
+            })?;
+
+        Ok(ProviderResponse {
+            text: text.to_string(),
+            token_usage: normalize_usage(body.get("usage")),
+        })
+    }
+}
+
+impl LlmProvider for OpenRouterClient {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn pricing(&self) -> ModelPricing {
+        self.pricing
+            .unwrap_or_else(|| pricing::bundled().pricing_for(&self.model))
+    }
+
+    fn context_window(&self) -> usize {
+        self.context_length
+            .unwrap_or_else(|| tokens::context_window_for(&self.model))
+    }
+
+    fn complete<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        Box::pin(async move {
+            self.chat_completion(api_key, kind, prompt, &GenerationSettings::default())
+                .await
+        })
+    }
+
+    fn complete_with_settings<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+        settings: &'a GenerationSettings,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        Box::pin(self.chat_completion(api_key, kind, prompt, settings))
+    }
+}
+
+fn map_request_error(err: reqwest::Error) -> AppError {
+    if err.is_timeout() {
+        AppError::ProviderTimeout
+    } else {
+        AppError::Network(err.to_string())
+    }
+}
+
+async fn check_status(response: reqwest::Response) -> AppResult<Value> {
+    let status = response.status();
+    if !status.is_success() {
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.text().await.unwrap_or_default();
+        return Err(errors::error_for_status(
+            status,
+            retry_after.as_deref(),
+            &body,
+        ));
+    }
+    response
+        .json()
+        .await
+        .map_err(|err| AppError::ProviderInvalidResponse(err.to_string()))
+}
+
+/// Maps OpenAI-style usage onto the Gemini field names the rest of the
+/// pipeline reads, keeping the original counters alongside.
+pub fn normalize_usage(usage: Option<&Value>) -> Value {
+    let count = |key: &str| {
+        usage
+            .and_then(|usage| usage.get(key))
+            .and_then(Value::as_i64)
+            .unwrap_or(0)
+    };
+    serde_json::json!({
+        "promptTokenCount": count("prompt_tokens"),
+        "candidatesTokenCount": count("completion_tokens"),
+        "totalTokenCount": count("total_tokens"),
+        "provider": "openrouter",
+    })
+}
+
+/// Reads one entry of the `/models` listing. Prices arrive as decimal strings
+/// in USD per token.
+pub fn parse_model(entry: &Value) -> Option<HostedModel> {
+    let id = entry.get("id").and_then(Value::as_str)?.to_string();
+    let price = |key: &str| {
+        let value = entry.get("pricing")?.get(key)?;
+        value
+            .as_str()
+            .and_then(|raw| raw.parse::<f64>().ok())
+            .or_else(|| value.as_f64())
+    };
+    let pricing = match (price("prompt"), price("completion")) {
+        (Some(input_per_token), Some(output_per_token)) => Some(ModelPricing {
+            input_per_token,
+            output_per_token,
+        }),
+        _ => None,
+    };
+    Some(HostedModel {
+        name: entry
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or(&id)
+            .to_string(),
+        context_length: entry
+            .get("context_length")
+            .and_then(Value::as_u64)
+            .map(|value| value as usize),
+        pricing,
+        id,
+    })
+}
@@ -0,0 +1,114 @@
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use serde::Deserialize;
+
+use crate::core::{
+    errors::{AppError, AppResult},
+    types::ModelPricing,
+};
+
+/// Rates used when a model appears in neither the bundled table nor the user's overrides.
+pub const FALLBACK_PRICING: ModelPricing = ModelPricing {
+    input_per_token: 0.0000003,
+    output_per_token: 0.0000012,
+};
+
+const BUNDLED_PRICING_JSON: &str = include_str!("pricing.json");
+
+#[derive(Debug, Deserialize)]
+struct PricingFile {
+    models: BTreeMap<String, PerMillionRates>,
+}
+
+/// Rates as published: USD per million tokens.
+#[derive(Debug, Deserialize)]
+struct PerMillionRates {
+    input: f64,
+    output: f64,
+}
+
+/// Per-model token rates, keyed by model id or id prefix.
+#[derive(Debug, Clone, Default)]
+pub struct PricingTable {
+    models: BTreeMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    /// Parses a table in the bundled `pricing.json` format.
+    pub fn from_json(raw: &str) -> AppResult<Self> {
+        let file: PricingFile = serde_json::from_str(raw)
+            .map_err(|err| AppError::Internal(format!("invalid pricing table: {err}")))?;
+        let models = file
+            .models
+            .into_iter()
+            .map(|(model, rates)| {
+                (
+                    model,
+                    ModelPricing {
+                        input_per_token: rates.input / 1_000_000.0,
+                        output_per_token: rates.output / 1_000_000.0,
+                    },
+                )
+            })
+            .collect();
+        Ok(Self { models })
+    }
+
+    /// Returns a copy of this table with `overrides` taking precedence.
+    pub fn with_overrides(&self, overrides: &BTreeMap<String, ModelPricing>) -> Self {
+        let mut models = self.models.clone();
+        models.extend(
+            overrides
+                .iter()
+                .map(|(model, pricing)| (model.trim().to_ascii_lowercase(), *pricing)),
+        );
+        Self { models }
+    }
+
+    /// Finds the rates for `model`: an exact id wins, otherwise the longest
+    /// matching prefix (so `gemini-2.0-flash-001` prices as `gemini-2.0-flash`).
+    /// Vendor-qualified ids such as `google/gemini-2.0-flash` also match the bare id.
+    pub fn lookup(&self, model: &str) -> Option<ModelPricing> {
+        let model = model.trim().to_ascii_lowercase();
+        let bare = model.rsplit('/').next().unwrap_or(&model);
+        [model.as_str(), bare].into_iter().find_map(|candidate| {
+            self.models.get(candidate).copied().or_else(|| {
+                self.models
+                    .iter()
+                    .filter(|(key, _)| candidate.starts_with(key.as_str()))
+                    .max_by_key(|(key, _)| key.len())
+                    .map(|(_, pricing)| *pricing)
+            })
+        })
+    }
+
+    pub fn pricing_for(&self, model: &str) -> ModelPricing {
+        self.lookup(model).unwrap_or(FALLBACK_PRICING)
+    }
+}
+
+/// The table shipped with the app.
+pub fn bundled() -> &'static PricingTable {
+    static TABLE: OnceLock<PricingTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        PricingTable::from_json(BUNDLED_PRICING_JSON).expect("bundled pricing table is valid")
+    })
+}
+
+/// Validates user-supplied rates before they are stored.
+pub fn validate_overrides(overrides: &BTreeMap<String, ModelPricing>) -> AppResult<()> {
+    for (model, pricing) in overrides {
+        if model.trim().is_empty() {
+            return Err(AppError::InvalidInput(
+                "pricing override model cannot be empty".to_string(),
+            ));
+        }
+        let rates = [pricing.input_per_token, pricing.output_per_token];
+        if rates.iter().any(|rate| !rate.is_finite() || *rate < 0.0) {
+            return Err(AppError::InvalidInput(format!(
+                "pricing for {model} must be finite and non-negative"
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use futures::future::BoxFuture;
+
+use crate::{
+    core::errors::{AppError, AppResult},
+    providers::{request_hash, CallKind, LlmProvider, ProviderResponse},
+};
+
+/// Serves responses recorded during an earlier run instead of calling a live model.
+#[derive(Debug, Clone)]
+pub struct ReplayProvider {
+    model: String,
+    responses: HashMap<String, ProviderResponse>,
+}
+
+impl ReplayProvider {
+    pub fn new(
+        model: impl Into<String>,
+        responses: impl IntoIterator<Item = (String, ProviderResponse)>,
+    ) -> Self {
+        Self {
+            model: model.into(),
+            responses: responses.into_iter().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
+
+impl LlmProvider for ReplayProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        let hash = request_hash(kind, prompt);
+        let outcome = self.responses.get(&hash).cloned().ok_or_else(|| {
+            AppError::ProviderInvalidResponse(format!(
+                "no recorded {} response for request {hash}",
+                kind.as_str()
+            ))
+        });
+        Box::pin(async move { outcome })
+    }
+
+    /// Recorded responses stand in for any model, including multimodal ones.
+    fn supports_images(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,73 @@
+//! HTTP clients and per-call deadlines built from [`ProviderTimeouts`].
+
+use std::time::Duration;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{ProviderTimeoutSettings, ProviderTimeouts},
+    },
+    providers::CallKind,
+};
+
+/// Longest timeout accepted for any setting, in seconds.
+pub const MAX_TIMEOUT_SECS: u64 = 3600;
+
+pub fn validate_settings(settings: &ProviderTimeoutSettings) -> AppResult<()> {
+    for (provider, timeouts) in [
+        ("gemini", &settings.gemini),
+        ("openrouter", &settings.openrouter),
+    ] {
+        for (name, secs) in [
+            ("connectSecs", timeouts.connect_secs),
+            ("plannerSecs", timeouts.planner_secs),
+            ("synthesisSecs", timeouts.synthesis_secs),
+            ("enrichmentSecs", timeouts.enrichment_secs),
+        ] {
+            if !(1..=MAX_TIMEOUT_SECS).contains(&secs) {
+                return Err(AppError::InvalidInput(format!(
+                    "{provider} {name} must be between 1 and {MAX_TIMEOUT_SECS}"
+                )));
+            }
+        }
+        if timeouts.keep_alive_secs > MAX_TIMEOUT_SECS {
+            return Err(AppError::InvalidInput(format!(
+                "{provider} keepAliveSecs must be at most {MAX_TIMEOUT_SECS}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Deadline for a whole request of `kind`, from sending it to reading the
+/// last byte of the response.
+pub fn request_timeout(timeouts: &ProviderTimeouts, kind: CallKind) -> Duration {
+    Duration::from_secs(match kind {
+        CallKind::Planner => timeouts.planner_secs,
+        CallKind::Synthesis => timeouts.synthesis_secs,
+        CallKind::Enrichment => timeouts.enrichment_secs,
+    })
+}
+
+/// A client that connects within the connect timeout and keeps idle
+/// connections for the keep-alive period. Requests set their own deadline
+/// with [`request_timeout`]; the longest call kind's applies otherwise.
+pub fn http_client(timeouts: &ProviderTimeouts) -> AppResult<reqwest::Client> {
+    let longest = timeouts
+        .planner_secs
+        .max(timeouts.synthesis_secs)
+        .max(timeouts.enrichment_secs);
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(timeouts.connect_secs))
+        .timeout(Duration::from_secs(longest));
+    builder = if timeouts.keep_alive_secs == 0 {
+        builder.pool_max_idle_per_host(0)
+    } else {
+        builder
+            .pool_idle_timeout(Duration::from_secs(timeouts.keep_alive_secs))
+            .tcp_keepalive(Duration::from_secs(timeouts.keep_alive_secs))
+    };
+    builder
+        .build()
+        .map_err(|err| AppError::Network(err.to_string()))
+}
@@ -0,0 +1,56 @@
+//! Prompt size estimation used to keep requests inside a model's context window.
+//!
+//! The estimate mirrors how BPE tokenizers split text: words are pre-tokenized on
+//! whitespace and punctuation, long words cost roughly one token per four bytes,
+//! and every punctuation mark is its own token. It errs on the high side so the
+//! trimmed prompt still fits when the real tokenizer disagrees.
+
+/// Context window assumed for models we do not recognise.
+pub const DEFAULT_CONTEXT_WINDOW: usize = 32_768;
+
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut word_len = 0;
+    for ch in text.chars() {
+        if ch.is_alphanumeric() {
+            word_len += ch.len_utf8();
+            continue;
+        }
+        tokens += word_tokens(word_len);
+        word_len = 0;
+        if !ch.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + word_tokens(word_len)
+}
+
+fn word_tokens(len: usize) -> usize {
+    len.div_ceil(4)
+}
+
+/// Input context size for a model name, by family.
+pub fn context_window_for(model: &str) -> usize {
+    let model = model.to_ascii_lowercase();
+    if model.starts_with("gemini-1.5-pro") {
+        2_097_152
+    } else if model.starts_with("gemini-") {
+        1_048_576
+    } else {
+        DEFAULT_CONTEXT_WINDOW
+    }
+}
+
+/// Returns how many leading items of a ranked list fit in `budget` tokens once
+/// rendered. At least one item is always kept so synthesis has something to cite.
+pub fn fit_ranked_items(
+    budget: usize,
+    item_count: usize,
+    render: impl Fn(usize) -> String,
+) -> usize {
+    let mut count = item_count;
+    while count > 1 && estimate_tokens(&render(count)) > budget {
+        count -= 1;
+    }
+    count
+}
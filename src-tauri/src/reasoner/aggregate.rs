@@ -0,0 +1,90 @@
+//! The Aggregate step: the quantities recorded in a run's evidence, totalled
+//! by unit and handed to the synthesis prompt, so an answer to "how much in
+//! total" is checked against a sum rather than the model's own arithmetic.
+
+use crate::sidecar::quantities::Quantity;
+
+/// The quantities of one base unit found across the evidence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitTotal {
+    pub unit: String,
+    pub count: usize,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Evidence nodes the values came from, in evidence order.
+    pub node_ids: Vec<String>,
+}
+
+impl UnitTotal {
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+/// Totals of `recorded`, each evidence node's quantities, by unit in order
+/// of first appearance.
+pub fn totals(recorded: &[(String, Vec<Quantity>)]) -> Vec<UnitTotal> {
+    let mut totals = Vec::<UnitTotal>::new();
+    for (node_id, quantities) in recorded {
+        for quantity in quantities {
+            let index = match totals.iter().position(|total| total.unit == quantity.unit) {
+                Some(index) => index,
+                None => {
+                    totals.push(UnitTotal {
+                        unit: quantity.unit.clone(),
+                        count: 0,
+                        sum: 0.0,
+                        min: quantity.value,
+                        max: quantity.value,
+                        node_ids: vec![],
+                    });
+                    totals.len() - 1
+                }
+            };
+            let total = &mut totals[index];
+            total.count += 1;
+            total.sum += quantity.value;
+            total.min = total.min.min(quantity.value);
+            total.max = total.max.max(quantity.value);
+            if !total.node_ids.contains(node_id) {
+                total.node_ids.push(node_id.clone());
+            }
+        }
+    }
+    totals
+}
+
+/// Appended to the synthesis prompt; empty when the evidence records no
+/// quantities.
+pub fn prompt_note(totals: &[UnitTotal]) -> String {
+    if totals.is_empty() {
+        return String::new();
+    }
+    let mut note = "\nQUANTITIES IN THE EVIDENCE (check totals against these; the same value may appear in more than one node):\n".to_string();
+    for total in totals {
+        let citations = total
+            .node_ids
+            .iter()
+            .map(|id| format!("[citation:{id}]"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        note.push_str(&format!(
+            "- {}: {} value(s), sum {}, mean {}, min {}, max {} {citations}\n",
+            total.unit,
+            total.count,
+            format_value(total.sum),
+            format_value(total.mean()),
+            format_value(total.min),
+            format_value(total.max),
+        ));
+    }
+    note
+}
+
+/// `value` without the float noise of adding decimals, e.g. "0.3" rather
+/// than "0.30000000000000004".
+pub fn format_value(value: f64) -> String {
+    let rounded = (value * 1e6).round() / 1e6;
+    format!("{rounded}")
+}
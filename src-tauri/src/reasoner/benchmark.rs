@@ -0,0 +1,310 @@
+//! Runs a user-provided Q&A set against a project and scores every answer, so
+//! a change of provider, model or prompts can be checked for regressions. A
+//! case passes when the run answers and the answer meets every expectation the
+//! case sets: keywords, citations and an exact answer.
+
+use std::path::Path;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{
+            BenchmarkCase, BenchmarkCaseResult, BenchmarkCaseStatus, BenchmarkReport,
+            BenchmarkSummary, QualityMetrics,
+        },
+    },
+    db::{
+        repositories::{documents, reasoning},
+        Database,
+    },
+    reasoner::{
+        executor::{ReasoningExecutor, RunOptions},
+        query_scope::requires_project_scope,
+    },
+};
+
+/// Reads a Q&A set: a JSON array of cases, an object with a `questions`
+/// array, or one JSON case per line.
+pub fn parse_qa_set(raw: &str) -> AppResult<Vec<BenchmarkCase>> {
+    let trimmed = raw.trim_start_matches('\u{feff}').trim();
+    let cases: Vec<BenchmarkCase> = if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed)
+            .map_err(|err| AppError::InvalidInput(format!("invalid Q&A set: {err}")))?
+    } else if let Ok(wrapped) = serde_json::from_str::<QaSetFile>(trimmed) {
+        wrapped.questions
+    } else {
+        trimmed
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line).map_err(|err| {
+                    AppError::InvalidInput(format!("invalid Q&A case on line {}: {err}", idx + 1))
+                })
+            })
+            .collect::<AppResult<_>>()?
+    };
+    if cases.is_empty() {
+        return Err(AppError::InvalidInput(
+            "the Q&A set has no questions".to_string(),
+        ));
+    }
+    if let Some(idx) = cases
+        .iter()
+        .position(|case| case.question.trim().is_empty())
+    {
+        return Err(AppError::InvalidInput(format!(
+            "question {} of the Q&A set is empty",
+            idx + 1
+        )));
+    }
+    Ok(cases)
+}
+
+#[derive(serde::Deserialize)]
+struct QaSetFile {
+    questions: Vec<BenchmarkCase>,
+}
+
+/// Asks every case in turn and scores the answers. `options` supplies keys,
+/// pricing and settings shared by all cases; `before_case` runs ahead of each
+/// question (e.g. to take a scheduler permit) and `on_case` after it.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_benchmark<B, G, F>(
+    executor: &ReasoningExecutor,
+    db: &Database,
+    project_id: &str,
+    qa_file: &str,
+    cases: &[BenchmarkCase],
+    options: RunOptions,
+    mut before_case: B,
+    mut on_case: F,
+) -> AppResult<BenchmarkReport>
+where
+    B: FnMut() -> G,
+    F: FnMut(&BenchmarkCaseResult),
+{
+    let started_at = Utc::now();
+    let mut results = Vec::with_capacity(cases.len());
+    for (index, case) in cases.iter().enumerate() {
+        let run_id = Uuid::new_v4().to_string();
+        let focus_document_id = case
+            .focus_document_id
+            .as_deref()
+            .filter(|_| !requires_project_scope(&case.question, &options.language));
+        let outcome = {
+            let _guard = before_case();
+            executor
+                .run_with_options(
+                    db,
+                    project_id,
+                    focus_document_id,
+                    run_id.clone(),
+                    &case.question,
+                    options.clone(),
+                    |_| {},
+                )
+                .await
+        };
+        let error = match outcome {
+            Ok(_) => None,
+            Err(err) => {
+                let _ = reasoning::fail_run(db.writer(), &run_id).await;
+                Some(err)
+            }
+        };
+        let result = score_case(db, index, case, run_id, error).await?;
+        on_case(&result);
+        results.push(result);
+    }
+
+    Ok(BenchmarkReport {
+        id: Uuid::new_v4().to_string(),
+        project_id: project_id.to_string(),
+        qa_file: qa_file.to_string(),
+        model: executor.model().to_string(),
+        started_at,
+        finished_at: Utc::now(),
+        summary: summarize(cases, &results),
+        cases: results,
+        report_path: None,
+    })
+}
+
+async fn score_case(
+    db: &Database,
+    index: usize,
+    case: &BenchmarkCase,
+    run_id: String,
+    error: Option<AppError>,
+) -> AppResult<BenchmarkCaseResult> {
+    let stored = reasoning::get_run(db.pool(), &run_id).await.ok();
+    let quality = stored.as_ref().and_then(|stored| {
+        serde_json::from_value::<QualityMetrics>(stored.run.quality_json.clone()).ok()
+    });
+    let answer = stored.as_ref().and_then(|stored| stored.answer.clone());
+    let answer_markdown = answer
+        .as_ref()
+        .map(|answer| answer.answer_markdown.as_str());
+
+    let mut labels = Vec::new();
+    for citation in answer.iter().flat_map(|answer| answer.citations.iter()) {
+        labels.extend(citation_labels(db, citation).await?);
+    }
+    let (keywords_matched, keywords_missing) = split_matches(&case.expected_keywords, |keyword| {
+        answer_markdown.is_some_and(|text| text.to_lowercase().contains(&keyword.to_lowercase()))
+    });
+    let (citations_matched, citations_missing) =
+        split_matches(&case.expected_citations, |expected| {
+            labels.contains(&expected.trim().to_lowercase())
+        });
+    let exact_match = case.expected_answer.as_deref().map(|expected| {
+        answer_markdown.is_some_and(|text| normalize_answer(text) == normalize_answer(expected))
+    });
+
+    let status = match &error {
+        Some(AppError::QualityGateFailed(_)) => BenchmarkCaseStatus::Failed,
+        Some(_) => BenchmarkCaseStatus::Errored,
+        // The quality gate answered with what is missing.
+        None if answer.as_ref().is_some_and(|answer| !answer.grounded) => {
+            BenchmarkCaseStatus::Failed
+        }
+        None if keywords_missing.is_empty()
+            && citations_missing.is_empty()
+            && exact_match != Some(false) =>
+        {
+            BenchmarkCaseStatus::Passed
+        }
+        None => BenchmarkCaseStatus::Failed,
+    };
+
+    Ok(BenchmarkCaseResult {
+        index,
+        id: case.id.clone(),
+        question: case.question.clone(),
+        run_id,
+        status,
+        error: error.map(|err| err.to_string()),
+        answer_markdown: answer_markdown.map(ToString::to_string),
+        confidence: answer.as_ref().map(|answer| answer.confidence),
+        quality,
+        keywords_matched,
+        keywords_missing,
+        citations_matched,
+        citations_missing,
+        exact_match,
+        cost_usd: stored
+            .as_ref()
+            .map(|stored| stored.run.cost_usd)
+            .unwrap_or_default(),
+        latency_ms: stored
+            .as_ref()
+            .and_then(|stored| stored.run.total_latency_ms)
+            .unwrap_or_default(),
+    })
+}
+
+/// The names a cited node answers to: its id, its document's id and its
+/// document's file name, lowercased.
+async fn citation_labels(db: &Database, node_id: &str) -> AppResult<Vec<String>> {
+    let mut labels = vec![node_id.to_lowercase()];
+    let node = match documents::get_node(db.pool(), node_id).await {
+        Ok(node) => node,
+        Err(AppError::NotFound(_)) => return Ok(labels),
+        Err(err) => return Err(err),
+    };
+    labels.push(node.id.to_lowercase());
+    labels.push(node.document_id.to_lowercase());
+    match documents::get_document(db.pool(), &node.document_id).await {
+        Ok(document) => labels.push(document.name.to_lowercase()),
+        Err(AppError::NotFound(_)) => {}
+        Err(err) => return Err(err),
+    }
+    Ok(labels)
+}
+
+fn split_matches(expected: &[String], found: impl Fn(&str) -> bool) -> (Vec<String>, Vec<String>) {
+    expected
+        .iter()
+        .filter(|value| !value.trim().is_empty())
+        .cloned()
+        .partition(|value| found(value))
+}
+
+/// Lowercases, collapses whitespace and drops trailing punctuation, so "50 ms."
+/// and "50 MS" compare equal.
+pub fn normalize_answer(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['.', '!', '?', ';', ':', ','])
+        .to_lowercase()
+}
+
+fn summarize(cases: &[BenchmarkCase], results: &[BenchmarkCaseResult]) -> BenchmarkSummary {
+    let count = |status| {
+        results
+            .iter()
+            .filter(|result| result.status == status)
+            .count()
+    };
+    let rate = |hits: usize, of: usize| (of > 0).then(|| hits as f64 / of as f64);
+
+    let overall = results
+        .iter()
+        .filter_map(|result| result.quality.as_ref().map(|quality| quality.overall))
+        .collect::<Vec<_>>();
+    let keywords_expected = results
+        .iter()
+        .map(|result| result.keywords_matched.len() + result.keywords_missing.len())
+        .sum();
+    let keywords_matched = results
+        .iter()
+        .map(|result| result.keywords_matched.len())
+        .sum();
+    let citations_expected = results
+        .iter()
+        .map(|result| result.citations_matched.len() + result.citations_missing.len())
+        .sum();
+    let citations_matched = results
+        .iter()
+        .map(|result| result.citations_matched.len())
+        .sum();
+    let exact_checked = results
+        .iter()
+        .filter(|result| result.exact_match.is_some())
+        .count();
+    let exact_matched = results
+        .iter()
+        .filter(|result| result.exact_match == Some(true))
+        .count();
+    let passed = count(BenchmarkCaseStatus::Passed);
+
+    BenchmarkSummary {
+        total: cases.len(),
+        passed,
+        failed: count(BenchmarkCaseStatus::Failed),
+        errored: count(BenchmarkCaseStatus::Errored),
+        pass_rate: rate(passed, cases.len()).unwrap_or_default(),
+        avg_overall: (!overall.is_empty())
+            .then(|| overall.iter().sum::<f64>() / overall.len() as f64),
+        keyword_recall: rate(keywords_matched, keywords_expected),
+        citation_recall: rate(citations_matched, citations_expected),
+        exact_match_rate: rate(exact_matched, exact_checked),
+        total_cost_usd: results.iter().map(|result| result.cost_usd).sum(),
+    }
+}
+
+/// Writes `report` as pretty-printed JSON, creating the folder if needed.
+pub fn write_report(report: &BenchmarkReport, path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json =
+        serde_json::to_string_pretty(report).map_err(|err| AppError::Internal(err.to_string()))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeSet;
+
+use crate::core::types::{
+    GetRunResponse, QualityMetrics, RunComparison, RunComparisonSide, RunDeltas,
+};
+
+/// Lines up two stored runs, usually the same question asked with different
+/// models or settings, and reports how B differs from A.
+pub fn compare_runs(a: &GetRunResponse, b: &GetRunResponse) -> RunComparison {
+    let run_a = side(a);
+    let run_b = side(b);
+
+    let cited_a: BTreeSet<&String> = run_a.citations.iter().collect();
+    let cited_b: BTreeSet<&String> = run_b.citations.iter().collect();
+    let shared_citations: Vec<String> = cited_a
+        .intersection(&cited_b)
+        .map(|id| id.to_string())
+        .collect();
+    let only_in_a = cited_a
+        .difference(&cited_b)
+        .map(|id| id.to_string())
+        .collect();
+    let only_in_b = cited_b
+        .difference(&cited_a)
+        .map(|id| id.to_string())
+        .collect();
+    let cited = cited_a.union(&cited_b).count();
+    let citation_overlap = if cited == 0 {
+        1.0
+    } else {
+        shared_citations.len() as f64 / cited as f64
+    };
+
+    let metric = |pick: fn(&QualityMetrics) -> f64| match (&run_a.quality, &run_b.quality) {
+        (Some(qa), Some(qb)) => Some(pick(qb) - pick(qa)),
+        _ => None,
+    };
+    let deltas = RunDeltas {
+        confidence: run_a
+            .confidence
+            .zip(run_b.confidence)
+            .map(|(ca, cb)| cb - ca),
+        overall: metric(|q| q.overall),
+        query_alignment: metric(|q| q.query_alignment),
+        citation_coverage: metric(|q| q.citation_coverage),
+        evidence_recall: metric(|q| q.evidence_recall),
+        cross_document_coverage: metric(|q| q.cross_document_coverage),
+        step_count: run_b.step_count - run_a.step_count,
+        cost_usd: run_b.cost_usd - run_a.cost_usd,
+        total_latency_ms: run_a
+            .total_latency_ms
+            .zip(run_b.total_latency_ms)
+            .map(|(la, lb)| lb - la),
+    };
+
+    RunComparison {
+        same_query: run_a.query.trim() == run_b.query.trim(),
+        shared_citations,
+        only_in_a,
+        only_in_b,
+        citation_overlap,
+        deltas,
+        run_a,
+        run_b,
+    }
+}
+
+fn side(stored: &GetRunResponse) -> RunComparisonSide {
+    let run = &stored.run;
+    RunComparisonSide {
+        run_id: run.id.clone(),
+        query: run.query.clone(),
+        status: run.status.clone(),
+        model: run.manifest.as_ref().map(|manifest| manifest.model.clone()),
+        answer_markdown: stored
+            .answer
+            .as_ref()
+            .map(|answer| answer.answer_markdown.clone()),
+        confidence: stored.answer.as_ref().map(|answer| answer.confidence),
+        citations: stored
+            .answer
+            .as_ref()
+            .map(|answer| answer.citations.clone())
+            .unwrap_or_default(),
+        step_count: stored.steps.len() as i64,
+        cost_usd: run.cost_usd,
+        total_latency_ms: run.total_latency_ms,
+        quality: serde_json::from_value(run.quality_json.clone()).ok(),
+    }
+}
@@ -1,28 +1,35 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::core::types::QualityMetrics;
+use crate::core::types::{LanguageResources, QualityMetrics, QualityWeights};
 
+/// Scores an answer. `cited_text` holds the text of the evidence the answer
+/// cites, by node id; it decides which of the answer's claims are backed.
+#[allow(clippy::too_many_arguments)]
 pub fn evaluate_answer(
     query: &str,
     answer_markdown: &str,
     citations: &[String],
     evidence_node_ids: &[String],
     citation_document_map: &HashMap<String, String>,
+    cited_text: &HashMap<String, String>,
     relation_query: bool,
+    language: &LanguageResources,
 ) -> QualityMetrics {
     let grounded = !answer_markdown.trim().is_empty() && !citations.is_empty();
-    let query_alignment = query_alignment_score(query, answer_markdown);
+    let query_alignment = query_alignment_score(query, answer_markdown, language);
 
     let evidence_set: HashSet<&str> = evidence_node_ids.iter().map(String::as_str).collect();
     let valid_citations = citations
         .iter()
         .filter(|citation| evidence_set.contains(citation.as_str()))
         .count();
-    let citation_coverage = if evidence_node_ids.is_empty() {
+    let evidence_recall = if evidence_node_ids.is_empty() {
         0.0
     } else {
         (valid_citations as f64 / evidence_node_ids.len() as f64).min(1.0)
     };
+    let citation_coverage =
+        claim_coverage(answer_markdown, cited_text, valid_citations > 0, language);
 
     let cross_document_coverage = if relation_query {
         let docs = citations
@@ -40,65 +47,261 @@ pub fn evaluate_answer(
         1.0
     };
 
-    let grounding_score = if grounded { 1.0 } else { 0.0 };
-    let overall = (query_alignment * 0.4)
-        + (citation_coverage * 0.25)
-        + (cross_document_coverage * 0.2)
-        + (grounding_score * 0.15);
-
-    QualityMetrics {
-        overall: overall.min(1.0),
+    let mut metrics = QualityMetrics {
+        overall: 0.0,
         query_alignment,
         citation_coverage,
+        evidence_recall,
         cross_document_coverage,
         grounded,
+    };
+    metrics.overall = weighted_overall(&metrics, &QualityWeights::default());
+    metrics
+}
+
+/// `overall` for `metrics` under `weights`; grounding counts 1 or 0.
+pub fn weighted_overall(metrics: &QualityMetrics, weights: &QualityWeights) -> f64 {
+    let grounding_score = if metrics.grounded { 1.0 } else { 0.0 };
+    let overall = (metrics.query_alignment * weights.query_alignment)
+        + (metrics.citation_coverage * weights.citation_coverage)
+        + (metrics.cross_document_coverage * weights.cross_document_coverage)
+        + (grounding_score * weights.grounding);
+    overall.min(1.0)
+}
+
+/// Claims shorter than this many words (fragments, "Yes.") are not scored.
+const MIN_CLAIM_WORDS: usize = 3;
+/// Content words a claim must share with cited evidence to count as backed.
+const MIN_SHARED_TERMS: usize = 2;
+
+/// Share of the answer's claims (sentences and list items, headings left
+/// out) that are backed by a citation: an inline `[citation:...]` marker, or
+/// enough content words in common with the cited evidence. Evidence that was
+/// retrieved but not needed does not lower it. An answer too short to hold a
+/// claim is covered when it cites anything.
+fn claim_coverage(
+    answer: &str,
+    cited_text: &HashMap<String, String>,
+    cites_anything: bool,
+    language: &LanguageResources,
+) -> f64 {
+    let claims = answer_claims(answer);
+    if claims.is_empty() {
+        return if cites_anything { 1.0 } else { 0.0 };
+    }
+    let evidence_terms = cited_text
+        .values()
+        .flat_map(|text| content_terms(text, language))
+        .collect::<HashSet<_>>();
+    let backed = claims
+        .iter()
+        .filter(|claim| {
+            if claim.contains("[citation:") {
+                return true;
+            }
+            let terms = content_terms(claim, language);
+            let shared = terms
+                .iter()
+                .filter(|term| evidence_terms.iter().any(|word| same_stem(term, word)))
+                .count();
+            shared > 0 && shared >= MIN_SHARED_TERMS.min(terms.len())
+        })
+        .count();
+    backed as f64 / claims.len() as f64
+}
+
+/// The answer's sentences and list items, without headings or list markers.
+fn answer_claims(answer: &str) -> Vec<String> {
+    answer
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .map(strip_list_marker)
+        .flat_map(sentences)
+        .filter(|claim| claim.split_whitespace().count() >= MIN_CLAIM_WORDS)
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn strip_list_marker(line: &str) -> &str {
+    if let Some(rest) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+    {
+        return rest.trim_start();
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    match rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+        Some(rest) if digits > 0 => rest.trim_start(),
+        _ => line,
+    }
+}
+
+/// Splits after `.`, `!` or `?` followed by whitespace, so "3.5" and "e.g"
+/// stay whole.
+fn sentences(line: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        let at_break = matches!(c, '.' | '!' | '?')
+            && chars.peek().is_some_and(|(_, next)| next.is_whitespace());
+        if at_break {
+            parts.push(line[start..=idx].trim());
+            start = idx + 1;
+        }
     }
+    parts.push(line[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Stemmed words of four or more characters that are not stopwords.
+fn content_terms(text: &str, language: &LanguageResources) -> HashSet<String> {
+    text.to_lowercase()
+        .split(|value: char| !value.is_alphanumeric())
+        .filter(|value| value.chars().count() > 3)
+        .filter(|value| !language.stopwords.iter().any(|stopword| stopword == value))
+        .map(stem)
+        .collect()
 }
 
-fn query_alignment_score(query: &str, answer: &str) -> f64 {
-    let answer_lower = answer.to_ascii_lowercase();
+/// Credit for a query term the answer only has a synonym of.
+const SYNONYM_CREDIT: f64 = 0.8;
+/// Credit for a query term the answer only has a word sharing a long prefix with.
+const PARTIAL_CREDIT: f64 = 0.5;
+/// Shortest stem that may match a longer one by prefix, and shortest shared
+/// prefix that earns partial credit.
+const MIN_PREFIX_CHARS: usize = 5;
+
+/// Suffixes `stem` strips, longest first, with what replaces them.
+const SUFFIXES: &[(&str, &str)] = &[
+    ("ational", "ate"),
+    ("ations", ""),
+    ("ation", ""),
+    ("ments", ""),
+    ("ities", ""),
+    ("ment", ""),
+    ("ness", ""),
+    ("ings", ""),
+    ("ions", ""),
+    ("ies", "y"),
+    ("ied", "y"),
+    ("ing", ""),
+    ("ion", ""),
+    ("ity", ""),
+    ("ers", ""),
+    ("ed", ""),
+    ("es", ""),
+    ("er", ""),
+    ("ly", ""),
+    ("s", ""),
+];
+
+/// Share of the query's terms the answer covers. A term counts fully when
+/// the answer has it or another form of it ("terminated" for
+/// "termination"), and partly when the answer has a synonym of it or a word
+/// sharing a long prefix with it.
+fn query_alignment_score(query: &str, answer: &str, language: &LanguageResources) -> f64 {
+    let answer_lower = answer.to_lowercase();
     let terms = query
-        .split(|value: char| !value.is_ascii_alphanumeric())
-        .map(|value| value.trim().to_ascii_lowercase())
-        .filter(|value| value.len() > 2)
-        .filter(|value| !is_stopword(value))
+        .split(|value: char| !value.is_alphanumeric())
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| value.chars().count() > 2)
+        .filter(|value| !language.stopwords.contains(value))
         .collect::<Vec<_>>();
 
     if terms.is_empty() {
         return 0.0;
     }
 
-    let matched = terms
+    let answer_stems = answer_lower
+        .split(|value: char| !value.is_alphanumeric())
+        .filter(|value| value.chars().count() > 2)
+        .map(stem)
+        .collect::<HashSet<_>>();
+    let credit = terms
         .iter()
-        .filter(|term| answer_lower.contains(term.as_str()))
-        .count();
-    (matched as f64 / terms.len() as f64).min(1.0)
+        .map(|term| term_credit(term, &answer_lower, &answer_stems, &language.synonyms))
+        .sum::<f64>();
+    (credit / terms.len() as f64).min(1.0)
 }
 
-fn is_stopword(value: &str) -> bool {
-    matches!(
-        value,
-        "the"
-            | "and"
-            | "for"
-            | "are"
-            | "how"
-            | "what"
-            | "with"
-            | "about"
-            | "that"
-            | "this"
-            | "these"
-            | "from"
-            | "into"
-            | "their"
-            | "they"
-    )
+fn term_credit(
+    term: &str,
+    answer_lower: &str,
+    answer_stems: &HashSet<String>,
+    synonyms: &[Vec<String>],
+) -> f64 {
+    if answer_lower.contains(term) {
+        return 1.0;
+    }
+    let term_stem = stem(term);
+    if answer_stems.iter().any(|word| same_stem(&term_stem, word)) {
+        return 1.0;
+    }
+    let has_synonym = synonyms
+        .iter()
+        .filter(|group| group.iter().any(|word| same_stem(&term_stem, &stem(word))))
+        .flatten()
+        .any(|word| {
+            answer_lower.contains(word.as_str())
+                || answer_stems
+                    .iter()
+                    .any(|answer_word| same_stem(&stem(word), answer_word))
+        });
+    if has_synonym {
+        return SYNONYM_CREDIT;
+    }
+    let partial = answer_stems.iter().any(|word| {
+        let shared = shared_prefix_chars(&term_stem, word);
+        let shorter = term_stem.chars().count().min(word.chars().count());
+        shared >= MIN_PREFIX_CHARS && shared * 3 >= shorter * 2
+    });
+    if partial {
+        PARTIAL_CREDIT
+    } else {
+        0.0
+    }
+}
+
+/// Strips one common inflectional or derivational suffix, keeping at least
+/// three characters. Meant for English; other languages still get the
+/// prefix rules in `same_stem` and partial credit.
+pub fn stem(word: &str) -> String {
+    let word = word.to_lowercase();
+    for (suffix, replacement) in SUFFIXES {
+        if let Some(base) = word.strip_suffix(suffix) {
+            if base.chars().count() >= 3 {
+                return format!("{base}{replacement}");
+            }
+        }
+    }
+    word
+}
+
+/// Equal stems, or a long stem that begins with the other one, as
+/// "termin" (termination) and "terminat" (terminated).
+pub fn same_stem(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    shorter.chars().count() >= MIN_PREFIX_CHARS && longer.starts_with(shorter)
+}
+
+fn shared_prefix_chars(a: &str, b: &str) -> usize {
+    a.chars()
+        .zip(b.chars())
+        .take_while(|(left, right)| left == right)
+        .count()
 }
 
 #[cfg(test)]
 mod tests {
     use super::evaluate_answer;
+    use crate::core::types::LanguageResources;
     use std::collections::HashMap;
 
     #[test]
@@ -108,6 +311,15 @@ mod tests {
         let mut doc_map = HashMap::new();
         doc_map.insert("n1".to_string(), "doc-a".to_string());
         doc_map.insert("n2".to_string(), "doc-b".to_string());
+        let mut cited_text = HashMap::new();
+        cited_text.insert(
+            "n1".to_string(),
+            "The architecture uses U-Net components.".to_string(),
+        );
+        cited_text.insert(
+            "n2".to_string(),
+            "Experiments reuse the shared U-Net components.".to_string(),
+        );
 
         let metrics = evaluate_answer(
             "Explain what these files are about and how they are related",
@@ -115,7 +327,9 @@ mod tests {
             &citations,
             &evidence,
             &doc_map,
+            &cited_text,
             true,
+            &LanguageResources::default(),
         );
 
         assert!(metrics.grounded);
@@ -131,10 +345,75 @@ mod tests {
             &[],
             &[],
             &HashMap::new(),
+            &HashMap::new(),
             false,
+            &LanguageResources::default(),
         );
 
         assert!(!metrics.grounded);
         assert!(metrics.overall < 0.3);
     }
+
+    fn alignment(query: &str, answer: &str) -> f64 {
+        evaluate_answer(
+            query,
+            answer,
+            &[],
+            &[],
+            &HashMap::new(),
+            &HashMap::new(),
+            false,
+            &LanguageResources::default(),
+        )
+        .query_alignment
+    }
+
+    #[test]
+    fn evaluator_credits_other_forms_synonyms_and_shared_prefixes() {
+        assert_eq!(
+            alignment(
+                "contract termination notice",
+                "The contract was terminated without notice."
+            ),
+            1.0
+        );
+        assert!((alignment("staff costs", "Employee expenses rose.") - 0.8).abs() < 1e-9);
+        assert_eq!(alignment("latency", "The latent period is short."), 0.5);
+        assert_eq!(alignment("latency", "The queue is short."), 0.0);
+    }
+
+    #[test]
+    fn citation_coverage_tracks_backed_claims_not_unused_evidence() {
+        let evidence = (1..=8).map(|n| format!("n{n}")).collect::<Vec<_>>();
+        let citations = evidence[..3].to_vec();
+        let cited_text = HashMap::from([
+            (
+                "n1".to_string(),
+                "Contracts may be terminated with ninety days notice.".to_string(),
+            ),
+            (
+                "n2".to_string(),
+                "Termination fees apply in the first year.".to_string(),
+            ),
+        ]);
+        let answer = "## Termination\n\
+            - Contracts can be terminated with ninety days notice.\n\
+            - A termination fee applies during the first year.\n\
+            - Renewal is automatic [citation:n3].\n\
+            - The office dog is called Biscuit.";
+
+        let metrics = evaluate_answer(
+            "How can contracts be terminated?",
+            answer,
+            &citations,
+            &evidence,
+            &HashMap::new(),
+            &cited_text,
+            false,
+            &LanguageResources::default(),
+        );
+
+        assert_eq!(metrics.citation_coverage, 0.75);
+        assert_eq!(metrics.evidence_recall, 3.0 / 8.0);
+    }
 }
@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    sync::Arc,
     time::Instant,
 };
 
@@ -8,22 +9,50 @@ use serde_json::Value;
 use crate::{
     core::{
         errors::{AppError, AppResult},
-        types::ReasoningStepEvent,
+        metrics::Metric,
+        privacy::Redactor,
+        types::{
+            DateRange, DocNodeSummary, GenerationSettings, LanguageResources, ModelCostEstimate,
+            ModelPricing, NodeType, PrivacySettings, Provider, QualityThresholds,
+            QueryCostEstimate, RankingWeights, ReasoningPhaseEvent, ReasoningStepEvent,
+            RetrievalSettings, RunManifest, RunManifestDocument, RunPhase, RunStatus,
+            ScopeDecision,
+        },
     },
     db::{
         repositories::{
-            documents,
-            reasoning::{self, NewStep},
+            acronyms, assets, documents, metrics, prompts,
+            provider_cache::{self, ProviderCacheConfig},
+            reasoning::{self, CallTiming, NewStep},
+            settings,
         },
         Database,
     },
-    providers::gemini::GeminiClient,
+    providers::{
+        gemini::{
+            estimate_cost_usd, parse_answer_from_raw_text, parse_answer_output, parse_planner_step,
+            GeminiOutput,
+        },
+        replay::ReplayProvider,
+        cache_key_with_images, request_hash,
+        tokens::{estimate_tokens, fit_ranked_items},
+        CallKind, ImagePart, LlmProvider, ProviderResponse,
+    },
     reasoner::{
-        evaluator::evaluate_answer,
-        planner::{Planner, PlannerConfig, PlannerDecision, PlannerInput, StepType},
-        prompts::{planner_prompt, synthesis_prompt},
-        query_scope::requires_project_scope,
+        aggregate,
+        evaluator::{evaluate_answer, weighted_overall},
+        feedback::EvidenceFeedback,
+        insufficient::EvidenceGaps,
+        narration,
+        navigation::{drill_down, evidence_context, query_hits},
+        planner::{BranchOutcome, Planner, PlannerConfig, PlannerDecision, PlannerInput, StepType},
+        prompts::{render_planner_prompt, render_synthesis_prompt, strict_json_prompt, PromptSet},
+        query_class::classify_query,
+        query_scope::{infer_focus_document, requires_project_scope},
+        ranking,
     },
+    security::key_pool::ApiKeyPool,
+    sidecar::{dates, quantities},
 };
 
 #[derive(Debug, Clone)]
@@ -34,22 +63,179 @@ pub struct ExecutionResult {
     pub total_latency_ms: i64,
     pub token_usage: Value,
     pub cost_usd: f64,
+    /// The quality gate turned the draft away and the answer lists what the
+    /// evidence does not cover instead.
+    pub insufficient_evidence: bool,
+}
+
+/// Progress notifications emitted while a run executes.
+#[derive(Debug, Clone)]
+pub enum RunEvent {
+    Step(ReasoningStepEvent),
+    Phase(ReasoningPhaseEvent),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub max_steps: Option<usize>,
+    /// Pins the prompt templates instead of loading the active versions.
+    pub prompt_set: Option<PromptSet>,
+    /// Source run when this execution replays recorded provider responses.
+    pub replay_of: Option<String>,
+    /// Source run when this execution re-asks its question with other settings.
+    pub rerun_of: Option<String>,
+    /// Sampling and safety settings, already layered over the project defaults.
+    pub generation: GenerationSettings,
+    /// Keys to call the provider with; rate-limited keys fail over to the next.
+    pub api_keys: ApiKeyPool,
+    /// User-configured rates that replace the provider's own pricing.
+    pub pricing: Option<ModelPricing>,
+    /// Like `pricing`, for the planner model.
+    pub planner_pricing: Option<ModelPricing>,
+    /// Sends the stored images of Figure evidence along with the synthesis
+    /// prompt when the synthesis model accepts images.
+    pub attach_figure_images: bool,
+    /// The provider the user selected, recorded in the run's manifest.
+    pub provider: Option<Provider>,
+    /// Reads paragraph and claim evidence together with its section heading
+    /// and neighbouring paragraphs.
+    pub expand_context: bool,
+    /// Without a focus document, scopes retrieval to a document the query
+    /// names; the inference is recorded in the planner trace.
+    pub infer_focus_document: bool,
+    /// Word lists for reading the query: scope detection and answer scoring.
+    pub language: LanguageResources,
+    /// Quality gate weights and thresholds per query class.
+    pub quality: QualityThresholds,
+    /// How the keyword search matches the query's terms and how its hits
+    /// are ranked.
+    pub retrieval: RetrievalSettings,
+    /// Keeps evidence to these dates. Without it, nodes dated in the period
+    /// the query names are ranked first; that reading is recorded in the
+    /// planner trace.
+    pub date_range: Option<DateRange>,
+    /// Whether text quoted in diagnostics is redacted.
+    pub privacy: PrivacySettings,
 }
 
 #[derive(Clone)]
 pub struct ReasoningExecutor {
     planner: Planner,
-    gemini: GeminiClient,
+    provider: Arc<dyn LlmProvider>,
+    planner_provider: Option<Arc<dyn LlmProvider>>,
+    cache: Option<ProviderCacheConfig>,
+}
+
+/// Per-run inputs shared by every provider call.
+struct CallContext<'a> {
+    db: &'a Database,
+    run_id: &'a str,
+    keys: &'a ApiKeyPool,
+    settings: &'a GenerationSettings,
+    pricing: ModelPricing,
+    planner_pricing: ModelPricing,
+    redactor: Redactor,
+}
+
+/// Per-run counters surfaced alongside token usage.
+#[derive(Debug, Default)]
+struct CallStats {
+    provider_calls: u32,
+    cache_hits: u32,
+    planner: ModelUsage,
+    synthesis: ModelUsage,
+}
+
+/// Tokens and cost accumulated by one model over a run.
+#[derive(Debug, Default)]
+struct ModelUsage {
+    calls: u32,
+    prompt_tokens: i64,
+    output_tokens: i64,
+    cost_usd: f64,
+}
+
+impl ModelUsage {
+    fn add(&mut self, token_usage: &Value, pricing: &ModelPricing) {
+        let count = |field: &str| token_usage.get(field).and_then(Value::as_i64).unwrap_or(0);
+        self.calls += 1;
+        self.prompt_tokens += count("promptTokenCount");
+        self.output_tokens += count("candidatesTokenCount");
+        self.cost_usd += estimate_cost_usd(token_usage, pricing);
+    }
+
+    fn to_json(&self, model: &str) -> Value {
+        serde_json::json!({
+            "model": model,
+            "calls": self.calls,
+            "promptTokenCount": self.prompt_tokens,
+            "candidatesTokenCount": self.output_tokens,
+            "totalTokenCount": self.prompt_tokens + self.output_tokens,
+            "costUsd": self.cost_usd,
+        })
+    }
 }
 
-const MIN_QUALITY_SCORE: f64 = 0.60;
-const MIN_RELATION_QUALITY_SCORE: f64 = 0.70;
+/// Step confidence at which a run stops looking for more evidence.
+const DONE_CONFIDENCE: f64 = 0.70;
+/// Extra synthesis attempts made with a stricter JSON prompt before falling back to raw text.
+const SYNTHESIS_JSON_RETRIES: usize = 1;
+/// Tokens left free in the context window for the synthesized answer.
+const SYNTHESIS_OUTPUT_RESERVE_TOKENS: usize = 2_048;
+/// Answer length assumed by cost estimates when no output limit is set.
+const ESTIMATED_SYNTHESIS_OUTPUT_TOKENS: usize = 600;
+/// Length of a plan step as cost estimates count it.
+const ESTIMATED_PLANNER_OUTPUT_TOKENS: usize = 120;
+/// Recent calls averaged for a model's latency in cost estimates.
+const LATENCY_SAMPLE_CALLS: i64 = 50;
+/// Upper bound on figure images attached to one synthesis call.
+const MAX_FIGURE_IMAGES: usize = 4;
+/// Siblings or children of each cited node added to the next retrieval.
+const NEIGHBOURS_PER_USEFUL_NODE: usize = 4;
+/// Drilled-down nodes added to the evidence the keyword search found.
+const MAX_DRILLED_EVIDENCE: usize = 4;
+/// Characters of surrounding text added to a paragraph or claim when
+/// `RunOptions::expand_context` is set.
+const EVIDENCE_CONTEXT_CHARS: usize = 600;
 
 impl ReasoningExecutor {
-    pub fn new(gemini: GeminiClient) -> Self {
+    pub fn new(provider: impl LlmProvider + 'static) -> Self {
+        Self::with_provider(Arc::new(provider))
+    }
+
+    pub fn with_provider(provider: Arc<dyn LlmProvider>) -> Self {
         Self {
             planner: Planner::new(PlannerConfig::default()),
-            gemini,
+            provider,
+            planner_provider: None,
+            cache: None,
+        }
+    }
+
+    /// Sends plan steps to `provider` instead of the synthesis model.
+    pub fn with_planner_provider(mut self, provider: Arc<dyn LlmProvider>) -> Self {
+        self.planner_provider = Some(provider);
+        self
+    }
+
+    /// Serves repeated identical provider requests from the SQLite response cache.
+    pub fn with_cache(mut self, config: ProviderCacheConfig) -> Self {
+        self.cache = Some(config);
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        self.provider.model()
+    }
+
+    pub fn planner_model(&self) -> &str {
+        self.provider_for(CallKind::Planner).model()
+    }
+
+    fn provider_for(&self, kind: CallKind) -> &Arc<dyn LlmProvider> {
+        match (kind, &self.planner_provider) {
+            (CallKind::Planner, Some(planner)) => planner,
+            _ => &self.provider,
         }
     }
 
@@ -67,37 +253,195 @@ impl ReasoningExecutor {
     where
         F: FnMut(ReasoningStepEvent) + Send,
     {
-        reasoning::create_run(db.pool(), &run_id, project_id, focus_document_id, query).await?;
+        self.run_with_options(
+            db,
+            project_id,
+            focus_document_id,
+            run_id,
+            query,
+            RunOptions {
+                max_steps,
+                api_keys: ApiKeyPool::single(api_key),
+                ..RunOptions::default()
+            },
+            |event| {
+                if let RunEvent::Step(step) = event {
+                    on_step(step);
+                }
+            },
+        )
+        .await
+    }
+
+    pub async fn run_with_options<F>(
+        &self,
+        db: &Database,
+        project_id: &str,
+        focus_document_id: Option<&str>,
+        run_id: String,
+        query: &str,
+        options: RunOptions,
+        mut on_event: F,
+    ) -> AppResult<ExecutionResult>
+    where
+        F: FnMut(RunEvent) + Send,
+    {
+        if let Some(document_id) = focus_document_id {
+            documents::ensure_unlocked(db.pool(), document_id).await?;
+        }
+        reasoning::create_run(db.writer(), &run_id, project_id, focus_document_id, query).await?;
+        let prompt_set = match options.prompt_set {
+            Some(prompt_set) => prompt_set,
+            None => prompts::load_prompt_set(db.pool()).await?,
+        };
+        reasoning::set_run_prompt_versions(db.writer(), &run_id, prompt_set.versions_json()).await?;
+
+        let inferred_focus = if focus_document_id.is_none()
+            && options.infer_focus_document
+            && !requires_project_scope(query, &options.language)
+        {
+            let mut documents = documents::list_documents(db.pool(), project_id, true).await?;
+            documents.retain(|document| !document.locked || document.unlocked);
+            infer_focus_document(query, &documents)
+        } else {
+            None
+        };
+        let requested_focus = focus_document_id;
+        let focus_document_id = focus_document_id.or(inferred_focus
+            .as_ref()
+            .map(|inferred| inferred.document_id.as_str()));
 
         let started = Instant::now();
-        let max_steps = max_steps.unwrap_or(6).max(2);
+        let max_steps = options.max_steps.unwrap_or(6).max(2);
+        reasoning::set_run_execution(
+            db.writer(),
+            &run_id,
+            max_steps as i64,
+            options.replay_of.as_deref(),
+            options.attach_figure_images,
+        )
+        .await?;
+        let relation_query =
+            focus_document_id.is_none() && requires_project_scope(query, &options.language);
+        let query_class = classify_query(query, relation_query, &options.language);
+        let quality_policy = options.quality.policy(query_class);
+        let search = search_terms(db, project_id, query, &options).await?;
+        let mut manifest = RunManifest {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            provider: options.provider.clone(),
+            model: self.model().to_string(),
+            planner_model: self.planner_model().to_string(),
+            prompt_versions: prompt_set.versions_json(),
+            generation: options.generation.clone(),
+            pricing: options.pricing,
+            planner_pricing: options.planner_pricing,
+            max_steps: max_steps as i64,
+            attach_figure_images: options.attach_figure_images,
+            expand_context: options.expand_context,
+            focus_document_id: requested_focus.map(ToString::to_string),
+            infer_focus_document: options.infer_focus_document,
+            fuzziness: options.retrieval.fuzziness,
+            ranking: Some(options.retrieval.ranking.clone()),
+            date_range: options.date_range,
+            query_class: Some(query_class),
+            quality_policy: Some(quality_policy),
+            language: Some(options.language.clone()),
+            quality: Some(options.quality.clone()),
+            privacy: Some(options.privacy),
+            replay_of: options.replay_of.clone(),
+            rerun_of: options.rerun_of.clone(),
+            documents: vec![],
+            created_at: chrono::Utc::now(),
+        };
+        reasoning::set_run_manifest(db.writer(), &run_id, &manifest).await?;
         let mut step_count: usize = 0;
         let mut backtrack_count: usize = 0;
         let mut explored_sections: Vec<String> = vec![];
+        let mut selected_sections: Vec<DocNodeSummary> = vec![];
+        let mut searched_sections: Vec<String> = vec![];
+        let mut drilled: Vec<DocNodeSummary> = vec![];
+        let mut branch_outcomes: Vec<BranchOutcome> = vec![];
         let mut confidence: Option<f64> = None;
         let mut evidence_ids: Vec<String> = vec![];
         let mut evidence_snippets: Vec<String> = vec![];
         let mut evidence_doc_map: HashMap<String, String> = HashMap::new();
+        let mut evidence_text: HashMap<String, String> = HashMap::new();
+        let mut evidence_branches: HashMap<String, String> = HashMap::new();
+        let mut cited_ids: Vec<String> = vec![];
+        let mut feedback = EvidenceFeedback::default();
+        let mut figure_ids: Vec<String> = vec![];
+        let mut aggregate_note = String::new();
         let mut answer_markdown = String::new();
         let mut token_usage = serde_json::json!({});
         let mut cost_usd = 0.0_f64;
         let mut planner_trace: Vec<Value> = vec![];
+        if let Some(inferred) = &inferred_focus {
+            planner_trace.push(serde_json::json!({
+                "step": "infer_focus",
+                "objective": format!(
+                    "Scope retrieval to '{}', which the query names",
+                    inferred.document_name
+                ),
+                "decision": "continue",
+                "documentId": inferred.document_id.clone(),
+                "matchedTerms": inferred.matched_terms.clone(),
+            }));
+        }
+        if let Some(range) = search.dates.filter(|_| !search.dates_required) {
+            planner_trace.push(serde_json::json!({
+                "step": "infer_dates",
+                "objective": format!("Rank evidence dated {} first", describe_range(&range)),
+                "decision": "continue",
+                "from": range.from,
+                "to": range.to,
+            }));
+        }
+        let mut diagnostics: Vec<Value> = vec![];
+        let mut call_stats = CallStats::default();
+        let call_ctx = CallContext {
+            db,
+            run_id: &run_id,
+            keys: &options.api_keys,
+            settings: &options.generation,
+            pricing: options.pricing.unwrap_or_else(|| self.provider.pricing()),
+            planner_pricing: options
+                .planner_pricing
+                .unwrap_or_else(|| self.provider_for(CallKind::Planner).pricing()),
+            redactor: Redactor::new(options.privacy),
+        };
+        let mut current_phase = RunPhase::Planning;
 
-        loop {
+        'run: loop {
             let planner_input = PlannerInput {
                 query: query.to_string(),
+                query_class,
                 last_confidence: confidence,
                 explored_sections: explored_sections.clone(),
+                branch_outcomes: branch_outcomes.clone(),
                 has_evidence: !evidence_ids.is_empty(),
                 step_count,
                 backtrack_count,
             };
 
-            let plan = match self
-                .gemini
-                .generate_plan_step(api_key, &planner_prompt(&planner_input))
-                .await
-            {
+            let planner_prompt = render_planner_prompt(&prompt_set.planner.body, &planner_input);
+            let planner_response = self
+                .call_provider(
+                    &call_ctx,
+                    CallKind::Planner,
+                    &planner_prompt,
+                    &[],
+                    // An unusable plan falls back to the heuristic planner
+                    // the same way every time, so it is worth caching too.
+                    |_| true,
+                    &mut call_stats,
+                )
+                .await;
+            if let Ok((response, false)) = &planner_response {
+                call_stats
+                    .planner
+                    .add(&response.token_usage, &call_ctx.planner_pricing);
+            }
+            let plan = match planner_response.and_then(|reply| parse_planner_step(&reply.0)) {
                 Ok(model_step) => self
                     .planner
                     .next_steps_from_model(&planner_input, &model_step)
@@ -108,6 +452,7 @@ impl ReasoningExecutor {
             if matches!(plan.decision, PlannerDecision::Stop) {
                 break;
             }
+            feedback.exhausted = self.planner.exhausted_branches(&planner_input);
             if matches!(plan.decision, PlannerDecision::Backtrack) {
                 backtrack_count += 1;
             }
@@ -118,8 +463,17 @@ impl ReasoningExecutor {
                 }
                 step_count += 1;
 
-                reasoning::update_run_phase(db.pool(), &run_id, phase_for_step(&planned.step_type))
-                    .await?;
+                let step_phase = phase_for_step(&planned.step_type);
+                if step_phase != current_phase {
+                    reasoning::update_run_phase(db.writer(), &run_id, step_phase.as_str()).await?;
+                    on_event(RunEvent::Phase(ReasoningPhaseEvent {
+                        run_id: run_id.clone(),
+                        phase: step_phase.clone(),
+                        previous_phase: current_phase.clone(),
+                        step_index: step_count as i64,
+                    }));
+                    current_phase = step_phase;
+                }
 
                 planner_trace.push(serde_json::json!({
                     "step": planned.step_type.as_str(),
@@ -132,12 +486,14 @@ impl ReasoningExecutor {
                 }));
 
                 let step_started = Instant::now();
+                let summary: String;
                 let (thought, action, observation, node_refs, local_confidence) = match planned.step_type
                 {
                     StepType::ScanRoot => {
                         let nodes = scope_nodes(db, project_id, focus_document_id, 2).await?;
                         let observed = format!("Scanned {} top-level nodes", nodes.len());
                         let refs = nodes.iter().take(3).map(|node| node.id.clone()).collect::<Vec<_>>();
+                        summary = narration::scan_root(&nodes);
                         (
                             "Need to establish broad candidate scope from document root".to_string(),
                             "Scan_Root()".to_string(),
@@ -148,7 +504,8 @@ impl ReasoningExecutor {
                     }
                     StepType::SelectSections => {
                         let candidates =
-                            pick_candidates(db, project_id, focus_document_id, query, 6).await?;
+                            pick_candidates(db, project_id, focus_document_id, &search, 6, &feedback)
+                                .await?;
                         explored_sections = candidates
                             .iter()
                             .map(|node| node.title.clone())
@@ -156,6 +513,13 @@ impl ReasoningExecutor {
                             .take(6)
                             .collect();
                         let refs = candidates.iter().map(|node| node.id.clone()).collect::<Vec<_>>();
+                        summary = narration::select_sections(&candidates);
+                        for node in &candidates {
+                            if !searched_sections.contains(&node.id) {
+                                searched_sections.push(node.id.clone());
+                            }
+                        }
+                        selected_sections = candidates;
                         (
                             "Selecting sections that semantically align with query intent".to_string(),
                             "Select_Sections()".to_string(),
@@ -165,43 +529,79 @@ impl ReasoningExecutor {
                         )
                     }
                     StepType::DrillDown => {
-                        let candidates =
-                            pick_candidates(db, project_id, focus_document_id, query, 12).await?;
+                        let mut candidates =
+                            drill_down(db, &selected_sections, &search.query, 12, &feedback).await?;
+                        let mut opened = selected_sections.len();
+                        if candidates.is_empty() {
+                            opened = 0;
+                            candidates = pick_candidates(
+                                db,
+                                project_id,
+                                focus_document_id,
+                                &search,
+                                12,
+                                &feedback,
+                            )
+                            .await?;
+                        }
                         let refs = candidates.iter().map(|node| node.id.clone()).collect::<Vec<_>>();
+                        summary = narration::drill_down(&candidates, opened);
+                        drilled = candidates;
                         (
                             "Drilling down into subsection-level detail".to_string(),
                             "Drill_Down()".to_string(),
-                            format!("Focused on {} atomic nodes", refs.len()),
+                            format!(
+                                "Focused on {} atomic nodes under {} selected section(s)",
+                                refs.len(),
+                                selected_sections.len()
+                            ),
                             refs,
                             0.58,
                         )
                     }
                     StepType::ExtractEvidence => {
-                        let candidates =
-                            pick_candidates(db, project_id, focus_document_id, query, 8).await?;
+                        let mut candidates =
+                            pick_candidates(db, project_id, focus_document_id, &search, 8, &feedback)
+                                .await?;
+                        // Nodes reached by drilling down that mention the query but
+                        // that the keyword search ranked out.
+                        let terms = documents::normalized_terms(&search.query);
+                        let mut added = 0;
+                        for node in &drilled {
+                            if added >= MAX_DRILLED_EVIDENCE {
+                                break;
+                            }
+                            if query_hits(node, &terms) > 0
+                                && !candidates.iter().any(|candidate| candidate.id == node.id)
+                            {
+                                candidates.push(node.clone());
+                                added += 1;
+                            }
+                        }
                         evidence_ids = candidates.iter().map(|node| node.id.clone()).collect();
+                        summary = narration::extract_evidence(&candidates);
+                        figure_ids = candidates
+                            .iter()
+                            .filter(|node| matches!(node.node_type, NodeType::Figure))
+                            .map(|node| node.id.clone())
+                            .collect();
                         evidence_doc_map = candidates
                             .iter()
                             .map(|node| (node.id.clone(), node.document_id.clone()))
                             .collect();
-                        evidence_snippets = candidates
+                        evidence_text = candidates
+                            .iter()
+                            .map(|node| (node.id.clone(), node.text.clone()))
+                            .collect();
+                        evidence_branches = candidates
                             .iter()
                             .map(|node| {
-                                let mut text = node.text.clone();
-                                if text.len() > 500 {
-                                    text.truncate(500);
-                                }
-                                format!(
-                                    "[citation:{}] document={} path={} type={} title={} excerpt={} ",
-                                    node.id,
-                                    node.document_id,
-                                    node.ordinal_path,
-                                    node_type_name(&node.node_type),
-                                    node.title,
-                                    text.replace('\n', " ")
-                                )
+                                let branch = node.parent_id.as_ref().unwrap_or(&node.id);
+                                (node.id.clone(), branch.clone())
                             })
                             .collect();
+                        evidence_snippets =
+                            render_evidence(db, &candidates, options.expand_context).await?;
                         (
                             "Extracting evidence claims and table rows from selected nodes".to_string(),
                             "Extract_Evidence()".to_string(),
@@ -210,18 +610,96 @@ impl ReasoningExecutor {
                             0.72,
                         )
                     }
+                    StepType::Aggregate => {
+                        let recorded =
+                            documents::get_node_quantities(db.pool(), &evidence_ids).await?;
+                        let totals = aggregate::totals(&recorded);
+                        aggregate_note = aggregate::prompt_note(&totals);
+                        let refs = recorded
+                            .iter()
+                            .map(|(id, _)| id.clone())
+                            .collect::<Vec<_>>();
+                        summary = narration::aggregate(&totals);
+                        (
+                            "Adding up the amounts recorded in the evidence".to_string(),
+                            "Aggregate()".to_string(),
+                            format!(
+                                "Totalled {} unit(s) across {} evidence node(s)",
+                                totals.len(),
+                                refs.len()
+                            ),
+                            refs,
+                            0.72,
+                        )
+                    }
                     StepType::Synthesize => {
                         if evidence_snippets.is_empty() {
-                            return Err(AppError::NotFound(
-                                "no evidence nodes found for query".to_string(),
-                            ));
+                            // Nothing to answer from; the gate reports what is missing.
+                            break 'run;
+                        }
+                        let budget = self
+                            .provider
+                            .context_window()
+                            .saturating_sub(SYNTHESIS_OUTPUT_RESERVE_TOKENS);
+                        let kept = fit_ranked_items(budget, evidence_snippets.len(), |count| {
+                            render_synthesis_prompt(
+                                &prompt_set.synthesis.body,
+                                query,
+                                &evidence_snippets[..count],
+                            )
+                        });
+                        let trimmed = evidence_snippets.len() - kept;
+                        if trimmed > 0 {
+                            evidence_snippets.truncate(kept);
+                            for dropped in evidence_ids.drain(kept..) {
+                                evidence_doc_map.remove(&dropped);
+                                evidence_text.remove(&dropped);
+                            }
+                        }
+                        let mut prompt = render_synthesis_prompt(
+                            &prompt_set.synthesis.body,
+                            query,
+                            &evidence_snippets,
+                        );
+                        let images =
+                            if options.attach_figure_images && self.provider.supports_images() {
+                                let attachable = figure_ids
+                                    .iter()
+                                    .filter(|id| evidence_ids.contains(id))
+                                    .take(MAX_FIGURE_IMAGES)
+                                    .cloned()
+                                    .collect::<Vec<_>>();
+                                assets::get_node_images(db, &attachable).await?
+                            } else {
+                                vec![]
+                            };
+                        if !images.is_empty() {
+                            prompt.push_str(&figure_images_note(&images));
+                        }
+                        prompt.push_str(&aggregate_note);
+                        let images = images
+                            .into_iter()
+                            .map(|(_, image)| image)
+                            .collect::<Vec<_>>();
+                        let (output, recovery) = self
+                            .synthesize(&call_ctx, &prompt, &images, &mut call_stats)
+                            .await?;
+                        if !recovery.is_empty() {
+                            diagnostics.extend(recovery);
+                            reasoning::set_run_diagnostics(
+                                db.writer(),
+                                &run_id,
+                                Value::Array(diagnostics.clone()),
+                            )
+                            .await?;
                         }
-                        let prompt = synthesis_prompt(query, &evidence_snippets);
-                        let output = self.gemini.generate_answer(api_key, &prompt).await?;
                         answer_markdown = output.answer.answer_markdown.trim().to_string();
-                        token_usage = output.token_usage.clone();
-                        cost_usd = output.estimated_cost_usd;
+                        // A backtrack synthesizes again; every answer is billed.
+                        add_token_usage(&mut token_usage, &output.token_usage);
+                        cost_usd += output.estimated_cost_usd;
                         let normalized = normalize_citations(&output.answer.citations, &evidence_ids);
+                        cited_ids = normalized.clone();
+                        summary = narration::synthesize(cited_ids.len(), evidence_ids.len());
                         let references = if normalized.is_empty() {
                             evidence_ids.iter().take(4).cloned().collect::<Vec<_>>()
                         } else {
@@ -235,10 +713,26 @@ impl ReasoningExecutor {
                         (
                             "Synthesizing answer from grounded evidence using Gemini".to_string(),
                             "Synthesize()".to_string(),
-                            format!(
-                                "Generated answer draft with {} citation(s)",
-                                references.len()
-                            ),
+                            {
+                                let mut observed = format!(
+                                    "Generated answer draft with {} citation(s)",
+                                    references.len()
+                                );
+                                if trimmed > 0 {
+                                    observed.push_str(&format!(
+                                        " • trimmed {} lowest-ranked evidence snippet(s) to fit ~{} prompt tokens",
+                                        trimmed,
+                                        estimate_tokens(&prompt)
+                                    ));
+                                }
+                                if !images.is_empty() {
+                                    observed.push_str(&format!(
+                                        " • attached {} figure image(s)",
+                                        images.len()
+                                    ));
+                                }
+                                observed
+                            },
                             references.clone(),
                             output.answer.confidence,
                         )
@@ -250,6 +744,20 @@ impl ReasoningExecutor {
                         } else {
                             0.28
                         };
+                        record_branch_outcomes(
+                            &mut branch_outcomes,
+                            &evidence_ids,
+                            &evidence_branches,
+                            &cited_ids,
+                            estimated,
+                        );
+                        feedback = EvidenceFeedback::score(
+                            &evidence_ids,
+                            &cited_ids,
+                            &evidence_doc_map,
+                            &evidence_branches,
+                        );
+                        summary = narration::self_check(grounded, estimated, DONE_CONFIDENCE);
                         (
                             "Checking whether answer is grounded and sufficiently supported".to_string(),
                             "Self_Check()".to_string(),
@@ -266,8 +774,9 @@ impl ReasoningExecutor {
 
                 confidence = Some(local_confidence);
                 let latency_ms = step_started.elapsed().as_millis() as i64;
+                let resolved_refs = documents::resolve_node_refs(db.pool(), &node_refs).await?;
                 reasoning::add_step(
-                    db.pool(),
+                    db.writer(),
                     NewStep {
                         run_id: &run_id,
                         idx: step_count as i64,
@@ -275,27 +784,31 @@ impl ReasoningExecutor {
                         thought: &thought,
                         action: &action,
                         observation: &observation,
+                        summary: &summary,
                         node_refs: node_refs.clone(),
+                        resolved_refs: &resolved_refs,
                         confidence: local_confidence,
                         latency_ms,
                     },
                 )
                 .await?;
 
-                on_step(ReasoningStepEvent {
+                on_event(RunEvent::Step(ReasoningStepEvent {
                     run_id: run_id.clone(),
                     step_index: step_count as i64,
                     step_type: planned.step_type.as_str().to_string(),
                     thought,
                     action,
                     observation,
+                    summary,
                     node_refs: node_refs.clone(),
+                    resolved_refs,
                     latency_ms,
                     confidence: local_confidence,
-                });
+                }));
             }
 
-            let done = confidence.unwrap_or_default() >= 0.70
+            let done = confidence.unwrap_or_default() >= DONE_CONFIDENCE
                 || step_count >= max_steps
                 || backtrack_count >= 2;
             if done {
@@ -303,42 +816,70 @@ impl ReasoningExecutor {
             }
         }
 
+        manifest.documents = manifest_documents(db, evidence_doc_map.values()).await?;
+        reasoning::set_run_manifest(db.writer(), &run_id, &manifest).await?;
+
         let final_confidence = confidence.unwrap_or(0.3);
         let total_latency_ms = started.elapsed().as_millis() as i64;
-        let citations = dedupe_citations(evidence_ids.clone());
-        let relation_query = focus_document_id.is_none() && requires_project_scope(query);
-        let quality = evaluate_answer(
+        let mut citations = dedupe_citations(evidence_ids.clone());
+        let cited_text = cited_ids
+            .iter()
+            .filter_map(|id| Some((id.clone(), evidence_text.get(id)?.clone())))
+            .collect::<HashMap<_, _>>();
+        let mut quality = evaluate_answer(
             query,
             &answer_markdown,
             &citations,
             &evidence_ids,
             &evidence_doc_map,
+            &cited_text,
             relation_query,
+            &options.language,
         );
-        let grounded = quality.grounded && is_answer_grounded(&answer_markdown, &citations);
-        let min_quality_score = if relation_query {
-            MIN_RELATION_QUALITY_SCORE
-        } else {
-            MIN_QUALITY_SCORE
-        };
-        let quality_gate_passed = grounded && quality.overall >= min_quality_score;
+        quality.grounded = quality.grounded && is_answer_grounded(&answer_markdown, &citations);
+        quality.overall = weighted_overall(&quality, &quality_policy.weights);
+        let min_quality_score = quality_policy.min_overall;
+        let quality_gate_passed = quality.grounded && quality.overall >= min_quality_score;
 
-        if !quality_gate_passed {
-            return Err(AppError::QualityGateFailed(format!(
-                "Insufficient answer quality ({:.0}% < {:.0}%). No answer returned; refine the question or add clearer source evidence.",
-                quality.overall * 100.0,
-                min_quality_score * 100.0
-            )));
+        let insufficient_evidence = !quality_gate_passed;
+        if insufficient_evidence {
+            // Withhold the draft; say what the evidence does and does not cover.
+            let searched = documents::resolve_node_refs(db.pool(), &searched_sections).await?;
+            let evidence = evidence_ids
+                .iter()
+                .filter_map(|id| Some((id.clone(), evidence_text.get(id)?.clone())))
+                .collect::<Vec<_>>();
+            let gaps = EvidenceGaps::find(query, &evidence, &searched, &options.language);
+            let mut trace = gaps.to_trace();
+            trace["overall"] = quality.overall.into();
+            trace["minOverall"] = min_quality_score.into();
+            planner_trace.push(trace);
+            answer_markdown = gaps.to_markdown();
+            citations = gaps.citations();
         }
+        let grounded = quality_gate_passed;
 
         let final_confidence = if grounded {
             final_confidence.max(quality.overall)
         } else {
             final_confidence.min(0.45).min(quality.overall.max(0.25))
         };
+        cost_usd += call_stats.planner.cost_usd;
+        if let Some(usage) = token_usage.as_object_mut() {
+            usage.insert("providerCalls".to_string(), call_stats.provider_calls.into());
+            usage.insert("cacheHits".to_string(), call_stats.cache_hits.into());
+            usage.insert(
+                "planner".to_string(),
+                call_stats.planner.to_json(self.planner_model()),
+            );
+            usage.insert(
+                "synthesis".to_string(),
+                call_stats.synthesis.to_json(self.model()),
+            );
+        }
         let answer_id = run_id.clone();
         reasoning::complete_run(
-            db.pool(),
+            db.writer(),
             &run_id,
             total_latency_ms,
             token_usage.clone(),
@@ -351,6 +892,12 @@ impl ReasoningExecutor {
             serde_json::Value::Array(planner_trace),
         )
         .await?;
+        on_event(RunEvent::Phase(ReasoningPhaseEvent {
+            run_id: run_id.clone(),
+            phase: RunPhase::Completed,
+            previous_phase: current_phase,
+            step_index: step_count as i64,
+        }));
 
         Ok(ExecutionResult {
             run_id,
@@ -359,9 +906,863 @@ impl ReasoningExecutor {
             total_latency_ms,
             token_usage,
             cost_usd,
+            insufficient_evidence,
         })
     }
 
+    /// Projects what `query` would cost, running retrieval the way the
+    /// evidence step does but calling no provider. The expected figures
+    /// follow the usual model-led plan, one planner call each to search, drill
+    /// down, synthesize and check; evidence the drill-down adds is not counted.
+    pub async fn estimate_cost(
+        &self,
+        db: &Database,
+        project_id: &str,
+        scope: ScopeDecision,
+        query: &str,
+        options: &RunOptions,
+    ) -> AppResult<QueryCostEstimate> {
+        let focus_document_id = scope.focus_document_id.as_deref();
+        if let Some(document_id) = focus_document_id {
+            documents::ensure_unlocked(db.pool(), document_id).await?;
+        }
+        let prompt_set = match &options.prompt_set {
+            Some(prompt_set) => prompt_set.clone(),
+            None => prompts::load_prompt_set(db.pool()).await?,
+        };
+
+        let search = search_terms(db, project_id, query, options).await?;
+        let candidates = pick_candidates(
+            db,
+            project_id,
+            focus_document_id,
+            &search,
+            8,
+            &EvidenceFeedback::default(),
+        )
+        .await?;
+        let mut snippets = render_evidence(db, &candidates, options.expand_context).await?;
+        let budget = self
+            .provider
+            .context_window()
+            .saturating_sub(SYNTHESIS_OUTPUT_RESERVE_TOKENS);
+        let kept = fit_ranked_items(budget, snippets.len(), |count| {
+            render_synthesis_prompt(&prompt_set.synthesis.body, query, &snippets[..count])
+        });
+        let trimmed_evidence = snippets.len() - kept;
+        snippets.truncate(kept);
+        let synthesis_prompt_tokens = estimate_tokens(&render_synthesis_prompt(
+            &prompt_set.synthesis.body,
+            query,
+            &snippets,
+        ));
+
+        let relation_query =
+            focus_document_id.is_none() && requires_project_scope(query, &options.language);
+        let first_plan = PlannerInput {
+            query: query.to_string(),
+            query_class: classify_query(query, relation_query, &options.language),
+            last_confidence: None,
+            explored_sections: vec![],
+            branch_outcomes: vec![],
+            has_evidence: false,
+            step_count: 0,
+            backtrack_count: 0,
+        };
+        let later_plan = PlannerInput {
+            explored_sections: candidates
+                .iter()
+                .map(|node| node.title.clone())
+                .filter(|title| !title.is_empty())
+                .take(6)
+                .collect(),
+            has_evidence: !snippets.is_empty(),
+            step_count: 2,
+            ..first_plan.clone()
+        };
+        let first_plan_tokens = estimate_tokens(&render_planner_prompt(
+            &prompt_set.planner.body,
+            &first_plan,
+        ));
+        let later_plan_tokens = estimate_tokens(&render_planner_prompt(
+            &prompt_set.planner.body,
+            &later_plan,
+        ));
+
+        let max_steps = options.max_steps.unwrap_or(6).max(2);
+        let step_limit = max_steps.min(PlannerConfig::default().max_steps);
+        // Steps each planner call adds on the usual plan.
+        let mut planner_calls = 0;
+        let mut planned_steps = 0;
+        for steps in [2, 2, 1, 1] {
+            if planned_steps >= step_limit {
+                break;
+            }
+            planner_calls += 1;
+            planned_steps += steps;
+        }
+        let synthesis_calls = usize::from(!snippets.is_empty() && step_limit >= 5);
+        // At worst every step is planned alone, each synthesis after the
+        // first evidence step needs its JSON retry, and a run allowed more
+        // steps than the planner takes asks it once more before stopping.
+        let max_planner_calls = step_limit + usize::from(max_steps > step_limit);
+        let max_synthesis_calls = if snippets.is_empty() {
+            0
+        } else {
+            (step_limit - 1) * (1 + SYNTHESIS_JSON_RETRIES)
+        };
+
+        let max_output_tokens = options.generation.max_output_tokens.map(|max| max as usize);
+        let synthesis_output_tokens = max_output_tokens
+            .unwrap_or(usize::MAX)
+            .min(ESTIMATED_SYNTHESIS_OUTPUT_TOKENS);
+        let max_synthesis_output_tokens =
+            max_output_tokens.unwrap_or(SYNTHESIS_OUTPUT_RESERVE_TOKENS);
+        let planner_pricing = options
+            .planner_pricing
+            .unwrap_or_else(|| self.provider_for(CallKind::Planner).pricing());
+        let synthesis_pricing = options.pricing.unwrap_or_else(|| self.provider.pricing());
+
+        let planner_prompt_tokens =
+            first_plan_tokens + later_plan_tokens * planner_calls.saturating_sub(1);
+        let planner = ModelCostEstimate {
+            call_kind: CallKind::Planner.as_str().to_string(),
+            model: self.planner_model().to_string(),
+            pricing: planner_pricing,
+            calls: planner_calls,
+            max_calls: max_planner_calls,
+            prompt_tokens: planner_prompt_tokens,
+            output_tokens: planner_calls * ESTIMATED_PLANNER_OUTPUT_TOKENS,
+            cost_usd: token_cost(
+                &planner_pricing,
+                planner_prompt_tokens,
+                planner_calls * ESTIMATED_PLANNER_OUTPUT_TOKENS,
+            ),
+            max_cost_usd: token_cost(
+                &planner_pricing,
+                first_plan_tokens + later_plan_tokens * max_planner_calls.saturating_sub(1),
+                max_planner_calls * ESTIMATED_PLANNER_OUTPUT_TOKENS,
+            ),
+            avg_call_latency_ms: reasoning::average_call_latency(
+                db.pool(),
+                self.planner_model(),
+                CallKind::Planner,
+                LATENCY_SAMPLE_CALLS,
+            )
+            .await?,
+        };
+        let synthesis = ModelCostEstimate {
+            call_kind: CallKind::Synthesis.as_str().to_string(),
+            model: self.model().to_string(),
+            pricing: synthesis_pricing,
+            calls: synthesis_calls,
+            max_calls: max_synthesis_calls,
+            prompt_tokens: synthesis_calls * synthesis_prompt_tokens,
+            output_tokens: synthesis_calls * synthesis_output_tokens,
+            cost_usd: token_cost(
+                &synthesis_pricing,
+                synthesis_calls * synthesis_prompt_tokens,
+                synthesis_calls * synthesis_output_tokens,
+            ),
+            max_cost_usd: token_cost(
+                &synthesis_pricing,
+                max_synthesis_calls * synthesis_prompt_tokens,
+                max_synthesis_calls * max_synthesis_output_tokens,
+            ),
+            avg_call_latency_ms: reasoning::average_call_latency(
+                db.pool(),
+                self.model(),
+                CallKind::Synthesis,
+                LATENCY_SAMPLE_CALLS,
+            )
+            .await?,
+        };
+
+        let models = vec![planner, synthesis];
+        let latency = |calls: fn(&ModelCostEstimate) -> usize| {
+            models
+                .iter()
+                .try_fold(0_i64, |total, model| match calls(model) {
+                    0 => Some(total),
+                    count => Some(total + model.avg_call_latency_ms? * count as i64),
+                })
+        };
+        Ok(QueryCostEstimate {
+            scope,
+            evidence_count: snippets.len(),
+            evidence_tokens: snippets
+                .iter()
+                .map(String::as_str)
+                .map(estimate_tokens)
+                .sum(),
+            trimmed_evidence,
+            cost_usd: models.iter().map(|model| model.cost_usd).sum(),
+            max_cost_usd: models.iter().map(|model| model.max_cost_usd).sum(),
+            latency_ms: latency(|model| model.calls),
+            max_latency_ms: latency(|model| model.max_calls),
+            models,
+        })
+    }
+
+    /// Brings `document_id` into the finished run `run_id` without asking its
+    /// question again from scratch: retrieval runs over only that document,
+    /// and what it finds joins the evidence the run answered from before the
+    /// answer is synthesized again. The answer goes through the same quality
+    /// gate and is stored as a new revision, so the one it replaces can be
+    /// restored; the cost and latency add to the run's.
+    pub async fn add_document<F>(
+        &self,
+        db: &Database,
+        run_id: &str,
+        document_id: &str,
+        options: RunOptions,
+        mut on_event: F,
+    ) -> AppResult<ExecutionResult>
+    where
+        F: FnMut(RunEvent) + Send,
+    {
+        let stored = reasoning::get_run(db.pool(), run_id).await?;
+        let run = &stored.run;
+        if run.status != RunStatus::Completed {
+            return Err(AppError::InvalidInput(format!(
+                "run {run_id} has not finished, so no document can be added to it"
+            )));
+        }
+        let document = documents::get_document(db.pool(), document_id).await?;
+        if !documents::document_project_ids(db.pool(), document_id)
+            .await?
+            .contains(&run.project_id)
+        {
+            return Err(AppError::InvalidInput(format!(
+                "{} is not in the run's project",
+                document.name
+            )));
+        }
+        documents::ensure_unlocked(db.pool(), document_id).await?;
+
+        let started = Instant::now();
+        let query = run.query.as_str();
+        let prompt_set =
+            prompts::load_prompt_set_for_versions(db.pool(), &run.prompt_versions_json).await?;
+        let relation_query =
+            run.document_id.is_none() && requires_project_scope(query, &options.language);
+        let quality_policy = run
+            .manifest
+            .as_ref()
+            .and_then(|manifest| manifest.quality_policy)
+            .unwrap_or_else(|| {
+                options
+                    .quality
+                    .policy(classify_query(query, relation_query, &options.language))
+            });
+        let call_ctx = CallContext {
+            db,
+            run_id,
+            keys: &options.api_keys,
+            settings: &options.generation,
+            pricing: options.pricing.unwrap_or_else(|| self.provider.pricing()),
+            planner_pricing: options
+                .planner_pricing
+                .unwrap_or_else(|| self.provider_for(CallKind::Planner).pricing()),
+            redactor: Redactor::new(options.privacy),
+        };
+        let mut call_stats = CallStats::default();
+        let mut step_index = stored.steps.iter().map(|step| step.idx).max().unwrap_or(0);
+
+        // What the run answered from, as far as it still exists, and what the
+        // added document has on the question.
+        let mut prior: Vec<DocNodeSummary> = vec![];
+        for node_id in stored.answer.iter().flat_map(|answer| &answer.citations) {
+            match documents::get_node(db.pool(), node_id).await {
+                Ok(node) => prior.push(DocNodeSummary {
+                    id: node.id,
+                    document_id: node.document_id,
+                    parent_id: node.parent_id,
+                    node_type: node.node_type,
+                    title: node.title,
+                    text: node.text,
+                    ordinal_path: node.ordinal_path,
+                    page_start: node.page_start,
+                    page_end: node.page_end,
+                }),
+                Err(AppError::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let search = search_terms(db, &run.project_id, query, &options).await?;
+        let found = pick_candidates(
+            db,
+            &run.project_id,
+            Some(document_id),
+            &search,
+            8,
+            &EvidenceFeedback::default(),
+        )
+        .await?;
+        let found_ids = found.iter().map(|node| node.id.clone()).collect::<Vec<_>>();
+        let new_evidence = found
+            .into_iter()
+            .filter(|node| !prior.iter().any(|known| known.id == node.id))
+            .collect::<Vec<_>>();
+        let added = new_evidence.len();
+        // Synthesis keeps a prefix of the evidence when the prompt has to fit
+        // the context window, so the added document's nodes take turns with
+        // the run's, leading, instead of queueing behind them to be cut first.
+        let evidence = alternate(&new_evidence, &prior);
+        if evidence.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "{} has nothing on the question, and the run had no evidence to add it to",
+                document.name
+            )));
+        }
+
+        let step_started = Instant::now();
+        step_index += 1;
+        let mut evidence_snippets = render_evidence(db, &evidence, options.expand_context).await?;
+        let mut evidence_ids = evidence
+            .iter()
+            .map(|node| node.id.clone())
+            .collect::<Vec<_>>();
+        let mut evidence_doc_map = evidence
+            .iter()
+            .map(|node| (node.id.clone(), node.document_id.clone()))
+            .collect::<HashMap<_, _>>();
+        let mut evidence_text = evidence
+            .iter()
+            .map(|node| (node.id.clone(), node.text.clone()))
+            .collect::<HashMap<_, _>>();
+        let observation = format!(
+            "Added {added} evidence snippet(s) from {} to the {} already used",
+            document.name,
+            evidence.len() - added
+        );
+        record_step(
+            db,
+            run_id,
+            step_index,
+            StepType::ExtractEvidence,
+            "Reading a document added to the question's evidence",
+            &format!("Extract_Evidence({})", document.name),
+            &observation,
+            &narration::extract_evidence(&new_evidence),
+            found_ids,
+            0.72,
+            step_started,
+            &mut on_event,
+        )
+        .await?;
+
+        let step_started = Instant::now();
+        step_index += 1;
+        let budget = self
+            .provider
+            .context_window()
+            .saturating_sub(SYNTHESIS_OUTPUT_RESERVE_TOKENS);
+        let kept = fit_ranked_items(budget, evidence_snippets.len(), |count| {
+            render_synthesis_prompt(
+                &prompt_set.synthesis.body,
+                query,
+                &evidence_snippets[..count],
+            )
+        });
+        evidence_snippets.truncate(kept);
+        for dropped in evidence_ids.drain(kept..) {
+            evidence_doc_map.remove(&dropped);
+            evidence_text.remove(&dropped);
+        }
+        let mut prompt =
+            render_synthesis_prompt(&prompt_set.synthesis.body, query, &evidence_snippets);
+        let images = if options.attach_figure_images && self.provider.supports_images() {
+            let attachable = evidence
+                .iter()
+                .filter(|node| matches!(node.node_type, NodeType::Figure))
+                .filter(|node| evidence_ids.contains(&node.id))
+                .take(MAX_FIGURE_IMAGES)
+                .map(|node| node.id.clone())
+                .collect::<Vec<_>>();
+            assets::get_node_images(db, &attachable).await?
+        } else {
+            vec![]
+        };
+        if !images.is_empty() {
+            prompt.push_str(&figure_images_note(&images));
+        }
+        let images = images
+            .into_iter()
+            .map(|(_, image)| image)
+            .collect::<Vec<_>>();
+        let (output, recovery) = self
+            .synthesize(&call_ctx, &prompt, &images, &mut call_stats)
+            .await?;
+        if !recovery.is_empty() {
+            let mut diagnostics = run.diagnostics_json.as_array().cloned().unwrap_or_default();
+            diagnostics.extend(recovery);
+            reasoning::set_run_diagnostics(db.writer(), run_id, Value::Array(diagnostics)).await?;
+        }
+        let mut answer_markdown = output.answer.answer_markdown.trim().to_string();
+        if answer_markdown.is_empty() {
+            answer_markdown =
+                "I could not produce a grounded answer from the available evidence.".to_string();
+        }
+        let cited_ids = normalize_citations(&output.answer.citations, &evidence_ids);
+        record_step(
+            db,
+            run_id,
+            step_index,
+            StepType::Synthesize,
+            "Synthesizing the answer again with the added evidence",
+            "Synthesize()",
+            &format!(
+                "Generated answer draft with {} citation(s)",
+                cited_ids.len()
+            ),
+            &narration::synthesize(cited_ids.len(), evidence_ids.len()),
+            if cited_ids.is_empty() {
+                evidence_ids.iter().take(4).cloned().collect()
+            } else {
+                cited_ids.clone()
+            },
+            output.answer.confidence,
+            step_started,
+            &mut on_event,
+        )
+        .await?;
+
+        let mut citations = dedupe_citations(evidence_ids.clone());
+        let cited_text = cited_ids
+            .iter()
+            .filter_map(|id| Some((id.clone(), evidence_text.get(id)?.clone())))
+            .collect::<HashMap<_, _>>();
+        let mut quality = evaluate_answer(
+            query,
+            &answer_markdown,
+            &citations,
+            &evidence_ids,
+            &evidence_doc_map,
+            &cited_text,
+            relation_query,
+            &options.language,
+        );
+        quality.grounded = quality.grounded && is_answer_grounded(&answer_markdown, &citations);
+        quality.overall = weighted_overall(&quality, &quality_policy.weights);
+        let grounded = quality.grounded && quality.overall >= quality_policy.min_overall;
+
+        let mut planner_trace = run
+            .planner_trace_json
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        planner_trace.push(serde_json::json!({
+            "step": "add_document",
+            "objective": format!("Read '{}' for the question", document.name),
+            "decision": "continue",
+            "documentId": document.id,
+            "evidenceAdded": added,
+        }));
+        if !grounded {
+            let searched = documents::resolve_node_refs(db.pool(), &evidence_ids).await?;
+            let evidence = evidence_ids
+                .iter()
+                .filter_map(|id| Some((id.clone(), evidence_text.get(id)?.clone())))
+                .collect::<Vec<_>>();
+            let gaps = EvidenceGaps::find(query, &evidence, &searched, &options.language);
+            let mut trace = gaps.to_trace();
+            trace["overall"] = quality.overall.into();
+            trace["minOverall"] = quality_policy.min_overall.into();
+            planner_trace.push(trace);
+            answer_markdown = gaps.to_markdown();
+            citations = gaps.citations();
+        }
+        let final_confidence = if grounded {
+            output.answer.confidence.max(quality.overall)
+        } else {
+            output
+                .answer
+                .confidence
+                .min(0.45)
+                .min(quality.overall.max(0.25))
+        };
+
+        if let Some(mut manifest) = run.manifest.clone() {
+            manifest.documents = manifest_documents(db, evidence_doc_map.values()).await?;
+            reasoning::set_run_manifest(db.writer(), run_id, &manifest).await?;
+        }
+        let mut token_usage = run.token_usage_json.clone();
+        if !token_usage.is_object() {
+            token_usage = serde_json::json!({});
+        }
+        let mut additions = token_usage["addedDocuments"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let mut addition = call_stats.synthesis.to_json(self.model());
+        addition["documentId"] = document.id.clone().into();
+        addition["providerCalls"] = call_stats.provider_calls.into();
+        addition["cacheHits"] = call_stats.cache_hits.into();
+        additions.push(addition);
+        token_usage["addedDocuments"] = Value::Array(additions);
+        let cost_usd = run.cost_usd + output.estimated_cost_usd;
+        let total_latency_ms =
+            run.total_latency_ms.unwrap_or(0) + started.elapsed().as_millis() as i64;
+        reasoning::complete_run(
+            db.writer(),
+            run_id,
+            total_latency_ms,
+            token_usage.clone(),
+            cost_usd,
+            &answer_markdown,
+            citations,
+            final_confidence,
+            grounded,
+            serde_json::to_value(quality).unwrap_or_else(|_| serde_json::json!({})),
+            Value::Array(planner_trace),
+        )
+        .await?;
+
+        Ok(ExecutionResult {
+            run_id: run_id.to_string(),
+            answer_id: run_id.to_string(),
+            final_confidence,
+            total_latency_ms,
+            token_usage,
+            cost_usd,
+            insufficient_evidence: !grounded,
+        })
+    }
+
+    /// Runs the synthesis call, re-prompting once with a stricter JSON instruction
+    /// and finally salvaging the raw text when the model keeps ignoring the format.
+    /// The returned diagnostics describe every recovery attempt that was needed.
+    async fn synthesize(
+        &self,
+        ctx: &CallContext<'_>,
+        prompt: &str,
+        images: &[ImagePart],
+        stats: &mut CallStats,
+    ) -> AppResult<(GeminiOutput, Vec<Value>)> {
+        let mut diagnostics = vec![];
+        let mut last_response = None;
+        let mut attempt_prompt = prompt.to_string();
+        // Every attempt is billed, not just the one whose output is used.
+        // Cached responses were billed by the run that first received them.
+        let mut token_usage = serde_json::json!({});
+
+        for attempt in 1..=(SYNTHESIS_JSON_RETRIES + 1) {
+            let parsed = match self
+                .call_provider(
+                    ctx,
+                    CallKind::Synthesis,
+                    &attempt_prompt,
+                    images,
+                    |response| parse_answer_output(response, &ctx.pricing).is_ok(),
+                    stats,
+                )
+                .await
+            {
+                Ok((response, from_cache)) => {
+                    if !from_cache {
+                        add_token_usage(&mut token_usage, &response.token_usage);
+                        stats.synthesis.add(&response.token_usage, &ctx.pricing);
+                    }
+                    let parsed = parse_answer_output(&response, &ctx.pricing);
+                    last_response = Some(response);
+                    parsed
+                }
+                Err(err) => Err(err),
+            };
+            match parsed {
+                Ok(mut output) => {
+                    output.estimated_cost_usd = estimate_cost_usd(&token_usage, &ctx.pricing);
+                    output.token_usage = token_usage;
+                    if attempt > 1 {
+                        diagnostics.push(serde_json::json!({
+                            "stage": "synthesis",
+                            "recovery": "strict_json_retry",
+                            "attempt": attempt,
+                        }));
+                    }
+                    return Ok((output, diagnostics));
+                }
+                Err(AppError::ProviderInvalidResponse(message)) => {
+                    diagnostics.push(serde_json::json!({
+                        "stage": "synthesis",
+                        "error": "PROVIDER_INVALID_RESPONSE",
+                        "message": ctx.redactor.quoted(&message),
+                        "attempt": attempt,
+                    }));
+                    attempt_prompt = strict_json_prompt(prompt);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let response = last_response.ok_or_else(|| {
+            AppError::ProviderInvalidResponse("synthesis produced no usable output".to_string())
+        })?;
+        let mut output = parse_answer_from_raw_text(&response, &ctx.pricing)?;
+        output.estimated_cost_usd = estimate_cost_usd(&token_usage, &ctx.pricing);
+        output.token_usage = token_usage;
+        diagnostics.push(serde_json::json!({
+            "stage": "synthesis",
+            "recovery": "raw_text_fallback",
+            "attempt": SYNTHESIS_JSON_RETRIES + 1,
+        }));
+        Ok((output, diagnostics))
+    }
+
+    /// Calls the provider, or serves an unexpired cached response for the same
+    /// request, and records the raw response so the run can be replayed. Only
+    /// responses `usable` accepts are cached, so a malformed answer is asked
+    /// for again rather than replayed. The flag is set for cached responses.
+    async fn call_provider(
+        &self,
+        ctx: &CallContext<'_>,
+        kind: CallKind,
+        prompt: &str,
+        images: &[ImagePart],
+        usable: impl Fn(&ProviderResponse) -> bool,
+        stats: &mut CallStats,
+    ) -> AppResult<(ProviderResponse, bool)> {
+        let model = self.provider_for(kind).model();
+        let key = cache_key_with_images(model, kind, prompt, ctx.settings, images);
+        let call_started = Instant::now();
+        let cached = match self.cache {
+            Some(_) => provider_cache::get_cached_response(ctx.db.writer(), &key).await?,
+            None => None,
+        };
+        let from_cache = cached.is_some();
+        let response = match cached {
+            Some(response) => {
+                stats.cache_hits += 1;
+                response
+            }
+            None => {
+                stats.provider_calls += 1;
+                let answered = ctx
+                    .keys
+                    .complete(
+                        ctx.db.writer(),
+                        self.provider_for(kind).as_ref(),
+                        kind,
+                        prompt,
+                        ctx.settings,
+                        images,
+                    )
+                    .await;
+                let metric = match answered {
+                    Ok(_) => Metric::ProviderCall,
+                    Err(_) => Metric::ProviderFailure,
+                };
+                let _ =
+                    metrics::record(ctx.db.writer(), metric, model, Some(call_started.elapsed()))
+                        .await;
+                let response = answered?;
+                if let Some(config) = self.cache.filter(|_| usable(&response)) {
+                    provider_cache::put_cached_response(
+                        ctx.db.writer(),
+                        &key,
+                        kind,
+                        model,
+                        &response,
+                        config,
+                    )
+                    .await?;
+                }
+                response
+            }
+        };
+        reasoning::record_provider_response(
+            ctx.db.writer(),
+            ctx.run_id,
+            kind,
+            &request_hash(kind, prompt),
+            model,
+            &response,
+            CallTiming {
+                latency_ms: call_started.elapsed().as_millis() as i64,
+                cached: from_cache,
+            },
+        )
+        .await?;
+        Ok((response, from_cache))
+    }
+}
+
+/// Re-executes `source_run_id` against its recorded provider responses and the
+/// prompt template versions it used, storing the result as a new run.
+pub async fn replay_run<F>(
+    db: &Database,
+    source_run_id: &str,
+    run_id: String,
+    on_event: F,
+) -> AppResult<ExecutionResult>
+where
+    F: FnMut(RunEvent) + Send,
+{
+    let source = reasoning::get_run(db.pool(), source_run_id).await?;
+    let recorded = reasoning::list_provider_responses(db.pool(), source_run_id).await?;
+    let model_for = |kind: CallKind| {
+        recorded
+            .iter()
+            .find(|entry| entry.call_kind == kind.as_str())
+            .or_else(|| recorded.first())
+            .map(|entry| entry.model.clone())
+    };
+    let (Some(model), Some(planner_model)) = (
+        model_for(CallKind::Synthesis),
+        model_for(CallKind::Planner),
+    ) else {
+        return Err(AppError::InvalidInput(format!(
+            "run {source_run_id} has no recorded provider responses to replay"
+        )));
+    };
+    let responses = recorded
+        .into_iter()
+        .map(|entry| (entry.request_hash, entry.response))
+        .collect::<Vec<_>>();
+    let mut executor =
+        ReasoningExecutor::new(ReplayProvider::new(model.clone(), responses.clone()));
+    if planner_model != model {
+        executor = executor
+            .with_planner_provider(Arc::new(ReplayProvider::new(planner_model, responses)));
+    }
+    let prompt_set =
+        prompts::load_prompt_set_for_versions(db.pool(), &source.run.prompt_versions_json).await?;
+    let manifest = source.run.manifest.as_ref();
+    let language = match manifest.and_then(|manifest| manifest.language.clone()) {
+        Some(language) => language,
+        None => settings::get_setting(db.pool(), settings::LANGUAGE_RESOURCES_SETTING).await?,
+    };
+    let quality = match manifest.and_then(|manifest| manifest.quality.clone()) {
+        Some(quality) => quality,
+        None => settings::get_setting(db.pool(), settings::QUALITY_THRESHOLDS_SETTING).await?,
+    };
+    let privacy = match manifest.and_then(|manifest| manifest.privacy) {
+        Some(privacy) => privacy,
+        None => settings::get_setting(db.pool(), settings::PRIVACY_SETTING).await?,
+    };
+
+    executor
+        .run_with_options(
+            db,
+            &source.run.project_id,
+            source.run.document_id.as_deref(),
+            run_id,
+            &source.run.query,
+            RunOptions {
+                max_steps: source.run.max_steps.map(|value| value.max(1) as usize),
+                prompt_set: Some(prompt_set),
+                replay_of: Some(source_run_id.to_string()),
+                attach_figure_images: source.run.attach_figure_images,
+                generation: source
+                    .run
+                    .manifest
+                    .as_ref()
+                    .map(|manifest| manifest.generation.clone())
+                    .unwrap_or_default(),
+                expand_context: source
+                    .run
+                    .manifest
+                    .as_ref()
+                    .is_some_and(|manifest| manifest.expand_context),
+                infer_focus_document: source
+                    .run
+                    .manifest
+                    .as_ref()
+                    .is_some_and(|manifest| manifest.infer_focus_document),
+                language,
+                quality,
+                privacy,
+                retrieval: RetrievalSettings {
+                    fuzziness: source
+                        .run
+                        .manifest
+                        .as_ref()
+                        .map_or(0, |manifest| manifest.fuzziness),
+                    ranking: source
+                        .run
+                        .manifest
+                        .as_ref()
+                        .and_then(|manifest| manifest.ranking.clone())
+                        .unwrap_or_default(),
+                },
+                date_range: source
+                    .run
+                    .manifest
+                    .as_ref()
+                    .and_then(|manifest| manifest.date_range),
+                ..RunOptions::default()
+            },
+            on_event,
+        )
+        .await
+}
+
+/// Stores a step of `run_id` and reports it.
+#[allow(clippy::too_many_arguments)]
+async fn record_step<F>(
+    db: &Database,
+    run_id: &str,
+    idx: i64,
+    step_type: StepType,
+    thought: &str,
+    action: &str,
+    observation: &str,
+    summary: &str,
+    node_refs: Vec<String>,
+    confidence: f64,
+    started: Instant,
+    on_event: &mut F,
+) -> AppResult<()>
+where
+    F: FnMut(RunEvent) + Send,
+{
+    let latency_ms = started.elapsed().as_millis() as i64;
+    let resolved_refs = documents::resolve_node_refs(db.pool(), &node_refs).await?;
+    reasoning::add_step(
+        db.writer(),
+        NewStep {
+            run_id,
+            idx,
+            step_type: step_type.as_str(),
+            thought,
+            action,
+            observation,
+            summary,
+            node_refs: node_refs.clone(),
+            resolved_refs: &resolved_refs,
+            confidence,
+            latency_ms,
+        },
+    )
+    .await?;
+    on_event(RunEvent::Step(ReasoningStepEvent {
+        run_id: run_id.to_string(),
+        step_index: idx,
+        step_type: step_type.as_str().to_string(),
+        thought: thought.to_string(),
+        action: action.to_string(),
+        observation: observation.to_string(),
+        summary: summary.to_string(),
+        node_refs,
+        resolved_refs,
+        latency_ms,
+        confidence,
+    }));
+    Ok(())
+}
+
+/// Tells the model which cited evidence each attached image belongs to.
+fn figure_images_note(images: &[(String, ImagePart)]) -> String {
+    let citations = images
+        .iter()
+        .map(|(node_id, _)| format!("[citation:{node_id}]"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "\n\nAttached images, in order, are the figures cited as {citations}. \
+         Read values from the images rather than their captions where they differ."
+    )
 }
 
 fn dedupe_citations(citations: Vec<String>) -> Vec<String> {
@@ -384,23 +1785,106 @@ fn local_confidence_for_answer(answer: &str, citation_count: usize) -> f64 {
     (0.15 + citation_bonus + content_bonus).min(0.92)
 }
 
+/// What retrieval looks for: the query with its acronyms and likely typos
+/// expanded, the dates evidence is kept to or ranked by, and how hits are
+/// weighed.
+struct SearchTerms {
+    query: String,
+    dates: Option<DateRange>,
+    /// Whether nodes not dated within `dates` are left out rather than
+    /// ranked after those that are.
+    dates_required: bool,
+    ranking: RankingWeights,
+}
+
+async fn search_terms(
+    db: &Database,
+    project_id: &str,
+    query: &str,
+    options: &RunOptions,
+) -> AppResult<SearchTerms> {
+    let expanded = acronyms::expand_query(db.pool(), project_id, query).await?;
+    let expanded =
+        documents::expand_fuzzy_terms(db.pool(), &expanded, options.retrieval.fuzziness).await?;
+    Ok(SearchTerms {
+        query: expanded,
+        dates: options.date_range.or_else(|| dates::range_in(query)),
+        dates_required: options.date_range.is_some(),
+        ranking: options.retrieval.ranking.clone(),
+    })
+}
+
+/// "2023-01-01 to 2023-12-31", "from 2021-05-01" or "until 2019-12-31".
+fn describe_range(range: &DateRange) -> String {
+    match (range.from, range.to) {
+        (Some(from), Some(to)) => format!("{from} to {to}"),
+        (Some(from), None) => format!("from {from}"),
+        (None, Some(to)) => format!("until {to}"),
+        (None, None) => "at any time".to_string(),
+    }
+}
+
+/// Ranks nodes for `search`, re-ordered by what the last checked answer cited;
+/// see [`EvidenceFeedback::rerank`].
 async fn pick_candidates(
     db: &Database,
     project_id: &str,
     focus_document_id: Option<&str>,
-    query: &str,
+    search: &SearchTerms,
     limit: usize,
+    feedback: &EvidenceFeedback,
 ) -> AppResult<Vec<crate::core::types::DocNodeSummary>> {
-    let mut ranked = documents::search_project_nodes(
+    let keyword_hits = documents::score_project_nodes(
         db.pool(),
         project_id,
         focus_document_id,
-        query,
+        &search.query,
+        search.ranking.title,
+        search.ranking.text,
         limit.saturating_mul(4).max(12),
     )
     .await?;
 
-    if ranked.is_empty() {
+    // One entry per date range or quantity filter a node matches.
+    let mut metadata_hits = Vec::<DocNodeSummary>::new();
+    let mut dated_ids = None;
+    if let Some(range) = &search.dates {
+        let dated = documents::search_date_nodes(
+            db.pool(),
+            project_id,
+            focus_document_id,
+            range,
+            limit.saturating_mul(4).max(12),
+        )
+        .await?;
+        if search.dates_required {
+            dated_ids = Some(
+                dated
+                    .iter()
+                    .map(|node| node.id.clone())
+                    .collect::<HashSet<_>>(),
+            );
+        }
+        metadata_hits.extend(dated);
+    }
+    for filter in quantities::filters(&search.query) {
+        let hits = documents::search_quantity_nodes(
+            db.pool(),
+            project_id,
+            focus_document_id,
+            &filter,
+            limit.saturating_mul(4).max(12),
+        )
+        .await?;
+        metadata_hits.extend(hits);
+    }
+
+    let mut ranked = ranking::rank(keyword_hits, metadata_hits, &search.query, &search.ranking);
+    if let Some(dated_ids) = &dated_ids {
+        ranked.retain(|node| dated_ids.contains(&node.id));
+    }
+
+    if ranked.is_empty() && !search.dates_required {
         ranked = scope_nodes(db, project_id, focus_document_id, 2).await?;
     }
 
@@ -408,6 +1892,21 @@ async fn pick_candidates(
         return Ok(vec![]);
     }
 
+    // Siblings and children of cited nodes may not match the query's terms;
+    // bring a few in so the re-ranking can favour them.
+    for useful in &feedback.useful {
+        for parent in [useful.branch_id.as_str(), useful.node_id.as_str()] {
+            let neighbours =
+                documents::get_tree(db.pool(), &useful.document_id, Some(parent), 1).await?;
+            for node in neighbours.into_iter().take(NEIGHBOURS_PER_USEFUL_NODE) {
+                if !ranked.iter().any(|ranked| ranked.id == node.id) {
+                    ranked.push(node);
+                }
+            }
+        }
+    }
+    let ranked = feedback.rerank(ranked);
+
     let mut selected = Vec::new();
     let mut per_document = HashMap::<String, usize>::new();
     let max_per_document = if focus_document_id.is_some() {
@@ -435,6 +1934,75 @@ async fn pick_candidates(
     Ok(selected)
 }
 
+/// Adds the counts in `usage` to `total`, field by field. Fields that are not
+/// whole numbers keep the latest value.
+fn add_token_usage(total: &mut Value, usage: &Value) {
+    let (Some(total), Some(usage)) = (total.as_object_mut(), usage.as_object()) else {
+        return;
+    };
+    for (field, value) in usage {
+        let sum = match (total.get(field).and_then(Value::as_i64), value.as_i64()) {
+            (Some(before), Some(count)) => Value::from(before + count),
+            _ => value.clone(),
+        };
+        total.insert(field.clone(), sum);
+    }
+}
+
+/// The synthesis prompt's evidence lines for `candidates`, with their
+/// surrounding text when `expand_context` is set.
+async fn render_evidence(
+    db: &Database,
+    candidates: &[DocNodeSummary],
+    expand_context: bool,
+) -> AppResult<Vec<String>> {
+    let mut snippets = candidates
+        .iter()
+        .map(|node| {
+            let mut text = node.text.clone();
+            if text.len() > 500 {
+                text.truncate(500);
+            }
+            format!(
+                "[citation:{}] document={} path={} type={} title={} excerpt={} ",
+                node.id,
+                node.document_id,
+                node.ordinal_path,
+                node.node_type.as_str(),
+                node.title,
+                text.replace('\n', " ")
+            )
+        })
+        .collect::<Vec<_>>();
+    if expand_context {
+        for (snippet, node) in snippets.iter_mut().zip(candidates) {
+            let prose = matches!(node.node_type, NodeType::Paragraph | NodeType::Claim);
+            if !prose {
+                continue;
+            }
+            if let Some(context) = evidence_context(db, node, EVIDENCE_CONTEXT_CHARS).await? {
+                snippet.push_str(&format!("context={context} "));
+            }
+        }
+    }
+    Ok(snippets)
+}
+
+/// `first` and `second` merged one from each in turn, starting with `first`;
+/// what is left of the longer one follows.
+fn alternate(first: &[DocNodeSummary], second: &[DocNodeSummary]) -> Vec<DocNodeSummary> {
+    let mut merged = Vec::with_capacity(first.len() + second.len());
+    for idx in 0..first.len().max(second.len()) {
+        merged.extend(first.get(idx).cloned());
+        merged.extend(second.get(idx).cloned());
+    }
+    merged
+}
+
+fn token_cost(pricing: &ModelPricing, prompt_tokens: usize, output_tokens: usize) -> f64 {
+    prompt_tokens as f64 * pricing.input_per_token + output_tokens as f64 * pricing.output_per_token
+}
+
 async fn scope_nodes(
     db: &Database,
     project_id: &str,
@@ -447,6 +2015,67 @@ async fn scope_nodes(
     documents::get_project_tree(db.pool(), project_id, depth).await
 }
 
+/// The evidence documents as they are now, in id order. A document deleted
+/// mid-run is left out.
+async fn manifest_documents(
+    db: &Database,
+    document_ids: impl Iterator<Item = &String>,
+) -> AppResult<Vec<RunManifestDocument>> {
+    let ids = document_ids.collect::<std::collections::BTreeSet<_>>();
+    let mut manifest = Vec::with_capacity(ids.len());
+    for id in ids {
+        match documents::get_document(db.pool(), id).await {
+            Ok(document) => manifest.push(RunManifestDocument {
+                id: document.id,
+                name: document.name,
+                checksum: document.checksum,
+            }),
+            Err(AppError::NotFound(_)) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(manifest)
+}
+
+/// Replaces the outcomes of the branches the evidence came from with how the
+/// latest checked answer used them.
+fn record_branch_outcomes(
+    outcomes: &mut Vec<BranchOutcome>,
+    evidence_ids: &[String],
+    evidence_branches: &HashMap<String, String>,
+    cited_ids: &[String],
+    confidence: f64,
+) {
+    let mut current: Vec<BranchOutcome> = vec![];
+    for id in evidence_ids {
+        let branch_id = evidence_branches.get(id).unwrap_or(id);
+        if !current
+            .iter()
+            .any(|outcome| &outcome.branch_id == branch_id)
+        {
+            current.push(BranchOutcome {
+                branch_id: branch_id.clone(),
+                node_ids: vec![],
+                cited: 0,
+                confidence,
+            });
+        }
+        if let Some(outcome) = current
+            .iter_mut()
+            .find(|outcome| &outcome.branch_id == branch_id)
+        {
+            outcome.node_ids.push(id.clone());
+            outcome.cited += usize::from(cited_ids.contains(id));
+        }
+    }
+    outcomes.retain(|outcome| {
+        !current
+            .iter()
+            .any(|branch| branch.branch_id == outcome.branch_id)
+    });
+    outcomes.extend(current);
+}
+
 fn is_answer_grounded(answer_markdown: &str, citations: &[String]) -> bool {
     if answer_markdown.trim().is_empty() {
         return false;
@@ -467,28 +2096,14 @@ fn normalize_citations(raw: &[String], evidence_ids: &[String]) -> Vec<String> {
         .collect::<Vec<_>>()
 }
 
-fn node_type_name(node_type: &crate::core::types::NodeType) -> &'static str {
-    match node_type {
-        crate::core::types::NodeType::Document => "document",
-        crate::core::types::NodeType::Section => "section",
-        crate::core::types::NodeType::Subsection => "subsection",
-        crate::core::types::NodeType::Paragraph => "paragraph",
-        crate::core::types::NodeType::Claim => "claim",
-        crate::core::types::NodeType::Table => "table",
-        crate::core::types::NodeType::Figure => "figure",
-        crate::core::types::NodeType::Equation => "equation",
-        crate::core::types::NodeType::Caption => "caption",
-        crate::core::types::NodeType::Reference => "reference",
-        crate::core::types::NodeType::Unknown => "unknown",
-    }
-}
-
-fn phase_for_step(step_type: &StepType) -> &'static str {
+fn phase_for_step(step_type: &StepType) -> RunPhase {
     match step_type {
-        StepType::ScanRoot | StepType::SelectSections | StepType::DrillDown | StepType::ExtractEvidence => {
-            "retrieval"
-        }
-        StepType::Synthesize => "synthesis",
-        StepType::SelfCheck => "validation",
+        StepType::ScanRoot
+        | StepType::SelectSections
+        | StepType::DrillDown
+        | StepType::ExtractEvidence
+        | StepType::Aggregate => RunPhase::Retrieval,
+        StepType::Synthesize => RunPhase::Synthesis,
+        StepType::SelfCheck => RunPhase::Validation,
     }
 }
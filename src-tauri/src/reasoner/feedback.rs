@@ -0,0 +1,115 @@
+//! Evidence usefulness feedback for retrieval. Once an answer has been
+//! checked, the nodes it cited tell the next retrieval where to look: their
+//! siblings and children move up the ranking, documents whose evidence went
+//! uncited move down, and exhausted branches are left out, so a backtrack
+//! does not re-run nearly the same retrieval.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::core::types::DocNodeSummary;
+
+/// A node the last checked answer cited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsefulNode {
+    pub node_id: String,
+    pub document_id: String,
+    /// The section the node sits under; a top-level node is its own branch.
+    pub branch_id: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvidenceFeedback {
+    pub useful: Vec<UsefulNode>,
+    /// Documents that supplied evidence none of which was cited.
+    pub idle_documents: HashSet<String>,
+    /// Branch and node ids to leave out, see `Planner::exhausted_branches`.
+    pub exhausted: HashSet<String>,
+}
+
+/// Where a candidate lands after feedback; lower ranks first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Tier {
+    NearUseful,
+    Neutral,
+    IdleDocument,
+}
+
+impl EvidenceFeedback {
+    /// Scores the evidence of the last checked answer by whether it was
+    /// cited. `evidence_docs` and `evidence_branches` map each evidence node
+    /// to its document and branch.
+    pub fn score(
+        evidence_ids: &[String],
+        cited_ids: &[String],
+        evidence_docs: &HashMap<String, String>,
+        evidence_branches: &HashMap<String, String>,
+    ) -> Self {
+        let mut useful = vec![];
+        let mut supplied = HashSet::new();
+        let mut contributed = HashSet::new();
+        for id in evidence_ids {
+            let Some(document_id) = evidence_docs.get(id) else {
+                continue;
+            };
+            supplied.insert(document_id.clone());
+            if cited_ids.contains(id) {
+                contributed.insert(document_id.clone());
+                useful.push(UsefulNode {
+                    node_id: id.clone(),
+                    document_id: document_id.clone(),
+                    branch_id: evidence_branches.get(id).unwrap_or(id).clone(),
+                });
+            }
+        }
+        Self {
+            useful,
+            idle_documents: supplied.difference(&contributed).cloned().collect(),
+            exhausted: HashSet::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.useful.is_empty() && self.idle_documents.is_empty() && self.exhausted.is_empty()
+    }
+
+    fn is_exhausted(&self, node: &DocNodeSummary) -> bool {
+        self.exhausted.contains(&node.id)
+            || node
+                .parent_id
+                .as_ref()
+                .is_some_and(|parent| self.exhausted.contains(parent))
+    }
+
+    fn tier(&self, node: &DocNodeSummary) -> Tier {
+        let near_useful = self.useful.iter().any(|useful| {
+            node.id == useful.node_id
+                || node.parent_id.as_deref() == Some(useful.node_id.as_str())
+                || (node.document_id == useful.document_id
+                    && node.parent_id.as_deref() == Some(useful.branch_id.as_str()))
+        });
+        if near_useful {
+            Tier::NearUseful
+        } else if self.idle_documents.contains(&node.document_id) {
+            Tier::IdleDocument
+        } else {
+            Tier::Neutral
+        }
+    }
+
+    /// Re-orders `ranked`, best first: nodes cited last time and their
+    /// siblings and children, then the rest, then nodes from idle documents.
+    /// Relevance order holds within each group. Exhausted nodes are dropped
+    /// unless nothing else is left.
+    pub fn rerank(&self, ranked: Vec<DocNodeSummary>) -> Vec<DocNodeSummary> {
+        if self.is_empty() {
+            return ranked;
+        }
+        let (exhausted, mut fresh): (Vec<_>, Vec<_>) =
+            ranked.into_iter().partition(|node| self.is_exhausted(node));
+        if fresh.is_empty() {
+            fresh = exhausted;
+        }
+        fresh.sort_by_key(|node| self.tier(node));
+        fresh
+    }
+}
@@ -0,0 +1,147 @@
+//! The answer a run gives when its evidence falls short. Rather than an
+//! error, the user gets the parts of the question the sources support, the
+//! parts they do not, and the sections that were searched, stored like any
+//! other answer but with `grounded = false`.
+
+use crate::{
+    core::types::{LanguageResources, NodeRef},
+    reasoner::evaluator::{same_stem, stem},
+};
+
+/// A content word of the query and the evidence node that mentions it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryAspect {
+    pub term: String,
+    pub node_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvidenceGaps {
+    pub aspects: Vec<QueryAspect>,
+    /// "Title (document)" of each section the run searched.
+    pub searched: Vec<String>,
+}
+
+impl EvidenceGaps {
+    /// Matches each content word of `query` against the text of the
+    /// evidence, given as `(node id, text)` in rank order.
+    pub fn find(
+        query: &str,
+        evidence: &[(String, String)],
+        searched: &[NodeRef],
+        language: &LanguageResources,
+    ) -> Self {
+        let evidence_stems = evidence
+            .iter()
+            .map(|(node_id, text)| {
+                (
+                    node_id,
+                    words(text).map(|word| stem(&word)).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut aspects: Vec<QueryAspect> = vec![];
+        for term in words(query).filter(|word| !language.stopwords.contains(word)) {
+            if aspects.iter().any(|aspect| aspect.term == term) {
+                continue;
+            }
+            let term_stem = stem(&term);
+            let node_id = evidence_stems
+                .iter()
+                .find(|(_, stems)| stems.iter().any(|word| same_stem(&term_stem, word)))
+                .map(|(node_id, _)| node_id.to_string());
+            aspects.push(QueryAspect { term, node_id });
+        }
+        let mut labels: Vec<String> = vec![];
+        for section in searched {
+            let title = section.title.trim();
+            let label = match (title.is_empty(), section.document_name.is_empty()) {
+                (false, false) => format!("{title} ({})", section.document_name),
+                (false, true) => title.to_string(),
+                (true, false) => section.document_name.clone(),
+                (true, true) => continue,
+            };
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        Self {
+            aspects,
+            searched: labels,
+        }
+    }
+
+    pub fn missing(&self) -> Vec<&str> {
+        self.aspects
+            .iter()
+            .filter(|aspect| aspect.node_id.is_none())
+            .map(|aspect| aspect.term.as_str())
+            .collect()
+    }
+
+    /// Evidence nodes backing the supported aspects, first mention first.
+    pub fn citations(&self) -> Vec<String> {
+        let mut citations: Vec<String> = vec![];
+        for node_id in self
+            .aspects
+            .iter()
+            .filter_map(|aspect| aspect.node_id.as_ref())
+        {
+            if !citations.contains(node_id) {
+                citations.push(node_id.clone());
+            }
+        }
+        citations
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from(
+            "## Not enough evidence\n\nThe sources searched do not answer this question in full.\n",
+        );
+        let missing = self.missing();
+        if !missing.is_empty() {
+            markdown.push_str("\n### No support found for\n");
+            for term in missing {
+                markdown.push_str(&format!("- {term}\n"));
+            }
+        }
+        let supported = self
+            .aspects
+            .iter()
+            .filter_map(|aspect| Some((&aspect.term, aspect.node_id.as_ref()?)))
+            .collect::<Vec<_>>();
+        if !supported.is_empty() {
+            markdown.push_str("\n### Mentioned in the sources\n");
+            for (term, node_id) in supported {
+                markdown.push_str(&format!("- {term} [citation:{node_id}]\n"));
+            }
+        }
+        markdown.push_str("\n### Sections searched\n");
+        if self.searched.is_empty() {
+            markdown.push_str("- No section matched the question.\n");
+        }
+        for label in &self.searched {
+            markdown.push_str(&format!("- {label}\n"));
+        }
+        markdown
+    }
+
+    /// The planner trace entry recording the gaps.
+    pub fn to_trace(&self) -> serde_json::Value {
+        serde_json::json!({
+            "step": "insufficient_evidence",
+            "objective": "Report which parts of the question the evidence does not cover",
+            "decision": "stop",
+            "missing": self.missing(),
+            "citations": self.citations(),
+            "searched": self.searched,
+        })
+    }
+}
+
+/// Lowercase words of three or more characters.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|value: char| !value.is_alphanumeric())
+        .filter(|value| value.chars().count() > 2)
+        .map(str::to_lowercase)
+}
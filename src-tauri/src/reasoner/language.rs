@@ -0,0 +1,404 @@
+//! Per-language word lists for query-scope detection and answer scoring.
+//! The bundled defaults cover a few languages; the lists the app uses are
+//! stored in settings so users can adjust them or add their own language.
+
+use std::collections::HashSet;
+
+use crate::core::{
+    errors::{AppError, AppResult},
+    types::{LanguageResources, LanguageResourcesResponse},
+};
+
+/// Languages with bundled defaults, English first.
+pub const LANGUAGES: &[&str] = &["en", "de", "es", "fr"];
+
+struct Defaults {
+    relation_hints: &'static [&'static str],
+    multi_doc_hints: &'static [&'static str],
+    single_doc_hints: &'static [&'static str],
+    plural_pronouns: &'static [&'static str],
+    stopwords: &'static [&'static str],
+    synonyms: &'static [&'static [&'static str]],
+    summary_hints: &'static [&'static str],
+    extraction_hints: &'static [&'static str],
+    aggregation_hints: &'static [&'static str],
+}
+
+const ENGLISH: Defaults = Defaults {
+    relation_hints: &[
+        "related",
+        "relationship",
+        "relationships",
+        "compare",
+        "comparison",
+        "differences",
+        "similarities",
+        "across",
+        "between",
+        "connect",
+        "overlap",
+        "fit together",
+        "how they",
+    ],
+    multi_doc_hints: &[
+        "files",
+        "documents",
+        "docs",
+        "papers",
+        "slides",
+        "presentations",
+        "sources",
+        "these files",
+        "these documents",
+        "all files",
+        "all documents",
+    ],
+    single_doc_hints: &[
+        "this file",
+        "this document",
+        "this slide",
+        "slide ",
+        "page ",
+        "section ",
+    ],
+    plural_pronouns: &["they", "them"],
+    stopwords: &[
+        "the", "and", "for", "are", "how", "what", "with", "about", "that", "this", "these",
+        "from", "into", "their", "they",
+    ],
+    synonyms: &[
+        &["terminate", "end", "cancel", "stop"],
+        &["increase", "rise", "grow", "raise"],
+        &["decrease", "drop", "decline", "reduce", "fall"],
+        &["cost", "price", "expense", "fee"],
+        &["revenue", "income", "sales"],
+        &["employee", "staff", "worker", "personnel"],
+        &["customer", "client", "buyer"],
+        &["error", "fault", "failure", "defect"],
+        &["fast", "quick", "rapid"],
+        &["begin", "start", "commence"],
+    ],
+    summary_hints: &[
+        "summarize",
+        "summarise",
+        "summary",
+        "overview",
+        "main points",
+        "key points",
+        "gist",
+        "what is this about",
+        "what are these about",
+        "describe",
+    ],
+    extraction_hints: &[
+        "list ",
+        "extract",
+        "enumerate",
+        "find all",
+        "all the ",
+        "every ",
+        "each ",
+    ],
+    aggregation_hints: &[
+        "total",
+        "sum of",
+        "add up",
+        "altogether",
+        "combined",
+        "average",
+        "how many",
+        "number of",
+        "count of",
+    ],
+};
+
+const GERMAN: Defaults = Defaults {
+    relation_hints: &[
+        "beziehung",
+        "zusammenhang",
+        "zusammenhängen",
+        "vergleich",
+        "unterschied",
+        "gemeinsamkeit",
+        "zwischen",
+        "verbunden",
+        "überschneid",
+        "wie sie",
+    ],
+    multi_doc_hints: &[
+        "dateien",
+        "dokumente",
+        "unterlagen",
+        "folien",
+        "präsentationen",
+        "quellen",
+        "diese dateien",
+        "diese dokumente",
+        "alle dateien",
+        "alle dokumente",
+    ],
+    single_doc_hints: &[
+        "diese datei",
+        "dieses dokument",
+        "diese folie",
+        "folie ",
+        "seite ",
+        "abschnitt ",
+    ],
+    plural_pronouns: &["sie"],
+    stopwords: &[
+        "der", "die", "das", "den", "dem", "des", "und", "für", "wie", "was", "mit", "über",
+        "dass", "diese", "dieser", "dieses", "von", "aus", "ihre", "sie", "ein", "eine", "ist",
+        "sind",
+    ],
+    synonyms: &[],
+    summary_hints: &[
+        "zusammenfass",
+        "überblick",
+        "übersicht",
+        "hauptpunkte",
+        "kernpunkte",
+        "worum geht",
+        "beschreibe",
+    ],
+    extraction_hints: &[
+        "liste",
+        "aufzähl",
+        "extrahier",
+        "alle ",
+        "sämtliche",
+        "jede ",
+    ],
+    aggregation_hints: &[
+        "insgesamt",
+        "summe",
+        "gesamt",
+        "zusammen",
+        "durchschnitt",
+        "wie viele",
+        "anzahl",
+    ],
+};
+
+const SPANISH: Defaults = Defaults {
+    relation_hints: &[
+        "relación",
+        "relaciones",
+        "relacionan",
+        "relacionados",
+        "compar",
+        "diferencias",
+        "similitudes",
+        "entre",
+        "conectan",
+        "coinciden",
+        "cómo se",
+    ],
+    multi_doc_hints: &[
+        "archivos",
+        "documentos",
+        "ficheros",
+        "artículos",
+        "diapositivas",
+        "presentaciones",
+        "fuentes",
+        "estos archivos",
+        "estos documentos",
+        "todos los archivos",
+        "todos los documentos",
+    ],
+    single_doc_hints: &[
+        "este archivo",
+        "este documento",
+        "esta diapositiva",
+        "diapositiva ",
+        "página ",
+        "sección ",
+    ],
+    plural_pronouns: &["ellos", "ellas"],
+    stopwords: &[
+        "el", "la", "los", "las", "y", "para", "cómo", "como", "qué", "que", "con", "sobre",
+        "este", "esta", "estos", "estas", "de", "del", "desde", "en", "su", "sus", "ellos", "son",
+        "es", "un", "una",
+    ],
+    synonyms: &[],
+    summary_hints: &[
+        "resum",
+        "visión general",
+        "puntos clave",
+        "ideas principales",
+        "de qué trata",
+        "describe",
+    ],
+    extraction_hints: &[
+        "lista",
+        "enumera",
+        "extrae",
+        "todos los ",
+        "todas las ",
+        "cada ",
+    ],
+    aggregation_hints: &[
+        "total",
+        "suma",
+        "en conjunto",
+        "promedio",
+        "cuántos",
+        "cuántas",
+        "número de",
+    ],
+};
+
+const FRENCH: Defaults = Defaults {
+    relation_hints: &[
+        "relation",
+        "lien",
+        "rapport",
+        "compar",
+        "différences",
+        "similitudes",
+        "entre",
+        "relient",
+        "recoup",
+        "comment ils",
+    ],
+    multi_doc_hints: &[
+        "fichiers",
+        "documents",
+        "docs",
+        "articles",
+        "diapositives",
+        "présentations",
+        "sources",
+        "ces fichiers",
+        "ces documents",
+        "tous les fichiers",
+        "tous les documents",
+    ],
+    single_doc_hints: &[
+        "ce fichier",
+        "ce document",
+        "cette diapositive",
+        "diapositive ",
+        "page ",
+        "section ",
+    ],
+    plural_pronouns: &["ils", "elles"],
+    stopwords: &[
+        "le", "la", "les", "et", "pour", "comment", "quoi", "que", "quel", "quelle", "avec", "sur",
+        "ce", "cette", "ces", "de", "des", "du", "dans", "leur", "leurs", "ils", "sont", "est",
+        "un", "une",
+    ],
+    synonyms: &[],
+    summary_hints: &[
+        "résum",
+        "aperçu",
+        "vue d'ensemble",
+        "points clés",
+        "idées principales",
+        "de quoi parle",
+        "décris",
+    ],
+    extraction_hints: &[
+        "liste",
+        "énumère",
+        "extrais",
+        "tous les ",
+        "toutes les ",
+        "chaque ",
+    ],
+    aggregation_hints: &[
+        "total",
+        "somme",
+        "au total",
+        "cumul",
+        "moyenne",
+        "combien",
+        "nombre de",
+    ],
+};
+
+/// The bundled lists for `language`, or `None` when none ship with the app.
+pub fn defaults(language: &str) -> Option<LanguageResources> {
+    let language = language.trim().to_lowercase();
+    let defaults = match language.as_str() {
+        "en" => &ENGLISH,
+        "de" => &GERMAN,
+        "es" => &SPANISH,
+        "fr" => &FRENCH,
+        _ => return None,
+    };
+    let owned = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+    Some(LanguageResources {
+        language,
+        relation_hints: owned(defaults.relation_hints),
+        multi_doc_hints: owned(defaults.multi_doc_hints),
+        single_doc_hints: owned(defaults.single_doc_hints),
+        plural_pronouns: owned(defaults.plural_pronouns),
+        stopwords: owned(defaults.stopwords),
+        synonyms: defaults
+            .synonyms
+            .iter()
+            .map(|&group| owned(group))
+            .collect(),
+        summary_hints: owned(defaults.summary_hints),
+        extraction_hints: owned(defaults.extraction_hints),
+        aggregation_hints: owned(defaults.aggregation_hints),
+    })
+}
+
+impl Default for LanguageResources {
+    fn default() -> Self {
+        defaults("en").expect("English defaults are bundled")
+    }
+}
+
+/// Lowercases every entry and drops blanks, repeats and synonym groups of
+/// fewer than two words. Hints are matched as
+/// substrings, so surrounding spaces are kept: `"page "` matches "page 4"
+/// but not "pages".
+pub fn validate_resources(resources: LanguageResources) -> AppResult<LanguageResources> {
+    let language = resources.language.trim().to_lowercase();
+    if language.is_empty() {
+        return Err(AppError::InvalidInput(
+            "language cannot be empty".to_string(),
+        ));
+    }
+    let clean = |words: Vec<String>| {
+        let mut seen = HashSet::new();
+        words
+            .into_iter()
+            .map(|word| word.to_lowercase())
+            .filter(|word| !word.trim().is_empty() && seen.insert(word.clone()))
+            .collect::<Vec<_>>()
+    };
+    Ok(LanguageResources {
+        language,
+        relation_hints: clean(resources.relation_hints),
+        multi_doc_hints: clean(resources.multi_doc_hints),
+        single_doc_hints: clean(resources.single_doc_hints),
+        plural_pronouns: clean(resources.plural_pronouns),
+        stopwords: clean(resources.stopwords),
+        synonyms: resources
+            .synonyms
+            .into_iter()
+            .map(&clean)
+            .filter(|group| group.len() >= 2)
+            .collect(),
+        summary_hints: clean(resources.summary_hints),
+        extraction_hints: clean(resources.extraction_hints),
+        aggregation_hints: clean(resources.aggregation_hints),
+    })
+}
+
+pub fn response(resources: LanguageResources) -> LanguageResourcesResponse {
+    let customized = defaults(&resources.language).as_ref() != Some(&resources);
+    LanguageResourcesResponse {
+        resources,
+        languages: LANGUAGES
+            .iter()
+            .map(|language| language.to_string())
+            .collect(),
+        customized,
+    }
+}
@@ -1,6 +1,19 @@
 pub mod agent_schema;
+pub mod aggregate;
+pub mod benchmark;
+pub mod compare;
 pub mod evaluator;
 pub mod executor;
+pub mod feedback;
+pub mod insufficient;
+pub mod language;
+pub mod narration;
+pub mod navigation;
 pub mod planner;
 pub mod prompts;
+pub mod query_class;
 pub mod query_scope;
+pub mod ranking;
+pub mod research_log;
+pub mod share;
+pub mod timeline;
@@ -0,0 +1,148 @@
+//! One-line, plain-language summaries of what each step found, streamed with
+//! the step so someone watching a run sees its progress in terms of their
+//! documents (section titles, match counts) rather than the fixed thought
+//! strings.
+
+use std::collections::HashSet;
+
+use crate::{
+    core::types::{DocNodeSummary, NodeType},
+    reasoner::aggregate::{format_value, UnitTotal},
+};
+
+/// Titles quoted in a summary before the rest are counted.
+const TITLES_SHOWN: usize = 3;
+
+pub fn scan_root(nodes: &[DocNodeSummary]) -> String {
+    if nodes.is_empty() {
+        return "Found no sections to start from.".to_string();
+    }
+    format!(
+        "Skimmed the outline: {} top-level part(s){}.",
+        nodes.len(),
+        titles_clause(nodes, ", including ")
+    )
+}
+
+pub fn select_sections(nodes: &[DocNodeSummary]) -> String {
+    if nodes.is_empty() {
+        return "No section matched the question's terms.".to_string();
+    }
+    format!(
+        "Picked {} section(s) that match the question{}.",
+        nodes.len(),
+        titles_clause(nodes, ": ")
+    )
+}
+
+pub fn drill_down(nodes: &[DocNodeSummary], sections: usize) -> String {
+    if nodes.is_empty() {
+        return "Found nothing below the selected sections.".to_string();
+    }
+    let opened = if sections > 0 {
+        format!("Opened {sections} section(s) and found")
+    } else {
+        "Searched again and found".to_string()
+    };
+    format!(
+        "{opened} {} passage(s){}.",
+        nodes.len(),
+        titles_clause(&nodes[..1], "; the closest match is ")
+    )
+}
+
+pub fn extract_evidence(nodes: &[DocNodeSummary]) -> String {
+    if nodes.is_empty() {
+        return "Could not find evidence for the question.".to_string();
+    }
+    let documents = nodes
+        .iter()
+        .map(|node| node.document_id.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+    let mut kinds = vec![];
+    for (node_type, label) in [
+        (NodeType::Table, "table"),
+        (NodeType::Figure, "figure"),
+        (NodeType::Equation, "equation"),
+    ] {
+        let count = nodes
+            .iter()
+            .filter(|node| node.node_type == node_type)
+            .count();
+        if count > 0 {
+            kinds.push(format!("{count} {label}(s)"));
+        }
+    }
+    let kinds = if kinds.is_empty() {
+        String::new()
+    } else {
+        format!(", including {}", kinds.join(" and "))
+    };
+    format!(
+        "Gathered {} piece(s) of evidence from {documents} document(s){kinds}.",
+        nodes.len()
+    )
+}
+
+pub fn aggregate(totals: &[UnitTotal]) -> String {
+    if totals.is_empty() {
+        return "The evidence records no amounts to add up.".to_string();
+    }
+    let sums = totals
+        .iter()
+        .map(|total| {
+            format!(
+                "{} {} value(s) summing to {}",
+                total.count,
+                total.unit,
+                format_value(total.sum)
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("Added up {}.", sums.join("; "))
+}
+
+pub fn synthesize(cited: usize, evidence: usize) -> String {
+    match cited {
+        0 => format!("Drafted an answer, but it cites none of the {evidence} evidence piece(s)."),
+        _ => format!("Drafted an answer citing {cited} of {evidence} evidence piece(s)."),
+    }
+}
+
+pub fn self_check(grounded: bool, confidence: f64, threshold: f64) -> String {
+    if !grounded {
+        "The answer is not backed by its evidence; looking again.".to_string()
+    } else if confidence < threshold {
+        format!(
+            "The answer holds up, but confidence {confidence:.2} is below {threshold:.2}; looking in other sections."
+        )
+    } else {
+        format!("The answer holds up with confidence {confidence:.2}.")
+    }
+}
+
+/// `prefix` followed by up to [`TITLES_SHOWN`] quoted titles and a count of
+/// the rest; empty when no node has a title.
+fn titles_clause(nodes: &[DocNodeSummary], prefix: &str) -> String {
+    let titles = nodes
+        .iter()
+        .map(|node| node.title.trim())
+        .filter(|title| !title.is_empty())
+        .collect::<Vec<_>>();
+    if titles.is_empty() {
+        return String::new();
+    }
+    let shown = titles
+        .iter()
+        .take(TITLES_SHOWN)
+        .map(|title| format!("\"{title}\""))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let more = titles.len().saturating_sub(TITLES_SHOWN);
+    if more > 0 {
+        format!("{prefix}{shown} and {more} more")
+    } else {
+        format!("{prefix}{shown}")
+    }
+}
@@ -0,0 +1,140 @@
+//! Hierarchical drill-down. Rather than searching the whole project again
+//! with a bigger limit, the run follows `parent_id` down from the sections it
+//! selected to their children and grandchildren, and ranks what it finds
+//! against the query. Evidence can also be read with its neighbours, so a
+//! statement is not taken out of its context.
+
+use std::cmp::Reverse;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::DocNodeSummary,
+    },
+    db::{repositories::documents, Database},
+    reasoner::feedback::EvidenceFeedback,
+};
+
+/// Levels below a selected section the drill-down reaches.
+pub const DRILL_DEPTH: i64 = 2;
+
+/// Collects the children and grandchildren of `sections` and returns the
+/// best `limit` of them for `query`. A selected node with no children is
+/// already as deep as the tree goes and stands in for its own subtree.
+pub async fn drill_down(
+    db: &Database,
+    sections: &[DocNodeSummary],
+    query: &str,
+    limit: usize,
+    feedback: &EvidenceFeedback,
+) -> AppResult<Vec<DocNodeSummary>> {
+    let mut reached: Vec<DocNodeSummary> = Vec::new();
+    for section in sections {
+        let subtree = documents::get_tree(
+            db.pool(),
+            &section.document_id,
+            Some(section.id.as_str()),
+            DRILL_DEPTH,
+        )
+        .await?;
+        let mut descendants = subtree
+            .into_iter()
+            .filter(|node| node.id != section.id)
+            .peekable();
+        let found = if descendants.peek().is_none() {
+            vec![section.clone()]
+        } else {
+            descendants.collect()
+        };
+        for node in found {
+            if !reached.iter().any(|seen| seen.id == node.id) {
+                reached.push(node);
+            }
+        }
+    }
+
+    let mut ranked = feedback.rerank(rank_descendants(reached, sections, query));
+    ranked.truncate(limit);
+    Ok(ranked)
+}
+
+/// Orders nodes reached from `sections`: the most query terms first, then
+/// the shallowest, then tree order.
+pub fn rank_descendants(
+    nodes: Vec<DocNodeSummary>,
+    sections: &[DocNodeSummary],
+    query: &str,
+) -> Vec<DocNodeSummary> {
+    let terms = documents::normalized_terms(query);
+    let depth = |node: &DocNodeSummary| {
+        if sections.iter().any(|section| section.id == node.id) {
+            0
+        } else if sections
+            .iter()
+            .any(|section| node.parent_id.as_deref() == Some(section.id.as_str()))
+        {
+            1
+        } else {
+            2
+        }
+    };
+    let mut nodes = nodes;
+    nodes.sort_by_key(|node| (Reverse(query_hits(node, &terms)), depth(node)));
+    nodes
+}
+
+/// How many of `terms` appear in the node's title or text.
+pub fn query_hits(node: &DocNodeSummary, terms: &[String]) -> usize {
+    let haystack = format!("{} {}", node.title, node.text).to_lowercase();
+    terms
+        .iter()
+        .filter(|term| haystack.contains(term.as_str()))
+        .count()
+}
+
+/// The heading of the section `node` sits in and the text of its previous
+/// and next siblings, cut to about `budget` characters in all; an exception
+/// clause in the next paragraph then reaches the model with the rule it
+/// qualifies. `None` when there is nothing around the node.
+pub async fn evidence_context(
+    db: &Database,
+    node: &DocNodeSummary,
+    budget: usize,
+) -> AppResult<Option<String>> {
+    let siblings =
+        documents::get_tree(db.pool(), &node.document_id, node.parent_id.as_deref(), 1).await?;
+    let position = siblings.iter().position(|sibling| sibling.id == node.id);
+    let previous = position
+        .and_then(|idx| idx.checked_sub(1))
+        .and_then(|idx| siblings.get(idx));
+    let next = position.and_then(|idx| siblings.get(idx + 1));
+    let heading = match &node.parent_id {
+        Some(parent) => match documents::get_node(db.pool(), parent).await {
+            Ok(parent) => Some(parent.title),
+            Err(AppError::NotFound(_)) => None,
+            Err(err) => return Err(err),
+        },
+        None => None,
+    };
+
+    let mut parts = Vec::new();
+    let mut remaining = budget;
+    if let Some(heading) = heading.as_deref().map(str::trim).filter(|h| !h.is_empty()) {
+        let heading = clip(heading, remaining);
+        remaining = remaining.saturating_sub(heading.chars().count());
+        parts.push(format!("section={heading}"));
+    }
+    // What is left is shared between the two neighbours.
+    let share = remaining / 2;
+    for (label, sibling) in [("before", previous), ("after", next)] {
+        let text = sibling.map(|sibling| clip(&sibling.text.replace('\n', " "), share));
+        if let Some(text) = text.filter(|text| !text.is_empty()) {
+            parts.push(format!("{label}={text}"));
+        }
+    }
+    Ok((!parts.is_empty()).then(|| parts.join(" ")))
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    text.trim().chars().take(max_chars).collect()
+}
@@ -1,6 +1,8 @@
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 
-use crate::providers::gemini::GeminiPlannerStep;
+use crate::{core::types::QueryClass, providers::gemini::GeminiPlannerStep};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -9,6 +11,9 @@ pub enum StepType {
     SelectSections,
     DrillDown,
     ExtractEvidence,
+    /// Adds up the quantities recorded in the evidence for a query asking
+    /// for a total, count or average.
+    Aggregate,
     Synthesize,
     SelfCheck,
 }
@@ -20,6 +25,7 @@ impl StepType {
             Self::SelectSections => "select_sections",
             Self::DrillDown => "drill_down",
             Self::ExtractEvidence => "extract_evidence",
+            Self::Aggregate => "aggregate",
             Self::Synthesize => "synthesize",
             Self::SelfCheck => "self_check",
         }
@@ -53,13 +59,47 @@ impl Default for PlannerConfig {
 #[derive(Debug, Clone)]
 pub struct PlannerInput {
     pub query: String,
+    /// What the query asks for; picks the steps of the first pass.
+    pub query_class: QueryClass,
     pub last_confidence: Option<f64>,
     pub explored_sections: Vec<String>,
+    /// What each branch checked so far in this run yielded.
+    pub branch_outcomes: Vec<BranchOutcome>,
     pub has_evidence: bool,
     pub step_count: usize,
     pub backtrack_count: usize,
 }
 
+/// What one explored branch, a section and the evidence nodes drawn from it,
+/// contributed to the last checked answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchOutcome {
+    /// The section the nodes sit under; a top-level node is its own branch.
+    pub branch_id: String,
+    pub node_ids: Vec<String>,
+    /// How many of `node_ids` the answer cited.
+    pub cited: usize,
+    /// Confidence of the answer the nodes went into.
+    pub confidence: f64,
+}
+
+impl BranchOutcome {
+    /// Share of the branch's nodes the answer cited.
+    pub fn usefulness(&self) -> f64 {
+        if self.node_ids.is_empty() {
+            0.0
+        } else {
+            self.cited as f64 / self.node_ids.len() as f64
+        }
+    }
+
+    /// A branch is exhausted when it fed an answer that fell short and none
+    /// of its nodes were cited: drawing on it again would not help.
+    pub fn is_exhausted(&self, confidence_threshold: f64) -> bool {
+        self.cited == 0 && self.confidence < confidence_threshold
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PlannedStep {
     pub step_type: StepType,
@@ -82,6 +122,18 @@ impl Planner {
         Self { config }
     }
 
+    /// Branch and node ids a backtrack should leave out of candidate selection.
+    pub fn exhausted_branches(&self, input: &PlannerInput) -> HashSet<String> {
+        input
+            .branch_outcomes
+            .iter()
+            .filter(|outcome| outcome.is_exhausted(self.config.confidence_threshold))
+            .flat_map(|outcome| {
+                std::iter::once(outcome.branch_id.clone()).chain(outcome.node_ids.iter().cloned())
+            })
+            .collect()
+    }
+
     pub fn next_steps(&self, input: &PlannerInput) -> PlannedSequence {
         if input.step_count >= self.config.max_steps {
             return PlannedSequence {
@@ -94,33 +146,44 @@ impl Planner {
             if confidence < self.config.confidence_threshold
                 && input.backtrack_count < self.config.max_backtracks
             {
+                let exhausted = input
+                    .branch_outcomes
+                    .iter()
+                    .filter(|outcome| outcome.is_exhausted(self.config.confidence_threshold))
+                    .count();
+                let mut steps = vec![
+                    PlannedStep {
+                        step_type: StepType::SelectSections,
+                        objective: format!(
+                            "Re-select sections for query '{}' skipping {exhausted} exhausted branch(es)",
+                            input.query
+                        ),
+                    },
+                    PlannedStep {
+                        step_type: StepType::DrillDown,
+                        objective: "Drill into candidate subsections".to_string(),
+                    },
+                    PlannedStep {
+                        step_type: StepType::ExtractEvidence,
+                        objective: "Extract stronger evidence nodes".to_string(),
+                    },
+                ];
+                if input.query_class == QueryClass::Aggregation {
+                    steps.push(aggregate_step());
+                }
+                steps.extend([
+                    PlannedStep {
+                        step_type: StepType::Synthesize,
+                        objective: "Synthesize revised answer".to_string(),
+                    },
+                    PlannedStep {
+                        step_type: StepType::SelfCheck,
+                        objective: "Estimate grounded confidence".to_string(),
+                    },
+                ]);
                 return PlannedSequence {
                     decision: PlannerDecision::Backtrack,
-                    steps: vec![
-                        PlannedStep {
-                            step_type: StepType::SelectSections,
-                            objective: format!(
-                                "Re-select sections for query '{}' skipping explored branches",
-                                input.query
-                            ),
-                        },
-                        PlannedStep {
-                            step_type: StepType::DrillDown,
-                            objective: "Drill into candidate subsections".to_string(),
-                        },
-                        PlannedStep {
-                            step_type: StepType::ExtractEvidence,
-                            objective: "Extract stronger evidence nodes".to_string(),
-                        },
-                        PlannedStep {
-                            step_type: StepType::Synthesize,
-                            objective: "Synthesize revised answer".to_string(),
-                        },
-                        PlannedStep {
-                            step_type: StepType::SelfCheck,
-                            objective: "Estimate grounded confidence".to_string(),
-                        },
-                    ],
+                    steps,
                 };
             }
         }
@@ -148,33 +211,7 @@ impl Planner {
 
         PlannedSequence {
             decision: PlannerDecision::Continue,
-            steps: vec![
-                PlannedStep {
-                    step_type: StepType::ScanRoot,
-                    objective,
-                },
-                PlannedStep {
-                    step_type: StepType::SelectSections,
-                    objective: "Select sections relevant to user query".to_string(),
-                },
-                PlannedStep {
-                    step_type: StepType::DrillDown,
-                    objective: "Navigate into subsections and atomic nodes".to_string(),
-                },
-                PlannedStep {
-                    step_type: StepType::ExtractEvidence,
-                    objective: "Extract claim/table/equation evidence".to_string(),
-                },
-                PlannedStep {
-                    step_type: StepType::Synthesize,
-                    objective: "Synthesize grounded answer".to_string(),
-                },
-                PlannedStep {
-                    step_type: StepType::SelfCheck,
-                    objective: "Measure confidence and decide if re-traversal is needed"
-                        .to_string(),
-                },
-            ],
+            steps: first_pass(input.query_class, objective),
         }
     }
 
@@ -252,16 +289,22 @@ impl Planner {
                     objective: "Select high-signal sections".to_string(),
                 },
             ],
-            StepType::DrillDown => vec![
-                PlannedStep {
-                    step_type: StepType::DrillDown,
-                    objective: model_step.objective.clone(),
-                },
-                PlannedStep {
-                    step_type: StepType::ExtractEvidence,
-                    objective: "Extract concrete supporting claims".to_string(),
-                },
-            ],
+            StepType::DrillDown => {
+                let mut steps = vec![
+                    PlannedStep {
+                        step_type: StepType::DrillDown,
+                        objective: model_step.objective.clone(),
+                    },
+                    PlannedStep {
+                        step_type: StepType::ExtractEvidence,
+                        objective: "Extract concrete supporting claims".to_string(),
+                    },
+                ];
+                if input.query_class == QueryClass::Aggregation {
+                    steps.push(aggregate_step());
+                }
+                steps
+            }
             StepType::Synthesize => vec![PlannedStep {
                 step_type: StepType::Synthesize,
                 objective: model_step.objective.clone(),
@@ -270,10 +313,12 @@ impl Planner {
                 step_type: StepType::SelfCheck,
                 objective: model_step.objective.clone(),
             }],
-            StepType::SelectSections | StepType::ExtractEvidence => vec![PlannedStep {
-                step_type: parse_step_kind(&model_step.step_type)?,
-                objective: model_step.objective.clone(),
-            }],
+            StepType::SelectSections | StepType::ExtractEvidence | StepType::Aggregate => {
+                vec![PlannedStep {
+                    step_type: parse_step_kind(&model_step.step_type)?,
+                    objective: model_step.objective.clone(),
+                }]
+            }
         };
 
         Some(PlannedSequence {
@@ -283,6 +328,63 @@ impl Planner {
     }
 }
 
+/// The steps a query of `class` starts with: a summary reads the selected
+/// sections whole instead of drilling into them, and an aggregation adds up
+/// the quantities in its evidence before the answer is drafted.
+fn first_pass(class: QueryClass, scan_objective: String) -> Vec<PlannedStep> {
+    let mut steps = vec![
+        PlannedStep {
+            step_type: StepType::ScanRoot,
+            objective: scan_objective,
+        },
+        PlannedStep {
+            step_type: StepType::SelectSections,
+            objective: match class {
+                QueryClass::Comparison => "Select sections from each side of the comparison",
+                _ => "Select sections relevant to user query",
+            }
+            .to_string(),
+        },
+    ];
+    if class != QueryClass::Summary {
+        steps.push(PlannedStep {
+            step_type: StepType::DrillDown,
+            objective: "Navigate into subsections and atomic nodes".to_string(),
+        });
+    }
+    steps.push(PlannedStep {
+        step_type: StepType::ExtractEvidence,
+        objective: match class {
+            QueryClass::Summary => "Extract the main points of the selected sections",
+            QueryClass::Extraction => "Extract every item of the kind the query lists",
+            QueryClass::Aggregation => "Extract the values the query combines",
+            QueryClass::Factual | QueryClass::Comparison => "Extract claim/table/equation evidence",
+        }
+        .to_string(),
+    });
+    if class == QueryClass::Aggregation {
+        steps.push(aggregate_step());
+    }
+    steps.extend([
+        PlannedStep {
+            step_type: StepType::Synthesize,
+            objective: "Synthesize grounded answer".to_string(),
+        },
+        PlannedStep {
+            step_type: StepType::SelfCheck,
+            objective: "Measure confidence and decide if re-traversal is needed".to_string(),
+        },
+    ]);
+    steps
+}
+
+fn aggregate_step() -> PlannedStep {
+    PlannedStep {
+        step_type: StepType::Aggregate,
+        objective: "Add up the quantities recorded in the evidence".to_string(),
+    }
+}
+
 fn parse_decision(raw: &str) -> PlannerDecision {
     match raw.trim().to_ascii_lowercase().as_str() {
         "stop" | "finish" | "done" => PlannerDecision::Stop,
@@ -297,6 +399,7 @@ fn parse_step_kind(raw: &str) -> Option<StepType> {
         "select_sections" => Some(StepType::SelectSections),
         "inspect" | "drill_down" => Some(StepType::DrillDown),
         "extract_evidence" => Some(StepType::ExtractEvidence),
+        "aggregate" => Some(StepType::Aggregate),
         "synthesize" => Some(StepType::Synthesize),
         "self_check" | "validate" => Some(StepType::SelfCheck),
         "finish" => Some(StepType::SelfCheck),
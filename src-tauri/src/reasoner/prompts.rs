@@ -1,60 +1,278 @@
-use crate::reasoner::planner::PlannerInput;
+use serde_json::Value;
 
-pub fn planner_prompt(input: &PlannerInput) -> String {
-    let mut text = String::new();
-    text.push_str("You are the reasoning planner for a document QA agent.\n");
-    text.push_str("Pick exactly one next action. Be concise and strategic.\n");
-    text.push_str("Return ONLY JSON with keys: stepType, objective, reasoning, decision.\n");
-    text.push_str("Allowed stepType: search, inspect, synthesize, self_check, finish.\n");
-    text.push_str("Allowed decision: continue, backtrack, stop.\n\n");
-    text.push_str("STATE:\n");
-    text.push_str(&format!("query: {}\n", input.query));
-    text.push_str(&format!("stepCount: {}\n", input.step_count));
-    text.push_str(&format!("backtrackCount: {}\n", input.backtrack_count));
-    text.push_str(&format!("hasEvidence: {}\n", input.has_evidence));
-    text.push_str(&format!(
-        "lastConfidence: {}\n",
-        input
-            .last_confidence
-            .map(|value| format!("{value:.2}"))
-            .unwrap_or_else(|| "none".to_string())
-    ));
+use crate::{
+    core::errors::{AppError, AppResult},
+    reasoner::planner::PlannerInput,
+};
+
+pub const PLANNER_TEMPLATE: &str = "planner";
+pub const SYNTHESIS_TEMPLATE: &str = "synthesis";
+pub const TEMPLATE_NAMES: &[&str] = &[PLANNER_TEMPLATE, SYNTHESIS_TEMPLATE];
+
+/// Version number reported for the compiled-in templates that have never been edited.
+pub const BUILTIN_TEMPLATE_VERSION: i64 = 0;
+
+const PLANNER_VARIABLES: &[&str] = &[
+    "query",
+    "query_class",
+    "step_count",
+    "backtrack_count",
+    "has_evidence",
+    "last_confidence",
+    "explored_sections",
+    "branch_outcomes",
+];
+const PLANNER_REQUIRED: &[&str] = &["query"];
+
+const SYNTHESIS_VARIABLES: &[&str] = &["query", "evidence"];
+const SYNTHESIS_REQUIRED: &[&str] = &["query", "evidence"];
+
+pub const DEFAULT_PLANNER_TEMPLATE: &str = "You are the reasoning planner for a document QA agent.
+Pick exactly one next action. Be concise and strategic.
+Return ONLY JSON with keys: stepType, objective, reasoning, decision.
+Allowed stepType: search, inspect, aggregate, synthesize, self_check, finish.
+Allowed decision: continue, backtrack, stop.
+
+STATE:
+query: {{query}}
+queryClass: {{query_class}}
+stepCount: {{step_count}}
+backtrackCount: {{backtrack_count}}
+hasEvidence: {{has_evidence}}
+lastConfidence: {{last_confidence}}
+{{explored_sections}}{{branch_outcomes}}
+Strategy hints:
+- Use search before inspect when evidence is weak.
+- Use aggregate after inspect when the query asks for a total, count or average.
+- Use synthesize only after evidence exists.
+- Use self_check after synthesis.
+- Use finish only when answer quality is sufficient.
+";
+
+pub const DEFAULT_SYNTHESIS_TEMPLATE: &str = "You are a retrieval reasoner. Answer only from the provided evidence.
+If evidence is insufficient, explicitly say what is missing instead of guessing.
+Do not paste raw node ids in prose except inside citations.
+
+USER QUERY:
+{{query}}
+
+EVIDENCE:
+{{evidence}}
+Output rules:
+- If the query compares or relates files/documents, structure answer_markdown with headings:
+  1) What each file is about
+  2) How they are related
+  3) Gaps or uncertainty
+- Every substantive claim must be grounded by at least one citation id.
+- citations must only contain ids that appear in evidence ([citation:...]).
+
+Return ONLY valid JSON with this exact shape:
+{\"answer_markdown\":\"...\",\"confidence\":0.0,\"citations\":[\"node-id\"]}
+";
+
+/// Appended to the synthesis prompt when the first response was not valid JSON.
+pub const STRICT_JSON_REMINDER: &str = "
+IMPORTANT: Your previous reply could not be parsed. Respond with a single JSON object and nothing else:
+no markdown fences, no commentary before or after it, no trailing commas.
+";
+
+/// A resolved prompt template together with the version it was loaded from.
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub version: i64,
+    pub body: String,
+}
+
+/// The templates used by a single reasoning run.
+#[derive(Debug, Clone)]
+pub struct PromptSet {
+    pub planner: PromptTemplate,
+    pub synthesis: PromptTemplate,
+}
+
+impl Default for PromptSet {
+    fn default() -> Self {
+        Self {
+            planner: PromptTemplate {
+                name: PLANNER_TEMPLATE.to_string(),
+                version: BUILTIN_TEMPLATE_VERSION,
+                body: DEFAULT_PLANNER_TEMPLATE.to_string(),
+            },
+            synthesis: PromptTemplate {
+                name: SYNTHESIS_TEMPLATE.to_string(),
+                version: BUILTIN_TEMPLATE_VERSION,
+                body: DEFAULT_SYNTHESIS_TEMPLATE.to_string(),
+            },
+        }
+    }
+}
+
+impl PromptSet {
+    pub fn versions_json(&self) -> Value {
+        serde_json::json!({
+            PLANNER_TEMPLATE: self.planner.version,
+            SYNTHESIS_TEMPLATE: self.synthesis.version,
+        })
+    }
+}
+
+pub fn default_template(name: &str) -> Option<&'static str> {
+    match name {
+        PLANNER_TEMPLATE => Some(DEFAULT_PLANNER_TEMPLATE),
+        SYNTHESIS_TEMPLATE => Some(DEFAULT_SYNTHESIS_TEMPLATE),
+        _ => None,
+    }
+}
+
+pub fn template_variables(name: &str) -> &'static [&'static str] {
+    match name {
+        PLANNER_TEMPLATE => PLANNER_VARIABLES,
+        SYNTHESIS_TEMPLATE => SYNTHESIS_VARIABLES,
+        _ => &[],
+    }
+}
+
+fn required_variables(name: &str) -> &'static [&'static str] {
+    match name {
+        PLANNER_TEMPLATE => PLANNER_REQUIRED,
+        SYNTHESIS_TEMPLATE => SYNTHESIS_REQUIRED,
+        _ => &[],
+    }
+}
+
+/// Checks that a template only references variables its renderer can supply
+/// and that the variables the pipeline depends on are present.
+pub fn validate_template(name: &str, body: &str) -> AppResult<()> {
+    if default_template(name).is_none() {
+        return Err(AppError::InvalidInput(format!("unknown prompt template '{name}'")));
+    }
+    if body.trim().is_empty() {
+        return Err(AppError::InvalidInput("prompt template cannot be empty".to_string()));
+    }
+
+    let allowed = template_variables(name);
+    let used = template_placeholders(body)?;
+    if let Some(unknown) = used.iter().find(|var| !allowed.contains(&var.as_str())) {
+        return Err(AppError::InvalidInput(format!(
+            "prompt template '{name}' uses unknown variable '{{{{{unknown}}}}}'; allowed: {}",
+            allowed.join(", ")
+        )));
+    }
+    if let Some(missing) = required_variables(name)
+        .iter()
+        .find(|var| !used.iter().any(|used| used == *var))
+    {
+        return Err(AppError::InvalidInput(format!(
+            "prompt template '{name}' must include '{{{{{missing}}}}}'"
+        )));
+    }
+    Ok(())
+}
+
+fn template_placeholders(body: &str) -> AppResult<Vec<String>> {
+    let mut names = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| {
+            AppError::InvalidInput("prompt template has an unterminated '{{' placeholder".to_string())
+        })?;
+        let name = after[..end].trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput(
+                "prompt template has an empty '{{}}' placeholder".to_string(),
+            ));
+        }
+        names.push(name.to_string());
+        rest = &after[end + 2..];
+    }
+    Ok(names)
+}
+
+fn render(template: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = after[..end].trim();
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn render_planner_prompt(template: &str, input: &PlannerInput) -> String {
+    let mut explored = String::new();
     if !input.explored_sections.is_empty() {
-        text.push_str("exploredSections:\n");
+        explored.push_str("exploredSections:\n");
         for section in &input.explored_sections {
-            text.push_str(&format!("- {section}\n"));
+            explored.push_str(&format!("- {section}\n"));
         }
     }
+    let mut outcomes = String::new();
+    if !input.branch_outcomes.is_empty() {
+        outcomes.push_str("branchOutcomes:\n");
+        for outcome in &input.branch_outcomes {
+            outcomes.push_str(&format!(
+                "- {}: tried {} node(s), cited {}, usefulness {:.2}, confidence {:.2}\n",
+                outcome.branch_id,
+                outcome.node_ids.len(),
+                outcome.cited,
+                outcome.usefulness(),
+                outcome.confidence
+            ));
+        }
+    }
+    render(
+        template,
+        &[
+            ("query", input.query.clone()),
+            ("query_class", input.query_class.as_str().to_string()),
+            ("step_count", input.step_count.to_string()),
+            ("backtrack_count", input.backtrack_count.to_string()),
+            ("has_evidence", input.has_evidence.to_string()),
+            (
+                "last_confidence",
+                input
+                    .last_confidence
+                    .map(|value| format!("{value:.2}"))
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            ("explored_sections", explored),
+            ("branch_outcomes", outcomes),
+        ],
+    )
+}
+
+pub fn render_synthesis_prompt(template: &str, query: &str, evidence: &[String]) -> String {
+    let mut listed = String::new();
+    for (idx, item) in evidence.iter().enumerate() {
+        listed.push_str(&format!("{}. {item}\n", idx + 1));
+    }
+    render(
+        template,
+        &[("query", query.to_string()), ("evidence", listed)],
+    )
+}
 
-    text.push_str("\nStrategy hints:\n");
-    text.push_str("- Use search before inspect when evidence is weak.\n");
-    text.push_str("- Use synthesize only after evidence exists.\n");
-    text.push_str("- Use self_check after synthesis.\n");
-    text.push_str("- Use finish only when answer quality is sufficient.\n");
-    text
+pub fn strict_json_prompt(prompt: &str) -> String {
+    format!("{prompt}{STRICT_JSON_REMINDER}")
+}
+
+pub fn planner_prompt(input: &PlannerInput) -> String {
+    render_planner_prompt(DEFAULT_PLANNER_TEMPLATE, input)
 }
 
 pub fn synthesis_prompt(query: &str, evidence: &[String]) -> String {
-    let mut text = String::new();
-    text.push_str("You are a retrieval reasoner. Answer only from the provided evidence.\n");
-    text.push_str(
-        "If evidence is insufficient, explicitly say what is missing instead of guessing.\n",
-    );
-    text.push_str("Do not paste raw node ids in prose except inside citations.\n\n");
-    text.push_str("USER QUERY:\n");
-    text.push_str(query);
-    text.push_str("\n\nEVIDENCE:\n");
-    for (idx, item) in evidence.iter().enumerate() {
-        text.push_str(&format!("{}. {item}\n", idx + 1));
-    }
-    text.push_str("\nOutput rules:\n");
-    text.push_str("- If the query compares or relates files/documents, structure answer_markdown with headings:\n");
-    text.push_str(
-        "  1) What each file is about\n  2) How they are related\n  3) Gaps or uncertainty\n",
-    );
-    text.push_str("- Every substantive claim must be grounded by at least one citation id.\n");
-    text.push_str("- citations must only contain ids that appear in evidence ([citation:...]).\n");
-    text.push_str("\nReturn ONLY valid JSON with this exact shape:\n");
-    text.push_str("{\"answer_markdown\":\"...\",\"confidence\":0.0,\"citations\":[\"node-id\"]}\n");
-    text
+    render_synthesis_prompt(DEFAULT_SYNTHESIS_TEMPLATE, query, evidence)
 }
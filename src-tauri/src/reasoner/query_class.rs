@@ -0,0 +1,87 @@
+//! Query classification for the planner and the quality gate. A short
+//! factual lookup and a broad comparison neither take the same steps nor
+//! should be held to the same score, so each run is classified once: the
+//! class picks the planner's first steps and the gate's weights and
+//! threshold.
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{LanguageResources, QualityPolicy, QualityThresholds, QueryClass},
+    },
+    reasoner::query_scope::requires_project_scope,
+};
+
+/// Classifies `query`. A question across documents (`relation_query`) or
+/// one using relation words is a comparison; otherwise summary, aggregation
+/// and then extraction words decide, and anything else is a factual lookup.
+pub fn classify_query(
+    query: &str,
+    relation_query: bool,
+    resources: &LanguageResources,
+) -> QueryClass {
+    let normalized = format!(" {} ", query.to_lowercase());
+    let mentions = |hints: &[String]| hints.iter().any(|hint| normalized.contains(hint.as_str()));
+    if relation_query
+        || requires_project_scope(query, resources)
+        || mentions(&resources.relation_hints)
+    {
+        QueryClass::Comparison
+    } else if mentions(&resources.summary_hints) {
+        QueryClass::Summary
+    } else if mentions(&resources.aggregation_hints) {
+        QueryClass::Aggregation
+    } else if mentions(&resources.extraction_hints) {
+        QueryClass::Extraction
+    } else {
+        QueryClass::Factual
+    }
+}
+
+/// Checks every threshold is within 0–1 and scales each class's weights to
+/// sum to 1.
+pub fn validate_thresholds(thresholds: QualityThresholds) -> AppResult<QualityThresholds> {
+    let check = |name: &str, policy: QualityPolicy| -> AppResult<QualityPolicy> {
+        if !(0.0..=1.0).contains(&policy.min_overall) {
+            return Err(AppError::InvalidInput(format!(
+                "{name} minimum quality must be between 0 and 1"
+            )));
+        }
+        let mut weights = policy.weights;
+        let parts = [
+            weights.query_alignment,
+            weights.citation_coverage,
+            weights.cross_document_coverage,
+            weights.grounding,
+        ];
+        if parts
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(AppError::InvalidInput(format!(
+                "{name} quality weights cannot be negative"
+            )));
+        }
+        let total = parts.iter().sum::<f64>();
+        if total <= 0.0 {
+            return Err(AppError::InvalidInput(format!(
+                "{name} quality weights cannot all be 0"
+            )));
+        }
+        weights.query_alignment /= total;
+        weights.citation_coverage /= total;
+        weights.cross_document_coverage /= total;
+        weights.grounding /= total;
+        Ok(QualityPolicy {
+            min_overall: policy.min_overall,
+            weights,
+        })
+    };
+    Ok(QualityThresholds {
+        factual: check("factual", thresholds.factual)?,
+        summary: check("summary", thresholds.summary)?,
+        comparison: check("comparison", thresholds.comparison)?,
+        extraction: check("extraction", thresholds.extraction)?,
+        aggregation: check("aggregation", thresholds.aggregation)?,
+    })
+}
@@ -1,62 +1,128 @@
-const RELATION_HINTS: &[&str] = &[
-    "related",
-    "relationship",
-    "relationships",
-    "compare",
-    "comparison",
-    "differences",
-    "similarities",
-    "across",
-    "between",
-    "connect",
-    "overlap",
-    "fit together",
-    "how they",
-];
-
-const MULTI_DOC_HINTS: &[&str] = &[
-    "files",
-    "documents",
-    "docs",
-    "papers",
-    "slides",
-    "presentations",
-    "sources",
-    "these files",
-    "these documents",
-    "all files",
-    "all documents",
-];
+use std::collections::HashSet;
 
-const SINGLE_DOC_HINTS: &[&str] = &[
-    "this file",
-    "this document",
-    "this slide",
-    "slide ",
-    "page ",
-    "section ",
-];
+use crate::core::types::{DocumentSummary, LanguageResources, QueryScope, ScopeDecision};
 
-pub fn requires_project_scope(query: &str) -> bool {
-    let normalized = format!(" {} ", query.to_ascii_lowercase());
-    let has_relation_hint = RELATION_HINTS.iter().any(|hint| normalized.contains(hint));
-    let has_multi_doc_hint = MULTI_DOC_HINTS.iter().any(|hint| normalized.contains(hint));
-    let has_single_doc_hint = SINGLE_DOC_HINTS
+/// Whether `query` asks about several documents at once, read with the word
+/// lists of the user's language.
+pub fn requires_project_scope(query: &str, resources: &LanguageResources) -> bool {
+    let normalized = format!(" {} ", query.to_lowercase());
+    let mentions = |hints: &[String]| hints.iter().any(|hint| normalized.contains(hint.as_str()));
+    let has_relation_hint = mentions(&resources.relation_hints);
+    let has_multi_doc_hint = mentions(&resources.multi_doc_hints);
+    let has_single_doc_hint = mentions(&resources.single_doc_hints);
+    let has_plural_pronoun = resources
+        .plural_pronouns
         .iter()
-        .any(|hint| normalized.contains(hint));
-    let has_plural_pronoun = normalized.contains(" they ") || normalized.contains(" them ");
+        .any(|pronoun| normalized.contains(&format!(" {pronoun} ")));
 
     if has_multi_doc_hint && (has_relation_hint || has_plural_pronoun) {
         return true;
     }
 
-    if normalized.contains("across documents") || normalized.contains("across files") {
-        return true;
-    }
-
     if has_relation_hint && has_plural_pronoun {
         return true;
     }
 
     has_relation_hint && has_multi_doc_hint && !has_single_doc_hint
 }
+
+/// Scopes a run: `force` wins; otherwise a focus document is kept unless the
+/// query reads as a question across documents.
+pub fn decide_scope(
+    query: &str,
+    focus_document_id: Option<String>,
+    force: Option<QueryScope>,
+    resources: &LanguageResources,
+) -> ScopeDecision {
+    let had_focus = focus_document_id.is_some();
+    let focus_document_id = match force {
+        Some(QueryScope::Project) => None,
+        Some(QueryScope::Document) => focus_document_id,
+        None if requires_project_scope(query, resources) => None,
+        None => focus_document_id,
+    };
+    ScopeDecision {
+        scope: if focus_document_id.is_some() {
+            QueryScope::Document
+        } else {
+            QueryScope::Project
+        },
+        focus_dropped: had_focus && focus_document_id.is_none() && force.is_none(),
+        forced: force.is_some(),
+        focus_document_id,
+    }
+}
+
+/// Words too common in file names to tell documents apart.
+const GENERIC_NAME_WORDS: &[&str] = &[
+    "the", "and", "of", "for", "to", "in", "on", "final", "draft", "copy", "new", "old", "doc",
+    "docs", "file", "document", "version", "rev",
+];
+
+/// A document the query names, e.g. "in the Q3 report" for `Q3_Report_2024.pdf`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusInference {
+    pub document_id: String,
+    pub document_name: String,
+    /// Words of the document's name found in the query.
+    pub matched_terms: Vec<String>,
+}
+
+/// The document `query` mentions by name, if exactly one does. A name counts
+/// as mentioned when the query has every distinctive word of it, or all but
+/// one for names of three words or more.
+pub fn infer_focus_document(query: &str, documents: &[DocumentSummary]) -> Option<FocusInference> {
+    let query_words = words(query).collect::<HashSet<_>>();
+    let mut best: Option<FocusInference> = None;
+    let mut tied = false;
+    for document in documents {
+        let name_words = name_words(&document.name);
+        if name_words.is_empty() || (name_words.len() == 1 && name_words[0].len() < 3) {
+            continue;
+        }
+        let matched_terms = name_words
+            .iter()
+            .filter(|word| query_words.contains(*word))
+            .cloned()
+            .collect::<Vec<_>>();
+        let needed = if name_words.len() >= 3 {
+            name_words.len() - 1
+        } else {
+            name_words.len()
+        };
+        if matched_terms.len() < needed {
+            continue;
+        }
+        let current = best.as_ref().map_or(0, |best| best.matched_terms.len());
+        if matched_terms.len() == current {
+            tied = true;
+        } else if matched_terms.len() > current {
+            tied = false;
+            best = Some(FocusInference {
+                document_id: document.id.clone(),
+                document_name: document.name.clone(),
+                matched_terms,
+            });
+        }
+    }
+    best.filter(|_| !tied)
+}
+
+/// Distinctive words of a file name, extension left out.
+fn name_words(name: &str) -> Vec<String> {
+    let stem = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && ext.len() <= 5 => stem,
+        _ => name,
+    };
+    let mut seen = HashSet::new();
+    words(stem)
+        .filter(|word| word.len() >= 2 && !GENERIC_NAME_WORDS.contains(&word.as_str()))
+        .filter(|word| seen.insert(word.clone()))
+        .collect()
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
@@ -0,0 +1,89 @@
+//! Orders a run's candidate nodes by [`RankingWeights`]: keyword relevance
+//! with title and text weighted apart, a bonus for each quantity or date
+//! filter a node's metadata matches, and a multiplier for node types the
+//! query hints at, such as tables for "how much".
+
+use std::collections::HashMap;
+
+use crate::core::{
+    errors::{AppError, AppResult},
+    types::{DocNodeSummary, RankingWeights},
+};
+
+/// `keyword_hits`, by their relevance, and `metadata_hits`, one entry per
+/// filter a node matched, ordered best first. Nodes only found by their
+/// metadata are added; ties keep the keyword search's order.
+pub fn rank(
+    keyword_hits: Vec<(DocNodeSummary, f64)>,
+    metadata_hits: Vec<DocNodeSummary>,
+    query: &str,
+    weights: &RankingWeights,
+) -> Vec<DocNodeSummary> {
+    let best = keyword_hits
+        .iter()
+        .map(|(_, relevance)| *relevance)
+        .fold(0.0, f64::max);
+    let mut index = HashMap::<String, usize>::new();
+    let mut scored = Vec::<(DocNodeSummary, f64)>::new();
+    for (node, relevance) in keyword_hits {
+        if index.contains_key(&node.id) {
+            continue;
+        }
+        let score = if best > 0.0 { relevance / best } else { 0.0 };
+        index.insert(node.id.clone(), scored.len());
+        scored.push((node, score));
+    }
+    for node in metadata_hits {
+        match index.get(&node.id) {
+            Some(&at) => scored[at].1 += weights.metadata,
+            None => {
+                index.insert(node.id.clone(), scored.len());
+                scored.push((node, weights.metadata));
+            }
+        }
+    }
+
+    let query = query.to_lowercase();
+    for (node, score) in &mut scored {
+        for boost in &weights.node_types {
+            let hinted =
+                boost.hints.is_empty() || boost.hints.iter().any(|hint| query.contains(hint));
+            if boost.node_type == node.node_type && hinted {
+                *score *= boost.weight;
+            }
+        }
+    }
+
+    scored.sort_by(|left, right| right.1.total_cmp(&left.1));
+    scored.into_iter().map(|(node, _)| node).collect()
+}
+
+/// Checks that every weight is a non-negative number and that keyword
+/// matches still count, and normalizes hints to trimmed lowercase.
+pub fn validate(weights: RankingWeights) -> AppResult<RankingWeights> {
+    let mut weights = weights;
+    let all = [weights.title, weights.text, weights.metadata]
+        .into_iter()
+        .chain(weights.node_types.iter().map(|boost| boost.weight));
+    for weight in all {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(AppError::InvalidInput(
+                "ranking weights cannot be negative".to_string(),
+            ));
+        }
+    }
+    if weights.title == 0.0 && weights.text == 0.0 {
+        return Err(AppError::InvalidInput(
+            "title and text weights cannot both be 0".to_string(),
+        ));
+    }
+    for boost in &mut weights.node_types {
+        boost.hints = boost
+            .hints
+            .iter()
+            .map(|hint| hint.trim().to_lowercase())
+            .filter(|hint| !hint.is_empty())
+            .collect();
+    }
+    Ok(weights)
+}
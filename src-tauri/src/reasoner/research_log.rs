@@ -0,0 +1,264 @@
+//! The project's Research Log: a Markdown document the app writes itself,
+//! with one section per accepted answer holding the question, the answer and
+//! the sources it cited. It is stored like an ingested document, so later
+//! queries retrieve and cite earlier findings. Entries are only ever
+//! appended.
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        errors::AppResult,
+        types::{AnswerRecord, NodeRef, ReasoningRun, ResearchLogAppendedEvent},
+    },
+    db::{
+        repositories::{documents, projects, reasoning},
+        Database,
+    },
+    reasoner::share,
+    sidecar::{anchors, types::SidecarNode},
+};
+
+pub const LOG_NAME: &str = "Research Log";
+pub const LOG_MIME: &str = "text/markdown";
+/// `parser` recorded in the metadata of the nodes the log is made of.
+pub const LOG_PARSER: &str = "research_log";
+
+/// The checksum the project's log is stored under. No file hashes to it, so
+/// ingestion never mistakes a file for the log.
+pub fn log_checksum(project_id: &str) -> String {
+    format!("research-log:{project_id}")
+}
+
+/// Appends the answer of `run_id` to its project's log, creating the log on
+/// first use. Nothing is appended when the project has the log switched off
+/// or is read-only or archived, or when the answer did not pass the quality
+/// gate.
+pub async fn append_run(
+    db: &Database,
+    run_id: &str,
+) -> AppResult<Option<ResearchLogAppendedEvent>> {
+    let pool = db.pool();
+    let stored = reasoning::get_run(pool, run_id).await?;
+    let Some(answer) = stored.answer.filter(|answer| answer.grounded) else {
+        return Ok(None);
+    };
+    let project_id = stored.run.project_id.clone();
+    let project = projects::get_project(pool, &project_id).await?;
+    if project.read_only
+        || project.archived
+        || !projects::get_research_log_settings(pool, &project_id)
+            .await?
+            .enabled
+    {
+        return Ok(None);
+    }
+
+    let checksum = log_checksum(&project_id);
+    let existing = documents::find_by_checksum(pool, &project_id, &checksum).await?;
+    let created = existing.is_none();
+    let (document_id, root_id, entries) = match existing {
+        Some(log) => {
+            let root = documents::get_tree(pool, &log.id, None, 1).await?.pop();
+            match root {
+                Some(root) => {
+                    let entries = documents::get_tree(pool, &log.id, Some(&root.id), 1)
+                        .await?
+                        .len();
+                    (log.id, root.id, entries)
+                }
+                None => {
+                    // A log without its root cannot be appended to; put the
+                    // root back rather than starting a second log.
+                    let root = root_node(&log.id);
+                    documents::insert_nodes(db.writer(), &log.id, std::slice::from_ref(&root))
+                        .await?;
+                    (log.id, root.id, 0)
+                }
+            }
+        }
+        None => {
+            let document_id = Uuid::new_v4().to_string();
+            let root = root_node(&document_id);
+            documents::insert_document(
+                db.writer(),
+                &document_id,
+                &project_id,
+                LOG_NAME,
+                LOG_MIME,
+                &checksum,
+                1,
+            )
+            .await?;
+            documents::insert_nodes(db.writer(), &document_id, std::slice::from_ref(&root)).await?;
+            (document_id, root.id, 0)
+        }
+    };
+
+    let order = share::citation_order(&answer);
+    let refs = documents::resolve_node_refs(pool, &order).await?;
+    let sources = order
+        .iter()
+        .map(|id| refs.iter().find(|node| &node.id == id))
+        .collect::<Vec<_>>();
+    let nodes = entry_nodes(
+        &document_id,
+        &root_id,
+        entries + 1,
+        &stored.run,
+        &answer,
+        &sources,
+    );
+    documents::insert_nodes(db.writer(), &document_id, &nodes).await?;
+
+    Ok(Some(ResearchLogAppendedEvent {
+        project_id,
+        run_id: run_id.to_string(),
+        document_id,
+        node_id: nodes[0].id.clone(),
+        created,
+    }))
+}
+
+/// The log's root node.
+pub fn root_node(document_id: &str) -> SidecarNode {
+    node(
+        document_id,
+        "root",
+        "Document",
+        None,
+        LOG_NAME,
+        "Answers accepted in this project, in the order they were given.",
+        "root".to_string(),
+        &json!({ "parser": LOG_PARSER }),
+    )
+}
+
+/// The nodes of the `number`th entry: a section titled with the question,
+/// a paragraph per block of the answer, and the sources. Citation markers
+/// become `[n]` pointing into the sources, which name the document and
+/// section each came from; `sources[i]` is `None` for a cited node that no
+/// longer exists. Ordinal paths are zero-padded so entries sort in order.
+pub fn entry_nodes(
+    document_id: &str,
+    root_id: &str,
+    number: usize,
+    run: &ReasoningRun,
+    answer: &AnswerRecord,
+    sources: &[Option<&NodeRef>],
+) -> Vec<SidecarNode> {
+    let metadata = json!({ "parser": LOG_PARSER, "runId": run.id });
+    let ordinal = format!("{number:04}");
+    let section = node(
+        document_id,
+        "s",
+        "Section",
+        Some(root_id),
+        run.query.trim(),
+        &format!(
+            "Asked {}; confidence {:.0}%.",
+            run.started_at.format("%Y-%m-%d %H:%M UTC"),
+            answer.confidence * 100.0
+        ),
+        ordinal.clone(),
+        &metadata,
+    );
+
+    let order = share::citation_order(answer);
+    let body = numbered_citations(&answer.answer_markdown, &order);
+    let mut nodes = vec![];
+    let blocks = body
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|block| !block.is_empty());
+    for (idx, block) in blocks.enumerate() {
+        nodes.push(node(
+            document_id,
+            "p",
+            "Paragraph",
+            Some(&section.id),
+            &format!("\u{00b6} {}", idx + 1),
+            block,
+            format!("{ordinal}.{:04}", idx + 1),
+            &metadata,
+        ));
+    }
+    if !order.is_empty() {
+        let lines = sources
+            .iter()
+            .enumerate()
+            .map(|(idx, source)| match source {
+                Some(source) if source.title.trim().is_empty() => {
+                    format!("[{}] {}", idx + 1, source.document_name)
+                }
+                Some(source) => format!(
+                    "[{}] {} — {}",
+                    idx + 1,
+                    source.document_name,
+                    source.title.trim()
+                ),
+                None => format!("[{}] (source no longer available)", idx + 1),
+            })
+            .collect::<Vec<_>>();
+        nodes.push(node(
+            document_id,
+            "r",
+            "Reference",
+            Some(&section.id),
+            "Sources",
+            &lines.join("\n"),
+            format!("{ordinal}.{:04}", nodes.len() + 1),
+            &metadata,
+        ));
+    }
+    nodes.insert(0, section);
+    nodes
+}
+
+/// `markdown` with each `[citation:id]` replaced by its number in `order`.
+fn numbered_citations(markdown: &str, order: &[String]) -> String {
+    let mut out = String::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find("[citation:") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + "[citation:".len()..];
+        let Some((id, tail)) = after.split_once(']') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        if let Some(idx) = order.iter().position(|cited| cited == id.trim()) {
+            out.push_str(&format!("[{}]", idx + 1));
+        }
+        rest = tail;
+    }
+    out.push_str(rest);
+    out
+}
+
+#[allow(clippy::too_many_arguments)]
+fn node(
+    document_id: &str,
+    prefix: &str,
+    node_type: &str,
+    parent_id: Option<&str>,
+    title: &str,
+    text: &str,
+    ordinal_path: String,
+    metadata: &Value,
+) -> SidecarNode {
+    let mut node = SidecarNode {
+        id: format!("{prefix}-"),
+        parent_id: parent_id.map(str::to_string),
+        node_type: node_type.to_string(),
+        title: title.to_string(),
+        text: text.to_string(),
+        page_start: None,
+        page_end: None,
+        ordinal_path,
+        bbox: Value::Null,
+        metadata: metadata.clone(),
+    };
+    node.id = anchors::stable_node_id(document_id, &node);
+    node
+}
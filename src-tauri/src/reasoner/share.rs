@@ -0,0 +1,300 @@
+//! A run's answer as one self-contained HTML file that can be emailed to
+//! someone without the app: the answer with numbered citations, the cited
+//! passages folded under each number, and the quality scores. Styles are
+//! inline and there are no scripts or external resources.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use sqlx::SqlitePool;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{AnswerRecord, DocumentSummary, QualityMetrics, ReasoningRun},
+    },
+    db::repositories::documents,
+};
+
+/// A cited node with what the reader needs to judge it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CitedSnippet {
+    pub node_id: String,
+    pub document_name: String,
+    pub title: String,
+    pub text: String,
+    pub page_start: Option<i64>,
+    /// The node's document is locked; its title and text are left empty.
+    pub locked: bool,
+}
+
+const STYLE: &str = "body{font-family:system-ui,-apple-system,'Segoe UI',sans-serif;max-width:46rem;margin:2rem auto;padding:0 1rem;line-height:1.55;color:#1f2328}\
+h1{font-size:1.4rem;margin-bottom:.25rem}\
+.meta{color:#59636e;font-size:.9rem;margin-top:0}\
+.badge{display:inline-block;padding:0 .5rem;border-radius:1rem;font-size:.8rem;background:#dafbe1;color:#116329}\
+.badge.warn{background:#fff8c5;color:#7d4e00}\
+sup a{text-decoration:none}\
+details{border:1px solid #d1d9e0;border-radius:.4rem;padding:.4rem .7rem;margin:.5rem 0}\
+summary{cursor:pointer;font-weight:600}\
+blockquote{margin:.5rem 0 0;padding-left:.8rem;border-left:3px solid #d1d9e0;white-space:pre-wrap}\
+table{border-collapse:collapse}td,th{padding:.2rem .8rem .2rem 0;text-align:left}\
+code{background:#f6f8fa;padding:0 .2rem;border-radius:.2rem}";
+
+/// Renders `answer` to `run`'s question. Citations are numbered in the
+/// order the answer first uses them, followed by any it lists without an
+/// inline marker.
+pub fn answer_html(run: &ReasoningRun, answer: &AnswerRecord, snippets: &[CitedSnippet]) -> String {
+    let order = citation_order(answer);
+    let mut html =
+        String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(&run.query)));
+    html.push_str(&format!("<style>{STYLE}</style>\n</head>\n<body>\n"));
+    html.push_str(&format!("<h1>{}</h1>\n", escape(&run.query)));
+    let badge = if answer.grounded {
+        "<span class=\"badge\">grounded</span>"
+    } else {
+        "<span class=\"badge warn\">not grounded</span>"
+    };
+    html.push_str(&format!(
+        "<p class=\"meta\">Answered {} · confidence {:.0}% {badge}</p>\n",
+        run.started_at.format("%Y-%m-%d %H:%M UTC"),
+        answer.confidence * 100.0
+    ));
+
+    html.push_str("<section>\n");
+    html.push_str(&markdown_html(&answer.answer_markdown, &order));
+    html.push_str("</section>\n");
+
+    if !order.is_empty() {
+        html.push_str("<h2>Sources</h2>\n");
+        for (idx, node_id) in order.iter().enumerate() {
+            let number = idx + 1;
+            match snippets.iter().find(|snippet| &snippet.node_id == node_id) {
+                Some(snippet) if snippet.locked => html.push_str(&format!(
+                    "<p id=\"cite-{number}\">[{number}] {} is locked; the passage is left out.</p>\n",
+                    escape(&snippet.document_name)
+                )),
+                Some(snippet) => {
+                    let page = snippet
+                        .page_start
+                        .map(|page| format!(", p. {page}"))
+                        .unwrap_or_default();
+                    let title = match snippet.title.trim() {
+                        "" => String::new(),
+                        title => format!("{} — ", escape(title)),
+                    };
+                    html.push_str(&format!(
+                        "<details id=\"cite-{number}\"><summary>[{number}] {title}{}{page}</summary><blockquote>{}</blockquote></details>\n",
+                        escape(&snippet.document_name),
+                        escape(snippet.text.trim())
+                    ));
+                }
+                None => html.push_str(&format!(
+                    "<p id=\"cite-{number}\">[{number}] Source no longer available.</p>\n"
+                )),
+            }
+        }
+    }
+
+    if let Ok(quality) = serde_json::from_value::<QualityMetrics>(run.quality_json.clone()) {
+        html.push_str("<h2>Quality</h2>\n<table>\n");
+        for (label, value) in [
+            ("Overall", quality.overall),
+            ("Query alignment", quality.query_alignment),
+            ("Citation coverage", quality.citation_coverage),
+            ("Cross-document coverage", quality.cross_document_coverage),
+        ] {
+            html.push_str(&format!(
+                "<tr><th>{label}</th><td>{:.0}%</td></tr>\n",
+                value * 100.0
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// The answer's cited nodes, in [`citation_order`]. Nodes deleted since the
+/// run are left out, and those in locked documents come without their text.
+pub async fn cited_snippets(
+    pool: &SqlitePool,
+    answer: &AnswerRecord,
+) -> AppResult<Vec<CitedSnippet>> {
+    let mut snippets = vec![];
+    let mut documents_by_id: HashMap<String, DocumentSummary> = HashMap::new();
+    for node_id in citation_order(answer) {
+        let node = match documents::get_node(pool, &node_id).await {
+            Ok(node) => node,
+            // Deleted since the run; the report says so.
+            Err(AppError::NotFound(_)) => continue,
+            Err(err) => return Err(err),
+        };
+        let document = match documents_by_id.entry(node.document_id.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(documents::get_document(pool, &node.document_id).await?)
+            }
+        };
+        let locked = document.locked && !document.unlocked;
+        snippets.push(CitedSnippet {
+            node_id,
+            document_name: document.name.clone(),
+            title: if locked { String::new() } else { node.title },
+            text: if locked { String::new() } else { node.text },
+            page_start: node.page_start,
+            locked,
+        });
+    }
+    Ok(snippets)
+}
+
+/// Cited node ids, inline markers first.
+pub fn citation_order(answer: &AnswerRecord) -> Vec<String> {
+    let mut order: Vec<String> = vec![];
+    let marked = answer
+        .answer_markdown
+        .split("[citation:")
+        .skip(1)
+        .filter_map(|rest| rest.split_once(']'))
+        .map(|(id, _)| id.trim().to_string());
+    for id in marked.chain(answer.citations.iter().cloned()) {
+        if !id.is_empty() && !order.contains(&id) {
+            order.push(id);
+        }
+    }
+    order
+}
+
+/// The subset of Markdown answers use: headings, bullet and numbered lists,
+/// paragraphs, bold and inline code.
+fn markdown_html(markdown: &str, order: &[String]) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<String> = vec![];
+    let mut list: Option<&str> = None;
+    let flush = |html: &mut String, paragraph: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+    for line in markdown.lines() {
+        let line = line.trim();
+        let heading = line
+            .find(|c| c != '#')
+            .filter(|&level| (1..=6).contains(&level) && line[level..].starts_with(' '));
+        let item = strip_list_marker(line);
+        if line.is_empty() || heading.is_some() || item.is_some() {
+            flush(&mut html, &mut paragraph);
+        }
+        if let Some(open) = list.filter(|&open| Some(open) != item.map(|(tag, _)| tag)) {
+            html.push_str(&format!("</{open}>\n"));
+            list = None;
+        }
+        if let Some(hashes) = heading {
+            // The page title is the h1.
+            let level = (hashes + 1).min(6);
+            html.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                inline(line[hashes..].trim(), order)
+            ));
+        } else if let Some((tag, text)) = item {
+            if list.is_none() {
+                html.push_str(&format!("<{tag}>\n"));
+                list = Some(tag);
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline(text, order)));
+        } else if !line.is_empty() {
+            paragraph.push(inline(line, order));
+        }
+    }
+    flush(&mut html, &mut paragraph);
+    if let Some(tag) = list {
+        html.push_str(&format!("</{tag}>\n"));
+    }
+    html
+}
+
+/// The list a line belongs to and its text, for `- `, `* ` and `1. ` items.
+fn strip_list_marker(line: &str) -> Option<(&'static str, &str)> {
+    if let Some(text) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(("ul", text));
+    }
+    let digits = line.find(|c: char| !c.is_ascii_digit())?;
+    let text = line[digits..].strip_prefix(". ")?;
+    (digits > 0).then_some(("ol", text))
+}
+
+/// Escapes `text` and renders bold, inline code and citation markers.
+fn inline(text: &str, order: &[String]) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[citation:") {
+        html.push_str(&emphasis(&escape(&rest[..start])));
+        let after = &rest[start + "[citation:".len()..];
+        let Some((id, tail)) = after.split_once(']') else {
+            html.push_str(&emphasis(&escape(&rest[start..])));
+            return html;
+        };
+        if let Some(idx) = order.iter().position(|cited| cited == id.trim()) {
+            let number = idx + 1;
+            html.push_str(&format!(
+                "<sup><a href=\"#cite-{number}\">[{number}]</a></sup>"
+            ));
+        }
+        rest = tail;
+    }
+    html.push_str(&emphasis(&escape(rest)));
+    html
+}
+
+/// `**bold**` and `` `code` `` in already escaped text.
+fn emphasis(text: &str) -> String {
+    let parts = text.split("**").collect::<Vec<_>>();
+    let mut html = String::new();
+    for (idx, part) in parts.iter().enumerate() {
+        // Odd parts sit between a pair of markers; an unpaired last marker
+        // is kept as written.
+        let paired = idx % 2 == 1 && idx + 1 < parts.len();
+        let part = code(part);
+        if paired {
+            html.push_str(&format!("<strong>{part}</strong>"));
+        } else {
+            if idx % 2 == 1 {
+                html.push_str("**");
+            }
+            html.push_str(&part);
+        }
+    }
+    html
+}
+
+fn code(text: &str) -> String {
+    let parts = text.split('`').collect::<Vec<_>>();
+    let mut html = String::new();
+    for (idx, part) in parts.iter().enumerate() {
+        if idx % 2 == 1 && idx + 1 < parts.len() {
+            html.push_str(&format!("<code>{part}</code>"));
+        } else {
+            if idx % 2 == 1 {
+                html.push('`');
+            }
+            html.push_str(part);
+        }
+    }
+    html
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,99 @@
+//! Breaks a stored run's time down by phase, step and provider call, so a
+//! slow run shows whether it waited on the model or on retrieval.
+
+use crate::{
+    core::types::{
+        GetRunResponse, PhaseTiming, ProviderCallTiming, RunPhase, RunTimeline, StepTiming,
+    },
+    db::repositories::reasoning::RecordedProviderResponse,
+    providers::CallKind,
+};
+
+pub fn build_timeline(run: &GetRunResponse, calls: &[RecordedProviderResponse]) -> RunTimeline {
+    let steps = run
+        .steps
+        .iter()
+        .map(|step| StepTiming {
+            idx: step.idx,
+            step_type: step.step_type.clone(),
+            phase: phase_for_step(&step.step_type),
+            latency_ms: step.latency_ms,
+        })
+        .collect::<Vec<_>>();
+    let call_ms = |kind: CallKind| {
+        calls
+            .iter()
+            .filter(|call| call.call_kind == kind.as_str())
+            .map(|call| call.latency_ms)
+            .sum::<i64>()
+    };
+    let step_ms = |phase: RunPhase| {
+        steps
+            .iter()
+            .filter(|step| step.phase == phase)
+            .map(|step| step.latency_ms)
+            .sum::<i64>()
+    };
+
+    // Planner calls run between steps; synthesis calls run inside the
+    // synthesize step.
+    let planner_ms = call_ms(CallKind::Planner);
+    let synthesis_calls_ms = call_ms(CallKind::Synthesis);
+    let phases = vec![
+        PhaseTiming {
+            phase: RunPhase::Planning,
+            duration_ms: planner_ms,
+            provider_ms: planner_ms,
+        },
+        PhaseTiming {
+            phase: RunPhase::Retrieval,
+            duration_ms: step_ms(RunPhase::Retrieval),
+            provider_ms: 0,
+        },
+        PhaseTiming {
+            phase: RunPhase::Synthesis,
+            duration_ms: step_ms(RunPhase::Synthesis),
+            provider_ms: synthesis_calls_ms.min(step_ms(RunPhase::Synthesis)),
+        },
+        PhaseTiming {
+            phase: RunPhase::Validation,
+            duration_ms: step_ms(RunPhase::Validation),
+            provider_ms: 0,
+        },
+    ];
+
+    let accounted = phases.iter().map(|phase| phase.duration_ms).sum::<i64>();
+    let db_ms = phases
+        .iter()
+        .map(|phase| (phase.duration_ms - phase.provider_ms).max(0))
+        .sum();
+    let total_ms = run.run.total_latency_ms;
+
+    RunTimeline {
+        run_id: run.run.id.clone(),
+        total_ms,
+        provider_ms: calls.iter().map(|call| call.latency_ms).sum(),
+        db_ms,
+        unaccounted_ms: total_ms.map(|total| (total - accounted).max(0)),
+        phases,
+        steps,
+        provider_calls: calls
+            .iter()
+            .map(|call| ProviderCallTiming {
+                seq: call.seq,
+                call_kind: call.call_kind.clone(),
+                model: call.model.clone(),
+                latency_ms: call.latency_ms,
+                cached: call.cached,
+            })
+            .collect(),
+    }
+}
+
+fn phase_for_step(step_type: &str) -> RunPhase {
+    match step_type {
+        "synthesize" => RunPhase::Synthesis,
+        "self_check" => RunPhase::Validation,
+        _ => RunPhase::Retrieval,
+    }
+}
@@ -0,0 +1,66 @@
+//! The ed25519 key that signs shared bundles. It is made on the first export
+//! and kept in the OS keyring, and its public half travels in each bundle's
+//! manifest. A signature shows the bundle is unchanged since that key signed
+//! it; recipients tell who holds the key by comparing it with the one the
+//! sender gives them.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519},
+};
+
+use crate::{
+    core::errors::{AppError, AppResult},
+    security::keyring,
+};
+
+pub struct BundleSigner {
+    pair: Ed25519KeyPair,
+}
+
+impl BundleSigner {
+    /// The key in the keyring, made and stored there on first use.
+    pub fn load_or_create() -> AppResult<Self> {
+        if let Some(stored) = keyring::get_bundle_signing_key()? {
+            let pkcs8 = STANDARD
+                .decode(stored.as_bytes())
+                .map_err(|err| AppError::Internal(format!("bundle signing key: {err}")))?;
+            return Self::from_pkcs8(&pkcs8);
+        }
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| AppError::Internal("cannot make a bundle signing key".to_string()))?;
+        keyring::set_bundle_signing_key(&STANDARD.encode(pkcs8.as_ref()))?;
+        Self::from_pkcs8(pkcs8.as_ref())
+    }
+
+    fn from_pkcs8(pkcs8: &[u8]) -> AppResult<Self> {
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8)
+            .map_err(|err| AppError::Internal(format!("bundle signing key: {err}")))?;
+        Ok(Self { pair })
+    }
+
+    /// The public key, in base64.
+    pub fn public_key(&self) -> String {
+        STANDARD.encode(self.pair.public_key().as_ref())
+    }
+
+    /// Signs `message`; the signature is in base64.
+    pub fn sign(&self, message: &[u8]) -> String {
+        STANDARD.encode(self.pair.sign(message).as_ref())
+    }
+}
+
+/// Whether `signature` is `public_key`'s signature of `message`, both in
+/// base64 as [`BundleSigner`] writes them.
+pub fn verify(public_key: &str, message: &[u8], signature: &str) -> bool {
+    let (Ok(public_key), Ok(signature)) = (
+        STANDARD.decode(public_key.as_bytes()),
+        STANDARD.decode(signature.as_bytes()),
+    ) else {
+        return false;
+    };
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(message, &signature)
+        .is_ok()
+}
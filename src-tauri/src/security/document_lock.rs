@@ -0,0 +1,136 @@
+//! The optional passphrase that guards locked documents on a shared machine.
+//! Only a PBKDF2-HMAC-SHA256 hash is stored, in `app_settings`, along with
+//! its salt and iteration count; with no passphrase set, locked documents
+//! unlock without one.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{
+    core::errors::{AppError, AppResult},
+    db::repositories::settings,
+};
+
+/// `app_settings` key holding the [`DocumentLockSettings`].
+pub const DOCUMENT_LOCK_SETTING: &str = "document_lock";
+/// How long an unlock lasts.
+pub const UNLOCK_SECONDS: i64 = 15 * 60;
+
+const SCHEME: &str = "pbkdf2-sha256";
+/// Iterations for new hashes; each hash records its own, so raising this
+/// leaves existing passphrases working.
+const ITERATIONS: u32 = 600_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DocumentLockSettings {
+    /// `pbkdf2-sha256$iterations$salt$hash`, salt and hash in base64.
+    pub passphrase_hash: Option<String>,
+}
+
+/// Hashes `passphrase` under a fresh random salt.
+pub fn hash_passphrase(passphrase: &str) -> String {
+    let mut salt = Vec::with_capacity(32);
+    salt.extend_from_slice(Uuid::new_v4().as_bytes());
+    salt.extend_from_slice(Uuid::new_v4().as_bytes());
+    let hash = derive(&salt, passphrase, ITERATIONS);
+    format!(
+        "{SCHEME}${ITERATIONS}${}${}",
+        STANDARD.encode(&salt),
+        STANDARD.encode(hash)
+    )
+}
+
+/// Whether `passphrase` matches `stored`, a value from [`hash_passphrase`].
+/// A malformed `stored` matches nothing.
+pub fn verify_passphrase(stored: &str, passphrase: &str) -> bool {
+    let mut parts = stored.split('$');
+    let (Some(SCHEME), Some(iterations), Some(salt), Some(hash), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return false;
+    };
+    let (Ok(iterations), Ok(salt), Ok(hash)) = (
+        iterations.parse::<u32>(),
+        STANDARD.decode(salt),
+        STANDARD.decode(hash),
+    ) else {
+        return false;
+    };
+    let computed = derive(&salt, passphrase, iterations.max(1));
+    // Compare every byte so the time taken does not give the match away.
+    hash.len() == computed.len()
+        && hash
+            .iter()
+            .zip(computed.iter())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn derive(salt: &[u8], passphrase: &str, iterations: u32) -> [u8; 32] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(passphrase.as_bytes(), salt, iterations)
+}
+
+/// Runs a hash derivation on the blocking pool; it takes long enough to
+/// stall the async workers.
+async fn off_runtime<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> AppResult<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|err| AppError::Internal(err.to_string()))
+}
+
+pub async fn passphrase_set(pool: &SqlitePool) -> AppResult<bool> {
+    let lock: DocumentLockSettings = settings::get_setting(pool, DOCUMENT_LOCK_SETTING).await?;
+    Ok(lock.passphrase_hash.is_some())
+}
+
+/// Fails unless `given` matches the passphrase; passes when none is set.
+pub async fn check_passphrase(pool: &SqlitePool, given: Option<&str>) -> AppResult<()> {
+    let lock: DocumentLockSettings = settings::get_setting(pool, DOCUMENT_LOCK_SETTING).await?;
+    let Some(stored) = lock.passphrase_hash else {
+        return Ok(());
+    };
+    let given = given.unwrap_or_default().to_string();
+    if off_runtime(move || verify_passphrase(&stored, &given)).await? {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(
+            "the passphrase does not match".to_string(),
+        ))
+    }
+}
+
+/// Sets, changes or (with `None`) removes the passphrase. Once one is set,
+/// `current` must match it.
+pub async fn set_passphrase(
+    pool: &SqlitePool,
+    current: Option<&str>,
+    passphrase: Option<&str>,
+) -> AppResult<()> {
+    check_passphrase(pool, current).await?;
+    let passphrase_hash = match passphrase {
+        Some(passphrase) if passphrase.trim().is_empty() => {
+            return Err(AppError::InvalidInput(
+                "the passphrase cannot be empty".to_string(),
+            ))
+        }
+        Some(passphrase) => {
+            let passphrase = passphrase.to_string();
+            Some(off_runtime(move || hash_passphrase(&passphrase)).await?)
+        }
+        None => None,
+    };
+    settings::set_setting(
+        pool,
+        DOCUMENT_LOCK_SETTING,
+        &DocumentLockSettings { passphrase_hash },
+    )
+    .await
+}
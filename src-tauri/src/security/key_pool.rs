@@ -0,0 +1,208 @@
+//! Selection among several API keys stored for one provider.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{GenerationSettings, KeySelection, Provider, ProviderKeyRecord},
+    },
+    db::repositories::provider_keys,
+    providers::{CallKind, ImagePart, LlmProvider, ProviderResponse},
+    security::keyring,
+};
+
+/// How long a key is passed over after the provider rate-limits it.
+pub const RATE_LIMIT_COOLDOWN_SECS: i64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyEntry {
+    /// Registered key id; `None` for ad-hoc keys that are not usage-tracked.
+    pub id: Option<String>,
+    pub secret: String,
+}
+
+/// The keys a run may use, tried in selection order with failover on 429.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyPool {
+    entries: Vec<ApiKeyEntry>,
+    selection: KeySelection,
+}
+
+impl ApiKeyPool {
+    pub fn new(entries: Vec<ApiKeyEntry>, selection: KeySelection) -> Self {
+        Self { entries, selection }
+    }
+
+    pub fn single(secret: impl Into<String>) -> Self {
+        Self::new(
+            vec![ApiKeyEntry {
+                id: None,
+                secret: secret.into(),
+            }],
+            KeySelection::default(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Keys in the order they should be tried for the next call. Providers that
+    /// need no credentials (replay, fixtures) run with an empty pool, which
+    /// yields a single blank key.
+    pub async fn ordered(&self, pool: &SqlitePool) -> AppResult<Vec<ApiKeyEntry>> {
+        if self.entries.is_empty() {
+            return Ok(vec![ApiKeyEntry {
+                id: None,
+                secret: String::new(),
+            }]);
+        }
+        let mut records = Vec::new();
+        for entry in &self.entries {
+            if let Some(id) = &entry.id {
+                match provider_keys::get_key(pool, id).await {
+                    Ok(record) => records.push(record),
+                    Err(AppError::NotFound(_)) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        let order = order_for_use(&records, self.selection, Utc::now());
+        let mut ordered = order
+            .iter()
+            .filter_map(|id| {
+                self.entries
+                    .iter()
+                    .find(|entry| entry.id.as_deref() == Some(id.as_str()))
+                    .cloned()
+            })
+            .collect::<Vec<_>>();
+        ordered.extend(
+            self.entries
+                .iter()
+                .filter(|entry| {
+                    entry
+                        .id
+                        .as_ref()
+                        .map_or(true, |id| !order.iter().any(|ordered_id| ordered_id == id))
+                })
+                .cloned(),
+        );
+        Ok(ordered)
+    }
+
+    /// Calls `provider` with each key in selection order, moving on when one is
+    /// rate limited or out of quota, and records usage against the key that
+    /// answered. A key out of quota is passed over until the provider said it
+    /// resets. When every key failed on quota, that error is returned.
+    pub async fn complete(
+        &self,
+        pool: &SqlitePool,
+        provider: &dyn LlmProvider,
+        kind: CallKind,
+        prompt: &str,
+        settings: &GenerationSettings,
+        images: &[ImagePart],
+    ) -> AppResult<ProviderResponse> {
+        let mut quota_error = None;
+        for key in self.ordered(pool).await? {
+            match provider
+                .complete_with_images(&key.secret, kind, prompt, settings, images)
+                .await
+            {
+                Ok(response) => {
+                    if let Some(id) = &key.id {
+                        let tokens = response
+                            .token_usage
+                            .get("totalTokenCount")
+                            .and_then(Value::as_i64)
+                            .unwrap_or(0);
+                        provider_keys::record_key_usage(pool, id, tokens).await?;
+                    }
+                    return Ok(response);
+                }
+                Err(AppError::ProviderRateLimited) => {
+                    if let Some(id) = &key.id {
+                        provider_keys::mark_rate_limited(pool, id, RATE_LIMIT_COOLDOWN_SECS)
+                            .await?;
+                    }
+                }
+                Err(err @ AppError::ProviderQuotaExceeded { .. }) => {
+                    if let Some(id) = &key.id {
+                        let cooldown = err
+                            .retry_after_secs()
+                            .map_or(RATE_LIMIT_COOLDOWN_SECS, |secs| {
+                                secs.min(i64::MAX as u64) as i64
+                            });
+                        provider_keys::mark_rate_limited(pool, id, cooldown).await?;
+                    }
+                    quota_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(quota_error.unwrap_or(AppError::ProviderRateLimited))
+    }
+}
+
+/// Orders registered keys for use. Keys that are cooling down after a 429 or
+/// have spent their daily quota go last, so they are only tried when every
+/// other key has failed.
+pub fn order_for_use(
+    records: &[ProviderKeyRecord],
+    selection: KeySelection,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let remaining = |record: &ProviderKeyRecord| {
+        record
+            .daily_request_quota
+            .map(|quota| quota - record.requests_today)
+            .unwrap_or(i64::MAX)
+    };
+    let (mut available, mut parked): (Vec<&ProviderKeyRecord>, Vec<&ProviderKeyRecord>) =
+        records.iter().partition(|record| {
+            record.cooldown_until.map_or(true, |until| until <= now) && remaining(record) > 0
+        });
+
+    match selection {
+        KeySelection::RoundRobin => available.sort_by_key(|record| record.last_used_at),
+        KeySelection::QuotaAware => available.sort_by(|a, b| {
+            remaining(b)
+                .cmp(&remaining(a))
+                .then(a.last_used_at.cmp(&b.last_used_at))
+        }),
+    }
+    parked.sort_by_key(|record| record.cooldown_until);
+
+    available
+        .into_iter()
+        .chain(parked)
+        .map(|record| record.id.clone())
+        .collect()
+}
+
+/// Loads every stored key for `provider`. Falls back to the single key saved
+/// before key rotation existed when no keys are registered.
+pub async fn load_key_pool(pool: &SqlitePool, provider: Provider) -> AppResult<ApiKeyPool> {
+    let selection = provider_keys::get_key_selection(pool).await?;
+    let mut entries = Vec::new();
+    for record in provider_keys::list_keys(pool, &provider).await? {
+        if let Ok(secret) = keyring::get_key_secret(&provider, &record.id) {
+            entries.push(ApiKeyEntry {
+                id: Some(record.id),
+                secret,
+            });
+        }
+    }
+    if entries.is_empty() {
+        return Ok(ApiKeyPool::single(keyring::get_provider_key(provider)?));
+    }
+    Ok(ApiKeyPool::new(entries, selection))
+}
@@ -3,24 +3,69 @@ use crate::core::{
     types::Provider,
 };
 
-fn username_for_provider(provider: &Provider) -> &'static str {
-    match provider {
-        Provider::Gemini => "gemini",
+/// Key id used for the key set through `set_provider_key`; it keeps the
+/// keyring username used before multiple keys were supported.
+pub const DEFAULT_KEY_ID: &str = "default";
+/// Keyring username of the key that signs shared bundles.
+const BUNDLE_SIGNING_USER: &str = "bundle-signing-key";
+
+fn username_for_key(provider: &Provider, key_id: &str) -> String {
+    let base = provider.as_str();
+    if key_id == DEFAULT_KEY_ID {
+        base.to_string()
+    } else {
+        format!("{base}:{key_id}")
     }
 }
 
+fn entry_for_key(provider: &Provider, key_id: &str) -> AppResult<keyring::Entry> {
+    keyring::Entry::new("vectorless", &username_for_key(provider, key_id))
+        .map_err(|err| AppError::Internal(err.to_string()))
+}
+
 pub fn set_provider_key(provider: Provider, api_key: &str) -> AppResult<()> {
-    let entry = keyring::Entry::new("vectorless", username_for_provider(&provider))
-        .map_err(|err| AppError::Internal(err.to_string()))?;
-    entry
+    set_key_secret(&provider, DEFAULT_KEY_ID, api_key)
+}
+
+pub fn get_provider_key(provider: Provider) -> AppResult<String> {
+    get_key_secret(&provider, DEFAULT_KEY_ID)
+}
+
+pub fn set_key_secret(provider: &Provider, key_id: &str, api_key: &str) -> AppResult<()> {
+    entry_for_key(provider, key_id)?
         .set_password(api_key)
         .map_err(|err| AppError::Internal(err.to_string()))
 }
 
-pub fn get_provider_key(provider: Provider) -> AppResult<String> {
-    let entry = keyring::Entry::new("vectorless", username_for_provider(&provider))
-        .map_err(|err| AppError::Internal(err.to_string()))?;
-    entry
+pub fn get_key_secret(provider: &Provider, key_id: &str) -> AppResult<String> {
+    entry_for_key(provider, key_id)?
         .get_password()
         .map_err(|_err| AppError::ProviderAuth)
 }
+
+pub fn delete_key_secret(provider: &Provider, key_id: &str) -> AppResult<()> {
+    match entry_for_key(provider, key_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(AppError::Internal(err.to_string())),
+    }
+}
+
+fn bundle_signing_entry() -> AppResult<keyring::Entry> {
+    keyring::Entry::new("vectorless", BUNDLE_SIGNING_USER)
+        .map_err(|err| AppError::Internal(err.to_string()))
+}
+
+/// The stored bundle signing key, `None` before the first export.
+pub fn get_bundle_signing_key() -> AppResult<Option<String>> {
+    match bundle_signing_entry()?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(AppError::Internal(err.to_string())),
+    }
+}
+
+pub fn set_bundle_signing_key(key: &str) -> AppResult<()> {
+    bundle_signing_entry()?
+        .set_password(key)
+        .map_err(|err| AppError::Internal(err.to_string()))
+}
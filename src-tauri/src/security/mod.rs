@@ -1 +1,4 @@
+pub mod bundle_signing;
+pub mod document_lock;
+pub mod key_pool;
 pub mod keyring;
@@ -0,0 +1,136 @@
+//! Stable node ids ("anchors"). A node's id is derived from its document, its
+//! ordinal path and a hash of its content, so re-ingesting an unchanged document
+//! reproduces the same ids. Nodes that moved or changed get new ids, and
+//! [`remap_node_ids`] works out which old id each of them replaces.
+
+use std::collections::{HashMap, HashSet};
+
+use sha2::{Digest, Sha256};
+
+use crate::sidecar::types::{NormalizedPayload, SidecarNode};
+
+/// Hex digits of the hash kept in an id, after the parser's type prefix.
+const ANCHOR_HASH_LEN: usize = 32;
+
+/// What re-ingestion compares to match an old node with its replacement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeFingerprint {
+    pub id: String,
+    pub node_type: String,
+    pub ordinal_path: String,
+    pub content_hash: String,
+}
+
+impl NodeFingerprint {
+    pub fn of(node: &SidecarNode) -> Self {
+        Self {
+            id: node.id.clone(),
+            node_type: node.node_type.clone(),
+            ordinal_path: node.ordinal_path.clone(),
+            content_hash: content_hash(&node.node_type, &node.title, &node.text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRemap {
+    pub old_id: String,
+    pub new_id: String,
+}
+
+pub fn content_hash(node_type: &str, title: &str, text: &str) -> String {
+    let node_type = node_type.to_ascii_lowercase();
+    let mut hasher = Sha256::new();
+    for part in [node_type.as_str(), title, text] {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Id for `node` within `document_id`, keeping the parser's prefix (`root`,
+/// `s`, `p`) so ids stay readable.
+pub fn stable_node_id(document_id: &str, node: &SidecarNode) -> String {
+    let prefix = node.id.split_once('-').map_or("n", |(prefix, _)| prefix);
+    let mut hasher = Sha256::new();
+    hasher.update(document_id.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(node.ordinal_path.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(content_hash(&node.node_type, &node.title, &node.text).as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    format!("{prefix}-{}", &digest[..ANCHOR_HASH_LEN])
+}
+
+/// Replaces the parser's random ids with stable ones, rewriting parent links
+/// and edges to match.
+pub fn assign_stable_ids(document_id: &str, payload: &mut NormalizedPayload) {
+    let renamed = payload
+        .nodes
+        .iter()
+        .map(|node| (node.id.clone(), stable_node_id(document_id, node)))
+        .collect::<HashMap<_, _>>();
+    let rename = |id: &mut String| {
+        if let Some(stable) = renamed.get(id.as_str()) {
+            *id = stable.clone();
+        }
+    };
+    for node in &mut payload.nodes {
+        rename(&mut node.id);
+        if let Some(parent_id) = node.parent_id.as_mut() {
+            rename(parent_id);
+        }
+    }
+    for edge in &mut payload.edges {
+        rename(&mut edge.from);
+        rename(&mut edge.to);
+    }
+}
+
+/// Pairs each old node that has no counterpart with the same id in `new` with
+/// the new node replacing it: first a node with identical content (it moved),
+/// then one of the same type at the same ordinal path (it was edited). Old
+/// nodes matching neither were removed and get no remap.
+pub fn remap_node_ids(old: &[NodeFingerprint], new: &[NodeFingerprint]) -> Vec<NodeRemap> {
+    let old_ids = old
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect::<HashSet<_>>();
+    let new_ids = new
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect::<HashSet<_>>();
+    let gone = old
+        .iter()
+        .filter(|node| !new_ids.contains(node.id.as_str()))
+        .collect::<Vec<_>>();
+    let mut candidates = new
+        .iter()
+        .filter(|node| !old_ids.contains(node.id.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut remaps = Vec::new();
+    let mut unmatched = Vec::new();
+    for node in gone {
+        match candidates.iter().position(|candidate| {
+            candidate.node_type == node.node_type && candidate.content_hash == node.content_hash
+        }) {
+            Some(index) => remaps.push(NodeRemap {
+                old_id: node.id.clone(),
+                new_id: candidates.remove(index).id.clone(),
+            }),
+            None => unmatched.push(node),
+        }
+    }
+    for node in unmatched {
+        if let Some(index) = candidates.iter().position(|candidate| {
+            candidate.node_type == node.node_type && candidate.ordinal_path == node.ordinal_path
+        }) {
+            remaps.push(NodeRemap {
+                old_id: node.id.clone(),
+                new_id: candidates.remove(index).id.clone(),
+            });
+        }
+    }
+    remaps
+}
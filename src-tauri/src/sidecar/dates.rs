@@ -0,0 +1,330 @@
+//! Dates in node text ("2023-03-14", "14 March 2023", "March 2023", "Q1
+//! 2023", "2023"), read at parse time into each node's `dates` metadata as
+//! ISO-8601 first and last days, and the period a question asks about
+//! ("events in 2023", "before March 2021") as a [`DateRange`].
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{core::types::DateRange, sidecar::types::NormalizedPayload};
+
+/// Most dates recorded for one node.
+const MAX_DATES_PER_NODE: usize = 32;
+
+/// Years a bare four-digit number is read as; others are more likely counts.
+const BARE_YEARS: std::ops::RangeInclusive<i32> = 1900..=2099;
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Words before a date in a question that make it one end of the period.
+const BEFORE: &[&str] = &["before", "prior to", "earlier than"];
+const UNTIL: &[&str] = &["until", "till", "through", "by", "up to"];
+const AFTER: &[&str] = &["after", "later than"];
+const SINCE: &[&str] = &["since", "from", "starting"];
+/// Words between two dates that make them the ends of one period.
+const RANGE_JOINERS: &[&str] = &["and", "to", "until", "through", "-", "–"];
+
+/// A date or period as a node's text writes it, by its first and last day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateMention {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub text: String,
+}
+
+/// A period found in text, by byte offsets.
+struct Found {
+    start: usize,
+    end: usize,
+    first: NaiveDate,
+    last: NaiveDate,
+}
+
+/// A run of letters and digits, by byte offsets.
+struct Word<'a> {
+    start: usize,
+    end: usize,
+    text: &'a str,
+}
+
+/// The dates in `text`, in order of appearance.
+pub fn extract(text: &str) -> Vec<DateMention> {
+    scan(text)
+        .into_iter()
+        .take(MAX_DATES_PER_NODE)
+        .map(|found| DateMention {
+            start: found.first,
+            end: found.last,
+            text: text[found.start..found.end].to_string(),
+        })
+        .collect()
+}
+
+/// Records each node's dates as its `dates` metadata.
+pub fn record(payload: &mut NormalizedPayload) {
+    for node in &mut payload.nodes {
+        let dates = extract(&node.text);
+        if dates.is_empty() {
+            continue;
+        }
+        if let Some(object) = node.metadata.as_object_mut() {
+            object.insert(
+                "dates".to_string(),
+                serde_json::to_value(dates).unwrap_or_default(),
+            );
+        }
+    }
+}
+
+/// The period `query` asks about, read from its first date: "in 2023" is
+/// that year, "before 2020" ends on 2019-12-31, "since May 2021" starts on
+/// 2021-05-01 and "between 2019 and 2021" spans both years.
+pub fn range_in(query: &str) -> Option<DateRange> {
+    let found = scan(query);
+    let first = found.first()?;
+    let lead = last_words(&query[..first.start]);
+    let leads_with = |phrases: &[&str]| {
+        phrases
+            .iter()
+            .any(|phrase| lead.ends_with(&format!(" {phrase}")))
+    };
+    if let Some(second) = found.get(1) {
+        let joiner = query[first.end..second.start].trim().to_lowercase();
+        if RANGE_JOINERS.contains(&joiner.as_str()) && (joiner != "and" || leads_with(&["between"]))
+        {
+            return Some(DateRange {
+                from: Some(first.first.min(second.first)),
+                to: Some(first.last.max(second.last)),
+            });
+        }
+    }
+    let (from, to) = if leads_with(BEFORE) {
+        (None, first.first.pred_opt())
+    } else if leads_with(UNTIL) {
+        (None, Some(first.last))
+    } else if leads_with(AFTER) {
+        (first.last.succ_opt(), None)
+    } else if leads_with(SINCE) {
+        (Some(first.first), None)
+    } else {
+        (Some(first.first), Some(first.last))
+    };
+    Some(DateRange { from, to })
+}
+
+/// The last two words of `text`, lowercase, with a leading space so a
+/// phrase only matches whole words.
+fn last_words(text: &str) -> String {
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    let tail = &words[words.len().saturating_sub(2)..];
+    format!(" {}", tail.join(" ").to_lowercase())
+}
+
+fn words(text: &str) -> Vec<Word<'_>> {
+    let mut words = vec![];
+    let mut start = None;
+    for (offset, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(offset),
+            (Some(begin), false) => {
+                words.push(Word {
+                    start: begin,
+                    end: offset,
+                    text: &text[begin..offset],
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Every date in `text`, the longest reading at each word: a full date
+/// before a month, a month before a bare year.
+fn scan(text: &str) -> Vec<Found> {
+    let words = words(text);
+    let gap = |index: usize| &text[words[index].end..words[index + 1].start];
+    // Spaces, optionally after a comma or an abbreviation's full stop.
+    let spaced = |index: usize| {
+        let gap = gap(index);
+        matches!(gap.trim(), "" | "," | ".") && gap.ends_with(' ')
+    };
+    let mut found = vec![];
+    let mut index = 0;
+    while index < words.len() {
+        let word = |offset: usize| words.get(index + offset).map(|word| word.text);
+        let has = |count: usize| index + count < words.len();
+        let mut period = None;
+
+        // 2023-03-14 or 2023-03.
+        if let (Some(year), Some(month)) = (word(0).and_then(year), word(1).and_then(month_number))
+        {
+            if gap(index) == "-" {
+                let day = word(2)
+                    .filter(|_| gap(index + 1) == "-")
+                    .and_then(|day| {
+                        day.get(..2)
+                            .filter(|_| day.len() == 2 || day[2..].starts_with('T'))
+                    })
+                    .and_then(|day| day.parse::<u32>().ok())
+                    .and_then(|day| NaiveDate::from_ymd_opt(year, month, day));
+                period = match day {
+                    Some(date) => Some((3, date, date)),
+                    None => month_period(year, month).map(|(first, last)| (2, first, last)),
+                };
+            }
+        }
+        // 14 March 2023.
+        if period.is_none() && has(2) && spaced(index) && spaced(index + 1) {
+            if let (Some(day), Some(month), Some(year)) = (
+                word(0).and_then(day_of_month),
+                word(1).and_then(month_name),
+                word(2).and_then(year),
+            ) {
+                period = NaiveDate::from_ymd_opt(year, month, day).map(|date| (3, date, date));
+            }
+        }
+        // March 14, 2023.
+        if period.is_none() && has(2) && spaced(index) && spaced(index + 1) {
+            if let (Some(month), Some(day), Some(year)) = (
+                word(0).and_then(month_name),
+                word(1).and_then(day_of_month),
+                word(2).and_then(year),
+            ) {
+                period = NaiveDate::from_ymd_opt(year, month, day).map(|date| (3, date, date));
+            }
+        }
+        // March 2023, Q1 2023.
+        if period.is_none() && has(1) && spaced(index) {
+            if let Some(year) = word(1).and_then(year) {
+                let first = word(0).unwrap_or_default();
+                period = match (month_name(first), quarter(first)) {
+                    (Some(month), _) => month_period(year, month),
+                    (None, Some(quarter)) => quarter_period(year, quarter),
+                    _ => None,
+                }
+                .map(|(first, last)| (2, first, last));
+            }
+        }
+        // 2023, unless it is part of a larger number or an amount.
+        if period.is_none() {
+            let before = text[..words[index].start].chars().next_back();
+            let after = text[words[index].end..].chars().next();
+            let in_number = before.is_some_and(|c| matches!(c, '.' | ',' | '$' | '€' | '£'))
+                || after.is_some_and(|c| c == '%')
+                || (matches!(after, Some('.' | ','))
+                    && text[words[index].end + 1..].starts_with(|c: char| c.is_ascii_digit()));
+            if let Some(year) = word(0)
+                .and_then(year)
+                .filter(|year| BARE_YEARS.contains(year))
+            {
+                if !in_number {
+                    period = NaiveDate::from_ymd_opt(year, 1, 1)
+                        .zip(NaiveDate::from_ymd_opt(year, 12, 31))
+                        .map(|(first, last)| (1, first, last));
+                }
+            }
+        }
+
+        match period {
+            Some((count, first, last)) => {
+                let last_word = &words[index + count - 1];
+                // A timestamp's date ends before its "T".
+                let end = match last_word.text.find('T') {
+                    Some(time) if count == 3 => last_word.start + time,
+                    _ => last_word.end,
+                };
+                found.push(Found {
+                    start: words[index].start,
+                    end,
+                    first,
+                    last,
+                });
+                index += count;
+            }
+            None => index += 1,
+        }
+    }
+    found
+}
+
+fn year(word: &str) -> Option<i32> {
+    (word.len() == 4 && word.chars().all(|c| c.is_ascii_digit()))
+        .then(|| word.parse().ok())
+        .flatten()
+}
+
+fn month_number(word: &str) -> Option<u32> {
+    (word.len() == 2)
+        .then(|| word.parse().ok())
+        .flatten()
+        .filter(|month| (1..=12).contains(month))
+}
+
+/// "March", "Mar" or "Sept".
+fn month_name(word: &str) -> Option<u32> {
+    let word = word.to_lowercase();
+    if word.len() < 3 || !word.chars().all(char::is_alphabetic) {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| {
+            *month == word
+                || word == "sept" && *month == "september"
+                || (word.len() == 3 && month.starts_with(&word))
+        })
+        .map(|index| index as u32 + 1)
+}
+
+/// "14", "1st" or "22nd".
+fn day_of_month(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") || digits.len() > 2 {
+        return None;
+    }
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+fn quarter(word: &str) -> Option<u32> {
+    let digit = word.strip_prefix(['Q', 'q'])?;
+    digit
+        .parse()
+        .ok()
+        .filter(|quarter| (1..=4).contains(quarter))
+}
+
+fn month_period(year: i32, month: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some((first, next.pred_opt()?))
+}
+
+fn quarter_period(year: i32, quarter: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let (first, _) = month_period(year, quarter * 3 - 2)?;
+    let (_, last) = month_period(year, quarter * 3)?;
+    Some((first, last))
+}
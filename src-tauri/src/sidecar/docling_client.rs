@@ -0,0 +1,371 @@
+//! External parser integration. Users can point the app at a high-fidelity
+//! parser (Docling or any tool speaking the same contract) and route chosen
+//! MIME types to it instead of the native heuristics.
+//!
+//! Contract: the executable is started with the configured arguments and
+//! receives one JSON object on stdin, `{"file_path": "...", "mime_type": "..."}`.
+//! It writes one JSON object to stdout, either a payload
+//! `{"document": {...}, "nodes": [...], "edges": [...]}` or `{"error": "..."}`,
+//! and exits. A non-zero exit, invalid JSON or running past the timeout is an
+//! error. Nodes use snake_case keys and `type` for the node type; everything
+//! but `id` and `type` is optional.
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    io::{Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        types::ExternalParserConfig,
+    },
+    sidecar::types::{NormalizedPayload, SidecarDocument, SidecarEdge, SidecarNode},
+};
+
+/// How much of the parser's stderr is quoted in an error.
+const STDERR_TAIL_BYTES: usize = 2000;
+/// Longest timeout a user can set, in seconds.
+const MAX_TIMEOUT_SECS: u64 = 3600;
+
+#[derive(Debug, Deserialize)]
+struct RawPayload {
+    #[serde(default)]
+    document: RawDocument,
+    #[serde(default)]
+    nodes: Vec<RawNode>,
+    #[serde(default)]
+    edges: Vec<SidecarEdge>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawDocument {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    pages: Option<i64>,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawNode {
+    id: String,
+    #[serde(default)]
+    parent_id: Option<String>,
+    #[serde(rename = "type", alias = "node_type")]
+    node_type: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    page_start: Option<i64>,
+    #[serde(default)]
+    page_end: Option<i64>,
+    #[serde(default)]
+    ordinal_path: String,
+    #[serde(default)]
+    bbox: Value,
+    #[serde(default)]
+    metadata: Value,
+}
+
+/// Trims and lowercases `config` and checks it can be run: an enabled parser
+/// needs a command and at least one MIME type.
+pub fn validate_config(config: ExternalParserConfig) -> AppResult<ExternalParserConfig> {
+    let config = ExternalParserConfig {
+        command: config.command.trim().to_string(),
+        mime_types: config
+            .mime_types
+            .iter()
+            .map(|mime| mime.trim().to_ascii_lowercase())
+            .filter(|mime| !mime.is_empty())
+            .collect(),
+        ..config
+    };
+    if !(1..=MAX_TIMEOUT_SECS).contains(&config.timeout_secs) {
+        return Err(AppError::InvalidInput(format!(
+            "timeout must be between 1 and {MAX_TIMEOUT_SECS} seconds"
+        )));
+    }
+    if let Some(mime) = config.mime_types.iter().find(|mime| !mime.contains('/')) {
+        return Err(AppError::InvalidInput(format!(
+            "'{mime}' is not a MIME type"
+        )));
+    }
+    if config.enabled && config.command.is_empty() {
+        return Err(AppError::InvalidInput(
+            "external parser command cannot be empty".to_string(),
+        ));
+    }
+    if config.enabled && config.mime_types.is_empty() {
+        return Err(AppError::InvalidInput(
+            "choose at least one MIME type for the external parser".to_string(),
+        ));
+    }
+    Ok(config)
+}
+
+/// True when `config` is enabled and routes `mime_type` to the external parser.
+pub fn handles(config: &ExternalParserConfig, mime_type: &str) -> bool {
+    let mime_type = mime_type.trim().to_ascii_lowercase();
+    config.enabled
+        && config.mime_types.iter().any(|pattern| {
+            let pattern = pattern.trim().to_ascii_lowercase();
+            match pattern.strip_suffix("/*") {
+                Some(prefix) => mime_type
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/')),
+                None => pattern == mime_type,
+            }
+        })
+}
+
+/// Runs the configured parser on `file_path` and normalizes what it prints.
+pub fn parse(
+    config: &ExternalParserConfig,
+    file_path: &Path,
+    mime_type: &str,
+) -> AppResult<NormalizedPayload> {
+    let command = config.command.trim();
+    if command.is_empty() {
+        return Err(AppError::InvalidInput(
+            "no external parser is configured".to_string(),
+        ));
+    }
+    let mut child = Command::new(command)
+        .args(&config.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Sidecar(format!("cannot start external parser {command}: {e}")))?;
+
+    let request = json!({
+        "file_path": file_path.to_string_lossy(),
+        "mime_type": mime_type,
+    })
+    .to_string();
+    // Pipes are drained on their own threads so a chatty parser cannot block
+    // on a full pipe while we wait for it to exit.
+    let mut stdin = child.stdin.take();
+    let writer = thread::spawn(move || {
+        // A parser that takes its input from its arguments may never read stdin.
+        if let Some(stdin) = stdin.as_mut() {
+            let _ = stdin.write_all(request.as_bytes());
+        }
+    });
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let timeout = Duration::from_secs(config.timeout_secs.max(1));
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| AppError::Sidecar(format!("external parser: {e}")))?
+        {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AppError::Sidecar(format!(
+                "external parser timed out after {}s",
+                timeout.as_secs()
+            )));
+        }
+        thread::sleep(Duration::from_millis(25));
+    };
+    let _ = writer.join();
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let tail =
+            String::from_utf8_lossy(&stderr[stderr.len().saturating_sub(STDERR_TAIL_BYTES)..]);
+        return Err(AppError::Sidecar(format!(
+            "external parser exited with {status}: {}",
+            tail.trim()
+        )));
+    }
+    let value: Value = serde_json::from_slice(&stdout)
+        .map_err(|e| AppError::Sidecar(format!("external parser wrote invalid JSON: {e}")))?;
+    if let Some(error) = value.get("error").and_then(Value::as_str) {
+        return Err(AppError::Sidecar(format!(
+            "external parser failed: {error}"
+        )));
+    }
+    normalize_sidecar_payload(&value)
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// Turns a sidecar's JSON into the payload the native parser produces: one
+/// `Document` root with ordinal path `root` (added when the sidecar sent none),
+/// orphans attached to it, nodes in ordinal order and `contains` edges when the
+/// sidecar sent none.
+pub fn normalize_sidecar_payload(payload: &Value) -> AppResult<NormalizedPayload> {
+    let raw: RawPayload = serde_json::from_value(payload.clone())
+        .map_err(|e| AppError::Sidecar(format!("invalid sidecar payload: {e}")))?;
+    if raw.nodes.is_empty() {
+        return Err(AppError::Sidecar(
+            "sidecar payload must contain at least one node".to_string(),
+        ));
+    }
+
+    {
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = raw.nodes.iter().find(|node| !seen.insert(node.id.as_str())) {
+            return Err(AppError::Sidecar(format!(
+                "sidecar payload repeats node id {}",
+                duplicate.id
+            )));
+        }
+    }
+
+    let pages = raw.document.pages.unwrap_or(1).max(1);
+    let mut nodes = raw.nodes.into_iter().map(into_node).collect::<Vec<_>>();
+    let roots = nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| {
+            node.parent_id.is_none() && node.node_type.eq_ignore_ascii_case("document")
+        })
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let root = match roots[..] {
+        [index] => {
+            let mut root = nodes.remove(index);
+            root.ordinal_path = "root".to_string();
+            root
+        }
+        _ => SidecarNode {
+            id: format!("root-{}", Uuid::new_v4()),
+            parent_id: None,
+            node_type: "Document".to_string(),
+            title: raw.document.title.clone(),
+            text: String::new(),
+            page_start: Some(1),
+            page_end: Some(pages),
+            ordinal_path: "root".to_string(),
+            bbox: json!({}),
+            metadata: json!({ "parser": "sidecar" }),
+        },
+    };
+
+    let ids = nodes
+        .iter()
+        .map(|node| node.id.clone())
+        .chain(std::iter::once(root.id.clone()))
+        .collect::<HashSet<_>>();
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if !node
+            .parent_id
+            .as_ref()
+            .is_some_and(|parent| ids.contains(parent))
+        {
+            node.parent_id = Some(root.id.clone());
+        }
+        if node.ordinal_path.is_empty() {
+            node.ordinal_path = format!("{}", index + 1);
+        }
+    }
+    nodes.sort_by(|a, b| compare_ordinals(&a.ordinal_path, &b.ordinal_path));
+    nodes.insert(0, root);
+
+    let edges = if raw.edges.is_empty() {
+        nodes
+            .iter()
+            .filter_map(|node| {
+                Some(SidecarEdge {
+                    from: node.parent_id.clone()?,
+                    to: node.id.clone(),
+                    relation: "contains".to_string(),
+                })
+            })
+            .collect()
+    } else {
+        raw.edges
+    };
+
+    let mut metadata = match raw.document.metadata {
+        Value::Object(map) => map,
+        _ => Default::default(),
+    };
+    metadata.insert("parser".to_string(), json!("sidecar"));
+    Ok(NormalizedPayload {
+        document: SidecarDocument {
+            title: raw.document.title,
+            pages,
+            metadata: Value::Object(metadata),
+        },
+        nodes,
+        edges,
+    })
+}
+
+fn into_node(raw: RawNode) -> SidecarNode {
+    let mut metadata = match raw.metadata {
+        Value::Object(map) => map,
+        _ => Default::default(),
+    };
+    metadata
+        .entry("parser".to_string())
+        .or_insert_with(|| json!("sidecar"));
+    SidecarNode {
+        id: raw.id,
+        parent_id: raw.parent_id,
+        node_type: raw.node_type,
+        title: raw.title,
+        text: raw.text,
+        page_start: raw.page_start,
+        page_end: raw.page_end,
+        ordinal_path: raw.ordinal_path,
+        bbox: raw.bbox,
+        metadata: Value::Object(metadata),
+    }
+}
+
+/// Orders ordinal paths segment by segment, numbers numerically, so `2.10`
+/// follows `2.9` and a parent precedes its children.
+fn compare_ordinals(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+        }
+    }
+}
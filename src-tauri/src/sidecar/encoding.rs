@@ -0,0 +1,44 @@
+//! Character-encoding detection for text files. Files are decoded from the
+//! encoding they were saved in (BOM first, then UTF-8, then chardetng's guess)
+//! instead of being rejected when they are not UTF-8. Bytes the encoding cannot
+//! map become U+FFFD, and the payload records the encoding and whether that
+//! happened.
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+use crate::sidecar::types::NormalizedPayload;
+
+/// How much of a streamed file is inspected before picking its encoding.
+pub const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Encoding of `sample`. `complete` is false when the sample is only the start
+/// of the file, so a multi-byte character cut off at the end is not held
+/// against UTF-8.
+pub fn detect(sample: &[u8], complete: bool) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return encoding;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => UTF_8,
+        Err(err) if !complete && err.error_len().is_none() => UTF_8,
+        Err(_) => {
+            let mut detector = EncodingDetector::new();
+            detector.feed(sample, complete);
+            detector.guess(None, true)
+        }
+    }
+}
+
+/// Decodes a whole file, returning the text, the encoding used and whether
+/// any bytes had to be replaced.
+pub fn decode(bytes: &[u8]) -> (String, &'static Encoding, bool) {
+    let (text, encoding, lossy) = detect(bytes, true).decode(bytes);
+    (text.into_owned(), encoding, lossy)
+}
+
+/// Notes the source encoding on the payload.
+pub fn record(payload: &mut NormalizedPayload, encoding: &'static Encoding, lossy: bool) {
+    payload.record_metadata("encoding", encoding.name().into());
+    payload.record_metadata("encodingLossy", lossy.into());
+}
@@ -0,0 +1,105 @@
+//! Locates the image behind a Figure node so it can be stored at ingest time.
+
+use std::path::{Component, Path, PathBuf};
+
+use base64::Engine;
+
+use crate::providers::ImagePart;
+
+/// Larger images are skipped; inline request payloads have tight size limits.
+pub const MAX_FIGURE_IMAGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Resolves the first image a figure block references: an inline `data:image/...`
+/// URI, or a markdown / `<img>` path relative to `source_dir`. Remote URLs,
+/// paths leading out of `source_dir` and formats multimodal models don't
+/// accept (e.g. SVG) are ignored.
+pub fn resolve_figure_image(text: &str, source_dir: Option<&Path>) -> Option<ImagePart> {
+    let target = image_target(text)?;
+    let image = if target.to_ascii_lowercase().starts_with("data:image/") {
+        decode_data_uri(&target)?
+    } else {
+        let dir = source_dir?;
+        if target.contains("://") {
+            return None;
+        }
+        let mime = mime_for_path(&target)?;
+        let data = std::fs::read(inside(dir, Path::new(&target))?).ok()?;
+        ImagePart {
+            mime: mime.to_string(),
+            data,
+        }
+    };
+    (!image.data.is_empty() && image.data.len() <= MAX_FIGURE_IMAGE_BYTES).then_some(image)
+}
+
+/// `relative` under `dir`, unless it is absolute, climbs out with `..`, or
+/// resolves through a symlink to somewhere outside `dir`. A document must
+/// not pull in pictures from elsewhere on the machine.
+fn inside(dir: &Path, relative: &Path) -> Option<PathBuf> {
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    let path = std::fs::canonicalize(dir.join(relative)).ok()?;
+    path.starts_with(std::fs::canonicalize(dir).ok()?)
+        .then_some(path)
+}
+
+fn image_target(text: &str) -> Option<String> {
+    if let Some(start) = text.find("![") {
+        let rest = &text[start..];
+        if let Some(open) = rest.find("](") {
+            let url = &rest[open + 2..];
+            let end = url.find(')')?;
+            let url = url[..end].split_whitespace().next()?;
+            return Some(url.trim_matches(|c| c == '<' || c == '>').to_string());
+        }
+    }
+    let lower = text.to_ascii_lowercase();
+    let img = lower.find("<img")?;
+    let src = img + lower[img..].find("src=")? + 4;
+    let quote = text[src..].chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let value = &text[src + 1..];
+    let end = value.find(quote)?;
+    Some(value[..end].to_string())
+}
+
+fn decode_data_uri(uri: &str) -> Option<ImagePart> {
+    let (header, payload) = uri.split_once(',')?;
+    let header = header.strip_prefix("data:")?;
+    let mime = header.strip_suffix(";base64")?.to_ascii_lowercase();
+    if !is_supported_mime(&mime) {
+        return None;
+    }
+    let compact: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(compact)
+        .ok()?;
+    Some(ImagePart { mime, data })
+}
+
+fn mime_for_path(path: &str) -> Option<&'static str> {
+    let ext = Path::new(path)
+        .extension()?
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "webp" => Some("image/webp"),
+        "gif" => Some("image/gif"),
+        _ => None,
+    }
+}
+
+fn is_supported_mime(mime: &str) -> bool {
+    matches!(
+        mime,
+        "image/png" | "image/jpeg" | "image/webp" | "image/gif"
+    )
+}
@@ -0,0 +1,574 @@
+//! Reads the block structure of an HTML page: headings, paragraphs and list
+//! items, tables and images. Saved pages are rarely well-formed, with
+//! unclosed `<p>` and `<li>`, uppercase tags and stray end tags, so a small
+//! tolerant tokenizer is used instead of an XML parser; block-level tags end
+//! whatever text came before them. Scripts, styles and other markup that is
+//! not content are dropped.
+
+/// A page as the blocks it is read into.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HtmlPage {
+    /// The `<title>`, if the page has a non-empty one.
+    pub title: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    /// `<h1>` to `<h6>`.
+    Heading { level: u8, text: String },
+    /// `<p>` or `<li>` text, or text left directly inside a container.
+    Paragraph { text: String, list_item: bool },
+    /// Cell text row by row; the first row is taken as the header.
+    Table {
+        caption: Option<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// An `<img>`, with its `<figcaption>` when it sits in a `<figure>`.
+    Figure {
+        src: String,
+        alt: String,
+        caption: Option<String>,
+    },
+}
+
+/// Whose content is not text on the page.
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "math", "iframe", "object", "select",
+];
+/// Whose content is read up to the end tag without looking for other tags.
+const RAW_TEXT: &[&str] = &["script", "style", "title", "textarea"];
+/// Tags that end the text before them and start a new block.
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "caption",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+enum Token<'a> {
+    Text(&'a str),
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+    },
+    End(String),
+}
+
+/// Reads `html` into blocks in document order.
+pub fn parse(html: &str) -> HtmlPage {
+    let mut reader = Reader::default();
+    for token in tokenize(html) {
+        match token {
+            Token::Text(text) => reader.text(text),
+            Token::Start { name, attributes } => reader.start(&name, &attributes),
+            Token::End(name) => reader.end(&name),
+        }
+    }
+    reader.finish()
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if open > 0 {
+            tokens.push(Token::Text(&rest[..open]));
+        }
+        rest = &rest[open..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let tail = &rest[name_start..];
+        if !tail.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            // A `<` that opens no tag, as in `a < b`.
+            tokens.push(Token::Text(&rest[..1]));
+            rest = &rest[1..];
+            continue;
+        }
+        let name_len = tail
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == ':'))
+            .unwrap_or(tail.len());
+        let name = tail[..name_len].to_ascii_lowercase();
+        let (attributes, self_closing, after) = read_attributes(&tail[name_len..]);
+        rest = after;
+        if closing {
+            tokens.push(Token::End(name));
+            continue;
+        }
+        let raw = RAW_TEXT.contains(&name.as_str()) && !self_closing;
+        tokens.push(Token::Start {
+            name: name.clone(),
+            attributes,
+        });
+        if raw {
+            let end = find_ignoring_case(rest, &format!("</{name}")).unwrap_or(rest.len());
+            if end > 0 {
+                tokens.push(Token::Text(&rest[..end]));
+            }
+            rest = &rest[end..];
+            rest = rest.find('>').map_or("", |close| &rest[close + 1..]);
+            tokens.push(Token::End(name));
+        } else if self_closing {
+            tokens.push(Token::End(name));
+        }
+    }
+    tokens
+}
+
+/// A tag's attributes, whether it ends in `/>`, and what follows it.
+fn read_attributes(tag: &str) -> (Vec<(String, String)>, bool, &str) {
+    let mut attributes = vec![];
+    let mut rest = tag;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return (attributes, false, rest);
+        }
+        if let Some(after) = rest.strip_prefix("/>") {
+            return (attributes, true, after);
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return (attributes, false, after);
+        }
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len());
+        if name_len == 0 {
+            // A stray `/` or `=`.
+            rest = &rest[1..];
+            continue;
+        }
+        let name = rest[..name_len].to_ascii_lowercase();
+        rest = rest[name_len..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let quoted = &after[1..];
+                    match quoted.find(quote) {
+                        Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                        None => (quoted, ""),
+                    }
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        attributes.push((name, value));
+    }
+}
+
+fn find_ignoring_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Replaces character references; unknown ones are left as written.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let reference = rest[1..]
+            .find(';')
+            .filter(|&end| end > 0 && end <= 10)
+            .and_then(|end| Some((entity(&rest[1..1 + end])?, end + 2)));
+        match reference {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '\u{2013}',
+        "mdash" => '\u{2014}',
+        "hellip" => '\u{2026}',
+        "lsquo" => '\u{2018}',
+        "rsquo" => '\u{2019}',
+        "ldquo" => '\u{201c}',
+        "rdquo" => '\u{201d}',
+        "bull" => '\u{2022}',
+        "middot" => '\u{b7}',
+        "copy" => '\u{a9}',
+        "reg" => '\u{ae}',
+        "trade" => '\u{2122}',
+        "deg" => '\u{b0}',
+        "times" => '\u{d7}',
+        "euro" => '\u{20ac}',
+        "pound" => '\u{a3}',
+        "yen" => '\u{a5}',
+        "cent" => '\u{a2}',
+        _ => return None,
+    })
+}
+
+/// Appends `text`, collapsing runs of whitespace as a browser would unless
+/// it is preformatted.
+fn push_text(buffer: &mut String, text: &str, preformatted: bool) {
+    if preformatted {
+        buffer.push_str(text);
+        return;
+    }
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !buffer.is_empty() && !buffer.ends_with([' ', '\n']) {
+                buffer.push(' ');
+            }
+        } else {
+            buffer.push(c);
+        }
+    }
+}
+
+/// Trims the lines of a block and drops empty ones.
+fn tidy(text: &str, preformatted: bool) -> String {
+    if preformatted {
+        return text
+            .trim_matches('\n')
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Default)]
+struct Table {
+    caption: Option<String>,
+    in_caption: bool,
+    rows: Vec<Vec<String>>,
+    cell: Option<String>,
+}
+
+impl Table {
+    fn end_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            if self.rows.is_empty() {
+                self.rows.push(vec![]);
+            }
+            let text = tidy(&cell, false).replace('\n', " ");
+            self.rows.last_mut().expect("pushed above").push(text);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Reader {
+    page: HtmlPage,
+    /// Text of the block being read.
+    text: String,
+    heading: Option<u8>,
+    list_item: bool,
+    preformatted: usize,
+    /// The outermost table being read, and how deeply tables are nested.
+    table: Option<Table>,
+    table_depth: usize,
+    /// Where the blocks of the `<figure>` being read start.
+    figure_start: Option<usize>,
+    in_figcaption: bool,
+    in_title: bool,
+    /// The skipped element being read through, and how many of it are open.
+    skipping: Option<(String, usize)>,
+}
+
+impl Reader {
+    fn text(&mut self, text: &str) {
+        if self.skipping.is_some() {
+            return;
+        }
+        let text = decode_entities(text);
+        if self.in_title {
+            let title = self.page.title.get_or_insert_with(String::new);
+            push_text(title, &text, false);
+        } else if let Some(table) = &mut self.table {
+            if table.in_caption {
+                push_text(table.caption.get_or_insert_with(String::new), &text, false);
+            } else if let Some(cell) = &mut table.cell {
+                push_text(cell, &text, false);
+            }
+        } else {
+            push_text(&mut self.text, &text, self.preformatted > 0);
+        }
+    }
+
+    fn start(&mut self, name: &str, attributes: &[(String, String)]) {
+        if let Some((skipped, open)) = &mut self.skipping {
+            if skipped == name {
+                *open += 1;
+            }
+            return;
+        }
+        if SKIPPED.contains(&name) {
+            self.skipping = Some((name.to_string(), 1));
+            return;
+        }
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.trim().to_string())
+        };
+        if name == "title" {
+            self.in_title = true;
+            return;
+        }
+        if self.table.is_some() {
+            self.start_in_table(name, attribute("alt"));
+            return;
+        }
+        if BLOCKS.contains(&name) {
+            self.end_block();
+            // Headings hold no blocks, so one left open ends here.
+            self.heading = None;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.heading = name[1..].parse().ok(),
+            "li" => self.list_item = true,
+            "pre" => self.preformatted += 1,
+            "br" => self.text.push('\n'),
+            "figure" => self.figure_start = Some(self.page.blocks.len()),
+            "figcaption" => self.in_figcaption = true,
+            "table" => {
+                self.table = Some(Table::default());
+                self.table_depth = 1;
+            }
+            "img" if self.heading.is_none() => {
+                let src = attribute("src").unwrap_or_default();
+                let alt = attribute("alt")
+                    .or_else(|| attribute("title"))
+                    .unwrap_or_default();
+                if !src.is_empty() || !alt.is_empty() {
+                    self.end_block();
+                    self.page.blocks.push(Block::Figure {
+                        src,
+                        alt,
+                        caption: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn start_in_table(&mut self, name: &str, alt: Option<String>) {
+        let table = self.table.as_mut().expect("checked by the caller");
+        match name {
+            "table" => self.table_depth += 1,
+            // Nested tables are read as text in the outer table's cell.
+            _ if self.table_depth > 1 => {
+                if let (Some(cell), "td" | "th" | "br") = (&mut table.cell, name) {
+                    push_text(cell, " ", false);
+                }
+            }
+            "caption" => table.in_caption = true,
+            "tr" => {
+                table.end_cell();
+                table.rows.push(vec![]);
+            }
+            "td" | "th" => {
+                table.end_cell();
+                table.cell = Some(String::new());
+            }
+            "br" | "p" | "div" | "li" => {
+                if let Some(cell) = &mut table.cell {
+                    push_text(cell, " ", false);
+                }
+            }
+            "img" => {
+                if let (Some(cell), Some(alt)) = (&mut table.cell, alt) {
+                    push_text(cell, &format!(" {alt} "), false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        if let Some((skipped, open)) = &mut self.skipping {
+            if skipped == name {
+                *open -= 1;
+                if *open == 0 {
+                    self.skipping = None;
+                }
+            }
+            return;
+        }
+        if name == "title" {
+            self.in_title = false;
+            return;
+        }
+        if let Some(table) = &mut self.table {
+            match name {
+                "table" => {
+                    self.table_depth -= 1;
+                    if self.table_depth == 0 {
+                        self.end_table();
+                    }
+                }
+                _ if self.table_depth > 1 => {}
+                "caption" => table.in_caption = false,
+                "td" | "th" | "tr" => table.end_cell(),
+                _ => {}
+            }
+            return;
+        }
+        if BLOCKS.contains(&name) {
+            self.end_block();
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.heading = None,
+            "li" | "ul" | "ol" => self.list_item = false,
+            "pre" => self.preformatted = self.preformatted.saturating_sub(1),
+            "figure" => self.figure_start = None,
+            "figcaption" => self.in_figcaption = false,
+            _ => {}
+        }
+    }
+
+    fn end_table(&mut self) {
+        let Some(mut table) = self.table.take() else {
+            return;
+        };
+        table.end_cell();
+        let rows = table
+            .rows
+            .into_iter()
+            .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+            .collect::<Vec<_>>();
+        let caption = table
+            .caption
+            .map(|caption| tidy(&caption, false))
+            .filter(|caption| !caption.is_empty());
+        if !rows.is_empty() {
+            self.page.blocks.push(Block::Table { caption, rows });
+        } else if let Some(caption) = caption {
+            self.page.blocks.push(Block::Paragraph {
+                text: caption,
+                list_item: false,
+            });
+        }
+    }
+
+    /// Ends the block whose text is being read.
+    fn end_block(&mut self) {
+        let text = tidy(&std::mem::take(&mut self.text), self.preformatted > 0);
+        if text.is_empty() {
+            return;
+        }
+        if self.in_figcaption {
+            let figure = self.figure_start.and_then(|start| {
+                self.page.blocks[start..]
+                    .iter_mut()
+                    .rev()
+                    .find_map(|block| match block {
+                        Block::Figure { caption, .. } => Some(caption),
+                        _ => None,
+                    })
+            });
+            if let Some(caption) = figure {
+                *caption = Some(text);
+                return;
+            }
+        }
+        self.page.blocks.push(match self.heading {
+            Some(level) => Block::Heading {
+                level,
+                text: text.replace('\n', " "),
+            },
+            None => Block::Paragraph {
+                text,
+                list_item: self.list_item,
+            },
+        });
+    }
+
+    fn finish(mut self) -> HtmlPage {
+        self.end_table();
+        self.end_block();
+        if let Some(title) = &mut self.page.title {
+            *title = tidy(title, false);
+        }
+        self.page.title = self.page.title.filter(|title| !title.is_empty());
+        self.page
+    }
+}
@@ -0,0 +1,200 @@
+//! Ignore rules for folder ingestion. [`scan_directory`] walks a folder and
+//! splits its files into ones worth parsing and ones a project's [`IngestRules`]
+//! leave out, so dependency trees, binaries and editor temp files never reach
+//! the parser. Watched folders check single paths with [`skip_reason`].
+
+use std::path::{Component, Path};
+
+use crate::core::{
+    errors::{AppError, AppResult},
+    paths,
+    types::{IngestRules, ScanDirectoryResponse, ScannedFile, SkippedPath},
+};
+
+/// Trims patterns and extensions, lowercases extensions and drops their leading
+/// dot, and rejects rules that could never match or would skip everything.
+pub fn normalize_rules(rules: IngestRules) -> AppResult<IngestRules> {
+    let mut ignore_globs = Vec::new();
+    for pattern in rules.ignore_globs {
+        let pattern = pattern.trim().replace('\\', "/");
+        let pattern = pattern.trim_matches('/');
+        if pattern.is_empty() {
+            return Err(AppError::InvalidInput(
+                "ignore pattern cannot be empty".to_string(),
+            ));
+        }
+        if matches!(pattern, "*" | "**") {
+            return Err(AppError::InvalidInput(format!(
+                "ignore pattern '{pattern}' would skip every file"
+            )));
+        }
+        if !ignore_globs.iter().any(|existing| existing == pattern) {
+            ignore_globs.push(pattern.to_string());
+        }
+    }
+
+    let mut ignored_extensions = Vec::new();
+    for extension in rules.ignored_extensions {
+        let extension = extension
+            .trim()
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        if extension.is_empty() {
+            return Err(AppError::InvalidInput(
+                "ignored extension cannot be empty".to_string(),
+            ));
+        }
+        if !ignored_extensions.contains(&extension) {
+            ignored_extensions.push(extension);
+        }
+    }
+
+    if rules.max_file_bytes == Some(0) {
+        return Err(AppError::InvalidInput(
+            "max file size must be greater than zero".to_string(),
+        ));
+    }
+
+    Ok(IngestRules {
+        ignore_globs,
+        ignored_extensions,
+        max_file_bytes: rules.max_file_bytes,
+    })
+}
+
+/// Why `relative` (a path under the scanned folder) should not be ingested, or
+/// `None` when it should. `size_bytes` is `None` for directories, which are only
+/// checked against the patterns.
+pub fn skip_reason(
+    rules: &IngestRules,
+    relative: &Path,
+    size_bytes: Option<u64>,
+) -> Option<String> {
+    let components = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(pattern) = rules
+        .ignore_globs
+        .iter()
+        .find(|pattern| pattern_matches(pattern, &components))
+    {
+        return Some(format!("matches ignore pattern '{pattern}'"));
+    }
+
+    let size_bytes = size_bytes?;
+    if let Some(extension) = relative
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+    {
+        if rules.ignored_extensions.contains(&extension) {
+            return Some(format!("extension '.{extension}' is ignored"));
+        }
+    }
+    match rules.max_file_bytes {
+        Some(max) if size_bytes > max => {
+            Some(format!("{size_bytes} bytes exceeds the {max} byte limit"))
+        }
+        _ => None,
+    }
+}
+
+/// Lists the files under `root` that `rules` let through, in path order. Ignored
+/// directories are reported once and not descended into; symlinks are skipped.
+pub fn scan_directory(root: &Path, rules: &IngestRules) -> AppResult<ScanDirectoryResponse> {
+    if !root.is_dir() {
+        return Err(AppError::NotFound(format!(
+            "directory {}",
+            paths::display(root)
+        )));
+    }
+    let mut scan = ScanDirectoryResponse::default();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir).map_err(|err| AppError::Io(err.to_string()))?;
+        for entry in entries {
+            let entry = entry.map_err(|err| AppError::Io(err.to_string()))?;
+            let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|err| AppError::Io(err.to_string()))?;
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let Ok(display) = paths::to_utf8(&paths::simplify(&path)) else {
+                // A name that is not Unicode cannot be handed back to open.
+                scan.skipped.push(SkippedPath {
+                    path: paths::display(&path),
+                    reason: "name is not valid Unicode".to_string(),
+                });
+                continue;
+            };
+            if file_type.is_symlink() {
+                scan.skipped.push(SkippedPath {
+                    path: display,
+                    reason: "symbolic links are not followed".to_string(),
+                });
+            } else if file_type.is_dir() {
+                match skip_reason(rules, relative, None) {
+                    Some(reason) => scan.skipped.push(SkippedPath {
+                        path: display,
+                        reason,
+                    }),
+                    None => pending.push(path),
+                }
+            } else {
+                let size_bytes = entry
+                    .metadata()
+                    .map_err(|err| AppError::Io(err.to_string()))?
+                    .len();
+                match skip_reason(rules, relative, Some(size_bytes)) {
+                    Some(reason) => scan.skipped.push(SkippedPath {
+                        path: display,
+                        reason,
+                    }),
+                    None => scan.files.push(ScannedFile {
+                        path: display,
+                        size_bytes,
+                    }),
+                }
+            }
+        }
+    }
+    scan.files.sort_by(|a, b| a.path.cmp(&b.path));
+    scan.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(scan)
+}
+
+fn pattern_matches(pattern: &str, components: &[String]) -> bool {
+    if !pattern.contains('/') {
+        return components
+            .iter()
+            .any(|component| segment_matches(pattern.as_bytes(), component.as_bytes()));
+    }
+    let segments = pattern.split('/').collect::<Vec<_>>();
+    path_matches(&segments, components)
+}
+
+fn path_matches(segments: &[&str], components: &[String]) -> bool {
+    match segments.split_first() {
+        None => components.is_empty(),
+        Some((&"**", rest)) => {
+            (0..=components.len()).any(|skip| path_matches(rest, &components[skip..]))
+        }
+        Some((segment, rest)) => components.split_first().is_some_and(|(component, tail)| {
+            segment_matches(segment.as_bytes(), component.as_bytes()) && path_matches(rest, tail)
+        }),
+    }
+}
+
+/// `*` and `?` wildcards within one path component.
+fn segment_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| segment_matches(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && segment_matches(rest, &name[1..]),
+        Some((byte, rest)) => name.first() == Some(byte) && segment_matches(rest, &name[1..]),
+    }
+}
@@ -0,0 +1,567 @@
+//! Importers for corpora kept in note tools. [`plan_import`] walks an Obsidian
+//! vault, a Notion export or a Zotero RDF export and lists the files to
+//! ingest: the project each goes to, the tags it carries over, and the files
+//! a note embeds or links to, which become its attachments. Top-level folders
+//! (Zotero collections) name projects and deeper ones become tags. Files go
+//! through the project's [`IngestRules`] like a folder scan.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Component, Path, PathBuf},
+};
+
+use roxmltree::Node;
+
+use crate::{
+    core::{
+        errors::{AppError, AppResult},
+        paths,
+        types::{ImportItem, ImportPlan, ImportSource, IngestRules, SkippedPath},
+    },
+    sidecar::{ignore, mime},
+};
+
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const ZOTERO_NS: &str = "http://www.zotero.org/namespaces/export#";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+const DCTERMS_NS: &str = "http://purl.org/dc/terms/";
+const LINK_NS: &str = "http://purl.org/rss/1.0/modules/link/";
+
+/// Lists what importing the export at `root` would ingest. Without
+/// `folders_as_projects` everything goes to the importing project and every
+/// folder becomes a tag.
+pub fn plan_import(
+    root: &Path,
+    source: ImportSource,
+    rules: &IngestRules,
+    folders_as_projects: bool,
+) -> AppResult<ImportPlan> {
+    match source {
+        ImportSource::Obsidian | ImportSource::Notion => {
+            plan_notes(root, source, rules, folders_as_projects)
+        }
+        ImportSource::Zotero => plan_zotero(root, rules, folders_as_projects),
+    }
+}
+
+fn plan_notes(
+    root: &Path,
+    source: ImportSource,
+    rules: &IngestRules,
+    folders_as_projects: bool,
+) -> AppResult<ImportPlan> {
+    let scan = ignore::scan_directory(root, rules)?;
+    let root = paths::simplify(root);
+    let files = scan
+        .files
+        .iter()
+        .map(|file| PathBuf::from(&file.path))
+        .collect::<Vec<_>>();
+    let mut items = Vec::with_capacity(files.len());
+    for path in &files {
+        let relative = path.strip_prefix(&root).unwrap_or(path);
+        let folders = relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(clean_name(&part.to_string_lossy(), source)),
+                _ => None,
+            })
+            .collect();
+        let (project, mut tags) = placement(folders, folders_as_projects);
+        let mut attachments = vec![];
+        if is_note(path, source) {
+            // A note that is not UTF-8 is still ingested; it just links nothing.
+            let text = std::fs::read_to_string(path).unwrap_or_default();
+            if source == ImportSource::Obsidian {
+                tags.extend(obsidian_tags(&text));
+            }
+            for target in note_links(&text) {
+                let Some(found) = resolve_link(&target, path, &root, &files, source) else {
+                    continue;
+                };
+                let found = paths::display(found);
+                if found != paths::display(path)
+                    && !is_note(Path::new(&found), source)
+                    && !attachments.contains(&found)
+                {
+                    attachments.push(found);
+                }
+            }
+        }
+        let display_name = display_name(path, source);
+        items.push(ImportItem {
+            path: paths::display(path),
+            mime_type: mime::from_extension(path).to_string(),
+            display_name,
+            project,
+            tags: dedup(tags),
+            attachments,
+        });
+    }
+    claim_attachments(&mut items);
+    Ok(ImportPlan {
+        items,
+        skipped: scan.skipped,
+    })
+}
+
+/// The project named by the first folder, when folders name projects, and
+/// the tags the others give.
+fn placement(folders: Vec<String>, folders_as_projects: bool) -> (Option<String>, Vec<String>) {
+    let mut folders = folders.into_iter().filter(|name| !name.is_empty());
+    let project = if folders_as_projects {
+        folders.next()
+    } else {
+        None
+    };
+    (project, folders.collect())
+}
+
+/// An attachment goes to the project of the first note that links to it,
+/// with that note's tags, so an attachments folder does not become a project
+/// of its own.
+fn claim_attachments(items: &mut [ImportItem]) {
+    let index = items
+        .iter()
+        .enumerate()
+        .map(|(at, item)| (item.path.clone(), at))
+        .collect::<HashMap<_, _>>();
+    let claims = items
+        .iter()
+        .flat_map(|note| {
+            note.attachments
+                .iter()
+                .filter_map(|attachment| index.get(attachment).copied())
+                .map(|at| (at, note.project.clone(), note.tags.clone()))
+        })
+        .collect::<Vec<_>>();
+    let mut claimed = vec![false; items.len()];
+    for (at, project, tags) in claims {
+        if !std::mem::replace(&mut claimed[at], true) {
+            items[at].project = project;
+            items[at].tags = tags;
+        }
+    }
+}
+
+fn is_note(path: &Path, source: ImportSource) -> bool {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match source {
+        ImportSource::Notion => matches!(ext.as_str(), "md" | "html" | "htm"),
+        _ => ext == "md",
+    }
+}
+
+/// Notion appends a 32-digit hex id to every page and folder name.
+fn clean_name(name: &str, source: ImportSource) -> String {
+    if source != ImportSource::Notion {
+        return name.trim().to_string();
+    }
+    match name.rsplit_once(' ') {
+        Some((title, id)) if id.len() == 32 && id.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            title.trim().to_string()
+        }
+        _ => name.trim().to_string(),
+    }
+}
+
+fn display_name(path: &Path, source: ImportSource) -> Option<String> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let cleaned = clean_name(&stem, source);
+    if cleaned == stem || cleaned.is_empty() {
+        return None;
+    }
+    Some(match path.extension() {
+        Some(ext) => format!("{cleaned}.{}", ext.to_string_lossy()),
+        None => cleaned,
+    })
+}
+
+/// Tags from an Obsidian note's front matter (`tags: [a, b]` or a list) and
+/// its inline `#tags`, outside code blocks.
+pub fn obsidian_tags(text: &str) -> Vec<String> {
+    let mut tags = vec![];
+    let mut lines = text.lines().peekable();
+    if lines.peek().map(|line| line.trim_end()) == Some("---") {
+        lines.next();
+        let mut in_tags = false;
+        for line in lines.by_ref() {
+            if line.trim_end() == "---" {
+                break;
+            }
+            if in_tags {
+                if let Some(item) = line.trim_start().strip_prefix('-') {
+                    tags.push(item.to_string());
+                    continue;
+                }
+                in_tags = false;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            if !matches!(key.trim().to_ascii_lowercase().as_str(), "tags" | "tag") {
+                continue;
+            }
+            let value = value.trim().trim_start_matches('[').trim_end_matches(']');
+            if value.is_empty() {
+                in_tags = true;
+            }
+            tags.extend(value.split(',').map(str::to_string));
+        }
+    }
+
+    let mut in_code = false;
+    for line in lines {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let mut rest = line;
+        let mut after_space = true;
+        while let Some(ch) = rest.chars().next() {
+            rest = &rest[ch.len_utf8()..];
+            if ch == '#' && after_space {
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')))
+                    .unwrap_or(rest.len());
+                let tag = &rest[..len];
+                if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                    tags.push(tag.to_string());
+                }
+                rest = &rest[len..];
+            }
+            after_space = ch.is_whitespace();
+        }
+    }
+
+    dedup(
+        tags.into_iter()
+            .map(|tag| {
+                tag.trim()
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .trim_start_matches('#')
+                    .to_string()
+            })
+            .collect(),
+    )
+}
+
+/// Targets of a note's wiki links (`[[Name|alias]]`, `![[image.png]]`),
+/// Markdown links and images, and HTML `href`/`src` attributes, as written.
+pub fn note_links(text: &str) -> Vec<String> {
+    let mut links = vec![];
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else { break };
+        let inner = &rest[..end];
+        links.push(inner.split('|').next().unwrap_or(inner).to_string());
+        rest = &rest[end + 2..];
+    }
+    let mut rest = text;
+    while let Some(start) = rest.find("](") {
+        rest = &rest[start + 2..];
+        let target = match rest.strip_prefix('<') {
+            Some(quoted) => quoted.split('>').next().unwrap_or(quoted),
+            None => rest
+                .split(|c: char| c == ')' || c.is_whitespace())
+                .next()
+                .unwrap_or(rest),
+        };
+        links.push(target.to_string());
+    }
+    for attribute in ["href=\"", "src=\""] {
+        let mut rest = text;
+        while let Some(start) = rest.find(attribute) {
+            rest = &rest[start + attribute.len()..];
+            let Some(end) = rest.find('"') else { break };
+            links.push(rest[..end].replace("&amp;", "&"));
+            rest = &rest[end + 1..];
+        }
+    }
+    links
+}
+
+/// The imported file a link points at: relative to the note, then to the
+/// export's root, and for Obsidian by file name anywhere in the vault.
+fn resolve_link<'a>(
+    target: &str,
+    note: &Path,
+    root: &Path,
+    files: &'a [PathBuf],
+    source: ImportSource,
+) -> Option<&'a PathBuf> {
+    let target = target.split(['#', '?']).next().unwrap_or(target);
+    let target = percent_decode(target.trim());
+    if target.is_empty() || target.contains("://") || target.starts_with("mailto:") {
+        return None;
+    }
+    let note_dir = note.parent().unwrap_or(root);
+    for base in [note_dir, root] {
+        let candidate = normalize(&base.join(&target));
+        if let Some(found) = files.iter().find(|file| **file == candidate) {
+            return Some(found);
+        }
+    }
+    if source != ImportSource::Obsidian {
+        return None;
+    }
+    // Obsidian links by name; of several files with it, the shallowest wins.
+    let wanted = target.replace('\\', "/").to_lowercase();
+    files
+        .iter()
+        .filter(|file| {
+            let relative = file.strip_prefix(root).unwrap_or(file);
+            let relative = relative.to_string_lossy().replace('\\', "/").to_lowercase();
+            [wanted.clone(), format!("{wanted}.md")]
+                .iter()
+                .any(|name| relative == *name || relative.ends_with(&format!("/{name}")))
+        })
+        .min_by_key(|file| file.components().count())
+}
+
+/// Resolves `.` and `..` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut at = 0;
+    while at < bytes.len() {
+        let hex = bytes
+            .get(at + 1..at + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[at], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                at += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                at += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn dedup(tags: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = vec![];
+    for tag in tags {
+        if !tag.is_empty() && !unique.contains(&tag) {
+            unique.push(tag);
+        }
+    }
+    unique
+}
+
+/// A Zotero item and the attachments linked from it.
+struct ZoteroItem {
+    about: String,
+    title: String,
+    tags: Vec<String>,
+    attachments: Vec<String>,
+}
+
+fn plan_zotero(
+    root: &Path,
+    rules: &IngestRules,
+    folders_as_projects: bool,
+) -> AppResult<ImportPlan> {
+    let mut rdf_files = std::fs::read_dir(paths::for_io(root))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rdf"))
+        .collect::<Vec<_>>();
+    rdf_files.sort();
+    let Some(rdf_file) = rdf_files.first() else {
+        return Err(AppError::InvalidInput(format!(
+            "{} has no Zotero RDF export (.rdf)",
+            paths::display(root)
+        )));
+    };
+    let xml = std::fs::read_to_string(rdf_file)?;
+    let doc = roxmltree::Document::parse(&xml).map_err(|err| {
+        AppError::InvalidInput(format!(
+            "{} is not valid RDF: {err}",
+            paths::display(rdf_file)
+        ))
+    })?;
+
+    let mut files = HashMap::new();
+    let mut items = vec![];
+    let mut collections = BTreeMap::new();
+    for node in doc.root_element().children().filter(Node::is_element) {
+        let Some(about) = node.attribute((RDF_NS, "about")).map(str::to_string) else {
+            continue;
+        };
+        let title = child_text(node, DC_NS, "title").unwrap_or_default();
+        if is(node, ZOTERO_NS, "Collection") {
+            let parts = resources(node, DCTERMS_NS, "hasPart");
+            collections.insert(about, (title, parts));
+        } else if is(node, ZOTERO_NS, "Attachment") {
+            if let Some(file) = resources(node, RDF_NS, "resource").into_iter().next() {
+                files.insert(about.clone(), file);
+            }
+            items.push(ZoteroItem {
+                about: about.clone(),
+                title,
+                tags: vec![],
+                attachments: vec![about],
+            });
+        } else {
+            let tags = node
+                .children()
+                .filter(|child| is(*child, DC_NS, "subject"))
+                .map(text_of)
+                .collect();
+            items.push(ZoteroItem {
+                about,
+                title,
+                tags,
+                attachments: resources(node, LINK_NS, "link"),
+            });
+        }
+    }
+    // An attachment of an item is imported with it, not on its own.
+    let linked = items
+        .iter()
+        .filter(|item| item.attachments.first() != Some(&item.about))
+        .flat_map(|item| item.attachments.clone())
+        .collect::<Vec<_>>();
+    items.retain(|item| {
+        item.attachments.first() != Some(&item.about) || !linked.contains(&item.about)
+    });
+
+    let mut parent = HashMap::new();
+    for (about, (_, parts)) in &collections {
+        for part in parts {
+            parent.entry(part.clone()).or_insert_with(|| about.clone());
+        }
+    }
+    let collection_path = |about: &str| {
+        let mut titles = vec![];
+        let mut current = parent.get(about);
+        while let Some(collection) = current {
+            if titles.len() > collections.len() {
+                break;
+            }
+            if let Some((title, _)) = collections.get(collection) {
+                titles.push(title.clone());
+            }
+            current = parent.get(collection);
+        }
+        titles.reverse();
+        titles
+    };
+
+    let mut plan = ImportPlan::default();
+    let root = paths::simplify(root);
+    for item in items {
+        let (project, mut tags) = placement(collection_path(&item.about), folders_as_projects);
+        tags.extend(item.tags);
+        let tags = dedup(tags);
+        let mut planned: Vec<ImportItem> = vec![];
+        for attachment in &item.attachments {
+            let Some(file) = files.get(attachment) else {
+                continue;
+            };
+            let relative = PathBuf::from(percent_decode(file));
+            let path = normalize(&root.join(&relative));
+            let display = paths::display(&path);
+            let Ok(meta) = std::fs::metadata(paths::for_io(&path)) else {
+                plan.skipped.push(SkippedPath {
+                    path: display,
+                    reason: "file is missing from the export".to_string(),
+                });
+                continue;
+            };
+            if let Some(reason) = ignore::skip_reason(rules, &relative, Some(meta.len())) {
+                plan.skipped.push(SkippedPath {
+                    path: display,
+                    reason,
+                });
+                continue;
+            }
+            let Ok(path) = paths::to_utf8(&path) else {
+                plan.skipped.push(SkippedPath {
+                    path: display,
+                    reason: "name is not valid Unicode".to_string(),
+                });
+                continue;
+            };
+            // The first file is the item itself and is named after it; the
+            // others are its attachments.
+            let display_name = match (planned.is_empty(), item.title.trim()) {
+                (true, title) if !title.is_empty() => Some(match relative.extension() {
+                    Some(ext) => format!("{title}.{}", ext.to_string_lossy()),
+                    None => title.to_string(),
+                }),
+                _ => None,
+            };
+            planned.push(ImportItem {
+                mime_type: mime::from_extension(&relative).to_string(),
+                path,
+                display_name,
+                project: project.clone(),
+                tags: tags.clone(),
+                attachments: vec![],
+            });
+        }
+        if let Some((first, rest)) = planned.split_first_mut() {
+            first.attachments = rest.iter().map(|other| other.path.clone()).collect();
+        }
+        plan.items.extend(planned);
+    }
+    Ok(plan)
+}
+
+fn is(node: Node, namespace: &str, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().namespace() == Some(namespace)
+        && node.tag_name().name() == name
+}
+
+fn text_of(node: Node) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn child_text(node: Node, namespace: &str, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| is(*child, namespace, name))
+        .map(text_of)
+}
+
+/// The `rdf:resource` of each `namespace:name` child.
+fn resources(node: Node, namespace: &str, name: &str) -> Vec<String> {
+    node.children()
+        .filter(|child| is(*child, namespace, name))
+        .filter_map(|child| child.attribute((RDF_NS, "resource")))
+        .map(str::to_string)
+        .collect()
+}
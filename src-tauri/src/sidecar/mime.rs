@@ -0,0 +1,171 @@
+//! Content-based MIME detection. The caller's MIME hint and the file extension
+//! pick the parser unless the file's magic bytes clearly disagree, e.g. a PDF
+//! renamed `.txt` or a text file saved as `.pdf`. Both the declared and the
+//! detected type end up in the document metadata.
+
+use std::{io::Read, path::Path};
+
+use crate::{
+    core::errors::{AppError, AppResult},
+    sidecar::sniff,
+};
+
+/// Bytes read from the start of a file for detection; OOXML packages need a
+/// few local file headers to be told apart from plain ZIPs.
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Which parser handles a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Pdf,
+    Docx,
+    Xlsx,
+    Pptx,
+    Epub,
+    Image,
+    Html,
+    Text,
+}
+
+impl Format {
+    /// Format named by a MIME hint and file extension.
+    pub fn from_hint(mime: &str, ext: &str) -> Self {
+        let mime = mime.trim().to_ascii_lowercase();
+        let ext = ext.to_ascii_lowercase();
+        if mime.contains("pdf") || ext == "pdf" {
+            Self::Pdf
+        } else if mime.contains("wordprocessingml") || ext == "docx" {
+            Self::Docx
+        } else if mime.contains("spreadsheetml") || ext == "xlsx" || ext == "xls" || ext == "xlsm" {
+            Self::Xlsx
+        } else if mime.contains("presentationml") || ext == "pptx" {
+            Self::Pptx
+        } else if mime.contains("epub") || ext == "epub" {
+            Self::Epub
+        } else if mime.contains("image")
+            || matches!(
+                ext.as_str(),
+                "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "tiff" | "tif"
+            )
+        {
+            Self::Image
+        } else if mime.contains("html") || matches!(ext.as_str(), "html" | "htm" | "xhtml") {
+            Self::Html
+        } else {
+            Self::Text
+        }
+    }
+
+    /// Formats read from text, where the hint rather than content detection
+    /// tells them apart: a Markdown file may well open with an HTML comment.
+    fn is_text(self) -> bool {
+        matches!(self, Self::Html | Self::Text)
+    }
+
+    /// Format of a MIME type reported by content detection, if we parse it.
+    fn from_detected(mime: &str) -> Option<Self> {
+        match mime {
+            "application/pdf" => Some(Self::Pdf),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+                Some(Self::Docx)
+            }
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            | "application/vnd.ms-excel" => Some(Self::Xlsx),
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation" => {
+                Some(Self::Pptx)
+            }
+            "application/epub+zip" => Some(Self::Epub),
+            "image/jpeg" | "image/png" | "image/gif" | "image/bmp" | "image/webp"
+            | "image/tiff" => Some(Self::Image),
+            "text/html" => Some(Self::Html),
+            mime if mime.starts_with("text/") || mime == "application/xml" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedMime {
+    /// What the caller said the file is.
+    pub declared: String,
+    /// What the file's content says, when it can tell.
+    pub detected: Option<String>,
+    /// The type the document is parsed and stored as.
+    pub mime: String,
+    pub format: Format,
+}
+
+/// Works out how to parse `file_path`. The hint wins unless detection names a
+/// different format we parse; a ZIP that is not recognisably OOXML, an
+/// unknown binary, or HTML against plain text leaves the hint alone.
+pub fn resolve(file_path: &Path, declared: &str) -> AppResult<ResolvedMime> {
+    let mut sample = Vec::new();
+    std::fs::File::open(file_path)
+        .and_then(|file| file.take(SAMPLE_BYTES).read_to_end(&mut sample))
+        .map_err(|e| AppError::Io(format!("cannot read file: {e}")))?;
+
+    let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let hinted = Format::from_hint(declared, ext);
+    let detected = detect(&sample);
+    let detected_format = detected.as_deref().and_then(Format::from_detected);
+
+    let (mime, format) = match (detected_format, detected.as_deref()) {
+        (Some(format), Some(detected))
+            if format != hinted && !(format.is_text() && hinted.is_text()) =>
+        {
+            (detected.to_string(), format)
+        }
+        _ => (declared.trim().to_string(), hinted),
+    };
+    Ok(ResolvedMime {
+        declared: declared.trim().to_string(),
+        detected,
+        mime,
+        format,
+    })
+}
+
+/// The MIME hint for a file the user did not pick by hand, from its
+/// extension; content detection still has the last word.
+pub fn from_extension(file_path: &Path) -> &'static str {
+    let ext = file_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "pdf" => "application/pdf",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "xls" => "application/vnd.ms-excel",
+        "epub" => "application/epub+zip",
+        "csv" => "text/csv",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "tiff" | "tif" => "image/tiff",
+        _ => "application/octet-stream",
+    }
+}
+
+/// MIME type of a file from its first bytes. Content with no known signature
+/// that does not look binary is reported as `text/plain`.
+pub fn detect(sample: &[u8]) -> Option<String> {
+    if let Some(kind) = infer::get(sample) {
+        return Some(kind.mime_type().to_string());
+    }
+    // PDF allows junk before the header within the first kilobyte.
+    if sample[..sample.len().min(1024)]
+        .windows(5)
+        .any(|window| window == b"%PDF-")
+    {
+        return Some("application/pdf".to_string());
+    }
+    if sniff::binary_kind(sample).is_none() {
+        return Some("text/plain".to_string());
+    }
+    None
+}
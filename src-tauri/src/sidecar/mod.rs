@@ -1,2 +1,20 @@
+pub mod anchors;
+pub mod dates;
+pub mod docling_client;
+pub mod encoding;
+pub mod figures;
+pub mod html;
+pub mod ignore;
+pub mod importers;
+pub mod mime;
 pub mod native_parser;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod pdf_layout;
+pub mod pdf_text;
+pub mod preview;
+pub mod quantities;
+pub mod references;
+pub mod sniff;
+pub mod thumbnails;
 pub mod types;
@@ -7,63 +7,345 @@
 //! `#` prefixes, DOCX style names) so PDFs and DOCX files yield a proper
 //! two-level tree instead of a flat list of chunks.
 
-use std::path::Path;
+use std::{collections::HashMap, io::Read, path::Path};
 
 use image::GenericImageView;
+use rayon::prelude::*;
 use serde_json::Value;
 use uuid::Uuid;
 
-use crate::core::errors::{AppError, AppResult};
+use crate::core::{
+    errors::{AppError, AppResult},
+    types::{ParserKind, ParserOverride},
+};
+use crate::sidecar::{
+    encoding, html,
+    mime::{self, Format},
+    pdf_layout, pdf_text, references, sniff,
+};
 use crate::sidecar::types::{NormalizedPayload, SidecarDocument, SidecarEdge, SidecarNode};
 
 const CHUNK_SIZE: usize = 600;
 const HEADING_MAX_LEN: usize = 120;
+/// Pages read up front to find a PDF's running headers and footers.
+const BOILERPLATE_SAMPLE_PAGES: usize = 16;
+
+/// How large a file the parser will take on, and when it stops reading text
+/// files whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// The project's ceiling for any file; `None` leaves only `max_in_memory_bytes`.
+    pub max_file_bytes: Option<u64>,
+    /// PDF, Office and image files are read whole, so they are refused above this.
+    pub max_in_memory_bytes: u64,
+    /// Text files above this are chunked line by line instead of read whole.
+    pub stream_text_above_bytes: u64,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: None,
+            max_in_memory_bytes: 256 * 1024 * 1024,
+            stream_text_above_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl ParseLimits {
+    pub fn with_max_file_bytes(self, max_file_bytes: Option<u64>) -> Self {
+        Self {
+            max_file_bytes,
+            ..self
+        }
+    }
+}
 
 // ─────────────────────────────────────────────────────────────────────────────
 
 pub fn parse(file_path: &Path, mime_type: &str) -> AppResult<NormalizedPayload> {
-    let mime = mime_type.trim().to_ascii_lowercase();
-    let ext = file_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_ascii_lowercase();
-
-    if mime.contains("pdf") || ext == "pdf" {
-        parse_pdf(file_path)
-    } else if mime.contains("wordprocessingml") || ext == "docx" {
-        parse_docx(file_path)
-    } else if mime.contains("spreadsheetml") || ext == "xlsx" || ext == "xls" || ext == "xlsm" {
-        parse_xlsx(file_path)
-    } else if mime.contains("presentationml") || ext == "pptx" {
-        parse_pptx(file_path)
-    } else if mime.contains("image") || matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "tiff" | "tif") {
-        parse_image(file_path)
-    } else {
-        parse_text(file_path)
+    parse_with_limits(file_path, mime_type, ParseLimits::default())
+}
+
+pub fn parse_with_limits(
+    file_path: &Path,
+    mime_type: &str,
+    limits: ParseLimits,
+) -> AppResult<NormalizedPayload> {
+    parse_with_progress(
+        file_path,
+        mime_type,
+        limits,
+        &ParserOverride::default(),
+        &mut |_, _| {},
+    )
+}
+
+/// [`parse_with_limits`] that honours a user's `parser_override` and calls
+/// `on_page(done, total)` after each PDF page.
+pub fn parse_with_progress(
+    file_path: &Path,
+    mime_type: &str,
+    limits: ParseLimits,
+    parser_override: &ParserOverride,
+    on_page: &mut dyn FnMut(u32, u32),
+) -> AppResult<NormalizedPayload> {
+    let size_bytes = check_file_size(file_path, limits.max_file_bytes)?;
+    let resolved = mime::resolve(file_path, mime_type)?;
+
+    let check_in_memory = || {
+        if size_bytes > limits.max_in_memory_bytes {
+            return Err(AppError::FileTooLarge {
+                size_bytes,
+                limit_bytes: limits.max_in_memory_bytes,
+            });
+        }
+        Ok(())
+    };
+    let (format, headings) = match parser_override.parser {
+        None => (resolved.format, Headings::Heuristic),
+        Some(ParserKind::Pdf) => (Format::Pdf, Headings::Heuristic),
+        Some(ParserKind::Docx) => (Format::Docx, Headings::Heuristic),
+        Some(ParserKind::Xlsx) => (Format::Xlsx, Headings::Heuristic),
+        Some(ParserKind::Pptx) => (Format::Pptx, Headings::Heuristic),
+        Some(ParserKind::Epub) => (Format::Epub, Headings::Heuristic),
+        Some(ParserKind::Image) => (Format::Image, Headings::Heuristic),
+        Some(ParserKind::Html) => (Format::Html, Headings::Heuristic),
+        Some(ParserKind::PlainText) => (Format::Text, Headings::Heuristic),
+        Some(ParserKind::MarkdownStrict) => (Format::Text, Headings::MarkdownStrict),
+        Some(ParserKind::External) => {
+            return Err(AppError::InvalidInput(
+                "the external parser runs through docling_client, not the native parser"
+                    .to_string(),
+            ))
+        }
+    };
+    if format != Format::Text {
+        check_in_memory()?;
+    }
+    let mut payload = match format {
+        Format::Pdf => parse_pdf(file_path, parser_override, on_page),
+        Format::Docx => parse_docx(file_path),
+        Format::Xlsx => parse_xlsx(file_path),
+        Format::Pptx => parse_pptx(file_path),
+        Format::Epub => parse_epub(file_path),
+        Format::Image => parse_image(file_path),
+        Format::Html => parse_html(file_path),
+        Format::Text if size_bytes > limits.stream_text_above_bytes => {
+            parse_text_streamed(file_path, headings)
+        }
+        Format::Text => parse_text(file_path, headings),
+    }?;
+    record_source(&mut payload, resolved, parser_override)?;
+    Ok(payload)
+}
+
+/// Notes the declared and detected MIME types, and any override, on a payload
+/// from either the native or an external parser.
+pub fn record_source(
+    payload: &mut NormalizedPayload,
+    resolved: mime::ResolvedMime,
+    parser_override: &ParserOverride,
+) -> AppResult<()> {
+    payload.record_metadata("mime", resolved.mime.into());
+    payload.record_metadata("declaredMime", resolved.declared.into());
+    payload.record_metadata("detectedMime", resolved.detected.into());
+    if *parser_override != ParserOverride::default() {
+        payload.record_metadata(
+            "parserOverride",
+            serde_json::to_value(parser_override)
+                .map_err(|e| AppError::Internal(format!("parser override: {e}")))?,
+        );
+    }
+    Ok(())
+}
+
+/// Size of the file at `file_path`, or [`AppError::FileTooLarge`] when it is over `limit_bytes`.
+pub fn check_file_size(file_path: &Path, limit_bytes: Option<u64>) -> AppResult<u64> {
+    let size_bytes = std::fs::metadata(file_path)
+        .map_err(|e| AppError::Io(format!("cannot stat file: {e}")))?
+        .len();
+    match limit_bytes {
+        Some(limit_bytes) if size_bytes > limit_bytes => Err(AppError::FileTooLarge {
+            size_bytes,
+            limit_bytes,
+        }),
+        _ => Ok(size_bytes),
     }
 }
 
 // ── PDF ───────────────────────────────────────────────────────────────────────
 
-fn parse_pdf(file_path: &Path) -> AppResult<NormalizedPayload> {
-    let bytes = std::fs::read(file_path)
-        .map_err(|e| AppError::Io(format!("cannot read PDF: {e}")))?;
-    
-    let text = pdf_extract::extract_text_from_mem(&bytes)
-        .map_err(|e| {
-            eprintln!("PDF extraction error for {:?}: {}", file_path, e);
-            AppError::Sidecar(format!("pdf-extract failed: {e}"))
-        })?;
-    
-    if text.trim().is_empty() {
+/// Extracts one page at a time and feeds it straight into the section builder,
+/// so only the parsed object tree and a single page of text are held at once
+/// rather than the whole document's text. A spread of up to
+/// `BOILERPLATE_SAMPLE_PAGES` pages is read first to learn the running headers
+/// and footers, which are then cut from every page. Page text comes from
+/// glyph positions so two-column pages read one column at a time.
+fn parse_pdf(
+    file_path: &Path,
+    options: &ParserOverride,
+    on_page: &mut dyn FnMut(u32, u32),
+) -> AppResult<NormalizedPayload> {
+    let mut doc = lopdf::Document::load(file_path).map_err(|e| {
+        eprintln!("PDF load error for {:?}: {}", file_path, e);
+        AppError::Sidecar(format!("pdf load failed: {e}"))
+    })?;
+    if doc.is_encrypted() {
+        doc.decrypt("")
+            .map_err(|e| AppError::Sidecar(format!("pdf decryption failed: {e}")))?;
+    }
+
+    let columns = options.pdf_columns.unwrap_or(true);
+    let extract = |page_number: u32| {
+        let text = if columns {
+            pdf_layout::page_text(&doc, page_number)
+        } else {
+            pdf_extract::extract_text_by_page(&doc, page_number).map_err(|e| {
+                AppError::Sidecar(format!("pdf-extract failed on page {page_number}: {e}"))
+            })
+        };
+        text.inspect_err(|e| {
+            eprintln!("PDF extraction error for {:?} page {}: {}", file_path, page_number, e);
+        })
+    };
+    let page_numbers = doc.get_pages().into_keys().collect::<Vec<_>>();
+    let total = page_numbers.len() as u32;
+    let step = page_numbers.len().div_ceil(BOILERPLATE_SAMPLE_PAGES).max(1);
+    let mut sampled = HashMap::new();
+    if options.pdf_boilerplate.unwrap_or(true) {
+        for &page_number in page_numbers.iter().step_by(step) {
+            sampled.insert(page_number, extract(page_number)?);
+        }
+    }
+    let boilerplate =
+        pdf_text::Boilerplate::detect(&sampled.values().map(String::as_str).collect::<Vec<_>>());
+
+    let mut sections = SectionBuilder::default();
+    let mut has_text = false;
+    let mut stripped_lines = 0;
+    for (done, page_number) in page_numbers.into_iter().enumerate() {
+        let text = match sampled.remove(&page_number) {
+            Some(text) => text,
+            None => extract(page_number)?,
+        };
+        let (text, stripped) = boilerplate.strip(&text);
+        stripped_lines += stripped;
+        has_text |= !text.trim().is_empty();
+        for block in text.split("\n\n") {
+            sections.push_paragraph(&pdf_text::normalize_block(block));
+        }
+        on_page(done as u32 + 1, total);
+    }
+
+    if !has_text {
+        return parse_scanned_pdf(file_path, &doc, on_page);
+    }
+
+    let title = stem(file_path);
+    let mut payload = build_hierarchy(title, i64::from(total.max(1)), sections.finish())?;
+    payload.record_metadata("boilerplateSamples", boilerplate.samples().into());
+    payload.record_metadata("boilerplateLinesStripped", stripped_lines.into());
+    payload.record_metadata("pdfInfo", references::pdf_info(&doc));
+    Ok(payload)
+}
+
+/// A PDF without a text layer, read page by page with OCR. Each page with
+/// text becomes a section, headed by its first line when that looks like a
+/// heading, and its nodes are marked `ocr` with the page's confidence.
+#[cfg(feature = "ocr")]
+fn parse_scanned_pdf(
+    file_path: &Path,
+    doc: &lopdf::Document,
+    on_page: &mut dyn FnMut(u32, u32),
+) -> AppResult<NormalizedPayload> {
+    use crate::sidecar::ocr;
+
+    let language = ocr::language();
+    let pages = doc.get_pages();
+    let total = pages.len() as u32;
+    let mut report = Vec::new();
+    let mut sections = Vec::new();
+    let mut section_pages = Vec::new();
+    for (done, (page_number, page_id)) in pages.into_iter().enumerate() {
+        let text = match ocr::page_image(doc, page_id) {
+            Ok(image) => ocr::recognize(&image, &language)?,
+            Err(reason) => {
+                report.push(ocr::OcrPage {
+                    page: page_number,
+                    confidence: None,
+                    words: 0,
+                    skipped: Some(reason),
+                });
+                on_page(done as u32 + 1, total);
+                continue;
+            }
+        };
+        report.push(ocr::OcrPage {
+            page: page_number,
+            confidence: text.confidence,
+            words: text.words,
+            skipped: None,
+        });
+        if let Some(section) = ocr_section(text.paragraphs, format!("Page {page_number}")) {
+            sections.push(section);
+            section_pages.push((i64::from(page_number), text.confidence));
+        }
+        on_page(done as u32 + 1, total);
+    }
+    if sections.is_empty() {
         return Err(AppError::InvalidInput(
-            "PDF contains no extractable text (may be image-based or encrypted)".to_string()
+            "PDF contains no extractable text, and OCR read none from its pages".to_string(),
         ));
     }
-    
-    let title = stem(file_path);
-    build_hierarchy(title, 1, text_to_sections(&text))
+
+    let mut payload = build_hierarchy(stem(file_path), i64::from(total.max(1)), sections)?;
+    let mut page = section_pages.into_iter();
+    let mut current = None;
+    for node in payload.nodes.iter_mut().skip(1) {
+        if node.node_type == "Section" {
+            current = page.next();
+        }
+        if let Some((page_number, confidence)) = current {
+            node.page_start = Some(page_number);
+            node.page_end = Some(page_number);
+            node.metadata["ocr"] = true.into();
+            node.metadata["ocrConfidence"] = confidence.into();
+        }
+    }
+    payload.record_metadata(
+        "ocr",
+        serde_json::json!({ "engine": "tesseract", "language": language, "pages": report }),
+    );
+    payload.record_metadata("pdfInfo", references::pdf_info(doc));
+    Ok(payload)
+}
+
+/// Paragraphs OCR read as a section, headed by the first when that looks like
+/// a heading and by `heading` otherwise; `None` when there is no text.
+#[cfg(feature = "ocr")]
+fn ocr_section(mut paragraphs: Vec<String>, heading: String) -> Option<Section> {
+    let found = (paragraphs.len() > 1 && looks_like_heading(&paragraphs[0]))
+        .then(|| clean_heading(&paragraphs.remove(0)));
+    let paragraphs = paragraphs
+        .iter()
+        .flat_map(|paragraph| text_to_chunks(paragraph))
+        .collect::<Vec<_>>();
+    (!paragraphs.is_empty()).then(|| Section {
+        heading: found.unwrap_or(heading),
+        paragraphs,
+    })
+}
+
+#[cfg(not(feature = "ocr"))]
+fn parse_scanned_pdf(
+    _file_path: &Path,
+    _doc: &lopdf::Document,
+    _on_page: &mut dyn FnMut(u32, u32),
+) -> AppResult<NormalizedPayload> {
+    Err(AppError::InvalidInput(
+        "PDF contains no extractable text (may be image-based or encrypted)".to_string(),
+    ))
 }
 
 // ── DOCX ──────────────────────────────────────────────────────────────────────
@@ -132,8 +414,6 @@ fn parse_docx_with_docx_rs(bytes: &[u8]) -> AppResult<Vec<(bool, String)>> {
 }
 
 fn parse_docx_with_xml_fallback(bytes: &[u8]) -> AppResult<Vec<(bool, String)>> {
-    use std::io::Read;
-
     let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
         .map_err(|e| AppError::Sidecar(format!("zip open failed: {e}")))?;
     let mut doc_xml = archive
@@ -203,17 +483,25 @@ fn parse_docx_with_xml_fallback(bytes: &[u8]) -> AppResult<Vec<(bool, String)>>
 
 // ── XLSX ──────────────────────────────────────────────────────────────────────
 
+/// Sheets are read in parallel, each rayon worker holding its own handle on the
+/// workbook since calamine readers need `&mut`. Sections keep the workbook's
+/// sheet order.
 fn parse_xlsx(file_path: &Path) -> AppResult<NormalizedPayload> {
     use calamine::{open_workbook_auto, Reader};
 
-    let mut workbook = open_workbook_auto(file_path)
-        .map_err(|e| AppError::Sidecar(format!("calamine failed: {e}")))?;
-
-    let sheet_names = workbook.sheet_names().to_vec();
-    let mut sections: Vec<Section> = Vec::new();
-
-    for sheet_name in &sheet_names {
-        if let Some(Ok(range)) = workbook.worksheet_range(sheet_name) {
+    let open = || {
+        open_workbook_auto(file_path)
+            .map_err(|e| AppError::Sidecar(format!("calamine failed: {e}")))
+    };
+    let sheet_names = open()?.sheet_names().to_vec();
+
+    let sheets = sheet_names
+        .par_iter()
+        .map_init(open, |workbook, sheet_name| {
+            let workbook = workbook.as_mut().map_err(|e| AppError::Sidecar(e.to_string()))?;
+            let Some(Ok(range)) = workbook.worksheet_range(sheet_name) else {
+                return Ok(None);
+            };
             let mut rows: Vec<String> = Vec::new();
             for row in range.rows() {
                 let cells: Vec<String> = row.iter().map(ToString::to_string).collect();
@@ -222,15 +510,16 @@ fn parse_xlsx(file_path: &Path) -> AppResult<NormalizedPayload> {
                     rows.push(line);
                 }
             }
-            if !rows.is_empty() {
-                let paragraphs = text_to_chunks(&rows.join("\n"));
-                sections.push(Section {
-                    heading: format!("Sheet: {sheet_name}"),
-                    paragraphs,
-                });
+            if rows.is_empty() {
+                return Ok(None);
             }
-        }
-    }
+            Ok(Some(Section {
+                heading: format!("Sheet: {sheet_name}"),
+                paragraphs: text_to_chunks(&rows.join("\n")),
+            }))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+    let sections: Vec<Section> = sheets.into_iter().flatten().collect();
 
     if sections.is_empty() {
         return Err(AppError::InvalidInput(
@@ -253,27 +542,35 @@ fn parse_pptx(file_path: &Path) -> AppResult<NormalizedPayload> {
         .parse_all()
         .map_err(|e| AppError::Sidecar(format!("pptx-to-md parse failed: {e}")))?;
 
-    let mut sections: Vec<Section> = Vec::new();
-    for (i, slide) in slides.iter().enumerate() {
-        let md = slide.convert_to_md().unwrap_or_default();
-        let text = md.trim().to_string();
-        if text.is_empty() {
-            continue;
-        }
-        let mut lines = text.lines();
-        let heading = lines
-            .next()
-            .map(clean_pptx_heading)
-            .filter(|l| !l.is_empty())
-            .unwrap_or_else(|| format!("Slide {}", i + 1));
-        let body: String = lines.collect::<Vec<_>>().join("\n").trim().to_string();
-        let paragraphs = if body.is_empty() {
-            vec![text]
-        } else {
-            text_to_chunks(&body)
-        };
-        sections.push(Section { heading, paragraphs });
-    }
+    // Markdown conversion (including embedded images) runs per slide in
+    // parallel; the indexed collect keeps slide order.
+    let sections: Vec<Section> = slides
+        .par_iter()
+        .enumerate()
+        .map(|(i, slide)| {
+            let md = slide.convert_to_md().unwrap_or_default();
+            let text = md.trim().to_string();
+            if text.is_empty() {
+                return None;
+            }
+            let mut lines = text.lines();
+            let heading = lines
+                .next()
+                .map(clean_pptx_heading)
+                .filter(|l| !l.is_empty())
+                .unwrap_or_else(|| format!("Slide {}", i + 1));
+            let body: String = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+            let paragraphs = if body.is_empty() {
+                vec![text]
+            } else {
+                text_to_chunks(&body)
+            };
+            Some(Section { heading, paragraphs })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .flatten()
+        .collect();
 
     if sections.is_empty() {
         return Err(AppError::InvalidInput(
@@ -286,16 +583,519 @@ fn parse_pptx(file_path: &Path) -> AppResult<NormalizedPayload> {
 
 // ── Plain text / Markdown / fallback ─────────────────────────────────────────
 
-fn parse_text(file_path: &Path) -> AppResult<NormalizedPayload> {
-    let text = std::fs::read_to_string(file_path)
+fn parse_text(file_path: &Path, headings: Headings) -> AppResult<NormalizedPayload> {
+    let bytes = std::fs::read(file_path)
         .map_err(|e| AppError::Io(format!("cannot read file as text: {e}")))?;
-    build_hierarchy(stem(file_path), 1, text_to_sections(&text))
+    sniff::ensure_text(&bytes)?;
+    let (text, encoding, lossy) = encoding::decode(&bytes);
+    let mut payload = build_hierarchy(stem(file_path), 1, text_to_sections(&text, headings))?;
+    encoding::record(&mut payload, encoding, lossy);
+    Ok(payload)
+}
+
+/// Block-by-block variant of [`parse_text`] for files too big to hold twice in
+/// memory. The encoding is picked from the first block; paragraphs end at blank
+/// lines or once they reach `CHUNK_SIZE`, so a log with no blank lines still
+/// becomes many small chunks.
+fn parse_text_streamed(file_path: &Path, headings: Headings) -> AppResult<NormalizedPayload> {
+    let read_error = |e: std::io::Error| AppError::Io(format!("cannot read file as text: {e}"));
+    let mut file = std::fs::File::open(file_path).map_err(read_error)?;
+    let mut block = vec![0u8; encoding::SAMPLE_BYTES];
+    let mut filled = 0;
+    while filled < block.len() {
+        let read = file.read(&mut block[filled..]).map_err(read_error)?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    sniff::ensure_text(&block[..filled])?;
+    let source_encoding = encoding::detect(&block[..filled], filled < block.len());
+    let mut decoder = source_encoding.new_decoder();
+
+    let mut lines = LineChunker::new(headings);
+    let mut pending = String::new();
+    let mut lossy = false;
+    loop {
+        let last = filled == 0;
+        pending.reserve(
+            decoder
+                .max_utf8_buffer_length(filled)
+                .unwrap_or(filled * 3 + 4),
+        );
+        let (_, _, replaced) = decoder.decode_to_string(&block[..filled], &mut pending, last);
+        lossy |= replaced;
+
+        let mut consumed = 0;
+        while let Some(offset) = pending[consumed..].find('\n') {
+            lines.push_line(&pending[consumed..consumed + offset]);
+            consumed += offset + 1;
+        }
+        pending.drain(..consumed);
+        if last {
+            lines.push_line(&pending);
+            break;
+        }
+        filled = file.read(&mut block).map_err(read_error)?;
+    }
+
+    let mut payload = build_hierarchy(stem(file_path), 1, lines.finish())?;
+    encoding::record(&mut payload, decoder.encoding(), lossy);
+    Ok(payload)
+}
+
+/// Groups streamed lines into paragraphs for a [`SectionBuilder`].
+struct LineChunker {
+    sections: SectionBuilder,
+    paragraph: String,
+}
+
+impl LineChunker {
+    fn new(headings: Headings) -> Self {
+        Self {
+            sections: SectionBuilder::new(headings),
+            paragraph: String::new(),
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            self.sections.push_paragraph(&self.paragraph);
+            self.paragraph.clear();
+            return;
+        }
+        if !self.paragraph.is_empty() {
+            self.paragraph.push('\n');
+        }
+        self.paragraph.push_str(line);
+        if self.paragraph.len() >= CHUNK_SIZE {
+            self.sections.push_paragraph(&self.paragraph);
+            self.paragraph.clear();
+        }
+    }
+
+    fn finish(mut self) -> Vec<Section> {
+        self.sections.push_paragraph(&self.paragraph);
+        self.sections.finish()
+    }
+}
+
+// ── HTML ──────────────────────────────────────────────────────────────────────
+
+/// Headings nest the way the page nests them: the top heading level used
+/// becomes Sections and deeper ones Subsections of the heading above them.
+/// A lone `<h1>` opening the page titles the document instead. Text before
+/// the first heading goes in an "Overview" section.
+fn parse_html(file_path: &Path) -> AppResult<NormalizedPayload> {
+    let bytes =
+        std::fs::read(file_path).map_err(|e| AppError::Io(format!("cannot read HTML: {e}")))?;
+    sniff::ensure_text(&bytes)?;
+    let (text, encoding, lossy) = encoding::decode(&bytes);
+    let mut page = html::parse(&text);
+
+    let h1_count = page
+        .blocks
+        .iter()
+        .filter(|block| matches!(block, html::Block::Heading { level: 1, .. }))
+        .count();
+    let mut title = page.title.take().unwrap_or_else(|| stem(file_path));
+    if h1_count == 1 {
+        if let Some(html::Block::Heading { level: 1, text }) = page.blocks.first() {
+            title = text.clone();
+            page.blocks.remove(0);
+        }
+    }
+    if !has_text(&page.blocks) {
+        return Err(AppError::InvalidInput(
+            "native parser: HTML contains no extractable text".to_string(),
+        ));
+    }
+    let part = HtmlPart {
+        lead_title: "Overview".to_string(),
+        blocks: page.blocks,
+        metadata: serde_json::json!({}),
+    };
+    let mut payload = build_html_tree(title, 1, vec![part]);
+    encoding::record(&mut payload, encoding, lossy);
+    Ok(payload)
+}
+
+fn has_text(blocks: &[html::Block]) -> bool {
+    blocks
+        .iter()
+        .any(|block| !matches!(block, html::Block::Heading { .. }))
+}
+
+/// A run of blocks whose headings nest among themselves: a whole page, or
+/// one chapter of an EPUB. Text before the first heading goes under a
+/// section titled `lead_title`, and `metadata` is added to every node.
+struct HtmlPart {
+    lead_title: String,
+    blocks: Vec<html::Block>,
+    metadata: Value,
+}
+
+/// An open heading in [`build_html_tree`]: its level (7 for the implicit
+/// Overview), node id, ordinal path and how many children it has so far.
+struct OpenHeading {
+    level: u8,
+    id: String,
+    ordinal: String,
+    children: usize,
+}
+
+fn build_html_tree(title: String, pages: i64, parts: Vec<HtmlPart>) -> NormalizedPayload {
+    let root_id = format!("root-{}", Uuid::new_v4());
+    let mut nodes = vec![SidecarNode {
+        id: root_id.clone(),
+        parent_id: None,
+        node_type: "Document".to_string(),
+        title: title.clone(),
+        text: String::new(),
+        page_start: Some(1),
+        page_end: Some(pages),
+        ordinal_path: "root".to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({ "parser": "native" }),
+    }];
+    let mut edges: Vec<SidecarEdge> = Vec::new();
+    let mut root_children = 0;
+
+    let mut add_node = |open: &mut Vec<OpenHeading>,
+                        node_type: &str,
+                        title: String,
+                        text: String,
+                        mut metadata: Value,
+                        shared: &Value| {
+        let (parent_id, ordinal) = match open.last_mut() {
+            Some(parent) => {
+                parent.children += 1;
+                (
+                    parent.id.clone(),
+                    format!("{}.{}", parent.ordinal, parent.children),
+                )
+            }
+            None => {
+                root_children += 1;
+                (root_id.clone(), root_children.to_string())
+            }
+        };
+        let prefix = match node_type {
+            "Section" | "Subsection" => "s",
+            _ => "p",
+        };
+        let id = format!("{prefix}-{}", Uuid::new_v4());
+        if let (Some(metadata), Some(shared)) = (metadata.as_object_mut(), shared.as_object()) {
+            metadata.extend(shared.clone());
+        }
+        metadata["parser"] = "native".into();
+        nodes.push(SidecarNode {
+            id: id.clone(),
+            parent_id: Some(parent_id.clone()),
+            node_type: node_type.to_string(),
+            title,
+            text,
+            page_start: None,
+            page_end: None,
+            ordinal_path: ordinal.clone(),
+            bbox: Value::Null,
+            metadata,
+        });
+        edges.push(SidecarEdge {
+            from: parent_id,
+            to: id.clone(),
+            relation: "contains".to_string(),
+        });
+        (id, ordinal)
+    };
+
+    for part in parts {
+        // Headings never nest across parts.
+        let mut open: Vec<OpenHeading> = Vec::new();
+        for block in part.blocks {
+            if let html::Block::Heading { level, text } = block {
+                while open.last().is_some_and(|heading| heading.level >= level) {
+                    open.pop();
+                }
+                let node_type = if open.is_empty() {
+                    "Section"
+                } else {
+                    "Subsection"
+                };
+                let (id, ordinal) = add_node(
+                    &mut open,
+                    node_type,
+                    text,
+                    String::new(),
+                    serde_json::json!({ "tag": format!("h{level}") }),
+                    &part.metadata,
+                );
+                open.push(OpenHeading {
+                    level,
+                    id,
+                    ordinal,
+                    children: 0,
+                });
+                continue;
+            }
+            if open.is_empty() {
+                let (id, ordinal) = add_node(
+                    &mut open,
+                    "Section",
+                    part.lead_title.clone(),
+                    String::new(),
+                    serde_json::json!({}),
+                    &part.metadata,
+                );
+                open.push(OpenHeading {
+                    level: 7,
+                    id,
+                    ordinal,
+                    children: 0,
+                });
+            }
+            let position = open.last().map_or(0, |parent| parent.children) + 1;
+            let (node_type, title, text, metadata) = match block {
+                html::Block::Paragraph { text, list_item } => (
+                    "Paragraph",
+                    format!("\u{00b6} {position}"),
+                    text,
+                    serde_json::json!({ "kind": if list_item { "list_item" } else { "paragraph" } }),
+                ),
+                html::Block::Table { caption, rows } => {
+                    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+                    (
+                        "Table",
+                        caption.unwrap_or_else(|| format!("Table {position}")),
+                        markdown_table(&rows, columns),
+                        serde_json::json!({
+                            "kind": "html_table",
+                            "rows": rows.len(),
+                            "columns": columns,
+                        }),
+                    )
+                }
+                html::Block::Figure { src, alt, caption } => {
+                    let mut text = format!("![{alt}]({src})");
+                    if let Some(caption) = &caption {
+                        text.push('\n');
+                        text.push_str(caption);
+                    }
+                    let title = caption
+                        .clone()
+                        .or_else(|| (!alt.is_empty()).then(|| alt.clone()))
+                        .unwrap_or_else(|| format!("Figure {position}"));
+                    (
+                        "Figure",
+                        title,
+                        text,
+                        serde_json::json!({ "kind": "html_image", "src": src, "alt": alt }),
+                    )
+                }
+                html::Block::Heading { .. } => unreachable!("headings are handled above"),
+            };
+            add_node(&mut open, node_type, title, text, metadata, &part.metadata);
+        }
+    }
+
+    NormalizedPayload {
+        document: SidecarDocument {
+            title,
+            pages,
+            metadata: serde_json::json!({ "parser": "native" }),
+        },
+        nodes,
+        edges,
+    }
+}
+
+/// Table rows as a Markdown table headed by the first row, padded to
+/// `columns` cells.
+fn markdown_table(rows: &[Vec<String>], columns: usize) -> String {
+    let line = |cells: &[String]| {
+        let cells = (0..columns)
+            .map(|i| cells.get(i).map_or("", String::as_str).replace('|', "\\|"))
+            .collect::<Vec<_>>();
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![line(&rows[0])];
+    lines.push(format!("|{}", " --- |".repeat(columns)));
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+// ── EPUB ──────────────────────────────────────────────────────────────────────
+
+/// Reads the chapters in spine order, each the way an HTML page is read, so
+/// a chapter's headings become Sections and Subsections and headings never
+/// nest across chapters. Text before a chapter's first heading goes in a
+/// section named after the chapter.
+fn parse_epub(file_path: &Path) -> AppResult<NormalizedPayload> {
+    let file = std::fs::File::open(file_path)
+        .map_err(|e| AppError::Io(format!("cannot read EPUB: {e}")))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::Sidecar(format!("EPUB is not a ZIP archive: {e}")))?;
+
+    let container = read_epub_entry(&mut archive, "META-INF/container.xml")?;
+    let package_path = epub_package_path(&String::from_utf8_lossy(&container))?;
+    let package = read_epub_entry(&mut archive, &package_path)?;
+    let (book_title, chapters) = epub_spine(&String::from_utf8_lossy(&package), &package_path)?;
+
+    let mut parts = Vec::new();
+    for (index, chapter) in chapters.iter().enumerate() {
+        let bytes = read_epub_entry(&mut archive, chapter)?;
+        sniff::ensure_text(&bytes).map_err(|_| {
+            AppError::Sidecar(format!(
+                "EPUB chapter {chapter} is not text; the book may be DRM-protected"
+            ))
+        })?;
+        let (text, _, _) = encoding::decode(&bytes);
+        let page = html::parse(&text);
+        if !has_text(&page.blocks) {
+            continue;
+        }
+        let number = index + 1;
+        let lead_title = page
+            .title
+            .filter(|title| Some(title) != book_title.as_ref())
+            .unwrap_or_else(|| format!("Chapter {number}"));
+        parts.push(HtmlPart {
+            lead_title,
+            blocks: page.blocks,
+            metadata: serde_json::json!({ "chapter": number, "href": chapter }),
+        });
+    }
+    if parts.is_empty() {
+        return Err(AppError::InvalidInput(
+            "native parser: EPUB contains no extractable text".to_string(),
+        ));
+    }
+
+    let title = book_title.unwrap_or_else(|| stem(file_path));
+    Ok(build_html_tree(title, chapters.len() as i64, parts))
+}
+
+fn read_epub_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> AppResult<Vec<u8>> {
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| AppError::Sidecar(format!("EPUB entry {name} missing: {e}")))?;
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| AppError::Sidecar(format!("cannot read EPUB entry {name}: {e}")))?;
+    Ok(bytes)
+}
+
+/// Path of the package document `META-INF/container.xml` points to.
+fn epub_package_path(container: &str) -> AppResult<String> {
+    let doc = roxmltree::Document::parse(container)
+        .map_err(|e| AppError::Sidecar(format!("EPUB container.xml parse failed: {e}")))?;
+    doc.descendants()
+        .filter(|node| node.is_element() && node.tag_name().name() == "rootfile")
+        .find(|node| {
+            matches!(
+                node.attribute("media-type"),
+                None | Some("application/oebps-package+xml")
+            )
+        })
+        .and_then(|node| node.attribute("full-path"))
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Sidecar("EPUB container.xml names no package".to_string()))
+}
+
+/// The book's title and the archive paths of its XHTML chapters in spine
+/// order. The navigation document is left out even when the spine lists it.
+fn epub_spine(package: &str, package_path: &str) -> AppResult<(Option<String>, Vec<String>)> {
+    let doc = roxmltree::Document::parse(package)
+        .map_err(|e| AppError::Sidecar(format!("EPUB package parse failed: {e}")))?;
+    let title = doc
+        .descendants()
+        .find(|node| node.is_element() && node.tag_name().name() == "title")
+        .and_then(|node| node.text())
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty());
+
+    let manifest = doc
+        .descendants()
+        .filter(|node| node.is_element() && node.tag_name().name() == "item")
+        .filter_map(|item| Some((item.attribute("id")?, item)))
+        .collect::<HashMap<_, _>>();
+    let base = package_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let chapters = doc
+        .descendants()
+        .filter(|node| node.is_element() && node.tag_name().name() == "itemref")
+        .filter_map(|itemref| manifest.get(itemref.attribute("idref")?))
+        .filter(|item| {
+            matches!(
+                item.attribute("media-type"),
+                Some("application/xhtml+xml" | "text/html")
+            ) && !item
+                .attribute("properties")
+                .is_some_and(|properties| properties.split_whitespace().any(|p| p == "nav"))
+        })
+        .filter_map(|item| item.attribute("href"))
+        .map(|href| epub_entry_path(base, href))
+        .collect::<Vec<_>>();
+    if chapters.is_empty() {
+        return Err(AppError::InvalidInput(
+            "native parser: EPUB spine lists no XHTML chapters".to_string(),
+        ));
+    }
+    Ok((title, chapters))
+}
+
+/// Archive path of a manifest `href`, which is percent-encoded and relative
+/// to the package document's directory.
+fn epub_entry_path(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let bytes = href.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = if bytes[i] == b'%' {
+            href.get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let href = String::from_utf8_lossy(&decoded);
+
+    let mut segments = base
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
 }
 
 // ── Image ─────────────────────────────────────────────────────────────────────
 
 fn parse_image(file_path: &Path) -> AppResult<NormalizedPayload> {
-    let img = image::open(file_path)
+    // Go by the content rather than the extension, which may be wrong.
+    let img = image::ImageReader::open(file_path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|e| AppError::Io(format!("cannot read image: {e}")))?
+        .decode()
         .map_err(|e| AppError::Sidecar(format!("image open failed: {e}")))?;
     
     let (width, height) = img.dimensions();
@@ -310,12 +1110,70 @@ fn parse_image(file_path: &Path) -> AppResult<NormalizedPayload> {
         title, format, width, height
     );
     
-    let sections = vec![Section {
+    let mut sections = vec![Section {
         heading: "Image Metadata".to_string(),
         paragraphs: vec![metadata_text],
     }];
-    
-    build_hierarchy(title, 1, sections)
+    let (text, ocr) = image_text(&img);
+    let confidence = ocr.get("confidence").cloned().unwrap_or(Value::Null);
+    sections.extend(text);
+
+    let mut payload = build_hierarchy(title, 1, sections)?;
+    if !ocr.is_null() {
+        // Everything after the metadata section was read by OCR.
+        let mut section = 0;
+        for node in payload.nodes.iter_mut().skip(1) {
+            if node.node_type == "Section" {
+                section += 1;
+            }
+            if section > 1 {
+                node.metadata["ocr"] = true.into();
+                node.metadata["ocrConfidence"] = confidence.clone();
+            }
+        }
+        payload.record_metadata("ocr", ocr);
+    }
+    Ok(payload)
+}
+
+/// The text in a picture, read with OCR the way a scanned page is, and the
+/// `ocr` metadata recording how. A screenshot or photographed page becomes
+/// searchable; when Tesseract cannot run, the image keeps only its metadata
+/// section and the error is recorded instead.
+#[cfg(feature = "ocr")]
+fn image_text(img: &image::DynamicImage) -> (Option<Section>, Value) {
+    use crate::sidecar::ocr;
+
+    let language = ocr::language();
+    let mut png = Vec::new();
+    let encoded = img
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| AppError::Sidecar(format!("image cannot be encoded for OCR: {e}")));
+    let text = encoded.and_then(|()| ocr::recognize(&png, &language));
+    match text {
+        Ok(text) => (
+            ocr_section(text.paragraphs, "Text".to_string()),
+            serde_json::json!({
+                "engine": "tesseract",
+                "language": language,
+                "confidence": text.confidence,
+                "words": text.words,
+            }),
+        ),
+        Err(err) => (
+            None,
+            serde_json::json!({
+                "engine": "tesseract",
+                "language": language,
+                "error": err.to_string(),
+            }),
+        ),
+    }
+}
+
+#[cfg(not(feature = "ocr"))]
+fn image_text(_img: &image::DynamicImage) -> (Option<Section>, Value) {
+    (None, Value::Null)
 }
 
 // ── Section detection ─────────────────────────────────────────────────────────
@@ -332,50 +1190,132 @@ enum BlockKind {
     Figure,
 }
 
-/// Split raw text into sections using heading heuristics.
-fn text_to_sections(text: &str) -> Vec<Section> {
-    let mut sections: Vec<Section> = Vec::new();
-    let mut current_heading = String::from("Overview");
-    let mut current_body: Vec<String> = Vec::new();
+/// How a [`SectionBuilder`] decides which paragraphs are headings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Headings {
+    /// [`looks_like_heading`]: short, capitalised, unpunctuated lines.
+    Heuristic,
+    /// Only ATX `#` headings outside fenced code blocks.
+    MarkdownStrict,
+}
 
+/// Split raw text into sections at heading paragraphs.
+fn text_to_sections(text: &str, headings: Headings) -> Vec<Section> {
+    let mut builder = SectionBuilder::new(headings);
     for para in text.split("\n\n") {
+        builder.push_paragraph(para);
+    }
+    builder.finish()
+}
+
+/// Collects paragraphs into sections as they arrive, starting a new section at
+/// each heading-like paragraph.
+struct SectionBuilder {
+    sections: Vec<Section>,
+    current_heading: String,
+    current_body: Vec<String>,
+    /// Heading-like paragraphs seen before any body text; they become numbered
+    /// parts if no body text ever arrives.
+    leading_headings: Vec<String>,
+    headings: Headings,
+    /// Inside a fenced code block, where `#` lines are comments, not headings.
+    in_fence: bool,
+}
+
+impl Default for SectionBuilder {
+    fn default() -> Self {
+        Self::new(Headings::Heuristic)
+    }
+}
+
+impl SectionBuilder {
+    fn new(headings: Headings) -> Self {
+        Self {
+            sections: Vec::new(),
+            current_heading: String::from("Overview"),
+            current_body: Vec::new(),
+            leading_headings: Vec::new(),
+            headings,
+            in_fence: false,
+        }
+    }
+
+    fn push_paragraph(&mut self, para: &str) {
         let para = para.trim();
         if para.is_empty() {
-            continue;
+            return;
+        }
+        if self.headings == Headings::MarkdownStrict {
+            self.push_markdown(para);
+            return;
         }
         if looks_like_heading(para) {
-            if !current_body.is_empty() {
-                sections.push(Section {
-                    heading: current_heading.clone(),
-                    paragraphs: current_body.drain(..).collect(),
-                });
+            if self.sections.is_empty() && self.current_body.is_empty() {
+                self.leading_headings.push(para.to_string());
             }
-            current_heading = clean_heading(para);
+            self.flush();
+            self.current_heading = clean_heading(para);
         } else {
-            for chunk in text_to_chunks(para) {
-                current_body.push(chunk);
+            self.push_body(para);
+        }
+    }
+
+    /// Strict Markdown: a heading is a `#` line outside a code fence, and any
+    /// lines after it in the same paragraph are body text.
+    fn push_markdown(&mut self, para: &str) {
+        let mut body = Vec::new();
+        for line in para.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                self.in_fence = !self.in_fence;
+            } else if !self.in_fence && is_atx_heading(trimmed) {
+                self.push_body(&body.join("\n"));
+                body.clear();
+                if self.sections.is_empty() && self.current_body.is_empty() {
+                    self.leading_headings.push(trimmed.to_string());
+                }
+                self.flush();
+                self.current_heading = clean_heading(trimmed.trim_end_matches('#'));
+                continue;
             }
+            body.push(line);
         }
+        self.push_body(&body.join("\n"));
     }
 
-    if !current_body.is_empty() {
-        sections.push(Section {
-            heading: current_heading,
-            paragraphs: current_body,
-        });
+    fn push_body(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        self.leading_headings.clear();
+        self.current_body.extend(text_to_chunks(text));
     }
 
-    // Fallback: no headings detected — number the chunks
-    if sections.is_empty() {
-        for (i, chunk) in text_to_chunks(text).into_iter().enumerate() {
-            sections.push(Section {
-                heading: format!("Part {}", i + 1),
-                paragraphs: vec![chunk],
+    fn flush(&mut self) {
+        if !self.current_body.is_empty() {
+            self.sections.push(Section {
+                heading: self.current_heading.clone(),
+                paragraphs: std::mem::take(&mut self.current_body),
             });
         }
     }
 
-    sections
+    fn finish(mut self) -> Vec<Section> {
+        self.flush();
+        if !self.sections.is_empty() {
+            return self.sections;
+        }
+
+        // Fallback: no headings detected — number the chunks
+        text_to_chunks(&self.leading_headings.join("\n\n"))
+            .into_iter()
+            .enumerate()
+            .map(|(i, chunk)| Section {
+                heading: format!("Part {}", i + 1),
+                paragraphs: vec![chunk],
+            })
+            .collect()
+    }
 }
 
 /// Group (is_heading, text) DOCX items into sections.
@@ -556,6 +1496,12 @@ fn looks_like_heading(para: &str) -> bool {
     starts_upper || is_mostly_upper
 }
 
+/// `# Title` through `###### Title`; `#hashtag` and `####### x` are not headings.
+fn is_atx_heading(line: &str) -> bool {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    (1..=6).contains(&level) && line[level..].starts_with([' ', '\t'])
+}
+
 /// Strip markdown `#` prefixes and trim.
 fn clean_heading(heading: &str) -> String {
     heading.trim_start_matches('#').trim().to_string()
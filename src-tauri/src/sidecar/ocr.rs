@@ -0,0 +1,253 @@
+//! OCR for scanned PDFs, whose pages are pictures with no text layer. There
+//! is no PDF rasterizer here, so each page is read through the largest image
+//! drawn on it (for a scan, the page itself) and handed to the `tesseract`
+//! executable, whose word confidences score the page. Pictures stored as
+//! CCITT, JBIG2 or JPEG 2000 are not decoded, so their pages are skipped.
+//! Image files are handed over whole.
+
+use std::{
+    io::{Cursor, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+use image::{DynamicImage, GrayImage, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::errors::{AppError, AppResult},
+    sidecar::pdf_text,
+};
+
+/// The executable run, unless `VECTORLESS_TESSERACT` names another.
+const TESSERACT: &str = "tesseract";
+/// Tesseract languages read with, unless `VECTORLESS_OCR_LANG` names others,
+/// e.g. `eng+deu`.
+const DEFAULT_LANGUAGE: &str = "eng";
+/// How much of Tesseract's stderr is quoted in an error.
+const STDERR_TAIL_BYTES: usize = 2000;
+/// Filters [`page_image`] can undo to get at raw pixels.
+const STREAM_FILTERS: &[&str] = &["FlateDecode", "LZWDecode", "ASCII85Decode"];
+
+/// What OCR read from one page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OcrText {
+    /// Paragraphs in reading order, their lines joined into running text.
+    pub paragraphs: Vec<String>,
+    /// Mean word confidence from 0 to 1; `None` when no words were read.
+    pub confidence: Option<f64>,
+    pub words: usize,
+}
+
+/// One page's entry in the `ocr` metadata of a scanned document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrPage {
+    pub page: u32,
+    pub confidence: Option<f64>,
+    pub words: usize,
+    /// Why the page was not read, e.g. a picture format not decoded here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// Tesseract languages to read with.
+pub fn language() -> String {
+    std::env::var("VECTORLESS_OCR_LANG")
+        .ok()
+        .map(|language| language.trim().to_string())
+        .filter(|language| !language.is_empty())
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+/// The picture to read `page_id` through, as JPEG or PNG bytes: the largest
+/// image on the page. `Err` says why there is none to read.
+pub fn page_image(doc: &lopdf::Document, page_id: lopdf::ObjectId) -> Result<Vec<u8>, String> {
+    let images = doc.get_page_images(page_id).unwrap_or_default();
+    let image = images
+        .into_iter()
+        .max_by_key(|image| image.width * image.height)
+        .ok_or_else(|| "no picture on the page".to_string())?;
+    let filters = image.filters.clone().unwrap_or_default();
+    if filters.last().is_some_and(|filter| filter == "DCTDecode") {
+        if filters.len() == 1 {
+            return Ok(image.content.to_vec());
+        }
+        return Err("picture is encoded twice".to_string());
+    }
+    if let Some(filter) = filters
+        .iter()
+        .find(|filter| !STREAM_FILTERS.contains(&filter.as_str()))
+    {
+        return Err(format!("{filter} pictures are not decoded"));
+    }
+    let pixels = if filters.is_empty() {
+        image.content.to_vec()
+    } else {
+        doc.get_object(image.id)
+            .and_then(lopdf::Object::as_stream)
+            .and_then(lopdf::Stream::decompressed_content)
+            .map_err(|e| format!("picture cannot be decompressed: {e}"))?
+    };
+    let inverted = image
+        .origin_dict
+        .get(b"Decode")
+        .and_then(lopdf::Object::as_array)
+        .ok()
+        .and_then(|decode| decode.first()?.as_float().ok())
+        .is_some_and(|first| first >= 1.0);
+    let width = u32::try_from(image.width).map_err(|_| "picture has no width".to_string())?;
+    let height = u32::try_from(image.height).map_err(|_| "picture has no height".to_string())?;
+    let decoded = raw_image(
+        &pixels,
+        width,
+        height,
+        image.color_space.as_deref().unwrap_or("DeviceGray"),
+        image.bits_per_component.unwrap_or(8),
+        inverted,
+    )
+    .ok_or_else(|| {
+        format!(
+            "{} pictures with {} bits per component are not decoded",
+            image.color_space.as_deref().unwrap_or("unknown"),
+            image.bits_per_component.unwrap_or(8)
+        )
+    })?;
+    let mut png = vec![];
+    decoded
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("picture cannot be encoded: {e}"))?;
+    Ok(png)
+}
+
+/// Raw samples as an image: 8-bit gray or RGB, or 1-bit gray as scanners
+/// write it, with rows padded to whole bytes.
+fn raw_image(
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    color_space: &str,
+    bits: i64,
+    inverted: bool,
+) -> Option<DynamicImage> {
+    let (w, h) = (width as usize, height as usize);
+    let image = match (color_space, bits) {
+        ("DeviceGray" | "CalGray", 8) => DynamicImage::ImageLuma8(GrayImage::from_raw(
+            width,
+            height,
+            pixels.get(..w * h)?.to_vec(),
+        )?),
+        ("DeviceRGB" | "CalRGB", 8) => DynamicImage::ImageRgb8(RgbImage::from_raw(
+            width,
+            height,
+            pixels.get(..w * h * 3)?.to_vec(),
+        )?),
+        ("DeviceGray" | "CalGray", 1) => {
+            let row = w.div_ceil(8);
+            let packed = pixels.get(..row * h)?;
+            let luma = (0..h)
+                .flat_map(|y| (0..w).map(move |x| (packed[y * row + x / 8] >> (7 - x % 8)) & 1))
+                .map(|bit| if (bit == 1) != inverted { 255 } else { 0 })
+                .collect();
+            DynamicImage::ImageLuma8(GrayImage::from_raw(width, height, luma)?)
+        }
+        _ => return None,
+    };
+    Some(image)
+}
+
+/// Runs Tesseract on `image`, a JPEG or PNG.
+pub fn recognize(image: &[u8], language: &str) -> AppResult<OcrText> {
+    let command = std::env::var("VECTORLESS_TESSERACT").unwrap_or_else(|_| TESSERACT.to_string());
+    let executable = which::which(&command).map_err(|e| {
+        AppError::Sidecar(format!(
+            "OCR needs Tesseract, but {command} was not found ({e}); install it or set VECTORLESS_TESSERACT"
+        ))
+    })?;
+    let mut child = Command::new(&executable)
+        .args(["stdin", "stdout", "-l", language, "tsv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Sidecar(format!("cannot start {command} for OCR: {e}")))?;
+    // Written on its own thread so a large page cannot block on a full pipe
+    // while Tesseract's output is read.
+    let stdin = child.stdin.take();
+    let image = image.to_vec();
+    let writer = thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(&image);
+        }
+    });
+    let output = child
+        .wait_with_output()
+        .map_err(|e| AppError::Sidecar(format!("OCR: {e}")))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        let stderr = &output.stderr;
+        let tail =
+            String::from_utf8_lossy(&stderr[stderr.len().saturating_sub(STDERR_TAIL_BYTES)..]);
+        return Err(AppError::Sidecar(format!(
+            "{command} exited with {}: {}",
+            output.status,
+            tail.trim()
+        )));
+    }
+    Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// A line number and the words read on it.
+type Line<'a> = (&'a str, Vec<&'a str>);
+
+/// Reads Tesseract's TSV output: words grouped into the lines and paragraphs
+/// it found, and their mean confidence.
+pub fn parse_tsv(tsv: &str) -> OcrText {
+    // Each paragraph's (block, paragraph) numbers and its lines.
+    let mut paragraphs: Vec<((&str, &str), Vec<Line>)> = vec![];
+    let mut confidence_sum = 0.0;
+    let mut words = 0;
+    for row in tsv.lines().skip(1) {
+        let fields = row.splitn(12, '\t').collect::<Vec<_>>();
+        let [level, _page, block, paragraph, line, _word, _left, _top, _width, _height, conf, text] =
+            fields[..]
+        else {
+            continue;
+        };
+        let text = text.trim();
+        let Ok(conf) = conf.trim().parse::<f64>() else {
+            continue;
+        };
+        if level != "5" || text.is_empty() || conf < 0.0 {
+            continue;
+        }
+        confidence_sum += conf;
+        words += 1;
+        let key = (block, paragraph);
+        if paragraphs.last().is_none_or(|(last, _)| *last != key) {
+            paragraphs.push((key, vec![]));
+        }
+        let lines = &mut paragraphs.last_mut().expect("pushed above").1;
+        match lines.last_mut() {
+            Some((number, line_words)) if *number == line => line_words.push(text),
+            _ => lines.push((line, vec![text])),
+        }
+    }
+    OcrText {
+        paragraphs: paragraphs
+            .into_iter()
+            .map(|(_, lines)| {
+                let block = lines
+                    .iter()
+                    .map(|(_, words)| words.join(" "))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                pdf_text::normalize_block(&block)
+            })
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect(),
+        confidence: (words > 0)
+            .then(|| (confidence_sum / words as f64 / 100.0 * 1000.0).round() / 1000.0),
+        words,
+    }
+}
@@ -0,0 +1,324 @@
+//! Layout-aware PDF text extraction. pdf-extract's plain-text output follows
+//! the content stream, which in two-column papers interleaves lines from both
+//! columns. Here each glyph's position is collected instead, lines are rebuilt
+//! from baselines, and when a page has a clear gutter the left column is read
+//! before the right one. Full-width lines (titles, wide figures, footers) keep
+//! their place between column runs.
+
+use pdf_extract::{MediaBox, OutputDev, OutputError, Transform};
+
+use crate::core::errors::{AppError, AppResult};
+
+/// Gap between glyphs, in ems, that ends a fragment of a line.
+const FRAGMENT_GAP_EMS: f64 = 1.5;
+/// Gap between glyphs, in ems, that counts as a word break.
+const WORD_GAP_EMS: f64 = 0.2;
+/// Baseline drop, in ems, that starts a new paragraph.
+const PARAGRAPH_GAP_EMS: f64 = 1.8;
+/// Rows each column needs before a page is treated as two-column.
+const MIN_COLUMN_ROWS: usize = 3;
+/// Narrowest fragment, as a share of page width, counted as column text.
+const MIN_COLUMN_SHARE: f64 = 0.15;
+/// Share of rows allowed to cross the gutter (titles, wide captions).
+const MAX_SPANNING_SHARE: f64 = 0.2;
+
+/// One positioned piece of text, in PDF user space (y grows upwards).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyph {
+    pub x: f64,
+    pub y: f64,
+    /// Horizontal advance.
+    pub width: f64,
+    /// Rendered font size.
+    pub size: f64,
+    pub text: String,
+}
+
+/// Text of one page in reading order.
+pub fn page_text(doc: &lopdf::Document, page_number: u32) -> AppResult<String> {
+    let mut collector = GlyphCollector::default();
+    pdf_extract::output_doc_page(doc, &mut collector, page_number)
+        .map_err(|e| AppError::Sidecar(format!("pdf-extract failed on page {page_number}: {e}")))?;
+    Ok(reading_order(&collector.glyphs, collector.page_width))
+}
+
+/// Rebuilds a page's text from its glyphs: lines separated by `\n`, paragraphs
+/// by a blank line, and the columns of a two-column page one after the other.
+pub fn reading_order(glyphs: &[Glyph], page_width: f64) -> String {
+    let rows = rows(glyphs);
+    let page_width = if page_width > 0.0 {
+        page_width
+    } else {
+        rows.iter()
+            .flat_map(|row| row.fragments.iter().map(|f| f.x1))
+            .fold(0.0, f64::max)
+    };
+    let Some(gutter) = find_gutter(&rows, page_width) else {
+        let mut out = Lines::default();
+        for row in &rows {
+            out.push(row.y, row.size, join_fragments(&row.fragments));
+        }
+        return out.text;
+    };
+
+    // Short lines above or below the columns (running heads, page numbers)
+    // stay where they are rather than joining a column.
+    let is_split = |row: &Row| {
+        row.fragments.iter().any(|f| f.x1 <= gutter) && row.fragments.iter().any(|f| f.x0 >= gutter)
+    };
+    let first_split = rows.iter().position(is_split).unwrap_or(0);
+    let last_split = rows.iter().rposition(is_split).unwrap_or(rows.len());
+
+    let mut out = Lines::default();
+    let mut left = Lines::default();
+    let mut right = Lines::default();
+    for (index, row) in rows.iter().enumerate() {
+        let crosses = row.fragments.iter().any(|f| f.x0 < gutter && f.x1 > gutter);
+        let outside = (index < first_split || index > last_split)
+            && row.fragments.iter().all(|f| !is_column_text(f, page_width));
+        if crosses || outside {
+            out.append(std::mem::take(&mut left));
+            out.append(std::mem::take(&mut right));
+            out.push(row.y, row.size, join_fragments(&row.fragments));
+            continue;
+        }
+        let (l, r): (Vec<_>, Vec<_>) = row.fragments.iter().partition(|f| f.x1 <= gutter);
+        if !l.is_empty() {
+            left.push(row.y, row.size, join_fragments(l));
+        }
+        if !r.is_empty() {
+            right.push(row.y, row.size, join_fragments(r));
+        }
+    }
+    out.append(left);
+    out.append(right);
+    out.text
+}
+
+#[derive(Debug, Default)]
+struct GlyphCollector {
+    glyphs: Vec<Glyph>,
+    page_width: f64,
+}
+
+impl OutputDev for GlyphCollector {
+    fn begin_page(
+        &mut self,
+        _page_num: u32,
+        media_box: &MediaBox,
+        _art_box: Option<(f64, f64, f64, f64)>,
+    ) -> Result<(), OutputError> {
+        self.page_width = (media_box.urx - media_box.llx).abs();
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &Transform,
+        width: f64,
+        spacing: f64,
+        font_size: f64,
+        char: &str,
+    ) -> Result<(), OutputError> {
+        let x_scale = trm.m11.hypot(trm.m12);
+        let y_scale = trm.m21.hypot(trm.m22);
+        self.glyphs.push(Glyph {
+            x: trm.m31,
+            y: trm.m32,
+            width: (width * font_size + spacing) * x_scale,
+            size: (font_size * y_scale).max(1.0),
+            text: char.to_string(),
+        });
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+/// A run of glyphs on one baseline with no wide gaps.
+#[derive(Debug)]
+struct Fragment<'a> {
+    x0: f64,
+    x1: f64,
+    glyphs: Vec<&'a Glyph>,
+}
+
+/// Glyphs sharing a baseline, split into fragments left to right.
+#[derive(Debug)]
+struct Row<'a> {
+    y: f64,
+    size: f64,
+    fragments: Vec<Fragment<'a>>,
+}
+
+/// Groups glyphs into rows from the top of the page down.
+fn rows(glyphs: &[Glyph]) -> Vec<Row<'_>> {
+    let mut sorted = glyphs
+        .iter()
+        .filter(|g| !g.text.is_empty() && g.x.is_finite() && g.y.is_finite())
+        .collect::<Vec<_>>();
+    sorted.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let mut grouped: Vec<Vec<&Glyph>> = Vec::new();
+    for glyph in sorted {
+        match grouped.last_mut() {
+            Some(row) if (row[0].y - glyph.y).abs() <= row[0].size.max(glyph.size) * 0.5 => {
+                row.push(glyph)
+            }
+            _ => grouped.push(vec![glyph]),
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|mut glyphs| {
+            glyphs.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let y = glyphs[0].y;
+            let size = glyphs.iter().map(|g| g.size).fold(0.0, f64::max);
+            let mut fragments: Vec<Fragment> = Vec::new();
+            for glyph in glyphs {
+                let is_space = glyph.text.trim().is_empty();
+                match fragments.last_mut() {
+                    Some(f) if glyph.x - f.x1 <= glyph.size * FRAGMENT_GAP_EMS => {
+                        if !is_space {
+                            f.x1 = f.x1.max(glyph.x + glyph.width);
+                        }
+                        f.glyphs.push(glyph);
+                    }
+                    _ if is_space => {}
+                    _ => fragments.push(Fragment {
+                        x0: glyph.x,
+                        x1: glyph.x + glyph.width,
+                        glyphs: vec![glyph],
+                    }),
+                }
+            }
+            Row { y, size, fragments }
+        })
+        .filter(|row| !row.fragments.is_empty())
+        .collect()
+}
+
+/// The x position of a column gutter near the middle of the page, if the page
+/// has enough rows on both sides of one and few lines crossing it.
+fn find_gutter(rows: &[Row], page_width: f64) -> Option<f64> {
+    if page_width <= 0.0 || rows.len() < MIN_COLUMN_ROWS * 2 {
+        return None;
+    }
+
+    // Widest run of candidate positions crossed by the fewest rows.
+    let crossing = |x: f64| {
+        rows.iter()
+            .filter(|row| row.fragments.iter().any(|f| f.x0 < x && f.x1 > x))
+            .count()
+    };
+    let candidates = ((page_width * 0.3) as usize..=(page_width * 0.7) as usize)
+        .map(|x| (x as f64, crossing(x as f64)))
+        .collect::<Vec<_>>();
+    let fewest = candidates.iter().map(|(_, count)| *count).min()?;
+    let (mut best, mut run_start) = ((0.0, 0.0), None);
+    for &(x, count) in &candidates {
+        if count == fewest {
+            let start = *run_start.get_or_insert(x);
+            let run_len = x - start;
+            if run_len >= best.1 {
+                best = ((start + x) / 2.0, run_len);
+            }
+        } else {
+            run_start = None;
+        }
+    }
+    let gutter = best.0;
+    if fewest as f64 > rows.len() as f64 * MAX_SPANNING_SHARE {
+        return None;
+    }
+
+    // Both sides need real columns, not a ragged edge or a narrow label.
+    let side_rows = |left: bool| {
+        rows.iter()
+            .filter(|row| {
+                row.fragments.iter().any(|f| {
+                    is_column_text(f, page_width)
+                        && if left { f.x1 <= gutter } else { f.x0 >= gutter }
+                })
+            })
+            .count()
+    };
+    (side_rows(true) >= MIN_COLUMN_ROWS && side_rows(false) >= MIN_COLUMN_ROWS).then_some(gutter)
+}
+
+/// True for a fragment wide enough to be a line of body text.
+fn is_column_text(fragment: &Fragment, page_width: f64) -> bool {
+    fragment.x1 - fragment.x0 >= page_width * MIN_COLUMN_SHARE
+}
+
+/// Text of a row's fragments, left to right.
+fn join_fragments<'a, 'g: 'a>(fragments: impl IntoIterator<Item = &'a Fragment<'g>>) -> String {
+    let mut text = String::new();
+    let mut end: Option<f64> = None;
+    for glyph in fragments.into_iter().flat_map(|f| f.glyphs.iter()) {
+        if let Some(end) = end {
+            let gap = glyph.x - end;
+            if gap > glyph.size * WORD_GAP_EMS
+                && !text.ends_with(char::is_whitespace)
+                && !glyph.text.starts_with(char::is_whitespace)
+            {
+                text.push(' ');
+            }
+        }
+        text.push_str(&glyph.text);
+        end = Some(glyph.x + glyph.width);
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lines collected for output, with a blank line wherever the baseline drops
+/// by more than a normal line step.
+#[derive(Debug, Default)]
+struct Lines {
+    text: String,
+    last: Option<(f64, f64)>,
+}
+
+impl Lines {
+    fn push(&mut self, y: f64, size: f64, line: String) {
+        if line.is_empty() {
+            return;
+        }
+        match self.last {
+            None if self.text.is_empty() => {}
+            Some((last_y, last_size)) if last_y - y <= last_size.max(size) * PARAGRAPH_GAP_EMS => {
+                self.text.push('\n')
+            }
+            _ => self.text.push_str("\n\n"),
+        }
+        self.text.push_str(&line);
+        self.last = Some((y, size));
+    }
+
+    /// Adds another run of lines as its own paragraph.
+    fn append(&mut self, other: Lines) {
+        if other.text.is_empty() {
+            return;
+        }
+        if !self.text.is_empty() {
+            self.text.push_str("\n\n");
+        }
+        self.text.push_str(&other.text);
+        self.last = None;
+    }
+}
@@ -0,0 +1,229 @@
+//! Cleanup for text extracted from PDFs. pdf-extract keeps the page's hard
+//! line wraps, so words hyphenated at a line end arrive split in two and
+//! sentences are broken across lines. Each blank-line separated block is
+//! rejoined here before sectioning. Running headers, footers and page numbers
+//! are found by comparing the edges of a sample of pages ([`Boilerplate`]) and
+//! cut from every page first.
+
+use std::collections::HashMap;
+
+/// Lines at the top and bottom of a page checked for headers and footers.
+const EDGE_LINES: usize = 2;
+/// Most header and footer lines kept as examples in document metadata.
+const MAX_SAMPLES: usize = 10;
+
+/// Running headers and footers learned from a sample of pages.
+#[derive(Debug, Clone, Default)]
+pub struct Boilerplate {
+    /// Digit-insensitive form of each repeated line, with the first line seen.
+    patterns: HashMap<String, String>,
+}
+
+impl Boilerplate {
+    /// Learns the lines that recur at the top or bottom of at least half of
+    /// `pages` (and of three or more), treating digits as interchangeable so
+    /// `Page 3 of 40` matches `Page 4 of 40`.
+    pub fn detect(pages: &[&str]) -> Self {
+        let mut seen: HashMap<String, (usize, String)> = HashMap::new();
+        for page in pages {
+            let lines = page.lines().collect::<Vec<_>>();
+            let mut keys = edge_indices(&lines)
+                .into_iter()
+                .map(|index| (line_key(lines[index]), lines[index].trim()))
+                .filter(|(key, _)| !key.is_empty())
+                .collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup_by(|a, b| a.0 == b.0);
+            for (key, line) in keys {
+                seen.entry(key).or_insert_with(|| (0, line.to_string())).0 += 1;
+            }
+        }
+        let needed = (pages.len() / 2).max(3);
+        Self {
+            patterns: seen
+                .into_iter()
+                .filter(|(_, (count, _))| *count >= needed)
+                .map(|(key, (_, sample))| (key, sample))
+                .collect(),
+        }
+    }
+
+    /// `page` without its header, footer and page-number lines, and how many
+    /// lines were removed. Only the first and last few lines are candidates, so
+    /// body text that happens to repeat is left alone.
+    pub fn strip(&self, page: &str) -> (String, usize) {
+        let lines = page.lines().collect::<Vec<_>>();
+        let removed = edge_indices(&lines)
+            .into_iter()
+            .filter(|&index| {
+                is_page_number(lines[index]) || self.patterns.contains_key(&line_key(lines[index]))
+            })
+            .collect::<Vec<_>>();
+        let kept = lines
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !removed.contains(index))
+            .map(|(_, line)| *line)
+            .collect::<Vec<_>>();
+        (kept.join("\n"), removed.len())
+    }
+
+    /// Example header and footer lines, sorted, for document metadata.
+    pub fn samples(&self) -> Vec<String> {
+        let mut samples = self.patterns.values().cloned().collect::<Vec<_>>();
+        samples.sort();
+        samples.truncate(MAX_SAMPLES);
+        samples
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+/// True for a line that is only a page number: `12`, `- 12 -`, `Page 12`,
+/// `12 / 40`, `Page 12 of 40` or a lowercase roman numeral.
+pub fn is_page_number(line: &str) -> bool {
+    let line = line
+        .trim()
+        .trim_matches(|c: char| c == '-' || c == '–' || c.is_whitespace());
+    let line = line.to_ascii_lowercase();
+    let line = line.strip_prefix("page").map(str::trim).unwrap_or(&line);
+    if line.is_empty() {
+        return false;
+    }
+    let mut parts = line
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .filter(|part| !part.is_empty());
+    let first = parts.next().unwrap_or_default();
+    let number = |part: &str| part.len() <= 5 && part.chars().all(|c| c.is_ascii_digit());
+    let roman =
+        |part: &str| part.len() <= 6 && part.chars().all(|c| matches!(c, 'i' | 'v' | 'x' | 'l'));
+    if !(number(first) || roman(first)) {
+        return false;
+    }
+    match (parts.next(), parts.next(), parts.next()) {
+        (None, _, _) => true,
+        (Some(total), None, _) => number(total),
+        (Some("of"), Some(total), None) => number(total),
+        _ => false,
+    }
+}
+
+/// Indices of the first and last few non-blank lines, in order.
+fn edge_indices(lines: &[&str]) -> Vec<usize> {
+    let content = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut edges = content
+        .iter()
+        .take(EDGE_LINES)
+        .chain(content.iter().rev().take(EDGE_LINES))
+        .copied()
+        .collect::<Vec<_>>();
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+/// Comparison form of a line: whitespace collapsed, digit runs replaced by `#`.
+fn line_key(line: &str) -> String {
+    let mut key = String::new();
+    let mut in_digits = false;
+    for word in line.split_whitespace() {
+        if !key.is_empty() {
+            key.push(' ');
+        }
+        for c in word.chars() {
+            if c.is_ascii_digit() {
+                if !in_digits {
+                    key.push('#');
+                }
+                in_digits = true;
+            } else {
+                key.push(c);
+                in_digits = false;
+            }
+        }
+        in_digits = false;
+    }
+    key
+}
+
+/// Joins a block's wrapped lines into running text: a word hyphenated at a
+/// line end is rejoined, other breaks become a space, and runs of whitespace
+/// collapse. Lines that start a list item keep their break.
+pub fn normalize_block(block: &str) -> String {
+    let mut out = String::with_capacity(block.len());
+    let mut soft_break = false;
+    for raw in block.lines() {
+        let line = collapse_whitespace(&normalize_chars(raw));
+        if line.is_empty() {
+            continue;
+        }
+        if out.is_empty() {
+            out.push_str(&line);
+        } else if starts_list_item(&line) {
+            out.push('\n');
+            out.push_str(&line);
+        } else if soft_break {
+            out.push_str(&line);
+        } else if ends_with_split_word(&out) {
+            // A lowercase continuation is one word broken by hyphenation; otherwise
+            // the hyphen is real (`Franco-German`) and stays.
+            if line.starts_with(char::is_lowercase) {
+                out.pop();
+            }
+            out.push_str(&line);
+        } else {
+            out.push(' ');
+            out.push_str(&line);
+        }
+        soft_break = raw.trim_end().ends_with('\u{00AD}');
+    }
+    out
+}
+
+fn collapse_whitespace(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Expands typographic ligatures and drops soft hyphens.
+fn normalize_chars(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for c in line.chars() {
+        match c {
+            '\u{FB00}' => out.push_str("ff"),
+            '\u{FB01}' => out.push_str("fi"),
+            '\u{FB02}' => out.push_str("fl"),
+            '\u{FB03}' => out.push_str("ffi"),
+            '\u{FB04}' => out.push_str("ffl"),
+            '\u{00AD}' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// True when `text` ends in a letter followed by a hyphen, as in `infor-`.
+fn ends_with_split_word(text: &str) -> bool {
+    let mut chars = text.chars().rev();
+    chars.next() == Some('-') && chars.next().is_some_and(char::is_alphabetic)
+}
+
+fn starts_list_item(line: &str) -> bool {
+    if line.starts_with(['•', '◦', '▪', '–', '*']) {
+        return true;
+    }
+    if let Some(rest) = line.strip_prefix("- ") {
+        return !rest.is_empty();
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0
+        && digits <= 3
+        && line[digits..].starts_with([')', '.'])
+        && line[digits + 1..].starts_with(' ')
+}
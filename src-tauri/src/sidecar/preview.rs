@@ -0,0 +1,149 @@
+//! Dry-run summaries of a parse. A preview shows the tree a file would become,
+//! with warnings for the usual signs of a bad parse, so options can be adjusted
+//! before a large file is committed to the database.
+
+use std::collections::HashMap;
+
+use crate::{
+    core::types::{IngestPreviewSection, PreviewIngestResponse},
+    sidecar::types::{NormalizedPayload, SidecarNode},
+};
+
+/// Characters of a section's first block shown in the preview.
+const EXCERPT_CHARS: usize = 200;
+/// Below this much text overall, the file is probably scanned or empty.
+const MIN_TEXT_CHARS: usize = 200;
+/// Below this many characters per page, some PDF pages are probably images.
+const MIN_CHARS_PER_PAGE: usize = 100;
+/// A lone section holding more blocks than this suggests missed headings.
+const MAX_BLOCKS_IN_ONLY_SECTION: usize = 50;
+
+/// Summarizes `payload` as it would be ingested.
+pub fn summarize(payload: &NormalizedPayload) -> PreviewIngestResponse {
+    let mut children: HashMap<&str, Vec<&SidecarNode>> = HashMap::new();
+    for node in &payload.nodes {
+        if let Some(parent) = node.parent_id.as_deref() {
+            children.entry(parent).or_default().push(node);
+        }
+    }
+    let count = |kind: &str| {
+        payload
+            .nodes
+            .iter()
+            .filter(|node| node.node_type.eq_ignore_ascii_case(kind))
+            .count()
+    };
+    let sections = payload
+        .nodes
+        .iter()
+        .filter(|node| is_section(node))
+        .map(|section| {
+            let blocks = children
+                .get(section.id.as_str())
+                .map(|blocks| {
+                    blocks
+                        .iter()
+                        .filter(|node| !is_section(node))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            IngestPreviewSection {
+                title: section.title.clone(),
+                block_count: blocks.len(),
+                char_count: blocks.iter().map(|node| node.text.chars().count()).sum(),
+                excerpt: blocks
+                    .first()
+                    .map(|node| node.text.chars().take(EXCERPT_CHARS).collect())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect::<Vec<_>>();
+    let char_count = payload
+        .nodes
+        .iter()
+        .map(|node| node.text.chars().count())
+        .sum();
+    let metadata = &payload.document.metadata;
+
+    let mut summary = PreviewIngestResponse {
+        title: payload.document.title.clone(),
+        mime: metadata["mime"].as_str().unwrap_or_default().to_string(),
+        pages: payload.document.pages,
+        node_count: payload.nodes.len(),
+        section_count: sections.len(),
+        paragraph_count: count("paragraph"),
+        table_count: count("table"),
+        figure_count: count("figure"),
+        char_count,
+        sections,
+        warnings: Vec::new(),
+        metadata: metadata.clone(),
+    };
+    summary.warnings = warnings(&summary);
+    summary
+}
+
+fn is_section(node: &SidecarNode) -> bool {
+    node.node_type.eq_ignore_ascii_case("section")
+        || node.node_type.eq_ignore_ascii_case("subsection")
+}
+
+fn warnings(summary: &PreviewIngestResponse) -> Vec<String> {
+    let metadata = &summary.metadata;
+    let mut warnings = Vec::new();
+
+    if let Some(declared) = metadata["declaredMime"].as_str() {
+        if !declared.is_empty() && !declared.eq_ignore_ascii_case(&summary.mime) {
+            warnings.push(format!(
+                "Parsed as {} although it was given as {declared}",
+                summary.mime
+            ));
+        }
+    }
+    if metadata["encodingLossy"].as_bool() == Some(true) {
+        warnings.push(format!(
+            "Some bytes were not valid {} and were replaced",
+            metadata["encoding"].as_str().unwrap_or("text")
+        ));
+    }
+    if summary.char_count < MIN_TEXT_CHARS {
+        warnings.push(format!(
+            "Only {} characters of text were extracted; the file may be scanned or empty",
+            summary.char_count
+        ));
+    } else if summary.mime.contains("pdf")
+        && summary.pages > 1
+        && summary.char_count / (summary.pages as usize) < MIN_CHARS_PER_PAGE
+    {
+        warnings.push(format!(
+            "About {} characters per page; some pages may be scanned images",
+            summary.char_count / summary.pages as usize
+        ));
+    }
+    if !summary.sections.is_empty()
+        && summary
+            .sections
+            .iter()
+            .all(|section| is_numbered_part(&section.title))
+    {
+        warnings.push(
+            "No headings were found, so sections are fixed-size parts; try another parser"
+                .to_string(),
+        );
+    } else if let [only] = &summary.sections[..] {
+        if only.block_count > MAX_BLOCKS_IN_ONLY_SECTION {
+            warnings.push(format!(
+                "All {} blocks landed in one section; headings may have been missed",
+                only.block_count
+            ));
+        }
+    }
+    warnings
+}
+
+/// The `Part N` titles the native parser falls back to without headings.
+fn is_numbered_part(title: &str) -> bool {
+    title
+        .strip_prefix("Part ")
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
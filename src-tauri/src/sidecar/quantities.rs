@@ -0,0 +1,350 @@
+//! Numbers with units ("100 ms", "$10,000", "2.5 GB"), read from node text
+//! at parse time into each node's `quantities` metadata and from questions
+//! into range filters, so "latency under 100ms" can find "p99 is 85 ms".
+//! Values are kept in one base unit per dimension (seconds, bytes, metres,
+//! grams, hertz, a currency code or percent) so different spellings compare.
+
+use serde::{Deserialize, Serialize};
+
+use crate::sidecar::types::NormalizedPayload;
+
+/// Most quantities recorded for one node; a large table stops here.
+const MAX_QUANTITIES_PER_NODE: usize = 32;
+
+/// Unit spellings, lowercase, with their base unit and what one of them is in it.
+const UNITS: &[(&str, &str, f64)] = &[
+    ("ns", "s", 1e-9),
+    ("µs", "s", 1e-6),
+    ("us", "s", 1e-6),
+    ("ms", "s", 1e-3),
+    ("msec", "s", 1e-3),
+    ("millisecond", "s", 1e-3),
+    ("milliseconds", "s", 1e-3),
+    ("s", "s", 1.0),
+    ("sec", "s", 1.0),
+    ("secs", "s", 1.0),
+    ("second", "s", 1.0),
+    ("seconds", "s", 1.0),
+    ("min", "s", 60.0),
+    ("mins", "s", 60.0),
+    ("minute", "s", 60.0),
+    ("minutes", "s", 60.0),
+    ("h", "s", 3600.0),
+    ("hr", "s", 3600.0),
+    ("hrs", "s", 3600.0),
+    ("hour", "s", 3600.0),
+    ("hours", "s", 3600.0),
+    ("day", "s", 86400.0),
+    ("days", "s", 86400.0),
+    ("byte", "B", 1.0),
+    ("bytes", "B", 1.0),
+    ("kb", "B", 1e3),
+    ("mb", "B", 1e6),
+    ("gb", "B", 1e9),
+    ("tb", "B", 1e12),
+    ("kib", "B", 1024.0),
+    ("mib", "B", 1_048_576.0),
+    ("gib", "B", 1_073_741_824.0),
+    ("mm", "m", 1e-3),
+    ("cm", "m", 1e-2),
+    ("m", "m", 1.0),
+    ("km", "m", 1e3),
+    ("mg", "g", 1e-3),
+    ("g", "g", 1.0),
+    ("kg", "g", 1e3),
+    ("hz", "Hz", 1.0),
+    ("khz", "Hz", 1e3),
+    ("mhz", "Hz", 1e6),
+    ("ghz", "Hz", 1e9),
+    ("%", "%", 1.0),
+    ("percent", "%", 1.0),
+    ("usd", "USD", 1.0),
+    ("dollars", "USD", 1.0),
+    ("eur", "EUR", 1.0),
+    ("euros", "EUR", 1.0),
+    ("gbp", "GBP", 1.0),
+];
+
+/// Currency symbols written before the amount.
+const CURRENCY_SYMBOLS: &[(char, &str)] = &[('$', "USD"), ('€', "EUR"), ('£', "GBP")];
+
+/// Words that scale an amount of money, as in "$2.5 million" or "$10k".
+const MAGNITUDES: &[(&str, f64)] = &[
+    ("k", 1e3),
+    ("thousand", 1e3),
+    ("m", 1e6),
+    ("mn", 1e6),
+    ("million", 1e6),
+    ("bn", 1e9),
+    ("billion", 1e9),
+];
+
+/// Words before a quantity in a question that bound it, lowercase.
+const COMPARATORS: &[(&str, Bound)] = &[
+    ("under", Bound::Below),
+    ("below", Bound::Below),
+    ("less than", Bound::Below),
+    ("lower than", Bound::Below),
+    ("fewer than", Bound::Below),
+    ("smaller than", Bound::Below),
+    ("<", Bound::Below),
+    ("at most", Bound::AtMost),
+    ("up to", Bound::AtMost),
+    ("within", Bound::AtMost),
+    ("no more than", Bound::AtMost),
+    ("not more than", Bound::AtMost),
+    ("<=", Bound::AtMost),
+    ("≤", Bound::AtMost),
+    ("over", Bound::Above),
+    ("above", Bound::Above),
+    ("more than", Bound::Above),
+    ("greater than", Bound::Above),
+    ("higher than", Bound::Above),
+    ("larger than", Bound::Above),
+    ("exceeding", Bound::Above),
+    (">", Bound::Above),
+    ("at least", Bound::AtLeast),
+    ("no less than", Bound::AtLeast),
+    ("not less than", Bound::AtLeast),
+    (">=", Bound::AtLeast),
+    ("≥", Bound::AtLeast),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Below,
+    AtMost,
+    Above,
+    AtLeast,
+}
+
+/// A number with a unit as a node's text writes it, and its value in the
+/// base unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: String,
+    pub text: String,
+}
+
+/// A range a question puts on quantities of one base unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantityFilter {
+    pub unit: String,
+    pub min: Option<f64>,
+    pub min_inclusive: bool,
+    pub max: Option<f64>,
+    pub max_inclusive: bool,
+}
+
+/// A number found in text, with its unit when one follows it.
+struct Found {
+    start: usize,
+    end: usize,
+    number: f64,
+    unit: Option<(&'static str, f64)>,
+}
+
+impl Found {
+    fn quantity(&self, text: &str) -> Option<Quantity> {
+        let (unit, scale) = self.unit?;
+        Some(Quantity {
+            value: round(self.number * scale),
+            unit: unit.to_string(),
+            text: text[self.start..self.end].to_string(),
+        })
+    }
+}
+
+/// The quantities in `text` that have a unit, in order of appearance.
+pub fn extract(text: &str) -> Vec<Quantity> {
+    scan(text)
+        .iter()
+        .filter_map(|found| found.quantity(text))
+        .take(MAX_QUANTITIES_PER_NODE)
+        .collect()
+}
+
+/// Records each node's quantities as its `quantities` metadata.
+pub fn record(payload: &mut NormalizedPayload) {
+    for node in &mut payload.nodes {
+        let quantities = extract(&node.text);
+        if quantities.is_empty() {
+            continue;
+        }
+        if let Some(object) = node.metadata.as_object_mut() {
+            object.insert(
+                "quantities".to_string(),
+                serde_json::to_value(quantities).unwrap_or_default(),
+            );
+        }
+    }
+}
+
+/// The ranges `query` asks for: "under 100ms", "at least 2 GB", "between
+/// 10 and 20 seconds", or a bare "$500" for that amount exactly.
+pub fn filters(query: &str) -> Vec<QuantityFilter> {
+    let found = scan(query);
+    let mut filters = vec![];
+    let mut index = 0;
+    while let Some(current) = found.get(index) {
+        index += 1;
+        let before = query[..current.start].to_lowercase();
+        if let Some(next) = found.get(index).filter(|next| {
+            ends_with_phrase(&before, "between")
+                && query[current.end..next.start]
+                    .trim()
+                    .eq_ignore_ascii_case("and")
+        }) {
+            // "between 10 and 20 ms" gives the first number the second's unit.
+            let Some((unit, scale)) = next.unit.or(current.unit) else {
+                continue;
+            };
+            let low = round(current.number * current.unit.map_or(scale, |(_, scale)| scale));
+            let high = round(next.number * next.unit.map_or(scale, |(_, scale)| scale));
+            filters.push(QuantityFilter {
+                unit: unit.to_string(),
+                min: Some(low.min(high)),
+                min_inclusive: true,
+                max: Some(low.max(high)),
+                max_inclusive: true,
+            });
+            index += 1;
+            continue;
+        }
+        let Some(quantity) = current.quantity(query) else {
+            continue;
+        };
+        let bound = COMPARATORS
+            .iter()
+            .filter(|(phrase, _)| ends_with_phrase(&before, phrase))
+            .max_by_key(|(phrase, _)| phrase.len())
+            .map(|(_, bound)| *bound);
+        let value = Some(quantity.value);
+        let (min, min_inclusive, max, max_inclusive) = match bound {
+            Some(Bound::Below) => (None, false, value, false),
+            Some(Bound::AtMost) => (None, false, value, true),
+            Some(Bound::Above) => (value, false, None, false),
+            Some(Bound::AtLeast) => (value, true, None, false),
+            None => (value, true, value, true),
+        };
+        filters.push(QuantityFilter {
+            unit: quantity.unit,
+            min,
+            min_inclusive,
+            max,
+            max_inclusive,
+        });
+    }
+    filters
+}
+
+/// Whether `before`, ignoring trailing spaces, ends with `phrase` as whole words.
+fn ends_with_phrase(before: &str, phrase: &str) -> bool {
+    let before = before.trim_end();
+    let Some(rest) = before.strip_suffix(phrase) else {
+        return false;
+    };
+    !phrase.starts_with(char::is_alphanumeric) || !rest.ends_with(|c: char| c.is_alphanumeric())
+}
+
+/// Every number in `text` that stands on its own, not inside a word like
+/// "COVID-19" or "v2".
+fn scan(text: &str) -> Vec<Found> {
+    let chars = text.char_indices().collect::<Vec<_>>();
+    let byte_at = |index: usize| chars.get(index).map_or(text.len(), |(offset, _)| *offset);
+    let char_at = |index: usize| chars.get(index).map(|(_, c)| *c);
+    let mut found = vec![];
+    let mut index = 0;
+    while index < chars.len() {
+        let start = index;
+        let previous = index.checked_sub(1).and_then(char_at);
+        let currency = char_at(index).and_then(|c| {
+            CURRENCY_SYMBOLS
+                .iter()
+                .find(|(symbol, _)| *symbol == c)
+                .map(|(_, unit)| *unit)
+        });
+        let mut cursor = index + usize::from(currency.is_some());
+        let starts_number = char_at(cursor).is_some_and(|c| c.is_ascii_digit())
+            && !previous.is_some_and(|c| c.is_alphanumeric() || c == '.');
+        if !starts_number {
+            index += 1;
+            // Skip the rest of a word so its digits are not read as a number.
+            while char_at(index - 1).is_some_and(char::is_alphanumeric)
+                && char_at(index).is_some_and(char::is_alphanumeric)
+            {
+                index += 1;
+            }
+            continue;
+        }
+
+        let mut digits = String::new();
+        let mut decimal = false;
+        while let Some(c) = char_at(cursor) {
+            let grouped = c == ','
+                && !decimal
+                && (1..=3).all(|ahead| char_at(cursor + ahead).is_some_and(|c| c.is_ascii_digit()))
+                && !char_at(cursor + 4).is_some_and(|c| c.is_ascii_digit());
+            if c.is_ascii_digit() {
+                digits.push(c);
+            } else if c == '.'
+                && !decimal
+                && char_at(cursor + 1).is_some_and(|c| c.is_ascii_digit())
+            {
+                decimal = true;
+                digits.push(c);
+            } else if !grouped {
+                break;
+            }
+            cursor += 1;
+        }
+        let Ok(number) = digits.parse::<f64>() else {
+            index = cursor;
+            continue;
+        };
+
+        // The word after the number, directly or after one space.
+        let word_start = if char_at(cursor) == Some(' ') {
+            cursor + 1
+        } else {
+            cursor
+        };
+        let mut word_end = word_start;
+        while char_at(word_end).is_some_and(|c| c.is_alphabetic() || c == '%' || c == 'µ') {
+            word_end += 1;
+        }
+        let word = text[byte_at(word_start)..byte_at(word_end)].to_lowercase();
+        let unit = match currency {
+            Some(code) => {
+                let magnitude = MAGNITUDES.iter().find(|(name, _)| *name == word);
+                if magnitude.is_some() {
+                    cursor = word_end;
+                }
+                Some((code, magnitude.map_or(1.0, |(_, scale)| *scale)))
+            }
+            None => UNITS
+                .iter()
+                .find(|(name, _, _)| *name == word)
+                .map(|(_, unit, scale)| {
+                    cursor = word_end;
+                    (*unit, *scale)
+                }),
+        };
+        found.push(Found {
+            start: byte_at(start),
+            end: byte_at(cursor),
+            number,
+            unit,
+        });
+        index = cursor;
+    }
+    found
+}
+
+/// `value` to twelve significant digits, so a value converted from "100 ms"
+/// equals one written "0.1 s".
+fn round(value: f64) -> f64 {
+    format!("{value:.11e}").parse().unwrap_or(value)
+}
@@ -0,0 +1,366 @@
+//! Reference metadata (title, authors, year, DOI) read at parse time into the
+//! root node's `reference` metadata, and the bibliography formats it is
+//! exported in. A PDF's document information is trusted first; the first
+//! page's byline ("By Ada Lovelace and Charles Babbage", "Authors: ..."),
+//! first plausible year and first DOI fill in what it leaves out. Documents
+//! with neither authors nor a DOI are not treated as references.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::sidecar::types::NormalizedPayload;
+
+/// Years read from a first page; others are more likely counts.
+const YEARS: std::ops::RangeInclusive<i32> = 1900..=2099;
+/// Longest line read as a byline.
+const MAX_BYLINE_CHARS: usize = 200;
+/// Nodes of the first page looked at, for documents without page numbers.
+const FIRST_PAGE_NODES: usize = 12;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reference {
+    pub title: String,
+    /// As written, e.g. "Ada Lovelace" or "Lovelace, Ada".
+    pub authors: Vec<String>,
+    pub year: Option<i32>,
+    pub doi: Option<String>,
+}
+
+/// A PDF's document information (`Title`, `Author`, `CreationDate`), for
+/// [`record`] to read back from the `pdfInfo` metadata.
+pub fn pdf_info(doc: &lopdf::Document) -> Value {
+    let info = match doc.trailer.get(b"Info") {
+        Ok(lopdf::Object::Reference(id)) => doc.get_dictionary(*id).ok(),
+        Ok(lopdf::Object::Dictionary(info)) => Some(info),
+        _ => None,
+    };
+    let field = |key: &[u8]| match info.and_then(|info| info.get(key).ok()) {
+        Some(lopdf::Object::String(bytes, _)) => Some(pdf_text_string(bytes)),
+        _ => None,
+    };
+    json!({
+        "title": field(b"Title"),
+        "author": field(b"Author"),
+        "creationDate": field(b"CreationDate"),
+    })
+}
+
+/// PDF text strings are UTF-16BE with a byte order mark or PDFDocEncoding,
+/// which matches Latin-1 for the letters names use.
+fn pdf_text_string(bytes: &[u8]) -> String {
+    let text = match bytes.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => String::from_utf16_lossy(
+            &utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        None => bytes.iter().map(|&byte| char::from(byte)).collect(),
+    };
+    text.trim().to_string()
+}
+
+/// Records the document's [`Reference`] on its root node, when it has one.
+pub fn record(payload: &mut NormalizedPayload) {
+    if let Some(reference) = detect(payload) {
+        payload.record_metadata(
+            "reference",
+            serde_json::to_value(reference).unwrap_or_default(),
+        );
+    }
+}
+
+pub fn detect(payload: &NormalizedPayload) -> Option<Reference> {
+    let info = &payload.document.metadata["pdfInfo"];
+    let first_page = payload
+        .nodes
+        .iter()
+        .skip(1)
+        .filter(|node| node.page_start.is_none_or(|page| page <= 1))
+        .take(FIRST_PAGE_NODES)
+        .collect::<Vec<_>>();
+
+    let mut reference = Reference {
+        title: info["title"]
+            .as_str()
+            .filter(|title| is_real_title(title))
+            .map(str::to_string)
+            .or_else(|| {
+                first_page
+                    .iter()
+                    .find(|node| node.node_type != "Paragraph" && !node.title.trim().is_empty())
+                    .map(|node| node.title.trim().to_string())
+            })
+            .unwrap_or_else(|| payload.document.title.trim().to_string()),
+        authors: info["author"]
+            .as_str()
+            .map(split_authors)
+            .unwrap_or_default(),
+        year: info["creationDate"]
+            .as_str()
+            .and_then(|date| date.trim_start_matches("D:").get(..4))
+            .and_then(|year| year.parse().ok())
+            .filter(|year| YEARS.contains(year)),
+        doi: None,
+    };
+    for node in &first_page {
+        for line in node.text.lines().chain([node.title.as_str()]) {
+            if reference.authors.is_empty() {
+                if let Some(byline) = byline(line) {
+                    let names = split_authors(byline);
+                    if names.iter().all(|name| looks_like_name(name)) {
+                        reference.authors = names;
+                    }
+                }
+            }
+            if reference.year.is_none() {
+                reference.year = first_year(line);
+            }
+        }
+    }
+    reference.doi = payload
+        .nodes
+        .iter()
+        .take(FIRST_PAGE_NODES * 4)
+        .find_map(|node| find_doi(&node.text));
+
+    if reference.title.is_empty() || (reference.authors.is_empty() && reference.doi.is_none()) {
+        return None;
+    }
+    Some(reference)
+}
+
+/// Word processors fill in the file name or an empty stand-in.
+fn is_real_title(title: &str) -> bool {
+    let lower = title.trim().to_lowercase();
+    !lower.is_empty()
+        && lower != "untitled"
+        && !lower.starts_with("microsoft word - ")
+        && ![".doc", ".docx", ".pdf", ".tex", ".dvi"]
+            .iter()
+            .any(|extension| lower.ends_with(extension))
+}
+
+/// The names after "By" or "Author(s):" on a short line.
+fn byline(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.chars().count() > MAX_BYLINE_CHARS {
+        return None;
+    }
+    let lower = line.to_lowercase();
+    ["by ", "authors:", "author:", "written by "]
+        .iter()
+        .find(|prefix| lower.starts_with(**prefix))
+        .map(|prefix| line[prefix.len()..].trim())
+        .filter(|names| !names.is_empty())
+}
+
+/// Two to five capitalized words, so "by the end of the year" is no byline.
+fn looks_like_name(name: &str) -> bool {
+    let words = name
+        .split([' ', ','])
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    (2..=5).contains(&words.len())
+        && words
+            .iter()
+            .all(|word| word.chars().next().is_some_and(char::is_uppercase))
+}
+
+/// Splits a list of names on ";", " and ", "&" and, when every part is more
+/// than one word, on ",", so "Lovelace, Ada" stays one name.
+fn split_authors(names: &str) -> Vec<String> {
+    names
+        .split(';')
+        .flat_map(|part| part.split(" and "))
+        .flat_map(|part| part.split('&'))
+        .flat_map(|part| {
+            let pieces = part
+                .split(',')
+                .map(str::trim)
+                .filter(|piece| !piece.is_empty())
+                .collect::<Vec<_>>();
+            if pieces.len() > 1 && pieces.iter().all(|piece| piece.contains(' ')) {
+                pieces
+            } else {
+                vec![part.trim()]
+            }
+        })
+        .map(|name| name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '*' || c == ','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn first_year(line: &str) -> Option<i32> {
+    line.split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| digits.len() == 4)
+        .filter_map(|digits| digits.parse().ok())
+        .find(|year| YEARS.contains(year))
+}
+
+/// The first DOI ("10.1000/xyz123") in `text`, without trailing punctuation.
+fn find_doi(text: &str) -> Option<String> {
+    let mut rest = text;
+    while let Some(at) = rest.find("10.") {
+        let candidate = rest[at..]
+            .split(|c: char| c.is_whitespace() || matches!(c, '"' | '<' | '>'))
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(['.', ',', ';', ')', ']']);
+        let registrant_ok = candidate
+            .get(3..)
+            .and_then(|rest| rest.split_once('/'))
+            .is_some_and(|(registrant, suffix)| {
+                (4..=9).contains(&registrant.len())
+                    && registrant.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+                    && !suffix.is_empty()
+            });
+        let starts_word = rest[..at]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        if registrant_ok && starts_word {
+            return Some(candidate.to_string());
+        }
+        rest = &rest[at + 3..];
+    }
+    None
+}
+
+/// A name as CSL's family and given parts.
+fn name_parts(name: &str) -> (String, String) {
+    if let Some((family, given)) = name.split_once(',') {
+        return (family.trim().to_string(), given.trim().to_string());
+    }
+    match name.trim().rsplit_once(' ') {
+        Some((given, family)) => (family.to_string(), given.trim().to_string()),
+        None => (name.trim().to_string(), String::new()),
+    }
+}
+
+/// BibTeX keys: first author's family name, year and first title word, with
+/// a letter added when two entries would share one.
+fn citation_keys(entries: &[(String, Reference)]) -> Vec<String> {
+    let ascii = |value: &str| {
+        value
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_lowercase()
+    };
+    let base = entries
+        .iter()
+        .map(|(_, reference)| {
+            let family = reference
+                .authors
+                .first()
+                .map(|name| ascii(&name_parts(name).0))
+                .unwrap_or_default();
+            let year = reference
+                .year
+                .map(|year| year.to_string())
+                .unwrap_or_default();
+            let word = reference
+                .title
+                .split_whitespace()
+                .map(ascii)
+                .find(|word| word.len() > 3)
+                .unwrap_or_default();
+            let key = format!("{family}{year}{word}");
+            if key.is_empty() {
+                "ref".to_string()
+            } else {
+                key
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for key in &base {
+        *totals.entry(key).or_default() += 1;
+    }
+    let mut seen: HashMap<&str, u8> = HashMap::new();
+    base.iter()
+        .map(|key| {
+            if totals[key.as_str()] == 1 {
+                return key.clone();
+            }
+            let count = seen.entry(key).or_default();
+            *count += 1;
+            format!("{key}{}", char::from(b'a' + (*count - 1) % 26))
+        })
+        .collect()
+}
+
+fn bibtex_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '{' | '}' | '&' | '%' | '$' | '#' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `entries`, by document id, as BibTeX `@misc` entries.
+pub fn bibtex(entries: &[(String, Reference)]) -> String {
+    let mut bib = String::new();
+    for ((_, reference), key) in entries.iter().zip(citation_keys(entries)) {
+        bib.push_str(&format!("@misc{{{key},\n"));
+        bib.push_str(&format!(
+            "  title = {{{}}},\n",
+            bibtex_escape(&reference.title)
+        ));
+        if !reference.authors.is_empty() {
+            let authors = reference
+                .authors
+                .iter()
+                .map(|name| bibtex_escape(name))
+                .collect::<Vec<_>>()
+                .join(" and ");
+            bib.push_str(&format!("  author = {{{authors}}},\n"));
+        }
+        if let Some(year) = reference.year {
+            bib.push_str(&format!("  year = {{{year}}},\n"));
+        }
+        if let Some(doi) = &reference.doi {
+            bib.push_str(&format!("  doi = {{{}}},\n", bibtex_escape(doi)));
+        }
+        bib.push_str("}\n\n");
+    }
+    bib
+}
+
+/// `entries`, by document id, as a CSL-JSON array.
+pub fn csl_json(entries: &[(String, Reference)]) -> Value {
+    entries
+        .iter()
+        .map(|(document_id, reference)| {
+            let mut item = json!({
+                "id": document_id,
+                "type": if reference.doi.is_some() { "article-journal" } else { "document" },
+                "title": reference.title,
+                "author": reference
+                    .authors
+                    .iter()
+                    .map(|name| {
+                        let (family, given) = name_parts(name);
+                        json!({ "family": family, "given": given })
+                    })
+                    .collect::<Vec<_>>(),
+            });
+            if let Some(year) = reference.year {
+                item["issued"] = json!({ "date-parts": [[year]] });
+            }
+            if let Some(doi) = &reference.doi {
+                item["DOI"] = json!(doi);
+            }
+            item
+        })
+        .collect()
+}
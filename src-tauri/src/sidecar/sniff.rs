@@ -0,0 +1,88 @@
+//! Binary-file sniffing for the text fallback. Anything the parser does not
+//! recognise is read as text, so the first bytes are checked for known binary
+//! signatures and for the null and control bytes text files do not contain.
+
+use crate::core::errors::{AppError, AppResult};
+
+/// Bytes from the start of a file that are inspected.
+pub const SNIFF_BYTES: usize = 8 * 1024;
+
+pub const SUPPORTED_FORMATS: &str =
+    "PDF, DOCX, XLSX, PPTX, EPUB, images (PNG, JPEG, GIF, BMP, WebP, TIFF), HTML and plain text (TXT, Markdown, CSV)";
+
+/// Share of null bytes above which a sample is treated as binary.
+const MAX_NULL_RATIO: f64 = 0.01;
+/// Share of other control bytes (not tab, newline, form feed, carriage
+/// return or escape) above which a sample is treated as binary.
+const MAX_CONTROL_RATIO: f64 = 0.1;
+
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"%PDF-", "a PDF"),
+    (b"PK\x03\x04", "a ZIP archive"),
+    (b"\x7fELF", "an ELF executable"),
+    (b"\xca\xfe\xba\xbe", "a Java class or Mach-O binary"),
+    (b"\xcf\xfa\xed\xfe", "a Mach-O binary"),
+    (b"\xce\xfa\xed\xfe", "a Mach-O binary"),
+    (b"\x00asm", "a WebAssembly module"),
+    (b"\x1f\x8b", "a gzip archive"),
+    (b"BZh", "a bzip2 archive"),
+    (b"\xfd7zXZ\x00", "an xz archive"),
+    (b"7z\xbc\xaf\x27\x1c", "a 7-Zip archive"),
+    (b"Rar!\x1a\x07", "a RAR archive"),
+    (b"SQLite format 3\x00", "an SQLite database"),
+    (
+        b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1",
+        "a legacy Office (OLE) document",
+    ),
+    (b"\x89PNG\r\n\x1a\n", "a PNG image"),
+    (b"\xff\xd8\xff", "a JPEG image"),
+    (b"GIF8", "a GIF image"),
+    (b"ID3", "an MP3 file"),
+    (b"OggS", "an Ogg media file"),
+    (b"fLaC", "a FLAC file"),
+];
+
+/// Rejects `sample` (the start of a file bound for the text parser) when it
+/// looks binary.
+pub fn ensure_text(sample: &[u8]) -> AppResult<()> {
+    let sample = &sample[..sample.len().min(SNIFF_BYTES)];
+    if let Some(kind) = binary_kind(sample) {
+        return Err(AppError::InvalidInput(format!(
+            "file looks like {kind}, not text; supported formats: {SUPPORTED_FORMATS}"
+        )));
+    }
+    Ok(())
+}
+
+/// What kind of binary file `sample` comes from, or `None` when it reads as text.
+pub fn binary_kind(sample: &[u8]) -> Option<&'static str> {
+    // UTF-16 text is full of nulls; a BOM is what tells it apart.
+    if sample.starts_with(b"\xff\xfe") || sample.starts_with(b"\xfe\xff") {
+        return None;
+    }
+    if let Some((_, kind)) = SIGNATURES
+        .iter()
+        .find(|(magic, _)| sample.starts_with(magic))
+    {
+        return Some(kind);
+    }
+    if sample.get(4..8) == Some(b"ftyp") {
+        return Some("an MP4 or QuickTime video");
+    }
+    if sample.is_empty() {
+        return None;
+    }
+
+    let nulls = sample.iter().filter(|&&byte| byte == 0).count();
+    let controls = sample
+        .iter()
+        .filter(|&&byte| {
+            byte != 0 && byte < 0x20 && !matches!(byte, b'\t' | b'\n' | 0x0c | b'\r' | 0x1b)
+        })
+        .count();
+    let len = sample.len() as f64;
+    if nulls as f64 / len > MAX_NULL_RATIO || controls as f64 / len > MAX_CONTROL_RATIO {
+        return Some("an unrecognised binary format");
+    }
+    None
+}
@@ -0,0 +1,164 @@
+//! Small previews shown beside documents in lists, made at ingest. An image
+//! file is scaled down; a PDF shows the picture drawn on its first page, and
+//! a presentation or any other format its first figure; everything else, or
+//! a document whose pictures cannot be read, gets a card with its title and
+//! opening text.
+
+use std::{io::Cursor, path::Path};
+
+use crate::{
+    providers::ImagePart,
+    sidecar::{figures, types::NormalizedPayload},
+};
+
+/// Longest edge of a picture thumbnail, in pixels.
+pub const THUMBNAIL_MAX_EDGE: u32 = 256;
+const CARD_WIDTH: u32 = 192;
+const CARD_HEIGHT: u32 = 256;
+const TITLE_LINES: usize = 2;
+const TITLE_LINE_CHARS: usize = 20;
+const BODY_LINES: usize = 12;
+const BODY_LINE_CHARS: usize = 32;
+
+/// The thumbnail for a document just parsed from `file_path`.
+pub fn generate(file_path: &Path, mime: &str, parsed: &NormalizedPayload) -> ImagePart {
+    let picture = if mime.starts_with("image/") {
+        std::fs::read(file_path).ok()
+    } else if mime == "application/pdf" {
+        first_pdf_page_image(file_path)
+    } else {
+        None
+    };
+    picture
+        .and_then(|bytes| downscale(&bytes))
+        .or_else(|| {
+            parsed
+                .nodes
+                .iter()
+                .filter(|node| node.node_type == "Figure" && node.page_start.unwrap_or(1) <= 1)
+                .find_map(|node| figures::resolve_figure_image(&node.text, file_path.parent()))
+                .and_then(|image| downscale(&image.data))
+        })
+        .unwrap_or_else(|| {
+            text_card(
+                &parsed.document.title,
+                parsed
+                    .nodes
+                    .iter()
+                    .filter(|node| node.node_type != "Figure")
+                    .map(|node| node.text.as_str()),
+            )
+        })
+}
+
+/// `bytes` decoded and shrunk to fit [`THUMBNAIL_MAX_EDGE`], as a PNG, or
+/// `None` when they are not an image the `image` crate reads.
+pub fn downscale(bytes: &[u8]) -> Option<ImagePart> {
+    let image = image::load_from_memory(bytes).ok()?;
+    let thumbnail = image.thumbnail(THUMBNAIL_MAX_EDGE, THUMBNAIL_MAX_EDGE);
+    let mut data = vec![];
+    thumbnail
+        .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+        .ok()?;
+    Some(ImagePart {
+        mime: "image/png".to_string(),
+        data,
+    })
+}
+
+/// An SVG card with the title and the first of `texts` that fit.
+pub fn text_card<'a>(title: &str, texts: impl IntoIterator<Item = &'a str>) -> ImagePart {
+    let mut body = String::new();
+    for text in texts {
+        if body.len() > BODY_LINES * BODY_LINE_CHARS {
+            break;
+        }
+        let text = text.trim();
+        if !text.is_empty() && text != title.trim() {
+            body.push_str(text);
+            body.push(' ');
+        }
+    }
+
+    let mut svg = format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{CARD_WIDTH}" height="{CARD_HEIGHT}" viewBox="0 0 {CARD_WIDTH} {CARD_HEIGHT}"><rect x="0.5" y="0.5" width="{}" height="{}" rx="8" fill="#f8fafc" stroke="#cbd5e1"/><g font-family="system-ui, sans-serif" fill="#0f172a">"##,
+        CARD_WIDTH - 1,
+        CARD_HEIGHT - 1
+    );
+    let mut y = 30;
+    for line in wrap(title, TITLE_LINE_CHARS, TITLE_LINES) {
+        svg.push_str(&format!(
+            r#"<text x="14" y="{y}" font-size="15" font-weight="600">{}</text>"#,
+            escape(&line)
+        ));
+        y += 20;
+    }
+    y += 6;
+    for line in wrap(&body, BODY_LINE_CHARS, BODY_LINES) {
+        svg.push_str(&format!(
+            r##"<text x="14" y="{y}" font-size="10" fill="#475569">{}</text>"##,
+            escape(&line)
+        ));
+        y += 14;
+    }
+    svg.push_str("</g></svg>");
+    ImagePart {
+        mime: "image/svg+xml".to_string(),
+        data: svg.into_bytes(),
+    }
+}
+
+/// There is no PDF rasterizer here, so the first page is shown through the
+/// largest JPEG drawn on it; for a scan, that is the page itself.
+fn first_pdf_page_image(file_path: &Path) -> Option<Vec<u8>> {
+    let mut doc = lopdf::Document::load(file_path).ok()?;
+    if doc.is_encrypted() {
+        doc.decrypt("").ok()?;
+    }
+    let (_, page_id) = doc.get_pages().into_iter().next()?;
+    let images = doc.get_page_images(page_id).ok()?;
+    images
+        .into_iter()
+        .filter(|image| matches!(image.filters.as_deref(), Some([filter]) if filter == "DCTDecode"))
+        .max_by_key(|image| image.width * image.height)
+        .map(|image| image.content.to_vec())
+}
+
+/// Greedy word wrap to `width` characters, at most `max_lines` lines; text
+/// that does not fit ends the last line with an ellipsis.
+fn wrap(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut words = text.split_whitespace().peekable();
+    while let Some(word) = words.peek() {
+        let word: String = word.chars().take(width).collect();
+        let needed =
+            current.chars().count() + usize::from(!current.is_empty()) + word.chars().count();
+        if needed <= width {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(&word);
+            words.next();
+        } else if lines.len() + 1 == max_lines {
+            break;
+        } else {
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        if words.peek().is_some() {
+            current = current.chars().take(width - 1).collect();
+            current.push('…');
+        }
+        lines.push(current);
+    }
+    lines
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
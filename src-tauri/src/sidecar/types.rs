@@ -35,3 +35,17 @@ pub struct NormalizedPayload {
     pub nodes: Vec<SidecarNode>,
     pub edges: Vec<SidecarEdge>,
 }
+
+impl NormalizedPayload {
+    /// Sets `key` on the document metadata and on the root node's, which is
+    /// where it is persisted.
+    pub fn record_metadata(&mut self, key: &str, value: Value) {
+        let metadata = std::iter::once(&mut self.document.metadata)
+            .chain(self.nodes.first_mut().map(|root| &mut root.metadata));
+        for metadata in metadata {
+            if let Some(object) = metadata.as_object_mut() {
+                object.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    db::{
+        repositories::{acronyms, documents},
+        Database,
+    },
+    enrichment::acronyms::{detect, index_acronyms, AcronymDefinition},
+};
+
+fn definition(acronym: &str, expansion: &str) -> AcronymDefinition {
+    AcronymDefinition {
+        acronym: acronym.to_string(),
+        expansion: expansion.to_string(),
+    }
+}
+
+#[test]
+fn definitions_are_found_in_either_order() {
+    assert_eq!(
+        detect("The Service Level Agreement (SLA) sets uptime targets."),
+        vec![definition("SLA", "Service Level Agreement")]
+    );
+    assert_eq!(
+        detect("Funded by NASA (National Aeronautics and Space Administration)."),
+        vec![definition(
+            "NASA",
+            "National Aeronautics and Space Administration"
+        )]
+    );
+    assert_eq!(
+        detect("Per the Department of Defense (DoD) and the Terms of Service (TOS)."),
+        vec![
+            definition("DoD", "Department of Defense"),
+            definition("TOS", "Terms of Service"),
+        ]
+    );
+    assert_eq!(
+        detect("Both Know-Your-Customer (KYC) checks and Service Level Agreements (SLAs)."),
+        vec![
+            definition("KYC", "Know Your Customer"),
+            definition("SLA", "Service Level Agreements"),
+        ]
+    );
+}
+
+#[test]
+fn other_parentheses_are_not_definitions() {
+    assert!(detect("Published in 2019 (see below), the results (p < 0.05) held.").is_empty());
+    assert!(detect("The annual report (SLA) is attached.").is_empty());
+}
+
+#[tokio::test]
+async fn questions_match_either_form_of_a_defined_acronym() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let nodes = vec![
+        node("root-msa", None, "Document", ""),
+        node(
+            "para-def",
+            Some("root-msa"),
+            "Paragraph",
+            "This Service Level Agreement (SLA) is part of the contract.",
+        ),
+        node(
+            "para-uptime",
+            Some("root-msa"),
+            "Paragraph",
+            "Under the service level agreement, uptime is 99.9 percent.",
+        ),
+        node(
+            "para-credits",
+            Some("root-msa"),
+            "Paragraph",
+            "Missing the SLA earns service credits.",
+        ),
+    ];
+    seed_document(&db, "project-default", "doc-msa", "MSA.pdf", &nodes).await;
+
+    let report = index_acronyms(&db, "doc-msa").await.expect("index");
+    assert_eq!(report.acronym_count, 1);
+    let listed = acronyms::list_project_acronyms(db.pool(), "project-default")
+        .await
+        .expect("list");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(
+        (listed[0].expansion.as_str(), listed[0].node_id.as_str()),
+        ("Service Level Agreement", "para-def")
+    );
+
+    let expanded =
+        acronyms::expand_query(db.pool(), "project-default", "what does the SLA require?")
+            .await
+            .expect("expand");
+    assert_eq!(
+        expanded,
+        "what does the SLA require? Service Level Agreement"
+    );
+    let hits = documents::search_project_nodes(db.pool(), "project-default", None, &expanded, 10)
+        .await
+        .expect("search");
+    assert!(hits.iter().any(|hit| hit.id == "para-uptime"));
+
+    let expanded = acronyms::expand_query(
+        db.pool(),
+        "project-default",
+        "When does the service-level agreement apply?",
+    )
+    .await
+    .expect("expand");
+    assert!(expanded.ends_with(" SLA"));
+
+    // A locked document's acronyms stay out of the project's.
+    documents::set_visibility(db.pool(), "doc-msa", false, true)
+        .await
+        .expect("lock");
+    assert!(
+        acronyms::list_project_acronyms(db.pool(), "project-default")
+            .await
+            .expect("list")
+            .is_empty()
+    );
+}
@@ -0,0 +1,122 @@
+use vectorless_lib::db::{
+    repositories::{
+        activity::{self, KEEP_PER_KIND},
+        documents, projects,
+    },
+    Database,
+};
+
+async fn document(db: &Database, project_id: &str, id: &str) {
+    documents::insert_document(
+        db.pool(),
+        id,
+        project_id,
+        &format!("{id}.md"),
+        "text/markdown",
+        &format!("checksum-{id}"),
+        1,
+    )
+    .await
+    .expect("insert document");
+}
+
+#[tokio::test]
+async fn recent_documents_are_listed_once_newest_first() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    projects::create_project(db.pool(), "beta", "Beta")
+        .await
+        .expect("project");
+    document(&db, "project-default", "notes").await;
+    document(&db, "project-default", "report").await;
+    document(&db, "beta", "plan").await;
+
+    for (project_id, document_id) in [
+        ("project-default", "notes"),
+        ("project-default", "report"),
+        ("beta", "plan"),
+        ("project-default", "notes"),
+    ] {
+        activity::record_document_opened(db.pool(), project_id, document_id)
+            .await
+            .expect("record");
+    }
+
+    let recent = activity::recent_documents(db.pool(), None, 10)
+        .await
+        .expect("recent");
+    let ids = recent
+        .iter()
+        .map(|recent| recent.document.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["notes", "plan", "report"]);
+    assert_eq!(recent[1].opened_in_project_id, "beta");
+
+    let in_default = activity::recent_documents(db.pool(), Some("project-default"), 1)
+        .await
+        .expect("recent");
+    assert_eq!(in_default.len(), 1);
+    assert_eq!(in_default[0].document.id, "notes");
+
+    documents::delete_document(db.pool(), "notes")
+        .await
+        .expect("delete");
+    let ids = activity::recent_documents(db.pool(), None, 10)
+        .await
+        .expect("recent")
+        .into_iter()
+        .map(|recent| recent.document.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["plan", "report"]);
+}
+
+#[tokio::test]
+async fn repeated_queries_point_at_their_latest_run() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    for (run_id, query) in [
+        ("run-1", "How fast is it?"),
+        ("run-2", "What changed?"),
+        ("run-3", "How fast is it?"),
+    ] {
+        activity::record_query_asked(db.pool(), "project-default", run_id, query)
+            .await
+            .expect("record");
+    }
+
+    let recent = activity::recent_queries(db.pool(), Some("project-default"), 10)
+        .await
+        .expect("recent");
+    let listed = recent
+        .iter()
+        .map(|recent| (recent.query.as_str(), recent.run_id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        listed,
+        vec![("How fast is it?", "run-3"), ("What changed?", "run-2")]
+    );
+}
+
+#[tokio::test]
+async fn only_the_newest_entries_are_kept() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let total = KEEP_PER_KIND + 5;
+    for idx in 0..total {
+        activity::record_query_asked(
+            db.pool(),
+            "project-default",
+            &format!("run-{idx}"),
+            &format!("question {idx}"),
+        )
+        .await
+        .expect("record");
+    }
+
+    let recent = activity::recent_queries(db.pool(), None, 1000)
+        .await
+        .expect("recent");
+    assert_eq!(recent.len() as i64, KEEP_PER_KIND);
+    assert_eq!(recent[0].query, format!("question {}", total - 1));
+    assert_eq!(
+        recent.last().expect("oldest kept").query,
+        format!("question {}", total - KEEP_PER_KIND)
+    );
+}
@@ -1,4 +1,5 @@
 use vectorless_lib::{
+    core::types::QueryClass,
     providers::gemini::GeminiPlannerStep,
     reasoner::planner::{Planner, PlannerConfig, PlannerDecision, PlannerInput, StepType},
 };
@@ -6,8 +7,10 @@ use vectorless_lib::{
 fn input() -> PlannerInput {
     PlannerInput {
         query: "How are these files related?".to_string(),
+        query_class: QueryClass::Comparison,
         last_confidence: Some(0.41),
         explored_sections: vec![],
+        branch_outcomes: vec![],
         has_evidence: false,
         step_count: 1,
         backtrack_count: 0,
@@ -17,8 +20,10 @@ fn input() -> PlannerInput {
 fn input_with_evidence(has_evidence: bool) -> PlannerInput {
     PlannerInput {
         query: "How are these files related?".to_string(),
+        query_class: QueryClass::Comparison,
         last_confidence: Some(0.41),
         explored_sections: vec![],
+        branch_outcomes: vec![],
         has_evidence,
         step_count: 1,
         backtrack_count: 0,
@@ -0,0 +1,81 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    db::{repositories::documents, Database},
+    reasoner::aggregate,
+    sidecar::{
+        quantities,
+        types::{NormalizedPayload, SidecarDocument},
+    },
+};
+
+#[tokio::test]
+async fn evidence_quantities_are_totalled_by_unit() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let mut payload = NormalizedPayload {
+        document: SidecarDocument {
+            title: "Invoices".to_string(),
+            pages: 1,
+            metadata: serde_json::json!({}),
+        },
+        nodes: vec![
+            node("root-invoices", None, "Document", ""),
+            node(
+                "para-march",
+                Some("root-invoices"),
+                "Paragraph",
+                "March hosting came to $1,200 and took 0.1 s per request.",
+            ),
+            node(
+                "para-april",
+                Some("root-invoices"),
+                "Paragraph",
+                "April hosting came to $1.5k.",
+            ),
+            node(
+                "para-note",
+                Some("root-invoices"),
+                "Paragraph",
+                "Both were paid on time.",
+            ),
+        ],
+        edges: vec![],
+    };
+    quantities::record(&mut payload);
+    seed_document(
+        &db,
+        "project-default",
+        "doc-invoices",
+        "Invoices.pdf",
+        &payload.nodes,
+    )
+    .await;
+
+    let evidence = ["para-april", "para-note", "para-march"].map(String::from);
+    let recorded = documents::get_node_quantities(db.pool(), &evidence)
+        .await
+        .expect("quantities");
+    assert_eq!(
+        recorded
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["para-april", "para-march"]
+    );
+
+    let totals = aggregate::totals(&recorded);
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[0].unit, "USD");
+    assert_eq!(totals[0].count, 2);
+    assert_eq!(totals[0].sum, 2700.0);
+    assert_eq!(totals[0].mean(), 1350.0);
+    assert_eq!(totals[0].node_ids, vec!["para-april", "para-march"]);
+    assert_eq!(totals[1].unit, "s");
+
+    let note = aggregate::prompt_note(&totals);
+    assert!(note.contains(
+        "- USD: 2 value(s), sum 2700, mean 1350, min 1200, max 1500 [citation:para-april] [citation:para-march]\n"
+    ));
+    assert!(aggregate::prompt_note(&[]).is_empty());
+}
@@ -0,0 +1,92 @@
+use vectorless_lib::{
+    core::errors::AppError,
+    db::{repositories::reasoning, Database},
+};
+
+async fn answer(db: &Database, markdown: &str, citations: &[&str], grounded: bool) {
+    reasoning::complete_run(
+        db.pool(),
+        "run-1",
+        10,
+        serde_json::json!({}),
+        0.0,
+        markdown,
+        citations.iter().map(ToString::to_string).collect(),
+        0.8,
+        grounded,
+        serde_json::json!({}),
+        serde_json::json!([]),
+    )
+    .await
+    .expect("complete run");
+}
+
+#[tokio::test]
+async fn regenerated_answers_keep_earlier_revisions() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    reasoning::create_run(
+        db.pool(),
+        "run-1",
+        "project-default",
+        None,
+        "How fast is it?",
+    )
+    .await
+    .expect("create run");
+    answer(&db, "p99 is 50ms.", &["node-a"], true).await;
+    sqlx::query(
+        r#"UPDATE reasoning_runs
+           SET manifest_json = '{"model":"gemini-2.5-pro","generation":{"temperature":0.1}}'
+           WHERE id = 'run-1'"#,
+    )
+    .execute(db.pool())
+    .await
+    .expect("manifest");
+    answer(&db, "p99 is 48ms.", &["node-b"], false).await;
+
+    let revisions = reasoning::list_answer_revisions(db.pool(), "run-1")
+        .await
+        .expect("revisions");
+    assert_eq!(
+        revisions.iter().map(|r| r.revision).collect::<Vec<_>>(),
+        vec![2, 1]
+    );
+    assert!(revisions[0].current && !revisions[1].current);
+    assert_eq!(revisions[0].generation["model"], "gemini-2.5-pro");
+    assert_eq!(revisions[0].generation["generation"]["temperature"], 0.1);
+    assert_eq!(revisions[1].citations, vec!["node-a"]);
+
+    let restored = reasoning::restore_answer_revision(db.pool(), "run-1", 1)
+        .await
+        .expect("restore");
+    assert_eq!(restored.answer_markdown, "p99 is 50ms.");
+    let run = reasoning::get_run(db.pool(), "run-1").await.expect("run");
+    let current = run.answer.expect("answer");
+    assert_eq!(current.answer_markdown, "p99 is 50ms.");
+    assert!(current.grounded);
+    let revisions = reasoning::list_answer_revisions(db.pool(), "run-1")
+        .await
+        .expect("revisions");
+    assert_eq!(revisions.len(), 2);
+    assert!(revisions[1].current);
+}
+
+#[tokio::test]
+async fn missing_runs_and_revisions_are_reported() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    assert!(matches!(
+        reasoning::list_answer_revisions(db.pool(), "run-missing").await,
+        Err(AppError::NotFound(_))
+    ));
+    reasoning::create_run(db.pool(), "run-1", "project-default", None, "q")
+        .await
+        .expect("create run");
+    assert!(reasoning::list_answer_revisions(db.pool(), "run-1")
+        .await
+        .expect("revisions")
+        .is_empty());
+    assert!(matches!(
+        reasoning::restore_answer_revision(db.pool(), "run-1", 1).await,
+        Err(AppError::NotFound(_))
+    ));
+}
@@ -0,0 +1,166 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{node, seed_document};
+use serde_json::json;
+use vectorless_lib::{
+    core::types::{AnswerRecord, ReasoningRun, RunPhase, RunStatus},
+    db::{repositories::documents, Database},
+    reasoner::share::{answer_html, citation_order, cited_snippets, CitedSnippet},
+};
+
+fn run(query: &str) -> ReasoningRun {
+    ReasoningRun {
+        id: "run-1".to_string(),
+        project_id: "project-1".to_string(),
+        document_id: None,
+        query: query.to_string(),
+        status: RunStatus::Completed,
+        phase: RunPhase::Completed,
+        started_at: Utc.with_ymd_and_hms(2026, 3, 4, 9, 30, 0).unwrap(),
+        ended_at: None,
+        total_latency_ms: Some(1200),
+        token_usage_json: json!({}),
+        cost_usd: 0.0,
+        quality_json: json!({
+            "overall": 0.82,
+            "queryAlignment": 0.9,
+            "citationCoverage": 0.75,
+            "evidenceRecall": 0.5,
+            "crossDocumentCoverage": 1.0,
+            "grounded": true
+        }),
+        planner_trace_json: json!([]),
+        prompt_versions_json: json!({}),
+        max_steps: None,
+        replay_of: None,
+        diagnostics_json: json!({}),
+        attach_figure_images: false,
+        manifest: None,
+    }
+}
+
+fn answer(markdown: &str, citations: &[&str]) -> AnswerRecord {
+    AnswerRecord {
+        run_id: "run-1".to_string(),
+        answer_markdown: markdown.to_string(),
+        citations: citations.iter().map(|id| id.to_string()).collect(),
+        confidence: 0.8,
+        grounded: true,
+    }
+}
+
+fn snippet(node_id: &str, text: &str) -> CitedSnippet {
+    CitedSnippet {
+        node_id: node_id.to_string(),
+        document_name: "Q3 Report.pdf".to_string(),
+        title: "Revenue".to_string(),
+        text: text.to_string(),
+        page_start: Some(4),
+        locked: false,
+    }
+}
+
+#[test]
+fn citations_are_numbered_in_the_order_the_answer_uses_them() {
+    let answer = answer(
+        "Revenue grew [citation:b] on demand [citation:a] and [citation:b].",
+        &["a", "b", "c"],
+    );
+    assert_eq!(citation_order(&answer), vec!["b", "a", "c"]);
+
+    let html = answer_html(
+        &run("How did revenue change?"),
+        &answer,
+        &[
+            snippet("a", "Demand rose."),
+            snippet("b", "Up 12%."),
+            snippet("c", "Costs flat."),
+        ],
+    );
+    assert!(html.contains(
+        "grew <sup><a href=\"#cite-1\">[1]</a></sup> on demand <sup><a href=\"#cite-2\">[2]</a></sup>"
+    ));
+    assert!(html.contains("<details id=\"cite-1\"><summary>[1] Revenue — Q3 Report.pdf, p. 4</summary><blockquote>Up 12%.</blockquote></details>"));
+    assert!(html.contains("<details id=\"cite-3\">"));
+    assert!(!html.contains("[citation:"));
+}
+
+#[test]
+fn the_page_is_self_contained_and_escapes_document_text() {
+    let html = answer_html(
+        &run("Is <b> & \"x\" safe?"),
+        &answer(
+            "## Findings\n\n- **Yes** [citation:a]\n- uses `<script>`\n\nDone.",
+            &["a"],
+        ),
+        &[snippet("a", "<script>alert('x')</script>")],
+    );
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Is &lt;b&gt; &amp; &quot;x&quot; safe?</title>"));
+    assert!(html.contains("<h3>Findings</h3>"));
+    assert!(html.contains("<ul>\n<li><strong>Yes</strong> <sup>"));
+    assert!(html.contains("<li>uses <code>&lt;script&gt;</code></li>\n</ul>"));
+    assert!(html.contains("<p>Done.</p>"));
+    assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("src="));
+    assert!(!html.contains("<link"));
+}
+
+#[test]
+fn quality_scores_and_missing_sources_are_reported() {
+    let html = answer_html(
+        &run("What changed?"),
+        &answer("Margins improved [citation:gone].", &[]),
+        &[],
+    );
+    assert!(html.contains("Answered 2026-03-04 09:30 UTC · confidence 80%"));
+    assert!(html.contains("<p id=\"cite-1\">[1] Source no longer available.</p>"));
+    assert!(html.contains("<tr><th>Overall</th><td>82%</td></tr>"));
+    assert!(html.contains("<tr><th>Citation coverage</th><td>75%</td></tr>"));
+
+    let mut unscored = run("What changed?");
+    unscored.quality_json = json!({});
+    let html = answer_html(&unscored, &answer("No citations.", &[]), &[]);
+    assert!(!html.contains("<h2>Quality</h2>"));
+    assert!(!html.contains("<h2>Sources</h2>"));
+}
+
+#[tokio::test]
+async fn exports_leave_out_passages_of_documents_locked_since_the_run() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    for (id, text) in [
+        ("payroll", "Salaries rose 4%."),
+        ("report", "Revenue rose 12%."),
+    ] {
+        let root = format!("root-{id}");
+        seed_document(
+            &db,
+            "project-default",
+            id,
+            &format!("{id}.md"),
+            &[
+                node(&root, None, "Document", ""),
+                node(&format!("sec-{id}"), Some(&root), "Section", text),
+            ],
+        )
+        .await;
+    }
+    let answer = answer(
+        "Revenue [citation:sec-report] and pay [citation:sec-payroll] rose.",
+        &[],
+    );
+    documents::set_visibility(db.pool(), "payroll", false, true)
+        .await
+        .expect("lock");
+
+    let snippets = cited_snippets(db.pool(), &answer).await.expect("snippets");
+    let html = answer_html(&run("What rose?"), &answer, &snippets);
+    assert!(html.contains("Revenue rose 12%."));
+    assert!(!html.contains("Salaries rose 4%."));
+    assert!(!html.contains("[2] sec-payroll"));
+    assert!(
+        html.contains("<p id=\"cite-2\">[2] payroll.md is locked; the passage is left out.</p>")
+    );
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use vectorless_lib::{
+    db::{
+        asset_store,
+        repositories::{assets, documents},
+        Database,
+    },
+    providers::ImagePart,
+    sidecar::types::SidecarNode,
+};
+
+fn node(id: &str, parent: Option<&str>, node_type: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: id.to_string(),
+        text: String::new(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    }
+}
+
+fn image(bytes: &[u8]) -> ImagePart {
+    ImagePart {
+        mime: "image/png".to_string(),
+        data: bytes.to_vec(),
+    }
+}
+
+async fn seed_document(db: &Database, document_id: &str) -> String {
+    documents::insert_document(
+        db.pool(),
+        document_id,
+        "project-default",
+        &format!("{document_id}.pdf"),
+        "application/pdf",
+        &format!("checksum-{document_id}"),
+        1,
+    )
+    .await
+    .expect("insert document");
+    let root = format!("{document_id}-root");
+    let figure = format!("{document_id}-fig");
+    let nodes = vec![
+        node(&root, None, "Document"),
+        node(&figure, Some(&root), "Figure"),
+    ];
+    documents::insert_nodes(db.pool(), document_id, &nodes)
+        .await
+        .expect("insert nodes");
+    figure
+}
+
+async fn asset_count(db: &Database) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM assets")
+        .fetch_one(db.pool())
+        .await
+        .expect("count assets")
+}
+
+#[tokio::test]
+async fn identical_images_are_stored_once() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let first = seed_document(&db, "doc-a").await;
+    let second = seed_document(&db, "doc-b").await;
+    assets::put_node_image(&db, &first, &image(b"same chart"))
+        .await
+        .expect("store first");
+    assets::put_node_image(&db, &second, &image(b"same chart"))
+        .await
+        .expect("store second");
+
+    assert_eq!(asset_count(&db).await, 1);
+    let asset_id = asset_store::asset_id(b"same chart");
+    assert!(db
+        .assets()
+        .root()
+        .join(&asset_id[..2])
+        .join(&asset_id)
+        .is_file());
+
+    let node = documents::get_node(db.pool(), &first).await.expect("node");
+    assert_eq!(
+        node.metadata_json["assets"],
+        serde_json::json!([{ "assetId": asset_id, "role": "figure" }])
+    );
+    let stored = assets::get_node_image(&db, &second)
+        .await
+        .expect("read image")
+        .expect("figure has an image");
+    assert_eq!(stored.data, b"same chart");
+
+    let asset = assets::get_asset(&db, &asset_id).await.expect("asset");
+    assert_eq!(asset.byte_len, 10);
+    assert!(asset.data_url.starts_with("data:image/png;base64,"));
+}
+
+#[tokio::test]
+async fn assets_only_locked_documents_use_are_withheld() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let first = seed_document(&db, "doc-a").await;
+    let second = seed_document(&db, "doc-b").await;
+    assets::put_node_image(&db, &first, &image(b"payroll chart"))
+        .await
+        .expect("store first");
+    assets::put_node_image(&db, &second, &image(b"payroll chart"))
+        .await
+        .expect("store second");
+    let asset_id = asset_store::asset_id(b"payroll chart");
+
+    documents::set_visibility(db.pool(), "doc-a", false, true)
+        .await
+        .expect("lock");
+    assets::ensure_asset_unlocked(db.pool(), &asset_id)
+        .await
+        .expect("doc-b still uses it");
+
+    documents::set_visibility(db.pool(), "doc-b", false, true)
+        .await
+        .expect("lock");
+    assert!(assets::ensure_asset_unlocked(db.pool(), &asset_id)
+        .await
+        .is_err());
+
+    documents::unlock_document(db.pool(), "doc-b", 60)
+        .await
+        .expect("unlock");
+    assets::ensure_asset_unlocked(db.pool(), &asset_id)
+        .await
+        .expect("doc-b is unlocked");
+}
+
+#[tokio::test]
+async fn malformed_asset_ids_are_rejected() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    assert!(db.assets().read("../vectorless.sqlite").is_err());
+    assert!(assets::get_asset(&db, &"A".repeat(64)).await.is_err());
+}
+
+#[tokio::test]
+async fn assets_are_collected_once_no_document_uses_them() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let first = seed_document(&db, "doc-a").await;
+    let second = seed_document(&db, "doc-b").await;
+    assets::put_node_image(&db, &first, &image(b"shared chart"))
+        .await
+        .expect("store shared");
+    assets::put_node_image(&db, &second, &image(b"shared chart"))
+        .await
+        .expect("store shared again");
+    assets::put_node_image(&db, &first, &image(b"replaced chart"))
+        .await
+        .expect("replace first");
+
+    // Only the replaced image has lost its last reference.
+    let report = assets::collect_garbage(&db, Duration::ZERO)
+        .await
+        .expect("collect");
+    assert_eq!(report.removed, 0);
+    documents::delete_document(db.pool(), "doc-b")
+        .await
+        .expect("delete document");
+    let report = assets::collect_garbage(&db, Duration::ZERO)
+        .await
+        .expect("collect");
+    assert_eq!(report.removed, 1);
+    assert_eq!(report.bytes_freed, b"shared chart".len() as i64);
+
+    let shared = asset_store::asset_id(b"shared chart");
+    assert!(db.assets().read(&shared).is_err());
+    let kept = assets::get_node_image(&db, &first)
+        .await
+        .expect("read image")
+        .expect("figure keeps its image");
+    assert_eq!(kept.data, b"replaced chart");
+
+    // A fresh asset is spared by the grace period even with no reference.
+    assets::put_asset(&db, "image/png", b"not yet attached")
+        .await
+        .expect("store loose asset");
+    let report = assets::collect_garbage(&db, assets::GC_GRACE)
+        .await
+        .expect("collect");
+    assert_eq!(report, Default::default());
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use vectorless_lib::{
+    core::types::BackupSettings,
+    db::{backups, repositories::documents, Database, MigrationPolicy},
+};
+
+fn enabled(folder: &str) -> BackupSettings {
+    BackupSettings {
+        enabled: true,
+        folder: Some(folder.to_string()),
+        interval_hours: 6,
+        keep: 2,
+        include_assets: false,
+    }
+}
+
+#[test]
+fn backups_are_due_once_the_interval_has_passed() {
+    assert!(backups::validate(BackupSettings {
+        enabled: true,
+        ..BackupSettings::default()
+    })
+    .is_err());
+    assert!(backups::validate(BackupSettings {
+        keep: 0,
+        ..BackupSettings::default()
+    })
+    .is_err());
+    let settings = backups::validate(enabled("  /backups  ")).expect("valid settings");
+    assert_eq!(settings.folder.as_deref(), Some("/backups"));
+
+    let now = Utc::now();
+    assert!(backups::is_due(&settings, None, now));
+    assert!(!backups::is_due(
+        &settings,
+        Some(now - chrono::Duration::hours(5)),
+        now
+    ));
+    assert!(backups::is_due(
+        &settings,
+        Some(now - chrono::Duration::hours(6)),
+        now
+    ));
+    assert!(!backups::is_due(&BackupSettings::default(), None, now));
+}
+
+#[tokio::test]
+async fn backups_rotate_and_restore_to_a_new_workspace() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("vectorless.sqlite");
+    let db = Database::open_file(&db_path, MigrationPolicy::Apply)
+        .await
+        .expect("db should initialize");
+    documents::insert_document(
+        db.pool(),
+        "doc-notes",
+        "project-default",
+        "Notes.md",
+        "text/markdown",
+        "checksum-notes",
+        1,
+    )
+    .await
+    .expect("insert document");
+
+    let folder = dir.path().join("backups");
+    assert!(backups::list_backups(&folder).expect("list").is_empty());
+    for _ in 0..3 {
+        backups::create_backup(&db, &folder, false)
+            .await
+            .expect("backup");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(backups::list_backups(&folder).expect("list").len(), 3);
+    assert_eq!(backups::rotate(&folder, 2).expect("rotate"), 1);
+    let listed = backups::list_backups(&folder).expect("list");
+    assert_eq!(listed.len(), 2);
+    assert!(listed[0].created_at > listed[1].created_at);
+
+    let dest = backups::restore_destination(&db_path);
+    backups::restore_to(std::path::Path::new(&listed[0].path), &dest).expect("restore");
+    let restored = Database::open_file(&dest, MigrationPolicy::Apply)
+        .await
+        .expect("restored db should open");
+    let document = documents::get_document(restored.pool(), "doc-notes")
+        .await
+        .expect("restored document");
+    assert_eq!(document.name, "Notes.md");
+
+    assert!(backups::restore_to(std::path::Path::new(&listed[1].path), &dest).is_err());
+    assert!(backups::restore_to(&db_path, &dir.path().join("other.sqlite")).is_err());
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::BenchmarkCaseStatus,
+    db::Database,
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind,
+    },
+    reasoner::{
+        benchmark::{normalize_answer, parse_qa_set, run_benchmark, write_report},
+        executor::{ReasoningExecutor, RunOptions},
+    },
+    security::key_pool::ApiKeyPool,
+};
+
+async fn seed(db: &Database) {
+    seed_document(
+        db,
+        "project-default",
+        "doc-bench",
+        "Latency.md",
+        &[
+            node("root-bench", None, "Document", ""),
+            node(
+                "sec-bench",
+                Some("root-bench"),
+                "Section",
+                "p99 latency dropped to 50ms after the cache rollout.",
+            ),
+        ],
+    )
+    .await;
+}
+
+fn provider() -> MockProvider {
+    let answer = serde_json::json!({
+        "answer_markdown": "p99 latency dropped to 50ms after the cache rollout.",
+        "confidence": 0.9,
+        "citations": ["sec-bench"],
+    })
+    .to_string();
+    MockProvider::new(MockFixture {
+        model: "mock-bench".to_string(),
+        context_window: None,
+        multimodal: false,
+        responses: vec![
+            MockResponse {
+                kind: CallKind::Planner,
+                request_hash: None,
+                text: "not a plan".to_string(),
+                token_usage: serde_json::json!({}),
+            },
+            MockResponse {
+                kind: CallKind::Synthesis,
+                request_hash: None,
+                text: answer,
+                token_usage: serde_json::json!({}),
+            },
+        ],
+    })
+}
+
+#[test]
+fn qa_sets_parse_from_json_arrays_objects_and_lines() {
+    let array = parse_qa_set(r#"[{"question": "What is p99?", "expectedKeywords": ["50ms"]}]"#)
+        .expect("array");
+    assert_eq!(array[0].expected_keywords, vec!["50ms"]);
+
+    let wrapped = parse_qa_set(r#"{"questions": [{"question": "What is p99?"}]}"#).expect("object");
+    assert_eq!(wrapped.len(), 1);
+
+    let lines =
+        parse_qa_set("{\"question\": \"One?\"}\n\n{\"question\": \"Two?\", \"id\": \"q2\"}\n")
+            .expect("lines");
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1].id.as_deref(), Some("q2"));
+
+    assert!(parse_qa_set("[]").is_err());
+    assert!(parse_qa_set(r#"[{"question": "  "}]"#).is_err());
+    let err = parse_qa_set("{\"question\": \"One?\"}\nnot json").expect_err("bad line");
+    assert!(err.to_string().contains("line 2"), "{err}");
+}
+
+#[test]
+fn exact_answers_ignore_case_whitespace_and_trailing_punctuation() {
+    assert_eq!(normalize_answer("  p99 is\n 50 MS. "), "p99 is 50 ms");
+    assert_eq!(
+        normalize_answer("p99 is 50 ms"),
+        normalize_answer("P99 IS 50 ms!")
+    );
+}
+
+#[tokio::test]
+async fn benchmark_scores_each_question_and_writes_a_report() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let cases = parse_qa_set(
+        r#"[
+            {"id": "latency", "question": "What happened to p99 latency after the cache rollout?",
+             "expectedKeywords": ["50ms"], "expectedCitations": ["latency.md"],
+             "expectedAnswer": "p99 latency dropped to 50ms after the cache rollout"},
+            {"id": "throughput", "question": "What happened to p99 latency after the cache rollout?",
+             "expectedKeywords": ["50ms", "throughput"], "expectedCitations": ["sec-missing"]}
+        ]"#,
+    )
+    .expect("cases");
+
+    let mut seen = vec![];
+    let report = run_benchmark(
+        &ReasoningExecutor::new(provider()),
+        &db,
+        "project-default",
+        "qa.json",
+        &cases,
+        RunOptions {
+            max_steps: Some(6),
+            api_keys: ApiKeyPool::single("unused"),
+            ..RunOptions::default()
+        },
+        || (),
+        |result| seen.push(result.index),
+    )
+    .await
+    .expect("benchmark");
+
+    assert_eq!(seen, vec![0, 1]);
+    assert_eq!(report.model, "mock-bench");
+    let [latency, throughput] = &report.cases[..] else {
+        panic!("expected two cases");
+    };
+    assert_eq!(latency.status, BenchmarkCaseStatus::Passed, "{latency:?}");
+    assert_eq!(latency.citations_matched, vec!["latency.md"]);
+    assert_eq!(latency.exact_match, Some(true));
+    assert!(latency.quality.is_some());
+    assert_eq!(throughput.status, BenchmarkCaseStatus::Failed);
+    assert_eq!(throughput.keywords_matched, vec!["50ms"]);
+    assert_eq!(throughput.keywords_missing, vec!["throughput"]);
+    assert_eq!(throughput.citations_missing, vec!["sec-missing"]);
+    assert_eq!(throughput.exact_match, None);
+
+    assert_eq!(report.summary.total, 2);
+    assert_eq!(report.summary.passed, 1);
+    assert_eq!(report.summary.failed, 1);
+    assert_eq!(report.summary.pass_rate, 0.5);
+    assert_eq!(report.summary.keyword_recall, Some(2.0 / 3.0));
+    assert_eq!(report.summary.citation_recall, Some(0.5));
+    assert_eq!(report.summary.exact_match_rate, Some(1.0));
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("reports").join("bench.json");
+    write_report(&report, &path).expect("write report");
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).expect("read")).expect("json");
+    assert_eq!(written["summary"]["passed"], 1);
+    assert_eq!(written["cases"][1]["status"], "failed");
+}
@@ -0,0 +1,192 @@
+use serde_json::{json, Value};
+use vectorless_lib::{
+    db::{repositories::documents, Database},
+    sidecar::{
+        references::{self, Reference},
+        types::{NormalizedPayload, SidecarDocument, SidecarNode},
+    },
+};
+
+fn node(id: &str, node_type: &str, title: &str, text: &str, metadata: Value) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: (node_type != "Document").then(|| "root".to_string()),
+        node_type: node_type.to_string(),
+        title: title.to_string(),
+        text: text.to_string(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: json!({}),
+        metadata,
+    }
+}
+
+fn payload(metadata: Value, nodes: Vec<SidecarNode>) -> NormalizedPayload {
+    let mut all = vec![node("root", "Document", "paper.pdf", "", json!({}))];
+    all.extend(nodes);
+    NormalizedPayload {
+        document: SidecarDocument {
+            title: "paper.pdf".to_string(),
+            pages: 1,
+            metadata,
+        },
+        nodes: all,
+        edges: vec![],
+    }
+}
+
+fn reference(title: &str, authors: &[&str], year: Option<i32>, doi: Option<&str>) -> Reference {
+    Reference {
+        title: title.to_string(),
+        authors: authors.iter().map(ToString::to_string).collect(),
+        year,
+        doi: doi.map(ToString::to_string),
+    }
+}
+
+#[test]
+fn references_are_read_from_the_byline_and_pdf_info() {
+    let mut paper = payload(
+        json!({}),
+        vec![
+            node(
+                "heading",
+                "Heading",
+                "Sparse Attention at Scale",
+                "",
+                json!({}),
+            ),
+            node(
+                "byline",
+                "Paragraph",
+                "",
+                "By Ada Lovelace and Charles Babbage\nPublished 2021, doi:10.1000/xyz123.",
+                json!({}),
+            ),
+        ],
+    );
+    assert_eq!(
+        references::detect(&paper),
+        Some(reference(
+            "Sparse Attention at Scale",
+            &["Ada Lovelace", "Charles Babbage"],
+            Some(2021),
+            Some("10.1000/xyz123"),
+        ))
+    );
+    references::record(&mut paper);
+    assert_eq!(
+        paper.nodes[0].metadata["reference"]["authors"][1],
+        "Charles Babbage"
+    );
+
+    // Placeholder titles give way to the first heading.
+    let from_info = payload(
+        json!({ "pdfInfo": {
+            "title": "Microsoft Word - draft.docx",
+            "author": "Lovelace, Ada; Babbage, Charles",
+            "creationDate": "D:19990101120000Z",
+        } }),
+        vec![node(
+            "heading",
+            "Heading",
+            "Notes on the Engine",
+            "",
+            json!({}),
+        )],
+    );
+    assert_eq!(
+        references::detect(&from_info),
+        Some(reference(
+            "Notes on the Engine",
+            &["Lovelace, Ada", "Babbage, Charles"],
+            Some(1999),
+            None,
+        ))
+    );
+
+    let memo = payload(
+        json!({}),
+        vec![node(
+            "para",
+            "Paragraph",
+            "",
+            "By the end of the year we shipped it.",
+            json!({}),
+        )],
+    );
+    assert_eq!(references::detect(&memo), None);
+}
+
+#[test]
+fn bibliographies_are_written_as_bibtex_and_csl_json() {
+    let entries = vec![
+        (
+            "doc-1".to_string(),
+            reference(
+                "Sparse Attention & Scale",
+                &["Ada Lovelace", "Charles Babbage"],
+                Some(2021),
+                Some("10.1000/xyz123"),
+            ),
+        ),
+        (
+            "doc-2".to_string(),
+            reference("Sparse Attention", &["Lovelace, Ada"], Some(2021), None),
+        ),
+    ];
+
+    let bib = references::bibtex(&entries);
+    assert!(bib.contains("@misc{lovelace2021sparsea,\n  title = {Sparse Attention \\& Scale},"));
+    assert!(bib.contains("  author = {Ada Lovelace and Charles Babbage},\n"));
+    assert!(bib.contains("  doi = {10.1000/xyz123},\n"));
+    assert!(bib.contains("@misc{lovelace2021sparseb,"));
+
+    let csl = references::csl_json(&entries);
+    assert_eq!(csl[0]["id"], "doc-1");
+    assert_eq!(csl[0]["type"], "article-journal");
+    assert_eq!(csl[0]["issued"]["date-parts"], json!([[2021]]));
+    assert_eq!(csl[0]["DOI"], "10.1000/xyz123");
+    assert_eq!(csl[1]["type"], "document");
+    assert_eq!(
+        csl[1]["author"],
+        json!([{ "family": "Lovelace", "given": "Ada" }])
+    );
+}
+
+#[tokio::test]
+async fn project_references_skip_documents_without_one() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let detected = reference("Notes on the Engine", &["Ada Lovelace"], Some(1843), None);
+    for (id, metadata) in [
+        ("doc-paper", json!({ "reference": detected })),
+        ("doc-memo", json!({})),
+        ("doc-locked", json!({ "reference": detected })),
+    ] {
+        documents::insert_document(
+            db.pool(),
+            id,
+            "project-default",
+            id,
+            "application/pdf",
+            &format!("checksum-{id}"),
+            1,
+        )
+        .await
+        .expect("insert document");
+        let root = node(&format!("root-{id}"), "Document", id, "", metadata);
+        documents::insert_nodes(db.pool(), id, &[root])
+            .await
+            .expect("insert nodes");
+    }
+    sqlx::query("UPDATE documents SET locked = 1 WHERE id = 'doc-locked'")
+        .execute(db.pool())
+        .await
+        .expect("lock");
+
+    let found = documents::project_references(db.pool(), "project-default")
+        .await
+        .expect("references");
+    assert_eq!(found, vec![("doc-paper".to_string(), detected)]);
+}
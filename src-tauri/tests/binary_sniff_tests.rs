@@ -0,0 +1,74 @@
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+use vectorless_lib::{
+    core::errors::AppError,
+    sidecar::{native_parser, sniff},
+};
+
+fn temp_file(suffix: &str, contents: &[u8]) -> NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(suffix)
+        .tempfile()
+        .expect("temp file");
+    file.write_all(contents).expect("write file");
+    file
+}
+
+#[test]
+fn known_binary_signatures_are_named() {
+    assert_eq!(
+        sniff::binary_kind(b"\x7fELF\x02\x01\x01"),
+        Some("an ELF executable")
+    );
+    assert_eq!(
+        sniff::binary_kind(b"PK\x03\x04\x14\x00"),
+        Some("a ZIP archive")
+    );
+    assert_eq!(
+        sniff::binary_kind(b"SQLite format 3\x00\x10\x00"),
+        Some("an SQLite database")
+    );
+    assert_eq!(
+        sniff::binary_kind(b"\x00\x00\x00\x18ftypmp42"),
+        Some("an MP4 or QuickTime video")
+    );
+}
+
+#[test]
+fn text_including_utf16_and_ansi_logs_is_not_binary() {
+    assert_eq!(sniff::binary_kind(b""), None);
+    assert_eq!(sniff::binary_kind(b"# Notes\n\nPlain\ttext\r\n"), None);
+    assert_eq!(
+        sniff::binary_kind(b"\x1b[32mINFO\x1b[0m server started\n"),
+        None
+    );
+    let mut utf16 = vec![0xFF, 0xFE];
+    utf16.extend("Hello".encode_utf16().flat_map(u16::to_le_bytes));
+    assert_eq!(sniff::binary_kind(&utf16), None);
+}
+
+#[test]
+fn unknown_binary_content_is_rejected_with_supported_formats() {
+    let mut bytes = b"\x13\x37header".to_vec();
+    bytes.extend((0..512u32).map(|i| (i % 7) as u8));
+    let file = temp_file(".dat", &bytes);
+
+    let err = native_parser::parse(file.path(), "application/octet-stream")
+        .expect_err("binary should be rejected");
+    let AppError::InvalidInput(message) = err else {
+        panic!("expected InvalidInput, got {err:?}");
+    };
+    assert!(message.contains("unrecognised binary format"));
+    assert!(message.contains(sniff::SUPPORTED_FORMATS));
+}
+
+#[test]
+fn renamed_executables_are_rejected_by_the_text_parser() {
+    let mut bytes = b"\x7fELF\x02\x01\x01".to_vec();
+    bytes.extend([0u8; 64]);
+    let file = temp_file(".txt", &bytes);
+
+    let err = native_parser::parse(file.path(), "text/plain").expect_err("not text");
+    assert!(err.to_string().contains("ELF executable"));
+}
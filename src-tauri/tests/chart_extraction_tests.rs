@@ -0,0 +1,202 @@
+use vectorless_lib::{
+    core::types::NodeType,
+    db::{
+        repositories::{assets, documents},
+        Database,
+    },
+    enrichment::charts::{
+        chart_table_node_id, extract_chart_tables, looks_like_chart, parse_chart_table,
+    },
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind, ImagePart, ProviderResponse,
+    },
+    security::key_pool::ApiKeyPool,
+    sidecar::types::SidecarNode,
+};
+
+const TABLE: &str = "| Quarter | Revenue (USD m) |\n| --- | --- |\n| Q1 | 12 |\n| Q2 | 18 |";
+
+fn response(text: &str) -> ProviderResponse {
+    ProviderResponse {
+        text: text.to_string(),
+        token_usage: serde_json::json!({}),
+    }
+}
+
+fn node(id: &str, parent: Option<&str>, node_type: &str, title: &str, text: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: title.to_string(),
+        text: text.to_string(),
+        page_start: Some(2),
+        page_end: Some(2),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-chart-1",
+        "project-default",
+        "Results.md",
+        "text/markdown",
+        "checksum-chart-1",
+        2,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        node("root-chart", None, "Document", "Results", ""),
+        node(
+            "sec-chart",
+            Some("root-chart"),
+            "Section",
+            "Revenue",
+            "Revenue grew.",
+        ),
+        node(
+            "fig-chart",
+            Some("sec-chart"),
+            "Figure",
+            "Figure 1",
+            "![Quarterly revenue bar chart](img/revenue.png)",
+        ),
+        node(
+            "fig-photo",
+            Some("sec-chart"),
+            "Figure",
+            "Figure 2",
+            "![Team photo](img/team.png)",
+        ),
+    ];
+    documents::insert_nodes(db.pool(), "doc-chart-1", &nodes)
+        .await
+        .expect("insert nodes");
+    for figure in ["fig-chart", "fig-photo"] {
+        assets::put_node_image(
+            db,
+            figure,
+            &ImagePart {
+                mime: "image/png".to_string(),
+                data: figure.as_bytes().to_vec(),
+            },
+        )
+        .await
+        .expect("store image");
+    }
+}
+
+fn provider(multimodal: bool, text: &str) -> MockProvider {
+    MockProvider::new(MockFixture {
+        model: "mock-vision".to_string(),
+        context_window: None,
+        multimodal,
+        responses: vec![MockResponse {
+            kind: CallKind::Enrichment,
+            request_hash: None,
+            text: text.to_string(),
+            token_usage: serde_json::json!({}),
+        }],
+    })
+}
+
+#[test]
+fn chart_detection_reads_captions_not_image_payloads() {
+    assert!(looks_like_chart(
+        "Figure 1",
+        "![Quarterly revenue bar chart](img/a.png)"
+    ));
+    assert!(looks_like_chart("Latency over time (line plot)", ""));
+    assert!(looks_like_chart(
+        "Figure 4",
+        r#"<img src="x.png" alt="Histogram of errors">"#
+    ));
+    assert!(!looks_like_chart("Figure 2", "![Team photo](img/team.png)"));
+    assert!(!looks_like_chart(
+        "Figure 3",
+        "![Logo](data:image/png;base64,Y2hhcnQgYmFyIGxpbmUgcGxvdA==chart)"
+    ));
+    assert!(!looks_like_chart(
+        "Figure 5",
+        r#"<img src="charts/bar.png" alt="Logo">"#
+    ));
+}
+
+#[test]
+fn parses_chart_tables_and_rejects_malformed_output() {
+    let parsed = parse_chart_table(&response(
+        &serde_json::json!({"is_chart": true, "title": "Revenue by quarter", "table_markdown": TABLE})
+            .to_string(),
+    ))
+    .expect("valid output")
+    .expect("chart table");
+    assert_eq!(parsed.title, "Revenue by quarter");
+    assert_eq!(parsed.table_markdown, TABLE);
+
+    let not_chart = parse_chart_table(&response(r#"{"is_chart": false}"#)).expect("valid output");
+    assert!(not_chart.is_none());
+
+    assert!(parse_chart_table(&response(
+        r#"{"is_chart": true, "table_markdown": "Revenue went up"}"#
+    ))
+    .is_err());
+    assert!(parse_chart_table(&response("not json at all")).is_err());
+}
+
+#[tokio::test]
+async fn chart_figures_gain_a_child_table_node() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let answer = serde_json::json!({
+        "is_chart": true,
+        "title": "Revenue by quarter",
+        "table_markdown": TABLE,
+    })
+    .to_string();
+    let provider = provider(true, &answer);
+    let report = extract_chart_tables(&db, &provider, &ApiKeyPool::default(), "doc-chart-1")
+        .await
+        .expect("extraction should run");
+
+    assert_eq!(report.figures_scanned, 2);
+    assert_eq!(
+        report.table_node_ids,
+        vec![chart_table_node_id("fig-chart")]
+    );
+    assert_eq!(provider.received_images().len(), 1);
+
+    let table = documents::get_node(db.pool(), &chart_table_node_id("fig-chart"))
+        .await
+        .expect("table node");
+    assert!(matches!(table.node_type, NodeType::Table));
+    assert_eq!(table.parent_id.as_deref(), Some("fig-chart"));
+    assert_eq!(table.text, TABLE);
+    assert_eq!(table.page_start, Some(2));
+    assert_eq!(table.metadata_json["kind"], "chart_data");
+    assert_eq!(table.metadata_json["sourceFigureId"], "fig-chart");
+
+    let rerun = extract_chart_tables(&db, &provider, &ApiKeyPool::default(), "doc-chart-1")
+        .await
+        .expect("rerun should succeed");
+    assert_eq!(rerun.already_extracted, 1);
+    assert!(rerun.table_node_ids.is_empty());
+}
+
+#[tokio::test]
+async fn extraction_requires_a_model_that_reads_images() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let provider = provider(false, r#"{"is_chart": false}"#);
+    let err = extract_chart_tables(&db, &provider, &ApiKeyPool::default(), "doc-chart-1")
+        .await
+        .expect_err("text-only model should be rejected");
+    assert_eq!(err.code(), "INVALID_INPUT");
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::{CitationHeatmap, NodeType},
+    db::{
+        repositories::{
+            projects,
+            reasoning::{self, HeatmapScope},
+        },
+        Database,
+    },
+};
+
+async fn seed(db: &Database) {
+    for (document_id, nodes) in [
+        (
+            "doc-a",
+            vec![
+                node("root-a", None, "Document", ""),
+                node("sec-a1", Some("root-a"), "Section", ""),
+                node("p-a1", Some("sec-a1"), "Paragraph", ""),
+                node("p-a2", Some("sec-a1"), "Paragraph", ""),
+                node("sec-a2", Some("root-a"), "Section", ""),
+                node("p-a3", Some("sec-a2"), "Paragraph", ""),
+            ],
+        ),
+        (
+            "doc-b",
+            vec![
+                node("root-b", None, "Document", ""),
+                node("sec-b1", Some("root-b"), "Section", ""),
+                node("p-b1", Some("sec-b1"), "Paragraph", ""),
+            ],
+        ),
+    ] {
+        seed_document(
+            db,
+            "project-default",
+            document_id,
+            &format!("{document_id}.pdf"),
+            &nodes,
+        )
+        .await;
+    }
+}
+
+async fn answered_run(db: &Database, run_id: &str, project_id: &str, cited: &[&str]) {
+    reasoning::create_run(db.pool(), run_id, project_id, None, "q")
+        .await
+        .expect("create run");
+    reasoning::complete_run(
+        db.pool(),
+        run_id,
+        10,
+        serde_json::json!({}),
+        0.0,
+        "answer",
+        cited.iter().map(ToString::to_string).collect(),
+        0.8,
+        true,
+        serde_json::json!({}),
+        serde_json::json!([]),
+    )
+    .await
+    .expect("complete run");
+}
+
+fn heat(map: &CitationHeatmap, node_id: &str) -> (i64, i64) {
+    map.nodes
+        .iter()
+        .find(|node| node.node_id == node_id)
+        .map(|node| (node.citations, node.total))
+        .unwrap_or((0, 0))
+}
+
+#[tokio::test]
+async fn sections_are_as_hot_as_the_answers_citing_their_content() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    answered_run(&db, "run-1", "project-default", &["p-a1", "p-a2"]).await;
+    answered_run(&db, "run-2", "project-default", &["p-a1", "p-b1"]).await;
+    answered_run(&db, "run-3", "project-default", &[]).await;
+
+    let map = reasoning::citation_heatmap(db.pool(), HeatmapScope::Project("project-default"))
+        .await
+        .expect("heatmap");
+    assert_eq!(map.answer_count, 2);
+    assert_eq!(map.max_total, 2);
+    assert_eq!(heat(&map, "p-a1"), (2, 2));
+    assert_eq!(heat(&map, "p-a2"), (1, 1));
+    // run-1 cites two of its paragraphs but counts once for the section.
+    assert_eq!(heat(&map, "sec-a1"), (0, 2));
+    assert_eq!(heat(&map, "root-a"), (0, 2));
+    assert_eq!(heat(&map, "sec-b1"), (0, 1));
+    assert!(map.nodes.iter().all(|node| node.node_id != "sec-a2"));
+
+    let first = &map.nodes[0];
+    assert_eq!(first.node_id, "p-a1");
+    assert_eq!(first.node_type, NodeType::Paragraph);
+    assert_eq!(first.intensity, 1.0);
+    let section = map
+        .nodes
+        .iter()
+        .find(|node| node.node_id == "sec-b1")
+        .expect("sec-b1");
+    assert_eq!(section.intensity, 0.5);
+}
+
+#[tokio::test]
+async fn a_document_map_counts_runs_from_every_project() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    projects::create_project(db.pool(), "project-other", "Other")
+        .await
+        .expect("create project");
+    answered_run(&db, "run-1", "project-default", &["p-a1"]).await;
+    answered_run(&db, "run-2", "project-other", &["p-gone", "p-b1"]).await;
+    sqlx::query(
+        "INSERT INTO node_id_remaps (old_node_id, new_node_id, document_id) VALUES ('p-gone', 'p-a3', 'doc-a')",
+    )
+    .execute(db.pool())
+    .await
+    .expect("remap");
+
+    let map = reasoning::citation_heatmap(db.pool(), HeatmapScope::Document("doc-a"))
+        .await
+        .expect("heatmap");
+    assert_eq!(map.document_id.as_deref(), Some("doc-a"));
+    assert_eq!(map.answer_count, 2);
+    assert_eq!(heat(&map, "p-a3"), (1, 1));
+    assert_eq!(heat(&map, "root-a"), (0, 2));
+    assert!(map.nodes.iter().all(|node| node.document_id == "doc-a"));
+}
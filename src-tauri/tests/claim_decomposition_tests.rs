@@ -0,0 +1,151 @@
+use vectorless_lib::{
+    core::types::NodeType,
+    db::{repositories::documents, Database},
+    enrichment::claims::{claim_node_id, decompose_claims, rule_based_claims, split_sentences},
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind, LlmProvider,
+    },
+    security::key_pool::ApiKeyPool,
+    sidecar::types::SidecarNode,
+};
+
+const FINDINGS: &str = "Revenue grew 12.5% in Q2, led by Dr. Smith's team. The EMEA region \
+                        contributed most of the growth, e.g. through new contracts. Why?";
+
+fn node(id: &str, parent: Option<&str>, node_type: &str, text: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: id.to_string(),
+        text: text.to_string(),
+        page_start: Some(3),
+        page_end: Some(3),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-claims-1",
+        "project-default",
+        "Findings.md",
+        "text/markdown",
+        "checksum-claims-1",
+        3,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        node("root-claims", None, "Document", ""),
+        node("sec-claims", Some("root-claims"), "Section", "Findings"),
+        node("para-findings", Some("sec-claims"), "Paragraph", FINDINGS),
+        node(
+            "para-short",
+            Some("sec-claims"),
+            "Paragraph",
+            "Costs stayed flat through the year.",
+        ),
+    ];
+    documents::insert_nodes(db.pool(), "doc-claims-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+#[test]
+fn sentence_rules_skip_abbreviations_initials_and_decimals() {
+    assert_eq!(
+        split_sentences(
+            "J. R. Smith wrote it in 2.5 days. It shipped vs. the plan. 3 bugs remain!"
+        ),
+        vec![
+            "J. R. Smith wrote it in 2.5 days.",
+            "It shipped vs. the plan.",
+            "3 bugs remain!",
+        ]
+    );
+    assert_eq!(
+        rule_based_claims(FINDINGS),
+        vec![
+            "Revenue grew 12.5% in Q2, led by Dr. Smith's team.",
+            "The EMEA region contributed most of the growth, e.g. through new contracts.",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rule_pass_adds_claim_children_once() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let report = decompose_claims(&db, None, "doc-claims-1", None)
+        .await
+        .expect("decomposition should run");
+    assert_eq!(report.paragraphs_scanned, 2);
+    assert_eq!(report.claims_created, 2);
+    assert_eq!(report.single_claim, 1);
+
+    let first = documents::get_node(db.pool(), &claim_node_id("para-findings", 1))
+        .await
+        .expect("claim node");
+    assert!(matches!(first.node_type, NodeType::Claim));
+    assert_eq!(first.parent_id.as_deref(), Some("para-findings"));
+    assert_eq!(first.ordinal_path, "para-findings.1");
+    assert_eq!(first.page_start, Some(3));
+    assert_eq!(first.metadata_json["method"], "rules");
+    assert_eq!(first.metadata_json["sourceParagraphId"], "para-findings");
+
+    let rerun = decompose_claims(&db, None, "doc-claims-1", None)
+        .await
+        .expect("rerun should run");
+    assert_eq!(rerun.already_decomposed, 1);
+    assert_eq!(rerun.claims_created, 0);
+}
+
+#[tokio::test]
+async fn model_pass_splits_only_the_selected_paragraphs() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let provider = MockProvider::new(MockFixture {
+        model: "mock-claims".to_string(),
+        context_window: None,
+        multimodal: false,
+        responses: vec![MockResponse {
+            kind: CallKind::Enrichment,
+            request_hash: None,
+            text: r#"{"claims": ["Costs stayed flat in 2024.", "Costs did not rise in any quarter of 2024."]}"#
+                .to_string(),
+            token_usage: serde_json::json!({}),
+        }],
+    });
+    let keys = ApiKeyPool::default();
+    let model: (&dyn LlmProvider, &ApiKeyPool) = (&provider, &keys);
+    let selected = vec!["para-short".to_string()];
+    let report = decompose_claims(&db, Some(model), "doc-claims-1", Some(&selected))
+        .await
+        .expect("decomposition should run");
+    assert_eq!(report.paragraphs_scanned, 1);
+    assert_eq!(report.claims_created, 2);
+
+    let second = documents::get_node(db.pool(), &claim_node_id("para-short", 2))
+        .await
+        .expect("claim node");
+    assert_eq!(second.text, "Costs did not rise in any quarter of 2024.");
+    assert_eq!(second.metadata_json["method"], "model");
+    assert_eq!(second.metadata_json["model"], "mock-claims");
+    assert!(
+        documents::get_node(db.pool(), &claim_node_id("para-findings", 1))
+            .await
+            .is_err()
+    );
+
+    let err = decompose_claims(&db, None, "doc-claims-1", Some(&["sec-claims".to_string()]))
+        .await
+        .expect_err("sections cannot be decomposed");
+    assert_eq!(err.code(), "INVALID_INPUT");
+}
@@ -0,0 +1,51 @@
+//! Fixtures shared by the integration tests. Each test crate uses only some
+//! of them.
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use vectorless_lib::{
+    db::{repositories::documents, Database},
+    sidecar::{mime, types::SidecarNode},
+};
+
+/// A node on page 1, titled and ordered by its id.
+pub fn node(id: &str, parent: Option<&str>, node_type: &str, text: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: id.to_string(),
+        text: text.to_string(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    }
+}
+
+/// Stores `nodes` as the one-page document `name` in `project_id`. Its type
+/// follows the file extension and its checksum is `checksum-<document_id>`.
+pub async fn seed_document(
+    db: &Database,
+    project_id: &str,
+    document_id: &str,
+    name: &str,
+    nodes: &[SidecarNode],
+) {
+    documents::insert_document(
+        db.pool(),
+        document_id,
+        project_id,
+        name,
+        mime::from_extension(Path::new(name)),
+        &format!("checksum-{document_id}"),
+        1,
+    )
+    .await
+    .expect("insert document");
+    documents::insert_nodes(db.pool(), document_id, nodes)
+        .await
+        .expect("insert nodes");
+}
@@ -0,0 +1,140 @@
+use vectorless_lib::{
+    core::types::{CorpusChange, CorpusStats},
+    db::{
+        repositories::{documents, projects},
+        Database,
+    },
+    sidecar::types::SidecarNode,
+};
+
+fn node(id: &str, parent: Option<&str>, node_type: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: id.to_string(),
+        text: String::new(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    }
+}
+
+async fn seed_document(db: &Database, document_id: &str, project_id: &str, sections: usize) {
+    documents::insert_document(
+        db.pool(),
+        document_id,
+        project_id,
+        &format!("{document_id}.pdf"),
+        "application/pdf",
+        &format!("checksum-{document_id}"),
+        1,
+    )
+    .await
+    .expect("insert document");
+    let root = format!("{document_id}-root");
+    let mut nodes = vec![node(&root, None, "Document")];
+    for index in 0..sections {
+        let section = format!("{document_id}-sec-{index}");
+        nodes.push(node(&section, Some(&root), "Section"));
+        nodes.push(node(
+            &format!("{section}-para"),
+            Some(&section),
+            "Paragraph",
+        ));
+    }
+    documents::insert_nodes(db.pool(), document_id, &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+#[tokio::test]
+async fn event_reports_the_document_and_project_totals() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(&db, "doc-a", "project-default", 2).await;
+    seed_document(&db, "doc-b", "project-default", 1).await;
+
+    let event = documents::corpus_changed_event(
+        db.pool(),
+        "project-default",
+        CorpusChange::DocumentAdded,
+        "doc-b",
+    )
+    .await
+    .expect("event");
+    assert_eq!(event.project_id, "project-default");
+    assert_eq!(event.document_id, "doc-b");
+    assert_eq!((event.node_count, event.section_count), (3, 1));
+    assert_eq!(
+        event.stats,
+        CorpusStats {
+            document_count: 2,
+            node_count: 5 + 3,
+        }
+    );
+
+    let payload = serde_json::to_value(&event).expect("serialize");
+    assert_eq!(payload["change"], "document_added");
+    assert_eq!(payload["stats"]["documentCount"], 2);
+}
+
+#[tokio::test]
+async fn removal_is_reported_to_every_project_holding_the_document() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    projects::create_project(db.pool(), "project-other", "Other")
+        .await
+        .expect("create project");
+    seed_document(&db, "doc-shared", "project-default", 1).await;
+    documents::link_document(db.pool(), "project-other", "doc-shared")
+        .await
+        .expect("link");
+
+    let holders = documents::document_project_ids(db.pool(), "doc-shared")
+        .await
+        .expect("holders");
+    assert_eq!(holders, vec!["project-default", "project-other"]);
+
+    documents::delete_document(db.pool(), "doc-shared")
+        .await
+        .expect("delete");
+    for project_id in &holders {
+        let event = documents::corpus_changed_event(
+            db.pool(),
+            project_id,
+            CorpusChange::DocumentRemoved,
+            "doc-shared",
+        )
+        .await
+        .expect("event");
+        assert_eq!((event.node_count, event.section_count), (0, 0));
+        assert_eq!(event.stats, CorpusStats::default());
+    }
+}
+
+#[tokio::test]
+async fn locked_documents_are_left_out_of_the_totals() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(&db, "doc-open", "project-default", 1).await;
+    seed_document(&db, "doc-locked", "project-default", 2).await;
+    documents::set_visibility(db.pool(), "doc-locked", false, true)
+        .await
+        .expect("lock");
+
+    let event = documents::corpus_changed_event(
+        db.pool(),
+        "project-default",
+        CorpusChange::NodesAdded,
+        "doc-open",
+    )
+    .await
+    .expect("event");
+    assert_eq!(
+        event.stats,
+        CorpusStats {
+            document_count: 1,
+            node_count: 3,
+        }
+    );
+}
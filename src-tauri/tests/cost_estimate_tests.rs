@@ -0,0 +1,264 @@
+use futures::future::BoxFuture;
+
+use vectorless_lib::{
+    core::{
+        errors::AppResult,
+        types::{GenerationSettings, ModelPricing, QueryScope, ScopeDecision},
+    },
+    db::{
+        repositories::{
+            documents,
+            reasoning::{self, CallTiming},
+        },
+        Database,
+    },
+    providers::{CallKind, LlmProvider, ProviderResponse},
+    reasoner::executor::{ReasoningExecutor, RunOptions},
+    sidecar::types::SidecarNode,
+};
+
+/// Fails the test if an estimate reaches the provider.
+struct UnreachableProvider(&'static str);
+
+impl LlmProvider for UnreachableProvider {
+    fn model(&self) -> &str {
+        self.0
+    }
+
+    fn context_window(&self) -> usize {
+        32_000
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _api_key: &'a str,
+        _kind: CallKind,
+        _prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        panic!("an estimate must not call the provider")
+    }
+}
+
+const PLANNER_PRICING: ModelPricing = ModelPricing {
+    input_per_token: 0.000001,
+    output_per_token: 0.000002,
+};
+const SYNTHESIS_PRICING: ModelPricing = ModelPricing {
+    input_per_token: 0.00001,
+    output_per_token: 0.00003,
+};
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-estimate-1",
+        "project-default",
+        "Report.pdf",
+        "application/pdf",
+        "checksum-estimate-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let node =
+        |id: &str, parent: Option<&str>, node_type: &str, title: &str, text: &str| SidecarNode {
+            id: id.to_string(),
+            parent_id: parent.map(ToString::to_string),
+            node_type: node_type.to_string(),
+            title: title.to_string(),
+            text: text.to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: id.to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        };
+    let nodes = vec![
+        node("root-estimate", None, "Document", "Report", ""),
+        node(
+            "sec-estimate-1",
+            Some("root-estimate"),
+            "Section",
+            "Revenue",
+            "Revenue grew 12% year over year, driven by subscriptions.",
+        ),
+        node(
+            "sec-estimate-2",
+            Some("root-estimate"),
+            "Section",
+            "Costs",
+            "Operating costs for revenue teams stayed flat.",
+        ),
+    ];
+    documents::insert_nodes(db.pool(), "doc-estimate-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+fn project_scope() -> ScopeDecision {
+    ScopeDecision {
+        scope: QueryScope::Project,
+        focus_document_id: None,
+        focus_dropped: false,
+        forced: false,
+    }
+}
+
+fn options() -> RunOptions {
+    RunOptions {
+        pricing: Some(SYNTHESIS_PRICING),
+        planner_pricing: Some(PLANNER_PRICING),
+        ..RunOptions::default()
+    }
+}
+
+fn executor() -> ReasoningExecutor {
+    ReasoningExecutor::new(UnreachableProvider("synth-model"))
+        .with_planner_provider(std::sync::Arc::new(UnreachableProvider("plan-model")))
+}
+
+#[tokio::test]
+async fn estimate_projects_cost_per_model_without_calling_the_provider() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let estimate = executor()
+        .estimate_cost(
+            &db,
+            "project-default",
+            project_scope(),
+            "How did revenue grow?",
+            &options(),
+        )
+        .await
+        .expect("estimate");
+
+    assert!(estimate.evidence_count > 0);
+    assert!(estimate.evidence_tokens > 0);
+    assert_eq!(estimate.trimmed_evidence, 0);
+    let [planner, synthesis] = estimate.models.as_slice() else {
+        panic!("expected a planner and a synthesis estimate");
+    };
+    assert_eq!(
+        (planner.call_kind.as_str(), planner.model.as_str()),
+        ("planner", "plan-model")
+    );
+    assert_eq!(
+        (synthesis.call_kind.as_str(), synthesis.model.as_str()),
+        ("synthesis", "synth-model")
+    );
+    assert_eq!((planner.calls, synthesis.calls), (4, 1));
+    assert!(planner.max_calls >= planner.calls);
+    assert!(synthesis.max_calls >= synthesis.calls);
+    // The synthesis prompt carries the evidence.
+    assert!(synthesis.prompt_tokens > estimate.evidence_tokens);
+
+    let expected_synthesis = synthesis.prompt_tokens as f64 * SYNTHESIS_PRICING.input_per_token
+        + synthesis.output_tokens as f64 * SYNTHESIS_PRICING.output_per_token;
+    assert!((synthesis.cost_usd - expected_synthesis).abs() < 1e-12);
+    assert!((estimate.cost_usd - (planner.cost_usd + synthesis.cost_usd)).abs() < 1e-12);
+    assert!(estimate.max_cost_usd > estimate.cost_usd);
+    // No calls recorded yet, so no latency to project from.
+    assert_eq!(estimate.latency_ms, None);
+
+    let runs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM reasoning_runs")
+        .fetch_one(db.pool())
+        .await
+        .expect("count runs");
+    assert_eq!(runs, 0, "an estimate must not record a run");
+}
+
+#[tokio::test]
+async fn latency_is_projected_from_recent_answered_calls() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    reasoning::create_run(
+        db.pool(),
+        "run-history",
+        "project-default",
+        None,
+        "Earlier question",
+    )
+    .await
+    .expect("create run");
+    let response = ProviderResponse {
+        text: "{}".to_string(),
+        token_usage: serde_json::json!({}),
+    };
+    for (kind, model, latency_ms, cached) in [
+        (CallKind::Planner, "plan-model", 200, false),
+        (CallKind::Planner, "plan-model", 400, false),
+        // Cache hits say nothing about the provider's speed.
+        (CallKind::Planner, "plan-model", 5, true),
+        (CallKind::Synthesis, "synth-model", 1_000, false),
+    ] {
+        reasoning::record_provider_response(
+            db.pool(),
+            "run-history",
+            kind,
+            "hash",
+            model,
+            &response,
+            CallTiming { latency_ms, cached },
+        )
+        .await
+        .expect("record response");
+    }
+
+    let estimate = executor()
+        .estimate_cost(
+            &db,
+            "project-default",
+            project_scope(),
+            "How did revenue grow?",
+            &options(),
+        )
+        .await
+        .expect("estimate");
+    assert_eq!(estimate.models[0].avg_call_latency_ms, Some(300));
+    assert_eq!(estimate.models[1].avg_call_latency_ms, Some(1_000));
+    assert_eq!(estimate.latency_ms, Some(4 * 300 + 1_000));
+    assert!(estimate.max_latency_ms.expect("max latency") >= 4 * 300 + 1_000);
+}
+
+#[tokio::test]
+async fn output_limit_and_step_budget_shape_the_estimate() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let capped = executor()
+        .estimate_cost(
+            &db,
+            "project-default",
+            project_scope(),
+            "How did revenue grow?",
+            &RunOptions {
+                generation: GenerationSettings {
+                    max_output_tokens: Some(100),
+                    ..GenerationSettings::default()
+                },
+                ..options()
+            },
+        )
+        .await
+        .expect("estimate");
+    assert_eq!(capped.models[1].output_tokens, 100);
+
+    // Too few steps to reach synthesis.
+    let short = executor()
+        .estimate_cost(
+            &db,
+            "project-default",
+            project_scope(),
+            "How did revenue grow?",
+            &RunOptions {
+                max_steps: Some(3),
+                ..options()
+            },
+        )
+        .await
+        .expect("estimate");
+    assert_eq!(short.models[1].calls, 0);
+    assert_eq!(short.models[1].cost_usd, 0.0);
+    assert_eq!(short.models[0].calls, 2);
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::DateRange,
+    db::{repositories::documents, Database},
+    sidecar::{
+        dates,
+        types::{NormalizedPayload, SidecarDocument},
+    },
+};
+
+fn day(text: &str) -> NaiveDate {
+    text.parse().expect("ISO date")
+}
+
+fn range(from: Option<&str>, to: Option<&str>) -> DateRange {
+    DateRange {
+        from: from.map(day),
+        to: to.map(day),
+    }
+}
+
+#[test]
+fn dates_are_normalized_to_their_first_and_last_day() {
+    let found = dates::extract(
+        "Signed 14 March 2023, released 2023-06-01T09:00, reviewed in Q3 2023 and Sept. 2024.",
+    );
+    let read = found
+        .iter()
+        .map(|date| {
+            (
+                date.start.to_string(),
+                date.end.to_string(),
+                date.text.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        read,
+        vec![
+            (
+                "2023-03-14".to_string(),
+                "2023-03-14".to_string(),
+                "14 March 2023"
+            ),
+            (
+                "2023-06-01".to_string(),
+                "2023-06-01".to_string(),
+                "2023-06-01"
+            ),
+            (
+                "2023-07-01".to_string(),
+                "2023-09-30".to_string(),
+                "Q3 2023"
+            ),
+            (
+                "2024-09-01".to_string(),
+                "2024-09-30".to_string(),
+                "Sept. 2024"
+            ),
+        ]
+    );
+    // Amounts and percentages that look like years are left alone.
+    assert!(dates::extract("It cost $2024 and grew 2023% on 1850 units.").is_empty());
+}
+
+#[test]
+fn temporal_phrases_give_a_range() {
+    assert_eq!(
+        dates::range_in("What events happened in 2023?"),
+        Some(range(Some("2023-01-01"), Some("2023-12-31")))
+    );
+    assert_eq!(
+        dates::range_in("contracts signed before March 2021"),
+        Some(range(None, Some("2021-02-28")))
+    );
+    assert_eq!(
+        dates::range_in("changes since May 2021"),
+        Some(range(Some("2021-05-01"), None))
+    );
+    assert_eq!(
+        dates::range_in("revenue between 2019 and 2021"),
+        Some(range(Some("2019-01-01"), Some("2021-12-31")))
+    );
+    assert_eq!(dates::range_in("who signed the contract?"), None);
+}
+
+#[tokio::test]
+async fn nodes_are_found_when_their_dates_overlap_the_range() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let mut payload = NormalizedPayload {
+        document: SidecarDocument {
+            title: "Log".to_string(),
+            pages: 1,
+            metadata: serde_json::json!({}),
+        },
+        nodes: vec![
+            node("root-log", None, "Document", ""),
+            node(
+                "para-2022",
+                Some("root-log"),
+                "Paragraph",
+                "The office opened on 3 October 2022.",
+            ),
+            node(
+                "para-2023",
+                Some("root-log"),
+                "Paragraph",
+                "Headcount doubled during Q2 2023.",
+            ),
+            node(
+                "para-undated",
+                Some("root-log"),
+                "Paragraph",
+                "The office has a garden.",
+            ),
+        ],
+        edges: vec![],
+    };
+    dates::record(&mut payload);
+    assert_eq!(
+        payload.nodes[1].metadata["dates"],
+        serde_json::json!([{
+            "start": "2022-10-03",
+            "end": "2022-10-03",
+            "text": "3 October 2022",
+        }])
+    );
+    seed_document(&db, "project-default", "doc-log", "Log.pdf", &payload.nodes).await;
+
+    let hits = |range: DateRange| {
+        let db = db.clone();
+        async move {
+            documents::search_date_nodes(db.pool(), "project-default", None, &range, 10)
+                .await
+                .expect("search")
+                .into_iter()
+                .map(|node| node.id)
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(
+        hits(dates::range_in("events in 2023").expect("range")).await,
+        vec!["para-2023"]
+    );
+    // A quarter counts when any of it falls in the range.
+    assert_eq!(
+        hits(range(Some("2023-06-30"), None)).await,
+        vec!["para-2023"]
+    );
+    assert_eq!(
+        hits(range(None, Some("2023-12-31"))).await,
+        vec!["para-2022", "para-2023"]
+    );
+    assert!(hits(range(Some("2024-01-01"), None)).await.is_empty());
+}
@@ -0,0 +1,109 @@
+use std::{
+    str::FromStr,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    Connection, SqliteConnection,
+};
+use vectorless_lib::{
+    core::errors::AppError,
+    db::{repositories::projects, retry_busy, Database},
+};
+
+#[tokio::test]
+async fn lock_contention_maps_to_retryable_busy_error() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let options = SqliteConnectOptions::from_str(&format!(
+        "sqlite:{}",
+        dir.path().join("busy.sqlite").to_string_lossy()
+    ))
+    .expect("options")
+    .create_if_missing(true)
+    .journal_mode(SqliteJournalMode::Wal)
+    .busy_timeout(Duration::ZERO);
+
+    let mut holder = SqliteConnection::connect_with(&options)
+        .await
+        .expect("connect");
+    sqlx::query("CREATE TABLE t (id INTEGER)")
+        .execute(&mut holder)
+        .await
+        .expect("create");
+    sqlx::query("BEGIN IMMEDIATE")
+        .execute(&mut holder)
+        .await
+        .expect("lock");
+
+    let mut other = SqliteConnection::connect_with(&options)
+        .await
+        .expect("connect");
+    let err = sqlx::query("INSERT INTO t (id) VALUES (1)")
+        .execute(&mut other)
+        .await
+        .expect_err("write should hit the lock");
+    let err = AppError::from(err);
+    assert!(matches!(err, AppError::DatabaseBusy(_)), "got {err:?}");
+    assert_eq!(err.code(), "DATABASE_BUSY");
+    assert!(err.retryable());
+}
+
+#[tokio::test]
+async fn retry_busy_repeats_only_busy_failures() {
+    let calls = &AtomicU32::new(0);
+    let value = retry_busy(|| async move {
+        if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+            Err(AppError::DatabaseBusy("database is locked".to_string()))
+        } else {
+            Ok(7)
+        }
+    })
+    .await
+    .expect("third attempt succeeds");
+    assert_eq!(value, 7);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let calls = &AtomicU32::new(0);
+    let err = retry_busy(|| async move {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>(AppError::Database("constraint failed".to_string()))
+    })
+    .await
+    .expect_err("other errors are not retried");
+    assert!(matches!(err, AppError::Database(_)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let calls = &AtomicU32::new(0);
+    let err = retry_busy(|| async move {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>(AppError::DatabaseBusy("database is locked".to_string()))
+    })
+    .await
+    .expect_err("persistent contention gives up");
+    assert!(matches!(err, AppError::DatabaseBusy(_)));
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn file_database_reads_see_writes_from_the_writer() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db = Database::new(dir.path()).await.expect("db should open");
+
+    projects::create_project(db.writer(), "project-rw", "Read/write")
+        .await
+        .expect("create project");
+    let listed = projects::list_projects(db.pool())
+        .await
+        .expect("list projects");
+    assert!(listed.iter().any(|project| project.id == "project-rw"));
+
+    let mut readers = Vec::new();
+    for _ in 0..4 {
+        readers.push(db.pool().acquire().await.expect("reader"));
+    }
+    assert!(db.pool().size() >= 4);
+    assert_eq!(db.writer().size(), 1);
+    drop(readers);
+}
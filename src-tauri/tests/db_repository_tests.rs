@@ -56,6 +56,23 @@ async fn document_repository_persists_tree_nodes() {
     assert_eq!(tree.len(), 2);
     assert_eq!(tree[0].id, "root-1");
     assert_eq!(tree[1].id, "sec-1");
+
+    let refs = documents::resolve_node_refs(
+        db.pool(),
+        &[
+            "sec-1".to_string(),
+            "gone".to_string(),
+            "root-1".to_string(),
+        ],
+    )
+    .await
+    .expect("resolve refs");
+    assert_eq!(refs.len(), 2);
+    assert_eq!(refs[0].id, "sec-1");
+    assert_eq!(refs[0].title, "Introduction");
+    assert_eq!(refs[0].document_name, "Spec.pdf");
+    assert_eq!(refs[0].ordinal_path, "1");
+    assert_eq!(refs[1].id, "root-1");
 }
 
 #[tokio::test]
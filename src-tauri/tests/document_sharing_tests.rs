@@ -0,0 +1,143 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::db::{
+    repositories::{documents, projects},
+    Database,
+};
+
+async fn seed(db: &Database) {
+    projects::create_project(db.pool(), "project-research", "Research")
+        .await
+        .expect("create project");
+    projects::create_project(db.pool(), "project-archive", "Archive")
+        .await
+        .expect("create project");
+    seed_document(
+        db,
+        "project-default",
+        "doc-shared-1",
+        "Handbook.md",
+        &[
+            node("root-shared", None, "Document", ""),
+            node(
+                "para-shared",
+                Some("root-shared"),
+                "Paragraph",
+                "Onboarding takes two weeks.",
+            ),
+        ],
+    )
+    .await;
+}
+
+fn ids(docs: &[vectorless_lib::core::types::DocumentSummary]) -> Vec<&str> {
+    docs.iter().map(|doc| doc.id.as_str()).collect()
+}
+
+#[tokio::test]
+async fn linked_documents_are_found_listed_and_searched_in_other_projects() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let found = documents::find_document_anywhere(db.pool(), "checksum-doc-shared-1")
+        .await
+        .expect("lookup")
+        .expect("document exists somewhere");
+    assert_eq!(found.project_id, "project-default");
+    assert!(
+        documents::find_by_checksum(db.pool(), "project-research", "checksum-doc-shared-1")
+            .await
+            .expect("lookup")
+            .is_none()
+    );
+
+    documents::link_document(db.pool(), "project-research", "doc-shared-1")
+        .await
+        .expect("link");
+    documents::link_document(db.pool(), "project-research", "doc-shared-1")
+        .await
+        .expect("relinking is a no-op");
+
+    let listed = documents::list_documents(db.pool(), "project-research", false)
+        .await
+        .expect("list");
+    assert_eq!(ids(&listed), vec!["doc-shared-1"]);
+    assert!(
+        documents::find_by_checksum(db.pool(), "project-research", "checksum-doc-shared-1")
+            .await
+            .expect("lookup")
+            .is_some()
+    );
+    let hits =
+        documents::search_project_nodes(db.pool(), "project-research", None, "onboarding", 5)
+            .await
+            .expect("search");
+    assert_eq!(hits[0].id, "para-shared");
+    let tree = documents::get_project_tree(db.pool(), "project-research", 3)
+        .await
+        .expect("tree");
+    assert_eq!(tree.len(), 2);
+    assert!(
+        documents::list_documents(db.pool(), "project-archive", false)
+            .await
+            .expect("list")
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn removing_from_one_project_keeps_the_document_for_the_others() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    documents::link_document(db.pool(), "project-research", "doc-shared-1")
+        .await
+        .expect("link");
+    documents::link_document(db.pool(), "project-archive", "doc-shared-1")
+        .await
+        .expect("link");
+
+    assert!(
+        documents::remove_document_from_project(db.pool(), "project-archive", "doc-shared-1")
+            .await
+            .expect("unlink")
+    );
+    assert!(
+        documents::list_documents(db.pool(), "project-archive", false)
+            .await
+            .expect("list")
+            .is_empty()
+    );
+
+    assert!(
+        documents::remove_document_from_project(db.pool(), "project-default", "doc-shared-1")
+            .await
+            .expect("owner removal")
+    );
+    let document = documents::get_document(db.pool(), "doc-shared-1")
+        .await
+        .expect("still stored");
+    assert_eq!(document.project_id, "project-research");
+    assert!(
+        documents::list_documents(db.pool(), "project-default", false)
+            .await
+            .expect("list")
+            .is_empty()
+    );
+
+    assert!(
+        documents::remove_document_from_project(db.pool(), "project-research", "doc-shared-1")
+            .await
+            .expect("last removal")
+    );
+    assert!(documents::get_document(db.pool(), "doc-shared-1")
+        .await
+        .is_err());
+    assert!(!documents::remove_document_from_project(
+        db.pool(),
+        "project-research",
+        "doc-shared-1"
+    )
+    .await
+    .expect("nothing left to remove"));
+}
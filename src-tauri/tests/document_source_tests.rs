@@ -0,0 +1,53 @@
+use chrono::Duration;
+use vectorless_lib::{
+    core::paths,
+    db::{repositories::documents, Database},
+};
+
+#[tokio::test]
+async fn the_ingest_source_is_recorded_and_checked_for_changes() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    documents::insert_document(
+        db.pool(),
+        "doc-notes",
+        "project-default",
+        "Notes.md",
+        "text/markdown",
+        "checksum-notes",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let listed = documents::list_documents(db.pool(), "project-default", false)
+        .await
+        .expect("list");
+    assert_eq!(listed[0].source_path, None);
+    assert_eq!(listed[0].last_ingested_at, None);
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("Notes.md");
+    std::fs::write(&path, "# Notes").expect("write");
+    let source_path = paths::to_utf8(&path).expect("utf-8 path");
+    documents::set_ingest_source(db.pool(), "doc-notes", &source_path)
+        .await
+        .expect("set source");
+
+    let document = documents::get_document(db.pool(), "doc-notes")
+        .await
+        .expect("document");
+    assert_eq!(document.source_path.as_deref(), Some(source_path.as_str()));
+    assert!(!document.stale);
+    let ingested_at = document.last_ingested_at.expect("ingest time");
+    assert!(!paths::modified_since(&source_path, ingested_at));
+    assert!(paths::modified_since(
+        &source_path,
+        ingested_at - Duration::hours(1)
+    ));
+
+    // A file that is gone cannot be reingested, so it is not flagged.
+    std::fs::remove_file(&path).expect("remove");
+    assert!(!paths::modified_since(
+        &source_path,
+        ingested_at - Duration::hours(1)
+    ));
+}
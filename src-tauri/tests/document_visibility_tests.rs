@@ -0,0 +1,230 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::errors::AppError,
+    db::{
+        repositories::{activity, documents},
+        Database,
+    },
+    security::document_lock::{self, hash_passphrase, verify_passphrase},
+};
+
+async fn document(db: &Database, id: &str, text: &str) {
+    let root = format!("root-{id}");
+    seed_document(
+        db,
+        "project-default",
+        id,
+        &format!("{id}.md"),
+        &[
+            node(&root, None, "Document", ""),
+            node(&format!("sec-{id}"), Some(&root), "Section", text),
+        ],
+    )
+    .await;
+}
+
+async fn seed(db: &Database) {
+    document(db, "salaries", "Salary bands for the engineering team.").await;
+    document(db, "roadmap", "The engineering roadmap for next year.").await;
+}
+
+fn listed_ids(documents: &[vectorless_lib::core::types::DocumentSummary]) -> Vec<&str> {
+    let mut ids = documents
+        .iter()
+        .map(|document| document.id.as_str())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn hidden_documents_leave_the_default_lists() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    activity::record_document_opened(db.pool(), "project-default", "salaries")
+        .await
+        .expect("record");
+
+    let hidden = documents::set_visibility(db.pool(), "salaries", true, false)
+        .await
+        .expect("hide");
+    assert!(hidden.hidden);
+    assert!(!hidden.locked);
+
+    let default = documents::list_documents(db.pool(), "project-default", false)
+        .await
+        .expect("list");
+    assert_eq!(listed_ids(&default), vec!["roadmap"]);
+    let all = documents::list_documents(db.pool(), "project-default", true)
+        .await
+        .expect("list");
+    assert_eq!(listed_ids(&all), vec!["roadmap", "salaries"]);
+    assert!(activity::recent_documents(db.pool(), None, 10)
+        .await
+        .expect("recent")
+        .is_empty());
+
+    // Hidden is not locked: its content is still retrieved.
+    let hits = documents::search_project_nodes(db.pool(), "project-default", None, "salary", 10)
+        .await
+        .expect("search");
+    assert_eq!(hits.len(), 1);
+
+    assert!(matches!(
+        documents::set_visibility(db.pool(), "missing", true, false).await,
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn locked_documents_stay_out_of_retrieval_until_unlocked() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let locked = documents::set_visibility(db.pool(), "salaries", false, true)
+        .await
+        .expect("lock");
+    assert!(locked.locked);
+    assert!(!locked.unlocked);
+
+    assert!(matches!(
+        documents::ensure_unlocked(db.pool(), "salaries").await,
+        Err(AppError::InvalidInput(message)) if message.contains("locked")
+    ));
+    documents::ensure_unlocked(db.pool(), "roadmap")
+        .await
+        .expect("not locked");
+    for query in ["engineering", "alar"] {
+        let hits = documents::search_project_nodes(db.pool(), "project-default", None, query, 10)
+            .await
+            .expect("search");
+        assert!(
+            hits.iter().all(|hit| hit.document_id == "roadmap"),
+            "{query}"
+        );
+    }
+    assert!(documents::search_all_nodes(db.pool(), "salary", 10, true)
+        .await
+        .expect("search")
+        .is_empty());
+    for depth in [1, 3] {
+        let tree = documents::get_project_tree(db.pool(), "project-default", depth)
+            .await
+            .expect("tree");
+        assert!(tree.iter().all(|node| node.document_id == "roadmap"));
+    }
+
+    let unlocked = documents::unlock_document(db.pool(), "salaries", 60)
+        .await
+        .expect("unlock");
+    assert!(unlocked.unlocked);
+    documents::ensure_unlocked(db.pool(), "salaries")
+        .await
+        .expect("unlocked");
+    let hits = documents::search_project_nodes(db.pool(), "project-default", None, "salary", 10)
+        .await
+        .expect("search");
+    assert_eq!(hits.len(), 1);
+
+    let relocked = documents::relock_document(db.pool(), "salaries")
+        .await
+        .expect("relock");
+    assert!(relocked.locked);
+    assert!(!relocked.unlocked);
+    assert!(
+        documents::search_project_nodes(db.pool(), "project-default", None, "salary", 10)
+            .await
+            .expect("search")
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn taking_the_lock_off_ends_the_unlock() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    documents::set_visibility(db.pool(), "salaries", false, true)
+        .await
+        .expect("lock");
+    documents::unlock_document(db.pool(), "salaries", 60)
+        .await
+        .expect("unlock");
+
+    let open = documents::set_visibility(db.pool(), "salaries", false, false)
+        .await
+        .expect("remove lock");
+    assert!(!open.locked);
+    let locked_again = documents::set_visibility(db.pool(), "salaries", false, true)
+        .await
+        .expect("lock");
+    assert!(!locked_again.unlocked);
+}
+
+#[test]
+fn passphrase_hashes_are_salted_and_verify() {
+    let first = hash_passphrase("correct horse");
+    let second = hash_passphrase("correct horse");
+    assert_ne!(first, second);
+    assert!(!first.contains("correct horse"));
+    assert!(verify_passphrase(&first, "correct horse"));
+    assert!(verify_passphrase(&second, "correct horse"));
+    assert!(!verify_passphrase(&first, "wrong horse"));
+    assert!(!verify_passphrase("not-a-hash", "correct horse"));
+}
+
+#[test]
+fn passphrase_hashes_are_checked_with_their_own_iteration_count() {
+    // PBKDF2-HMAC-SHA256 of "password" salted with "salt", 4096 iterations.
+    let stored = "pbkdf2-sha256$4096$c2FsdA==$xeR41ZKIyEGqUw22hFxMjZYok6ABzk4RpJY4c6qYE0o=";
+    assert!(verify_passphrase(stored, "password"));
+    assert!(!verify_passphrase(
+        &stored.replace("$4096$", "$4095$"),
+        "password"
+    ));
+    assert!(hash_passphrase("password").starts_with("pbkdf2-sha256$600000$"));
+}
+
+#[tokio::test]
+async fn the_passphrase_guards_changes_once_set() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    assert!(!document_lock::passphrase_set(db.pool())
+        .await
+        .expect("status"));
+    document_lock::check_passphrase(db.pool(), None)
+        .await
+        .expect("no passphrase set");
+
+    document_lock::set_passphrase(db.pool(), None, Some("s3cret"))
+        .await
+        .expect("set");
+    assert!(document_lock::passphrase_set(db.pool())
+        .await
+        .expect("status"));
+    document_lock::check_passphrase(db.pool(), Some("s3cret"))
+        .await
+        .expect("matches");
+    for given in [None, Some("guess")] {
+        assert!(matches!(
+            document_lock::check_passphrase(db.pool(), given).await,
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+    assert!(
+        document_lock::set_passphrase(db.pool(), Some("guess"), None)
+            .await
+            .is_err()
+    );
+    assert!(
+        document_lock::set_passphrase(db.pool(), Some("s3cret"), Some("  "))
+            .await
+            .is_err()
+    );
+
+    document_lock::set_passphrase(db.pool(), Some("s3cret"), None)
+        .await
+        .expect("remove");
+    assert!(!document_lock::passphrase_set(db.pool())
+        .await
+        .expect("status"));
+}
@@ -0,0 +1,264 @@
+use std::sync::Arc;
+
+use vectorless_lib::{
+    core::types::ModelPricing,
+    db::{
+        repositories::{documents, reasoning},
+        Database,
+    },
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind,
+    },
+    reasoner::executor::{self, ReasoningExecutor, RunOptions},
+    security::key_pool::ApiKeyPool,
+    sidecar::types::SidecarNode,
+};
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-dual-1",
+        "project-default",
+        "Spec.pdf",
+        "application/pdf",
+        "checksum-dual-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        SidecarNode {
+            id: "root-dual-1".to_string(),
+            parent_id: None,
+            node_type: "Document".to_string(),
+            title: "Spec".to_string(),
+            text: "".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "root".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+        SidecarNode {
+            id: "sec-dual-1".to_string(),
+            parent_id: Some("root-dual-1".to_string()),
+            node_type: "Section".to_string(),
+            title: "Latency".to_string(),
+            text: "Latency dropped to 50ms p99 after the cache rollout.".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "1".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+    ];
+    documents::insert_nodes(db.pool(), "doc-dual-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+fn mock(model: &str, kind: CallKind, text: &str, usage: serde_json::Value) -> MockProvider {
+    MockProvider::new(MockFixture {
+        model: model.to_string(),
+        context_window: None,
+        multimodal: false,
+        responses: vec![MockResponse {
+            kind,
+            request_hash: None,
+            text: text.to_string(),
+            token_usage: usage,
+        }],
+    })
+}
+
+fn executor() -> ReasoningExecutor {
+    answering_with_confidence(0.9)
+}
+
+fn answering_with_confidence(confidence: f64) -> ReasoningExecutor {
+    let answer = serde_json::json!({
+        "answer_markdown": "Latency dropped to 50ms p99 after the cache rollout.",
+        "confidence": confidence,
+        "citations": ["sec-dual-1"],
+    })
+    .to_string();
+    let synthesis = mock(
+        "mock-strong",
+        CallKind::Synthesis,
+        &answer,
+        serde_json::json!({"promptTokenCount": 2000, "candidatesTokenCount": 500}),
+    );
+    let planner = mock(
+        "mock-cheap",
+        CallKind::Planner,
+        "not a plan",
+        serde_json::json!({"promptTokenCount": 1000, "candidatesTokenCount": 100}),
+    );
+    ReasoningExecutor::new(synthesis).with_planner_provider(Arc::new(planner))
+}
+
+fn rates(input: f64, output: f64) -> ModelPricing {
+    ModelPricing {
+        input_per_token: input,
+        output_per_token: output,
+    }
+}
+
+#[tokio::test]
+async fn plan_steps_and_synthesis_use_their_own_models() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let executor = executor();
+    assert_eq!(executor.model(), "mock-strong");
+    assert_eq!(executor.planner_model(), "mock-cheap");
+    executor
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-dual-1"),
+            "run-dual-1".to_string(),
+            "What is the latency?",
+            RunOptions {
+                max_steps: Some(6),
+                api_keys: ApiKeyPool::single("unused"),
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+
+    let recorded = reasoning::list_provider_responses(db.pool(), "run-dual-1")
+        .await
+        .expect("recorded responses");
+    assert!(recorded.iter().any(|entry| entry.call_kind == "planner"));
+    for entry in &recorded {
+        let expected = if entry.call_kind == "planner" {
+            "mock-cheap"
+        } else {
+            "mock-strong"
+        };
+        assert_eq!(entry.model, expected);
+    }
+}
+
+#[tokio::test]
+async fn token_and_cost_accounting_is_split_by_model() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let result = executor()
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-dual-1"),
+            "run-dual-cost".to_string(),
+            "What is the latency?",
+            RunOptions {
+                max_steps: Some(6),
+                api_keys: ApiKeyPool::single("unused"),
+                pricing: Some(rates(0.00001, 0.00003)),
+                planner_pricing: Some(rates(0.000001, 0.000002)),
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+
+    let planner = &result.token_usage["planner"];
+    let synthesis = &result.token_usage["synthesis"];
+    assert_eq!(planner["model"], "mock-cheap");
+    assert_eq!(synthesis["model"], "mock-strong");
+
+    let planner_calls = planner["calls"].as_f64().expect("planner calls");
+    assert!(planner_calls >= 1.0);
+    assert_eq!(
+        planner["promptTokenCount"].as_f64(),
+        Some(1000.0 * planner_calls)
+    );
+    let planner_cost = planner["costUsd"].as_f64().expect("planner cost");
+    assert!((planner_cost - 0.0012 * planner_calls).abs() < 1e-9);
+
+    let synthesis_cost = synthesis["costUsd"].as_f64().expect("synthesis cost");
+    assert!((synthesis_cost - 0.035).abs() < 1e-9);
+    assert!((result.cost_usd - (planner_cost + synthesis_cost)).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn every_synthesis_of_a_backtracking_run_is_accounted() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    // Low confidence keeps the run looking for evidence and answering again.
+    let result = answering_with_confidence(0.2)
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-dual-1"),
+            "run-dual-backtrack".to_string(),
+            "What is the latency?",
+            RunOptions {
+                max_steps: Some(12),
+                api_keys: ApiKeyPool::single("unused"),
+                pricing: Some(rates(0.00001, 0.00003)),
+                planner_pricing: Some(rates(0.000001, 0.000002)),
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+
+    let answered = reasoning::list_provider_responses(db.pool(), "run-dual-backtrack")
+        .await
+        .expect("recorded responses")
+        .iter()
+        .filter(|entry| entry.call_kind == "synthesis")
+        .count() as f64;
+    let synthesis = &result.token_usage["synthesis"];
+    assert_eq!(synthesis["calls"].as_f64(), Some(answered));
+    assert_eq!(
+        synthesis["promptTokenCount"].as_f64(),
+        Some(2000.0 * answered)
+    );
+    let synthesis_cost = synthesis["costUsd"].as_f64().expect("synthesis cost");
+    assert!((synthesis_cost - 0.035 * answered).abs() < 1e-9);
+    let planner_cost = result.token_usage["planner"]["costUsd"]
+        .as_f64()
+        .expect("planner cost");
+    assert!((result.cost_usd - (planner_cost + synthesis_cost)).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn replay_keeps_planner_and_synthesis_models_apart() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    executor()
+        .run(
+            &db,
+            "project-default",
+            Some("doc-dual-1"),
+            "run-dual-source".to_string(),
+            "What is the latency?",
+            Some(6),
+            "unused",
+            |_| {},
+        )
+        .await
+        .expect("source run should complete");
+
+    let result = executor::replay_run(
+        &db,
+        "run-dual-source",
+        "run-dual-replay".to_string(),
+        |_| {},
+    )
+    .await
+    .expect("replay should complete");
+    assert_eq!(result.token_usage["planner"]["model"], "mock-cheap");
+    assert_eq!(result.token_usage["synthesis"]["model"], "mock-strong");
+}
@@ -0,0 +1,166 @@
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+use vectorless_lib::sidecar::{
+    mime::{self, Format},
+    native_parser,
+    types::{NormalizedPayload, SidecarNode},
+};
+use zip::{write::FileOptions, CompressionMethod};
+
+const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+
+const PACKAGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="uid">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="uid">urn:uuid:cache-book</dc:identifier>
+    <dc:title>The Cache Book</dc:title>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="style" href="style.css" media-type="text/css"/>
+    <item id="ch1" href="Text/chapter%201.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch2" href="Text/ch2.xhtml#start" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="nav"/>
+    <itemref idref="ch1"/>
+    <itemref idref="ch2"/>
+  </spine>
+</package>"#;
+
+const NAV: &str = r#"<html xmlns="http://www.w3.org/1999/xhtml"><body>
+<nav><ol><li><a href="Text/chapter%201.xhtml">Table of contents entry</a></li></ol></nav>
+</body></html>"#;
+
+const CHAPTER_ONE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><head><title>The Cache Book</title></head>
+<body>
+  <h1>Warm-up</h1>
+  <p>Caches keep hot data close.</p>
+  <h2>Eviction</h2>
+  <p>LRU evicts the least recently used entry.</p>
+</body></html>"#;
+
+const CHAPTER_TWO: &str = r#"<html xmlns="http://www.w3.org/1999/xhtml"><head><title>Interlude</title></head>
+<body><p>This chapter has no headings.</p><h3>Aside</h3><p>Nor nests under chapter one.</p></body></html>"#;
+
+fn epub(entries: &[(&str, &str)]) -> NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(".epub")
+        .tempfile()
+        .expect("temp file");
+    let mut zip = zip::ZipWriter::new(file.as_file_mut());
+    let stored: FileOptions<'_, ()> =
+        FileOptions::default().compression_method(CompressionMethod::Stored);
+    zip.start_file("mimetype", stored).expect("start file");
+    zip.write_all(b"application/epub+zip").expect("write");
+    let options: FileOptions<'_, ()> = FileOptions::default();
+    for (name, contents) in entries {
+        zip.start_file(*name, options).expect("start file");
+        zip.write_all(contents.as_bytes()).expect("write");
+    }
+    zip.finish().expect("finish zip");
+    file
+}
+
+fn book() -> NamedTempFile {
+    epub(&[
+        ("META-INF/container.xml", CONTAINER),
+        ("OEBPS/content.opf", PACKAGE),
+        ("OEBPS/nav.xhtml", NAV),
+        ("OEBPS/style.css", "p { margin: 0; }"),
+        ("OEBPS/Text/chapter 1.xhtml", CHAPTER_ONE),
+        ("OEBPS/Text/ch2.xhtml", CHAPTER_TWO),
+    ])
+}
+
+fn node<'a>(payload: &'a NormalizedPayload, title: &str) -> &'a SidecarNode {
+    payload
+        .nodes
+        .iter()
+        .find(|node| node.title == title)
+        .unwrap_or_else(|| panic!("no node titled {title}"))
+}
+
+fn children<'a>(payload: &'a NormalizedPayload, parent: &SidecarNode) -> Vec<&'a SidecarNode> {
+    payload
+        .nodes
+        .iter()
+        .filter(|node| node.parent_id.as_deref() == Some(parent.id.as_str()))
+        .collect()
+}
+
+#[test]
+fn chapters_are_read_in_spine_order() {
+    let file = book();
+    let resolved = mime::resolve(file.path(), mime::from_extension(file.path())).expect("resolve");
+    assert_eq!(resolved.format, Format::Epub);
+
+    let payload = native_parser::parse(file.path(), "application/epub+zip").expect("parses");
+    assert_eq!(payload.document.title, "The Cache Book");
+    assert_eq!(payload.document.pages, 2);
+
+    let root = &payload.nodes[0];
+    assert_eq!(
+        children(&payload, root)
+            .iter()
+            .map(|node| (node.node_type.as_str(), node.title.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Section", "Warm-up"),
+            ("Section", "Interlude"),
+            ("Section", "Aside")
+        ]
+    );
+
+    let warm_up = node(&payload, "Warm-up");
+    assert_eq!(warm_up.metadata["chapter"], 1);
+    assert_eq!(warm_up.metadata["href"], "OEBPS/Text/chapter 1.xhtml");
+    let eviction = node(&payload, "Eviction");
+    assert_eq!(eviction.node_type, "Subsection");
+    assert_eq!(eviction.parent_id.as_deref(), Some(warm_up.id.as_str()));
+    let paragraphs = children(&payload, eviction);
+    assert_eq!(paragraphs[0].node_type, "Paragraph");
+    assert_eq!(
+        paragraphs[0].text,
+        "LRU evicts the least recently used entry."
+    );
+
+    // A chapter's text before any heading is titled after the chapter.
+    let interlude = node(&payload, "Interlude");
+    assert_eq!(interlude.metadata["chapter"], 2);
+    assert_eq!(
+        children(&payload, interlude)[0].text,
+        "This chapter has no headings."
+    );
+
+    // The navigation document is not content.
+    assert!(payload
+        .nodes
+        .iter()
+        .all(|node| !node.text.contains("Table of contents entry")));
+}
+
+#[test]
+fn books_without_chapter_text_are_refused() {
+    let file = epub(&[
+        ("META-INF/container.xml", CONTAINER),
+        ("OEBPS/content.opf", PACKAGE),
+        ("OEBPS/nav.xhtml", NAV),
+        (
+            "OEBPS/Text/chapter 1.xhtml",
+            "<html><body><h1>Cover</h1></body></html>",
+        ),
+        ("OEBPS/Text/ch2.xhtml", "<html><body></body></html>"),
+    ]);
+    assert!(native_parser::parse(file.path(), "application/epub+zip").is_err());
+
+    let missing = epub(&[("META-INF/container.xml", CONTAINER)]);
+    assert!(native_parser::parse(missing.path(), "application/epub+zip").is_err());
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use vectorless_lib::{
+    core::types::{DocNodeSummary, NodeType},
+    reasoner::feedback::EvidenceFeedback,
+};
+
+fn node(id: &str, document_id: &str, parent_id: Option<&str>) -> DocNodeSummary {
+    DocNodeSummary {
+        id: id.to_string(),
+        document_id: document_id.to_string(),
+        parent_id: parent_id.map(ToString::to_string),
+        node_type: NodeType::Paragraph,
+        title: id.to_string(),
+        text: format!("text of {id}"),
+        ordinal_path: "1".to_string(),
+        page_start: None,
+        page_end: None,
+    }
+}
+
+fn ids(nodes: &[DocNodeSummary]) -> Vec<&str> {
+    nodes.iter().map(|node| node.id.as_str()).collect()
+}
+
+fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn cited_evidence_lifts_neighbours_and_sinks_idle_documents() {
+    let evidence = vec!["p-1".to_string(), "p-2".to_string(), "q-1".to_string()];
+    let cited = vec!["p-1".to_string()];
+    let feedback = EvidenceFeedback::score(
+        &evidence,
+        &cited,
+        &map(&[("p-1", "doc-a"), ("p-2", "doc-a"), ("q-1", "doc-b")]),
+        &map(&[("p-1", "sec-a"), ("p-2", "sec-a"), ("q-1", "sec-b")]),
+    );
+    assert_eq!(feedback.useful.len(), 1);
+    assert_eq!(feedback.useful[0].branch_id, "sec-a");
+    assert!(feedback.idle_documents.contains("doc-b"));
+    assert!(!feedback.idle_documents.contains("doc-a"));
+
+    let ranked = vec![
+        node("q-2", "doc-b", Some("sec-b")),
+        node("c-1", "doc-c", Some("sec-c")),
+        node("p-3", "doc-a", Some("sec-a")),
+        node("c-2", "doc-c", None),
+        node("p-1a", "doc-a", Some("p-1")),
+    ];
+    assert_eq!(
+        ids(&feedback.rerank(ranked)),
+        vec!["p-3", "p-1a", "c-1", "c-2", "q-2"]
+    );
+}
+
+#[test]
+fn exhausted_branches_are_dropped_unless_nothing_else_is_left() {
+    let feedback = EvidenceFeedback {
+        exhausted: ["sec-a".to_string(), "p-9".to_string()].into(),
+        ..EvidenceFeedback::default()
+    };
+
+    let ranked = vec![
+        node("p-1", "doc-a", Some("sec-a")),
+        node("p-9", "doc-a", None),
+        node("p-4", "doc-a", Some("sec-b")),
+    ];
+    assert_eq!(ids(&feedback.rerank(ranked)), vec!["p-4"]);
+
+    let only_exhausted = vec![
+        node("p-1", "doc-a", Some("sec-a")),
+        node("p-9", "doc-a", None),
+    ];
+    assert_eq!(ids(&feedback.rerank(only_exhausted)), vec!["p-1", "p-9"]);
+
+    let ranked = vec![node("x", "doc-a", None), node("y", "doc-b", None)];
+    assert_eq!(
+        ids(&EvidenceFeedback::default().rerank(ranked)),
+        vec!["x", "y"]
+    );
+}
@@ -0,0 +1,78 @@
+use vectorless_lib::core::{
+    errors::AppError,
+    exports::{self, ExportRegistry},
+};
+
+#[test]
+fn only_files_in_export_folders_can_be_revealed() {
+    let data_dir = tempfile::tempdir().expect("tempdir");
+    let exports_dir = data_dir.path().join("exports");
+    let registry = ExportRegistry::new(&exports_dir);
+
+    // The default folder is allowed before it exists.
+    std::fs::create_dir_all(&exports_dir).expect("exports dir");
+    let report = exports_dir.join("Report.md");
+    std::fs::write(&report, "# Report").expect("write");
+    let revealed = registry
+        .check(&report.to_string_lossy())
+        .expect("inside the exports folder");
+    assert!(revealed.ends_with("Report.md"));
+
+    let secret = data_dir.path().join("vectorless.db");
+    std::fs::write(&secret, "data").expect("write");
+    assert!(matches!(
+        registry.check(&secret.to_string_lossy()),
+        Err(AppError::InvalidInput(_))
+    ));
+    let escape = exports_dir.join("..").join("vectorless.db");
+    assert!(matches!(
+        registry.check(&escape.to_string_lossy()),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        registry.check(&exports_dir.join("missing.md").to_string_lossy()),
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[test]
+fn exports_elsewhere_are_allowed_but_not_their_folder() {
+    let chosen = tempfile::tempdir().expect("tempdir");
+    let registry = ExportRegistry::new(&chosen.path().join("unused"));
+    let file = chosen.path().join("Notes.md");
+    std::fs::write(&file, "# Notes").expect("write");
+    let sibling = chosen.path().join("Private.md");
+    std::fs::write(&sibling, "private").expect("write");
+    assert!(registry.check(&file.to_string_lossy()).is_err());
+
+    registry.record(&file);
+    assert!(registry.check(&file.to_string_lossy()).is_ok());
+    assert!(matches!(
+        registry.check(&chosen.path().to_string_lossy()),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        registry.check(&sibling.to_string_lossy()),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn a_chosen_file_that_exists_is_kept_unless_overwrite_is_set() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let chosen = dir.path().join("Notes.md");
+    let dest = chosen.to_string_lossy();
+    let default_dir = dir.path().join("exports");
+
+    let fresh =
+        exports::destination(Some(&dest), &default_dir, "Notes", "md", false).expect("destination");
+    assert_eq!(fresh, chosen);
+
+    std::fs::write(&chosen, "# Notes").expect("write");
+    let numbered =
+        exports::destination(Some(&dest), &default_dir, "Notes", "md", false).expect("destination");
+    assert_eq!(numbered, dir.path().join("Notes (2).md"));
+    let replaced =
+        exports::destination(Some(&dest), &default_dir, "Notes", "md", true).expect("destination");
+    assert_eq!(replaced, chosen);
+}
@@ -0,0 +1,126 @@
+use vectorless_lib::{
+    core::types::ExternalParserConfig,
+    sidecar::docling_client::{self, validate_config},
+};
+
+#[cfg(unix)]
+fn shell(script: &str, timeout_secs: u64) -> ExternalParserConfig {
+    ExternalParserConfig {
+        enabled: true,
+        command: "sh".to_string(),
+        args: vec!["-c".to_string(), script.to_string()],
+        timeout_secs,
+        mime_types: vec!["application/pdf".to_string()],
+    }
+}
+
+#[test]
+fn mime_routing_matches_exact_types_and_families() {
+    let config = ExternalParserConfig {
+        enabled: true,
+        command: "docling-sidecar".to_string(),
+        mime_types: vec!["application/pdf".to_string(), "image/*".to_string()],
+        ..ExternalParserConfig::default()
+    };
+
+    assert!(docling_client::handles(&config, "application/pdf"));
+    assert!(docling_client::handles(&config, "IMAGE/PNG"));
+    assert!(!docling_client::handles(&config, "imagex/png"));
+    assert!(!docling_client::handles(&config, "text/plain"));
+    assert!(!docling_client::handles(
+        &ExternalParserConfig {
+            enabled: false,
+            ..config
+        },
+        "application/pdf"
+    ));
+}
+
+#[test]
+fn config_validation_requires_a_command_when_enabled() {
+    let err = validate_config(ExternalParserConfig {
+        enabled: true,
+        ..ExternalParserConfig::default()
+    })
+    .expect_err("no command");
+    assert!(err.to_string().contains("command"));
+
+    let err = validate_config(ExternalParserConfig {
+        timeout_secs: 0,
+        ..ExternalParserConfig::default()
+    })
+    .expect_err("zero timeout");
+    assert!(err.to_string().contains("timeout"));
+
+    let config = validate_config(ExternalParserConfig {
+        enabled: true,
+        command: "  /opt/docling/bin/sidecar ".to_string(),
+        mime_types: vec![" Application/PDF ".to_string(), String::new()],
+        ..ExternalParserConfig::default()
+    })
+    .expect("valid");
+    assert_eq!(config.command, "/opt/docling/bin/sidecar");
+    assert_eq!(config.mime_types, vec!["application/pdf"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn external_parser_output_is_normalized() {
+    let config = shell(
+        r#"read -r request
+case "$request" in
+  *'"mime_type":"application/pdf"'*) ;;
+  *) echo "unexpected request: $request" >&2; exit 3 ;;
+esac
+printf '%s' '{"document":{"title":"Spec","pages":2,"metadata":{}},"nodes":[
+  {"id":"p1","parent_id":"s1","type":"Paragraph","text":"Latency is 4ms.","ordinal_path":"1.1"},
+  {"id":"s1","type":"Section","title":"Performance","ordinal_path":"1"}]}'"#,
+        10,
+    );
+
+    let payload = docling_client::parse(
+        &config,
+        std::path::Path::new("/tmp/spec.pdf"),
+        "application/pdf",
+    )
+    .expect("parses");
+
+    let ordinals = payload
+        .nodes
+        .iter()
+        .map(|node| node.ordinal_path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ordinals, vec!["root", "1", "1.1"]);
+    assert_eq!(
+        payload.nodes[1].parent_id.as_ref(),
+        Some(&payload.nodes[0].id)
+    );
+    assert_eq!(payload.edges.len(), 2);
+    assert_eq!(payload.document.metadata["parser"], "sidecar");
+}
+
+#[cfg(unix)]
+#[test]
+fn external_parser_failures_are_reported() {
+    let path = std::path::Path::new("/tmp/spec.pdf");
+
+    let err = docling_client::parse(&shell("sleep 5", 1), path, "application/pdf")
+        .expect_err("times out");
+    assert!(err.to_string().contains("timed out"), "{err}");
+
+    let err = docling_client::parse(
+        &shell(r#"printf '{"error":"encrypted PDF"}'"#, 10),
+        path,
+        "application/pdf",
+    )
+    .expect_err("reports error");
+    assert!(err.to_string().contains("encrypted PDF"), "{err}");
+
+    let err = docling_client::parse(&shell("echo boom >&2; exit 2", 10), path, "application/pdf")
+        .expect_err("non-zero exit");
+    assert!(err.to_string().contains("boom"), "{err}");
+
+    let err = docling_client::parse(&shell("echo not json", 10), path, "application/pdf")
+        .expect_err("invalid json");
+    assert!(err.to_string().contains("invalid JSON"), "{err}");
+}
@@ -0,0 +1,227 @@
+use vectorless_lib::{
+    db::{
+        repositories::{assets, documents, reasoning},
+        Database,
+    },
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind, ImagePart,
+    },
+    reasoner::executor::{self, ReasoningExecutor, RunOptions},
+    security::key_pool::ApiKeyPool,
+    sidecar::{figures::resolve_figure_image, types::SidecarNode},
+};
+
+const CHART_PNG_BASE64: &str = "iVBORw0KGgpjaGFydC1ieXRlcw==";
+
+fn chart_image() -> ImagePart {
+    ImagePart {
+        mime: "image/png".to_string(),
+        data: b"\x89PNG\r\n\x1a\nchart-bytes".to_vec(),
+    }
+}
+
+fn node(id: &str, parent: Option<&str>, node_type: &str, title: &str, text: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: title.to_string(),
+        text: text.to_string(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-figure-1",
+        "project-default",
+        "Report.md",
+        "text/markdown",
+        "checksum-figure-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        node("root-figure-1", None, "Document", "Report", ""),
+        node(
+            "sec-figure-1",
+            Some("root-figure-1"),
+            "Section",
+            "Latency",
+            "The latency chart shows p99 latency dropping to 50ms after the cache rollout.",
+        ),
+        node(
+            "fig-figure-1",
+            Some("sec-figure-1"),
+            "Figure",
+            "Latency chart",
+            &format!("![Latency chart](data:image/png;base64,{CHART_PNG_BASE64})"),
+        ),
+    ];
+    documents::insert_nodes(db.pool(), "doc-figure-1", &nodes)
+        .await
+        .expect("insert nodes");
+    assets::put_node_image(db, "fig-figure-1", &chart_image())
+        .await
+        .expect("store figure image");
+}
+
+fn provider(multimodal: bool) -> MockProvider {
+    let answer = serde_json::json!({
+        "answer_markdown": "The latency chart shows p99 latency dropping to 50ms after the cache rollout.",
+        "confidence": 0.9,
+        "citations": ["sec-figure-1", "fig-figure-1"],
+    })
+    .to_string();
+    MockProvider::new(MockFixture {
+        model: "mock-vision".to_string(),
+        context_window: None,
+        multimodal,
+        responses: vec![
+            MockResponse {
+                kind: CallKind::Planner,
+                request_hash: None,
+                text: "not a plan".to_string(),
+                token_usage: serde_json::json!({}),
+            },
+            MockResponse {
+                kind: CallKind::Synthesis,
+                request_hash: None,
+                text: answer,
+                token_usage: serde_json::json!({}),
+            },
+        ],
+    })
+}
+
+async fn run(db: &Database, provider: MockProvider, run_id: &str, attach: bool) {
+    ReasoningExecutor::new(provider)
+        .run_with_options(
+            db,
+            "project-default",
+            Some("doc-figure-1"),
+            run_id.to_string(),
+            "What does the latency chart show?",
+            RunOptions {
+                max_steps: Some(6),
+                api_keys: ApiKeyPool::single("unused"),
+                attach_figure_images: attach,
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+}
+
+fn synthesis_observation(steps: &[vectorless_lib::core::types::ReasoningStep]) -> String {
+    steps
+        .iter()
+        .find(|step| step.step_type == "synthesize")
+        .map(|step| step.observation.clone())
+        .expect("synthesis step")
+}
+
+#[test]
+fn resolves_inline_and_relative_figure_images() {
+    let inline = resolve_figure_image(
+        &format!("![Chart](data:image/png;base64,{CHART_PNG_BASE64})"),
+        None,
+    )
+    .expect("data uri should decode");
+    assert_eq!(inline, chart_image());
+
+    let dir = tempfile::tempdir().expect("temp dir");
+    std::fs::create_dir_all(dir.path().join("img")).expect("img dir");
+    std::fs::write(dir.path().join("img/chart.jpg"), b"jpeg-bytes").expect("write image");
+    let relative = resolve_figure_image("![Chart](./img/chart.jpg)", Some(dir.path()))
+        .expect("relative path should resolve");
+    assert_eq!(relative.mime, "image/jpeg");
+    assert_eq!(relative.data, b"jpeg-bytes");
+    let html = resolve_figure_image(r#"<img src="img/chart.jpg" alt="Chart">"#, Some(dir.path()))
+        .expect("img tag should resolve");
+    assert_eq!(html.data, b"jpeg-bytes");
+
+    assert!(
+        resolve_figure_image("![Chart](https://example.com/chart.png)", Some(dir.path())).is_none()
+    );
+    assert!(resolve_figure_image("![Chart](img/chart.svg)", Some(dir.path())).is_none());
+    assert!(resolve_figure_image("![Chart](img/missing.png)", Some(dir.path())).is_none());
+}
+
+#[test]
+fn figure_paths_cannot_leave_the_document_folder() {
+    let root = tempfile::tempdir().expect("temp dir");
+    let docs = root.path().join("docs");
+    std::fs::create_dir_all(&docs).expect("docs dir");
+    let private = root.path().join("passport.jpg");
+    std::fs::write(&private, b"private").expect("write image");
+
+    assert!(resolve_figure_image("![x](../passport.jpg)", Some(&docs)).is_none());
+    assert!(resolve_figure_image(r#"<img src="./../passport.jpg">"#, Some(&docs)).is_none());
+    let absolute = format!("![x]({})", private.display());
+    assert!(resolve_figure_image(&absolute, Some(&docs)).is_none());
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&private, docs.join("link.jpg")).expect("symlink");
+        assert!(resolve_figure_image("![x](link.jpg)", Some(&docs)).is_none());
+    }
+}
+
+#[tokio::test]
+async fn figure_images_are_attached_to_multimodal_synthesis() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let provider = provider(true);
+    run(&db, provider.clone(), "run-figure-1", true).await;
+
+    assert_eq!(provider.received_images(), vec![chart_image()]);
+    let stored = reasoning::get_run(db.pool(), "run-figure-1")
+        .await
+        .expect("run");
+    assert!(stored.run.attach_figure_images);
+    assert!(synthesis_observation(&stored.steps).contains("attached 1 figure image(s)"));
+}
+
+#[tokio::test]
+async fn figure_images_stay_off_unless_requested_and_supported() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let not_requested = provider(true);
+    run(&db, not_requested.clone(), "run-figure-off", false).await;
+    assert!(not_requested.received_images().is_empty());
+
+    let text_only = provider(false);
+    run(&db, text_only.clone(), "run-figure-text", true).await;
+    assert!(text_only.received_images().is_empty());
+    let stored = reasoning::get_run(db.pool(), "run-figure-text")
+        .await
+        .expect("run");
+    assert!(!synthesis_observation(&stored.steps).contains("figure image"));
+}
+
+#[tokio::test]
+async fn replay_reproduces_a_run_with_figure_images() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    run(&db, provider(true), "run-figure-source", true).await;
+
+    executor::replay_run(
+        &db,
+        "run-figure-source",
+        "run-figure-replay".to_string(),
+        |_| {},
+    )
+    .await
+    .expect("replay should match the recorded synthesis request");
+}
@@ -0,0 +1,125 @@
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+use vectorless_lib::{
+    core::errors::AppError,
+    sidecar::{
+        native_parser::{self, ParseLimits},
+        types::NormalizedPayload,
+    },
+};
+
+fn temp_file(suffix: &str, contents: &[u8]) -> NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(suffix)
+        .tempfile()
+        .expect("temp file");
+    file.write_all(contents).expect("write file");
+    file
+}
+
+fn streamed() -> ParseLimits {
+    ParseLimits {
+        stream_text_above_bytes: 0,
+        ..ParseLimits::default()
+    }
+}
+
+fn sections_and_paragraphs(payload: &NormalizedPayload) -> Vec<(String, String)> {
+    payload
+        .nodes
+        .iter()
+        .filter(|node| node.node_type != "Document")
+        .map(|node| (node.node_type.clone(), node.text.clone()))
+        .collect()
+}
+
+#[test]
+fn streamed_text_matches_whole_file_parse_for_ordinary_documents() {
+    let markdown = "# Onboarding\n\nNew hires shadow a mentor for two weeks.\n\n# Benefits\n\nHealth cover starts on day one.\nDental follows after probation.\n";
+    let file = temp_file(".md", markdown.as_bytes());
+
+    let whole = native_parser::parse(file.path(), "text/markdown").expect("whole parse");
+    let streamed = native_parser::parse_with_limits(file.path(), "text/markdown", streamed())
+        .expect("streamed");
+
+    assert_eq!(
+        sections_and_paragraphs(&whole),
+        sections_and_paragraphs(&streamed)
+    );
+}
+
+#[test]
+fn streamed_text_splits_blank_line_free_logs_into_bounded_chunks() {
+    let mut log = Vec::new();
+    for i in 0..2_000 {
+        writeln!(
+            log,
+            "2024-05-01T10:00:{:02}Z worker {i} finished batch",
+            i % 60
+        )
+        .expect("write");
+    }
+    log.extend_from_slice(b"trailing \xff\xfe bytes\n");
+    let file = temp_file(".log", &log);
+
+    let payload =
+        native_parser::parse_with_limits(file.path(), "text/plain", streamed()).expect("streamed");
+    let paragraphs = payload
+        .nodes
+        .iter()
+        .filter(|node| node.node_type == "Paragraph")
+        .collect::<Vec<_>>();
+
+    assert!(
+        paragraphs.len() > 100,
+        "log should be chunked, got {}",
+        paragraphs.len()
+    );
+    assert!(paragraphs.iter().all(|node| node.text.len() < 1_200));
+    assert!(paragraphs
+        .last()
+        .expect("last paragraph")
+        .text
+        .contains("trailing"));
+}
+
+#[test]
+fn files_over_the_configured_limit_are_refused_before_reading() {
+    let file = temp_file(".txt", &[b'a'; 2_048]);
+
+    let err = native_parser::parse_with_limits(
+        file.path(),
+        "text/plain",
+        ParseLimits::default().with_max_file_bytes(Some(1_024)),
+    )
+    .expect_err("over the project limit");
+    assert!(matches!(
+        err,
+        AppError::FileTooLarge {
+            size_bytes: 2_048,
+            limit_bytes: 1_024
+        }
+    ));
+    assert_eq!(err.code(), "FILE_TOO_LARGE");
+    assert!(native_parser::check_file_size(file.path(), Some(4_096)).is_ok());
+}
+
+#[test]
+fn whole_file_formats_are_refused_above_the_in_memory_cap() {
+    let file = temp_file(".pdf", &[0u8; 2_048]);
+    let limits = ParseLimits {
+        max_in_memory_bytes: 1_024,
+        ..ParseLimits::default()
+    };
+
+    let err = native_parser::parse_with_limits(file.path(), "application/pdf", limits)
+        .expect_err("too big to read whole");
+    assert!(matches!(
+        err,
+        AppError::FileTooLarge {
+            limit_bytes: 1_024,
+            ..
+        }
+    ));
+}
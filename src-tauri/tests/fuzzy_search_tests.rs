@@ -0,0 +1,77 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::db::{repositories::documents, Database};
+
+async fn seed(db: &Database) {
+    let nodes = vec![
+        node("root-msa", None, "Document", ""),
+        node(
+            "para-cap",
+            Some("root-msa"),
+            "Paragraph",
+            "The supplier's liability is capped at the fees paid.",
+        ),
+        node(
+            "para-term",
+            Some("root-msa"),
+            "Paragraph",
+            "Either party may terminate with thirty days notice.",
+        ),
+    ];
+    seed_document(db, "project-default", "doc-msa", "MSA.pdf", &nodes).await;
+}
+
+async fn hits(db: &Database, query: &str) -> Vec<String> {
+    documents::search_project_nodes(db.pool(), "project-default", None, query, 10)
+        .await
+        .expect("search")
+        .into_iter()
+        .map(|node| node.id)
+        .collect()
+}
+
+#[tokio::test]
+async fn misspelled_terms_find_the_indexed_word_when_fuzziness_allows() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let exact = documents::expand_fuzzy_terms(db.pool(), "liabiltiy", 0)
+        .await
+        .expect("expand");
+    assert_eq!(exact, "liabiltiy");
+    assert!(hits(&db, &exact).await.is_empty());
+
+    // Swapped letters count as one edit.
+    let expanded = documents::expand_fuzzy_terms(db.pool(), "liabiltiy", 1)
+        .await
+        .expect("expand");
+    assert_eq!(expanded, "liabiltiy liability");
+    assert_eq!(hits(&db, &expanded).await, vec!["para-cap"]);
+
+    let two_edits = documents::expand_fuzzy_terms(db.pool(), "termnate notce", 2)
+        .await
+        .expect("expand");
+    assert_eq!(hits(&db, &two_edits).await, vec!["para-term"]);
+}
+
+#[tokio::test]
+async fn known_and_short_terms_are_left_alone() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    // "liab" already finds "liability" as a prefix; "fes" is too short to guess.
+    assert_eq!(
+        documents::expand_fuzzy_terms(db.pool(), "liab fes", 2)
+            .await
+            .expect("expand"),
+        "liab fes"
+    );
+    // Nothing in the index is within reach of this one.
+    assert_eq!(
+        documents::expand_fuzzy_terms(db.pool(), "xylophone", 2)
+            .await
+            .expect("expand"),
+        "xylophone"
+    );
+}
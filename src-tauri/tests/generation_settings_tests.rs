@@ -0,0 +1,123 @@
+use vectorless_lib::{
+    core::{
+        errors::AppError,
+        types::{GenerationSettings, SafetySetting},
+    },
+    db::{repositories::projects, Database},
+    providers::{
+        generation::{gemini_generation_config, merge_settings, validate_settings},
+        CallKind,
+    },
+};
+
+fn safety(category: &str, threshold: &str) -> SafetySetting {
+    SafetySetting {
+        category: category.to_string(),
+        threshold: threshold.to_string(),
+    }
+}
+
+#[test]
+fn validate_rejects_out_of_range_and_unknown_values() {
+    assert!(validate_settings(&GenerationSettings::default()).is_ok());
+    let hot = GenerationSettings {
+        temperature: Some(2.5),
+        ..GenerationSettings::default()
+    };
+    assert!(matches!(
+        validate_settings(&hot),
+        Err(AppError::InvalidInput(_))
+    ));
+    let unknown = GenerationSettings {
+        safety_settings: vec![safety("HARM_CATEGORY_HARASSMENT", "BLOCK_EVERYTHING")],
+        ..GenerationSettings::default()
+    };
+    assert!(matches!(
+        validate_settings(&unknown),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn run_override_layers_over_project_settings() {
+    let project = GenerationSettings {
+        temperature: Some(0.4),
+        top_p: Some(0.9),
+        max_output_tokens: None,
+        safety_settings: vec![
+            safety("HARM_CATEGORY_HARASSMENT", "BLOCK_ONLY_HIGH"),
+            safety("HARM_CATEGORY_HATE_SPEECH", "BLOCK_ONLY_HIGH"),
+        ],
+    };
+    let overrides = GenerationSettings {
+        temperature: Some(0.0),
+        max_output_tokens: Some(1024),
+        safety_settings: vec![safety("HARM_CATEGORY_HATE_SPEECH", "BLOCK_NONE")],
+        ..GenerationSettings::default()
+    };
+
+    let merged = merge_settings(&project, &overrides);
+    assert_eq!(merged.temperature, Some(0.0));
+    assert_eq!(merged.top_p, Some(0.9));
+    assert_eq!(merged.max_output_tokens, Some(1024));
+    assert_eq!(
+        merged.safety_settings,
+        vec![
+            safety("HARM_CATEGORY_HARASSMENT", "BLOCK_ONLY_HIGH"),
+            safety("HARM_CATEGORY_HATE_SPEECH", "BLOCK_NONE"),
+        ]
+    );
+}
+
+#[test]
+fn gemini_config_uses_per_call_defaults() {
+    let defaults = GenerationSettings::default();
+    assert_eq!(
+        gemini_generation_config(&defaults, CallKind::Planner)["temperature"],
+        0.1
+    );
+    assert_eq!(
+        gemini_generation_config(&defaults, CallKind::Synthesis)["temperature"],
+        0.2
+    );
+    assert!(gemini_generation_config(&defaults, CallKind::Synthesis)
+        .get("topP")
+        .is_none());
+
+    let tuned = GenerationSettings {
+        temperature: Some(0.7),
+        top_p: Some(0.5),
+        max_output_tokens: Some(256),
+        ..GenerationSettings::default()
+    };
+    let config = gemini_generation_config(&tuned, CallKind::Planner);
+    assert_eq!(config["temperature"], 0.7);
+    assert_eq!(config["topP"], 0.5);
+    assert_eq!(config["maxOutputTokens"], 256);
+    assert_eq!(config["responseMimeType"], "application/json");
+}
+
+#[tokio::test]
+async fn project_generation_settings_round_trip() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let initial = projects::get_generation_settings(db.pool(), "project-default")
+        .await
+        .expect("default settings");
+    assert_eq!(initial, GenerationSettings::default());
+
+    let settings = GenerationSettings {
+        temperature: Some(0.3),
+        safety_settings: vec![safety(
+            "HARM_CATEGORY_DANGEROUS_CONTENT",
+            "BLOCK_LOW_AND_ABOVE",
+        )],
+        ..GenerationSettings::default()
+    };
+    let stored = projects::set_generation_settings(db.pool(), "project-default", &settings)
+        .await
+        .expect("store settings");
+    assert_eq!(stored, settings);
+
+    let missing = projects::get_generation_settings(db.pool(), "project-missing").await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::db::{
+    repositories::{documents, projects},
+    Database,
+};
+
+async fn document(db: &Database, project_id: &str, id: &str, name: &str, text: &str) {
+    let root = format!("root-{id}");
+    seed_document(
+        db,
+        project_id,
+        id,
+        name,
+        &[
+            node(&root, None, "Document", ""),
+            node(&format!("sec-{id}"), Some(&root), "Section", text),
+        ],
+    )
+    .await;
+}
+
+async fn seed(db: &Database) {
+    projects::create_project(db.pool(), "alpha", "Alpha")
+        .await
+        .expect("project");
+    projects::create_project(db.pool(), "beta", "Beta")
+        .await
+        .expect("project");
+    document(db, "alpha", "q3", "Q3.md", "Quarterly revenue rose 12%.").await;
+    document(
+        db,
+        "beta",
+        "plan",
+        "Plan.md",
+        "The revenue forecast for next year.",
+    )
+    .await;
+    document(
+        db,
+        "beta",
+        "hiring",
+        "Hiring.md",
+        "Two engineers join in May.",
+    )
+    .await;
+    documents::link_document(db.pool(), "beta", "q3")
+        .await
+        .expect("link");
+}
+
+#[tokio::test]
+async fn hits_from_every_project_are_grouped_by_project() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let groups = documents::search_all_nodes(db.pool(), "revenue", 50, false)
+        .await
+        .expect("search");
+    let mut names = groups
+        .iter()
+        .map(|group| group.project_name.as_str())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["Alpha", "Beta"]);
+
+    let alpha = groups
+        .iter()
+        .find(|group| group.project_id == "alpha")
+        .expect("alpha");
+    assert_eq!(alpha.hits.len(), 1);
+    assert_eq!(alpha.hits[0].document_name, "Q3.md");
+    assert_eq!(alpha.hits[0].node.id, "sec-q3");
+
+    // The linked document shows up in the project it was linked into too.
+    let beta = groups
+        .iter()
+        .find(|group| group.project_id == "beta")
+        .expect("beta");
+    let mut documents_hit = beta
+        .hits
+        .iter()
+        .map(|hit| hit.document_name.as_str())
+        .collect::<Vec<_>>();
+    documents_hit.sort();
+    assert_eq!(documents_hit, vec!["Plan.md", "Q3.md"]);
+}
+
+#[tokio::test]
+async fn partial_words_fall_back_to_substring_matches() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let groups = documents::search_all_nodes(db.pool(), "ngineer", 50, false)
+        .await
+        .expect("search");
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].project_id, "beta");
+    assert_eq!(groups[0].hits[0].document_name, "Hiring.md");
+
+    assert!(documents::search_all_nodes(db.pool(), "the of", 50, false)
+        .await
+        .expect("search")
+        .is_empty());
+    assert!(
+        documents::search_all_nodes(db.pool(), "spreadsheet", 50, false)
+            .await
+            .expect("search")
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn the_limit_caps_hits_across_all_projects() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let groups = documents::search_all_nodes(db.pool(), "revenue", 1, false)
+        .await
+        .expect("search");
+    let hits = groups.iter().map(|group| group.hits.len()).sum::<usize>();
+    assert_eq!(hits, 1);
+}
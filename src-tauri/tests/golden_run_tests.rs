@@ -0,0 +1,143 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use vectorless_lib::{
+    core::types::RunStatus,
+    db::{
+        repositories::{documents, reasoning},
+        Database,
+    },
+    providers::mock::MockProvider,
+    reasoner::executor::ReasoningExecutor,
+    sidecar::types::SidecarNode,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoldenCase {
+    query: String,
+    focus_document_id: Option<String>,
+    max_steps: usize,
+    documents: Vec<GoldenDocument>,
+    expect: GoldenExpectation,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoldenDocument {
+    id: String,
+    name: String,
+    nodes: Vec<SidecarNode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoldenExpectation {
+    completed: bool,
+    step_types: Vec<String>,
+    citations: Vec<String>,
+    grounded: bool,
+    min_overall: f64,
+}
+
+fn golden_cases() -> Vec<PathBuf> {
+    let root = Path::new("tests/fixtures/golden");
+    let mut cases = std::fs::read_dir(root)
+        .expect("golden fixture directory")
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.join("case.json").exists())
+        .collect::<Vec<_>>();
+    cases.sort();
+    cases
+}
+
+async fn run_case(case_dir: &Path) {
+    let name = case_dir.display();
+    let raw = std::fs::read_to_string(case_dir.join("case.json")).expect("case.json");
+    let case: GoldenCase = serde_json::from_str(&raw).expect("valid case.json");
+
+    let db = Database::in_memory().await.expect("db should initialize");
+    for document in &case.documents {
+        documents::insert_document(
+            db.pool(),
+            &document.id,
+            "project-default",
+            &document.name,
+            "application/pdf",
+            &format!("checksum-{}", document.id),
+            1,
+        )
+        .await
+        .expect("insert document");
+        documents::insert_nodes(db.pool(), &document.id, &document.nodes)
+            .await
+            .expect("insert nodes");
+    }
+
+    let provider = MockProvider::from_file(&case_dir.join("provider.json")).expect("provider.json");
+    let executor = ReasoningExecutor::new(provider);
+    let run_id = format!("golden-{}", case_dir.file_name().unwrap().to_string_lossy());
+    let outcome = executor
+        .run(
+            &db,
+            "project-default",
+            case.focus_document_id.as_deref(),
+            run_id.clone(),
+            &case.query,
+            Some(case.max_steps),
+            "unused",
+            |_| {},
+        )
+        .await;
+    assert_eq!(
+        outcome.is_ok(),
+        case.expect.completed,
+        "{name}: unexpected outcome {outcome:?}"
+    );
+
+    let stored = reasoning::get_run(db.pool(), &run_id)
+        .await
+        .expect("stored run");
+    let step_types = stored
+        .steps
+        .iter()
+        .map(|step| step.step_type.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(step_types, case.expect.step_types, "{name}: step sequence");
+
+    if !case.expect.completed {
+        return;
+    }
+    assert_eq!(
+        stored.run.status,
+        RunStatus::Completed,
+        "{name}: run status"
+    );
+    let answer = stored.answer.expect("answer should be stored");
+    assert_eq!(answer.grounded, case.expect.grounded, "{name}: grounded");
+    for citation in &case.expect.citations {
+        assert!(
+            answer.citations.contains(citation),
+            "{name}: missing citation {citation} in {:?}",
+            answer.citations
+        );
+    }
+    let overall = stored.run.quality_json["overall"]
+        .as_f64()
+        .unwrap_or_default();
+    assert!(
+        overall >= case.expect.min_overall,
+        "{name}: overall quality {overall:.2} below {:.2}",
+        case.expect.min_overall
+    );
+}
+
+#[tokio::test]
+async fn golden_runs_match_expectations() {
+    let cases = golden_cases();
+    assert!(!cases.is_empty(), "expected at least one golden fixture");
+    for case_dir in cases {
+        run_case(&case_dir).await;
+    }
+}
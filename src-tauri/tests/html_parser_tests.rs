@@ -0,0 +1,184 @@
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+use vectorless_lib::sidecar::{
+    html::{self, Block},
+    mime::{self, Format},
+    native_parser,
+    types::{NormalizedPayload, SidecarNode},
+};
+
+fn temp_file(suffix: &str, contents: &str) -> NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(suffix)
+        .tempfile()
+        .expect("temp file");
+    file.write_all(contents.as_bytes()).expect("write file");
+    file
+}
+
+fn node<'a>(payload: &'a NormalizedPayload, title: &str) -> &'a SidecarNode {
+    payload
+        .nodes
+        .iter()
+        .find(|node| node.title == title)
+        .unwrap_or_else(|| panic!("no node titled {title}"))
+}
+
+fn children<'a>(payload: &'a NormalizedPayload, parent: &SidecarNode) -> Vec<&'a SidecarNode> {
+    payload
+        .nodes
+        .iter()
+        .filter(|node| node.parent_id.as_deref() == Some(parent.id.as_str()))
+        .collect()
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>Site | Release notes</title>
+  <style>p { color: red; }</style>
+  <script>var evidence = "<p>not text</p>";</script>
+</head>
+<body>
+  <h1>Release &amp; rollout notes</h1>
+  <!-- <p>commented out</p> -->
+  <h2>Latency</h2>
+  <p>Median latency fell to <b>42&nbsp;ms</b>
+     after the cache change.
+  <p>Tail latency is unchanged.</p>
+  <h3>Regions</h3>
+  <ul>
+    <li>EU &ndash; 38 ms
+    <li>US &ndash; 45 ms
+  </ul>
+  <table>
+    <caption>Latency by region</caption>
+    <tr><th>Region</th><th>p50</th></tr>
+    <tr><td>EU</td><td>38 ms</td></tr>
+    <tr><td>US|East</td><td>45 ms</td></tr>
+  </table>
+  <h2>Architecture</h2>
+  <figure>
+    <img src="diagram.png" alt="Cache diagram">
+    <figcaption>The cache sits in front of the store.</figcaption>
+  </figure>
+</body>
+</html>
+"#;
+
+#[test]
+fn headings_become_sections_and_subsections() {
+    let file = temp_file(".html", PAGE);
+    let payload = native_parser::parse(file.path(), "text/html").expect("parses");
+
+    let root = &payload.nodes[0];
+    assert_eq!(root.node_type, "Document");
+    assert_eq!(payload.document.title, "Release & rollout notes");
+    let sections = children(&payload, root);
+    assert_eq!(
+        sections
+            .iter()
+            .map(|node| (node.node_type.as_str(), node.title.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("Section", "Latency"), ("Section", "Architecture")]
+    );
+
+    let latency = node(&payload, "Latency");
+    let paragraphs = children(&payload, latency);
+    assert_eq!(paragraphs[0].node_type, "Paragraph");
+    assert_eq!(
+        paragraphs[0].text,
+        "Median latency fell to 42 ms after the cache change."
+    );
+    assert_eq!(paragraphs[1].text, "Tail latency is unchanged.");
+    let regions = node(&payload, "Regions");
+    assert_eq!(regions.node_type, "Subsection");
+    assert_eq!(regions.parent_id.as_deref(), Some(latency.id.as_str()));
+    assert_eq!(regions.ordinal_path, "1.3");
+
+    let blocks = children(&payload, regions);
+    assert_eq!(
+        blocks
+            .iter()
+            .map(|node| node.node_type.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Paragraph", "Paragraph", "Table"]
+    );
+    assert_eq!(blocks[0].text, "EU \u{2013} 38 ms");
+    assert_eq!(blocks[0].metadata["kind"], "list_item");
+    assert_eq!(blocks[2].title, "Latency by region");
+    assert_eq!(
+        blocks[2].text,
+        "| Region | p50 |\n| --- | --- |\n| EU | 38 ms |\n| US\\|East | 45 ms |"
+    );
+    assert_eq!(blocks[2].metadata["rows"], 3);
+
+    let figure = &children(&payload, node(&payload, "Architecture"))[0];
+    assert_eq!(figure.node_type, "Figure");
+    assert_eq!(figure.title, "The cache sits in front of the store.");
+    assert_eq!(
+        figure.text,
+        "![Cache diagram](diagram.png)\nThe cache sits in front of the store."
+    );
+    assert_eq!(figure.metadata["src"], "diagram.png");
+
+    // No markup, script or style reaches the evidence.
+    for node in &payload.nodes {
+        assert!(!node.text.contains("<p>") && !node.text.contains("color"));
+        assert!(!node.text.contains("commented out"));
+    }
+}
+
+#[test]
+fn text_before_the_first_heading_is_an_overview() {
+    let page = html::parse(
+        "<P>Intro &#8212; see below.<H2>Details</H2><div>Loose <i>text</i><br>on two lines</div>",
+    );
+    assert_eq!(page.title, None);
+    assert_eq!(
+        page.blocks,
+        vec![
+            Block::Paragraph {
+                text: "Intro \u{2014} see below.".to_string(),
+                list_item: false
+            },
+            Block::Heading {
+                level: 2,
+                text: "Details".to_string()
+            },
+            Block::Paragraph {
+                text: "Loose text\non two lines".to_string(),
+                list_item: false
+            },
+        ]
+    );
+
+    let file = temp_file(
+        ".htm",
+        "<p>Intro.</p><h2>Details</h2><p>More.</p><h2>Notes</h2><p>Last.</p>",
+    );
+    let payload = native_parser::parse(file.path(), "text/html").expect("parses");
+    let root = &payload.nodes[0];
+    assert_eq!(
+        children(&payload, root)
+            .iter()
+            .map(|node| node.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Overview", "Details", "Notes"]
+    );
+}
+
+#[test]
+fn html_is_told_from_text_by_the_hint() {
+    let page = temp_file(".html", "<p>No doctype here.</p>");
+    let resolved = mime::resolve(page.path(), "text/html").expect("resolve");
+    assert_eq!(resolved.format, Format::Html);
+
+    let notes = temp_file(".md", "<!-- draft -->\n# Notes\n\nShip on Friday.\n");
+    let resolved = mime::resolve(notes.path(), "text/markdown").expect("resolve");
+    assert_eq!(resolved.format, Format::Text);
+
+    let empty = temp_file(".html", "<html><body><h1>Only a heading</h1></body></html>");
+    assert!(native_parser::parse(empty.path(), "text/html").is_err());
+}
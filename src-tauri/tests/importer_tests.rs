@@ -0,0 +1,239 @@
+use std::path::Path;
+
+use vectorless_lib::{
+    core::{
+        paths,
+        types::{ImportItem, ImportSource, IngestRules},
+    },
+    db::{repositories::documents, Database},
+    sidecar::importers,
+};
+
+fn write(root: &Path, relative: &str, contents: &str) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+    std::fs::write(path, contents).expect("write");
+}
+
+fn item<'a>(items: &'a [ImportItem], root: &Path, relative: &str) -> &'a ImportItem {
+    let path = paths::display(&paths::simplify(&root.join(relative)));
+    items
+        .iter()
+        .find(|item| item.path == path)
+        .unwrap_or_else(|| panic!("{relative} should be imported"))
+}
+
+#[test]
+fn obsidian_notes_carry_tags_and_link_their_attachments() {
+    let vault = tempfile::tempdir().expect("tempdir");
+    let root = vault.path();
+    write(
+        root,
+        "Research/Papers/Transformers.md",
+        "---\ntags: [ml, \"reading\"]\n---\n# Notes\nSee ![[diagram.png]] and [the paper](<../../Attachments/attention paper.pdf>).\n#todo but not #123\n```\n#not-a-tag\n```\n",
+    );
+    write(root, "Attachments/diagram.png", "png");
+    write(root, "Attachments/attention paper.pdf", "%PDF-1.4");
+    write(
+        root,
+        "Inbox.md",
+        "---\ntags:\n  - quick\n---\nLinks to [[Transformers]].",
+    );
+    write(root, ".obsidian/app.json", "{}");
+
+    let plan = importers::plan_import(root, ImportSource::Obsidian, &IngestRules::default(), true)
+        .expect("plan");
+    assert_eq!(plan.items.len(), 4);
+    assert!(plan
+        .skipped
+        .iter()
+        .any(|skip| skip.path.ends_with(".obsidian")));
+
+    let note = item(&plan.items, root, "Research/Papers/Transformers.md");
+    assert_eq!(note.project.as_deref(), Some("Research"));
+    assert_eq!(note.tags, vec!["Papers", "ml", "reading", "todo"]);
+    assert_eq!(note.mime_type, "text/markdown");
+    assert_eq!(note.attachments.len(), 2);
+
+    // Attachments follow the note that links to them.
+    let pdf = item(&plan.items, root, "Attachments/attention paper.pdf");
+    assert_eq!(pdf.project.as_deref(), Some("Research"));
+    assert_eq!(pdf.tags, note.tags);
+    assert!(note.attachments.contains(&pdf.path));
+
+    let inbox = item(&plan.items, root, "Inbox.md");
+    assert_eq!(inbox.project, None);
+    assert_eq!(inbox.tags, vec!["quick"]);
+    // Links between notes are not attachments.
+    assert!(inbox.attachments.is_empty());
+
+    let flat = importers::plan_import(root, ImportSource::Obsidian, &IngestRules::default(), false)
+        .expect("plan");
+    let note = item(&flat.items, root, "Research/Papers/Transformers.md");
+    assert_eq!(note.project, None);
+    assert_eq!(note.tags[..2], ["Research", "Papers"]);
+}
+
+#[test]
+fn notion_ids_are_dropped_from_names() {
+    let export = tempfile::tempdir().expect("tempdir");
+    let root = export.path();
+    let id = "0123456789abcdef0123456789abcdef";
+    write(
+        root,
+        &format!("Team Wiki {id}/Roadmap {id}.html"),
+        &format!(
+            "<p><a href=\"Roadmap%20{id}/plan.pdf\">plan</a> <a href=\"https://notion.so\">x</a></p>"
+        ),
+    );
+    write(
+        root,
+        &format!("Team Wiki {id}/Roadmap {id}/plan.pdf"),
+        "%PDF-1.4",
+    );
+
+    let plan = importers::plan_import(root, ImportSource::Notion, &IngestRules::default(), true)
+        .expect("plan");
+    let page = item(
+        &plan.items,
+        root,
+        &format!("Team Wiki {id}/Roadmap {id}.html"),
+    );
+    assert_eq!(page.display_name.as_deref(), Some("Roadmap.html"));
+    assert_eq!(page.project.as_deref(), Some("Team Wiki"));
+    assert_eq!(page.mime_type, "text/html");
+    let pdf = item(
+        &plan.items,
+        root,
+        &format!("Team Wiki {id}/Roadmap {id}/plan.pdf"),
+    );
+    assert_eq!(page.attachments, vec![pdf.path.clone()]);
+    assert_eq!(pdf.display_name, None);
+    assert!(page.tags.is_empty());
+}
+
+#[test]
+fn zotero_items_are_named_and_filed_by_collection() {
+    let export = tempfile::tempdir().expect("tempdir");
+    let root = export.path();
+    write(
+        root,
+        "Exported Items.rdf",
+        r##"<rdf:RDF
+ xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+ xmlns:z="http://www.zotero.org/namespaces/export#"
+ xmlns:dc="http://purl.org/dc/elements/1.1/"
+ xmlns:dcterms="http://purl.org/dc/terms/"
+ xmlns:bib="http://purl.org/net/biblio#"
+ xmlns:link="http://purl.org/rss/1.0/modules/link/">
+  <bib:Article rdf:about="urn:paper">
+    <z:itemType>journalArticle</z:itemType>
+    <dc:title>Attention Is All You Need</dc:title>
+    <dc:subject>transformers</dc:subject>
+    <dc:subject><z:AutomaticTag><rdf:value>nlp</rdf:value></z:AutomaticTag></dc:subject>
+    <link:link rdf:resource="#item_2"/>
+    <link:link rdf:resource="#item_3"/>
+    <link:link rdf:resource="#item_4"/>
+  </bib:Article>
+  <z:Attachment rdf:about="#item_2">
+    <rdf:resource rdf:resource="files/2/attention.pdf"/>
+    <dc:title>Full Text PDF</dc:title>
+  </z:Attachment>
+  <z:Attachment rdf:about="#item_3">
+    <rdf:resource rdf:resource="files/3/Supplement%20A.pdf"/>
+  </z:Attachment>
+  <z:Attachment rdf:about="#item_4">
+    <rdf:resource rdf:resource="files/4/missing.pdf"/>
+  </z:Attachment>
+  <z:Collection rdf:about="#collection_1">
+    <dc:title>Thesis</dc:title>
+    <dcterms:hasPart rdf:resource="#collection_2"/>
+  </z:Collection>
+  <z:Collection rdf:about="#collection_2">
+    <dc:title>Background</dc:title>
+    <dcterms:hasPart rdf:resource="urn:paper"/>
+  </z:Collection>
+</rdf:RDF>"##,
+    );
+    write(root, "files/2/attention.pdf", "%PDF-1.4");
+    write(root, "files/3/Supplement A.pdf", "%PDF-1.4");
+
+    let plan = importers::plan_import(root, ImportSource::Zotero, &IngestRules::default(), true)
+        .expect("plan");
+    assert_eq!(plan.items.len(), 2);
+    let paper = item(&plan.items, root, "files/2/attention.pdf");
+    assert_eq!(
+        paper.display_name.as_deref(),
+        Some("Attention Is All You Need.pdf")
+    );
+    assert_eq!(paper.project.as_deref(), Some("Thesis"));
+    assert_eq!(paper.tags, vec!["Background", "transformers", "nlp"]);
+    let supplement = item(&plan.items, root, "files/3/Supplement A.pdf");
+    assert_eq!(paper.attachments, vec![supplement.path.clone()]);
+    assert_eq!(supplement.display_name, None);
+    assert_eq!(plan.skipped.len(), 1);
+    assert_eq!(plan.skipped[0].reason, "file is missing from the export");
+
+    let empty = tempfile::tempdir().expect("tempdir");
+    assert!(importers::plan_import(
+        empty.path(),
+        ImportSource::Zotero,
+        &IngestRules::default(),
+        true
+    )
+    .is_err());
+}
+
+#[tokio::test]
+async fn tags_and_attachments_are_stored_per_document() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    for (id, checksum) in [("doc-note", "checksum-note"), ("doc-pdf", "checksum-pdf")] {
+        documents::insert_document(
+            db.pool(),
+            id,
+            "project-default",
+            id,
+            "text/markdown",
+            checksum,
+            1,
+        )
+        .await
+        .expect("insert document");
+    }
+    documents::set_tags(db.pool(), "doc-note", &["ml".to_string(), "ml".to_string()])
+        .await
+        .expect("tags");
+    documents::set_tags(
+        db.pool(),
+        "doc-note",
+        &["todo".to_string(), "ml".to_string()],
+    )
+    .await
+    .expect("tags");
+    assert_eq!(
+        documents::get_tags(db.pool(), "doc-note")
+            .await
+            .expect("tags"),
+        vec!["ml", "todo"]
+    );
+
+    documents::link_attachment(db.pool(), "doc-note", "doc-pdf")
+        .await
+        .expect("link");
+    documents::link_attachment(db.pool(), "doc-note", "doc-gone")
+        .await
+        .expect("link");
+    assert_eq!(
+        documents::get_attachment_ids(db.pool(), "doc-note")
+            .await
+            .expect("attachments"),
+        vec!["doc-pdf"]
+    );
+    documents::delete_document(db.pool(), "doc-pdf")
+        .await
+        .expect("delete");
+    assert!(documents::get_attachment_ids(db.pool(), "doc-note")
+        .await
+        .expect("attachments")
+        .is_empty());
+}
@@ -0,0 +1,50 @@
+use serde_json::json;
+use vectorless_lib::core::{
+    errors::AppError,
+    types::{IngestCompleteEvent, IngestFailedEvent},
+};
+
+#[test]
+fn complete_event_serializes_camel_case_summary() {
+    let event = IngestCompleteEvent {
+        job_id: "job-1".to_string(),
+        project_id: "project-default".to_string(),
+        file_path: "/tmp/report.pdf".to_string(),
+        document_id: "doc-1".to_string(),
+        root_node_id: "root-1".to_string(),
+        node_count: 12,
+        section_count: 3,
+        shared_from_project_id: None,
+        reingested: false,
+        warnings: vec!["Only 120 characters of text were found".to_string()],
+    };
+    let value = serde_json::to_value(&event).expect("serialize");
+    assert_eq!(value["jobId"], json!("job-1"));
+    assert_eq!(value["documentId"], json!("doc-1"));
+    assert_eq!(value["rootNodeId"], json!("root-1"));
+    assert_eq!(value["nodeCount"], json!(12));
+    assert_eq!(value["sectionCount"], json!(3));
+    assert_eq!(value["sharedFromProjectId"], json!(null));
+    assert_eq!(value["warnings"].as_array().map(Vec::len), Some(1));
+}
+
+#[test]
+fn failed_event_carries_typed_error() {
+    let err = AppError::Sidecar("external parser timed out after 5s".to_string());
+    let event = IngestFailedEvent {
+        job_id: "job-2".to_string(),
+        project_id: Some("project-default".to_string()),
+        document_id: None,
+        file_path: "/tmp/scan.pdf".to_string(),
+        code: err.code().to_string(),
+        message: err.to_string(),
+        retryable: err.retryable(),
+    };
+    let value = serde_json::to_value(&event).expect("serialize");
+    assert_eq!(value["code"], json!(err.code()));
+    assert_eq!(value["retryable"], json!(err.retryable()));
+    assert!(value["message"]
+        .as_str()
+        .is_some_and(|message| message.contains("timed out")));
+    assert_eq!(value["documentId"], json!(null));
+}
@@ -0,0 +1,60 @@
+use vectorless_lib::{
+    core::types::PreviewIngestResponse,
+    sidecar::{native_parser, preview},
+};
+
+fn preview_of(name: &str, contents: &str, mime: &str) -> PreviewIngestResponse {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join(name);
+    std::fs::write(&path, contents).expect("write");
+    preview::summarize(&native_parser::parse(&path, mime).expect("parses"))
+}
+
+#[test]
+fn preview_reports_sections_and_counts() {
+    let body = "The rollout reached every region in the first quarter of the year. ".repeat(4);
+    let contents = format!(
+        "# Rollout\n\n{body}\n\n# Costs\n\n{body}\n\nTravel was the only line that grew.\n"
+    );
+
+    let preview = preview_of("report.md", &contents, "text/markdown");
+
+    assert_eq!(preview.mime, "text/markdown");
+    assert_eq!(preview.section_count, 2);
+    assert_eq!(preview.paragraph_count, 3);
+    assert_eq!(preview.sections[0].title, "Rollout");
+    assert_eq!(preview.sections[1].block_count, 2);
+    assert!(preview.sections[0]
+        .excerpt
+        .starts_with("The rollout reached"));
+    assert!(preview.sections[0].excerpt.chars().count() <= 200);
+    assert!(preview.warnings.is_empty(), "{:?}", preview.warnings);
+}
+
+#[test]
+fn preview_warns_about_thin_headingless_text() {
+    let preview = preview_of("note.txt", "Short note", "text/plain");
+
+    assert_eq!(preview.sections[0].title, "Part 1");
+    assert!(preview
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("No headings were found")));
+    assert!(preview
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("Only 10 characters")));
+}
+
+#[test]
+fn preview_warns_when_content_overrides_the_declared_type() {
+    let contents = "Minutes of the planning meeting held on Tuesday morning. ".repeat(5);
+
+    let preview = preview_of("minutes.pdf", &contents, "application/pdf");
+
+    assert_eq!(preview.mime, "text/plain");
+    assert!(preview
+        .warnings
+        .iter()
+        .any(|w| w == "Parsed as text/plain although it was given as application/pdf"));
+}
@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+use vectorless_lib::core::progress::{estimate_remaining, IngestProgress};
+
+#[test]
+fn eta_extrapolates_the_rate_so_far() {
+    assert_eq!(estimate_remaining(Duration::from_secs(10), 0, 1000), None);
+    assert_eq!(
+        estimate_remaining(Duration::from_secs(10), 250, 1000),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+        estimate_remaining(Duration::from_secs(10), 1000, 1000),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(estimate_remaining(Duration::from_secs(10), 5, 0), None);
+}
+
+#[test]
+fn page_events_estimate_bytes_from_pages() {
+    let started = Instant::now() - Duration::from_secs(4);
+    let progress = IngestProgress::started_at("job-1", 400_000_000, started);
+
+    let start = progress.start("parse", 30, "Parsing document");
+    assert_eq!(start.bytes_processed, 0);
+    assert_eq!(start.total_bytes, 400_000_000);
+    assert_eq!(start.eta_ms, None);
+
+    let event = progress.pages(55, 50, 100);
+    assert_eq!(event.job_id, "job-1");
+    assert_eq!(event.stage, "parse");
+    assert_eq!(event.bytes_processed, 200_000_000);
+    assert_eq!(event.message, "Parsed page 50 of 100");
+    let eta = event.eta_ms.expect("eta once bytes are processed");
+    assert!((3_900..=4_500).contains(&eta), "eta was {eta}ms");
+    assert_eq!(event.document_id, None);
+}
+
+#[test]
+fn finalize_reports_the_document_id() {
+    let progress = IngestProgress::new("job-2", 1024);
+    let event = progress.finalize("doc-42");
+    assert_eq!(event.stage, "finalize");
+    assert_eq!(event.percent, 100);
+    assert_eq!(event.bytes_processed, 1024);
+    assert_eq!(event.eta_ms, Some(0));
+    assert_eq!(event.document_id.as_deref(), Some("doc-42"));
+
+    let value = serde_json::to_value(&event).expect("serialize");
+    assert_eq!(value["documentId"], "doc-42");
+    assert_eq!(value["totalBytes"], 1024);
+    assert_eq!(value["etaMs"], 0);
+}
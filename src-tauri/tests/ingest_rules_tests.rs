@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use vectorless_lib::{
+    core::types::IngestRules,
+    db::{repositories::projects, Database},
+    sidecar::ignore::{normalize_rules, scan_directory, skip_reason},
+};
+
+fn write(root: &Path, relative: &str, contents: &[u8]) {
+    let path = root.join(relative);
+    std::fs::create_dir_all(path.parent().expect("parent")).expect("create dirs");
+    std::fs::write(path, contents).expect("write file");
+}
+
+#[test]
+fn default_rules_skip_dependencies_temp_files_and_binaries() {
+    let rules = IngestRules::default();
+    let skipped = |path: &str, size: Option<u64>| skip_reason(&rules, Path::new(path), size);
+
+    assert!(skipped("web/node_modules", None).is_some());
+    assert!(skipped("notes/.DS_Store", Some(10)).is_some());
+    assert!(skipped("reports/~$draft.docx", Some(10)).is_some());
+    assert!(skipped("reports/draft.docx~", Some(10)).is_some());
+    assert!(skipped("tools/setup.EXE", Some(10)).is_some());
+    assert!(skipped("dump.txt", Some(200 * 1024 * 1024)).is_some());
+    assert_eq!(skipped("reports/q3.pdf", Some(10)), None);
+    assert_eq!(skipped("reports", None), None);
+}
+
+#[test]
+fn path_patterns_match_relative_to_the_scanned_folder() {
+    let rules = IngestRules {
+        ignore_globs: vec!["build/**".to_string(), "docs/**/draft-?.md".to_string()],
+        ignored_extensions: Vec::new(),
+        max_file_bytes: None,
+    };
+    let skipped = |path: &str| skip_reason(&rules, Path::new(path), Some(1)).is_some();
+
+    assert!(skipped("build"));
+    assert!(skipped("build/out/index.md"));
+    assert!(skipped("docs/draft-1.md"));
+    assert!(skipped("docs/a/b/draft-2.md"));
+    assert!(!skipped("docs/draft-10.md"));
+    assert!(!skipped("src/build/notes.md"));
+}
+
+#[test]
+fn normalize_rules_cleans_entries_and_rejects_degenerate_ones() {
+    let rules = normalize_rules(IngestRules {
+        ignore_globs: vec![" /vendor/ ".to_string(), "vendor".to_string()],
+        ignored_extensions: vec![".PSD".to_string(), "psd".to_string()],
+        max_file_bytes: Some(1024),
+    })
+    .expect("valid rules");
+    assert_eq!(rules.ignore_globs, vec!["vendor"]);
+    assert_eq!(rules.ignored_extensions, vec!["psd"]);
+
+    for invalid in [
+        IngestRules {
+            ignore_globs: vec!["**".to_string()],
+            ..IngestRules::default()
+        },
+        IngestRules {
+            ignored_extensions: vec![" . ".to_string()],
+            ..IngestRules::default()
+        },
+        IngestRules {
+            max_file_bytes: Some(0),
+            ..IngestRules::default()
+        },
+    ] {
+        assert!(normalize_rules(invalid).is_err());
+    }
+}
+
+#[test]
+fn scan_directory_lists_ingestable_files_and_reports_skips() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    write(dir.path(), "handbook.md", b"# Handbook");
+    write(dir.path(), "reports/q3.csv", b"a,b\n1,2");
+    write(dir.path(), "reports/.~lock.q3.csv#", b"lock");
+    write(dir.path(), "node_modules/pkg/readme.md", b"# pkg");
+    write(dir.path(), "archive.zip", b"PK");
+    write(dir.path(), "huge.txt", &[b'x'; 64]);
+
+    let rules = IngestRules {
+        max_file_bytes: Some(32),
+        ..IngestRules::default()
+    };
+    let scan = scan_directory(dir.path(), &rules).expect("scan");
+
+    let files = scan
+        .files
+        .iter()
+        .map(|file| {
+            Path::new(&file.path)
+                .strip_prefix(dir.path())
+                .expect("under root")
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(files, vec!["handbook.md", "reports/q3.csv"]);
+    assert_eq!(scan.skipped.len(), 4);
+    assert!(scan
+        .skipped
+        .iter()
+        .any(|skip| skip.path.ends_with("node_modules") && skip.reason.contains("node_modules")));
+    assert!(scan
+        .skipped
+        .iter()
+        .any(|skip| skip.path.ends_with("huge.txt") && skip.reason.contains("limit")));
+
+    assert!(scan_directory(&dir.path().join("missing"), &rules).is_err());
+}
+
+#[tokio::test]
+async fn project_ingest_rules_round_trip() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let initial = projects::get_ingest_rules(db.pool(), "project-default")
+        .await
+        .expect("defaults");
+    assert_eq!(initial, IngestRules::default());
+
+    let rules = IngestRules {
+        ignore_globs: vec!["drafts".to_string()],
+        ignored_extensions: vec!["psd".to_string()],
+        max_file_bytes: None,
+    };
+    let stored = projects::set_ingest_rules(db.pool(), "project-default", &rules)
+        .await
+        .expect("store");
+    assert_eq!(stored, rules);
+
+    let missing = projects::set_ingest_rules(db.pool(), "project-missing", &rules).await;
+    assert!(missing.is_err());
+}
@@ -0,0 +1,67 @@
+use vectorless_lib::{
+    core::types::{LanguageResources, NodeRef, NodeType},
+    reasoner::insufficient::EvidenceGaps,
+};
+
+fn section(id: &str, title: &str, document_name: &str) -> NodeRef {
+    NodeRef {
+        id: id.to_string(),
+        title: title.to_string(),
+        document_id: "doc-1".to_string(),
+        document_name: document_name.to_string(),
+        node_type: NodeType::Section,
+        ordinal_path: id.to_string(),
+    }
+}
+
+#[test]
+fn gaps_split_the_question_into_supported_and_missing_terms() {
+    let evidence = vec![
+        ("n1".to_string(), "The p99 latency is 50ms.".to_string()),
+        (
+            "n2".to_string(),
+            "Latencies were measured weekly.".to_string(),
+        ),
+    ];
+    let searched = vec![
+        section("s1", "Performance", "Report.md"),
+        section("s1", "Performance", "Report.md"),
+        section("s2", "", "Costs.md"),
+    ];
+    let gaps = EvidenceGaps::find(
+        "What is the p99 latency and the cost of failover?",
+        &evidence,
+        &searched,
+        &LanguageResources::default(),
+    );
+
+    assert_eq!(gaps.missing(), vec!["cost", "failover"]);
+    assert_eq!(gaps.citations(), vec!["n1"]);
+    assert_eq!(gaps.searched, vec!["Performance (Report.md)", "Costs.md"]);
+
+    let markdown = gaps.to_markdown();
+    assert!(markdown.starts_with("## Not enough evidence"));
+    assert!(markdown.contains("### No support found for\n- cost\n- failover\n"));
+    assert!(markdown.contains("- latency [citation:n1]"));
+    assert!(markdown.contains("- Performance (Report.md)"));
+
+    let trace = gaps.to_trace();
+    assert_eq!(trace["step"], "insufficient_evidence");
+    assert_eq!(trace["missing"], serde_json::json!(["cost", "failover"]));
+}
+
+#[test]
+fn gaps_without_evidence_list_every_term_as_missing() {
+    let gaps = EvidenceGaps::find(
+        "Who approved the budget?",
+        &[],
+        &[],
+        &LanguageResources::default(),
+    );
+
+    assert_eq!(gaps.missing(), vec!["who", "approved", "budget"]);
+    assert!(gaps.citations().is_empty());
+    assert!(gaps
+        .to_markdown()
+        .contains("### Sections searched\n- No section matched the question.\n"));
+}
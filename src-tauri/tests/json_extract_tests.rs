@@ -0,0 +1,36 @@
+use vectorless_lib::providers::json_extract::extract_json_object;
+
+#[test]
+fn parses_plain_json_object() {
+    let value = extract_json_object(r#"{"confidence":0.8}"#).expect("plain object");
+    assert_eq!(value["confidence"], 0.8);
+}
+
+#[test]
+fn strips_markdown_code_fences() {
+    let text = "```json\n{\"answer_markdown\":\"ok\",\"citations\":[]}\n```";
+    let value = extract_json_object(text).expect("fenced object");
+    assert_eq!(value["answer_markdown"], "ok");
+}
+
+#[test]
+fn finds_first_balanced_object_amid_prose() {
+    let text = r#"Here is the answer: {"answer_markdown":"uses {braces} inside","confidence":0.7} Hope that helps! {"extra":true}"#;
+    let value = extract_json_object(text).expect("embedded object");
+    assert_eq!(value["answer_markdown"], "uses {braces} inside");
+    assert!(value.get("extra").is_none());
+}
+
+#[test]
+fn repairs_trailing_commas_outside_strings() {
+    let text = r#"{"citations":["a","b",],"answer_markdown":"x, ]",}"#;
+    let value = extract_json_object(text).expect("repaired object");
+    assert_eq!(value["citations"].as_array().map(Vec::len), Some(2));
+    assert_eq!(value["answer_markdown"], "x, ]");
+}
+
+#[test]
+fn rejects_text_without_an_object() {
+    assert!(extract_json_object("no json here").is_err());
+    assert!(extract_json_object("[1, 2, 3]").is_err());
+}
@@ -0,0 +1,228 @@
+use chrono::{Duration, Utc};
+use futures::future::BoxFuture;
+
+use vectorless_lib::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{KeySelection, Provider, ProviderKeyRecord},
+    },
+    db::{
+        repositories::{documents, provider_keys},
+        Database,
+    },
+    providers::{CallKind, LlmProvider, ProviderResponse},
+    reasoner::executor::{ReasoningExecutor, RunOptions},
+    security::key_pool::{order_for_use, ApiKeyEntry, ApiKeyPool},
+    sidecar::types::SidecarNode,
+};
+
+fn record(
+    id: &str,
+    minutes_since_use: Option<i64>,
+    quota: Option<i64>,
+    used: i64,
+) -> ProviderKeyRecord {
+    let now = Utc::now();
+    ProviderKeyRecord {
+        id: id.to_string(),
+        provider: Provider::Gemini,
+        label: id.to_string(),
+        key_hint: "…abcd".to_string(),
+        daily_request_quota: quota,
+        requests_today: used,
+        total_requests: used,
+        total_tokens: 0,
+        rate_limited_count: 0,
+        cooldown_until: None,
+        last_used_at: minutes_since_use.map(|minutes| now - Duration::minutes(minutes)),
+        created_at: now,
+    }
+}
+
+#[test]
+fn round_robin_prefers_least_recently_used() {
+    let records = vec![
+        record("recent", Some(1), None, 0),
+        record("never", None, None, 0),
+        record("older", Some(30), None, 0),
+    ];
+    assert_eq!(
+        order_for_use(&records, KeySelection::RoundRobin, Utc::now()),
+        vec!["never", "older", "recent"]
+    );
+}
+
+#[test]
+fn quota_aware_prefers_most_remaining_quota() {
+    let records = vec![
+        record("nearly-spent", Some(5), Some(100), 95),
+        record("roomy", Some(1), Some(100), 10),
+        record("spent", None, Some(50), 50),
+    ];
+    assert_eq!(
+        order_for_use(&records, KeySelection::QuotaAware, Utc::now()),
+        vec!["roomy", "nearly-spent", "spent"]
+    );
+}
+
+#[test]
+fn cooling_keys_are_tried_last() {
+    let mut cooling = record("cooling", None, None, 0);
+    cooling.cooldown_until = Some(Utc::now() + Duration::seconds(60));
+    let records = vec![cooling, record("ready", Some(1), None, 0)];
+    assert_eq!(
+        order_for_use(&records, KeySelection::RoundRobin, Utc::now()),
+        vec!["ready", "cooling"]
+    );
+}
+
+/// Rate limits every call made with `limited-secret`.
+struct KeyAwareProvider;
+
+impl LlmProvider for KeyAwareProvider {
+    fn model(&self) -> &str {
+        "key-aware"
+    }
+
+    fn complete<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        _prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        let outcome = if api_key == "limited-secret" {
+            Err(AppError::ProviderRateLimited)
+        } else {
+            let text = match kind {
+                CallKind::Planner => "not a plan".to_string(),
+                CallKind::Synthesis => serde_json::json!({
+                    "answer_markdown": "Latency dropped to 50ms p99.",
+                    "confidence": 0.9,
+                    "citations": ["sec-keys-1"],
+                })
+                .to_string(),
+            };
+            Ok(ProviderResponse {
+                text,
+                token_usage: serde_json::json!({"totalTokenCount": 10}),
+            })
+        };
+        Box::pin(async move { outcome })
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-keys-1",
+        "project-default",
+        "Spec.pdf",
+        "application/pdf",
+        "checksum-keys-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        SidecarNode {
+            id: "root-keys-1".to_string(),
+            parent_id: None,
+            node_type: "Document".to_string(),
+            title: "Spec".to_string(),
+            text: "".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "root".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+        SidecarNode {
+            id: "sec-keys-1".to_string(),
+            parent_id: Some("root-keys-1".to_string()),
+            node_type: "Section".to_string(),
+            title: "Latency".to_string(),
+            text: "Latency dropped to 50ms p99.".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "1".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+    ];
+    documents::insert_nodes(db.pool(), "doc-keys-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+#[tokio::test]
+async fn rate_limited_key_fails_over_and_usage_is_tracked() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    for id in ["key-limited", "key-healthy"] {
+        provider_keys::upsert_key(db.pool(), id, &Provider::Gemini, id, "…0000", None)
+            .await
+            .expect("register key");
+    }
+    let pool = ApiKeyPool::new(
+        vec![
+            ApiKeyEntry {
+                id: Some("key-limited".to_string()),
+                secret: "limited-secret".to_string(),
+            },
+            ApiKeyEntry {
+                id: Some("key-healthy".to_string()),
+                secret: "healthy-secret".to_string(),
+            },
+        ],
+        KeySelection::RoundRobin,
+    );
+
+    ReasoningExecutor::new(KeyAwareProvider)
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-keys-1"),
+            "run-keys-1".to_string(),
+            "What is the latency?",
+            RunOptions {
+                api_keys: pool,
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("run should fail over to the healthy key");
+
+    let limited = provider_keys::get_key(db.pool(), "key-limited")
+        .await
+        .expect("limited");
+    let healthy = provider_keys::get_key(db.pool(), "key-healthy")
+        .await
+        .expect("healthy");
+    assert_eq!(limited.rate_limited_count, 1);
+    assert!(limited.cooldown_until.is_some());
+    assert_eq!(limited.total_requests, 0);
+    assert!(healthy.total_requests >= 2);
+    assert_eq!(healthy.requests_today, healthy.total_requests);
+    assert_eq!(healthy.total_tokens, healthy.total_requests * 10);
+}
+
+#[tokio::test]
+async fn key_selection_setting_round_trips() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    assert_eq!(
+        provider_keys::get_key_selection(db.pool())
+            .await
+            .expect("default"),
+        KeySelection::RoundRobin
+    );
+    provider_keys::set_key_selection(db.pool(), KeySelection::QuotaAware)
+        .await
+        .expect("store selection");
+    assert_eq!(
+        provider_keys::get_key_selection(db.pool())
+            .await
+            .expect("stored"),
+        KeySelection::QuotaAware
+    );
+}
@@ -0,0 +1,108 @@
+use vectorless_lib::{
+    core::types::LanguageResources,
+    db::{repositories::settings, Database},
+    reasoner::{
+        evaluator::evaluate_answer,
+        language::{defaults, response, validate_resources},
+        query_scope::requires_project_scope,
+    },
+};
+
+#[test]
+fn bundled_lists_detect_relation_queries_in_other_languages() {
+    let german = defaults("de").expect("German defaults");
+    let spanish = defaults("es").expect("Spanish defaults");
+    let french = defaults("FR").expect("French defaults");
+
+    assert!(requires_project_scope(
+        "Vergleiche die Dokumente und fasse die Unterschiede zusammen",
+        &german
+    ));
+    assert!(requires_project_scope(
+        "¿Cuál es la relación entre estos documentos?",
+        &spanish
+    ));
+    assert!(requires_project_scope(
+        "Comparez les fichiers et résumez les différences",
+        &french
+    ));
+    assert!(!requires_project_scope(
+        "Was steht auf Seite 4 über die Latenz?",
+        &german
+    ));
+    // English hints do not fire on the German query.
+    assert!(!requires_project_scope(
+        "Vergleiche die Dokumente und fasse die Unterschiede zusammen",
+        &LanguageResources::default()
+    ));
+    assert_eq!(defaults("xx"), None);
+}
+
+#[test]
+fn query_alignment_skips_the_languages_stopwords() {
+    let german = defaults("de").expect("German defaults");
+    let citations = vec!["n1".to_string()];
+    let score = |language: &LanguageResources| {
+        evaluate_answer(
+            "Wie ist die Latenz der Anfragen?",
+            "Die Latenz der Anfragen beträgt 50 ms.",
+            &citations,
+            &citations,
+            &Default::default(),
+            &Default::default(),
+            false,
+            language,
+        )
+        .query_alignment
+    };
+
+    assert_eq!(score(&german), 1.0);
+    assert!(score(&LanguageResources::default()) < 1.0);
+}
+
+#[tokio::test]
+async fn edited_lists_are_cleaned_and_stored_in_settings() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let stored: LanguageResources =
+        settings::get_setting(db.pool(), settings::LANGUAGE_RESOURCES_SETTING)
+            .await
+            .expect("read setting");
+    assert_eq!(stored, LanguageResources::default());
+    assert!(!response(stored).customized);
+
+    let mut edited = defaults("en").expect("English defaults");
+    edited.language = " EN ".to_string();
+    edited.relation_hints.push("Versus".to_string());
+    edited.relation_hints.push("versus".to_string());
+    edited.stopwords.push("  ".to_string());
+    let edited = validate_resources(edited).expect("valid lists");
+    assert_eq!(edited.language, "en");
+    assert_eq!(
+        edited.relation_hints.last().map(String::as_str),
+        Some("versus")
+    );
+    assert_eq!(
+        edited.relation_hints.len(),
+        LanguageResources::default().relation_hints.len() + 1
+    );
+    assert_eq!(edited.stopwords, LanguageResources::default().stopwords);
+
+    settings::set_setting(db.pool(), settings::LANGUAGE_RESOURCES_SETTING, &edited)
+        .await
+        .expect("store setting");
+    let stored: LanguageResources =
+        settings::get_setting(db.pool(), settings::LANGUAGE_RESOURCES_SETTING)
+            .await
+            .expect("read setting");
+    assert!(requires_project_scope(
+        "plan a versus plan b in the files",
+        &stored
+    ));
+    assert!(response(stored).customized);
+
+    let blank = LanguageResources {
+        language: " ".to_string(),
+        ..LanguageResources::default()
+    };
+    assert!(validate_resources(blank).is_err());
+}
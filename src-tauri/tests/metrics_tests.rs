@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use vectorless_lib::{
+    core::{
+        metrics::{prometheus_text, Metric},
+        types::MetricsSettings,
+    },
+    db::{repositories::metrics, Database},
+};
+
+async fn enable(db: &Database) {
+    metrics::set_settings(db.pool(), &MetricsSettings { enabled: true })
+        .await
+        .expect("enable metrics");
+}
+
+#[tokio::test]
+async fn nothing_is_recorded_while_metrics_are_off() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    assert!(
+        !metrics::get_settings(db.pool())
+            .await
+            .expect("settings")
+            .enabled
+    );
+
+    metrics::record(
+        db.pool(),
+        Metric::Ingest,
+        "ingest",
+        Some(Duration::from_millis(40)),
+    )
+    .await
+    .expect("record");
+    assert!(metrics::list_metrics(db.pool())
+        .await
+        .expect("list")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn counts_and_durations_are_totalled_per_label() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    enable(&db).await;
+
+    for ms in [120, 80] {
+        metrics::record(
+            db.pool(),
+            Metric::ProviderCall,
+            "gemini-2.0-flash",
+            Some(Duration::from_millis(ms)),
+        )
+        .await
+        .expect("record");
+    }
+    metrics::record(
+        db.pool(),
+        Metric::ProviderCall,
+        "gpt-4o",
+        Some(Duration::from_millis(300)),
+    )
+    .await
+    .expect("record");
+    // Failures only count, whatever duration comes with them.
+    metrics::record(
+        db.pool(),
+        Metric::IngestFailure,
+        "ingest",
+        Some(Duration::from_millis(999)),
+    )
+    .await
+    .expect("record");
+
+    let listed = metrics::list_metrics(db.pool()).await.expect("list");
+    let summary = listed
+        .iter()
+        .map(|sample| {
+            (
+                sample.name.as_str(),
+                sample.label.as_str(),
+                sample.count,
+                sample.total_ms,
+                sample.max_ms,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("ingest_failures", "ingest", 1, 0, 0),
+            ("provider_calls", "gemini-2.0-flash", 2, 200, 120),
+            ("provider_calls", "gpt-4o", 1, 300, 300),
+        ]
+    );
+
+    metrics::reset_metrics(db.pool()).await.expect("reset");
+    assert!(metrics::list_metrics(db.pool())
+        .await
+        .expect("list")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn metrics_render_in_the_prometheus_text_format() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    enable(&db).await;
+    metrics::record(
+        db.pool(),
+        Metric::Run,
+        "",
+        Some(Duration::from_millis(1500)),
+    )
+    .await
+    .expect("record");
+    metrics::record(db.pool(), Metric::RunFailure, "", None)
+        .await
+        .expect("record");
+    metrics::record(
+        db.pool(),
+        Metric::ProviderFailure,
+        "model \"x\"",
+        Some(Duration::from_millis(5)),
+    )
+    .await
+    .expect("record");
+
+    let text = prometheus_text(&metrics::list_metrics(db.pool()).await.expect("list"));
+    let lines = text.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"# TYPE vectorless_runs_total counter"));
+    assert!(lines.contains(&"vectorless_runs_total 1"));
+    assert!(lines.contains(&"vectorless_runs_seconds_total 1.500"));
+    assert!(lines.contains(&"vectorless_runs_seconds_max 1.500"));
+    assert!(lines.contains(&"vectorless_run_failures_total 1"));
+    assert!(!text.contains("vectorless_run_failures_seconds"));
+    assert!(lines.contains(&"vectorless_provider_failures_total{model=\"model \\\"x\\\"\"} 1"));
+    assert!(!text.contains("vectorless_ingests"));
+}
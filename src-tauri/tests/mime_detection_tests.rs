@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use vectorless_lib::sidecar::{
+    mime::{self, Format},
+    native_parser,
+};
+
+fn write_png(path: &Path) {
+    image::RgbImage::from_pixel(4, 3, image::Rgb([200, 30, 30]))
+        .save_with_format(path, image::ImageFormat::Png)
+        .expect("write png");
+}
+
+#[test]
+fn detection_overrides_a_wrong_hint_and_keeps_a_consistent_one() {
+    let dir = tempfile::tempdir().expect("tempdir");
+
+    let renamed_pdf = dir.path().join("statement.txt");
+    std::fs::write(&renamed_pdf, b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n1 0 obj\n").expect("write");
+    let resolved = mime::resolve(&renamed_pdf, "text/plain").expect("resolve");
+    assert_eq!(resolved.format, Format::Pdf);
+    assert_eq!(resolved.mime, "application/pdf");
+    assert_eq!(resolved.declared, "text/plain");
+
+    let notes = dir.path().join("notes.md");
+    std::fs::write(&notes, "# Notes\n\nShip on Friday.\n").expect("write");
+    let resolved = mime::resolve(&notes, "text/markdown").expect("resolve");
+    assert_eq!(resolved.format, Format::Text);
+    assert_eq!(resolved.mime, "text/markdown");
+    assert_eq!(resolved.detected.as_deref(), Some("text/plain"));
+
+    let plain_zip = dir.path().join("report.docx");
+    std::fs::write(&plain_zip, b"PK\x03\x04\x14\x00\x00\x00\x08\x00").expect("write");
+    let resolved = mime::resolve(
+        &plain_zip,
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    )
+    .expect("resolve");
+    assert_eq!(resolved.format, Format::Docx);
+}
+
+#[test]
+fn image_saved_as_text_is_parsed_as_an_image() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("chart.txt");
+    write_png(&path);
+
+    let payload = native_parser::parse(&path, "text/plain").expect("parses as image");
+
+    assert!(payload
+        .nodes
+        .iter()
+        .any(|node| node.text.contains("Dimensions: 4x3")));
+    assert_eq!(payload.document.metadata["mime"], "image/png");
+    assert_eq!(payload.document.metadata["declaredMime"], "text/plain");
+    assert_eq!(payload.document.metadata["detectedMime"], "image/png");
+}
+
+#[test]
+fn text_saved_as_pdf_is_parsed_as_text() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("minutes.pdf");
+    std::fs::write(
+        &path,
+        "Minutes\n\nThe committee agreed to move the launch to March.\n",
+    )
+    .expect("write");
+
+    let payload = native_parser::parse(&path, "application/pdf").expect("parses as text");
+
+    assert!(payload
+        .nodes
+        .iter()
+        .any(|node| node.text.contains("launch to March")));
+    let root = payload.nodes.first().expect("root");
+    assert_eq!(root.metadata["mime"], "text/plain");
+    assert_eq!(root.metadata["declaredMime"], "application/pdf");
+}
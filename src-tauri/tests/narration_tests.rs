@@ -0,0 +1,67 @@
+use vectorless_lib::{
+    core::types::{DocNodeSummary, NodeType},
+    reasoner::narration,
+};
+
+fn node(id: &str, document_id: &str, node_type: NodeType, title: &str) -> DocNodeSummary {
+    DocNodeSummary {
+        id: id.to_string(),
+        document_id: document_id.to_string(),
+        parent_id: None,
+        node_type,
+        title: title.to_string(),
+        text: String::new(),
+        ordinal_path: id.to_string(),
+        page_start: None,
+        page_end: None,
+    }
+}
+
+#[test]
+fn summaries_name_what_the_step_found() {
+    let sections = vec![
+        node("1", "doc-a", NodeType::Section, "Latency"),
+        node("2", "doc-a", NodeType::Section, "Caching"),
+        node("3", "doc-a", NodeType::Section, ""),
+        node("4", "doc-b", NodeType::Section, "Costs"),
+        node("5", "doc-b", NodeType::Section, "Limits"),
+    ];
+    assert_eq!(
+        narration::select_sections(&sections),
+        "Picked 5 section(s) that match the question: \"Latency\", \"Caching\", \"Costs\" and 1 more."
+    );
+    assert_eq!(
+        narration::drill_down(&sections[..2], 1),
+        "Opened 1 section(s) and found 2 passage(s); the closest match is \"Latency\"."
+    );
+
+    let evidence = vec![
+        node("p-1", "doc-a", NodeType::Paragraph, "¶ 1"),
+        node("t-1", "doc-b", NodeType::Table, "Table 1"),
+    ];
+    assert_eq!(
+        narration::extract_evidence(&evidence),
+        "Gathered 2 piece(s) of evidence from 2 document(s), including 1 table(s)."
+    );
+    assert_eq!(
+        narration::synthesize(1, 2),
+        "Drafted an answer citing 1 of 2 evidence piece(s)."
+    );
+}
+
+#[test]
+fn summaries_say_when_nothing_was_found_or_confidence_is_short() {
+    assert_eq!(
+        narration::select_sections(&[]),
+        "No section matched the question's terms."
+    );
+    assert_eq!(
+        narration::self_check(true, 0.42, 0.70),
+        "The answer holds up, but confidence 0.42 is below 0.70; looking in other sections."
+    );
+    assert_eq!(
+        narration::self_check(true, 0.81, 0.70),
+        "The answer holds up with confidence 0.81."
+    );
+    assert!(narration::self_check(false, 0.28, 0.70).contains("not backed"));
+}
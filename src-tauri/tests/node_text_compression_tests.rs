@@ -0,0 +1,116 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::db::{node_text, repositories::documents, Database};
+
+fn long_text(term: &str) -> String {
+    let sentence = format!("Quarterly {term} figures are reconciled against the ledger. ");
+    sentence.repeat(node_text::COMPRESS_MIN_BYTES / sentence.len() + 10)
+}
+
+async fn seed(db: &Database) {
+    let nodes = vec![
+        node("root-zstd", None, "Document", "short"),
+        node(
+            "para-zstd",
+            Some("root-zstd"),
+            "Paragraph",
+            &long_text("revenue"),
+        ),
+    ];
+    seed_document(db, "project-default", "doc-zstd", "Ledger.pdf", &nodes).await;
+}
+
+async fn stored_columns(db: &Database, node_id: &str) -> (String, Option<Vec<u8>>) {
+    sqlx::query_as("SELECT text, text_zstd FROM doc_nodes WHERE id = ?1")
+        .bind(node_id)
+        .fetch_one(db.pool())
+        .await
+        .expect("stored node")
+}
+
+#[test]
+fn short_text_stays_plain_and_long_text_round_trips() {
+    assert_eq!(
+        node_text::compress("a short paragraph").expect("compress"),
+        None
+    );
+    let text = long_text("margin");
+    let bytes = node_text::compress(&text)
+        .expect("compress")
+        .expect("long text compresses");
+    assert!(bytes.len() < text.len());
+    assert_eq!(node_text::decompress(&bytes).expect("decompress"), text);
+}
+
+#[tokio::test]
+async fn long_text_is_compressed_and_read_back_transparently() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let (text, compressed) = stored_columns(&db, "para-zstd").await;
+    assert_eq!(text, "");
+    assert!(compressed.is_some());
+    let (text, compressed) = stored_columns(&db, "root-zstd").await;
+    assert_eq!((text.as_str(), compressed), ("short", None));
+
+    let node = documents::get_node(db.pool(), "para-zstd")
+        .await
+        .expect("node");
+    assert_eq!(node.text, long_text("revenue"));
+    let tree = documents::get_tree(db.pool(), "doc-zstd", None, 4)
+        .await
+        .expect("tree");
+    assert!(tree.iter().any(|node| node.text == long_text("revenue")));
+
+    // The index holds the plain text, for full-text matches and for the
+    // substring fallback when those find nothing.
+    let hits = documents::search_project_nodes(db.pool(), "project-default", None, "revenue", 10)
+        .await
+        .expect("search");
+    assert_eq!(hits[0].id, "para-zstd");
+    let hits = documents::search_project_nodes(db.pool(), "project-default", None, "econcile", 10)
+        .await
+        .expect("substring search");
+    assert!(hits.iter().any(|hit| hit.id == "para-zstd"));
+}
+
+#[tokio::test]
+async fn text_stored_before_compression_can_be_compressed_later() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    sqlx::query(
+        r#"
+        INSERT INTO doc_nodes (id, document_id, parent_id, node_type, title, text, ordinal_path)
+        VALUES ('legacy-zstd', 'doc-zstd', 'root-zstd', 'Paragraph', 'Legacy', ?1, 'legacy')
+        "#,
+    )
+    .bind(long_text("forecast"))
+    .execute(db.pool())
+    .await
+    .expect("insert plain node");
+
+    let (compressed, saved) = documents::compress_stored_text(db.pool(), 100)
+        .await
+        .expect("compress");
+    assert_eq!(compressed, 1);
+    assert!(saved > 0);
+    let (text, bytes) = stored_columns(&db, "legacy-zstd").await;
+    assert_eq!(text, "");
+    assert!(bytes.is_some());
+    assert_eq!(
+        documents::compress_stored_text(db.pool(), 100)
+            .await
+            .expect("nothing left"),
+        (0, 0)
+    );
+
+    let node = documents::get_node(db.pool(), "legacy-zstd")
+        .await
+        .expect("node");
+    assert_eq!(node.text, long_text("forecast"));
+    let hits = documents::search_project_nodes(db.pool(), "project-default", None, "forecast", 10)
+        .await
+        .expect("search");
+    assert_eq!(hits[0].id, "legacy-zstd");
+}
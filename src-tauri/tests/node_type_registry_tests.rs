@@ -0,0 +1,160 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::{
+        errors::AppError,
+        types::{DocNodeSummary, NodeType, RankingWeights},
+    },
+    db::{
+        repositories::{documents, node_types},
+        Database,
+    },
+    reasoner::ranking,
+    sidecar::types::SidecarNode,
+};
+
+fn titled(title: &str, node: SidecarNode) -> SidecarNode {
+    SidecarNode {
+        title: title.to_string(),
+        ..node
+    }
+}
+
+async fn seeded() -> Database {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(
+        &db,
+        "project-default",
+        "doc-spec",
+        "Spec.pdf",
+        &[
+            titled("Spec", node("0", None, "Document", "Text of 0.")),
+            titled("Login", node("1", Some("0"), "Section", "Text of 1.")),
+            titled(
+                "Lockout",
+                node("1.1", Some("1"), "Requirement", "Text of 1.1."),
+            ),
+            titled(
+                "Lockout after five tries",
+                node("1.2", Some("1"), "TestCase", "Text of 1.2."),
+            ),
+        ],
+    )
+    .await;
+    db
+}
+
+#[test]
+fn custom_types_round_trip_under_their_key() {
+    assert_eq!(NodeType::from_str("Section"), NodeType::Section);
+    assert_eq!(NodeType::from_str(""), NodeType::Unknown);
+    assert_eq!(
+        NodeType::from_str("TestCase"),
+        NodeType::Custom("test_case".to_string())
+    );
+    assert_eq!(
+        NodeType::from_str("test case"),
+        NodeType::from_str("test-case")
+    );
+
+    let json =
+        serde_json::to_string(&[NodeType::Table, NodeType::from_str("Clause")]).expect("serialize");
+    assert_eq!(json, r#"["table","clause"]"#);
+    let parsed: Vec<NodeType> = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(parsed, vec![NodeType::Table, NodeType::from_str("clause")]);
+}
+
+#[tokio::test]
+async fn registry_lists_built_in_defined_and_parsed_types() {
+    let db = seeded().await;
+    let saved = node_types::save_node_type(
+        db.pool(),
+        "Requirement",
+        Some("Requirement"),
+        Some("A shall statement."),
+        Some("#c0392b"),
+    )
+    .await
+    .expect("save");
+    assert_eq!(saved.node_type, NodeType::from_str("requirement"));
+    assert_eq!(saved.node_count, 1);
+
+    let types = node_types::list_node_types(db.pool()).await.expect("list");
+    assert_eq!(types.iter().filter(|info| info.built_in).count(), 11);
+    let section = types
+        .iter()
+        .find(|info| info.node_type == NodeType::Section)
+        .expect("section");
+    assert_eq!(section.node_count, 1);
+    let custom = types
+        .iter()
+        .filter(|info| !info.built_in)
+        .map(|info| {
+            (
+                info.node_type.as_str(),
+                info.label.as_str(),
+                info.node_count,
+            )
+        })
+        .collect::<Vec<_>>();
+    // The test case type came from the parser and was never defined.
+    assert_eq!(
+        custom,
+        vec![
+            ("requirement", "Requirement", 1),
+            ("test_case", "Test Case", 1)
+        ]
+    );
+
+    assert!(matches!(
+        node_types::save_node_type(db.pool(), "Table", None, None, None).await,
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        node_types::save_node_type(db.pool(), "Clause", None, None, Some("red")).await,
+        Err(AppError::InvalidInput(_))
+    ));
+
+    assert!(node_types::delete_node_type(db.pool(), "requirement")
+        .await
+        .expect("delete"));
+    assert!(!node_types::delete_node_type(db.pool(), "requirement")
+        .await
+        .expect("delete again"));
+}
+
+#[tokio::test]
+async fn custom_types_are_weighted_and_exported_with_their_label() {
+    let db = seeded().await;
+    node_types::save_node_type(db.pool(), "requirement", Some("Req"), None, None)
+        .await
+        .expect("save");
+
+    let nodes = documents::get_document_preview(db.pool(), "doc-spec")
+        .await
+        .expect("tree");
+    let hits = nodes
+        .into_iter()
+        .filter(|node| node.node_type.is_custom())
+        .map(|node| (node, 1.0))
+        .collect::<Vec<(DocNodeSummary, f64)>>();
+    let weights: RankingWeights = serde_json::from_value(serde_json::json!({
+        "title": 2.0,
+        "text": 1.0,
+        "metadata": 1.0,
+        "nodeTypes": [{ "nodeType": "TestCase", "weight": 2.0 }]
+    }))
+    .expect("weights");
+    let ranked = ranking::rank(hits, vec![], "lockout", &weights);
+    assert_eq!(ranked[0].id, "1.2");
+
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("spec.md");
+    documents::export_markdown(db.pool(), "doc-spec", &path)
+        .await
+        .expect("export");
+    let markdown = std::fs::read_to_string(path).expect("read export");
+    assert!(markdown.contains("**Req: Lockout**\nText of 1.1."));
+    assert!(markdown.contains("**Test Case: Lockout after five tries**"));
+}
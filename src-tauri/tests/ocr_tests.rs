@@ -0,0 +1,163 @@
+#![cfg(feature = "ocr")]
+
+use lopdf::{dictionary, Document, Object, Stream};
+use vectorless_lib::sidecar::{native_parser, ocr};
+
+const TSV: &str =
+    "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t800\t1000\t-1\t
+2\t1\t1\t0\t0\t0\t40\t40\t300\t30\t-1\t
+5\t1\t1\t1\t1\t1\t40\t40\t120\t30\t96.5\tQuarterly
+5\t1\t1\t1\t1\t2\t170\t40\t120\t30\t93.5\tReport
+5\t1\t2\t1\t1\t1\t40\t100\t80\t20\t90\tRevenue
+5\t1\t2\t1\t1\t2\t130\t100\t80\t20\t88\tgrew
+5\t1\t2\t1\t1\t3\t220\t100\t80\t20\t80\tcon-
+5\t1\t2\t1\t2\t1\t40\t130\t80\t20\t92\tsiderably.
+5\t1\t2\t1\t2\t2\t130\t130\t80\t20\t-1\t
+";
+
+/// A one-page PDF drawing `image` as its only XObject.
+fn pdf_with_image(image: Stream) -> (Document, lopdf::ObjectId) {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let image_id = doc.add_object(image);
+    let content_id = doc.add_object(Stream::new(
+        dictionary! {},
+        b"q 100 0 0 100 0 0 cm /Im0 Do Q".to_vec(),
+    ));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "Resources" => dictionary! { "XObject" => dictionary! { "Im0" => image_id } },
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![Object::Reference(page_id)],
+            "Count" => 1,
+        }),
+    );
+    (doc, page_id)
+}
+
+fn image_stream(extra: lopdf::Dictionary, content: Vec<u8>) -> Stream {
+    let mut dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => 4,
+        "Height" => 2,
+        "ColorSpace" => "DeviceGray",
+        "BitsPerComponent" => 8,
+    };
+    for (key, value) in extra {
+        dict.set(key, value);
+    }
+    Stream::new(dict, content).with_compression(false)
+}
+
+#[test]
+fn tesseract_output_is_read_into_paragraphs_with_a_confidence() {
+    let text = ocr::parse_tsv(TSV);
+    assert_eq!(
+        text.paragraphs,
+        vec!["Quarterly Report", "Revenue grew considerably."]
+    );
+    assert_eq!(text.words, 6);
+    assert_eq!(text.confidence, Some(0.9));
+
+    let empty = ocr::parse_tsv("level\tpage_num\n");
+    assert!(empty.paragraphs.is_empty());
+    assert_eq!(empty.confidence, None);
+}
+
+#[test]
+fn raw_page_pictures_are_handed_over_as_png() {
+    let (doc, page_id) = pdf_with_image(image_stream(
+        dictionary! {},
+        vec![0, 64, 128, 255, 255, 128, 64, 0],
+    ));
+    let png = ocr::page_image(&doc, page_id).expect("raw gray picture");
+    let decoded = image::load_from_memory(&png).expect("png").to_luma8();
+    assert_eq!(decoded.dimensions(), (4, 2));
+    assert_eq!(decoded.get_pixel(1, 0).0, [64]);
+
+    // One bit per pixel, with the Decode array saying 1 is black.
+    let (doc, page_id) = pdf_with_image(image_stream(
+        dictionary! {
+            "BitsPerComponent" => 1,
+            "Decode" => vec![Object::Integer(1), Object::Integer(0)],
+        },
+        vec![0b1010_0000, 0b0101_0000],
+    ));
+    let png = ocr::page_image(&doc, page_id).expect("bilevel picture");
+    let decoded = image::load_from_memory(&png).expect("png").to_luma8();
+    assert_eq!(decoded.get_pixel(0, 0).0, [0]);
+    assert_eq!(decoded.get_pixel(1, 0).0, [255]);
+}
+
+#[test]
+fn jpeg_pages_pass_through_and_other_codecs_are_skipped() {
+    let jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0xFF, 0xD9];
+    let (doc, page_id) = pdf_with_image(image_stream(
+        dictionary! { "Filter" => "DCTDecode" },
+        jpeg.clone(),
+    ));
+    assert_eq!(ocr::page_image(&doc, page_id), Ok(jpeg));
+
+    let (doc, page_id) = pdf_with_image(image_stream(
+        dictionary! { "Filter" => "CCITTFaxDecode" },
+        vec![0; 8],
+    ));
+    let reason = ocr::page_image(&doc, page_id).expect_err("not decoded");
+    assert!(reason.contains("CCITTFaxDecode"));
+}
+
+/// Text in a picture is read into a section after its metadata. A stand-in
+/// for Tesseract prints [`TSV`] whatever it is given.
+#[cfg(unix)]
+#[test]
+fn images_get_a_text_section_read_by_ocr() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().expect("temp dir");
+    let tsv = dir.path().join("page.tsv");
+    std::fs::write(&tsv, TSV).expect("write tsv");
+    let tesseract = dir.path().join("tesseract");
+    std::fs::write(
+        &tesseract,
+        format!("#!/bin/sh\ncat > /dev/null\ncat '{}'\n", tsv.display()),
+    )
+    .expect("write script");
+    std::fs::set_permissions(&tesseract, std::fs::Permissions::from_mode(0o755))
+        .expect("make executable");
+    std::env::set_var("VECTORLESS_TESSERACT", &tesseract);
+
+    let path = dir.path().join("screenshot.png");
+    image::GrayImage::new(40, 20)
+        .save(&path)
+        .expect("save image");
+    let payload = native_parser::parse(&path, "image/png").expect("parses");
+
+    let sections = payload
+        .nodes
+        .iter()
+        .filter(|node| node.node_type == "Section")
+        .map(|node| node.title.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(sections, vec!["Image Metadata", "Quarterly Report"]);
+    let paragraph = payload
+        .nodes
+        .iter()
+        .find(|node| node.text == "Revenue grew considerably.")
+        .expect("OCR paragraph");
+    assert_eq!(paragraph.node_type, "Paragraph");
+    assert_eq!(paragraph.metadata["ocr"], true);
+    assert_eq!(paragraph.metadata["ocrConfidence"], 0.9);
+    assert!(payload
+        .nodes
+        .iter()
+        .any(|node| node.text.contains("Dimensions: 40x20 pixels")));
+    assert_eq!(payload.document.metadata["ocr"]["words"], 6);
+}
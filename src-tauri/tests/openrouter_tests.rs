@@ -0,0 +1,105 @@
+use vectorless_lib::{
+    core::types::{ModelPricing, ModelSelection, Provider, ProviderTimeoutSettings},
+    db::{repositories::settings, Database},
+    providers::{
+        gemini::parse_answer_output,
+        openrouter::{normalize_usage, parse_model},
+        provider_for_selection, ProviderResponse,
+    },
+};
+
+#[test]
+fn parses_model_listing_with_string_prices() {
+    let entry = serde_json::json!({
+        "id": "anthropic/claude-3.5-haiku",
+        "name": "Claude 3.5 Haiku",
+        "context_length": 200000,
+        "pricing": {"prompt": "0.0000008", "completion": "0.000004"}
+    });
+    let model = parse_model(&entry).expect("model should parse");
+    assert_eq!(model.id, "anthropic/claude-3.5-haiku");
+    assert_eq!(model.context_length, Some(200_000));
+    assert_eq!(
+        model.pricing,
+        Some(ModelPricing {
+            input_per_token: 0.0000008,
+            output_per_token: 0.000004,
+        })
+    );
+
+    let unpriced = parse_model(&serde_json::json!({"id": "x/y"})).expect("id is enough");
+    assert_eq!(unpriced.name, "x/y");
+    assert!(unpriced.pricing.is_none());
+    assert!(parse_model(&serde_json::json!({"name": "no id"})).is_none());
+}
+
+#[test]
+fn usage_is_normalized_and_priced_with_model_rates() {
+    let usage = normalize_usage(Some(&serde_json::json!({
+        "prompt_tokens": 1000,
+        "completion_tokens": 200,
+        "total_tokens": 1200
+    })));
+    assert_eq!(usage["promptTokenCount"], 1000);
+    assert_eq!(usage["candidatesTokenCount"], 200);
+    assert_eq!(usage["totalTokenCount"], 1200);
+
+    let response = ProviderResponse {
+        text: r#"{"answer_markdown":"ok","confidence":0.8,"citations":[]}"#.to_string(),
+        token_usage: usage,
+    };
+    let pricing = ModelPricing {
+        input_per_token: 0.000001,
+        output_per_token: 0.00001,
+    };
+    let output = parse_answer_output(&response, &pricing).expect("answer should parse");
+    assert!((output.estimated_cost_usd - 0.003).abs() < 1e-12);
+}
+
+#[test]
+fn openrouter_selection_carries_listing_metadata() {
+    let pricing = ModelPricing {
+        input_per_token: 0.000002,
+        output_per_token: 0.000008,
+    };
+    let provider = provider_for_selection(
+        &ModelSelection {
+            provider: Provider::OpenRouter,
+            model: "openai/gpt-4o-mini".to_string(),
+            context_length: Some(128_000),
+            pricing: Some(pricing),
+            planner: None,
+        },
+        &ProviderTimeoutSettings::default(),
+    )
+    .expect("provider should build");
+    assert_eq!(provider.model(), "openai/gpt-4o-mini");
+    assert_eq!(provider.context_window(), 128_000);
+    assert_eq!(provider.pricing(), pricing);
+}
+
+#[tokio::test]
+async fn model_selection_defaults_to_gemini_and_persists() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let initial: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING)
+            .await
+            .expect("default selection");
+    assert_eq!(initial, ModelSelection::default());
+
+    let selection = ModelSelection {
+        provider: Provider::OpenRouter,
+        model: "openai/gpt-4o-mini".to_string(),
+        context_length: Some(128_000),
+        pricing: None,
+        planner: None,
+    };
+    settings::set_setting(db.pool(), settings::MODEL_SELECTION_SETTING, &selection)
+        .await
+        .expect("store selection");
+    let stored: ModelSelection =
+        settings::get_setting(db.pool(), settings::MODEL_SELECTION_SETTING)
+            .await
+            .expect("stored selection");
+    assert_eq!(stored, selection);
+}
@@ -0,0 +1,117 @@
+use std::io::{Cursor, Write};
+
+use vectorless_lib::sidecar::native_parser;
+use zip::write::FileOptions;
+
+const SHEETS: usize = 8;
+
+/// Minimal workbook with inline-string cells, one small table per sheet.
+fn build_xlsx_bytes() -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::<u8>::new()));
+    let options: FileOptions<'_, ()> = FileOptions::default();
+
+    let mut content_types = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    );
+    let mut sheets = String::new();
+    let mut rels = String::new();
+    for i in 1..=SHEETS {
+        content_types.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{i}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#
+        ));
+        sheets.push_str(&format!(
+            r#"<sheet name="Region {i}" sheetId="{i}" r:id="rId{i}"/>"#
+        ));
+        rels.push_str(&format!(
+            r#"<Relationship Id="rId{i}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{i}.xml"/>"#
+        ));
+    }
+    content_types.push_str("</Types>");
+
+    zip.start_file("[Content_Types].xml", options)
+        .expect("start file");
+    zip.write_all(content_types.as_bytes()).expect("write");
+    zip.start_file("_rels/.rels", options).expect("start file");
+    zip.write_all(
+        br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>
+</Relationships>"#,
+    )
+    .expect("write");
+    zip.start_file("xl/workbook.xml", options)
+        .expect("start file");
+    zip.write_all(
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <sheets>{sheets}</sheets>
+</workbook>"#
+        )
+        .as_bytes(),
+    )
+    .expect("write");
+    zip.start_file("xl/_rels/workbook.xml.rels", options)
+        .expect("start file");
+    zip.write_all(
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{rels}</Relationships>"#
+        )
+        .as_bytes(),
+    )
+    .expect("write");
+    for i in 1..=SHEETS {
+        zip.start_file(format!("xl/worksheets/sheet{i}.xml"), options)
+            .expect("start file");
+        zip.write_all(
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <sheetData>
+    <row r="1"><c r="A1" t="inlineStr"><is><t>Quarter</t></is></c><c r="B1" t="inlineStr"><is><t>Sales</t></is></c></row>
+    <row r="2"><c r="A2" t="inlineStr"><is><t>Q1</t></is></c><c r="B2"><v>{}</v></c></row>
+  </sheetData>
+</worksheet>"#,
+                i * 100
+            )
+            .as_bytes(),
+        )
+        .expect("write");
+    }
+
+    zip.finish().expect("finish zip").into_inner()
+}
+
+#[test]
+fn workbook_sheets_parse_in_workbook_order() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("regions.xlsx");
+    std::fs::write(&path, build_xlsx_bytes()).expect("write xlsx");
+
+    for _ in 0..3 {
+        let payload = native_parser::parse(
+            &path,
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        )
+        .expect("xlsx parses");
+        let headings = payload
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == "Section")
+            .map(|node| node.title.clone())
+            .collect::<Vec<_>>();
+        let expected = (1..=SHEETS)
+            .map(|i| format!("Sheet: Region {i}"))
+            .collect::<Vec<_>>();
+        assert_eq!(headings, expected);
+        assert!(payload
+            .nodes
+            .iter()
+            .any(|node| node.text.contains(&(SHEETS * 100).to_string())));
+    }
+}
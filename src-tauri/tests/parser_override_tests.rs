@@ -0,0 +1,79 @@
+use vectorless_lib::{
+    core::types::{ParserKind, ParserOverride},
+    sidecar::{
+        native_parser::{self, ParseLimits},
+        types::NormalizedPayload,
+    },
+};
+
+const README: &str = "# Install\nDownload the release.\n\nSummary\n\nIt installs in a minute.\n\n\
+```sh\ncurl -O https://example.com/tool.tgz\n\n# unpack it\ntar xzf tool.tgz\n```\n\n## Usage\n\nRun `tool --help`.\n";
+
+fn parse(contents: &str, parser_override: &ParserOverride) -> NormalizedPayload {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("README.md");
+    std::fs::write(&path, contents).expect("write");
+    native_parser::parse_with_progress(
+        &path,
+        "text/markdown",
+        ParseLimits::default(),
+        parser_override,
+        &mut |_, _| {},
+    )
+    .expect("parses")
+}
+
+fn section_titles(payload: &NormalizedPayload) -> Vec<&str> {
+    payload
+        .nodes
+        .iter()
+        .filter(|node| node.node_type == "Section")
+        .map(|node| node.title.as_str())
+        .collect()
+}
+
+#[test]
+fn markdown_strict_only_splits_on_headings_outside_code() {
+    let strict = ParserOverride {
+        parser: Some(ParserKind::MarkdownStrict),
+        ..ParserOverride::default()
+    };
+
+    let payload = parse(README, &strict);
+
+    assert_eq!(section_titles(&payload), vec!["Install", "Usage"]);
+    let text = payload
+        .nodes
+        .iter()
+        .map(|node| node.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(text.contains("# unpack it"));
+    assert!(text.contains("Summary"));
+}
+
+#[test]
+fn heuristic_parse_is_unchanged_without_an_override() {
+    let payload = parse(README, &ParserOverride::default());
+
+    let titles = section_titles(&payload);
+    assert!(titles.contains(&"Summary"));
+    assert!(titles.iter().any(|title| title.starts_with("unpack it")));
+    assert!(payload.document.metadata.get("parserOverride").is_none());
+}
+
+#[test]
+fn override_is_recorded_in_metadata() {
+    let plain = ParserOverride {
+        parser: Some(ParserKind::PlainText),
+        pdf_columns: Some(false),
+        ..ParserOverride::default()
+    };
+
+    let payload = parse(README, &plain);
+
+    let root = payload.nodes.first().expect("root");
+    assert_eq!(root.metadata["parserOverride"]["parser"], "plainText");
+    assert_eq!(root.metadata["parserOverride"]["pdfColumns"], false);
+    assert!(root.metadata["parserOverride"]["pdfBoilerplate"].is_null());
+}
@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use vectorless_lib::core::{
+    errors::AppError,
+    paths::{self, extend_str, simplify_str},
+};
+
+#[test]
+fn input_paths_lose_whitespace_and_copied_quotes() {
+    assert_eq!(
+        paths::from_input("  \"C:\\Users\\Zoë\\Q3 report.pdf\" \n").expect("path"),
+        Path::new("C:\\Users\\Zoë\\Q3 report.pdf")
+    );
+    assert_eq!(
+        paths::from_input("/tmp/notes.md").expect("path"),
+        Path::new("/tmp/notes.md")
+    );
+    assert!(matches!(
+        paths::from_input(" \"\" "),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn exotic_file_names_resolve_to_the_same_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for name in [
+        "Résumé – final (2).pdf",
+        "数据 报告.md",
+        "emoji 📈 growth.txt",
+        "#hash&amp; 'quoted'.csv",
+        " leading space.md",
+    ] {
+        let file = dir.path().join(name);
+        std::fs::write(&file, "content").expect("write");
+        let raw = file.to_str().expect("utf-8 temp path");
+
+        let resolved = paths::resolve_input(raw).expect(name);
+        assert!(resolved.is_absolute(), "{name}");
+        assert_eq!(
+            resolved.file_name().and_then(|name| name.to_str()),
+            Some(name)
+        );
+        assert_eq!(std::fs::read_to_string(&resolved).expect(name), "content");
+        assert_eq!(
+            paths::resolve_input(&format!("\"{raw}\"")).expect(name),
+            resolved
+        );
+    }
+
+    let missing = paths::resolve_input(&dir.path().join("gone.pdf").to_string_lossy());
+    assert!(matches!(missing, Err(AppError::NotFound(message)) if message.contains("gone.pdf")));
+}
+
+#[test]
+fn verbatim_prefixes_are_dropped_only_when_safe() {
+    assert_eq!(
+        simplify_str(r"\\?\C:\Users\Zoë\report.pdf").as_deref(),
+        Some(r"C:\Users\Zoë\report.pdf")
+    );
+    assert_eq!(
+        simplify_str(r"\\?\UNC\fileserver\team\Q3\report.pdf").as_deref(),
+        Some(r"\\fileserver\team\Q3\report.pdf")
+    );
+    // Already plain, a device path, reserved names, trailing dots and
+    // lengths past MAX_PATH need the prefix or are not verbatim.
+    assert_eq!(simplify_str(r"C:\Users\report.pdf"), None);
+    assert_eq!(simplify_str(r"\\?\Volume{1234}\report.pdf"), None);
+    assert_eq!(simplify_str(r"\\?\C:\share\CON.txt"), None);
+    assert_eq!(simplify_str(r"\\?\C:\share\notes."), None);
+    let long = format!(r"\\?\C:\{}\report.pdf", "a".repeat(300));
+    assert_eq!(simplify_str(&long), None);
+}
+
+#[test]
+fn long_windows_paths_gain_the_verbatim_prefix() {
+    let folder = "very long folder name ".repeat(12);
+    let drive = format!(r"C:\{folder}\report.pdf");
+    assert_eq!(
+        extend_str(&drive).as_deref(),
+        Some(format!(r"\\?\C:\{folder}\report.pdf").as_str())
+    );
+    let unc = format!(r"\\fileserver\team\{folder}/report.pdf");
+    assert_eq!(
+        extend_str(&unc).as_deref(),
+        Some(format!(r"\\?\UNC\fileserver\team\{folder}\report.pdf").as_str())
+    );
+
+    assert_eq!(extend_str(r"C:\short\report.pdf"), None);
+    assert_eq!(extend_str(&format!(r"\\?\C:\{folder}")), None);
+    assert_eq!(extend_str(&format!("relative/{folder}")), None);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn names_that_are_not_unicode_are_skipped_not_mangled() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    use vectorless_lib::{core::types::IngestRules, sidecar::ignore::scan_directory};
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::write(dir.path().join(OsStr::from_bytes(b"report-\xff.md")), "x").expect("write");
+    std::fs::write(dir.path().join("notes.md"), "x").expect("write");
+
+    let scan = scan_directory(dir.path(), &IngestRules::default()).expect("scan");
+    assert_eq!(scan.files.len(), 1);
+    assert!(scan.files[0].path.ends_with("notes.md"));
+    assert_eq!(scan.skipped.len(), 1);
+    assert_eq!(scan.skipped[0].reason, "name is not valid Unicode");
+    assert!(scan.skipped[0].path.ends_with("report-\u{FFFD}.md"));
+
+    let invalid = dir.path().join(OsStr::from_bytes(b"report-\xff.md"));
+    assert!(matches!(
+        paths::to_utf8(&invalid),
+        Err(AppError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn export_names_are_safe_on_every_platform() {
+    assert_eq!(paths::sanitize_file_name("Q3 Report.pdf"), "Q3 Report");
+    assert_eq!(
+        paths::sanitize_file_name("Plan: A/B <draft>?"),
+        "Plan_ A_B _draft__"
+    );
+    assert_eq!(
+        paths::sanitize_file_name("  Notes\n\tv1.2 final  "),
+        "Notes v1.2 final"
+    );
+    assert_eq!(
+        paths::sanitize_file_name("Ends with dots..."),
+        "Ends with dots"
+    );
+    assert_eq!(paths::sanitize_file_name("con.txt"), "con_");
+    assert_eq!(paths::sanitize_file_name(" ... "), "document");
+    assert_eq!(
+        paths::sanitize_file_name(&"é".repeat(300)).chars().count(),
+        paths::MAX_FILE_NAME_CHARS
+    );
+}
+
+#[test]
+fn export_paths_avoid_existing_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let first = paths::available_path(dir.path(), "Report", "md");
+    assert_eq!(first, dir.path().join("Report.md"));
+
+    std::fs::write(&first, "old").expect("write");
+    let second = paths::available_path(dir.path(), "Report", "md");
+    assert_eq!(second, dir.path().join("Report (2).md"));
+
+    std::fs::write(&second, "old").expect("write");
+    assert_eq!(
+        paths::available_path(dir.path(), "Report", "md"),
+        dir.path().join("Report (3).md")
+    );
+}
@@ -0,0 +1,84 @@
+use vectorless_lib::sidecar::pdf_text::{is_page_number, Boilerplate};
+
+fn page(number: usize, body: &str) -> String {
+    format!("ACME Corp — Annual Report 2024\n\n{body}\n\nConfidential\nPage {number} of 12")
+}
+
+#[test]
+fn repeated_headers_footers_and_page_numbers_are_stripped() {
+    let bodies = [
+        "Letter from the chair.",
+        "Highlights of the year.",
+        "Operating review by region.",
+        "Risks and uncertainties.",
+        "Board of directors.",
+        "Financial statements.",
+    ];
+    let pages = bodies
+        .iter()
+        .enumerate()
+        .map(|(index, body)| page(index + 1, body))
+        .collect::<Vec<_>>();
+    let boilerplate = Boilerplate::detect(&pages.iter().map(String::as_str).collect::<Vec<_>>());
+
+    assert_eq!(
+        boilerplate.samples(),
+        vec![
+            "ACME Corp — Annual Report 2024",
+            "Confidential",
+            "Page 1 of 12"
+        ]
+    );
+
+    let (text, removed) = boilerplate.strip(&page(9, "Revenue grew 12% in 2024."));
+    assert_eq!(text.trim(), "Revenue grew 12% in 2024.");
+    assert_eq!(removed, 3);
+}
+
+#[test]
+fn lines_repeated_on_too_few_pages_are_kept() {
+    let pages = [
+        "Draft\nIntroduction",
+        "Draft\nMethods",
+        "Results\nDiscussion",
+        "Appendix\nReferences",
+    ];
+    let boilerplate = Boilerplate::detect(&pages);
+
+    assert!(boilerplate.is_empty());
+    assert_eq!(boilerplate.strip("Draft\nIntroduction").1, 0);
+}
+
+#[test]
+fn body_lines_away_from_the_page_edges_are_left_alone() {
+    let pages = (1..=4)
+        .map(|n| format!("Header\nIntro {n}\nsee below\nHeader\nmore\ntext"))
+        .collect::<Vec<_>>();
+    let boilerplate = Boilerplate::detect(&pages.iter().map(String::as_str).collect::<Vec<_>>());
+
+    let (text, _) = boilerplate.strip("Header\na\nb\nHeader\nc\nd");
+    assert_eq!(text, "a\nb\nHeader\nc\nd");
+}
+
+#[test]
+fn page_number_lines_are_recognised() {
+    for line in [
+        "12",
+        "- 12 -",
+        "Page 12",
+        "page 3 of 40",
+        "12 / 40",
+        "  xiv  ",
+    ] {
+        assert!(is_page_number(line), "{line:?}");
+    }
+    for line in [
+        "12 apples",
+        "Pages 3",
+        "Chapter 4",
+        "",
+        "2024 was a good year",
+    ] {
+        assert!(!is_page_number(line), "{line:?}");
+    }
+}
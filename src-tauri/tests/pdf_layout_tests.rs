@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Document, Object, Stream,
+};
+use vectorless_lib::sidecar::{
+    native_parser,
+    pdf_layout::{reading_order, Glyph},
+};
+
+const PAGE_WIDTH: f64 = 612.0;
+
+/// Lays `text` out in 10pt monospace starting at (`x`, `y`).
+fn line(text: &str, x: f64, y: f64) -> Vec<Glyph> {
+    text.chars()
+        .enumerate()
+        .map(|(index, c)| Glyph {
+            x: x + index as f64 * 6.0,
+            y,
+            width: 6.0,
+            size: 10.0,
+            text: c.to_string(),
+        })
+        .collect()
+}
+
+fn two_column_page() -> Vec<Glyph> {
+    let left = [
+        "Left one: the study starts here and runs",
+        "Left two: across several lines of text",
+        "Left three: before it reaches the end.",
+        "Left four: the first column closes now.",
+    ];
+    let right = [
+        "Right one: the second column carries on",
+        "Right two: from where the first left off",
+        "Right three: and finishes the argument.",
+    ];
+    let mut glyphs = line("A Study of Column Layouts", 200.0, 740.0);
+    for (index, text) in left.iter().enumerate() {
+        glyphs.extend(line(text, 50.0, 700.0 - index as f64 * 12.0));
+    }
+    for (index, text) in right.iter().enumerate() {
+        glyphs.extend(line(text, 320.0, 700.0 - index as f64 * 12.0));
+    }
+    glyphs.extend(line("3", 303.0, 40.0));
+    glyphs
+}
+
+#[test]
+fn two_columns_are_read_left_then_right() {
+    let text = reading_order(&two_column_page(), PAGE_WIDTH);
+
+    assert_eq!(
+        text,
+        "A Study of Column Layouts\n\n\
+         Left one: the study starts here and runs\n\
+         Left two: across several lines of text\n\
+         Left three: before it reaches the end.\n\
+         Left four: the first column closes now.\n\n\
+         Right one: the second column carries on\n\
+         Right two: from where the first left off\n\
+         Right three: and finishes the argument.\n\n\
+         3"
+    );
+}
+
+#[test]
+fn single_column_pages_keep_line_order() {
+    let mut glyphs = Vec::new();
+    for (index, text) in ["First line of the page.", "Second line.", "Third line."]
+        .iter()
+        .enumerate()
+    {
+        glyphs.extend(line(text, 72.0, 700.0 - index as f64 * 12.0));
+    }
+    glyphs.extend(line("New paragraph after a gap.", 72.0, 640.0));
+
+    assert_eq!(
+        reading_order(&glyphs, PAGE_WIDTH),
+        "First line of the page.\nSecond line.\nThird line.\n\nNew paragraph after a gap."
+    );
+}
+
+#[test]
+fn narrow_label_columns_are_not_treated_as_a_layout() {
+    let mut glyphs = Vec::new();
+    for (index, (label, value)) in [("Name", "Ada"), ("Role", "Analyst"), ("Team", "Risk")]
+        .iter()
+        .enumerate()
+    {
+        let y = 700.0 - index as f64 * 12.0;
+        glyphs.extend(line(label, 72.0, y));
+        glyphs.extend(line(value, 400.0, y));
+    }
+
+    assert_eq!(
+        reading_order(&glyphs, PAGE_WIDTH),
+        "Name Ada\nRole Analyst\nTeam Risk"
+    );
+}
+
+/// Writes a one-page PDF with each `(x, y, text)` drawn in 10pt Courier.
+fn write_positioned_pdf(path: &Path, lines: &[(i64, i64, &str)]) {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+    let mut operations = Vec::new();
+    for (x, y, text) in lines {
+        operations.extend([
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 10.into()]),
+            Operation::new("Td", vec![(*x).into(), (*y).into()]),
+            Operation::new("Tj", vec![Object::string_literal(*text)]),
+            Operation::new("ET", vec![]),
+        ]);
+    }
+    let content_id = doc.add_object(Stream::new(
+        dictionary! {},
+        Content { operations }.encode().expect("encode content"),
+    ));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).expect("save pdf");
+}
+
+#[test]
+fn interleaved_content_stream_is_parsed_in_column_order() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("paper.pdf");
+    // Drawn row by row across both columns, the way many typesetters emit them.
+    let mut lines = Vec::new();
+    for row in 0..6 {
+        let y = 700 - row * 12;
+        lines.push((50, y, "alpha alpha alpha alpha alpha alpha"));
+        lines.push((320, y, "omega omega omega omega omega omega"));
+    }
+    write_positioned_pdf(&path, &lines);
+
+    let payload = native_parser::parse(&path, "application/pdf").expect("pdf parses");
+    let text = payload
+        .nodes
+        .iter()
+        .map(|node| node.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let last_alpha = text.rfind("alpha").expect("left column");
+    let first_omega = text.find("omega").expect("right column");
+    assert!(last_alpha < first_omega, "{text}");
+}
@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use lopdf::{
+    content::{Content, Operation},
+    dictionary, Document, Object, Stream,
+};
+use vectorless_lib::{
+    core::types::ParserOverride,
+    sidecar::native_parser::{self, ParseLimits},
+};
+
+/// Writes a PDF with one line of Courier text per page.
+fn write_pdf(path: &Path, pages: &[&str]) {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+    let mut kids = Vec::new();
+    for text in pages {
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                Operation::new("Td", vec![72.into(), 720.into()]),
+                Operation::new("Tj", vec![Object::string_literal(*text)]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        let content_id = doc.add_object(Stream::new(
+            dictionary! {},
+            content.encode().expect("encode content"),
+        ));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        kids.push(page_id.into());
+    }
+    let count = kids.len() as i64;
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).expect("save pdf");
+}
+
+#[test]
+fn pdf_pages_are_parsed_one_at_a_time_with_progress() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("report.pdf");
+    write_pdf(
+        &path,
+        &[
+            "Revenue grew in every region this year.",
+            "Costs were flat apart from travel.",
+            "The board approved the new budget.",
+        ],
+    );
+
+    let mut progress = Vec::new();
+    let payload = native_parser::parse_with_progress(
+        &path,
+        "application/pdf",
+        ParseLimits::default(),
+        &ParserOverride::default(),
+        &mut |done, total| progress.push((done, total)),
+    )
+    .expect("pdf parses");
+
+    assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+    assert_eq!(payload.document.pages, 3);
+    let text = payload
+        .nodes
+        .iter()
+        .map(|node| node.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(text.contains("Revenue grew"));
+    assert!(text.contains("approved the new budget"));
+}
+
+#[test]
+fn pdf_without_text_is_rejected() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("blank.pdf");
+    write_pdf(&path, &[""]);
+
+    assert!(native_parser::parse(&path, "application/pdf").is_err());
+}
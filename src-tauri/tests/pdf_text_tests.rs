@@ -0,0 +1,41 @@
+use vectorless_lib::sidecar::pdf_text::normalize_block;
+
+#[test]
+fn wrapped_lines_are_joined_and_hyphenation_repaired() {
+    let block = "The committee reviewed the inter-\nnational  shipping   rules and\nagreed on a new sched-\nule for Q3.";
+    assert_eq!(
+        normalize_block(block),
+        "The committee reviewed the international shipping rules and agreed on a new schedule for Q3."
+    );
+}
+
+#[test]
+fn real_hyphens_at_line_ends_are_kept() {
+    assert_eq!(
+        normalize_block("A joint Franco-\nGerman project"),
+        "A joint Franco-German project"
+    );
+    assert_eq!(
+        normalize_block("Released as version-\n2 last week"),
+        "Released as version-2 last week"
+    );
+}
+
+#[test]
+fn list_items_keep_their_line_breaks() {
+    let block = "Requirements:\n• a valid passport\n• two photos taken\nwithin six months\n1. Submit the form\n2) Pay the fee";
+    assert_eq!(
+        normalize_block(block),
+        "Requirements:\n• a valid passport\n• two photos taken within six months\n1. Submit the form\n2) Pay the fee"
+    );
+}
+
+#[test]
+fn ligatures_soft_hyphens_and_odd_whitespace_are_cleaned() {
+    assert_eq!(
+        normalize_block(
+            "The \u{FB01}nal e\u{FB00}ect\u{00A0}was\tsigni\u{00AD}ficant\nand perma\u{00AD}\nnent"
+        ),
+        "The final effect was significant and permanent"
+    );
+}
@@ -1,5 +1,9 @@
-use vectorless_lib::reasoner::planner::{
-    Planner, PlannerConfig, PlannerDecision, PlannerInput, StepType,
+use vectorless_lib::{
+    core::types::QueryClass,
+    reasoner::{
+        planner::{BranchOutcome, Planner, PlannerConfig, PlannerDecision, PlannerInput, StepType},
+        prompts::planner_prompt,
+    },
 };
 
 #[test]
@@ -7,8 +11,10 @@ fn planner_emits_default_reasoning_sequence() {
     let planner = Planner::new(PlannerConfig::default());
     let input = PlannerInput {
         query: "What are the limitations of this approach?".to_string(),
+        query_class: QueryClass::Factual,
         last_confidence: None,
         explored_sections: vec![],
+        branch_outcomes: vec![],
         has_evidence: false,
         step_count: 0,
         backtrack_count: 0,
@@ -30,13 +36,58 @@ fn planner_emits_default_reasoning_sequence() {
     );
 }
 
+#[test]
+fn query_class_picks_the_first_pass() {
+    let planner = Planner::new(PlannerConfig::default());
+    let kinds = |query: &str, query_class: QueryClass| {
+        let input = PlannerInput {
+            query: query.to_string(),
+            query_class,
+            last_confidence: None,
+            explored_sections: vec![],
+            branch_outcomes: vec![],
+            has_evidence: false,
+            step_count: 0,
+            backtrack_count: 0,
+        };
+        planner
+            .next_steps(&input)
+            .steps
+            .into_iter()
+            .map(|step| step.step_type)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        kinds(
+            "What did the invoices cost in total?",
+            QueryClass::Aggregation
+        ),
+        vec![
+            StepType::ScanRoot,
+            StepType::SelectSections,
+            StepType::DrillDown,
+            StepType::ExtractEvidence,
+            StepType::Aggregate,
+            StepType::Synthesize,
+            StepType::SelfCheck,
+        ]
+    );
+    assert!(
+        !kinds("Summarize the incident report", QueryClass::Summary).contains(&StepType::DrillDown)
+    );
+    assert!(!kinds("What is the p99 latency?", QueryClass::Factual).contains(&StepType::Aggregate));
+}
+
 #[test]
 fn planner_backtracks_when_confidence_is_low() {
     let planner = Planner::new(PlannerConfig::default());
     let input = PlannerInput {
         query: "Find the read latency limit".to_string(),
+        query_class: QueryClass::Factual,
         last_confidence: Some(0.42),
         explored_sections: vec!["2.0 Overview".to_string()],
+        branch_outcomes: vec![],
         has_evidence: true,
         step_count: 5,
         backtrack_count: 0,
@@ -61,8 +112,10 @@ fn planner_stops_after_max_steps() {
 
     let input = PlannerInput {
         query: "Any query".to_string(),
+        query_class: QueryClass::Factual,
         last_confidence: Some(0.20),
         explored_sections: vec![],
+        branch_outcomes: vec![],
         has_evidence: false,
         step_count: 6,
         backtrack_count: 0,
@@ -73,3 +126,57 @@ fn planner_stops_after_max_steps() {
     assert_eq!(plan.decision, PlannerDecision::Stop);
     assert!(plan.steps.is_empty());
 }
+
+#[test]
+fn backtracking_skips_branches_that_were_mined_without_help() {
+    let planner = Planner::new(PlannerConfig::default());
+    let outcome = |branch: &str, nodes: &[&str], cited: usize| BranchOutcome {
+        branch_id: branch.to_string(),
+        node_ids: nodes.iter().map(ToString::to_string).collect(),
+        cited,
+        confidence: 0.42,
+    };
+    let input = PlannerInput {
+        query: "Find the read latency limit".to_string(),
+        query_class: QueryClass::Factual,
+        last_confidence: Some(0.42),
+        explored_sections: vec!["2.0 Overview".to_string()],
+        branch_outcomes: vec![
+            outcome("sec-overview", &["p-1", "p-2"], 0),
+            outcome("sec-limits", &["p-7", "p-8"], 1),
+        ],
+        has_evidence: true,
+        step_count: 5,
+        backtrack_count: 0,
+    };
+
+    let exhausted = planner.exhausted_branches(&input);
+    assert!(exhausted.contains("sec-overview"));
+    assert!(exhausted.contains("p-2"));
+    assert!(!exhausted.contains("sec-limits"));
+    assert!(!exhausted.contains("p-7"));
+    assert_eq!(input.branch_outcomes[1].usefulness(), 0.5);
+
+    let plan = planner.next_steps(&input);
+    assert_eq!(plan.decision, PlannerDecision::Backtrack);
+    let objective = &plan.steps[0].objective;
+    assert!(
+        objective.contains("skipping 1 exhausted branch(es)"),
+        "{objective}"
+    );
+
+    let prompt = planner_prompt(&input);
+    assert!(prompt.contains(
+        "branchOutcomes:\n- sec-overview: tried 2 node(s), cited 0, usefulness 0.00, confidence 0.42\n"
+    ));
+
+    // A confident answer leaves nothing exhausted, cited or not.
+    let confident = PlannerInput {
+        branch_outcomes: vec![BranchOutcome {
+            confidence: 0.9,
+            ..outcome("sec-overview", &["p-1"], 0)
+        }],
+        ..input
+    };
+    assert!(planner.exhausted_branches(&confident).is_empty());
+}
@@ -0,0 +1,173 @@
+use std::path::Path;
+
+use vectorless_lib::{
+    core::{
+        errors::{AppError, AppResult},
+        policy,
+        types::{
+            AdminPolicy, KeySelection, PolicyFeature, PrivacySettings, Provider, RetrievalSettings,
+        },
+    },
+    db::{
+        repositories::{provider_keys, settings},
+        Database,
+    },
+};
+
+const CONFIG: &str = r#"
+offline = true
+disabled_features = ["shared_bundles", "external_parser"]
+locked = ["privacy", "provider_timeouts"]
+
+[endpoints]
+gemini = "https://llm-gateway.example.com/gemini/v1beta/"
+
+[settings.privacy]
+redactText = true
+
+[settings.retrieval]
+fuzziness = 2
+
+[settings.external_parser]
+enabled = true
+command = "/opt/docling/bin/docling"
+"#;
+
+fn parse(text: &str) -> AppResult<AdminPolicy> {
+    policy::parse(text, Path::new("vectorless.config.toml"))
+}
+
+#[test]
+fn the_config_file_sets_endpoints_features_and_locks() {
+    let admin = parse(CONFIG).expect("config");
+    assert!(admin.offline);
+    assert!(matches!(
+        admin.ensure_online(),
+        Err(AppError::PolicyRestricted(_))
+    ));
+    assert_eq!(
+        admin.endpoint(&Provider::Gemini, "https://default"),
+        "https://llm-gateway.example.com/gemini/v1beta"
+    );
+    assert_eq!(
+        admin.endpoint(&Provider::OpenRouter, "https://default"),
+        "https://default"
+    );
+    assert!(admin.ensure_enabled(PolicyFeature::Import).is_ok());
+    assert!(admin.ensure_enabled(PolicyFeature::SharedBundles).is_err());
+
+    // Turning the external parser off locks it at its default.
+    assert_eq!(
+        admin.locked_settings,
+        vec!["external_parser", "privacy", "provider_timeouts"]
+    );
+    assert!(!admin.presets.contains_key("external_parser"));
+    assert!(admin.ensure_unlocked("retrieval").is_ok());
+    let err = admin.ensure_unlocked("privacy").expect_err("locked");
+    assert_eq!(err.code(), "POLICY_RESTRICTED");
+    settings::validate_policy(&admin).expect("valid presets");
+
+    let unrestricted = parse("").expect("empty config");
+    assert!(unrestricted.ensure_online().is_ok());
+    assert!(unrestricted.locked_settings.is_empty());
+}
+
+#[test]
+fn mistakes_in_the_config_file_are_refused() {
+    assert!(parse("ofline = true").is_err());
+    assert!(parse("disabled_features = [\"url_ingestion\"]").is_err());
+    assert!(parse("[endpoints]\ngemini = \"llm-gateway.example.com\"").is_err());
+    assert!(parse("[endpoints]\nopen_router = \"https://gateway\"").is_err());
+
+    let unknown = parse("locked = [\"theme\"]").expect("parses");
+    assert!(settings::validate_policy(&unknown).is_err());
+    let misshapen = parse("[settings.retrieval]\nfuzziness = \"high\"").expect("parses");
+    assert!(settings::validate_policy(&misshapen).is_err());
+}
+
+#[tokio::test]
+async fn locked_settings_are_enforced_and_presets_fill_gaps() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let admin = parse(CONFIG).expect("config");
+    settings::set_setting(
+        db.pool(),
+        settings::PRIVACY_SETTING,
+        &PrivacySettings { redact_text: false },
+    )
+    .await
+    .expect("user privacy");
+    settings::set_setting(
+        db.pool(),
+        settings::RETRIEVAL_SETTING,
+        &RetrievalSettings {
+            fuzziness: 1,
+            ..RetrievalSettings::default()
+        },
+    )
+    .await
+    .expect("user retrieval");
+
+    settings::apply_policy(db.pool(), &admin)
+        .await
+        .expect("apply");
+    let privacy: PrivacySettings = settings::get_setting(db.pool(), settings::PRIVACY_SETTING)
+        .await
+        .expect("privacy");
+    assert!(privacy.redact_text);
+    // Unlocked presets do not replace what the user chose.
+    let retrieval: RetrievalSettings =
+        settings::get_setting(db.pool(), settings::RETRIEVAL_SETTING)
+            .await
+            .expect("retrieval");
+    assert_eq!(retrieval.fuzziness, 1);
+    let parser: serde_json::Value =
+        settings::get_setting(db.pool(), settings::EXTERNAL_PARSER_SETTING)
+            .await
+            .expect("parser");
+    assert_eq!(parser["enabled"], false);
+
+    let fresh = Database::in_memory().await.expect("db should initialize");
+    settings::apply_policy(fresh.pool(), &admin)
+        .await
+        .expect("apply");
+    let retrieval: RetrievalSettings =
+        settings::get_setting(fresh.pool(), settings::RETRIEVAL_SETTING)
+            .await
+            .expect("retrieval");
+    assert_eq!(retrieval.fuzziness, 2);
+}
+
+#[tokio::test]
+async fn keys_key_selection_and_generation_settings_can_be_locked() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let admin = parse(
+        r#"
+locked = ["provider_keys", "generation", "provider_key_selection"]
+
+[settings]
+provider_key_selection = "quota_aware"
+"#,
+    )
+    .expect("config");
+    settings::validate_policy(&admin).expect("valid locks");
+    for key in [
+        settings::PROVIDER_KEYS_LOCK,
+        settings::GENERATION_SETTINGS_LOCK,
+        settings::KEY_SELECTION_SETTING,
+    ] {
+        assert!(admin.ensure_unlocked(key).is_err(), "{key} is locked");
+    }
+
+    settings::apply_policy(db.pool(), &admin)
+        .await
+        .expect("apply");
+    assert_eq!(
+        provider_keys::get_key_selection(db.pool())
+            .await
+            .expect("selection"),
+        KeySelection::QuotaAware
+    );
+
+    let preset_generation = parse("[settings.generation]\ntemperature = 0.1").expect("parses");
+    assert!(settings::validate_policy(&preset_generation).is_err());
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use vectorless_lib::{
+    core::types::ModelPricing,
+    providers::{
+        gemini::parse_answer_output,
+        pricing::{self, PricingTable, FALLBACK_PRICING},
+        LlmProvider, ProviderResponse,
+    },
+};
+
+fn rates(input: f64, output: f64) -> ModelPricing {
+    ModelPricing {
+        input_per_token: input,
+        output_per_token: output,
+    }
+}
+
+#[test]
+fn bundled_table_prices_known_models_per_token() {
+    let table = pricing::bundled();
+    assert_eq!(
+        table.lookup("gemini-2.0-flash"),
+        Some(rates(0.1 / 1_000_000.0, 0.4 / 1_000_000.0))
+    );
+    assert_eq!(
+        table.lookup("gemini-1.5-pro"),
+        Some(rates(1.25 / 1_000_000.0, 5.0 / 1_000_000.0))
+    );
+    assert_eq!(table.lookup("unknown-model"), None);
+    assert_eq!(table.pricing_for("unknown-model"), FALLBACK_PRICING);
+}
+
+#[test]
+fn lookup_prefers_exact_then_longest_prefix_and_ignores_vendor() {
+    let table = pricing::bundled();
+    let flash = table.lookup("gemini-2.0-flash").expect("flash priced");
+    let lite = table.lookup("gemini-2.0-flash-lite").expect("lite priced");
+    assert_ne!(flash, lite);
+
+    assert_eq!(table.lookup("gemini-2.0-flash-001"), Some(flash));
+    assert_eq!(table.lookup("gemini-2.0-flash-lite-001"), Some(lite));
+    assert_eq!(table.lookup("google/gemini-2.0-flash-001"), Some(flash));
+    assert_eq!(table.lookup("Gemini-2.0-Flash"), Some(flash));
+}
+
+#[test]
+fn overrides_take_precedence_over_bundled_rates() {
+    let mut overrides = BTreeMap::new();
+    overrides.insert("Gemini-2.0-Flash".to_string(), rates(0.000001, 0.000002));
+    overrides.insert("acme/custom".to_string(), rates(0.0, 0.0));
+
+    let table = pricing::bundled().with_overrides(&overrides);
+    assert_eq!(
+        table.lookup("gemini-2.0-flash-001"),
+        Some(rates(0.000001, 0.000002))
+    );
+    assert_eq!(table.lookup("acme/custom"), Some(rates(0.0, 0.0)));
+    assert!(table.lookup("gemini-1.5-pro").is_some());
+
+    let only_overrides = PricingTable::default().with_overrides(&overrides);
+    assert_eq!(only_overrides.lookup("gemini-1.5-pro"), None);
+}
+
+#[test]
+fn overrides_reject_negative_or_non_finite_rates() {
+    let mut overrides = BTreeMap::new();
+    overrides.insert("gemini-2.0-flash".to_string(), rates(-0.1, 0.0));
+    assert!(pricing::validate_overrides(&overrides).is_err());
+
+    overrides.insert("gemini-2.0-flash".to_string(), rates(f64::NAN, 0.0));
+    assert!(pricing::validate_overrides(&overrides).is_err());
+
+    overrides.clear();
+    overrides.insert(" ".to_string(), rates(0.0, 0.0));
+    assert!(pricing::validate_overrides(&overrides).is_err());
+
+    overrides.clear();
+    overrides.insert("gemini-2.0-flash".to_string(), rates(0.0, 0.000001));
+    assert!(pricing::validate_overrides(&overrides).is_ok());
+}
+
+#[test]
+fn cost_depends_on_the_model_being_priced() {
+    let response = ProviderResponse {
+        text: r#"{"answer_markdown":"ok","confidence":0.9,"citations":[]}"#.to_string(),
+        token_usage: serde_json::json!({
+            "promptTokenCount": 1_000_000,
+            "candidatesTokenCount": 100_000
+        }),
+    };
+    let flash = parse_answer_output(
+        &response,
+        &pricing::bundled().pricing_for("gemini-2.0-flash"),
+    )
+    .expect("answer should parse");
+    let pro = parse_answer_output(&response, &pricing::bundled().pricing_for("gemini-1.5-pro"))
+        .expect("answer should parse");
+    assert!((flash.estimated_cost_usd - 0.14).abs() < 1e-9);
+    assert!((pro.estimated_cost_usd - 1.75).abs() < 1e-9);
+}
+
+#[test]
+fn providers_default_to_bundled_pricing_for_their_model() {
+    let gemini = vectorless_lib::providers::gemini::GeminiClient::new("gemini-1.5-pro")
+        .expect("client should build");
+    assert_eq!(
+        gemini.pricing(),
+        pricing::bundled().pricing_for("gemini-1.5-pro")
+    );
+}
@@ -0,0 +1,69 @@
+use vectorless_lib::{
+    core::{
+        errors::AppError,
+        privacy::{fingerprint, Redactor},
+        types::PrivacySettings,
+    },
+    db::{repositories::settings, Database},
+};
+
+#[test]
+fn privacy_mode_replaces_quoted_text_with_a_fingerprint() {
+    let redactor = Redactor::default();
+
+    let redacted = redactor.text("The merger closes on 4 May.");
+    assert!(redacted.starts_with("[27 chars #"));
+    assert_eq!(redacted, fingerprint("The merger closes on 4 May."));
+    assert_ne!(redacted, fingerprint("The merger closes on 5 May."));
+
+    let body = redactor.error(&AppError::ProviderInvalidResponse(
+        "status 400 body {\"prompt\": \"The merger closes\"}".to_string(),
+    ));
+    assert!(body.starts_with("provider invalid response: status 400 body [31 chars #"));
+    assert!(!body.contains("merger"));
+
+    let stderr = redactor.error(&AppError::Sidecar(
+        "parser exited with status 1: Confidential draft".to_string(),
+    ));
+    assert!(stderr.starts_with("sidecar error: parser exited with status 1: ["));
+    assert!(!stderr.contains("Confidential"));
+
+    // The app's own wording is kept.
+    assert_eq!(
+        redactor.quoted("synthesis produced no usable output"),
+        "synthesis produced no usable output"
+    );
+    assert_eq!(
+        redactor.error(&AppError::InvalidInput("query cannot be empty".to_string())),
+        "invalid input: query cannot be empty"
+    );
+}
+
+#[test]
+fn redaction_can_be_turned_off() {
+    let redactor = Redactor::new(PrivacySettings { redact_text: false });
+    assert_eq!(redactor.text("The merger closes."), "The merger closes.");
+    assert_eq!(
+        redactor.error(&AppError::Sidecar("exited: Confidential draft".to_string())),
+        "sidecar error: exited: Confidential draft"
+    );
+}
+
+#[tokio::test]
+async fn privacy_setting_defaults_to_redacting() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let stored: PrivacySettings = settings::get_setting(db.pool(), settings::PRIVACY_SETTING)
+        .await
+        .expect("setting");
+    assert!(stored.redact_text);
+
+    settings::set_setting(
+        db.pool(),
+        settings::PRIVACY_SETTING,
+        &PrivacySettings { redact_text: false },
+    )
+    .await
+    .expect("store setting");
+    let redactor = settings::privacy_redactor(db.pool()).await;
+    assert_eq!(redactor.text("plain"), "plain");
+}
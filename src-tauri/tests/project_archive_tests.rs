@@ -0,0 +1,150 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::errors::AppError,
+    db::{
+        repositories::{documents, projects, reasoning},
+        Database,
+    },
+};
+
+async fn seed(db: &Database) {
+    projects::create_project(db.pool(), "old", "Old work")
+        .await
+        .expect("project");
+    seed_document(
+        db,
+        "old",
+        "memo",
+        "Memo.md",
+        &[
+            node("root-memo", None, "Document", ""),
+            node(
+                "sec-memo",
+                Some("root-memo"),
+                "Section",
+                "The migration budget.",
+            ),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn archived_projects_stay_openable_but_cannot_change() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let archived = projects::set_archived(db.pool(), "old", true)
+        .await
+        .expect("archive");
+    assert!(archived.archived);
+    assert!(!archived.read_only);
+    assert!(projects::list_projects(db.pool())
+        .await
+        .expect("list")
+        .iter()
+        .any(|project| project.id == "old" && project.archived));
+    assert_eq!(
+        documents::get_tree(db.pool(), "memo", None, 8)
+            .await
+            .expect("tree")
+            .len(),
+        2
+    );
+
+    assert!(matches!(
+        projects::ensure_writable(db.pool(), "old").await,
+        Err(AppError::InvalidInput(message)) if message.contains("archived")
+    ));
+    assert!(matches!(
+        projects::ensure_document_writable(db.pool(), "memo").await,
+        Err(AppError::InvalidInput(message)) if message.contains("archived")
+    ));
+
+    let restored = projects::set_archived(db.pool(), "old", false)
+        .await
+        .expect("unarchive");
+    assert!(!restored.archived);
+    projects::ensure_writable(db.pool(), "old")
+        .await
+        .expect("writable again");
+    projects::ensure_document_writable(db.pool(), "memo")
+        .await
+        .expect("writable again");
+
+    assert!(matches!(
+        projects::set_archived(db.pool(), "missing", true).await,
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn global_search_skips_archived_projects_unless_asked() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    projects::set_archived(db.pool(), "old", true)
+        .await
+        .expect("archive");
+
+    for query in ["budget", "udge"] {
+        assert!(documents::search_all_nodes(db.pool(), query, 50, false)
+            .await
+            .expect("search")
+            .is_empty());
+        let groups = documents::search_all_nodes(db.pool(), query, 50, true)
+            .await
+            .expect("search");
+        assert_eq!(groups.len(), 1, "{query}");
+        assert_eq!(groups[0].project_id, "old");
+    }
+}
+
+#[tokio::test]
+async fn delete_impact_counts_what_the_cascade_removes() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    reasoning::create_run(db.pool(), "run-budget", "old", None, "What is the budget?")
+        .await
+        .expect("create run");
+    documents::link_document(db.pool(), "project-default", "memo")
+        .await
+        .expect("link");
+
+    let impact = projects::delete_impact(db.pool(), "old")
+        .await
+        .expect("impact");
+    assert_eq!(
+        (
+            impact.documents,
+            impact.nodes,
+            impact.runs,
+            impact.layout_positions,
+            impact.shared_documents
+        ),
+        (1, 2, 1, 0, 1)
+    );
+    assert_eq!(
+        projects::delete_impact(db.pool(), "old")
+            .await
+            .expect("impact")
+            .confirmation_token,
+        impact.confirmation_token
+    );
+
+    reasoning::create_run(db.pool(), "run-later", "old", None, "Who signed it?")
+        .await
+        .expect("create run");
+    assert_ne!(
+        projects::delete_impact(db.pool(), "old")
+            .await
+            .expect("impact")
+            .confirmation_token,
+        impact.confirmation_token
+    );
+    assert!(matches!(
+        projects::delete_impact(db.pool(), "missing").await,
+        Err(AppError::NotFound(_))
+    ));
+}
@@ -0,0 +1,97 @@
+use vectorless_lib::{
+    core::{errors::AppError, types::QueryClass},
+    db::{repositories::prompts, Database},
+    reasoner::{
+        planner::PlannerInput,
+        prompts::{
+            planner_prompt, render_planner_prompt, validate_template, PLANNER_TEMPLATE,
+            SYNTHESIS_TEMPLATE,
+        },
+    },
+};
+
+fn input() -> PlannerInput {
+    PlannerInput {
+        query: "What is the latency budget?".to_string(),
+        query_class: QueryClass::Factual,
+        last_confidence: None,
+        explored_sections: vec!["Latency".to_string()],
+        branch_outcomes: vec![],
+        has_evidence: false,
+        step_count: 0,
+        backtrack_count: 0,
+    }
+}
+
+#[test]
+fn default_planner_template_renders_state() {
+    let prompt = planner_prompt(&input());
+    assert!(prompt.contains("query: What is the latency budget?"));
+    assert!(prompt.contains("queryClass: factual"));
+    assert!(prompt.contains("lastConfidence: none"));
+    assert!(prompt.contains("exploredSections:\n- Latency\n"));
+    assert!(!prompt.contains("{{"));
+}
+
+#[test]
+fn template_validation_rejects_unknown_and_missing_variables() {
+    assert!(matches!(
+        validate_template(PLANNER_TEMPLATE, "Plan for {{query}} using {{evidence}}"),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        validate_template(SYNTHESIS_TEMPLATE, "Answer {{query}} without evidence"),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        validate_template(PLANNER_TEMPLATE, "Plan for {{query"),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(validate_template(SYNTHESIS_TEMPLATE, "Q: {{query}}\nE: {{evidence}}").is_ok());
+}
+
+#[tokio::test]
+async fn saved_templates_are_versioned_and_resettable() {
+    let db = Database::in_memory().await.expect("db should initialize");
+
+    let builtin = prompts::get_active_template(db.pool(), PLANNER_TEMPLATE)
+        .await
+        .expect("builtin template");
+    assert!(builtin.builtin);
+    assert_eq!(builtin.version, 0);
+
+    let saved = prompts::save_template(db.pool(), PLANNER_TEMPLATE, "Custom plan for {{query}}")
+        .await
+        .expect("save template");
+    assert_eq!(saved.version, 1);
+
+    let prompt_set = prompts::load_prompt_set(db.pool())
+        .await
+        .expect("prompt set");
+    assert_eq!(prompt_set.planner.version, 1);
+    assert_eq!(
+        render_planner_prompt(&prompt_set.planner.body, &input()),
+        "Custom plan for What is the latency budget?"
+    );
+
+    let reset = prompts::reset_template(db.pool(), PLANNER_TEMPLATE)
+        .await
+        .expect("reset template");
+    assert_eq!(reset.version, 2);
+    assert_eq!(reset.body, builtin.body);
+
+    let history = prompts::list_template_history(db.pool(), PLANNER_TEMPLATE)
+        .await
+        .expect("history");
+    assert_eq!(
+        history
+            .iter()
+            .map(|record| record.version)
+            .collect::<Vec<_>>(),
+        vec![2, 1, 0]
+    );
+
+    assert!(prompts::save_template(db.pool(), "unknown", "{{query}}")
+        .await
+        .is_err());
+}
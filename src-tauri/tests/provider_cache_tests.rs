@@ -0,0 +1,323 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use futures::future::BoxFuture;
+
+use vectorless_lib::{
+    core::{errors::AppResult, types::GenerationSettings},
+    db::{
+        repositories::{
+            documents,
+            provider_cache::{self, ProviderCacheConfig},
+            reasoning,
+        },
+        Database,
+    },
+    providers::{cache_key, CallKind, LlmProvider, ProviderResponse},
+    reasoner::executor::ReasoningExecutor,
+    sidecar::types::SidecarNode,
+};
+
+struct CountingProvider {
+    calls: Arc<AtomicUsize>,
+}
+
+impl LlmProvider for CountingProvider {
+    fn model(&self) -> &str {
+        "counting"
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _api_key: &'a str,
+        kind: CallKind,
+        _prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let text = match kind {
+            CallKind::Planner => "not a plan".to_string(),
+            CallKind::Synthesis => serde_json::json!({
+                "answer_markdown": "Latency dropped to 50ms p99.",
+                "confidence": 0.9,
+                "citations": ["sec-cache-1"],
+            })
+            .to_string(),
+        };
+        Box::pin(async move {
+            Ok(ProviderResponse {
+                text,
+                token_usage: serde_json::json!({
+                    "promptTokenCount": 100,
+                    "candidatesTokenCount": 20,
+                }),
+            })
+        })
+    }
+}
+
+/// Answers every synthesis call with text that is not the expected JSON.
+struct MalformedProvider {
+    calls: Arc<AtomicUsize>,
+}
+
+impl LlmProvider for MalformedProvider {
+    fn model(&self) -> &str {
+        "malformed"
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _api_key: &'a str,
+        _kind: CallKind,
+        _prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move { Ok(response("Latency dropped to 50ms p99.")) })
+    }
+}
+
+fn response(text: &str) -> ProviderResponse {
+    ProviderResponse {
+        text: text.to_string(),
+        token_usage: serde_json::json!({}),
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-cache-1",
+        "project-default",
+        "Spec.pdf",
+        "application/pdf",
+        "checksum-cache-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        SidecarNode {
+            id: "root-cache-1".to_string(),
+            parent_id: None,
+            node_type: "Document".to_string(),
+            title: "Spec".to_string(),
+            text: "".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "root".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+        SidecarNode {
+            id: "sec-cache-1".to_string(),
+            parent_id: Some("root-cache-1".to_string()),
+            node_type: "Section".to_string(),
+            title: "Latency".to_string(),
+            text: "Latency dropped to 50ms p99.".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "1".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+    ];
+    documents::insert_nodes(db.pool(), "doc-cache-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+#[tokio::test]
+async fn cached_response_round_trips_until_expiry() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let key = cache_key(
+        "m",
+        CallKind::Planner,
+        "prompt",
+        &GenerationSettings::default(),
+    );
+
+    provider_cache::put_cached_response(
+        db.pool(),
+        &key,
+        CallKind::Planner,
+        "m",
+        &response("cached"),
+        ProviderCacheConfig::default(),
+    )
+    .await
+    .expect("put");
+    let hit = provider_cache::get_cached_response(db.pool(), &key)
+        .await
+        .expect("get");
+    assert_eq!(hit.map(|value| value.text), Some("cached".to_string()));
+
+    provider_cache::put_cached_response(
+        db.pool(),
+        &key,
+        CallKind::Planner,
+        "m",
+        &response("stale"),
+        ProviderCacheConfig {
+            ttl_secs: -1,
+            max_entries: 10,
+        },
+    )
+    .await
+    .expect("put expired");
+    let miss = provider_cache::get_cached_response(db.pool(), &key)
+        .await
+        .expect("get");
+    assert!(miss.is_none());
+}
+
+#[tokio::test]
+async fn cache_evicts_beyond_size_cap() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let config = ProviderCacheConfig {
+        ttl_secs: 3_600,
+        max_entries: 2,
+    };
+    for prompt in ["a", "b", "c"] {
+        let key = cache_key(
+            "m",
+            CallKind::Synthesis,
+            prompt,
+            &GenerationSettings::default(),
+        );
+        provider_cache::put_cached_response(
+            db.pool(),
+            &key,
+            CallKind::Synthesis,
+            "m",
+            &response(prompt),
+            config,
+        )
+        .await
+        .expect("put");
+    }
+
+    assert_eq!(
+        provider_cache::clear_cache(db.pool()).await.expect("clear"),
+        2
+    );
+}
+
+#[tokio::test]
+async fn repeated_run_is_served_from_cache() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let calls = Arc::new(AtomicUsize::new(0));
+    let executor = ReasoningExecutor::new(CountingProvider {
+        calls: Arc::clone(&calls),
+    })
+    .with_cache(ProviderCacheConfig::default());
+
+    for run_id in ["run-cache-1", "run-cache-2"] {
+        executor
+            .run(
+                &db,
+                "project-default",
+                Some("doc-cache-1"),
+                run_id.to_string(),
+                "What is the latency?",
+                Some(6),
+                "unused",
+                |_| {},
+            )
+            .await
+            .expect("run should complete");
+    }
+
+    let first = reasoning::get_run(db.pool(), "run-cache-1")
+        .await
+        .expect("first run");
+    let second = reasoning::get_run(db.pool(), "run-cache-2")
+        .await
+        .expect("second run");
+    let first_calls = first.run.token_usage_json["providerCalls"]
+        .as_u64()
+        .expect("calls");
+    assert_eq!(calls.load(Ordering::SeqCst) as u64, first_calls);
+    assert_eq!(second.run.token_usage_json["providerCalls"], 0);
+    assert_eq!(second.run.token_usage_json["cacheHits"], first_calls);
+}
+
+#[tokio::test]
+async fn cached_responses_are_not_billed_again() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let executor = ReasoningExecutor::new(CountingProvider {
+        calls: Arc::new(AtomicUsize::new(0)),
+    })
+    .with_cache(ProviderCacheConfig::default());
+
+    for run_id in ["run-billed-1", "run-billed-2"] {
+        executor
+            .run(
+                &db,
+                "project-default",
+                Some("doc-cache-1"),
+                run_id.to_string(),
+                "What is the latency?",
+                Some(6),
+                "unused",
+                |_| {},
+            )
+            .await
+            .expect("run should complete");
+    }
+
+    let second = reasoning::get_run(db.pool(), "run-billed-2")
+        .await
+        .expect("second run");
+    assert_eq!(second.run.token_usage_json["providerCalls"], 0);
+    assert_eq!(
+        second.run.token_usage_json["synthesis"]["promptTokenCount"],
+        0
+    );
+    assert_eq!(
+        second.run.token_usage_json["planner"]["promptTokenCount"],
+        0
+    );
+    assert_eq!(second.run.cost_usd, 0.0);
+}
+
+#[tokio::test]
+async fn malformed_answers_are_not_cached() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let calls = Arc::new(AtomicUsize::new(0));
+    let executor = ReasoningExecutor::new(MalformedProvider {
+        calls: Arc::clone(&calls),
+    })
+    .with_cache(ProviderCacheConfig::default());
+
+    for run_id in ["run-malformed-1", "run-malformed-2"] {
+        executor
+            .run(
+                &db,
+                "project-default",
+                Some("doc-cache-1"),
+                run_id.to_string(),
+                "What is the latency?",
+                Some(6),
+                "unused",
+                |_| {},
+            )
+            .await
+            .expect("raw text fallback should complete the run");
+    }
+
+    let second = reasoning::get_run(db.pool(), "run-malformed-2")
+        .await
+        .expect("second run");
+    let provider_calls = second.run.token_usage_json["providerCalls"]
+        .as_u64()
+        .expect("calls");
+    assert!(
+        provider_calls > 0,
+        "malformed answers were served from cache"
+    );
+}
@@ -0,0 +1,110 @@
+use reqwest::StatusCode;
+use vectorless_lib::{core::errors::AppError, providers::errors::error_for_status};
+
+const GEMINI_QUOTA_BODY: &str = r#"{
+  "error": {
+    "code": 429,
+    "message": "You exceeded your current quota, please check your plan and billing details.",
+    "status": "RESOURCE_EXHAUSTED",
+    "details": [
+      {
+        "@type": "type.googleapis.com/google.rpc.QuotaFailure",
+        "violations": [
+          {
+            "quotaMetric": "generativelanguage.googleapis.com/generate_content_free_tier_requests",
+            "quotaId": "GenerateRequestsPerDayPerProjectPerModel-FreeTier"
+          }
+        ]
+      },
+      {
+        "@type": "type.googleapis.com/google.rpc.RetryInfo",
+        "retryDelay": "36.4s"
+      }
+    ]
+  }
+}"#;
+
+#[test]
+fn gemini_resource_exhausted_becomes_quota_exceeded_with_reset_hint() {
+    let err = error_for_status(StatusCode::TOO_MANY_REQUESTS, None, GEMINI_QUOTA_BODY);
+    match &err {
+        AppError::ProviderQuotaExceeded {
+            message,
+            retry_after_secs,
+        } => {
+            assert!(message.starts_with("You exceeded your current quota"));
+            assert!(message.contains("GenerateRequestsPerDayPerProjectPerModel-FreeTier"));
+            assert_eq!(*retry_after_secs, Some(37));
+        }
+        other => panic!("expected a quota error, got {other:?}"),
+    }
+    assert!(err.retryable());
+    assert_eq!(err.code(), "PROVIDER_QUOTA_EXCEEDED");
+
+    let serialized = serde_json::to_value(&err).expect("serialize");
+    assert_eq!(serialized["code"], "PROVIDER_QUOTA_EXCEEDED");
+    assert_eq!(serialized["retryAfterSecs"], 37);
+}
+
+#[test]
+fn array_wrapped_errors_are_unwrapped() {
+    let body = format!("[{GEMINI_QUOTA_BODY}]");
+    assert!(matches!(
+        error_for_status(StatusCode::TOO_MANY_REQUESTS, None, &body),
+        AppError::ProviderQuotaExceeded {
+            retry_after_secs: Some(37),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn openrouter_spent_credits_are_quota_errors_without_a_hint() {
+    let body = r#"{"error":{"code":402,"message":"Insufficient credits"}}"#;
+    let err = error_for_status(StatusCode::PAYMENT_REQUIRED, None, body);
+    assert!(matches!(
+        &err,
+        AppError::ProviderQuotaExceeded {
+            message,
+            retry_after_secs: None,
+        } if message == "Insufficient credits"
+    ));
+    // Without a reset time, waiting is no use.
+    assert!(!err.retryable());
+    assert_eq!(
+        serde_json::to_value(&err).expect("serialize")["retryAfterSecs"],
+        serde_json::Value::Null
+    );
+}
+
+#[test]
+fn openrouter_daily_limit_uses_the_retry_after_header_first() {
+    let body = r#"{"error":{"code":429,"message":"Rate limit exceeded: free-models-per-day","metadata":{"headers":{"X-RateLimit-Reset":"4102444800000"}}}}"#;
+    let err = error_for_status(StatusCode::TOO_MANY_REQUESTS, Some("120"), body);
+    assert_eq!(err.retry_after_secs(), Some(120));
+
+    let err = error_for_status(StatusCode::TOO_MANY_REQUESTS, None, body);
+    assert!(matches!(err, AppError::ProviderQuotaExceeded { .. }));
+    assert!(err.retry_after_secs().expect("reset hint") > 120);
+}
+
+#[test]
+fn other_statuses_keep_their_existing_errors() {
+    assert!(matches!(
+        error_for_status(StatusCode::TOO_MANY_REQUESTS, Some("5"), "slow down"),
+        AppError::ProviderRateLimited
+    ));
+    assert!(matches!(
+        error_for_status(
+            StatusCode::UNAUTHORIZED,
+            None,
+            r#"{"error":{"message":"bad key"}}"#
+        ),
+        AppError::ProviderAuth
+    ));
+    assert!(matches!(
+        error_for_status(StatusCode::INTERNAL_SERVER_ERROR, None, "boom"),
+        AppError::ProviderInvalidResponse(message) if message.contains("boom")
+    ));
+    assert_eq!(AppError::ProviderRateLimited.retry_after_secs(), None);
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use vectorless_lib::{
+    core::types::{
+        ModelSelection, PlannerModel, Provider, ProviderTimeoutSettings, ProviderTimeouts,
+    },
+    db::{repositories::settings, Database},
+    providers::{
+        planner_provider_for_selection, provider_for_selection,
+        timeouts::{http_client, request_timeout, validate_settings},
+        CallKind,
+    },
+};
+
+#[test]
+fn planner_calls_get_a_shorter_deadline_than_synthesis() {
+    let timeouts = ProviderTimeouts::default();
+    assert!(
+        request_timeout(&timeouts, CallKind::Planner)
+            < request_timeout(&timeouts, CallKind::Synthesis)
+    );
+
+    let custom = ProviderTimeouts {
+        planner_secs: 15,
+        synthesis_secs: 600,
+        enrichment_secs: 240,
+        ..ProviderTimeouts::default()
+    };
+    assert_eq!(
+        request_timeout(&custom, CallKind::Planner),
+        Duration::from_secs(15)
+    );
+    assert_eq!(
+        request_timeout(&custom, CallKind::Synthesis),
+        Duration::from_secs(600)
+    );
+    assert_eq!(
+        request_timeout(&custom, CallKind::Enrichment),
+        Duration::from_secs(240)
+    );
+}
+
+#[test]
+fn out_of_range_timeouts_are_rejected() {
+    assert!(validate_settings(&ProviderTimeoutSettings::default()).is_ok());
+
+    let mut settings = ProviderTimeoutSettings::default();
+    settings.openrouter.synthesis_secs = 0;
+    let err = validate_settings(&settings).expect_err("zero deadline");
+    assert!(err.to_string().contains("openrouter synthesisSecs"));
+
+    let mut settings = ProviderTimeoutSettings::default();
+    settings.gemini.keep_alive_secs = 7200;
+    assert!(validate_settings(&settings).is_err());
+
+    // Keep-alive may be turned off.
+    let mut settings = ProviderTimeoutSettings::default();
+    settings.gemini.keep_alive_secs = 0;
+    assert!(validate_settings(&settings).is_ok());
+    http_client(&settings.gemini).expect("client without pooling");
+}
+
+#[test]
+fn providers_build_with_their_own_timeouts() {
+    let mut timeouts = ProviderTimeoutSettings::default();
+    timeouts.openrouter.synthesis_secs = 900;
+    let selection = ModelSelection {
+        provider: Provider::OpenRouter,
+        model: "openai/gpt-4o".to_string(),
+        context_length: None,
+        pricing: None,
+        planner: Some(PlannerModel {
+            model: "openai/gpt-4o-mini".to_string(),
+            context_length: None,
+            pricing: None,
+        }),
+    };
+    assert_eq!(
+        timeouts.for_provider(&Provider::OpenRouter).synthesis_secs,
+        900
+    );
+    assert_eq!(
+        timeouts.for_provider(&Provider::Gemini),
+        ProviderTimeouts::default()
+    );
+    let provider = provider_for_selection(&selection, &timeouts).expect("provider");
+    assert_eq!(provider.model(), "openai/gpt-4o");
+    let planner = planner_provider_for_selection(&selection, &timeouts)
+        .expect("planner")
+        .expect("planner is set");
+    assert_eq!(planner.model(), "openai/gpt-4o-mini");
+}
+
+#[tokio::test]
+async fn timeout_settings_round_trip_and_fill_in_defaults() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let initial: ProviderTimeoutSettings =
+        settings::get_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING)
+            .await
+            .expect("defaults");
+    assert_eq!(initial, ProviderTimeoutSettings::default());
+
+    let mut stored = ProviderTimeoutSettings::default();
+    stored.gemini.synthesis_secs = 420;
+    settings::set_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING, &stored)
+        .await
+        .expect("store");
+    let loaded: ProviderTimeoutSettings =
+        settings::get_setting(db.pool(), settings::PROVIDER_TIMEOUTS_SETTING)
+            .await
+            .expect("stored");
+    assert_eq!(loaded, stored);
+
+    // Settings saved with only some fields keep the defaults for the rest.
+    let partial: ProviderTimeoutSettings =
+        serde_json::from_value(serde_json::json!({"gemini": {"plannerSecs": 20}}))
+            .expect("partial settings");
+    assert_eq!(partial.gemini.planner_secs, 20);
+    assert_eq!(
+        partial.gemini.synthesis_secs,
+        ProviderTimeouts::default().synthesis_secs
+    );
+    assert_eq!(partial.openrouter, ProviderTimeouts::default());
+}
@@ -0,0 +1,94 @@
+use vectorless_lib::{
+    core::types::QualityAggregate,
+    db::{repositories::reasoning, Database},
+};
+
+async fn evaluated_run(
+    db: &Database,
+    id: &str,
+    day: &str,
+    overall: f64,
+    grounded: bool,
+    passed: bool,
+) {
+    reasoning::create_run(db.pool(), id, "project-default", None, "How fast is it?")
+        .await
+        .expect("create run");
+    let quality = serde_json::json!({
+        "overall": overall,
+        "queryAlignment": overall,
+        "citationCoverage": overall,
+        "crossDocumentCoverage": 0.0,
+        "grounded": grounded,
+    });
+    if passed {
+        reasoning::complete_run(
+            db.pool(),
+            id,
+            10,
+            serde_json::json!({}),
+            0.0,
+            "p99 is 50ms.",
+            vec![],
+            overall,
+            grounded,
+            quality,
+            serde_json::json!([]),
+        )
+        .await
+        .expect("complete run");
+    } else {
+        reasoning::set_run_quality(db.pool(), id, quality)
+            .await
+            .expect("store quality");
+        reasoning::fail_run(db.pool(), id).await.expect("fail run");
+    }
+    sqlx::query("UPDATE reasoning_runs SET started_at = ?2 WHERE id = ?1")
+        .bind(id)
+        .bind(format!("{day}T09:00:00.000Z"))
+        .execute(db.pool())
+        .await
+        .expect("backdate run");
+}
+
+#[tokio::test]
+async fn quality_trends_aggregate_evaluated_runs_per_day() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    evaluated_run(&db, "run-1", "2026-03-01", 0.4, false, false).await;
+    evaluated_run(&db, "run-2", "2026-03-01", 0.8, true, true).await;
+    evaluated_run(&db, "run-3", "2026-03-02", 0.9, true, true).await;
+
+    // Runs that never reached the gate are not part of the trend.
+    reasoning::create_run(db.pool(), "run-error", "project-default", None, "q")
+        .await
+        .expect("create run");
+    reasoning::fail_run(db.pool(), "run-error")
+        .await
+        .expect("fail run");
+    reasoning::create_run(db.pool(), "run-open", "project-default", None, "q")
+        .await
+        .expect("create run");
+
+    let trends = reasoning::quality_trends(db.pool(), "project-default")
+        .await
+        .expect("trends");
+    let days = trends
+        .points
+        .iter()
+        .map(|p| p.day.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(days, vec!["2026-03-01", "2026-03-02"]);
+    assert_eq!(trends.points[0].quality.run_count, 2);
+    assert!((trends.points[0].quality.avg_overall - 0.6).abs() < 1e-9);
+    assert_eq!(trends.points[0].quality.grounding_rate, 0.5);
+    assert_eq!(trends.points[0].quality.gate_failure_rate, 0.5);
+    assert_eq!(trends.points[1].quality.gate_failure_rate, 0.0);
+    assert_eq!(trends.total.run_count, 3);
+    assert!((trends.total.avg_overall - 0.7).abs() < 1e-9);
+
+    let empty = reasoning::quality_trends(db.pool(), "project-missing")
+        .await
+        .expect("trends");
+    assert!(empty.points.is_empty());
+    assert_eq!(empty.total, QualityAggregate::default());
+}
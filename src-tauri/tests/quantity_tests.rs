@@ -0,0 +1,130 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    db::{repositories::documents, Database},
+    sidecar::{
+        quantities::{self, QuantityFilter},
+        types::{NormalizedPayload, SidecarDocument},
+    },
+};
+
+async fn hits(db: &Database, query: &str) -> Vec<String> {
+    let mut ids = vec![];
+    for filter in quantities::filters(query) {
+        let nodes =
+            documents::search_quantity_nodes(db.pool(), "project-default", None, &filter, 10)
+                .await
+                .expect("search");
+        ids.extend(nodes.into_iter().map(|node| node.id));
+    }
+    ids
+}
+
+#[test]
+fn quantities_are_read_in_base_units() {
+    let found = quantities::extract("p99 latency is 85 ms, worst case 1.2s; we paid $2.5 million.");
+    let read = found
+        .iter()
+        .map(|quantity| {
+            (
+                quantity.value,
+                quantity.unit.as_str(),
+                quantity.text.as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        read,
+        vec![
+            (0.085, "s", "85 ms"),
+            (1.2, "s", "1.2s"),
+            (2_500_000.0, "USD", "$2.5 million"),
+        ]
+    );
+    // Numbers inside words and numbers without a unit are not quantities.
+    assert!(quantities::extract("COVID-19 cases in v2 of 2021").is_empty());
+}
+
+#[test]
+fn questions_give_ranges() {
+    assert_eq!(
+        quantities::filters("latency under 100ms"),
+        vec![QuantityFilter {
+            unit: "s".to_string(),
+            min: None,
+            min_inclusive: false,
+            max: Some(0.1),
+            max_inclusive: false,
+        }]
+    );
+    assert_eq!(
+        quantities::filters("payments of no less than $10,000"),
+        vec![QuantityFilter {
+            unit: "USD".to_string(),
+            min: Some(10_000.0),
+            min_inclusive: true,
+            max: None,
+            max_inclusive: false,
+        }]
+    );
+    assert_eq!(
+        quantities::filters("files between 1 and 2 GB"),
+        vec![QuantityFilter {
+            unit: "B".to_string(),
+            min: Some(1e9),
+            min_inclusive: true,
+            max: Some(2e9),
+            max_inclusive: true,
+        }]
+    );
+    assert!(quantities::filters("what changed in 2021?").is_empty());
+}
+
+#[tokio::test]
+async fn nodes_are_found_by_the_range_of_their_quantities() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let mut payload = NormalizedPayload {
+        document: SidecarDocument {
+            title: "Ops".to_string(),
+            pages: 1,
+            metadata: serde_json::json!({}),
+        },
+        nodes: vec![
+            node("root-ops", None, "Document", ""),
+            node(
+                "para-fast",
+                Some("root-ops"),
+                "Paragraph",
+                "Search answers in 85 ms at p99.",
+            ),
+            node(
+                "para-slow",
+                Some("root-ops"),
+                "Paragraph",
+                "Exports take 0.1 s or more.",
+            ),
+            node(
+                "para-payments",
+                Some("root-ops"),
+                "Paragraph",
+                "Refunds above $12,500 need approval.",
+            ),
+        ],
+        edges: vec![],
+    };
+    quantities::record(&mut payload);
+    assert!(payload.nodes[0].metadata.get("quantities").is_none());
+    seed_document(&db, "project-default", "doc-ops", "Ops.pdf", &payload.nodes).await;
+
+    assert_eq!(hits(&db, "latency under 100ms").await, vec!["para-fast"]);
+    assert_eq!(
+        hits(&db, "latency of at most 100 milliseconds").await,
+        vec!["para-fast", "para-slow"]
+    );
+    assert_eq!(
+        hits(&db, "payments over $10,000").await,
+        vec!["para-payments"]
+    );
+    assert!(hits(&db, "payments over $20k").await.is_empty());
+}
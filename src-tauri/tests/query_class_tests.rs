@@ -0,0 +1,116 @@
+use vectorless_lib::{
+    core::types::{LanguageResources, QualityThresholds, QualityWeights, QueryClass},
+    reasoner::{
+        evaluator::{evaluate_answer, weighted_overall},
+        language::defaults,
+        query_class::{classify_query, validate_thresholds},
+    },
+};
+
+#[test]
+fn classifies_queries_by_the_answer_they_ask_for() {
+    let english = LanguageResources::default();
+    let cases = [
+        ("What is the p99 latency?", false, QueryClass::Factual),
+        ("Summarize the incident report", false, QueryClass::Summary),
+        (
+            "List all vendors named in the contract",
+            false,
+            QueryClass::Extraction,
+        ),
+        (
+            "What did the invoices cost in total?",
+            false,
+            QueryClass::Aggregation,
+        ),
+        (
+            "How many incidents were reported?",
+            false,
+            QueryClass::Aggregation,
+        ),
+        (
+            "Compare section 2 and section 3",
+            false,
+            QueryClass::Comparison,
+        ),
+        (
+            "How do these files fit together?",
+            true,
+            QueryClass::Comparison,
+        ),
+    ];
+    for (query, relation_query, expected) in cases {
+        assert_eq!(
+            classify_query(query, relation_query, &english),
+            expected,
+            "{query}"
+        );
+    }
+
+    let german = defaults("de").expect("German defaults");
+    assert_eq!(
+        classify_query("Gib mir eine Zusammenfassung des Berichts", false, &german),
+        QueryClass::Summary
+    );
+}
+
+#[test]
+fn each_class_has_its_own_gate_and_weights() {
+    let thresholds = QualityThresholds::default();
+    assert!(
+        thresholds.policy(QueryClass::Comparison).min_overall
+            > thresholds.policy(QueryClass::Factual).min_overall
+    );
+
+    let metrics = evaluate_answer(
+        "Summarize the incident report",
+        "The outage lasted two hours [citation:n1].",
+        &["n1".to_string()],
+        &["n1".to_string()],
+        &Default::default(),
+        &Default::default(),
+        false,
+        &LanguageResources::default(),
+    );
+    assert_eq!(
+        metrics.overall,
+        weighted_overall(&metrics, &QualityWeights::default())
+    );
+    let summary = thresholds.policy(QueryClass::Summary);
+    assert_ne!(
+        weighted_overall(&metrics, &summary.weights),
+        metrics.overall
+    );
+}
+
+#[test]
+fn thresholds_are_checked_and_weights_scaled_to_one() {
+    let mut thresholds = QualityThresholds::default();
+    thresholds.factual.weights = QualityWeights {
+        query_alignment: 2.0,
+        citation_coverage: 1.0,
+        cross_document_coverage: 0.5,
+        grounding: 0.5,
+    };
+    let saved = validate_thresholds(thresholds.clone()).expect("valid thresholds");
+    assert_eq!(saved.factual.weights.query_alignment, 0.5);
+    assert_eq!(saved.factual.weights.grounding, 0.125);
+    assert_eq!(saved.comparison, QualityThresholds::default().comparison);
+
+    let mut too_high = thresholds.clone();
+    too_high.summary.min_overall = 1.5;
+    assert!(validate_thresholds(too_high).is_err());
+
+    let mut negative = thresholds.clone();
+    negative.extraction.weights.grounding = -0.1;
+    assert!(validate_thresholds(negative).is_err());
+
+    let mut unset = thresholds;
+    unset.aggregation.weights = QualityWeights {
+        query_alignment: 0.0,
+        citation_coverage: 0.0,
+        cross_document_coverage: 0.0,
+        grounding: 0.0,
+    };
+    assert!(validate_thresholds(unset).is_err());
+}
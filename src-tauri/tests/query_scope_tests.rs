@@ -1,21 +1,107 @@
-use vectorless_lib::reasoner::query_scope::requires_project_scope;
+use vectorless_lib::{
+    core::types::{DocumentSummary, LanguageResources, QueryScope},
+    reasoner::query_scope::{decide_scope, infer_focus_document, requires_project_scope},
+};
 
 #[test]
 fn detects_multi_document_relation_queries() {
     assert!(requires_project_scope(
-        "Explain what these files are about and how they are related"
+        "Explain what these files are about and how they are related",
+        &LanguageResources::default()
     ));
     assert!(requires_project_scope(
-        "Compare the documents and summarize differences"
+        "Compare the documents and summarize differences",
+        &LanguageResources::default()
     ));
 }
 
 #[test]
 fn keeps_single_document_queries_focused() {
     assert!(!requires_project_scope(
-        "What does slide 8 say about the model?"
+        "What does slide 8 say about the model?",
+        &LanguageResources::default()
     ));
     assert!(!requires_project_scope(
-        "Summarize this document in five bullets"
+        "Summarize this document in five bullets",
+        &LanguageResources::default()
     ));
 }
+
+fn document(id: &str, name: &str) -> DocumentSummary {
+    DocumentSummary {
+        id: id.to_string(),
+        project_id: "project-default".to_string(),
+        name: name.to_string(),
+        mime: "application/pdf".to_string(),
+        checksum: format!("checksum-{id}"),
+        pages: 1,
+        hidden: false,
+        locked: false,
+        unlocked: false,
+        source_path: None,
+        last_ingested_at: None,
+        stale: false,
+        tags: vec![],
+        attachment_ids: vec![],
+        created_at: chrono::Utc::now(),
+    }
+}
+
+#[test]
+fn infers_the_document_a_query_names() {
+    let documents = vec![
+        document("doc-q3", "Q3_Report_2024.pdf"),
+        document("doc-q4", "Q4 Report 2024.pdf"),
+        document("doc-spec", "Spec.pdf"),
+    ];
+
+    let inferred = infer_focus_document("In the Q3 report, what drove revenue?", &documents)
+        .expect("the Q3 report is named");
+    assert_eq!(inferred.document_id, "doc-q3");
+    assert_eq!(inferred.matched_terms, vec!["q3", "report"]);
+
+    let spec = infer_focus_document("What does the spec say about retries?", &documents)
+        .expect("the spec is named");
+    assert_eq!(spec.document_id, "doc-spec");
+
+    // "report 2024" fits both quarterly reports equally well.
+    assert_eq!(
+        infer_focus_document("Summarize the 2024 report", &documents),
+        None
+    );
+    assert_eq!(
+        infer_focus_document("What is the p99 latency?", &documents),
+        None
+    );
+}
+
+#[test]
+fn reports_the_scope_decision_and_honours_a_forced_scope() {
+    let focus = || Some("doc-a".to_string());
+    let relation = "Compare the documents and summarize differences";
+    let english = LanguageResources::default();
+
+    let dropped = decide_scope(relation, focus(), None, &english);
+    assert_eq!(dropped.scope, QueryScope::Project);
+    assert_eq!(dropped.focus_document_id, None);
+    assert!(dropped.focus_dropped);
+    assert!(!dropped.forced);
+
+    let kept = decide_scope(relation, focus(), Some(QueryScope::Document), &english);
+    assert_eq!(kept.scope, QueryScope::Document);
+    assert_eq!(kept.focus_document_id.as_deref(), Some("doc-a"));
+    assert!(kept.forced);
+
+    let widened = decide_scope(
+        "What is the p99 latency?",
+        focus(),
+        Some(QueryScope::Project),
+        &english,
+    );
+    assert_eq!(widened.scope, QueryScope::Project);
+    assert!(!widened.focus_dropped);
+
+    let plain = decide_scope("What is the p99 latency?", focus(), None, &english);
+    assert_eq!(plain.scope, QueryScope::Document);
+    assert!(!plain.focus_dropped && !plain.forced);
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::{DocNodeSummary, NodeType, NodeTypeWeight, RankingWeights},
+    db::{repositories::documents, Database},
+    reasoner::ranking,
+    sidecar::types::SidecarNode,
+};
+
+fn titled(title: &str, node: SidecarNode) -> SidecarNode {
+    SidecarNode {
+        title: title.to_string(),
+        ..node
+    }
+}
+
+fn summary(id: &str, node_type: NodeType) -> DocNodeSummary {
+    DocNodeSummary {
+        id: id.to_string(),
+        document_id: "doc-report".to_string(),
+        parent_id: None,
+        node_type,
+        title: id.to_string(),
+        text: String::new(),
+        ordinal_path: id.to_string(),
+        page_start: None,
+        page_end: None,
+    }
+}
+
+fn ids(nodes: &[DocNodeSummary]) -> Vec<&str> {
+    nodes.iter().map(|node| node.id.as_str()).collect()
+}
+
+async fn seeded() -> Database {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(
+        &db,
+        "project-default",
+        "doc-report",
+        "Report.pdf",
+        &[
+            titled("Report", node("root-report", None, "Document", "")),
+            titled(
+                "Revenue",
+                node(
+                    "section-revenue",
+                    Some("root-report"),
+                    "Section",
+                    "Figures for the year.",
+                ),
+            ),
+            titled(
+                "Outlook",
+                node(
+                    "para-outlook",
+                    Some("root-report"),
+                    "Paragraph",
+                    "Revenue grew and revenue will keep growing, revenue guidance says.",
+                ),
+            ),
+        ],
+    )
+    .await;
+    db
+}
+
+#[tokio::test]
+async fn title_weight_decides_between_title_and_text_hits() {
+    let db = seeded().await;
+    let order = |title: f64| {
+        let db = db.clone();
+        async move {
+            documents::score_project_nodes(
+                db.pool(),
+                "project-default",
+                None,
+                "revenue",
+                title,
+                1.0,
+                10,
+            )
+            .await
+            .expect("search")
+            .into_iter()
+            .map(|(node, _)| node.id)
+            .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(order(0.0).await, vec!["para-outlook", "section-revenue"]);
+    assert_eq!(order(3.0).await, vec!["section-revenue", "para-outlook"]);
+}
+
+#[test]
+fn tables_are_boosted_for_questions_asking_for_amounts() {
+    let weights = RankingWeights::default();
+    let hits = || {
+        vec![
+            (summary("para-summary", NodeType::Paragraph), 2.0),
+            (summary("table-costs", NodeType::Table), 1.6),
+        ]
+    };
+    assert_eq!(
+        ids(&ranking::rank(
+            hits(),
+            vec![],
+            "How much did hosting cost?",
+            &weights
+        )),
+        vec!["table-costs", "para-summary"]
+    );
+    assert_eq!(
+        ids(&ranking::rank(
+            hits(),
+            vec![],
+            "Why did hosting cost rise?",
+            &weights
+        )),
+        vec!["para-summary", "table-costs"]
+    );
+}
+
+#[test]
+fn metadata_matches_add_to_keyword_relevance() {
+    let weights = RankingWeights::default();
+    let ranked = ranking::rank(
+        vec![
+            (summary("para-best", NodeType::Paragraph), 4.0),
+            (summary("para-dated", NodeType::Paragraph), 1.0),
+        ],
+        vec![
+            summary("para-dated", NodeType::Paragraph),
+            summary("para-only-dated", NodeType::Paragraph),
+        ],
+        "events in 2023",
+        &weights,
+    );
+    // 0.25 + 1 outranks the best keyword hit, which stays ahead of the
+    // metadata-only node it ties with.
+    assert_eq!(
+        ids(&ranked),
+        vec!["para-dated", "para-best", "para-only-dated"]
+    );
+}
+
+#[test]
+fn weights_are_validated() {
+    let negative = RankingWeights {
+        metadata: -1.0,
+        ..RankingWeights::default()
+    };
+    assert!(ranking::validate(negative).is_err());
+    let no_keywords = RankingWeights {
+        title: 0.0,
+        text: 0.0,
+        ..RankingWeights::default()
+    };
+    assert!(ranking::validate(no_keywords).is_err());
+
+    let weights = ranking::validate(RankingWeights {
+        node_types: vec![NodeTypeWeight {
+            node_type: NodeType::Figure,
+            weight: 2.0,
+            hints: vec![" Chart ".to_string(), "  ".to_string()],
+        }],
+        ..RankingWeights::default()
+    })
+    .expect("valid weights");
+    assert_eq!(weights.node_types[0].hints, vec!["chart"]);
+}
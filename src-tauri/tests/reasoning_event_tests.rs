@@ -1,8 +1,10 @@
 use std::sync::{Arc, Mutex};
 
 use vectorless_lib::{
-    core::errors::AppError,
-    db::{repositories::documents, Database},
+    db::{
+        repositories::{documents, reasoning},
+        Database,
+    },
     providers::gemini::GeminiClient,
     reasoner::executor::ReasoningExecutor,
     sidecar::types::SidecarNode,
@@ -74,9 +76,10 @@ async fn reasoning_step_event_includes_node_refs() {
         )
         .await;
 
+    let result = result.expect("run should answer");
     assert!(
-        matches!(result, Err(AppError::QualityGateFailed(_))),
-        "quality gate should reject incomplete low-quality runs"
+        result.insufficient_evidence,
+        "quality gate should turn incomplete low-quality runs into a gaps answer"
     );
 
     let observed = events.lock().expect("events lock");
@@ -85,10 +88,24 @@ async fn reasoning_step_event_includes_node_refs() {
         observed.iter().any(|event| !event.node_refs.is_empty()),
         "expected at least one step to include node references",
     );
+    assert!(
+        observed
+            .iter()
+            .any(|event| event.step_type == "select_sections"
+                && event.summary.contains("\"Latency\"")),
+        "expected the section step to name the section it picked",
+    );
+    assert!(
+        observed
+            .iter()
+            .flat_map(|event| &event.resolved_refs)
+            .any(|node| node.title == "Latency" && node.document_name == "Spec.pdf"),
+        "expected node refs to arrive resolved",
+    );
 }
 
 #[tokio::test]
-async fn reasoning_run_without_synthesis_is_rejected_by_quality_gate() {
+async fn reasoning_run_without_synthesis_reports_missing_evidence() {
     let db = Database::in_memory().await.expect("db should initialize");
     let doc_id = "doc-reasoning-2";
     documents::insert_document(
@@ -149,8 +166,21 @@ async fn reasoning_run_without_synthesis_is_rejected_by_quality_gate() {
         )
         .await;
 
-    assert!(
-        matches!(result, Err(AppError::QualityGateFailed(_))),
-        "quality gate should reject runs that never synthesize grounded answers"
-    );
+    let result = result.expect("run should answer");
+    assert!(result.insufficient_evidence);
+
+    let stored = reasoning::get_run(db.pool(), "run-reasoning-2")
+        .await
+        .expect("stored run");
+    let answer = stored.answer.expect("stored answer");
+    assert!(!answer.grounded);
+    assert!(answer.answer_markdown.starts_with("## Not enough evidence"));
+    assert!(answer.answer_markdown.contains("### Sections searched"));
+    assert!(stored
+        .run
+        .planner_trace_json
+        .as_array()
+        .is_some_and(|steps| steps
+            .iter()
+            .any(|step| step["step"] == "insufficient_evidence")));
 }
@@ -0,0 +1,245 @@
+use futures::future::BoxFuture;
+
+use vectorless_lib::{
+    core::{
+        errors::{AppError, AppResult},
+        types::{QualityThresholds, RunPhase, RunStatus},
+    },
+    db::{
+        repositories::{documents, reasoning, settings},
+        Database,
+    },
+    providers::{CallKind, LlmProvider, ProviderResponse},
+    reasoner::executor::{replay_run, ReasoningExecutor, RunEvent, RunOptions},
+    sidecar::types::SidecarNode,
+};
+
+struct ScriptedProvider;
+
+impl LlmProvider for ScriptedProvider {
+    fn model(&self) -> &str {
+        "scripted"
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        let citation = prompt
+            .split("[citation:")
+            .nth(1)
+            .and_then(|rest| rest.split(']').next())
+            .unwrap_or_default()
+            .to_string();
+        let text = match kind {
+            CallKind::Planner => "not a plan".to_string(),
+            CallKind::Synthesis => serde_json::json!({
+                "answer_markdown": "Latency dropped to 50ms p99.",
+                "confidence": 0.9,
+                "citations": [citation],
+            })
+            .to_string(),
+        };
+        Box::pin(async move {
+            Ok(ProviderResponse {
+                text,
+                token_usage: serde_json::json!({}),
+            })
+        })
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-replay-1",
+        "project-default",
+        "Spec.pdf",
+        "application/pdf",
+        "checksum-replay-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        SidecarNode {
+            id: "root-replay-1".to_string(),
+            parent_id: None,
+            node_type: "Document".to_string(),
+            title: "Spec".to_string(),
+            text: "".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "root".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+        SidecarNode {
+            id: "sec-replay-1".to_string(),
+            parent_id: Some("root-replay-1".to_string()),
+            node_type: "Section".to_string(),
+            title: "Latency".to_string(),
+            text: "Latency dropped to 50ms p99.".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "1".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+    ];
+    documents::insert_nodes(db.pool(), "doc-replay-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+#[tokio::test]
+async fn replay_reproduces_run_from_recorded_responses() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let executor = ReasoningExecutor::new(ScriptedProvider);
+    executor
+        .run(
+            &db,
+            "project-default",
+            Some("doc-replay-1"),
+            "run-live-1".to_string(),
+            "What is the latency?",
+            Some(6),
+            "unused",
+            |_| {},
+        )
+        .await
+        .expect("live run should complete");
+
+    let recorded = reasoning::list_provider_responses(db.pool(), "run-live-1")
+        .await
+        .expect("recorded responses");
+    assert!(recorded.iter().any(|entry| entry.call_kind == "planner"));
+    assert!(recorded.iter().any(|entry| entry.call_kind == "synthesis"));
+
+    replay_run(&db, "run-live-1", "run-replay-1".to_string(), |_| {})
+        .await
+        .expect("replay should complete");
+
+    let live = reasoning::get_run(db.pool(), "run-live-1")
+        .await
+        .expect("live run");
+    let replayed = reasoning::get_run(db.pool(), "run-replay-1")
+        .await
+        .expect("replayed run");
+    assert_eq!(replayed.run.status, RunStatus::Completed);
+    assert_eq!(replayed.run.replay_of.as_deref(), Some("run-live-1"));
+    assert_eq!(replayed.run.max_steps, Some(6));
+    assert_eq!(replayed.steps.len(), live.steps.len());
+    assert_eq!(
+        replayed.answer.expect("replayed answer").answer_markdown,
+        live.answer.expect("live answer").answer_markdown
+    );
+}
+
+#[tokio::test]
+async fn replay_reads_the_query_with_the_runs_own_settings() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let mut strict = QualityThresholds::default();
+    strict.factual.min_overall = 0.95;
+    ReasoningExecutor::new(ScriptedProvider)
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-replay-1"),
+            "run-live-2".to_string(),
+            "What is the latency?",
+            RunOptions {
+                quality: strict.clone(),
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("live run should complete");
+    // The settings change after the run; its replay must not notice.
+    settings::set_setting(
+        db.pool(),
+        settings::QUALITY_THRESHOLDS_SETTING,
+        &QualityThresholds::default(),
+    )
+    .await
+    .expect("save thresholds");
+
+    replay_run(&db, "run-live-2", "run-replay-3".to_string(), |_| {})
+        .await
+        .expect("replay should complete");
+    let live = reasoning::get_run(db.pool(), "run-live-2")
+        .await
+        .expect("live run")
+        .run
+        .manifest
+        .expect("live manifest");
+    let replayed = reasoning::get_run(db.pool(), "run-replay-3")
+        .await
+        .expect("replayed run")
+        .run
+        .manifest
+        .expect("replayed manifest");
+    assert_eq!(live.quality, Some(strict));
+    assert_eq!(replayed.quality, live.quality);
+    assert_eq!(replayed.quality_policy, live.quality_policy);
+    assert_eq!(replayed.language, live.language);
+    assert_eq!(replayed.privacy, live.privacy);
+}
+
+#[tokio::test]
+async fn replay_requires_recorded_responses() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    reasoning::create_run(db.pool(), "run-empty-1", "project-default", None, "q")
+        .await
+        .expect("create run");
+
+    let result = replay_run(&db, "run-empty-1", "run-replay-2".to_string(), |_| {}).await;
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+}
+
+#[tokio::test]
+async fn run_emits_phase_transitions_once_per_change() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let executor = ReasoningExecutor::new(ScriptedProvider);
+    let mut phases = Vec::new();
+    executor
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-replay-1"),
+            "run-phase-1".to_string(),
+            "What is the latency?",
+            RunOptions::default(),
+            |event| {
+                if let RunEvent::Phase(phase) = event {
+                    phases.push((phase.previous_phase, phase.phase));
+                }
+            },
+        )
+        .await
+        .expect("run should complete");
+
+    assert_eq!(
+        phases.first().map(|(_, phase)| phase.clone()),
+        Some(RunPhase::Retrieval)
+    );
+    assert_eq!(
+        phases.last().map(|(_, phase)| phase.clone()),
+        Some(RunPhase::Completed)
+    );
+    assert!(phases.iter().all(|(previous, phase)| previous != phase));
+
+    let stored = reasoning::get_run(db.pool(), "run-phase-1")
+        .await
+        .expect("stored run");
+    assert_eq!(stored.run.phase, RunPhase::Completed);
+}
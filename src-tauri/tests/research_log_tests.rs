@@ -0,0 +1,161 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::{NodeType, ResearchLogSettings},
+    db::{
+        repositories::{documents, projects, reasoning},
+        Database,
+    },
+    reasoner::research_log::{self, append_run},
+};
+
+async fn seed(db: &Database) {
+    seed_document(
+        db,
+        "project-default",
+        "doc-latency",
+        "Latency.md",
+        &[
+            node("root-latency", None, "Document", ""),
+            node(
+                "sec-cache",
+                Some("root-latency"),
+                "Section",
+                "p99 latency dropped to 50ms after the cache rollout.",
+            ),
+        ],
+    )
+    .await;
+}
+
+async fn answered_run(db: &Database, id: &str, query: &str, answer: &str, grounded: bool) {
+    reasoning::create_run(db.pool(), id, "project-default", None, query)
+        .await
+        .expect("create run");
+    reasoning::complete_run(
+        db.pool(),
+        id,
+        10,
+        serde_json::json!({}),
+        0.0,
+        answer,
+        vec!["sec-cache".to_string(), "sec-removed".to_string()],
+        0.9,
+        grounded,
+        serde_json::json!({}),
+        serde_json::json!([]),
+    )
+    .await
+    .expect("complete run");
+}
+
+async fn enable(db: &Database) {
+    projects::set_research_log_settings(
+        db.pool(),
+        "project-default",
+        &ResearchLogSettings { enabled: true },
+    )
+    .await
+    .expect("enable research log");
+}
+
+#[tokio::test]
+async fn nothing_is_logged_until_the_project_turns_the_log_on() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    answered_run(
+        &db,
+        "run-1",
+        "How fast is it?",
+        "Fast [citation:sec-cache].",
+        true,
+    )
+    .await;
+
+    assert_eq!(
+        projects::get_research_log_settings(db.pool(), "project-default")
+            .await
+            .expect("settings"),
+        ResearchLogSettings::default()
+    );
+    assert!(append_run(&db, "run-1").await.expect("append").is_none());
+    let checksum = research_log::log_checksum("project-default");
+    assert!(
+        documents::find_by_checksum(db.pool(), "project-default", &checksum)
+            .await
+            .expect("lookup")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn accepted_answers_are_appended_in_order_and_become_searchable() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    enable(&db).await;
+    answered_run(
+        &db,
+        "run-1",
+        "How fast is it?",
+        "p99 is 50ms [citation:sec-cache].\n\nEarlier numbers [citation:sec-removed] were higher.",
+        true,
+    )
+    .await;
+    answered_run(&db, "run-2", "Why is it slow?", "Unclear.", false).await;
+    answered_run(
+        &db,
+        "run-3",
+        "What changed?",
+        "The cache rollout [citation:sec-cache].",
+        true,
+    )
+    .await;
+
+    let first = append_run(&db, "run-1")
+        .await
+        .expect("append")
+        .expect("accepted answer is logged");
+    assert!(first.created);
+    assert!(append_run(&db, "run-2").await.expect("append").is_none());
+    let second = append_run(&db, "run-3")
+        .await
+        .expect("append")
+        .expect("accepted answer is logged");
+    assert!(!second.created);
+    assert_eq!(second.document_id, first.document_id);
+
+    let log = documents::get_document(db.pool(), &first.document_id)
+        .await
+        .expect("log document");
+    assert_eq!(log.name, research_log::LOG_NAME);
+    assert_eq!(log.project_id, "project-default");
+
+    let tree = documents::get_tree(db.pool(), &first.document_id, None, 8)
+        .await
+        .expect("tree");
+    let entries = tree
+        .iter()
+        .filter(|node| node.node_type == NodeType::Section)
+        .map(|node| node.title.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(entries, vec!["How fast is it?", "What changed?"]);
+
+    let paragraphs = tree
+        .iter()
+        .filter(|node| node.parent_id.as_deref() == Some(first.node_id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(paragraphs.len(), 3);
+    assert_eq!(paragraphs[0].text, "p99 is 50ms [1].");
+    assert_eq!(paragraphs[1].text, "Earlier numbers [2] were higher.");
+    assert_eq!(paragraphs[2].node_type, NodeType::Reference);
+    assert_eq!(
+        paragraphs[2].text,
+        "[1] Latency.md — sec-cache\n[2] (source no longer available)"
+    );
+
+    let hits = documents::search_project_nodes(db.pool(), "project-default", None, "rollout", 10)
+        .await
+        .expect("search");
+    assert!(hits.iter().any(|hit| hit.document_id == first.document_id));
+}
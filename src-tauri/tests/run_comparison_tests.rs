@@ -0,0 +1,122 @@
+use vectorless_lib::{
+    db::{
+        repositories::reasoning::{self, NewStep},
+        Database,
+    },
+    reasoner::compare::compare_runs,
+};
+
+async fn completed_run(
+    db: &Database,
+    id: &str,
+    steps: i64,
+    cost_usd: f64,
+    overall: f64,
+    citations: &[&str],
+) {
+    reasoning::create_run(
+        db.pool(),
+        id,
+        "project-default",
+        None,
+        "What is p99 latency?",
+    )
+    .await
+    .expect("create run");
+    for idx in 0..steps {
+        reasoning::add_step(
+            db.pool(),
+            NewStep {
+                run_id: id,
+                idx,
+                step_type: "scan_root",
+                thought: "",
+                action: "Scan_Root()",
+                observation: "",
+                summary: "",
+                node_refs: vec![],
+                resolved_refs: &[],
+                confidence: 0.5,
+                latency_ms: 1,
+            },
+        )
+        .await
+        .expect("add step");
+    }
+    reasoning::complete_run(
+        db.pool(),
+        id,
+        100 * steps,
+        serde_json::json!({}),
+        cost_usd,
+        "p99 is 50ms.",
+        citations.iter().map(ToString::to_string).collect(),
+        overall,
+        true,
+        serde_json::json!({
+            "overall": overall,
+            "queryAlignment": 0.5,
+            "citationCoverage": 1.0,
+            "crossDocumentCoverage": 0.0,
+            "grounded": true,
+        }),
+        serde_json::json!([]),
+    )
+    .await
+    .expect("complete run");
+}
+
+#[tokio::test]
+async fn compares_answers_citations_and_metrics() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    completed_run(&db, "run-a", 4, 0.02, 0.6, &["n1", "n2"]).await;
+    completed_run(&db, "run-b", 3, 0.01, 0.8, &["n2", "n3"]).await;
+    let a = reasoning::get_run(db.pool(), "run-a").await.expect("run a");
+    let b = reasoning::get_run(db.pool(), "run-b").await.expect("run b");
+
+    let comparison = compare_runs(&a, &b);
+    assert!(comparison.same_query);
+    assert_eq!(comparison.shared_citations, vec!["n2"]);
+    assert_eq!(comparison.only_in_a, vec!["n1"]);
+    assert_eq!(comparison.only_in_b, vec!["n3"]);
+    assert!((comparison.citation_overlap - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(comparison.run_a.step_count, 4);
+    assert_eq!(
+        comparison.run_b.answer_markdown.as_deref(),
+        Some("p99 is 50ms.")
+    );
+    assert_eq!(comparison.deltas.step_count, -1);
+    assert_eq!(comparison.deltas.total_latency_ms, Some(-100));
+    assert!((comparison.deltas.cost_usd + 0.01).abs() < 1e-9);
+    assert!((comparison.deltas.overall.expect("overall") - 0.2).abs() < 1e-9);
+    assert_eq!(comparison.deltas.query_alignment, Some(0.0));
+}
+
+#[tokio::test]
+async fn unfinished_runs_compare_without_metric_deltas() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    completed_run(&db, "run-a", 2, 0.01, 0.7, &["n1"]).await;
+    reasoning::create_run(
+        db.pool(),
+        "run-b",
+        "project-default",
+        None,
+        "Other question",
+    )
+    .await
+    .expect("create run");
+    reasoning::fail_run(db.pool(), "run-b")
+        .await
+        .expect("fail run");
+    let a = reasoning::get_run(db.pool(), "run-a").await.expect("run a");
+    let b = reasoning::get_run(db.pool(), "run-b").await.expect("run b");
+
+    let comparison = compare_runs(&a, &b);
+    assert!(!comparison.same_query);
+    assert_eq!(comparison.run_b.answer_markdown, None);
+    assert_eq!(comparison.run_b.quality, None);
+    assert_eq!(comparison.only_in_a, vec!["n1"]);
+    assert_eq!(comparison.citation_overlap, 0.0);
+    assert_eq!(comparison.deltas.overall, None);
+    assert_eq!(comparison.deltas.confidence, None);
+}
@@ -0,0 +1,243 @@
+use futures::future::BoxFuture;
+
+use vectorless_lib::{
+    core::errors::{AppError, AppResult},
+    db::{
+        repositories::{documents, reasoning},
+        Database,
+    },
+    providers::{CallKind, LlmProvider, ProviderResponse},
+    reasoner::executor::{ReasoningExecutor, RunEvent, RunOptions},
+    sidecar::types::SidecarNode,
+};
+
+/// Answers from, and cites, every piece of evidence it is shown.
+struct CitingProvider;
+
+impl LlmProvider for CitingProvider {
+    fn model(&self) -> &str {
+        "citing"
+    }
+
+    fn complete<'a>(
+        &'a self,
+        _api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        let citations = prompt
+            .split("[citation:")
+            .skip(1)
+            .filter_map(|rest| rest.split(']').next())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let text = match kind {
+            CallKind::Planner => "not a plan".to_string(),
+            CallKind::Synthesis => serde_json::json!({
+                "answer_markdown": format!(
+                    "Latency is 50ms p99 in the US and 80ms p99 in the EU. {}",
+                    citations
+                        .iter()
+                        .map(|id| format!("[citation:{id}]"))
+                        .collect::<String>()
+                ),
+                "confidence": 0.9,
+                "citations": citations,
+            })
+            .to_string(),
+        };
+        Box::pin(async move {
+            Ok(ProviderResponse {
+                text,
+                token_usage: serde_json::json!({}),
+            })
+        })
+    }
+}
+
+/// [`CitingProvider`] with room in its context window for only the first
+/// piece of evidence.
+struct NarrowProvider(CitingProvider);
+
+impl LlmProvider for NarrowProvider {
+    fn model(&self) -> &str {
+        self.0.model()
+    }
+
+    fn context_window(&self) -> usize {
+        1
+    }
+
+    fn complete<'a>(
+        &'a self,
+        api_key: &'a str,
+        kind: CallKind,
+        prompt: &'a str,
+    ) -> BoxFuture<'a, AppResult<ProviderResponse>> {
+        self.0.complete(api_key, kind, prompt)
+    }
+}
+
+async fn seed(db: &Database, document_id: &str, title: &str, text: &str) {
+    documents::insert_document(
+        db.pool(),
+        document_id,
+        "project-default",
+        &format!("{title}.pdf"),
+        "application/pdf",
+        &format!("checksum-{document_id}"),
+        1,
+    )
+    .await
+    .expect("insert document");
+    let node = |id: String, parent_id: Option<String>, node_type: &str, text: &str| SidecarNode {
+        id,
+        parent_id,
+        node_type: node_type.to_string(),
+        title: title.to_string(),
+        text: text.to_string(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: "1".to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    };
+    let root = format!("root-{document_id}");
+    let nodes = vec![
+        node(root.clone(), None, "Document", ""),
+        node(format!("sec-{document_id}"), Some(root), "Section", text),
+    ];
+    documents::insert_nodes(db.pool(), document_id, &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+#[tokio::test]
+async fn an_added_document_joins_the_evidence_of_a_finished_run() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db, "doc-us", "US", "Latency in the US region is 50ms p99.").await;
+    seed(&db, "doc-eu", "EU", "Latency in the EU region is 80ms p99.").await;
+    let executor = ReasoningExecutor::new(CitingProvider);
+    executor
+        .run(
+            &db,
+            "project-default",
+            Some("doc-us"),
+            "run-1".to_string(),
+            "What is the latency?",
+            Some(6),
+            "unused",
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+    let before = reasoning::get_run(db.pool(), "run-1").await.expect("run");
+
+    let mut steps = vec![];
+    let result = executor
+        .add_document(&db, "run-1", "doc-eu", RunOptions::default(), |event| {
+            if let RunEvent::Step(step) = event {
+                steps.push(step);
+            }
+        })
+        .await
+        .expect("document should be added");
+    assert_eq!(result.run_id, "run-1");
+    assert_eq!(
+        steps
+            .iter()
+            .map(|step| step.step_type.as_str())
+            .collect::<Vec<_>>(),
+        vec!["extract_evidence", "synthesize"]
+    );
+    assert_eq!(steps[0].node_refs, vec!["sec-doc-eu"]);
+    let earlier = before.answer.as_ref().expect("answer").citations.len();
+    assert!(earlier > 0);
+    assert!(steps[0]
+        .observation
+        .ends_with(&format!("to the {earlier} already used")));
+
+    let after = reasoning::get_run(db.pool(), "run-1").await.expect("run");
+    assert_eq!(after.steps.len(), before.steps.len() + 2);
+    assert_eq!(
+        after.steps.last().expect("step").idx,
+        before.steps.len() as i64 + 2
+    );
+    let evidence_documents = after
+        .run
+        .manifest
+        .expect("manifest")
+        .documents
+        .into_iter()
+        .map(|document| document.id)
+        .collect::<Vec<_>>();
+    assert!(evidence_documents.contains(&"doc-eu".to_string()));
+    let trace = after.run.planner_trace_json.as_array().expect("trace");
+    assert!(trace
+        .iter()
+        .any(|entry| entry["step"] == "add_document" && entry["documentId"] == "doc-eu"));
+    assert_eq!(
+        after.run.token_usage_json["addedDocuments"][0]["documentId"],
+        "doc-eu"
+    );
+
+    // The earlier answer is kept as a revision.
+    let revisions = reasoning::list_answer_revisions(db.pool(), "run-1")
+        .await
+        .expect("revisions");
+    assert_eq!(revisions.len(), 2);
+    assert!(revisions[0].current);
+}
+
+#[tokio::test]
+async fn an_added_document_is_not_the_first_evidence_cut_to_fit_the_prompt() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db, "doc-us", "US", "Latency in the US region is 50ms p99.").await;
+    seed(&db, "doc-eu", "EU", "Latency in the EU region is 80ms p99.").await;
+    ReasoningExecutor::new(CitingProvider)
+        .run(
+            &db,
+            "project-default",
+            Some("doc-us"),
+            "run-1".to_string(),
+            "What is the latency?",
+            Some(6),
+            "unused",
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+    let before = reasoning::get_run(db.pool(), "run-1").await.expect("run");
+    assert!(!before.answer.expect("answer").citations.is_empty());
+
+    ReasoningExecutor::new(NarrowProvider(CitingProvider))
+        .add_document(&db, "run-1", "doc-eu", RunOptions::default(), |_| {})
+        .await
+        .expect("document should be added");
+    let after = reasoning::get_run(db.pool(), "run-1").await.expect("run");
+    assert_eq!(
+        after.answer.expect("answer").citations,
+        vec!["sec-doc-eu".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn documents_are_only_added_to_finished_runs() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db, "doc-eu", "EU", "Latency in the EU region is 80ms p99.").await;
+    reasoning::create_run(db.pool(), "run-open", "project-default", None, "q")
+        .await
+        .expect("create run");
+    let executor = ReasoningExecutor::new(CitingProvider);
+    let err = executor
+        .add_document(&db, "run-open", "doc-eu", RunOptions::default(), |_| {})
+        .await
+        .expect_err("run is still running");
+    assert!(matches!(err, AppError::InvalidInput(_)));
+
+    let err = executor
+        .add_document(&db, "run-missing", "doc-eu", RunOptions::default(), |_| {})
+        .await
+        .expect_err("no such run");
+    assert!(matches!(err, AppError::NotFound(_)));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::{GenerationSettings, Provider, QualityThresholds, QueryClass},
+    db::{repositories::reasoning, Database},
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind,
+    },
+    reasoner::executor::{self, ReasoningExecutor, RunOptions},
+    security::key_pool::ApiKeyPool,
+};
+
+async fn seed(db: &Database) {
+    seed_document(
+        db,
+        "project-default",
+        "doc-manifest",
+        "Latency.md",
+        &[
+            node("root-manifest", None, "Document", ""),
+            node(
+                "sec-manifest",
+                Some("root-manifest"),
+                "Section",
+                "p99 latency dropped to 50ms after the cache rollout.",
+            ),
+        ],
+    )
+    .await;
+}
+
+fn provider() -> MockProvider {
+    let answer = serde_json::json!({
+        "answer_markdown": "p99 latency dropped to 50ms after the cache rollout.",
+        "confidence": 0.9,
+        "citations": ["sec-manifest"],
+    })
+    .to_string();
+    MockProvider::new(MockFixture {
+        model: "mock-manifest".to_string(),
+        context_window: None,
+        multimodal: false,
+        responses: vec![
+            MockResponse {
+                kind: CallKind::Planner,
+                request_hash: None,
+                text: "not a plan".to_string(),
+                token_usage: serde_json::json!({}),
+            },
+            MockResponse {
+                kind: CallKind::Synthesis,
+                request_hash: None,
+                text: answer,
+                token_usage: serde_json::json!({}),
+            },
+        ],
+    })
+}
+
+fn generation() -> GenerationSettings {
+    GenerationSettings {
+        temperature: Some(0.2),
+        max_output_tokens: Some(512),
+        ..GenerationSettings::default()
+    }
+}
+
+#[tokio::test]
+async fn runs_record_a_manifest_with_settings_and_document_checksums() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    ReasoningExecutor::new(provider())
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-manifest"),
+            "run-manifest".to_string(),
+            "What happened to latency?",
+            RunOptions {
+                max_steps: Some(6),
+                api_keys: ApiKeyPool::single("unused"),
+                generation: generation(),
+                provider: Some(Provider::Gemini),
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+
+    let stored = reasoning::get_run(db.pool(), "run-manifest")
+        .await
+        .expect("run");
+    let manifest = stored.run.manifest.expect("manifest");
+    assert_eq!(manifest.app_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(manifest.provider, Some(Provider::Gemini));
+    assert_eq!(manifest.model, "mock-manifest");
+    assert_eq!(manifest.generation, generation());
+    assert_eq!(manifest.max_steps, 6);
+    assert_eq!(manifest.focus_document_id.as_deref(), Some("doc-manifest"));
+    assert_eq!(manifest.query_class, Some(QueryClass::Factual));
+    assert_eq!(
+        manifest.quality_policy,
+        Some(QualityThresholds::default().factual)
+    );
+    assert_eq!(manifest.prompt_versions, stored.run.prompt_versions_json);
+    assert_eq!(manifest.documents.len(), 1);
+    assert_eq!(manifest.documents[0].id, "doc-manifest");
+    assert_eq!(manifest.documents[0].checksum, "checksum-doc-manifest");
+
+    // A replay reuses the recorded settings and points back at its source.
+    executor::replay_run(
+        &db,
+        "run-manifest",
+        "run-manifest-replay".to_string(),
+        |_| {},
+    )
+    .await
+    .expect("replay");
+    let replayed = reasoning::get_run(db.pool(), "run-manifest-replay")
+        .await
+        .expect("replayed run")
+        .run
+        .manifest
+        .expect("replay manifest");
+    assert_eq!(replayed.generation, generation());
+    assert_eq!(replayed.replay_of.as_deref(), Some("run-manifest"));
+    assert_eq!(replayed.documents, manifest.documents);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::RunPhase,
+    db::{repositories::reasoning, Database},
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind,
+    },
+    reasoner::{
+        executor::{ReasoningExecutor, RunOptions},
+        timeline::build_timeline,
+    },
+    security::key_pool::ApiKeyPool,
+};
+
+async fn seed(db: &Database) {
+    seed_document(
+        db,
+        "project-default",
+        "doc-timeline",
+        "Latency.md",
+        &[
+            node("root-timeline", None, "Document", ""),
+            node(
+                "sec-timeline",
+                Some("root-timeline"),
+                "Section",
+                "p99 latency dropped to 50ms after the cache rollout.",
+            ),
+        ],
+    )
+    .await;
+}
+
+fn provider() -> MockProvider {
+    let answer = serde_json::json!({
+        "answer_markdown": "p99 latency dropped to 50ms after the cache rollout.",
+        "confidence": 0.9,
+        "citations": ["sec-timeline"],
+    })
+    .to_string();
+    MockProvider::new(MockFixture {
+        model: "mock-timeline".to_string(),
+        context_window: None,
+        multimodal: false,
+        responses: vec![
+            MockResponse {
+                kind: CallKind::Planner,
+                request_hash: None,
+                text: "not a plan".to_string(),
+                token_usage: serde_json::json!({}),
+            },
+            MockResponse {
+                kind: CallKind::Synthesis,
+                request_hash: None,
+                text: answer,
+                token_usage: serde_json::json!({}),
+            },
+        ],
+    })
+}
+
+#[tokio::test]
+async fn timeline_splits_run_time_by_phase_step_and_provider_call() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    ReasoningExecutor::new(provider())
+        .run_with_options(
+            &db,
+            "project-default",
+            Some("doc-timeline"),
+            "run-timeline".to_string(),
+            "What happened to latency?",
+            RunOptions {
+                max_steps: Some(6),
+                api_keys: ApiKeyPool::single("unused"),
+                ..RunOptions::default()
+            },
+            |_| {},
+        )
+        .await
+        .expect("run should complete");
+
+    let mut run = reasoning::get_run(db.pool(), "run-timeline")
+        .await
+        .expect("run");
+    let mut calls = reasoning::list_provider_responses(db.pool(), "run-timeline")
+        .await
+        .expect("calls");
+    assert!(calls.iter().all(|call| !call.cached));
+
+    let timeline = build_timeline(&run, &calls);
+    assert_eq!(timeline.steps.len(), run.steps.len());
+    assert_eq!(timeline.provider_calls.len(), calls.len());
+    assert_eq!(timeline.phases.len(), 4);
+    assert!(timeline.unaccounted_ms.is_some());
+
+    // Pin the durations so the arithmetic can be checked exactly.
+    run.run.total_latency_ms = Some(1_000);
+    for step in &mut run.steps {
+        step.latency_ms = match step.step_type.as_str() {
+            "synthesize" => 400,
+            "self_check" => 20,
+            _ => 30,
+        };
+    }
+    for call in &mut calls {
+        call.latency_ms = match call.call_kind.as_str() {
+            "synthesis" => 350,
+            _ => 100,
+        };
+    }
+    let retrieval_steps = run
+        .steps
+        .iter()
+        .filter(|step| !matches!(step.step_type.as_str(), "synthesize" | "self_check"))
+        .count() as i64;
+    let planner_calls = calls
+        .iter()
+        .filter(|call| call.call_kind == "planner")
+        .count() as i64;
+
+    let timeline = build_timeline(&run, &calls);
+    let phase = |phase: RunPhase| {
+        timeline
+            .phases
+            .iter()
+            .find(|timing| timing.phase == phase)
+            .cloned()
+            .expect("phase")
+    };
+    assert_eq!(phase(RunPhase::Planning).duration_ms, planner_calls * 100);
+    assert_eq!(phase(RunPhase::Retrieval).duration_ms, retrieval_steps * 30);
+    assert_eq!(phase(RunPhase::Synthesis).duration_ms, 400);
+    assert_eq!(phase(RunPhase::Synthesis).provider_ms, 350);
+    assert_eq!(phase(RunPhase::Validation).duration_ms, 20);
+    assert_eq!(timeline.provider_ms, planner_calls * 100 + 350);
+    assert_eq!(timeline.db_ms, retrieval_steps * 30 + 50 + 20);
+    assert_eq!(
+        timeline.unaccounted_ms,
+        Some(1_000 - planner_calls * 100 - retrieval_steps * 30 - 420)
+    );
+}
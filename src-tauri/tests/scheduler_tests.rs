@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use vectorless_lib::{
+    commands::health::health,
+    core::{scheduler::Scheduler, types::QueueDepth},
+    db::Database,
+};
+
+#[tokio::test]
+async fn background_work_waits_for_interactive_runs() {
+    let scheduler = Scheduler::new(2);
+    let query = scheduler.interactive();
+
+    let waiting = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move {
+            let _permit = scheduler.background().await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!waiting.is_finished(), "write ran during a query");
+    assert_eq!(
+        scheduler.depth(),
+        QueueDepth {
+            interactive_active: 1,
+            background_running: 0,
+            background_queued: 1,
+        }
+    );
+
+    drop(query);
+    tokio::time::timeout(Duration::from_secs(2), waiting)
+        .await
+        .expect("write should start once the query ends")
+        .expect("task should not panic");
+    assert_eq!(scheduler.depth(), QueueDepth::default());
+}
+
+#[tokio::test]
+async fn background_slots_limit_concurrent_writers() {
+    let scheduler = Scheduler::new(1);
+    let first = scheduler.background().await;
+    let second = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move {
+            let _permit = scheduler.background().await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(scheduler.depth().background_running, 1);
+    assert_eq!(scheduler.depth().background_queued, 1);
+
+    drop(first);
+    tokio::time::timeout(Duration::from_secs(2), second)
+        .await
+        .expect("second writer should run")
+        .expect("task should not panic");
+}
+
+#[tokio::test]
+async fn interactive_permits_never_wait() {
+    let scheduler = Scheduler::new(1);
+    let _write = scheduler.background().await;
+    let a = scheduler.interactive();
+    let b = scheduler.interactive();
+    assert_eq!(scheduler.depth().interactive_active, 2);
+    drop((a, b));
+    assert_eq!(scheduler.depth().interactive_active, 0);
+}
+
+#[tokio::test]
+async fn cancelled_wait_leaves_the_queue() {
+    let scheduler = Scheduler::new(1);
+    let query = scheduler.interactive();
+    let result = tokio::time::timeout(Duration::from_millis(20), scheduler.background()).await;
+    assert!(result.is_err());
+    assert_eq!(scheduler.depth().background_queued, 0);
+    drop(query);
+}
+
+#[tokio::test]
+async fn health_check_reports_database_and_queue() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let scheduler = Scheduler::default();
+    let _query = scheduler.interactive();
+
+    let report = health(&db, &scheduler).await;
+    assert!(report.database_ok);
+    assert_eq!(report.pool_size, 1);
+    assert_eq!(report.queue.interactive_active, 1);
+    let value = serde_json::to_value(&report).expect("serialize");
+    assert_eq!(value["queue"]["backgroundQueued"], 0);
+}
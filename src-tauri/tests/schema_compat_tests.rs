@@ -0,0 +1,110 @@
+use vectorless_lib::{
+    core::errors::AppError,
+    db::{repositories::projects, schema, Database, MigrationPolicy},
+};
+
+const FUTURE_VERSION: i64 = 9999;
+
+async fn database_from_newer_app(dir: &std::path::Path) {
+    let db = Database::new(dir).await.expect("db should open");
+    projects::create_project(db.writer(), "project-keep", "Keep me")
+        .await
+        .expect("create project");
+    sqlx::query(
+        r#"
+        INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+        VALUES (?1, 'added by a newer release', 1, X'00', 0)
+        "#,
+    )
+    .bind(FUTURE_VERSION)
+    .execute(db.writer())
+    .await
+    .expect("record future migration");
+    db.writer().close().await;
+    db.pool().close().await;
+}
+
+#[tokio::test]
+async fn newer_schema_is_refused_with_typed_error() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    database_from_newer_app(dir.path()).await;
+
+    for policy in [MigrationPolicy::Apply, MigrationPolicy::Defer] {
+        let err = Database::open(dir.path(), policy)
+            .await
+            .err()
+            .expect("newer schema should be refused");
+        match &err {
+            AppError::IncompatibleSchema {
+                database_version,
+                app_version,
+            } => {
+                assert_eq!(*database_version, FUTURE_VERSION);
+                assert_eq!(*app_version, schema::latest_version());
+            }
+            other => panic!("unexpected error {other:?}"),
+        }
+        assert_eq!(err.code(), "INCOMPATIBLE_SCHEMA");
+        assert!(err.to_string().contains("export_portable_database"));
+    }
+}
+
+#[tokio::test]
+async fn read_only_open_reports_and_exports_newer_data() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    database_from_newer_app(dir.path()).await;
+
+    let db = Database::open_read_only(dir.path())
+        .await
+        .expect("read-only open");
+    let info = schema::schema_info(&db).await.expect("schema info");
+    assert!(info.read_only);
+    assert_eq!(info.unknown_versions, vec![FUTURE_VERSION]);
+
+    let err = schema::run_pending_migrations(&db)
+        .await
+        .err()
+        .expect("read-only database is not migrated");
+    assert!(matches!(err, AppError::InvalidInput(_)));
+    assert!(projects::create_project(db.writer(), "project-new", "New")
+        .await
+        .is_err());
+
+    let dest = dir.path().join("export").join("vectorless.json");
+    let report = schema::export_portable(&db, &dest).await.expect("export");
+    assert_eq!(report.schema_version, Some(FUTURE_VERSION));
+    assert!(report.rows > 0);
+
+    let exported: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&dest).expect("read export")).expect("json");
+    assert_eq!(exported["format"], schema::PORTABLE_FORMAT);
+    let tables = exported["tables"].as_array().expect("tables");
+    assert!(tables
+        .iter()
+        .all(|table| table["name"] != "_sqlx_migrations"));
+    let projects = tables
+        .iter()
+        .find(|table| table["name"] == "projects")
+        .expect("projects table");
+    let id_column = projects["columns"]
+        .as_array()
+        .expect("columns")
+        .iter()
+        .position(|column| column == "id")
+        .expect("id column");
+    assert!(projects["rows"]
+        .as_array()
+        .expect("rows")
+        .iter()
+        .any(|row| row[id_column] == "project-keep"));
+}
+
+#[test]
+fn incompatible_schema_error_is_not_retryable() {
+    let err = AppError::IncompatibleSchema {
+        database_version: 20,
+        app_version: 17,
+    };
+    assert!(!err.retryable());
+    assert!(err.to_string().contains("v20"));
+}
@@ -0,0 +1,90 @@
+use std::{borrow::Cow, path::Path, str::FromStr};
+
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use vectorless_lib::db::{
+    schema::{self, MIGRATOR},
+    Database, MigrationPolicy,
+};
+
+async fn open_pool(path: &Path) -> sqlx::SqlitePool {
+    let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", path.to_string_lossy()))
+        .expect("options")
+        .create_if_missing(true);
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .expect("pool")
+}
+
+#[tokio::test]
+async fn migrated_database_reports_no_pending_migrations() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let info = schema::schema_info(&db).await.expect("schema info");
+    assert!(info.pending.is_empty());
+    assert_eq!(info.current_version, Some(schema::latest_version()));
+    assert_eq!(info.applied.len(), MIGRATOR.iter().count());
+    assert!(info.applied.iter().all(|m| m.installed_on.is_some()));
+    assert_eq!(info.db_path, None);
+}
+
+#[tokio::test]
+async fn deferred_open_leaves_a_new_database_unmigrated() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db = Database::open(dir.path(), MigrationPolicy::Defer)
+        .await
+        .expect("db should open");
+    let info = schema::schema_info(&db).await.expect("schema info");
+    assert_eq!(info.current_version, None);
+    assert_eq!(info.pending.len(), MIGRATOR.iter().count());
+    assert!(info.db_file_bytes > 0);
+
+    let run = schema::run_pending_migrations(&db).await.expect("migrate");
+    assert_eq!(run.backup_path, None, "a new database needs no backup");
+    assert_eq!(run.applied.len(), MIGRATOR.iter().count());
+    assert_eq!(run.current_version, Some(schema::latest_version()));
+
+    let again = schema::run_pending_migrations(&db).await.expect("no-op");
+    assert!(again.applied.is_empty());
+    assert_eq!(again.backup_path, None);
+}
+
+#[tokio::test]
+async fn upgrading_backs_up_the_previous_schema() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let db_path = dir.path().join("vectorless.sqlite");
+    {
+        // An install from one release back: every migration but the newest.
+        let mut older = Migrator::new(Path::new("./src/db/migrations"))
+            .await
+            .expect("migrator");
+        let keep = older.migrations.len() - 1;
+        older.migrations = Cow::Owned(older.migrations[..keep].to_vec());
+        let pool = open_pool(&db_path).await;
+        older.run(&pool).await.expect("older migrations");
+        pool.close().await;
+    }
+
+    let db = Database::open(dir.path(), MigrationPolicy::Defer)
+        .await
+        .expect("db should open");
+    let info = schema::schema_info(&db).await.expect("schema info");
+    assert_eq!(info.pending.len(), 1);
+    assert_eq!(info.pending[0].version, schema::latest_version());
+    let previous = info.current_version.expect("older schema");
+
+    let run = schema::run_pending_migrations(&db).await.expect("migrate");
+    assert_eq!(run.applied.len(), 1);
+    assert_eq!(run.current_version, Some(schema::latest_version()));
+    let backup = run.backup_path.expect("backup of the older schema");
+    assert!(backup.contains(&format!("-v{previous}-")));
+
+    let backup_pool = open_pool(Path::new(&backup)).await;
+    let applied = schema::applied_migrations(&backup_pool)
+        .await
+        .expect("backup migrations");
+    assert_eq!(applied.last().map(|m| m.version), Some(previous));
+}
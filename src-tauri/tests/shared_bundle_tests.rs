@@ -0,0 +1,218 @@
+use std::io::{Read, Write};
+
+use vectorless_lib::{
+    core::errors::AppError,
+    db::{
+        bundle,
+        repositories::{assets, documents, projects},
+        Database,
+    },
+    providers::ImagePart,
+    sidecar::types::SidecarNode,
+};
+
+fn node(id: &str, parent: Option<&str>, node_type: &str, text: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: id.to_string(),
+        text: text.to_string(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({ "sourceNodeId": parent }),
+    }
+}
+
+async fn seed(db: &Database) {
+    projects::create_project(db.pool(), "project-client", "Client Review")
+        .await
+        .expect("create project");
+    documents::insert_document(
+        db.pool(),
+        "doc-report",
+        "project-client",
+        "Report.pdf",
+        "application/pdf",
+        "checksum-report",
+        3,
+    )
+    .await
+    .expect("insert document");
+    documents::insert_nodes(
+        db.pool(),
+        "doc-report",
+        &[
+            node("root", None, "Document", ""),
+            node("sec-1", Some("root"), "Section", "Findings"),
+            node("fig-1", Some("sec-1"), "Figure", "Revenue by quarter"),
+        ],
+    )
+    .await
+    .expect("insert nodes");
+    let image = ImagePart {
+        mime: "image/png".to_string(),
+        data: vec![0x89, b'P', b'N', b'G'],
+    };
+    assets::put_node_image(db, "fig-1", &image)
+        .await
+        .expect("store image");
+}
+
+#[tokio::test]
+async fn bundle_round_trips_into_a_read_only_project() {
+    let source = Database::in_memory().await.expect("db");
+    seed(&source).await;
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("client.vlbundle");
+
+    let exported = bundle::export_shared_bundle(&source, "project-client", &path)
+        .await
+        .expect("export");
+    assert_eq!(exported.manifest.documents, 1);
+    assert_eq!(exported.manifest.nodes, 3);
+    assert_eq!(
+        bundle::verify_shared_bundle(&path).expect("verify"),
+        exported.manifest
+    );
+
+    // Opening into the source database must not collide with the originals.
+    let opened = bundle::open_shared_bundle(&source, &path)
+        .await
+        .expect("open");
+    assert!(!opened.already_open);
+    assert!(opened.project.read_only);
+    assert_eq!(opened.project.name, "Client Review (shared)");
+
+    let docs = documents::list_documents(source.pool(), &opened.project.id, false)
+        .await
+        .expect("list");
+    assert_eq!(docs.len(), 1);
+    assert_ne!(docs[0].id, "doc-report");
+    let tree = documents::get_tree(source.pool(), &docs[0].id, None, 8)
+        .await
+        .expect("tree");
+    assert_eq!(tree.len(), 3);
+    let figure = tree
+        .iter()
+        .find(|n| n.text == "Revenue by quarter")
+        .expect("figure");
+    assert!(assets::get_node_image(&source, &figure.id)
+        .await
+        .expect("image")
+        .is_some());
+
+    assert!(matches!(
+        projects::ensure_writable(source.pool(), &opened.project.id).await,
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        projects::ensure_document_writable(source.pool(), &docs[0].id).await,
+        Err(AppError::InvalidInput(_))
+    ));
+    projects::ensure_document_writable(source.pool(), "doc-report")
+        .await
+        .expect("source stays writable");
+
+    let again = bundle::open_shared_bundle(&source, &path)
+        .await
+        .expect("reopen");
+    assert!(again.already_open);
+    assert_eq!(again.project.id, opened.project.id);
+}
+
+#[tokio::test]
+async fn bundles_edited_after_export_fail_their_checksum() {
+    let source = Database::in_memory().await.expect("db");
+    seed(&source).await;
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("client.vlbundle");
+    bundle::export_shared_bundle(&source, "project-client", &path)
+        .await
+        .expect("export");
+
+    // Rewrite the payload with an edited paragraph, keeping the manifest.
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).expect("open")).expect("zip");
+    let mut manifest = String::new();
+    archive
+        .by_name("manifest.json")
+        .expect("manifest")
+        .read_to_string(&mut manifest)
+        .expect("read");
+    let mut payload = String::new();
+    archive
+        .by_name("payload.json")
+        .expect("payload")
+        .read_to_string(&mut payload)
+        .expect("read");
+    let edited = dir.path().join("edited.vlbundle");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&edited).expect("create"));
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("manifest.json", options).expect("entry");
+    zip.write_all(manifest.as_bytes()).expect("write");
+    zip.start_file("payload.json", options).expect("entry");
+    zip.write_all(payload.replace("Revenue", "Losses").as_bytes())
+        .expect("write");
+    zip.finish().expect("finish");
+
+    let target = Database::in_memory().await.expect("db");
+    let err = bundle::open_shared_bundle(&target, &edited)
+        .await
+        .expect_err("edited bundle must be refused");
+    assert!(err.to_string().contains("checksum"), "{err}");
+    assert!(projects::list_projects(target.pool())
+        .await
+        .expect("list")
+        .iter()
+        .all(|project| !project.read_only));
+}
+
+#[tokio::test]
+async fn bundles_need_their_own_signature() {
+    let source = Database::in_memory().await.expect("db");
+    seed(&source).await;
+    let dir = tempfile::tempdir().expect("tempdir");
+    let first = dir.path().join("first.vlbundle");
+    let second = dir.path().join("second.vlbundle");
+    let exported = bundle::export_shared_bundle(&source, "project-client", &first)
+        .await
+        .expect("export");
+    assert!(!exported.manifest.signer_key.is_empty());
+    let other = bundle::export_shared_bundle(&source, "project-client", &second)
+        .await
+        .expect("export");
+    bundle::verify_shared_bundle(&first).expect("signed bundle verifies");
+
+    // The signature is left out of the checksum, so only its check catches
+    // one lifted from another bundle.
+    let mut archive =
+        zip::ZipArchive::new(std::fs::File::open(&first).expect("open")).expect("zip");
+    let mut manifest = String::new();
+    archive
+        .by_name("manifest.json")
+        .expect("manifest")
+        .read_to_string(&mut manifest)
+        .expect("read");
+    let mut payload = String::new();
+    archive
+        .by_name("payload.json")
+        .expect("payload")
+        .read_to_string(&mut payload)
+        .expect("read");
+    let mut manifest: serde_json::Value = serde_json::from_str(&manifest).expect("json");
+    manifest["signature"] = other.manifest.signature.clone().into();
+    let forged = dir.path().join("forged.vlbundle");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&forged).expect("create"));
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("manifest.json", options).expect("entry");
+    zip.write_all(manifest.to_string().as_bytes())
+        .expect("write");
+    zip.start_file("payload.json", options).expect("entry");
+    zip.write_all(payload.as_bytes()).expect("write");
+    zip.finish().expect("finish");
+
+    let err = bundle::verify_shared_bundle(&forged).expect_err("forged bundle must be refused");
+    assert!(err.to_string().contains("signature"), "{err}");
+}
@@ -0,0 +1,177 @@
+use std::path::Path;
+
+use vectorless_lib::{
+    db::{repositories::documents, Database},
+    sidecar::{
+        anchors::{assign_stable_ids, remap_node_ids, NodeFingerprint, NodeRemap},
+        native_parser,
+        types::NormalizedPayload,
+    },
+};
+
+const V1: &str = "# Intro\n\nAlpha paragraph about revenue.\n\nBeta paragraph about costs.\n\n# Results\n\nGamma paragraph about margins.\n";
+const V2: &str = "# Intro\n\nAlpha paragraph about revenue, restated.\n\nBeta paragraph about costs.\n\n# Results\n\nA new paragraph about hiring.\n\nGamma paragraph about margins.\n";
+const V3: &str = "# Intro\n\nAlpha paragraph about revenue, restated.\n\nBeta paragraph about costs.\n\n# Results\n\nA new paragraph about hiring.\n\nGamma paragraph about margins, corrected.\n";
+
+fn parse_version(path: &Path, text: &str, document_id: &str) -> NormalizedPayload {
+    std::fs::write(path, text).expect("write markdown");
+    let mut payload = native_parser::parse(path, "text/markdown").expect("parse markdown");
+    assign_stable_ids(document_id, &mut payload);
+    payload
+}
+
+fn id_of(payload: &NormalizedPayload, text_prefix: &str) -> String {
+    payload
+        .nodes
+        .iter()
+        .find(|node| node.text.starts_with(text_prefix))
+        .map(|node| node.id.clone())
+        .unwrap_or_else(|| panic!("no node starting with {text_prefix:?}"))
+}
+
+async fn reingest(db: &Database, document_id: &str, payload: &NormalizedPayload, checksum: &str) {
+    let old = documents::get_node_fingerprints(db.pool(), document_id)
+        .await
+        .expect("fingerprints");
+    let new = payload
+        .nodes
+        .iter()
+        .map(NodeFingerprint::of)
+        .collect::<Vec<_>>();
+    let remaps = remap_node_ids(&old, &new);
+    documents::replace_document_nodes(
+        db.pool(),
+        document_id,
+        checksum,
+        "text/markdown",
+        payload.document.pages,
+        &payload.nodes,
+        &remaps,
+    )
+    .await
+    .expect("replace nodes");
+}
+
+#[test]
+fn stable_ids_repeat_per_document_and_rewrite_links() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("report.md");
+    let first = parse_version(&path, V1, "doc-a");
+    let again = parse_version(&path, V1, "doc-a");
+    let other = parse_version(&path, V1, "doc-b");
+
+    let ids = |payload: &NormalizedPayload| {
+        payload
+            .nodes
+            .iter()
+            .map(|node| node.id.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&first), ids(&again));
+    assert!(ids(&first).iter().all(|id| !ids(&other).contains(id)));
+    assert!(id_of(&first, "Alpha").starts_with("p-"));
+
+    let known = ids(&first);
+    assert!(first
+        .nodes
+        .iter()
+        .filter_map(|node| node.parent_id.as_ref())
+        .all(|parent| known.contains(parent)));
+    assert!(first
+        .edges
+        .iter()
+        .all(|edge| known.contains(&edge.from) && known.contains(&edge.to)));
+}
+
+#[test]
+fn moved_nodes_match_by_content_and_edited_ones_by_position() {
+    let fingerprint = |id: &str, ordinal: &str, hash: &str| NodeFingerprint {
+        id: id.to_string(),
+        node_type: "Paragraph".to_string(),
+        ordinal_path: ordinal.to_string(),
+        content_hash: hash.to_string(),
+    };
+    let old = vec![
+        fingerprint("p-kept", "1.1", "kept"),
+        fingerprint("p-edited", "1.2", "before"),
+        fingerprint("p-moved", "1.3", "moved"),
+        fingerprint("p-removed", "1.4", "removed"),
+    ];
+    let new = vec![
+        fingerprint("p-kept", "1.1", "kept"),
+        fingerprint("p-edited-2", "1.2", "after"),
+        fingerprint("p-inserted", "1.3", "inserted"),
+        fingerprint("p-moved-2", "1.4", "moved"),
+    ];
+    assert_eq!(
+        remap_node_ids(&old, &new),
+        vec![
+            NodeRemap {
+                old_id: "p-moved".to_string(),
+                new_id: "p-moved-2".to_string(),
+            },
+            NodeRemap {
+                old_id: "p-edited".to_string(),
+                new_id: "p-edited-2".to_string(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn old_ids_resolve_after_repeated_reingestion() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("report.md");
+
+    let v1 = parse_version(&path, V1, "doc-anchor-1");
+    documents::insert_document(
+        db.pool(),
+        "doc-anchor-1",
+        "project-default",
+        "report.md",
+        "text/markdown",
+        "checksum-v1",
+        v1.document.pages,
+    )
+    .await
+    .expect("insert document");
+    documents::insert_nodes(db.pool(), "doc-anchor-1", &v1.nodes)
+        .await
+        .expect("insert nodes");
+
+    let v2 = parse_version(&path, V2, "doc-anchor-1");
+    reingest(&db, "doc-anchor-1", &v2, "checksum-v2").await;
+
+    assert_eq!(id_of(&v1, "Beta"), id_of(&v2, "Beta"));
+    let alpha = documents::get_node(db.pool(), &id_of(&v1, "Alpha"))
+        .await
+        .expect("edited node resolves");
+    assert_eq!(alpha.id, id_of(&v2, "Alpha"));
+    assert_eq!(alpha.text, "Alpha paragraph about revenue, restated.");
+    assert_eq!(
+        documents::remapped_node_id(db.pool(), &id_of(&v1, "Gamma"))
+            .await
+            .expect("lookup"),
+        Some(id_of(&v2, "Gamma"))
+    );
+    assert_eq!(
+        documents::get_document(db.pool(), "doc-anchor-1")
+            .await
+            .expect("document")
+            .checksum,
+        "checksum-v2"
+    );
+
+    let v3 = parse_version(&path, V3, "doc-anchor-1");
+    reingest(&db, "doc-anchor-1", &v3, "checksum-v3").await;
+
+    let gamma = documents::get_node(db.pool(), &id_of(&v1, "Gamma"))
+        .await
+        .expect("twice-replaced node resolves");
+    assert_eq!(gamma.id, id_of(&v3, "Gamma"));
+    assert_eq!(gamma.text, "Gamma paragraph about margins, corrected.");
+    assert!(documents::get_node(db.pool(), "p-never-existed")
+        .await
+        .is_err());
+}
@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use vectorless_lib::db::{
+    storage::{self, StorageMode},
+    Database, MigrationPolicy,
+};
+
+#[test]
+fn sync_folders_are_recognised() {
+    let cases = [
+        (
+            "/Users/ana/Library/Mobile Documents/com~apple~CloudDocs/vl/db.sqlite",
+            Some("iCloud Drive"),
+        ),
+        ("/Users/ana/Dropbox/vectorless/db.sqlite", Some("Dropbox")),
+        (
+            "C:\\Users\\ana\\OneDrive - Contoso\\db.sqlite",
+            Some("OneDrive"),
+        ),
+        (
+            "/Users/ana/Library/CloudStorage/GoogleDrive-ana@example.com/My Drive/db.sqlite",
+            Some("Google Drive"),
+        ),
+        (
+            "/Users/ana/Library/CloudStorage/Acme-ana/db.sqlite",
+            Some("a cloud storage provider"),
+        ),
+        ("/home/ana/.local/share/vectorless/vectorless.sqlite", None),
+        ("/home/ana/dropbox-export/db.sqlite", None),
+    ];
+    for (path, expected) in cases {
+        let path = path.replace('\\', std::path::MAIN_SEPARATOR_STR);
+        assert_eq!(storage::sync_provider(Path::new(&path)), expected, "{path}");
+    }
+}
+
+#[test]
+fn auto_mode_only_goes_sync_safe_inside_a_synced_folder() {
+    let synced = Path::new("/home/ana/Dropbox/db.sqlite");
+    let local = Path::new("/home/ana/db.sqlite");
+    assert!(StorageMode::Auto.sync_safe_for(synced));
+    assert!(!StorageMode::Auto.sync_safe_for(local));
+    assert!(!StorageMode::Standard.sync_safe_for(synced));
+    assert!(StorageMode::SyncSafe.sync_safe_for(local));
+}
+
+#[tokio::test]
+async fn sync_safe_databases_use_a_rollback_journal() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let synced = dir.path().join("Dropbox").join("vectorless.sqlite");
+    std::fs::create_dir_all(synced.parent().expect("parent")).expect("mkdir");
+
+    let db = Database::open_file(&synced, MigrationPolicy::Apply)
+        .await
+        .expect("open");
+    let status = storage::storage_status(&db).await.expect("status");
+    assert!(status.sync_safe);
+    assert_eq!(status.journal_mode, "delete");
+    assert_eq!(status.sync_provider.as_deref(), Some("Dropbox"));
+    assert!(status.warning.is_some());
+    sqlx::query("INSERT INTO projects (id, name) VALUES ('p1', 'Synced')")
+        .execute(db.writer())
+        .await
+        .expect("write");
+    assert!(!Path::new(&format!("{}-wal", synced.display())).exists());
+
+    // Moving it to a local folder keeps the data and goes back to WAL.
+    let local = dir.path().join("local").join("vectorless.sqlite");
+    storage::copy_to(&db, &local).await.expect("copy");
+    let moved = Database::open_file(&local, MigrationPolicy::Apply)
+        .await
+        .expect("open copy");
+    let status = storage::storage_status(&moved).await.expect("status");
+    assert!(!status.sync_safe);
+    assert_eq!(status.journal_mode, "wal");
+    assert_eq!(status.warning, None);
+    let name: String = sqlx::query_scalar("SELECT name FROM projects WHERE id = 'p1'")
+        .fetch_one(moved.pool())
+        .await
+        .expect("read");
+    assert_eq!(name, "Synced");
+}
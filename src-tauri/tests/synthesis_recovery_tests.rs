@@ -0,0 +1,200 @@
+use vectorless_lib::{
+    core::types::RunStatus,
+    db::{
+        repositories::{documents, reasoning},
+        Database,
+    },
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind,
+    },
+    reasoner::executor::ReasoningExecutor,
+    sidecar::types::SidecarNode,
+};
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-recovery-1",
+        "project-default",
+        "Spec.pdf",
+        "application/pdf",
+        "checksum-recovery-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        SidecarNode {
+            id: "root-recovery-1".to_string(),
+            parent_id: None,
+            node_type: "Document".to_string(),
+            title: "Spec".to_string(),
+            text: "".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "root".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+        SidecarNode {
+            id: "sec-recovery-1".to_string(),
+            parent_id: Some("root-recovery-1".to_string()),
+            node_type: "Section".to_string(),
+            title: "Latency".to_string(),
+            text: "Latency dropped to 50ms p99 after the cache rollout.".to_string(),
+            page_start: Some(1),
+            page_end: Some(1),
+            ordinal_path: "1".to_string(),
+            bbox: serde_json::json!({}),
+            metadata: serde_json::json!({}),
+        },
+    ];
+    documents::insert_nodes(db.pool(), "doc-recovery-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+fn provider(synthesis: &[&str]) -> MockProvider {
+    provider_with_usage(
+        &synthesis
+            .iter()
+            .map(|text| (*text, serde_json::json!({})))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn provider_with_usage(synthesis: &[(&str, serde_json::Value)]) -> MockProvider {
+    let mut responses = vec![MockResponse {
+        kind: CallKind::Planner,
+        request_hash: None,
+        text: "not a plan".to_string(),
+        token_usage: serde_json::json!({}),
+    }];
+    responses.extend(synthesis.iter().map(|(text, token_usage)| MockResponse {
+        kind: CallKind::Synthesis,
+        request_hash: None,
+        text: text.to_string(),
+        token_usage: token_usage.clone(),
+    }));
+    MockProvider::new(MockFixture {
+        model: "mock".to_string(),
+        context_window: None,
+        multimodal: false,
+        responses,
+    })
+}
+
+async fn run(db: &Database, provider: MockProvider, run_id: &str) {
+    ReasoningExecutor::new(provider)
+        .run(
+            db,
+            "project-default",
+            Some("doc-recovery-1"),
+            run_id.to_string(),
+            "What is the latency?",
+            Some(6),
+            "unused",
+            |_| {},
+        )
+        .await
+        .expect("run should recover and complete");
+}
+
+#[tokio::test]
+async fn strict_json_retry_recovers_non_json_synthesis() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let valid = serde_json::json!({
+        "answer_markdown": "Latency dropped to 50ms p99 after the cache rollout.",
+        "confidence": 0.9,
+        "citations": ["sec-recovery-1"],
+    })
+    .to_string();
+    run(
+        &db,
+        provider(&["Sure! Here is the answer.", &valid]),
+        "run-retry-1",
+    )
+    .await;
+
+    let stored = reasoning::get_run(db.pool(), "run-retry-1")
+        .await
+        .expect("run");
+    assert_eq!(stored.run.status, RunStatus::Completed);
+    let diagnostics = stored
+        .run
+        .diagnostics_json
+        .as_array()
+        .expect("diagnostics array");
+    assert_eq!(diagnostics[0]["error"], "PROVIDER_INVALID_RESPONSE");
+    assert_eq!(diagnostics[1]["recovery"], "strict_json_retry");
+}
+
+#[tokio::test]
+async fn failed_synthesis_attempts_count_toward_token_usage() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let valid = serde_json::json!({
+        "answer_markdown": "Latency dropped to 50ms p99 after the cache rollout.",
+        "confidence": 0.9,
+        "citations": ["sec-recovery-1"],
+    })
+    .to_string();
+    run(
+        &db,
+        provider_with_usage(&[
+            (
+                "Sure! Here is the answer.",
+                serde_json::json!({"promptTokenCount": 100, "candidatesTokenCount": 10}),
+            ),
+            (
+                &valid,
+                serde_json::json!({"promptTokenCount": 120, "candidatesTokenCount": 30}),
+            ),
+        ]),
+        "run-usage-1",
+    )
+    .await;
+
+    let stored = reasoning::get_run(db.pool(), "run-usage-1")
+        .await
+        .expect("run");
+    let usage = &stored.run.token_usage_json;
+    assert_eq!(usage["promptTokenCount"], 220);
+    assert_eq!(usage["candidatesTokenCount"], 40);
+    assert_eq!(usage["synthesis"]["promptTokenCount"], 220);
+}
+
+#[tokio::test]
+async fn raw_text_fallback_used_after_retries_exhausted() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    run(
+        &db,
+        provider(&["Latency dropped to 50ms p99 after the cache rollout."]),
+        "run-fallback-1",
+    )
+    .await;
+
+    let stored = reasoning::get_run(db.pool(), "run-fallback-1")
+        .await
+        .expect("run");
+    let diagnostics = stored
+        .run
+        .diagnostics_json
+        .as_array()
+        .expect("diagnostics array");
+    assert_eq!(
+        diagnostics.last().map(|entry| entry["recovery"].clone()),
+        Some(serde_json::json!("raw_text_fallback"))
+    );
+    assert!(stored
+        .answer
+        .expect("answer")
+        .answer_markdown
+        .contains("50ms p99"));
+}
@@ -0,0 +1,254 @@
+use vectorless_lib::{
+    core::types::{ColumnType, HeaderSource, TableSchema},
+    db::{repositories::documents, Database},
+    enrichment::tables::{
+        cell_number, enrich_table_schemas, infer_table_schema, parse_table, split_header_unit,
+        TABLE_SCHEMA_KEY,
+    },
+    providers::{
+        mock::{MockFixture, MockProvider, MockResponse},
+        CallKind, LlmProvider,
+    },
+    security::key_pool::ApiKeyPool,
+    sidecar::types::SidecarNode,
+};
+
+const REVENUE_TABLE: &str = "| Quarter | Revenue (USD m) | Growth |\n| --- | ---: | --- |\n| Q1 | 1,200 | 12% |\n| Q2 | 1,350.5 | 12.5% |\n| Q3 | - | n/a |";
+const HEADERLESS_TABLE: &str = "| Q1 | 12 |\n| Q2 | 18 |\n| Q3 | 21 |";
+
+fn node(id: &str, parent: Option<&str>, node_type: &str, text: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: format!("{node_type} {id}"),
+        text: text.to_string(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({"parser": "native"}),
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-tables-1",
+        "project-default",
+        "Report.md",
+        "text/markdown",
+        "checksum-tables-1",
+        1,
+    )
+    .await
+    .expect("insert document");
+    let nodes = vec![
+        node("root-tables", None, "Document", ""),
+        node("tbl-revenue", Some("root-tables"), "Table", REVENUE_TABLE),
+        node(
+            "tbl-headerless",
+            Some("root-tables"),
+            "Table",
+            HEADERLESS_TABLE,
+        ),
+        node(
+            "tbl-prose",
+            Some("root-tables"),
+            "Table",
+            "Revenue rose every quarter.",
+        ),
+    ];
+    documents::insert_nodes(db.pool(), "doc-tables-1", &nodes)
+        .await
+        .expect("insert nodes");
+}
+
+async fn stored_schema(db: &Database, node_id: &str) -> TableSchema {
+    let node = documents::get_node(db.pool(), node_id)
+        .await
+        .expect("table node");
+    serde_json::from_value(node.metadata_json[TABLE_SCHEMA_KEY].clone()).expect("stored schema")
+}
+
+fn provider(text: &str) -> MockProvider {
+    MockProvider::new(MockFixture {
+        model: "mock-enrich".to_string(),
+        context_window: None,
+        multimodal: false,
+        responses: vec![MockResponse {
+            kind: CallKind::Enrichment,
+            request_hash: None,
+            text: text.to_string(),
+            token_usage: serde_json::json!({}),
+        }],
+    })
+}
+
+#[test]
+fn markdown_headers_yield_names_units_and_types() {
+    let table = parse_table(REVENUE_TABLE).expect("markdown table");
+    assert!(table.markdown_header);
+    assert_eq!(table.rows.len(), 4);
+
+    let schema = infer_table_schema(&table);
+    assert_eq!(schema.header_source, HeaderSource::Markdown);
+    assert_eq!(schema.row_count, 3);
+    let summary = schema
+        .columns
+        .iter()
+        .map(|column| {
+            (
+                column.name.as_str(),
+                column.data_type,
+                column.unit.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("Quarter", ColumnType::Text, None),
+            ("Revenue", ColumnType::Number, Some("USD m")),
+            ("Growth", ColumnType::Percent, Some("%")),
+        ]
+    );
+}
+
+#[test]
+fn text_first_row_above_typed_rows_is_a_header() {
+    let table = parse_table(
+        "Date\tLatency\tPassed\tPrice\n2024-01-02\t12 ms\tyes\t$4.50\n2024-01-03\t15 ms\tno\t$5",
+    )
+    .expect("tsv table");
+    assert!(!table.markdown_header);
+
+    let schema = infer_table_schema(&table);
+    assert_eq!(schema.header_source, HeaderSource::Inferred);
+    assert!(schema.has_header_row);
+    let types = schema
+        .columns
+        .iter()
+        .map(|column| (column.data_type, column.unit.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            (ColumnType::Date, None),
+            (ColumnType::Integer, Some("ms")),
+            (ColumnType::Boolean, None),
+            (ColumnType::Currency, Some("$")),
+        ]
+    );
+}
+
+#[test]
+fn headerless_tables_keep_types_with_positional_names() {
+    let schema = infer_table_schema(&parse_table(HEADERLESS_TABLE).expect("table"));
+    assert_eq!(schema.header_source, HeaderSource::None);
+    assert!(!schema.has_header_row);
+    assert_eq!(schema.row_count, 3);
+    assert_eq!(schema.columns[0].name, "column_1");
+    assert_eq!(schema.columns[1].data_type, ColumnType::Integer);
+
+    assert!(parse_table("Revenue rose every quarter.").is_none());
+}
+
+#[test]
+fn cell_values_and_header_units_parse() {
+    assert_eq!(cell_number("$1,200"), Some(1200.0));
+    assert_eq!(cell_number("(3.5)"), Some(-3.5));
+    assert_eq!(cell_number("-12%"), Some(-12.0));
+    assert_eq!(cell_number("15 ms"), Some(15.0));
+    assert_eq!(cell_number("**42**"), Some(42.0));
+    assert_eq!(cell_number("n/a"), None);
+    assert_eq!(cell_number("Q1"), None);
+    assert_eq!(cell_number("2024-01-02"), None);
+
+    assert_eq!(
+        split_header_unit("Latency [ms]"),
+        ("Latency".to_string(), Some("ms".to_string()))
+    );
+    assert_eq!(
+        split_header_unit("Share %"),
+        ("Share".to_string(), Some("%".to_string()))
+    );
+    assert_eq!(split_header_unit("Region"), ("Region".to_string(), None));
+}
+
+#[tokio::test]
+async fn local_pass_types_tables_and_model_names_headerless_ones() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let local = enrich_table_schemas(&db, None, "doc-tables-1")
+        .await
+        .expect("local pass should run");
+    assert_eq!(local.tables_scanned, 3);
+    assert_eq!(local.inferred_locally, 1);
+    assert_eq!(local.unresolved, 1);
+    assert_eq!(local.unparseable, 1);
+
+    let revenue = documents::get_node(db.pool(), "tbl-revenue")
+        .await
+        .expect("table node");
+    assert_eq!(revenue.metadata_json["parser"], "native");
+    assert_eq!(
+        revenue.metadata_json[TABLE_SCHEMA_KEY]["columns"][1]["unit"],
+        "USD m"
+    );
+    assert_eq!(
+        stored_schema(&db, "tbl-headerless").await.header_source,
+        HeaderSource::None
+    );
+
+    let rerun = enrich_table_schemas(&db, None, "doc-tables-1")
+        .await
+        .expect("rerun should run");
+    assert_eq!(rerun.already_typed, 2);
+
+    let provider = provider(
+        &serde_json::json!({
+            "has_header_row": false,
+            "columns": [
+                {"name": "Quarter", "unit": null},
+                {"name": "Revenue", "unit": "USD m"}
+            ]
+        })
+        .to_string(),
+    );
+    let keys = ApiKeyPool::default();
+    let model: (&dyn LlmProvider, &ApiKeyPool) = (&provider, &keys);
+    let assisted = enrich_table_schemas(&db, Some(model), "doc-tables-1")
+        .await
+        .expect("model pass should run");
+    assert_eq!(assisted.already_typed, 1);
+    assert_eq!(assisted.inferred_with_model, 1);
+
+    let schema = stored_schema(&db, "tbl-headerless").await;
+    assert_eq!(schema.header_source, HeaderSource::Model);
+    assert_eq!(schema.row_count, 3);
+    assert_eq!(schema.columns[0].name, "Quarter");
+    assert_eq!(schema.columns[1].name, "Revenue");
+    assert_eq!(schema.columns[1].unit.as_deref(), Some("USD m"));
+    assert_eq!(schema.columns[1].data_type, ColumnType::Integer);
+}
+
+#[tokio::test]
+async fn model_answers_with_the_wrong_column_count_are_left_unresolved() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+
+    let provider = provider(r#"{"has_header_row": false, "columns": [{"name": "Quarter"}]}"#);
+    let keys = ApiKeyPool::default();
+    let model: (&dyn LlmProvider, &ApiKeyPool) = (&provider, &keys);
+    let report = enrich_table_schemas(&db, Some(model), "doc-tables-1")
+        .await
+        .expect("model pass should run");
+    assert_eq!(report.inferred_locally, 1);
+    assert_eq!(report.unresolved, 1);
+    assert_eq!(
+        stored_schema(&db, "tbl-headerless").await.header_source,
+        HeaderSource::None
+    );
+}
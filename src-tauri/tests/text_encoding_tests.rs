@@ -0,0 +1,94 @@
+use std::io::Write;
+
+use tempfile::NamedTempFile;
+use vectorless_lib::sidecar::{
+    encoding,
+    native_parser::{self, ParseLimits},
+    types::NormalizedPayload,
+};
+
+fn temp_file(contents: &[u8]) -> NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(".txt")
+        .tempfile()
+        .expect("temp file");
+    file.write_all(contents).expect("write file");
+    file
+}
+
+fn all_text(payload: &NormalizedPayload) -> String {
+    payload
+        .nodes
+        .iter()
+        .map(|node| node.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const FRENCH: &str = "Le café de la gare ouvre à sept heures. Les crêpes sont délicieuses, et le thé est très chaud. Où se trouve la bibliothèque municipale?";
+const JAPANESE: &str = "東京の天気は晴れです。明日は雨が降るでしょう。会議は午後三時に始まります。資料を準備してください。";
+
+#[test]
+fn windows_1252_text_is_decoded_and_recorded() {
+    let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode(FRENCH);
+    let file = temp_file(&bytes);
+
+    let payload = native_parser::parse(file.path(), "text/plain").expect("parses");
+
+    assert!(all_text(&payload).contains("Où se trouve la bibliothèque"));
+    assert_eq!(payload.document.metadata["encoding"], "windows-1252");
+    assert_eq!(payload.document.metadata["encodingLossy"], false);
+    let root = payload.nodes.first().expect("root");
+    assert_eq!(root.metadata["encoding"], "windows-1252");
+    assert_eq!(root.metadata["parser"], "native");
+}
+
+#[test]
+fn shift_jis_text_is_decoded() {
+    let text = JAPANESE.repeat(4);
+    let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode(&text);
+    let file = temp_file(&bytes);
+
+    let payload = native_parser::parse(file.path(), "text/plain").expect("parses");
+
+    assert!(all_text(&payload).contains("会議は午後三時に始まります"));
+    assert_eq!(payload.document.metadata["encoding"], "Shift_JIS");
+}
+
+#[test]
+fn utf8_and_bom_marked_files_keep_their_encoding() {
+    let utf8 = temp_file(FRENCH.as_bytes());
+    let payload = native_parser::parse(utf8.path(), "text/plain").expect("parses");
+    assert_eq!(payload.document.metadata["encoding"], "UTF-8");
+
+    let mut utf16 = vec![0xFF, 0xFE];
+    utf16.extend(FRENCH.encode_utf16().flat_map(u16::to_le_bytes));
+    let file = temp_file(&utf16);
+    let payload = native_parser::parse(file.path(), "text/plain").expect("parses");
+    assert!(all_text(&payload).contains("crêpes"));
+    assert_eq!(payload.document.metadata["encoding"], "UTF-16LE");
+}
+
+#[test]
+fn streamed_text_uses_the_detected_encoding() {
+    let text = format!("{FRENCH}\n\n").repeat(20);
+    let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode(&text);
+    let file = temp_file(&bytes);
+    let limits = ParseLimits {
+        stream_text_above_bytes: 0,
+        ..ParseLimits::default()
+    };
+
+    let payload =
+        native_parser::parse_with_limits(file.path(), "text/plain", limits).expect("parses");
+
+    assert!(all_text(&payload).contains("Les crêpes sont délicieuses"));
+    assert_eq!(payload.document.metadata["encoding"], "windows-1252");
+}
+
+#[test]
+fn truncated_utf8_sample_is_still_utf8() {
+    let bytes = "naïve".as_bytes();
+    let cut = &bytes[..3];
+    assert_eq!(encoding::detect(cut, false), encoding_rs::UTF_8);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{node, seed_document};
+use std::time::Duration;
+
+use vectorless_lib::{
+    db::{
+        repositories::{assets, documents},
+        Database,
+    },
+    sidecar::{
+        thumbnails,
+        types::{NormalizedPayload, SidecarDocument, SidecarNode},
+    },
+};
+
+fn payload(nodes: Vec<SidecarNode>) -> NormalizedPayload {
+    NormalizedPayload {
+        document: SidecarDocument {
+            title: "Quarterly <Report>".to_string(),
+            pages: 1,
+            metadata: serde_json::json!({}),
+        },
+        nodes,
+        edges: vec![],
+    }
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbImage::from_pixel(width, height, image::Rgb([20, 120, 200]));
+    let mut bytes = vec![];
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Png,
+        )
+        .expect("encode png");
+    bytes
+}
+
+#[test]
+fn image_files_are_scaled_down() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("photo.png");
+    std::fs::write(&path, png(1024, 512)).expect("write image");
+
+    let thumbnail = thumbnails::generate(&path, "image/png", &payload(vec![]));
+    assert_eq!(thumbnail.mime, "image/png");
+    let decoded = image::load_from_memory(&thumbnail.data).expect("decode thumbnail");
+    assert_eq!(
+        (decoded.width(), decoded.height()),
+        (
+            thumbnails::THUMBNAIL_MAX_EDGE,
+            thumbnails::THUMBNAIL_MAX_EDGE / 2
+        )
+    );
+}
+
+#[test]
+fn a_first_page_figure_is_used_before_a_text_card() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    std::fs::write(dir.path().join("slide.png"), png(64, 48)).expect("write figure");
+    let path = dir.path().join("deck.pptx");
+    let parsed = payload(vec![
+        node("root", None, "Document", ""),
+        node("fig", Some("root"), "Figure", "![Slide](slide.png)"),
+    ]);
+
+    let thumbnail = thumbnails::generate(
+        &path,
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        &parsed,
+    );
+    assert_eq!(thumbnail.mime, "image/png");
+}
+
+#[test]
+fn documents_without_pictures_get_an_escaped_text_card() {
+    let parsed = payload(vec![
+        node("root", None, "Document", ""),
+        node(
+            "para",
+            Some("root"),
+            "Paragraph",
+            "Revenue & margin grew in Q3.",
+        ),
+    ]);
+    let thumbnail = thumbnails::generate(
+        std::path::Path::new("/nonexistent/report.txt"),
+        "text/plain",
+        &parsed,
+    );
+
+    assert_eq!(thumbnail.mime, "image/svg+xml");
+    let svg = String::from_utf8(thumbnail.data).expect("utf-8 svg");
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("Quarterly &lt;Report&gt;"));
+    assert!(svg.contains("Revenue &amp; margin grew in Q3."));
+}
+
+#[tokio::test]
+async fn thumbnails_are_kept_on_the_root_node_and_collected_with_the_document() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(
+        &db,
+        "project-default",
+        "doc-thumb",
+        "Report.txt",
+        &[node("root-thumb", None, "Document", "")],
+    )
+    .await;
+    assert!(assets::get_document_thumbnail(&db, "doc-thumb")
+        .await
+        .expect("lookup")
+        .is_none());
+
+    let card = thumbnails::text_card("Report", ["Opening paragraph."]);
+    let stored = assets::put_document_thumbnail(&db, "doc-thumb", &card)
+        .await
+        .expect("store thumbnail");
+    let fetched = assets::get_document_thumbnail(&db, "doc-thumb")
+        .await
+        .expect("lookup")
+        .expect("thumbnail");
+    assert_eq!(fetched.asset_id, stored.asset_id);
+    assert!(fetched.data_url.starts_with("data:image/svg+xml;base64,"));
+
+    documents::delete_document(db.pool(), "doc-thumb")
+        .await
+        .expect("delete document");
+    let report = assets::collect_garbage(&db, Duration::ZERO)
+        .await
+        .expect("collect");
+    assert_eq!(report.removed, 1);
+    assert!(db.assets().read(&stored.asset_id).is_err());
+}
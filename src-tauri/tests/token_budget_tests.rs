@@ -0,0 +1,34 @@
+use vectorless_lib::providers::tokens::{
+    context_window_for, estimate_tokens, fit_ranked_items, DEFAULT_CONTEXT_WINDOW,
+};
+
+#[test]
+fn estimate_counts_words_and_punctuation() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("latency is low"), 4);
+    assert_eq!(estimate_tokens("p99: 50ms."), 4);
+    assert!(estimate_tokens(&"word ".repeat(1_000)) >= 1_000);
+}
+
+#[test]
+fn context_window_follows_model_family() {
+    assert_eq!(context_window_for("gemini-2.0-flash"), 1_048_576);
+    assert_eq!(context_window_for("gemini-1.5-pro-latest"), 2_097_152);
+    assert_eq!(context_window_for("unknown-model"), DEFAULT_CONTEXT_WINDOW);
+}
+
+#[test]
+fn fit_ranked_items_drops_lowest_ranked_first() {
+    let items = ["alpha ".repeat(40), "beta ".repeat(40), "gamma ".repeat(40)];
+    let render = |count: usize| items[..count].join("\n");
+
+    assert_eq!(fit_ranked_items(10_000, items.len(), render), 3);
+    let two = estimate_tokens(&render(2));
+    assert_eq!(fit_ranked_items(two, items.len(), render), 2);
+}
+
+#[test]
+fn fit_ranked_items_keeps_at_least_one() {
+    let render = |count: usize| "evidence ".repeat(100 * count);
+    assert_eq!(fit_ranked_items(1, 4, render), 1);
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::GetProjectTreeDeltaResponse,
+    db::{
+        repositories::{documents, projects},
+        Database,
+    },
+    sidecar::types::SidecarNode,
+};
+
+/// A document root with one section under it.
+fn tree(document_id: &str) -> Vec<SidecarNode> {
+    let root = format!("{document_id}-root");
+    vec![
+        node(&root, None, "Document", ""),
+        node(&format!("{document_id}-sec"), Some(&root), "Section", ""),
+    ]
+}
+
+fn node_ids(delta: &GetProjectTreeDeltaResponse) -> Vec<&str> {
+    delta.nodes.iter().map(|node| node.id.as_str()).collect()
+}
+
+#[tokio::test]
+async fn delta_returns_only_nodes_changed_since_the_revision() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(&db, "project-default", "doc-a", "doc-a.pdf", &tree("doc-a")).await;
+
+    let full = documents::get_project_tree_delta(db.pool(), "project-default", 0)
+        .await
+        .expect("full tree");
+    assert!(full.full);
+    assert_eq!(node_ids(&full), vec!["doc-a-root", "doc-a-sec"]);
+
+    let unchanged = documents::get_project_tree_delta(db.pool(), "project-default", full.revision)
+        .await
+        .expect("empty delta");
+    assert!(!unchanged.full);
+    assert!(unchanged.nodes.is_empty());
+    assert_eq!(unchanged.revision, full.revision);
+
+    documents::update_node_metadata(db.pool(), "doc-a-sec", &serde_json::json!({"k": 1}))
+        .await
+        .expect("update metadata");
+    seed_document(&db, "project-default", "doc-b", "doc-b.pdf", &tree("doc-b")).await;
+    let delta = documents::get_project_tree_delta(db.pool(), "project-default", full.revision)
+        .await
+        .expect("delta");
+    assert!(delta.revision > full.revision);
+    assert_eq!(
+        node_ids(&delta),
+        vec!["doc-a-sec", "doc-b-root", "doc-b-sec"]
+    );
+    assert!(delta.removed_node_ids.is_empty());
+
+    // A revision this database never issued gets the whole tree again.
+    let stale =
+        documents::get_project_tree_delta(db.pool(), "project-default", delta.revision + 100)
+            .await
+            .expect("full tree");
+    assert!(stale.full);
+    assert_eq!(stale.nodes.len(), 4);
+}
+
+#[tokio::test]
+async fn removed_nodes_and_documents_are_reported() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(&db, "project-default", "doc-a", "doc-a.pdf", &tree("doc-a")).await;
+    seed_document(&db, "project-default", "doc-b", "doc-b.pdf", &tree("doc-b")).await;
+    let start = documents::get_project_tree_delta(db.pool(), "project-default", 0)
+        .await
+        .expect("full tree")
+        .revision;
+
+    sqlx::query("DELETE FROM doc_nodes WHERE id = 'doc-a-sec'")
+        .execute(db.pool())
+        .await
+        .expect("delete node");
+    documents::delete_document(db.pool(), "doc-b")
+        .await
+        .expect("delete document");
+
+    let delta = documents::get_project_tree_delta(db.pool(), "project-default", start)
+        .await
+        .expect("delta");
+    assert!(delta.nodes.is_empty());
+    assert_eq!(delta.removed_node_ids, vec!["doc-a-sec"]);
+    assert_eq!(delta.removed_document_ids, vec!["doc-b"]);
+}
+
+#[tokio::test]
+async fn linking_locking_and_ownership_follow_the_project() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    projects::create_project(db.pool(), "project-other", "Other")
+        .await
+        .expect("create project");
+    seed_document(
+        &db,
+        "project-default",
+        "doc-shared",
+        "doc-shared.pdf",
+        &tree("doc-shared"),
+    )
+    .await;
+    let start = documents::get_project_tree_delta(db.pool(), "project-other", 0)
+        .await
+        .expect("empty tree")
+        .revision;
+
+    // Linking brings in nodes that have not changed themselves.
+    documents::link_document(db.pool(), "project-other", "doc-shared")
+        .await
+        .expect("link");
+    let linked = documents::get_project_tree_delta(db.pool(), "project-other", start)
+        .await
+        .expect("delta");
+    assert_eq!(node_ids(&linked), vec!["doc-shared-root", "doc-shared-sec"]);
+
+    documents::set_visibility(db.pool(), "doc-shared", false, true)
+        .await
+        .expect("lock");
+    let locked = documents::get_project_tree_delta(db.pool(), "project-other", linked.revision)
+        .await
+        .expect("delta");
+    assert!(locked.nodes.is_empty());
+    assert_eq!(locked.locked_document_ids, vec!["doc-shared"]);
+
+    // The owner lets go: the linked project inherits the document and keeps it.
+    documents::set_visibility(db.pool(), "doc-shared", false, false)
+        .await
+        .expect("unlock");
+    let before_removal = documents::get_project_tree_delta(db.pool(), "project-other", 0)
+        .await
+        .expect("full tree")
+        .revision;
+    documents::remove_document_from_project(db.pool(), "project-default", "doc-shared")
+        .await
+        .expect("remove");
+    let heir = documents::get_project_tree_delta(db.pool(), "project-other", before_removal)
+        .await
+        .expect("delta");
+    assert!(heir.removed_document_ids.is_empty());
+    let former_owner =
+        documents::get_project_tree_delta(db.pool(), "project-default", before_removal)
+            .await
+            .expect("delta");
+    assert_eq!(former_owner.removed_document_ids, vec!["doc-shared"]);
+}
+
+#[tokio::test]
+async fn an_ingest_moves_the_tree_one_revision() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    let start = documents::get_project_tree_delta(db.pool(), "project-default", 0)
+        .await
+        .expect("empty tree")
+        .revision;
+    seed_document(&db, "project-default", "doc-a", "doc-a.pdf", &tree("doc-a")).await;
+    let delta = documents::get_project_tree_delta(db.pool(), "project-default", start)
+        .await
+        .expect("delta");
+    assert_eq!(delta.revision, start + 1);
+    assert_eq!(node_ids(&delta), vec!["doc-a-root", "doc-a-sec"]);
+
+    documents::replace_document_nodes(
+        db.pool(),
+        "doc-a",
+        "checksum-doc-a-2",
+        "application/pdf",
+        1,
+        &[node("doc-a-root", None, "Document", "")],
+        &[],
+    )
+    .await
+    .expect("reingest");
+    let reingested =
+        documents::get_project_tree_delta(db.pool(), "project-default", delta.revision)
+            .await
+            .expect("delta");
+    assert_eq!(reingested.revision, delta.revision + 1);
+    assert_eq!(node_ids(&reingested), vec!["doc-a-root"]);
+    assert_eq!(reingested.removed_node_ids, vec!["doc-a-sec"]);
+}
+
+#[tokio::test]
+async fn clients_older_than_the_kept_removals_reload_the_tree() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(&db, "project-default", "doc-a", "doc-a.pdf", &tree("doc-a")).await;
+    seed_document(&db, "project-default", "doc-b", "doc-b.pdf", &tree("doc-b")).await;
+    let start = documents::get_project_tree_delta(db.pool(), "project-default", 0)
+        .await
+        .expect("full tree")
+        .revision;
+    sqlx::query("DELETE FROM doc_nodes WHERE id = 'doc-a-sec'")
+        .execute(db.pool())
+        .await
+        .expect("delete node");
+    let recent = documents::get_project_tree_delta(db.pool(), "project-default", start)
+        .await
+        .expect("delta")
+        .revision;
+    documents::delete_document(db.pool(), "doc-b")
+        .await
+        .expect("delete document");
+
+    documents::prune_tree_history(db.pool(), 1)
+        .await
+        .expect("prune");
+    let tombstones: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM doc_node_tombstones")
+        .fetch_one(db.pool())
+        .await
+        .expect("count");
+    assert_eq!(tombstones, 0);
+
+    let stale = documents::get_project_tree_delta(db.pool(), "project-default", start)
+        .await
+        .expect("full tree");
+    assert!(stale.full);
+    assert_eq!(node_ids(&stale), vec!["doc-a-root"]);
+    let caught_up = documents::get_project_tree_delta(db.pool(), "project-default", recent)
+        .await
+        .expect("delta");
+    assert!(!caught_up.full);
+    assert_eq!(caught_up.removed_document_ids, vec!["doc-b"]);
+}
@@ -0,0 +1,188 @@
+use vectorless_lib::{
+    db::{repositories::documents, Database},
+    reasoner::{
+        feedback::EvidenceFeedback,
+        navigation::{drill_down, evidence_context},
+    },
+    sidecar::types::SidecarNode,
+};
+
+fn node(id: &str, parent: Option<&str>, node_type: &str, title: &str, text: &str) -> SidecarNode {
+    SidecarNode {
+        id: id.to_string(),
+        parent_id: parent.map(ToString::to_string),
+        node_type: node_type.to_string(),
+        title: title.to_string(),
+        text: text.to_string(),
+        page_start: Some(1),
+        page_end: Some(1),
+        ordinal_path: id.to_string(),
+        bbox: serde_json::json!({}),
+        metadata: serde_json::json!({}),
+    }
+}
+
+async fn seed(db: &Database) {
+    documents::insert_document(
+        db.pool(),
+        "doc-tree",
+        "project-default",
+        "Report.md",
+        "text/markdown",
+        "checksum-tree",
+        1,
+    )
+    .await
+    .expect("insert document");
+    documents::insert_nodes(
+        db.pool(),
+        "doc-tree",
+        &[
+            node("1", None, "Section", "Performance", ""),
+            node(
+                "1.1",
+                Some("1"),
+                "Paragraph",
+                "Intro",
+                "How the report was put together.",
+            ),
+            node("1.2", Some("1"), "Subsection", "Latency", ""),
+            node(
+                "1.2.1",
+                Some("1.2"),
+                "Paragraph",
+                "¶ 1",
+                "The p99 latency is 50ms.",
+            ),
+            node(
+                "1.2.1.1",
+                Some("1.2.1"),
+                "Claim",
+                "Claim",
+                "p99 latency 50ms",
+            ),
+            node("2", None, "Section", "Costs", ""),
+            node(
+                "2.1",
+                Some("2"),
+                "Paragraph",
+                "¶ 1",
+                "The p99 latency budget is not costed.",
+            ),
+            node(
+                "2.2",
+                Some("2"),
+                "Paragraph",
+                "¶ 2",
+                "Except during failover,\nwhen it is.",
+            ),
+        ],
+    )
+    .await
+    .expect("insert nodes");
+}
+
+#[tokio::test]
+async fn drill_down_follows_selected_sections_to_grandchildren() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let roots = documents::get_tree(db.pool(), "doc-tree", None, 1)
+        .await
+        .expect("roots");
+    let performance = roots
+        .into_iter()
+        .filter(|node| node.id == "1")
+        .collect::<Vec<_>>();
+
+    let drilled = drill_down(
+        &db,
+        &performance,
+        "What is the p99 latency?",
+        12,
+        &EvidenceFeedback::default(),
+    )
+    .await
+    .expect("drill down");
+    let ids = drilled
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect::<Vec<_>>();
+
+    // Best match first, then the child that mentions the query, then the rest;
+    // nothing from the unselected section and nothing past two levels.
+    assert_eq!(ids, vec!["1.2.1", "1.2", "1.1"]);
+
+    let limited = drill_down(
+        &db,
+        &performance,
+        "What is the p99 latency?",
+        1,
+        &EvidenceFeedback::default(),
+    )
+    .await
+    .expect("drill down");
+    assert_eq!(limited.len(), 1);
+}
+
+#[tokio::test]
+async fn selected_leaves_stand_in_for_their_subtree() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let leaves = documents::get_tree(db.pool(), "doc-tree", Some("2"), 1)
+        .await
+        .expect("children");
+
+    let drilled = drill_down(&db, &leaves, "latency", 12, &EvidenceFeedback::default())
+        .await
+        .expect("drill down");
+    let ids = drilled
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["2.1", "2.2"]);
+
+    let none = drill_down(&db, &[], "latency", 12, &EvidenceFeedback::default())
+        .await
+        .expect("drill down");
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn evidence_context_reads_heading_and_neighbours_within_budget() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    let costs = documents::get_tree(db.pool(), "doc-tree", Some("2"), 1)
+        .await
+        .expect("children");
+
+    let context = evidence_context(&db, &costs[0], 600)
+        .await
+        .expect("context");
+    assert_eq!(
+        context.as_deref(),
+        Some("section=Costs after=Except during failover, when it is.")
+    );
+
+    let clipped = evidence_context(&db, &costs[1], 15).await.expect("context");
+    assert_eq!(clipped.as_deref(), Some("section=Costs before=The p"));
+
+    let roots = documents::get_tree(db.pool(), "doc-tree", None, 1)
+        .await
+        .expect("roots");
+    let lonely = documents::get_tree(db.pool(), "doc-tree", Some("1.2"), 1)
+        .await
+        .expect("children");
+    assert_eq!(
+        evidence_context(&db, &lonely[0], 600)
+            .await
+            .expect("context")
+            .as_deref(),
+        Some("section=Latency")
+    );
+    assert_eq!(
+        evidence_context(&db, &roots[0], 600)
+            .await
+            .expect("context"),
+        None
+    );
+}
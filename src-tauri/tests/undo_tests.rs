@@ -0,0 +1,142 @@
+mod common;
+
+use common::{node, seed_document};
+use std::time::Duration;
+
+use vectorless_lib::{
+    core::types::{GraphNodePosition, UndoKind},
+    db::{
+        repositories::{documents, reasoning},
+        undo::{self, UndoSnapshot},
+        Database,
+    },
+};
+
+fn position(node_id: &str, x: f64) -> GraphNodePosition {
+    GraphNodePosition {
+        node_id: node_id.to_string(),
+        x,
+        y: 0.0,
+    }
+}
+
+async fn seeded() -> Database {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed_document(
+        &db,
+        "project-default",
+        "doc-memo",
+        "Memo.md",
+        &[
+            node("root-memo", None, "Document", ""),
+            node(
+                "para-budget",
+                Some("root-memo"),
+                "Paragraph",
+                "The budget is final.",
+            ),
+        ],
+    )
+    .await;
+    documents::save_graph_layout(
+        db.pool(),
+        "doc-memo",
+        &[position("root-memo", 1.0), position("para-budget", 2.0)],
+    )
+    .await
+    .expect("save layout");
+    db
+}
+
+#[tokio::test]
+async fn a_deleted_document_comes_back_with_its_ids() {
+    let db = seeded().await;
+    reasoning::create_run(
+        db.pool(),
+        "run-budget",
+        "project-default",
+        Some("doc-memo"),
+        "Is the budget final?",
+    )
+    .await
+    .expect("create run");
+
+    let snapshot = undo::snapshot_document(&db, "doc-memo")
+        .await
+        .expect("snapshot");
+    assert!(documents::delete_document(db.pool(), "doc-memo")
+        .await
+        .expect("delete"));
+    undo::record(&db, "Delete Memo.md", &snapshot)
+        .await
+        .expect("record");
+
+    let operation = undo::undo_last(&db, undo::UNDO_WINDOW)
+        .await
+        .expect("undo")
+        .expect("an operation to undo");
+    assert_eq!(operation.kind, UndoKind::DocumentDelete);
+    assert_eq!(operation.label, "Delete Memo.md");
+
+    let document = documents::get_document(db.pool(), "doc-memo")
+        .await
+        .expect("restored document");
+    assert_eq!(document.project_id, "project-default");
+    let tree = documents::get_tree(db.pool(), "doc-memo", None, 8)
+        .await
+        .expect("tree");
+    assert_eq!(tree.len(), 2);
+    assert_eq!(
+        documents::get_graph_layout(db.pool(), "doc-memo")
+            .await
+            .expect("layout")
+            .len(),
+        2
+    );
+    let run_document: Option<String> =
+        sqlx::query_scalar("SELECT document_id FROM reasoning_runs WHERE id = 'run-budget'")
+            .fetch_one(db.pool())
+            .await
+            .expect("run");
+    assert_eq!(run_document.as_deref(), Some("doc-memo"));
+    assert!(undo::undo_last(&db, undo::UNDO_WINDOW)
+        .await
+        .expect("undo")
+        .is_none());
+}
+
+#[tokio::test]
+async fn only_recent_operations_can_be_undone() {
+    let db = seeded().await;
+    let wipe = UndoSnapshot::LayoutWipe {
+        document_id: "doc-memo".to_string(),
+        positions: vec![position("root-memo", 1.0)],
+    };
+    for _ in 0..undo::UNDO_LIMIT + 2 {
+        undo::record(&db, "Clear graph layout", &wipe)
+            .await
+            .expect("record");
+    }
+    let mut undone = 0;
+    while undo::undo_last(&db, undo::UNDO_WINDOW)
+        .await
+        .expect("undo")
+        .is_some()
+    {
+        undone += 1;
+    }
+    assert_eq!(undone, undo::UNDO_LIMIT);
+
+    undo::record(&db, "Clear graph layout", &wipe)
+        .await
+        .expect("record");
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    assert!(undo::undo_last(&db, Duration::ZERO)
+        .await
+        .expect("undo")
+        .is_none());
+    assert!(undo::undo_last(&db, undo::UNDO_WINDOW)
+        .await
+        .expect("undo")
+        .is_none());
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{node, seed_document};
+use vectorless_lib::{
+    core::types::NodeType,
+    db::{
+        repositories::reasoning::{self, NewStep},
+        Database,
+    },
+};
+
+async fn seed(db: &Database) {
+    for (document_id, nodes) in [
+        (
+            "doc-a",
+            vec![
+                node("root-a", None, "Document", "Text of root-a."),
+                node("sec-a1", Some("root-a"), "Section", "Text of sec-a1."),
+                node("p-a1", Some("sec-a1"), "Paragraph", "Text of p-a1."),
+                node("sec-a2", Some("root-a"), "Section", "Text of sec-a2."),
+                node("sub-a2", Some("sec-a2"), "Subsection", "Text of sub-a2."),
+                node("p-a2", Some("sub-a2"), "Paragraph", "Text of p-a2."),
+            ],
+        ),
+        (
+            "doc-b",
+            vec![
+                node("root-b", None, "Document", "Text of root-b."),
+                node("sec-b1", Some("root-b"), "Section", "Text of sec-b1."),
+                node("p-b1", Some("sec-b1"), "Paragraph", "Text of p-b1."),
+            ],
+        ),
+    ] {
+        seed_document(
+            db,
+            "project-default",
+            document_id,
+            &format!("{document_id}.pdf"),
+            &nodes,
+        )
+        .await;
+    }
+}
+
+async fn answered_run(db: &Database, run_id: &str, retrieved: &[&str], cited: &[&str]) {
+    reasoning::create_run(db.pool(), run_id, "project-default", None, "q")
+        .await
+        .expect("create run");
+    reasoning::add_step(
+        db.pool(),
+        NewStep {
+            run_id,
+            idx: 1,
+            step_type: "extract_evidence",
+            thought: "",
+            action: "",
+            observation: "",
+            summary: "",
+            node_refs: retrieved.iter().map(ToString::to_string).collect(),
+            resolved_refs: &[],
+            confidence: 0.5,
+            latency_ms: 1,
+        },
+    )
+    .await
+    .expect("add step");
+    reasoning::complete_run(
+        db.pool(),
+        run_id,
+        10,
+        serde_json::json!({}),
+        0.0,
+        "answer",
+        cited.iter().map(ToString::to_string).collect(),
+        0.8,
+        true,
+        serde_json::json!({}),
+        serde_json::json!([]),
+    )
+    .await
+    .expect("complete run");
+}
+
+#[tokio::test]
+async fn sections_and_documents_no_run_used_are_reported() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    answered_run(&db, "run-1", &["p-b1"], &["p-a1"]).await;
+
+    let report = reasoning::unused_content(db.pool(), "project-default")
+        .await
+        .expect("report");
+    assert_eq!(report.run_count, 1);
+    // The unused subsection is covered by its unused section.
+    assert_eq!(report.sections.len(), 1);
+    let section = &report.sections[0];
+    assert_eq!(section.node_id, "sec-a2");
+    assert_eq!(section.node_type, NodeType::Section);
+    assert_eq!(section.document_name, "doc-a.pdf");
+    assert_eq!(section.node_count, 3);
+    assert!(section.estimated_tokens > 0);
+
+    // doc-b was looked at but never cited.
+    assert_eq!(report.documents.len(), 1);
+    let document = &report.documents[0];
+    assert_eq!(document.document_id, "doc-b");
+    assert_eq!(document.node_count, 3);
+    assert_eq!(document.retrieved_nodes, 1);
+}
+
+#[tokio::test]
+async fn citations_of_re_ingested_nodes_count_under_their_new_ids() {
+    let db = Database::in_memory().await.expect("db should initialize");
+    seed(&db).await;
+    answered_run(&db, "run-1", &["p-b1"], &["p-a1", "p-b1"]).await;
+    sqlx::query(
+        "INSERT INTO node_id_remaps (old_node_id, new_node_id, document_id) VALUES ('p-gone', 'p-a2', 'doc-a')",
+    )
+    .execute(db.pool())
+    .await
+    .expect("remap");
+    answered_run(&db, "run-2", &[], &["p-gone"]).await;
+
+    let report = reasoning::unused_content(db.pool(), "project-default")
+        .await
+        .expect("report");
+    assert_eq!(report.run_count, 2);
+    assert!(report.sections.is_empty());
+    assert!(report.documents.is_empty());
+}
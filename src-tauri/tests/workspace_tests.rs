@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use vectorless_lib::{
+    core::{errors::AppError, scheduler::Scheduler},
+    db::{
+        workspaces::{self, SharedDatabase, WorkspaceRegistry},
+        Database, MigrationPolicy, DB_FILE_NAME,
+    },
+};
+
+#[tokio::test]
+async fn registry_lists_default_first_and_remembers_the_active_workspace() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let data_dir = dir.path().join("data");
+    let registry = WorkspaceRegistry::new(&data_dir);
+    let default_path = data_dir.join(DB_FILE_NAME);
+
+    assert_eq!(registry.active_path().expect("active"), default_path);
+    let listed = registry.list(Some(&default_path)).expect("list");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, "Default");
+    assert!(listed[0].active);
+    assert!(!listed[0].exists);
+
+    let client = dir.path().join("client-a.sqlite");
+    let _db = workspaces::create_new(&client).await.expect("create");
+    registry
+        .remember(&client, Some("Client A"))
+        .expect("remember");
+    registry
+        .remember(&client, None)
+        .expect("reopen keeps the name");
+
+    assert_eq!(registry.active_path().expect("active"), client);
+    let listed = registry.list(Some(&client)).expect("list");
+    let names = listed.iter().map(|w| w.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["Default", "Client A"]);
+    assert!(!listed[0].active);
+    assert!(listed[1].active && listed[1].exists);
+    assert!(listed[1].last_opened_at.is_some());
+
+    // A missing workspace falls back to the default at the next startup.
+    std::fs::remove_file(&client).expect("remove");
+    assert_eq!(registry.active_path().expect("active"), default_path);
+}
+
+#[tokio::test]
+async fn create_refuses_existing_files_and_open_requires_one() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = workspaces::resolve_workspace_path(&dir.path().join("team").to_string_lossy())
+        .expect("resolve");
+    assert_eq!(path, dir.path().join("team").join(DB_FILE_NAME));
+
+    assert!(matches!(
+        workspaces::open_existing(&path).await,
+        Err(AppError::NotFound(_))
+    ));
+    workspaces::create_new(&path).await.expect("create");
+    assert!(matches!(
+        workspaces::create_new(&path).await,
+        Err(AppError::InvalidInput(_))
+    ));
+    let db = workspaces::open_existing(&path).await.expect("open");
+    assert_eq!(db.path(), Some(path.as_path()));
+}
+
+#[tokio::test]
+async fn replacing_the_database_leaves_existing_handles_working() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let first = workspaces::create_new(&dir.path().join("first.sqlite"))
+        .await
+        .expect("first");
+    let shared = SharedDatabase::new(first);
+    let in_flight = shared.get();
+
+    let second_path = dir.path().join("second.sqlite");
+    let second = Database::open_file(&second_path, MigrationPolicy::Apply)
+        .await
+        .expect("second");
+    let old = shared.replace(second);
+    drop(old);
+
+    assert_eq!(shared.get().path(), Some(second_path.as_path()));
+    sqlx::query("SELECT 1")
+        .execute(in_flight.pool())
+        .await
+        .expect("old handle still usable");
+}
+
+#[tokio::test]
+async fn drain_waits_for_running_queries_and_blocks_background_writes() {
+    let scheduler = Scheduler::new(2);
+    let query = scheduler.interactive();
+
+    let draining = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move { scheduler.drain().await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!draining.is_finished(), "drain finished during a query");
+
+    drop(query);
+    let drain = tokio::time::timeout(Duration::from_secs(2), draining)
+        .await
+        .expect("drain should finish once the query ends")
+        .expect("task should not panic");
+
+    let writer = tokio::spawn({
+        let scheduler = scheduler.clone();
+        async move {
+            let _permit = scheduler.background().await;
+        }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!writer.is_finished(), "write ran during a swap");
+    drop(drain);
+    tokio::time::timeout(Duration::from_secs(2), writer)
+        .await
+        .expect("write should start after the swap")
+        .expect("task should not panic");
+}
@@ -16,6 +16,7 @@ import {
   onReasoningComplete,
   onReasoningError,
   onReasoningStep,
+  reingestDocument,
   runReasoningQuery,
 } from "../lib/tauriApi";
 import { useVectorlessStore } from "../lib/state";
@@ -95,7 +96,7 @@ export function App() {
     const confirmed = window.confirm("Delete this document from the project?");
     if (!confirmed) return;
     try {
-      await deleteDocument(documentId);
+      await deleteDocument(documentId, activeProjectId);
       const docs = await listDocuments(activeProjectId);
       setDocuments(docs);
       if (activeDocumentId === documentId) {
@@ -108,6 +109,18 @@ export function App() {
     }
   };
 
+  const handleReingestDocument = async (documentId: string) => {
+    if (!activeProjectId) return;
+    try {
+      await reingestDocument(documentId);
+      setDocuments(await listDocuments(activeProjectId));
+      const nodes = await getProjectTree(activeProjectId, 6);
+      setTree(nodes);
+    } catch (error) {
+      setErrorMessage(String(error));
+    }
+  };
+
   const activeDocument = useMemo(
     () => documents.find((item) => item.id === activeDocumentId) ?? null,
     [documents, activeDocumentId],
@@ -162,7 +175,9 @@ export function App() {
           thought: event.thought,
           action: event.action,
           observation: event.observation,
+          summary: event.summary,
           nodeRefs: event.nodeRefs,
+          resolvedRefs: event.resolvedRefs,
           confidence: event.confidence,
           latencyMs: event.latencyMs,
         };
@@ -279,6 +294,7 @@ export function App() {
             node={nodeDetail}
             confidence={activeTraceConfidence}
             onSelectNode={selectNode}
+            onReingest={(documentId) => void handleReingestDocument(documentId)}
             tree={tree}
           />
         </section>
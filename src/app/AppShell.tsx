@@ -28,9 +28,12 @@ function inferMimeType(filePath: string): string {
     case "docx":  return "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
     case "xlsx":  return "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
     case "xls":   return "application/vnd.ms-excel";
+    case "epub":  return "application/epub+zip";
     case "csv":   return "text/csv";
     case "txt":   return "text/plain";
     case "md":    return "text/markdown";
+    case "html":
+    case "htm":   return "text/html";
     case "png":   return "image/png";
     case "jpg":
     case "jpeg":  return "image/jpeg";
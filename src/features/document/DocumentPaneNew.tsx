@@ -16,6 +16,7 @@ interface DocumentPaneProps {
   node: DocNodeDetail | null;
   confidence: number;
   onSelectNode: (nodeId: string) => void;
+  onReingest?: (documentId: string) => void;
   tree: DocNodeSummary[];
 }
 
@@ -108,6 +109,7 @@ export function DocumentPaneNew({
   node,
   confidence,
   onSelectNode,
+  onReingest,
   tree,
 }: DocumentPaneProps) {
   const contentRef = useRef<HTMLDivElement>(null);
@@ -160,6 +162,17 @@ export function DocumentPaneNew({
         </div>
       </header>
 
+      {document?.stale && (
+        <div className="stale-document-banner" role="status">
+          <span>The file changed on disk since it was ingested.</span>
+          {onReingest && (
+            <button type="button" className="doc-action-btn secondary" onClick={() => onReingest(document.id)}>
+              Reingest
+            </button>
+          )}
+        </div>
+      )}
+
       <div className="document-content" ref={contentRef}>
         <article className="preview-reader">
           <div className="reader-rule" />
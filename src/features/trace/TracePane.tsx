@@ -247,7 +247,12 @@ export function TracePane({
           <div className="timeline-stream">
             {steps.map((step) => {
             const firstNodeRef = step.nodeRefs[0];
-            const nodeTitle = firstNodeRef ? (nodeById.get(firstNodeRef)?.title || firstNodeRef) : null;
+            const resolved = step.resolvedRefs?.find((ref) => ref.id === firstNodeRef);
+            const nodeTitle = firstNodeRef
+              ? resolved
+                ? `${resolved.title || resolved.ordinalPath} · ${resolved.documentName}`
+                : nodeById.get(firstNodeRef)?.title || firstNodeRef
+              : null;
             return (
               <article
                 key={`${step.runId}-${step.idx}`}
@@ -261,11 +266,14 @@ export function TracePane({
                     <span className="timeline-step-latency">{formatLatency(step.latencyMs)}</span>
                   </div>
                   <div className="timeline-step-card">
-                    {step.observation ? (
-                      <p className="trace-observation">{step.observation}</p>
+                    {step.summary || step.observation ? (
+                      <p className="trace-observation">{step.summary || step.observation}</p>
                     ) : null}
                     {showDebugDetails ? (
                       <>
+                        {step.summary && step.observation ? (
+                          <p className="trace-thought">{step.observation}</p>
+                        ) : null}
                         <p className="trace-thought">{step.thought}</p>
                         {step.action ? (
                           <pre className="trace-snippet"><code>{step.action}</code></pre>
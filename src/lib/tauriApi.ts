@@ -1,23 +1,215 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import { open } from "@tauri-apps/plugin-dialog";
+import { open, save } from "@tauri-apps/plugin-dialog";
 
 import type {
+  AdminPolicy,
+  AnswerRecord,
+  AnswerRevision,
+  BackupInfo,
+  BackupSettings,
+  BenchmarkProgressEvent,
+  BenchmarkReport,
+  BibliographyFormat,
+  BundleManifest,
+  CitationHeatmap,
+  CompressNodeTextResponse,
+  CorpusChangedEvent,
+  DateRange,
+  DecomposeClaimsResponse,
   DocNodeDetail,
   DocNodeSummary,
+  DocumentLockStatus,
   DocumentPreviewBlock,
   DocumentSummary,
+  EnrichTablesResponse,
+  ExportBibliographyResponse,
+  ExportedFile,
+  ExportSharedBundleResponse,
+  ExternalParserConfig,
+  ExtractChartDataResponse,
+  GenerationSettings,
+  GenerationSettingsResponse,
+  GetAssetResponse,
+  GetMetricsResponse,
+  GetProjectTreeDeltaResponse,
   GraphNodePosition,
+  HealthCheckResponse,
+  HostedModel,
+  ImportCorpusResponse,
+  ImportPlan,
+  ImportSource,
+  IndexAcronymsResponse,
+  IngestCompleteEvent,
+  IngestFailedEvent,
   IngestProgressEvent,
+  IngestRules,
+  IngestRulesResponse,
+  KeySelection,
+  LanguageResources,
+  LanguageResourcesResponse,
+  ListAcronymsResponse,
+  ListNodeTypesResponse,
+  ListProviderKeysResponse,
+  MetricsSettings,
+  ModelPricing,
+  ModelSelection,
+  NodeTypeInfo,
+  OpenSharedBundleResponse,
+  ParserOverride,
+  PortableExportResponse,
+  PreviewIngestResponse,
+  PricingOverridesResponse,
+  PrivacySettings,
+  ProjectDeleteImpact,
   ProjectSummary,
+  ProviderKeyRecord,
+  ProviderName,
+  ProviderTimeoutSettings,
+  QualityThresholds,
+  QualityTrendsResponse,
+  QueryCostEstimate,
+  QueryScope,
   ReasoningCompleteEvent,
   ReasoningErrorEvent,
+  ReasoningPhaseEvent,
   ReasoningStepEvent,
+  RecentDocument,
+  RecentQuery,
+  ReingestDocumentResponse,
+  ResearchLogAppendedEvent,
+  ResearchLogSettings,
+  ResearchLogSettingsResponse,
+  RetrievalSettings,
+  RevealExportResponse,
+  RunMigrationsResponse,
+  RunComparison,
+  RunTimeline,
+  RunOverrides,
   RunPayload,
+  RunStarted,
+  ScanDirectoryResponse,
+  SearchAllProjectsResponse,
+  SchemaInfo,
+  StorageStatus,
+  UndoOperation,
+  UnusedContentReport,
+  WorkspaceSummary,
 } from "./types";
 
-export async function setProviderKey(apiKey: string): Promise<{ stored: boolean }> {
-  return invoke("set_provider_key", { provider: "gemini", apiKey });
+export async function setProviderKey(
+  apiKey: string,
+  provider: ProviderName = "gemini",
+): Promise<{ stored: boolean }> {
+  return invoke("set_provider_key", { provider, apiKey });
+}
+
+export async function listOpenRouterModels(): Promise<{ provider: ProviderName; models: HostedModel[] }> {
+  return invoke("list_openrouter_models");
+}
+
+export async function getModelSelection(): Promise<ModelSelection> {
+  return invoke("get_model_selection");
+}
+
+export async function setModelSelection(
+  provider: ProviderName,
+  model: string,
+  plannerModel?: string,
+): Promise<ModelSelection> {
+  return invoke("set_model_selection", { provider, model, plannerModel: plannerModel ?? null });
+}
+
+export async function getPricingOverrides(): Promise<PricingOverridesResponse> {
+  return invoke("get_pricing_overrides");
+}
+
+export async function setPricingOverrides(
+  overrides: Record<string, ModelPricing>,
+): Promise<PricingOverridesResponse> {
+  return invoke("set_pricing_overrides", { overrides });
+}
+
+export async function getExternalParser(): Promise<ExternalParserConfig> {
+  return invoke("get_external_parser");
+}
+
+export async function setExternalParser(
+  config: ExternalParserConfig,
+): Promise<ExternalParserConfig> {
+  return invoke("set_external_parser", { config });
+}
+
+export async function getLanguageResources(): Promise<LanguageResourcesResponse> {
+  return invoke("get_language_resources");
+}
+
+export async function setLanguageResources(
+  resources: LanguageResources,
+): Promise<LanguageResourcesResponse> {
+  return invoke("set_language_resources", { resources });
+}
+
+export async function resetLanguageResources(language: string): Promise<LanguageResourcesResponse> {
+  return invoke("reset_language_resources", { language });
+}
+
+export async function getQualityThresholds(): Promise<QualityThresholds> {
+  return invoke("get_quality_thresholds");
+}
+
+export async function setQualityThresholds(thresholds: QualityThresholds): Promise<QualityThresholds> {
+  return invoke("set_quality_thresholds", { thresholds });
+}
+
+export async function getPrivacySettings(): Promise<PrivacySettings> {
+  return invoke("get_privacy_settings");
+}
+
+export async function setPrivacySettings(privacy: PrivacySettings): Promise<PrivacySettings> {
+  return invoke("set_privacy_settings", { privacy });
+}
+
+export async function getRetrievalSettings(): Promise<RetrievalSettings> {
+  return invoke("get_retrieval_settings");
+}
+
+export async function setRetrievalSettings(retrieval: RetrievalSettings): Promise<RetrievalSettings> {
+  return invoke("set_retrieval_settings", { retrieval });
+}
+
+export async function getProviderTimeouts(): Promise<ProviderTimeoutSettings> {
+  return invoke("get_provider_timeouts");
+}
+
+export async function setProviderTimeouts(
+  timeouts: ProviderTimeoutSettings,
+): Promise<ProviderTimeoutSettings> {
+  return invoke("set_provider_timeouts", { timeouts });
+}
+
+export async function getAdminPolicy(): Promise<AdminPolicy> {
+  return invoke("get_admin_policy");
+}
+
+export async function listProviderKeys(): Promise<ListProviderKeysResponse> {
+  return invoke("list_provider_keys", { provider: "gemini" });
+}
+
+export async function addProviderKey(
+  label: string,
+  apiKey: string,
+  dailyRequestQuota?: number | null,
+): Promise<{ key: ProviderKeyRecord }> {
+  return invoke("add_provider_key", { provider: "gemini", label, apiKey, dailyRequestQuota });
+}
+
+export async function removeProviderKey(keyId: string): Promise<{ removed: boolean }> {
+  return invoke("remove_provider_key", { provider: "gemini", keyId });
+}
+
+export async function setKeySelection(selection: KeySelection): Promise<KeySelection> {
+  return invoke("set_key_selection", { selection });
 }
 
 export async function ingestDocument(input: {
@@ -25,10 +217,41 @@ export async function ingestDocument(input: {
   mimeType: string;
   displayName?: string;
   projectId: string;
-}): Promise<{ documentId: string; rootNodeId: string; nodeCount: number; sectionCount: number }> {
+  shareExisting?: boolean;
+  parserOverride?: ParserOverride;
+}): Promise<{
+  documentId: string;
+  rootNodeId: string;
+  nodeCount: number;
+  sectionCount: number;
+  sharedFromProjectId: string | null;
+}> {
   return invoke("ingest_document", input);
 }
 
+export async function previewIngest(input: {
+  filePath: string;
+  mimeType: string;
+  projectId?: string;
+  parserOverride?: ParserOverride;
+}): Promise<PreviewIngestResponse> {
+  return invoke("preview_ingest", input);
+}
+
+export async function reingestDocument(
+  documentId: string,
+  filePath?: string,
+  mimeType?: string,
+  parserOverride?: ParserOverride,
+): Promise<ReingestDocumentResponse> {
+  return invoke("reingest_document", {
+    documentId,
+    filePath: filePath ?? null,
+    mimeType: mimeType ?? null,
+    parserOverride: parserOverride ?? null,
+  });
+}
+
 export async function pickDocumentFiles(): Promise<string[]> {
   const selected = await open({
     multiple: true,
@@ -36,7 +259,7 @@ export async function pickDocumentFiles(): Promise<string[]> {
     filters: [
         {
           name: "Supported Documents",
-          extensions: ["pdf", "pptx", "docx", "txt", "md", "csv", "png", "jpg", "jpeg", "webp", "tiff"],
+          extensions: ["pdf", "pptx", "docx", "epub", "txt", "md", "html", "htm", "csv", "png", "jpg", "jpeg", "webp", "tiff"],
         },
       ],
   });
@@ -46,11 +269,38 @@ export async function pickDocumentFiles(): Promise<string[]> {
   return [];
 }
 
-export async function listDocuments(projectId: string): Promise<DocumentSummary[]> {
-  const result = await invoke<{ documents: DocumentSummary[] }>("list_documents", { projectId });
+export async function listDocuments(projectId: string, includeHidden = false): Promise<DocumentSummary[]> {
+  const result = await invoke<{ documents: DocumentSummary[] }>("list_documents", {
+    projectId,
+    includeHidden,
+  });
+  return result.documents;
+}
+
+export async function openDocument(documentId: string, projectId?: string): Promise<DocumentSummary> {
+  const result = await invoke<{ document: DocumentSummary }>("open_document", {
+    documentId,
+    projectId: projectId ?? null,
+  });
+  return result.document;
+}
+
+export async function listRecentDocuments(projectId?: string, limit?: number): Promise<RecentDocument[]> {
+  const result = await invoke<{ documents: RecentDocument[] }>("list_recent_documents", {
+    projectId: projectId ?? null,
+    limit: limit ?? null,
+  });
   return result.documents;
 }
 
+export async function listRecentQueries(projectId?: string, limit?: number): Promise<RecentQuery[]> {
+  const result = await invoke<{ queries: RecentQuery[] }>("list_recent_queries", {
+    projectId: projectId ?? null,
+    limit: limit ?? null,
+  });
+  return result.queries;
+}
+
 export async function getTree(
   documentId: string,
   parentNodeId?: string,
@@ -82,20 +332,282 @@ export async function runReasoningQuery(
   query: string,
   maxSteps = 6,
   focusDocumentId?: string | null,
-): Promise<{ runId: string; status: string }> {
-  return invoke("run_reasoning_query", { projectId, query, maxSteps, focusDocumentId });
+  generation?: GenerationSettings | null,
+  attachFigureImages = false,
+  expandContext = false,
+  inferFocusDocument = true,
+  forceScope?: QueryScope | null,
+  dateRange?: DateRange | null,
+): Promise<RunStarted> {
+  return invoke("run_reasoning_query", {
+    projectId,
+    query,
+    maxSteps,
+    focusDocumentId,
+    generation,
+    attachFigureImages,
+    expandContext,
+    inferFocusDocument,
+    forceScope: forceScope ?? null,
+    dateRange: dateRange ?? null,
+  });
+}
+
+export async function getGenerationSettings(projectId: string): Promise<GenerationSettingsResponse> {
+  return invoke("get_generation_settings", { projectId });
+}
+
+export async function setGenerationSettings(
+  projectId: string,
+  settings: GenerationSettings,
+): Promise<GenerationSettingsResponse> {
+  return invoke("set_generation_settings", { projectId, settings });
+}
+
+export async function getIngestRules(projectId: string): Promise<IngestRulesResponse> {
+  return invoke("get_ingest_rules", { projectId });
+}
+
+export async function setIngestRules(
+  projectId: string,
+  rules: IngestRules,
+): Promise<IngestRulesResponse> {
+  return invoke("set_ingest_rules", { projectId, rules });
+}
+
+export async function listAcronyms(projectId: string): Promise<ListAcronymsResponse> {
+  return invoke("list_acronyms", { projectId });
+}
+
+export async function listNodeTypes(): Promise<ListNodeTypesResponse> {
+  return invoke("list_node_types");
+}
+
+export async function saveNodeType(input: {
+  name: string;
+  label?: string | null;
+  description?: string | null;
+  color?: string | null;
+}): Promise<{ nodeType: NodeTypeInfo }> {
+  return invoke("save_node_type", input);
+}
+
+export async function deleteNodeType(name: string): Promise<{ removed: boolean }> {
+  return invoke("delete_node_type", { name });
+}
+
+export async function getResearchLogSettings(projectId: string): Promise<ResearchLogSettingsResponse> {
+  return invoke("get_research_log_settings", { projectId });
+}
+
+export async function setResearchLogSettings(
+  projectId: string,
+  settings: ResearchLogSettings,
+): Promise<ResearchLogSettingsResponse> {
+  return invoke("set_research_log_settings", { projectId, settings });
+}
+
+export async function scanDirectory(
+  projectId: string,
+  dirPath: string,
+): Promise<ScanDirectoryResponse> {
+  return invoke("scan_directory", { projectId, dirPath });
+}
+
+export async function previewImport(
+  projectId: string,
+  source: ImportSource,
+  dirPath: string,
+  foldersAsProjects = true,
+): Promise<ImportPlan> {
+  return invoke("preview_import", { projectId, source, dirPath, foldersAsProjects });
+}
+
+export async function importCorpus(
+  projectId: string,
+  source: ImportSource,
+  dirPath: string,
+  foldersAsProjects = true,
+): Promise<ImportCorpusResponse> {
+  return invoke("import_corpus", { projectId, source, dirPath, foldersAsProjects });
 }
 
 export async function getRun(runId: string): Promise<RunPayload> {
   return invoke("get_run", { runId });
 }
 
-export async function exportMarkdown(documentId: string): Promise<{ filePath: string }> {
-  return invoke("export_markdown", { documentId });
+export async function listAnswerRevisions(runId: string): Promise<AnswerRevision[]> {
+  return invoke("list_answer_revisions", { runId });
+}
+
+export async function restoreAnswerRevision(
+  runId: string,
+  revision: number,
+): Promise<AnswerRecord> {
+  return invoke("restore_answer_revision", { runId, revision });
+}
+
+export async function rerunWith(
+  runId: string,
+  overrides?: RunOverrides,
+): Promise<RunStarted> {
+  return invoke("rerun_with", { runId, overrides: overrides ?? null });
+}
+
+export async function addDocumentToRun(
+  runId: string,
+  documentId: string,
+): Promise<ReasoningCompleteEvent> {
+  return invoke("add_document_to_run", { runId, documentId });
+}
+
+export async function estimateQueryCost(
+  projectId: string,
+  query: string,
+  focusDocumentId?: string | null,
+  settings?: RunOverrides,
+): Promise<QueryCostEstimate> {
+  return invoke("estimate_query_cost", {
+    projectId,
+    query,
+    focusDocumentId: focusDocumentId ?? null,
+    settings: settings ?? null,
+  });
+}
+
+export async function compareRuns(runA: string, runB: string): Promise<RunComparison> {
+  return invoke("compare_runs", { runA, runB });
+}
+
+export async function getRunTimeline(runId: string): Promise<RunTimeline> {
+  return invoke("get_run_timeline", { runId });
+}
+
+export async function getQualityTrends(projectId: string): Promise<QualityTrendsResponse> {
+  return invoke("get_quality_trends", { projectId });
+}
+
+export async function getCitationHeatmap(
+  scope: { projectId: string } | { documentId: string },
+): Promise<CitationHeatmap> {
+  return invoke("get_citation_heatmap", scope);
 }
 
-export async function deleteDocument(documentId: string): Promise<{ deleted: boolean }> {
-  return invoke("delete_document", { documentId });
+export async function getUnusedContent(projectId: string): Promise<UnusedContentReport> {
+  return invoke("get_unused_content", { projectId });
+}
+
+export async function runBenchmark(projectId: string, qaFile: string): Promise<BenchmarkReport> {
+  return invoke("run_benchmark", { projectId, qaFile });
+}
+
+export async function exportMarkdown(
+  documentId: string,
+  destPath?: string,
+  overwrite?: boolean,
+): Promise<ExportedFile> {
+  return invoke("export_markdown", { documentId, destPath: destPath ?? null, overwrite: overwrite ?? null });
+}
+
+export async function revealExport(path: string): Promise<RevealExportResponse> {
+  return invoke("reveal_export", { path });
+}
+
+export async function exportAnswerHtml(
+  runId: string,
+  destPath?: string,
+  overwrite?: boolean,
+): Promise<ExportedFile> {
+  return invoke("export_answer_html", { runId, destPath: destPath ?? null, overwrite: overwrite ?? null });
+}
+
+export async function pickExportPath(
+  defaultName: string,
+  format: "markdown" | "html" = "markdown",
+): Promise<string | null> {
+  return save({
+    defaultPath: defaultName,
+    filters:
+      format === "html"
+        ? [{ name: "HTML", extensions: ["html"] }]
+        : [{ name: "Markdown", extensions: ["md"] }],
+  });
+}
+
+export async function deleteDocument(documentId: string, projectId?: string): Promise<{ deleted: boolean }> {
+  return invoke("delete_document", { documentId, projectId: projectId ?? null });
+}
+
+export async function undoLastOperation(): Promise<UndoOperation | null> {
+  const result = await invoke<{ operation: UndoOperation | null }>("undo_last_operation");
+  return result.operation;
+}
+
+export async function getAsset(assetId: string): Promise<GetAssetResponse> {
+  return invoke("get_asset", { assetId });
+}
+
+export async function getDocumentThumbnail(documentId: string): Promise<GetAssetResponse> {
+  return invoke("get_document_thumbnail", { documentId });
+}
+
+export async function setDocumentVisibility(
+  documentId: string,
+  hidden: boolean,
+  locked: boolean,
+  passphrase?: string,
+): Promise<DocumentSummary> {
+  const result = await invoke<{ document: DocumentSummary }>("set_document_visibility", {
+    documentId,
+    hidden,
+    locked,
+    passphrase: passphrase ?? null,
+  });
+  return result.document;
+}
+
+export async function unlockDocument(documentId: string, passphrase?: string): Promise<DocumentSummary> {
+  const result = await invoke<{ document: DocumentSummary }>("unlock_document", {
+    documentId,
+    passphrase: passphrase ?? null,
+  });
+  return result.document;
+}
+
+export async function relockDocument(documentId: string): Promise<DocumentSummary> {
+  const result = await invoke<{ document: DocumentSummary }>("relock_document", { documentId });
+  return result.document;
+}
+
+export async function getDocumentLockStatus(): Promise<DocumentLockStatus> {
+  return invoke("get_document_lock_status");
+}
+
+export async function setDocumentLockPassphrase(
+  current: string | null,
+  passphrase: string | null,
+): Promise<DocumentLockStatus> {
+  return invoke("set_document_lock_passphrase", { current, passphrase });
+}
+
+export async function extractChartData(documentId: string): Promise<ExtractChartDataResponse> {
+  return invoke("extract_chart_data", { documentId });
+}
+
+export async function enrichTables(documentId: string, useModel = false): Promise<EnrichTablesResponse> {
+  return invoke("enrich_tables", { documentId, useModel });
+}
+
+export async function indexAcronyms(documentId: string): Promise<IndexAcronymsResponse> {
+  return invoke("index_acronyms", { documentId });
+}
+
+export async function decomposeClaims(
+  documentId: string,
+  paragraphIds?: string[],
+  useModel = false,
+): Promise<DecomposeClaimsResponse> {
+  return invoke("decompose_claims", { documentId, paragraphIds: paragraphIds ?? null, useModel });
 }
 
 export async function getGraphLayout(documentId: string): Promise<GraphNodePosition[]> {
@@ -116,10 +628,42 @@ export function onIngestProgress(handler: (event: IngestProgressEvent) => void):
   return listen("ingest/progress", (event) => handler(event.payload as IngestProgressEvent));
 }
 
+export function onIngestComplete(handler: (event: IngestCompleteEvent) => void): Promise<UnlistenFn> {
+  return listen("ingest/complete", (event) => handler(event.payload as IngestCompleteEvent));
+}
+
+export function onIngestFailed(handler: (event: IngestFailedEvent) => void): Promise<UnlistenFn> {
+  return listen("ingest/failed", (event) => handler(event.payload as IngestFailedEvent));
+}
+
+export function onCorpusChanged(handler: (event: CorpusChangedEvent) => void): Promise<UnlistenFn> {
+  return listen("corpus/changed", (event) => handler(event.payload as CorpusChangedEvent));
+}
+
+export function onResearchLogAppended(
+  handler: (event: ResearchLogAppendedEvent) => void,
+): Promise<UnlistenFn> {
+  return listen("research_log/appended", (event) => handler(event.payload as ResearchLogAppendedEvent));
+}
+
+export function onWorkspaceChanged(handler: (event: WorkspaceSummary) => void): Promise<UnlistenFn> {
+  return listen("workspace/changed", (event) => handler(event.payload as WorkspaceSummary));
+}
+
+export function onBenchmarkProgress(
+  handler: (event: BenchmarkProgressEvent) => void,
+): Promise<UnlistenFn> {
+  return listen("benchmark/progress", (event) => handler(event.payload as BenchmarkProgressEvent));
+}
+
 export function onReasoningStep(handler: (event: ReasoningStepEvent) => void): Promise<UnlistenFn> {
   return listen("reasoning/step", (event) => handler(event.payload as ReasoningStepEvent));
 }
 
+export function onReasoningPhase(handler: (event: ReasoningPhaseEvent) => void): Promise<UnlistenFn> {
+  return listen("reasoning/phase", (event) => handler(event.payload as ReasoningPhaseEvent));
+}
+
 export function onReasoningComplete(
   handler: (event: ReasoningCompleteEvent) => void,
 ): Promise<UnlistenFn> {
@@ -132,6 +676,82 @@ export function onReasoningError(handler: (event: ReasoningErrorEvent) => void):
   return listen("reasoning/error", (event) => handler(event.payload as ReasoningErrorEvent));
 }
 
+export async function healthCheck(): Promise<HealthCheckResponse> {
+  return invoke("health_check");
+}
+
+export async function getMetrics(): Promise<GetMetricsResponse> {
+  return invoke("get_metrics");
+}
+
+export async function setMetricsSettings(settings: MetricsSettings): Promise<MetricsSettings> {
+  return invoke("set_metrics_settings", { settings });
+}
+
+export async function resetMetrics(): Promise<GetMetricsResponse> {
+  return invoke("reset_metrics");
+}
+
+export async function exportMetricsPrometheus(
+  destPath?: string,
+  overwrite = false,
+): Promise<ExportedFile> {
+  return invoke("export_metrics_prometheus", { destPath: destPath ?? null, overwrite });
+}
+
+export async function getSchemaInfo(): Promise<SchemaInfo> {
+  return invoke("get_schema_info");
+}
+
+export async function runPendingMigrations(): Promise<RunMigrationsResponse> {
+  return invoke("run_pending_migrations");
+}
+
+export async function compressNodeText(): Promise<CompressNodeTextResponse> {
+  return invoke("compress_node_text");
+}
+
+export async function exportPortableDatabase(destPath: string): Promise<PortableExportResponse> {
+  return invoke("export_portable_database", { destPath });
+}
+
+export async function getStorageStatus(): Promise<StorageStatus> {
+  return invoke("get_storage_status");
+}
+
+export async function getBackupSettings(): Promise<BackupSettings> {
+  return invoke("get_backup_settings");
+}
+
+export async function setBackupSettings(settings: BackupSettings): Promise<BackupSettings> {
+  return invoke("set_backup_settings", { settings });
+}
+
+export async function listBackups(): Promise<{ folder: string | null; backups: BackupInfo[] }> {
+  return invoke("list_backups");
+}
+
+export async function restoreBackup(path: string): Promise<WorkspaceSummary> {
+  return invoke("restore_backup", { path });
+}
+
+export async function relocateWorkspace(destPath: string): Promise<WorkspaceSummary> {
+  return invoke("relocate_workspace", { destPath });
+}
+
+export async function listWorkspaces(): Promise<WorkspaceSummary[]> {
+  const result = await invoke<{ workspaces: WorkspaceSummary[] }>("list_workspaces");
+  return result.workspaces;
+}
+
+export async function openWorkspace(path: string, name?: string): Promise<WorkspaceSummary> {
+  return invoke("open_workspace", { path, name: name ?? null });
+}
+
+export async function createWorkspace(path: string, name?: string): Promise<WorkspaceSummary> {
+  return invoke("create_workspace", { path, name: name ?? null });
+}
+
 // Project CRUD functions
 export async function listProjects(): Promise<ProjectSummary[]> {
   const result = await invoke<{ projects: ProjectSummary[] }>("list_projects");
@@ -148,8 +768,55 @@ export async function renameProject(projectId: string, name: string): Promise<Pr
   return result.project;
 }
 
-export async function deleteProject(projectId: string): Promise<{ deleted: boolean }> {
-  return invoke("delete_project", { projectId });
+export async function getDeleteImpact(projectId: string): Promise<ProjectDeleteImpact> {
+  const result = await invoke<{ impact: ProjectDeleteImpact }>("get_delete_impact", { projectId });
+  return result.impact;
+}
+
+export async function deleteProject(
+  projectId: string,
+  confirmationToken: string,
+): Promise<{ deleted: boolean }> {
+  return invoke("delete_project", { projectId, confirmationToken });
+}
+
+export async function archiveProject(projectId: string): Promise<ProjectSummary> {
+  const result = await invoke<{ project: ProjectSummary }>("archive_project", { projectId });
+  return result.project;
+}
+
+export async function unarchiveProject(projectId: string): Promise<ProjectSummary> {
+  const result = await invoke<{ project: ProjectSummary }>("unarchive_project", { projectId });
+  return result.project;
+}
+
+export async function exportSharedBundle(
+  projectId: string,
+  destPath?: string,
+): Promise<ExportSharedBundleResponse> {
+  return invoke("export_shared_bundle", { projectId, destPath: destPath ?? null });
+}
+
+export async function exportBibliography(
+  projectId: string,
+  format: BibliographyFormat,
+  destPath?: string,
+  overwrite?: boolean,
+): Promise<ExportBibliographyResponse> {
+  return invoke("export_bibliography", {
+    projectId,
+    format,
+    destPath: destPath ?? null,
+    overwrite: overwrite ?? null,
+  });
+}
+
+export async function verifySharedBundle(path: string): Promise<BundleManifest> {
+  return invoke("verify_shared_bundle", { path });
+}
+
+export async function openSharedBundle(path: string): Promise<OpenSharedBundleResponse> {
+  return invoke("open_shared_bundle", { path });
 }
 
 export async function getProjectTree(
@@ -162,3 +829,24 @@ export async function getProjectTree(
   });
   return result.nodes;
 }
+
+export async function getProjectTreeDelta(
+  projectId: string,
+  sinceRevision = 0,
+): Promise<GetProjectTreeDeltaResponse> {
+  return invoke("get_project_tree_delta", { projectId, sinceRevision });
+}
+
+export async function searchAllProjects(
+  query: string,
+  limit?: number,
+  includeArchived?: boolean,
+  fuzziness?: number,
+): Promise<SearchAllProjectsResponse> {
+  return invoke("search_all_projects", {
+    query,
+    limit: limit ?? null,
+    includeArchived: includeArchived ?? null,
+    fuzziness: fuzziness ?? null,
+  });
+}
@@ -11,15 +11,41 @@ export type NodeType =
   | "equation"
   | "caption"
   | "reference"
-  | "unknown";
+  | "unknown"
+  | (string & {});
+
+export interface NodeTypeInfo {
+  nodeType: NodeType;
+  label: string;
+  description: string;
+  color: string | null;
+  builtIn: boolean;
+  nodeCount: number;
+}
+
+export interface ListNodeTypesResponse {
+  nodeTypes: NodeTypeInfo[];
+}
 
 export interface ProjectSummary {
   id: string;
   name: string;
+  readOnly: boolean;
+  archived: boolean;
   createdAt: string;
   updatedAt: string;
 }
 
+export interface ProjectDeleteImpact {
+  projectId: string;
+  documents: number;
+  nodes: number;
+  runs: number;
+  layoutPositions: number;
+  sharedDocuments: number;
+  confirmationToken: string;
+}
+
 export interface DocumentSummary {
   id: string;
   projectId: string;
@@ -27,9 +53,53 @@ export interface DocumentSummary {
   mime: string;
   checksum: string;
   pages: number;
+  hidden: boolean;
+  locked: boolean;
+  unlocked: boolean;
+  sourcePath?: string | null;
+  lastIngestedAt?: string | null;
+  stale?: boolean;
+  tags?: string[];
+  attachmentIds?: string[];
+  createdAt: string;
+}
+
+export type UndoKind = "documentDelete" | "nodeEdit" | "layoutWipe";
+
+export interface UndoOperation {
+  id: number;
+  kind: UndoKind;
+  documentId: string;
+  label: string;
   createdAt: string;
 }
 
+export interface DocumentLockStatus {
+  passphraseSet: boolean;
+}
+
+export interface RecentDocument {
+  document: DocumentSummary;
+  openedInProjectId: string;
+  openedAt: string;
+}
+
+export interface RecentQuery {
+  projectId: string;
+  runId: string;
+  query: string;
+  askedAt: string;
+}
+
+export interface NodeRef {
+  id: string;
+  title: string;
+  documentId: string;
+  documentName: string;
+  nodeType: NodeType;
+  ordinalPath: string;
+}
+
 export interface DocNodeSummary {
   id: string;
   documentId: string;
@@ -42,6 +112,40 @@ export interface DocNodeSummary {
   pageEnd: number | null;
 }
 
+export interface GetProjectTreeDeltaResponse {
+  revision: number;
+  full: boolean;
+  nodes: DocNodeSummary[];
+  removedNodeIds: string[];
+  removedDocumentIds: string[];
+  lockedDocumentIds: string[];
+}
+
+export type AssetRole = "figure" | "pageImage" | "pagePreview" | "thumbnail";
+
+export interface GetAssetResponse {
+  assetId: string;
+  mime: string;
+  byteLen: number;
+  dataUrl: string;
+}
+
+export interface GlobalSearchHit {
+  node: DocNodeSummary;
+  documentName: string;
+}
+
+export interface ProjectSearchGroup {
+  projectId: string;
+  projectName: string;
+  hits: GlobalSearchHit[];
+}
+
+export interface SearchAllProjectsResponse {
+  query: string;
+  groups: ProjectSearchGroup[];
+}
+
 export interface DocNodeDetail extends DocNodeSummary {
   bboxJson: Record<string, unknown>;
   metadataJson: Record<string, unknown>;
@@ -57,20 +161,62 @@ export interface DocumentPreviewBlock {
   ordinalPath: string;
 }
 
+export type RunPhase = "planning" | "retrieval" | "synthesis" | "validation" | "completed" | "failed";
+
 export interface ReasoningRun {
   id: string;
   projectId: string;
   documentId: string | null;
   query: string;
   status: "running" | "completed" | "failed";
-  phase?: "planning" | "retrieval" | "synthesis" | "validation" | "completed" | "failed";
+  phase: RunPhase;
   startedAt: string;
   endedAt: string | null;
   totalLatencyMs: number | null;
   tokenUsageJson: Record<string, unknown>;
   costUsd: number;
-  qualityJson?: Record<string, unknown>;
-  plannerTraceJson?: unknown[];
+  qualityJson: Record<string, unknown>;
+  plannerTraceJson: unknown[];
+  promptVersionsJson: Record<string, number>;
+  maxSteps: number | null;
+  replayOf: string | null;
+  diagnosticsJson: Record<string, unknown>[];
+  attachFigureImages: boolean;
+  manifest: RunManifest | null;
+}
+
+export interface RunManifestDocument {
+  id: string;
+  name: string;
+  checksum: string;
+}
+
+export interface RunManifest {
+  appVersion: string;
+  provider: ProviderName | null;
+  model: string;
+  plannerModel: string;
+  promptVersions: Record<string, unknown>;
+  generation: GenerationSettings;
+  pricing: ModelPricing | null;
+  plannerPricing: ModelPricing | null;
+  maxSteps: number;
+  attachFigureImages: boolean;
+  expandContext: boolean;
+  focusDocumentId: string | null;
+  inferFocusDocument: boolean;
+  fuzziness?: number;
+  ranking?: RankingWeights | null;
+  dateRange?: DateRange | null;
+  queryClass?: QueryClass | null;
+  qualityPolicy?: QualityPolicy | null;
+  language?: LanguageResources | null;
+  quality?: QualityThresholds | null;
+  privacy?: PrivacySettings | null;
+  replayOf: string | null;
+  rerunOf: string | null;
+  documents: RunManifestDocument[];
+  createdAt: string;
 }
 
 export interface ReasoningStep {
@@ -80,7 +226,9 @@ export interface ReasoningStep {
   thought: string;
   action: string;
   observation: string;
+  summary: string;
   nodeRefs: string[];
+  resolvedRefs: NodeRef[];
   confidence: number;
   latencyMs: number;
 }
@@ -93,17 +241,555 @@ export interface AnswerRecord {
   grounded: boolean;
 }
 
+export interface AnswerRevision {
+  runId: string;
+  revision: number;
+  answerMarkdown: string;
+  citations: string[];
+  confidence: number;
+  grounded: boolean;
+  generation: Record<string, unknown>;
+  current: boolean;
+  createdAt: string;
+}
+
 export interface RunPayload {
   run: ReasoningRun;
   steps: ReasoningStep[];
   answer?: AnswerRecord;
 }
 
+export type QueryScope = "document" | "project";
+
+export interface ScopeDecision {
+  scope: QueryScope;
+  focusDocumentId?: string | null;
+  focusDropped: boolean;
+  forced: boolean;
+}
+
+export interface RunStarted {
+  runId: string;
+  status: string;
+  scope: ScopeDecision;
+}
+
+export interface RunOverrides {
+  provider?: ProviderName;
+  model?: string;
+  plannerModel?: string;
+  generation?: GenerationSettings;
+  maxSteps?: number;
+  attachFigureImages?: boolean;
+  expandContext?: boolean;
+  inferFocusDocument?: boolean;
+}
+
+export interface ModelCostEstimate {
+  callKind: "planner" | "synthesis";
+  model: string;
+  pricing: ModelPricing;
+  calls: number;
+  maxCalls: number;
+  promptTokens: number;
+  outputTokens: number;
+  costUsd: number;
+  maxCostUsd: number;
+  avgCallLatencyMs: number | null;
+}
+
+export interface QueryCostEstimate {
+  scope: ScopeDecision;
+  evidenceCount: number;
+  evidenceTokens: number;
+  trimmedEvidence: number;
+  models: ModelCostEstimate[];
+  costUsd: number;
+  maxCostUsd: number;
+  latencyMs: number | null;
+  maxLatencyMs: number | null;
+}
+
+export interface QualityMetrics {
+  overall: number;
+  queryAlignment: number;
+  citationCoverage: number;
+  evidenceRecall: number;
+  crossDocumentCoverage: number;
+  grounded: boolean;
+}
+
+export type QueryClass = "factual" | "summary" | "comparison" | "extraction" | "aggregation";
+
+export interface QualityWeights {
+  queryAlignment: number;
+  citationCoverage: number;
+  crossDocumentCoverage: number;
+  grounding: number;
+}
+
+export interface QualityPolicy {
+  minOverall: number;
+  weights: QualityWeights;
+}
+
+export interface QualityThresholds {
+  factual: QualityPolicy;
+  summary: QualityPolicy;
+  comparison: QualityPolicy;
+  extraction: QualityPolicy;
+  aggregation: QualityPolicy;
+}
+
+export interface PrivacySettings {
+  redactText: boolean;
+}
+
+export type PolicyFeature = "import" | "external_parser" | "shared_bundles";
+
+export interface ProviderEndpoints {
+  gemini: string | null;
+  openRouter: string | null;
+}
+
+export interface AdminPolicy {
+  source: string | null;
+  offline: boolean;
+  endpoints: ProviderEndpoints;
+  disabledFeatures: PolicyFeature[];
+  lockedSettings: string[];
+  presets: Record<string, unknown>;
+}
+
+export interface RetrievalSettings {
+  fuzziness: number;
+  ranking: RankingWeights;
+}
+
+export interface RankingWeights {
+  title: number;
+  text: number;
+  metadata: number;
+  nodeTypes: NodeTypeWeight[];
+}
+
+export interface NodeTypeWeight {
+  nodeType: NodeType;
+  weight: number;
+  hints: string[];
+}
+
+export interface DateRange {
+  from?: string | null;
+  to?: string | null;
+}
+
+export interface ProviderTimeouts {
+  connectSecs: number;
+  plannerSecs: number;
+  synthesisSecs: number;
+  enrichmentSecs: number;
+  keepAliveSecs: number;
+}
+
+export interface ProviderTimeoutSettings {
+  gemini: ProviderTimeouts;
+  openrouter: ProviderTimeouts;
+}
+
+export interface RunComparisonSide {
+  runId: string;
+  query: string;
+  status: ReasoningRun["status"];
+  model: string | null;
+  answerMarkdown: string | null;
+  confidence: number | null;
+  citations: string[];
+  stepCount: number;
+  costUsd: number;
+  totalLatencyMs: number | null;
+  quality: QualityMetrics | null;
+}
+
+export interface RunDeltas {
+  confidence: number | null;
+  overall: number | null;
+  queryAlignment: number | null;
+  citationCoverage: number | null;
+  evidenceRecall: number | null;
+  crossDocumentCoverage: number | null;
+  stepCount: number;
+  costUsd: number;
+  totalLatencyMs: number | null;
+}
+
+export interface RunComparison {
+  runA: RunComparisonSide;
+  runB: RunComparisonSide;
+  sameQuery: boolean;
+  sharedCitations: string[];
+  onlyInA: string[];
+  onlyInB: string[];
+  citationOverlap: number;
+  deltas: RunDeltas;
+}
+
+export interface PhaseTiming {
+  phase: RunPhase;
+  durationMs: number;
+  providerMs: number;
+}
+
+export interface StepTiming {
+  idx: number;
+  stepType: string;
+  phase: RunPhase;
+  latencyMs: number;
+}
+
+export interface ProviderCallTiming {
+  seq: number;
+  callKind: string;
+  model: string;
+  latencyMs: number;
+  cached: boolean;
+}
+
+export interface RunTimeline {
+  runId: string;
+  totalMs: number | null;
+  phases: PhaseTiming[];
+  steps: StepTiming[];
+  providerCalls: ProviderCallTiming[];
+  providerMs: number;
+  dbMs: number;
+  unaccountedMs: number | null;
+}
+
+export interface BenchmarkCase {
+  id?: string;
+  question: string;
+  expectedKeywords?: string[];
+  expectedCitations?: string[];
+  expectedAnswer?: string;
+  focusDocumentId?: string;
+}
+
+export type BenchmarkCaseStatus = "passed" | "failed" | "errored";
+
+export interface BenchmarkCaseResult {
+  index: number;
+  id: string | null;
+  question: string;
+  runId: string;
+  status: BenchmarkCaseStatus;
+  error: string | null;
+  answerMarkdown: string | null;
+  confidence: number | null;
+  quality: QualityMetrics | null;
+  keywordsMatched: string[];
+  keywordsMissing: string[];
+  citationsMatched: string[];
+  citationsMissing: string[];
+  exactMatch: boolean | null;
+  costUsd: number;
+  latencyMs: number;
+}
+
+export interface BenchmarkSummary {
+  total: number;
+  passed: number;
+  failed: number;
+  errored: number;
+  passRate: number;
+  avgOverall: number | null;
+  keywordRecall: number | null;
+  citationRecall: number | null;
+  exactMatchRate: number | null;
+  totalCostUsd: number;
+}
+
+export interface BenchmarkReport {
+  id: string;
+  projectId: string;
+  qaFile: string;
+  model: string;
+  startedAt: string;
+  finishedAt: string;
+  summary: BenchmarkSummary;
+  cases: BenchmarkCaseResult[];
+  reportPath: string | null;
+}
+
+export interface BenchmarkProgressEvent {
+  benchmarkId: string;
+  completed: number;
+  total: number;
+  result: BenchmarkCaseResult;
+}
+
+export interface QualityAggregate {
+  runCount: number;
+  avgOverall: number;
+  groundingRate: number;
+  gateFailureRate: number;
+}
+
+export interface QualityTrendPoint extends QualityAggregate {
+  day: string;
+}
+
+export interface QualityTrendsResponse {
+  projectId: string;
+  points: QualityTrendPoint[];
+  total: QualityAggregate;
+}
+
+export interface CitationHeat {
+  nodeId: string;
+  documentId: string;
+  parentId: string | null;
+  nodeType: NodeType;
+  title: string;
+  ordinalPath: string;
+  citations: number;
+  total: number;
+  intensity: number;
+}
+
+export interface CitationHeatmap {
+  projectId: string | null;
+  documentId: string | null;
+  answerCount: number;
+  maxTotal: number;
+  nodes: CitationHeat[];
+}
+
+export interface UnusedSection {
+  nodeId: string;
+  documentId: string;
+  documentName: string;
+  nodeType: NodeType;
+  title: string;
+  ordinalPath: string;
+  nodeCount: number;
+  estimatedTokens: number;
+}
+
+export interface UnusedDocument {
+  documentId: string;
+  name: string;
+  nodeCount: number;
+  estimatedTokens: number;
+  retrievedNodes: number;
+}
+
+export interface UnusedContentReport {
+  projectId: string;
+  runCount: number;
+  sections: UnusedSection[];
+  documents: UnusedDocument[];
+}
+
+export interface ReingestDocumentResponse {
+  documentId: string;
+  rootNodeId: string;
+  nodeCount: number;
+  unchangedNodes: number;
+  remappedNodes: number;
+  droppedNodes: number;
+}
+
+export interface QueueDepth {
+  interactiveActive: number;
+  backgroundRunning: number;
+  backgroundQueued: number;
+}
+
+export interface HealthCheckResponse {
+  databaseOk: boolean;
+  poolSize: number;
+  poolIdle: number;
+  queue: QueueDepth;
+}
+
+export interface MetricsSettings {
+  enabled: boolean;
+}
+
+export interface MetricSample {
+  name: string;
+  label: string;
+  count: number;
+  totalMs: number;
+  maxMs: number;
+  updatedAt: string;
+}
+
+export interface GetMetricsResponse {
+  settings: MetricsSettings;
+  metrics: MetricSample[];
+}
+
+export interface MigrationInfo {
+  version: number;
+  description: string;
+  installedOn: string | null;
+}
+
+export interface SchemaInfo {
+  currentVersion: number | null;
+  latestVersion: number;
+  applied: MigrationInfo[];
+  pending: MigrationInfo[];
+  unknownVersions: number[];
+  readOnly: boolean;
+  dbPath: string | null;
+  dbFileBytes: number;
+}
+
+export interface RunMigrationsResponse {
+  applied: MigrationInfo[];
+  backupPath: string | null;
+  currentVersion: number | null;
+}
+
+export interface CompressNodeTextResponse {
+  compressedNodes: number;
+  bytesSaved: number;
+}
+
+export interface PortableExportResponse {
+  path: string;
+  schemaVersion: number | null;
+  tables: number;
+  rows: number;
+}
+
+export interface BundleManifest {
+  format: string;
+  formatVersion: number;
+  bundleId: string;
+  projectName: string;
+  exportedAt: string;
+  documents: number;
+  nodes: number;
+  summaries: number;
+  payloadSha256: string;
+  manifestSha256: string;
+  signerKey: string;
+  signature: string;
+}
+
+export interface ExportedFile {
+  filePath: string;
+  replaced: boolean;
+}
+
+export type BibliographyFormat = "bibtex" | "cslJson";
+
+export interface ExportBibliographyResponse extends ExportedFile {
+  entryCount: number;
+}
+
+export interface RevealExportResponse {
+  path: string;
+}
+
+export interface ExportSharedBundleResponse {
+  path: string;
+  manifest: BundleManifest;
+}
+
+export interface OpenSharedBundleResponse {
+  project: ProjectSummary;
+  manifest: BundleManifest;
+  alreadyOpen: boolean;
+}
+
+export interface StorageStatus {
+  path: string | null;
+  syncProvider: string | null;
+  syncSafe: boolean;
+  journalMode: string;
+  warning: string | null;
+}
+
+export interface BackupSettings {
+  enabled: boolean;
+  folder: string | null;
+  intervalHours: number;
+  keep: number;
+  includeAssets: boolean;
+}
+
+export interface BackupInfo {
+  path: string;
+  createdAt: string;
+  byteLen: number;
+  includesAssets: boolean;
+}
+
+export interface WorkspaceSummary {
+  path: string;
+  name: string;
+  active: boolean;
+  exists: boolean;
+  lastOpenedAt: string | null;
+}
+
 export interface IngestProgressEvent {
   jobId: string;
   stage: string;
   percent: number;
   message: string;
+  bytesProcessed: number;
+  totalBytes: number;
+  etaMs: number | null;
+  documentId: string | null;
+}
+
+export interface IngestCompleteEvent {
+  jobId: string;
+  projectId: string;
+  filePath: string;
+  documentId: string;
+  rootNodeId: string;
+  nodeCount: number;
+  sectionCount: number;
+  sharedFromProjectId: string | null;
+  reingested: boolean;
+  warnings: string[];
+}
+
+export type CorpusChange =
+  | "document_added"
+  | "document_removed"
+  | "document_reingested"
+  | "nodes_added";
+
+export interface CorpusStats {
+  documentCount: number;
+  nodeCount: number;
+}
+
+export interface CorpusChangedEvent {
+  projectId: string;
+  change: CorpusChange;
+  documentId: string;
+  nodeCount: number;
+  sectionCount: number;
+  stats: CorpusStats;
+}
+
+export interface IngestFailedEvent {
+  jobId: string;
+  projectId: string | null;
+  documentId: string | null;
+  filePath: string;
+  code: string;
+  message: string;
+  retryable: boolean;
 }
 
 export interface ReasoningStepEvent {
@@ -113,11 +799,20 @@ export interface ReasoningStepEvent {
   thought: string;
   action: string;
   observation: string;
+  summary: string;
   nodeRefs: string[];
+  resolvedRefs: NodeRef[];
   latencyMs: number;
   confidence: number;
 }
 
+export interface ReasoningPhaseEvent {
+  runId: string;
+  phase: RunPhase;
+  previousPhase: RunPhase;
+  stepIndex: number;
+}
+
 export interface GraphNodePosition {
   nodeId: string;
   x: number;
@@ -131,6 +826,7 @@ export interface ReasoningCompleteEvent {
   totalLatencyMs: number;
   tokenUsage: Record<string, unknown>;
   costUsd: number;
+  insufficientEvidence: boolean;
 }
 
 export interface ReasoningErrorEvent {
@@ -138,4 +834,262 @@ export interface ReasoningErrorEvent {
   code: string;
   message: string;
   retryable: boolean;
+  retryAfterSecs: number | null;
+}
+
+export interface SafetySetting {
+  category: string;
+  threshold: string;
+}
+
+export interface GenerationSettings {
+  temperature?: number | null;
+  topP?: number | null;
+  maxOutputTokens?: number | null;
+  safetySettings?: SafetySetting[];
+}
+
+export interface GenerationSettingsResponse {
+  projectId: string;
+  settings: GenerationSettings;
+}
+
+export type KeySelection = "round_robin" | "quota_aware";
+
+export interface ProviderKeyRecord {
+  id: string;
+  provider: ProviderName;
+  label: string;
+  keyHint: string;
+  dailyRequestQuota: number | null;
+  requestsToday: number;
+  totalRequests: number;
+  totalTokens: number;
+  rateLimitedCount: number;
+  cooldownUntil: string | null;
+  lastUsedAt: string | null;
+  createdAt: string;
+}
+
+export interface ListProviderKeysResponse {
+  keys: ProviderKeyRecord[];
+  selection: KeySelection;
+}
+
+export type ProviderName = "gemini" | "openrouter";
+
+export interface ModelPricing {
+  inputPerToken: number;
+  outputPerToken: number;
+}
+
+export interface HostedModel {
+  id: string;
+  name: string;
+  contextLength: number | null;
+  pricing: ModelPricing | null;
+}
+
+export interface PlannerModel {
+  model: string;
+  contextLength: number | null;
+  pricing: ModelPricing | null;
+}
+
+export interface ModelSelection {
+  provider: ProviderName;
+  model: string;
+  contextLength: number | null;
+  pricing: ModelPricing | null;
+  planner: PlannerModel | null;
+}
+
+export interface PricingOverridesResponse {
+  overrides: Record<string, ModelPricing>;
+}
+
+export interface ExtractChartDataResponse {
+  documentId: string;
+  figuresScanned: number;
+  alreadyExtracted: number;
+  skippedWithoutImage: number;
+  notCharts: number;
+  failed: number;
+  tableNodeIds: string[];
+}
+
+export type ColumnType = "integer" | "number" | "percent" | "currency" | "date" | "boolean" | "text" | "empty";
+
+export type HeaderSource = "markdown" | "inferred" | "model" | "none";
+
+export interface TableColumn {
+  name: string;
+  dataType: ColumnType;
+  unit: string | null;
+}
+
+export interface TableSchema {
+  headerSource: HeaderSource;
+  hasHeaderRow: boolean;
+  rowCount: number;
+  columns: TableColumn[];
+}
+
+export interface EnrichTablesResponse {
+  documentId: string;
+  tablesScanned: number;
+  alreadyTyped: number;
+  inferredLocally: number;
+  inferredWithModel: number;
+  unresolved: number;
+  unparseable: number;
+}
+
+export interface DecomposeClaimsResponse {
+  documentId: string;
+  paragraphsScanned: number;
+  alreadyDecomposed: number;
+  singleClaim: number;
+  failed: number;
+  claimsCreated: number;
+}
+
+export interface IndexAcronymsResponse {
+  documentId: string;
+  acronymCount: number;
+}
+
+export interface Acronym {
+  acronym: string;
+  expansion: string;
+  documentId: string;
+  nodeId: string;
+}
+
+export interface ListAcronymsResponse {
+  projectId: string;
+  acronyms: Acronym[];
+}
+
+export interface IngestRules {
+  ignoreGlobs: string[];
+  ignoredExtensions: string[];
+  maxFileBytes: number | null;
+}
+
+export interface IngestRulesResponse {
+  projectId: string;
+  rules: IngestRules;
+}
+
+export interface ResearchLogSettings {
+  enabled: boolean;
+}
+
+export interface ResearchLogSettingsResponse {
+  projectId: string;
+  settings: ResearchLogSettings;
+}
+
+export interface ResearchLogAppendedEvent {
+  projectId: string;
+  runId: string;
+  documentId: string;
+  nodeId: string;
+  created: boolean;
+}
+
+export type ParserKind =
+  | "pdf"
+  | "docx"
+  | "xlsx"
+  | "pptx"
+  | "epub"
+  | "image"
+  | "html"
+  | "plainText"
+  | "markdownStrict"
+  | "external";
+
+export interface ParserOverride {
+  parser?: ParserKind | null;
+  pdfColumns?: boolean | null;
+  pdfBoilerplate?: boolean | null;
+}
+
+export interface IngestPreviewSection {
+  title: string;
+  blockCount: number;
+  charCount: number;
+  excerpt: string;
+}
+
+export interface PreviewIngestResponse {
+  title: string;
+  mime: string;
+  pages: number;
+  nodeCount: number;
+  sectionCount: number;
+  paragraphCount: number;
+  tableCount: number;
+  figureCount: number;
+  charCount: number;
+  sections: IngestPreviewSection[];
+  warnings: string[];
+  metadata: Record<string, unknown>;
+}
+
+export interface ExternalParserConfig {
+  enabled: boolean;
+  command: string;
+  args: string[];
+  timeoutSecs: number;
+  mimeTypes: string[];
+}
+
+export interface LanguageResources {
+  language: string;
+  relationHints: string[];
+  multiDocHints: string[];
+  singleDocHints: string[];
+  pluralPronouns: string[];
+  stopwords: string[];
+  synonyms: string[][];
+  summaryHints: string[];
+  extractionHints: string[];
+  aggregationHints: string[];
+}
+
+export interface LanguageResourcesResponse {
+  resources: LanguageResources;
+  languages: string[];
+  customized: boolean;
+}
+
+export interface ScanDirectoryResponse {
+  files: { path: string; sizeBytes: number }[];
+  skipped: { path: string; reason: string }[];
+}
+
+export type ImportSource = "obsidian" | "notion" | "zotero";
+
+export interface ImportItem {
+  path: string;
+  mimeType: string;
+  displayName: string | null;
+  project: string | null;
+  tags: string[];
+  attachments: string[];
+}
+
+export interface ImportPlan {
+  items: ImportItem[];
+  skipped: { path: string; reason: string }[];
+}
+
+export interface ImportCorpusResponse {
+  imported: { path: string; documentId: string; projectId: string }[];
+  failed: { path: string; reason: string }[];
+  skipped: { path: string; reason: string }[];
+  createdProjectIds: string[];
 }